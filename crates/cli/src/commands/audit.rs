@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use colored::*;
+use common::audit::{parse_since, AuditConfig, AuditEntry, AuditLog, AuditQuery};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Debug, Args)]
+pub struct AuditCommand {
+    #[command(subcommand)]
+    command: AuditSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum AuditSubcommand {
+    /// Показать последние записи журнала аудита
+    Tail {
+        /// Количество записей
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+        /// Вывести записи как JSONL
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Поиск по журналу (подстрока в любом поле)
+    Search {
+        /// Строка поиска (без учёта регистра)
+        query: Option<String>,
+        /// Фильтр по топику или его префиксу (tool, policy.ask, llm)
+        #[arg(long)]
+        topic: Option<String>,
+        /// Начиная с: RFC 3339, YYYY-MM-DD или относительно (24h, 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Максимум записей (последние)
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+        /// Вывести записи как JSONL
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Проверить целостность hash-цепочки
+    Verify {
+        /// Вывести отчёт в JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Экспорт записей в JSONL
+    Export {
+        /// Начиная с: RFC 3339, YYYY-MM-DD или относительно (24h, 7d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Файл назначения (по умолчанию stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl AuditCommand {
+    pub async fn execute(self) -> Result<()> {
+        handle_audit_command(self.command).await
+    }
+}
+
+/// Audit settings for this process: `MAGRAY_AUDIT_*` env over `<magray_home>/audit`
+pub fn audit_config() -> AuditConfig {
+    AuditConfig::from_env(&crate::util::magray_home())
+}
+
+fn open_log() -> Result<AuditLog> {
+    let cfg = audit_config();
+    AuditLog::open(cfg).context("cannot open audit log")
+}
+
+fn print_entry(e: &AuditEntry, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(e)?);
+    } else {
+        println!(
+            "{} {} {} {} {}",
+            format!("#{}", e.seq).dimmed(),
+            e.ts.format("%Y-%m-%d %H:%M:%S%.3f").to_string().cyan(),
+            e.topic.bold(),
+            format!("[{}]", e.source).dimmed(),
+            e.payload
+        );
+    }
+    Ok(())
+}
+
+async fn handle_audit_command(cmd: AuditSubcommand) -> Result<()> {
+    let log = open_log()?;
+    match cmd {
+        AuditSubcommand::Tail { lines, json } => {
+            let entries = log.query(&AuditQuery {
+                limit: Some(lines),
+                ..Default::default()
+            })?;
+            if entries.is_empty() && !json {
+                println!("Журнал аудита пуст ({})", log.config().dir.display());
+            }
+            for e in &entries {
+                print_entry(e, json)?;
+            }
+        }
+        AuditSubcommand::Search {
+            query,
+            topic,
+            since,
+            limit,
+            json,
+        } => {
+            let since = since.as_deref().map(parse_since).transpose()?;
+            let entries = log.query(&AuditQuery {
+                since,
+                topic,
+                text: query,
+                limit: Some(limit),
+            })?;
+            for e in &entries {
+                print_entry(e, json)?;
+            }
+            if !json {
+                println!("{} записей найдено", entries.len());
+            }
+        }
+        AuditSubcommand::Verify { json } => {
+            let report = log.verify()?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if report.is_valid() {
+                println!(
+                    "{} Цепочка цела: {} записей (seq {}..{})",
+                    "✓".green(),
+                    report.entries,
+                    report.first_seq.unwrap_or(0),
+                    report.last_seq.unwrap_or(0)
+                );
+                if report.truncated {
+                    println!(
+                        "  {} старые сегменты удалены по retention, проверка начата с seq {}",
+                        "ℹ".blue(),
+                        report.first_seq.unwrap_or(0)
+                    );
+                }
+            } else {
+                for err in &report.errors {
+                    println!("  {} {}", "✗".red(), err);
+                }
+            }
+            if !report.is_valid() {
+                anyhow::bail!(
+                    "audit log integrity check failed ({} problems)",
+                    report.errors.len()
+                );
+            }
+        }
+        AuditSubcommand::Export { since, output } => {
+            let since = since.as_deref().map(parse_since).transpose()?;
+            match output {
+                Some(path) => {
+                    let mut file = fs::File::create(&path)
+                        .with_context(|| format!("cannot create {}", path.display()))?;
+                    let n = log.export_jsonl(since, &mut file)?;
+                    file.flush()?;
+                    println!("{} {} записей → {}", "✓".green(), n, path.display());
+                }
+                None => {
+                    let stdout = std::io::stdout();
+                    let mut lock = stdout.lock();
+                    log.export_jsonl(since, &mut lock)?;
+                    lock.flush()?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
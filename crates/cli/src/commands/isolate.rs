@@ -0,0 +1,124 @@
+//! Изолированный запуск (`--isolate`) для `magray smart` и `magray run`
+//!
+//! Агент работает во временном git worktree на новой ветке `magray/...`:
+//! инструменты gate запускаются в нём и пишут только туда, рабочая копия
+//! пользователя не меняется. По завершении изменения коммитятся в ветку, в
+//! worktree запускаются тесты, а итоговый diff показывается в TUI для merge,
+//! cherry-pick или discard.
+
+use anyhow::{anyhow, Result};
+use colored::*;
+use orchestrator::{IsolatedWorkspace, ReviewDecision};
+use std::io::IsTerminal;
+use tools::invocation::ToolGate;
+use ui::components::action_buttons::ButtonAction;
+use ui::components::diff_viewer::DiffData;
+use ui::tui::TUIApp;
+
+pub(super) struct Isolation {
+    workspace: IsolatedWorkspace,
+}
+
+/// Создать worktree для текущего репозитория
+pub(super) fn start(label: &str) -> Result<Isolation> {
+    let cwd = std::env::current_dir()?;
+    let workspace = IsolatedWorkspace::create(&cwd, label, &crate::util::worktrees_dir())?;
+    println!(
+        "🌿 Изолированный запуск: ветка {} ({})",
+        workspace.branch().bold(),
+        workspace.path().display()
+    );
+    Ok(Isolation { workspace })
+}
+
+impl Isolation {
+    pub(super) fn branch(&self) -> &str {
+        self.workspace.branch()
+    }
+
+    /// Gate, инструменты которого работают в worktree и пишут только туда
+    pub(super) fn scope_gate(&self, gate: ToolGate) -> ToolGate {
+        self.workspace.scope_gate(gate)
+    }
+
+    /// Закоммитить изменения, прогнать тесты и применить решение по ветке.
+    /// Возвращает `outcome` запуска, а если он успешен — ошибку применения
+    /// решения.
+    pub(super) fn finish(
+        self,
+        title: &str,
+        outcome: Result<()>,
+        test_cmd: Option<&str>,
+        decision: Option<ReviewDecision>,
+    ) -> Result<()> {
+        let finished = tokio::task::block_in_place(|| self.review(title, test_cmd, decision));
+        if let Err(e) = &finished {
+            println!("{} {}", "✗".red(), e);
+        }
+        outcome.and(finished)
+    }
+
+    fn review(
+        self,
+        title: &str,
+        test_cmd: Option<&str>,
+        decision: Option<ReviewDecision>,
+    ) -> Result<()> {
+        let workspace = self.workspace;
+        workspace.commit(&format!("magray: {title}"))?;
+        if workspace.commit_count()? == 0 {
+            println!("🌿 Изменений нет, ветка {} удалена", workspace.branch());
+            workspace.finish(ReviewDecision::Discard)?;
+            return Ok(());
+        }
+
+        let tests = workspace.run_tests(test_cmd)?;
+        match &tests {
+            Some(run) if run.success => println!("{} Тесты прошли: {}", "✓".green(), run.command),
+            Some(run) => {
+                println!("{}", run.output.trim_end());
+                println!("{} Тесты не прошли: {}", "✗".red(), run.command);
+            }
+            None => println!("ℹ️  Команда тестов не найдена, тесты пропущены"),
+        }
+        println!("{}", workspace.diff_stat()?.trim_end());
+
+        let decision = match decision {
+            Some(decision) => decision,
+            None if std::io::stdout().is_terminal() => {
+                let tests = match &tests {
+                    Some(run) if run.success => "tests passed",
+                    Some(_) => "TESTS FAILED",
+                    None => "no tests",
+                };
+                review_in_tui(
+                    &workspace,
+                    &format!("{} | {} | {}", workspace.branch(), title, tests),
+                )?
+            }
+            None => ReviewDecision::Keep,
+        };
+        let message = workspace.finish(decision)?;
+        println!("🌿 {message}");
+        Ok(())
+    }
+}
+
+/// Diff ветки в TUI `DiffViewer`; выход без выбора оставляет ветку
+fn review_in_tui(workspace: &IsolatedWorkspace, summary: &str) -> Result<ReviewDecision> {
+    let diff = DiffData::from_unified(summary, &workspace.diff()?);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut app = TUIApp::new()
+        .map_err(|e| anyhow!("Failed to initialize TUI: {}", e))?
+        .with_review(serde_json::to_string(&diff)?, summary.to_string(), tx);
+    app.run()
+        .map_err(|e| anyhow!("TUI execution failed: {}", e))?;
+    drop(app);
+
+    Ok(match rx.try_recv() {
+        Ok(ButtonAction::Merge) => ReviewDecision::Merge,
+        Ok(ButtonAction::CherryPick) => ReviewDecision::CherryPick,
+        Ok(ButtonAction::Discard) => ReviewDecision::Discard,
+        _ => ReviewDecision::Keep,
+    })
+}
@@ -1,13 +1,18 @@
 pub mod agent;
+pub mod audit;
 pub mod config;
 pub mod gpu;
+mod isolate;
 #[cfg(not(feature = "minimal"))]
 pub mod memory;
 #[cfg(feature = "minimal")]
 pub mod memory_stub;
 pub mod models;
 pub mod orchestrator;
+pub mod policy;
 pub mod router;
+pub mod run;
+pub mod schedule;
 pub mod smart;
 pub mod tasks;
 pub mod tools;
@@ -16,6 +21,7 @@ pub mod tools;
 #[cfg(any(feature = "cpu", feature = "gpu"))]
 pub mod ai;
 
+pub use audit::AuditCommand;
 pub use gpu::GpuCommand;
 #[cfg(not(feature = "minimal"))]
 pub use memory::MemoryCommand;
@@ -23,6 +29,9 @@ pub use memory::MemoryCommand;
 pub use memory_stub::MemoryCommand;
 pub use models::ModelsCommand;
 pub use orchestrator::OrchestratorCommand;
+pub use policy::PolicySubcommand;
+pub use run::RunCommand;
+pub use schedule::ScheduleCommand;
 pub use smart::SmartCommand;
 pub use tasks::TasksCommand;
 pub use tools::ToolsCommand;
@@ -1,6 +1,17 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use clap::{Args, Subcommand};
-use tracing::info;
+use orchestrator::agents::InteractionHandler;
+use orchestrator::{
+    create_agent_event_publisher, AgentOrchestrator, CompensationReportEntry, CompensationStatus,
+    ExecutionStatus, OrchestratorConfig, RecoveryReport, SpendBudget, StoredWorkflow, SystemConfig,
+    TaskPriority, WorkflowConfig, WorkflowRequest, WorkflowResult, WorkflowStore,
+    WorkflowTransition,
+};
+use std::sync::Arc;
+use tools::invocation::ToolGate;
+use tracing::{info, warn};
+
+use super::run::TerminalInteraction;
 
 #[derive(Debug, Args)]
 pub struct OrchestratorCommand {
@@ -23,6 +34,10 @@ pub enum OrchestratorAction {
         /// Enable dry-run mode (preview without execution)
         #[arg(long)]
         dry_run: bool,
+
+        /// Spend limits, e.g. `tokens=20000/50000,cost=1.5,tools=/10,time=10m` (SOFT/HARD)
+        #[arg(long, value_parser = <SpendBudget as std::str::FromStr>::from_str)]
+        budget: Option<SpendBudget>,
     },
 
     /// Show agent status and health information
@@ -47,6 +62,12 @@ pub enum OrchestratorAction {
         history: bool,
     },
 
+    /// Resume an interrupted or failed workflow from its last completed step
+    Resume {
+        /// Workflow ID (or unique prefix) to resume
+        workflow_id: String,
+    },
+
     /// Cancel a running workflow
     Cancel {
         /// Workflow ID to cancel
@@ -58,12 +79,179 @@ pub enum OrchestratorAction {
         /// Workflow ID to inspect
         workflow_id: String,
 
-        /// Show detailed step information
+        /// Show step results and saga checkpoints
         #[arg(short, long)]
         details: bool,
     },
 }
 
+/// Open the workflow state store at `<magray_home>/orchestrator/workflows.db`
+pub fn open_workflow_store() -> Result<Arc<WorkflowStore>> {
+    let path = WorkflowStore::default_path(&crate::util::magray_home());
+    Ok(Arc::new(WorkflowStore::open(path)?))
+}
+
+/// Create an orchestrator backed by the workflow store and run startup
+/// recovery (interrupted workflows, pending saga compensations)
+pub async fn create_durable_orchestrator(
+    store: Arc<WorkflowStore>,
+    interaction: Option<Arc<dyn InteractionHandler>>,
+) -> Result<(AgentOrchestrator, RecoveryReport)> {
+    let event_publisher = create_agent_event_publisher()
+        .await
+        .map_err(|e| anyhow!("Failed to create event publisher: {}", e))?;
+
+    // Executors call the same gated tools as `magray run`
+    let registry = Arc::new(super::tools::load_tool_registry().await);
+    let (home_policy, project_root) = super::policy::policy_sources()?;
+    let gate = Arc::new(ToolGate::for_project(home_policy.as_deref(), &project_root));
+
+    let mut orchestrator = AgentOrchestrator::new(
+        SystemConfig::default(),
+        OrchestratorConfig::default(),
+        event_publisher,
+    )
+    .await
+    .map_err(|e| anyhow!("Failed to create orchestrator: {}", e))?
+    .with_state_store(store)
+    .with_tools(registry, Arc::clone(&gate));
+
+    #[cfg(not(feature = "minimal"))]
+    {
+        use memory::api::{MemoryServiceTrait, UnifiedMemoryAPI};
+        use orchestrator::agents::{MemoryApiInvoker, MemoryApiUndoTarget};
+
+        let api = Arc::new(UnifiedMemoryAPI::new(
+            Arc::new(memory::di::UnifiedContainer::new()) as Arc<dyn MemoryServiceTrait>,
+        ));
+        orchestrator = orchestrator.with_memory(
+            Arc::new(MemoryApiInvoker::new(Arc::clone(&api), gate)),
+            Arc::new(MemoryApiUndoTarget::new(api)),
+        );
+    }
+    if let Some(interaction) = interaction {
+        orchestrator = orchestrator.with_interaction_handler(interaction);
+    }
+
+    let report = orchestrator
+        .recover_interrupted()
+        .await
+        .map_err(|e| anyhow!("Workflow recovery failed: {}", e))?;
+
+    orchestrator
+        .initialize_agents()
+        .await
+        .map_err(|e| anyhow!("Failed to initialize agents: {}", e))?;
+
+    Ok((orchestrator, report))
+}
+
+pub fn print_recovery_report(report: &RecoveryReport) {
+    for id in &report.interrupted_workflows {
+        println!("⚠️  Workflow {id} was interrupted; run `magray orchestrator resume {id}`");
+    }
+    for saga in &report.compensated_sagas {
+        println!(
+            "↩️  Saga {} compensated: {} step(s), {} failed ({:?})",
+            saga.saga_id, saga.compensated_steps, saga.failed_compensations, saga.status
+        );
+        print_compensation_report(&saga.report);
+    }
+    for error in &report.errors {
+        println!("❌ Recovery error: {error}");
+    }
+}
+
+fn compensation_icon(status: &CompensationStatus) -> &'static str {
+    match status {
+        CompensationStatus::Completed => "✅",
+        CompensationStatus::Failed => "❌",
+        CompensationStatus::Skipped => "⏭️",
+        CompensationStatus::NotCompensable => "⛔",
+        CompensationStatus::Pending | CompensationStatus::Executing => "⏳",
+    }
+}
+
+pub fn print_compensation_report(report: &[CompensationReportEntry]) {
+    for entry in report {
+        match &entry.error {
+            Some(error) => println!(
+                "     {} {} - {error}",
+                compensation_icon(&entry.status),
+                entry.description
+            ),
+            None => println!(
+                "     {} {}",
+                compensation_icon(&entry.status),
+                entry.description
+            ),
+        }
+    }
+}
+
+pub fn print_workflow_result(result: &WorkflowResult) {
+    for step in &result.steps_completed {
+        println!("  ✅ {step}");
+    }
+    println!("⏱️  {}ms", result.execution_time_ms);
+    if let Some(spend) = &result.resource_usage.spend {
+        println!("💰 {}", spend.summary());
+    }
+    let compensation_report = result
+        .execution_results
+        .as_ref()
+        .and_then(|results| results.pointer("/metadata/compensation_report"))
+        .and_then(|report| {
+            serde_json::from_value::<Vec<CompensationReportEntry>>(report.clone()).ok()
+        });
+    if let Some(report) = compensation_report.filter(|report| !report.is_empty()) {
+        println!("↩️  Rolled back:");
+        print_compensation_report(&report);
+    }
+    if result.success {
+        println!(
+            "\n✅ Workflow {} completed successfully",
+            result.workflow_id
+        );
+    } else {
+        println!(
+            "\n❌ Workflow {} failed: {}",
+            result.workflow_id,
+            result.error.as_deref().unwrap_or("unknown error")
+        );
+        println!(
+            "   Resume with: magray orchestrator resume {}",
+            result.workflow_id
+        );
+    }
+}
+
+fn status_icon(status: &ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Pending => "⏳",
+        ExecutionStatus::Running => "🔄",
+        ExecutionStatus::Completed => "✅",
+        ExecutionStatus::Failed => "❌",
+        ExecutionStatus::Skipped => "⏹️",
+    }
+}
+
+fn find_workflow(store: &WorkflowStore, workflow_id: &str) -> Result<StoredWorkflow> {
+    store
+        .find(workflow_id)?
+        .ok_or_else(|| anyhow!("Workflow not found: {workflow_id}"))
+}
+
+pub fn parse_workflow_config(config: &str) -> Result<WorkflowConfig> {
+    let raw = if std::path::Path::new(config).is_file() {
+        std::fs::read_to_string(config)
+            .with_context(|| format!("Failed to read workflow config {config}"))?
+    } else {
+        config.to_string()
+    };
+    serde_json::from_str(&raw).context("Invalid workflow config (expected JSON)")
+}
+
 impl OrchestratorCommand {
     pub async fn execute(&self) -> Result<()> {
         match &self.action {
@@ -71,8 +259,9 @@ impl OrchestratorCommand {
                 intent,
                 config,
                 dry_run,
+                budget,
             } => {
-                self.execute_workflow(intent, config.as_deref(), *dry_run)
+                self.execute_workflow(intent, config.as_deref(), *dry_run, *budget)
                     .await
             }
             OrchestratorAction::Status { detailed, agent } => {
@@ -81,6 +270,7 @@ impl OrchestratorCommand {
             OrchestratorAction::List { running, history } => {
                 self.list_workflows(*running, *history).await
             }
+            OrchestratorAction::Resume { workflow_id } => self.resume_workflow(workflow_id).await,
             OrchestratorAction::Cancel { workflow_id } => self.cancel_workflow(workflow_id).await,
             OrchestratorAction::Info {
                 workflow_id,
@@ -94,6 +284,7 @@ impl OrchestratorCommand {
         intent: &str,
         config: Option<&str>,
         dry_run: bool,
+        budget: Option<SpendBudget>,
     ) -> Result<()> {
         info!("Starting multi-agent orchestration for intent: {}", intent);
 
@@ -101,37 +292,70 @@ impl OrchestratorCommand {
             println!("🔍 Dry-run mode: Analyzing workflow without execution...");
         }
 
-        // Mock workflow execution for now - will be implemented with real orchestrator integration
         println!("🤖 Multi-Agent Orchestration System");
         println!("==================================");
         println!("📝 Intent: {intent}");
-        if let Some(cfg) = config {
-            println!("⚙️ Config: {cfg}");
-        }
-
-        // Simulate workflow steps
-        let steps = vec![
-            ("🔍 IntentAnalyzer", "Analyzing user intent"),
-            ("📋 Planner", "Creating execution plan"),
-            ("⚡ Executor", "Executing planned steps"),
-            ("🔍 Critic", "Analyzing execution results"),
-        ];
-
-        for (agent, description) in steps {
-            println!("  {agent} {description}");
-            if !dry_run {
-                // Simulate processing time
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        let config_overrides = match config {
+            Some(cfg) => {
+                println!("⚙️ Config: {cfg}");
+                Some(parse_workflow_config(cfg)?)
             }
-            println!("    ✅ Completed");
+            None => None,
+        };
+
+        let (orchestrator, report) = create_durable_orchestrator(
+            open_workflow_store()?,
+            Some(Arc::new(TerminalInteraction { dry_run })),
+        )
+        .await?;
+        print_recovery_report(&report);
+
+        let request = WorkflowRequest {
+            user_input: intent.to_string(),
+            context: None,
+            priority: TaskPriority::Normal,
+            dry_run,
+            timeout_ms: None,
+            config_overrides,
+            budget,
+        };
+        let result = orchestrator
+            .execute_workflow(request)
+            .await
+            .map_err(|e| anyhow!("Workflow execution failed: {}", e))?;
+        print_workflow_result(&result);
+
+        if let Err(e) = orchestrator.shutdown().await {
+            warn!("Orchestrator shutdown failed: {}", e);
         }
+        Ok(())
+    }
 
-        if dry_run {
-            println!("\n🔍 Dry-run completed - no actual execution performed");
-        } else {
-            println!("\n✅ Workflow completed successfully");
+    async fn resume_workflow(&self, workflow_id: &str) -> Result<()> {
+        let store = open_workflow_store()?;
+        let stored = find_workflow(&store, workflow_id)?;
+        let id = stored.state.id;
+
+        println!("▶️  Resuming workflow: {id}");
+        println!("📝 Intent: {}", stored.state.user_input);
+        println!("📍 Last step: {}", stored.state.current_step);
+
+        let (orchestrator, report) = create_durable_orchestrator(
+            store,
+            Some(Arc::new(TerminalInteraction { dry_run: false })),
+        )
+        .await?;
+        print_recovery_report(&report);
+
+        let result = orchestrator
+            .resume_workflow(id)
+            .await
+            .map_err(|e| anyhow!("Failed to resume workflow {}: {}", id, e))?;
+        print_workflow_result(&result);
+
+        if let Err(e) = orchestrator.shutdown().await {
+            warn!("Orchestrator shutdown failed: {}", e);
         }
-
         Ok(())
     }
 
@@ -192,6 +416,25 @@ impl OrchestratorCommand {
             }
         }
 
+        // Live spend of running workflows, as last checkpointed by their process
+        let running: Vec<StoredWorkflow> = open_workflow_store()?
+            .list(100)?
+            .into_iter()
+            .filter(|stored| stored.state.status == ExecutionStatus::Running)
+            .collect();
+        if running
+            .iter()
+            .any(|stored| stored.state.resource_usage.spend.is_some())
+        {
+            println!("💰 Spend:");
+            for stored in &running {
+                if let Some(spend) = &stored.state.resource_usage.spend {
+                    println!("   {} {}", stored.state.id, spend.summary());
+                }
+            }
+            println!();
+        }
+
         if detailed {
             println!("📋 Recent Activity:");
             println!("   • 12:34:15: Workflow WF-001 completed successfully");
@@ -205,111 +448,170 @@ impl OrchestratorCommand {
     }
 
     async fn list_workflows(&self, running_only: bool, show_history: bool) -> Result<()> {
+        let store = open_workflow_store()?;
+        let workflows = store.list(100)?;
+
         if show_history {
             println!("📋 Workflow History");
             println!("==================");
-
-            // Mock workflow history
-            let history = vec![
-                (
-                    "WF-001",
-                    "✅",
-                    "2025-08-12 12:30",
-                    "Search memory for user query",
-                ),
-                (
-                    "WF-002",
-                    "✅",
-                    "2025-08-12 12:25",
-                    "Generate project documentation",
-                ),
-                (
-                    "WF-003",
-                    "❌",
-                    "2025-08-12 12:20",
-                    "Deploy to production server",
-                ),
-                ("WF-004", "✅", "2025-08-12 12:15", "Run test suite"),
-                ("WF-005", "⏹️", "2025-08-12 12:10", "Large file processing"),
-            ];
-
-            for (id, status_icon, created_at, intent) in history {
-                println!("{status_icon} {id} [{created_at}] - {intent}");
-            }
         } else {
             println!("🔄 Active Workflows");
             println!("==================");
+        }
 
-            // Mock active workflows
-            let active_workflows = vec![
-                ("WF-006", "🔄", "Memory indexing operation", 45),
-                ("WF-007", "⏳", "Tool discovery and registration", 10),
-                ("WF-008", "⏸️", "Large data analysis", 78),
-            ];
-
-            if active_workflows.is_empty() {
-                println!("No active workflows");
-                return Ok(());
+        let mut shown = 0;
+        for stored in &workflows {
+            let state = &stored.state;
+            let active = matches!(
+                state.status,
+                ExecutionStatus::Running | ExecutionStatus::Pending
+            );
+            if !show_history && !active {
+                continue;
             }
-
-            for (id, status_icon, intent, progress) in active_workflows {
-                if running_only && status_icon != "🔄" {
-                    continue;
-                }
-
-                println!("{status_icon} {id} - {intent} (Progress: {progress}%)");
+            if running_only && state.status != ExecutionStatus::Running {
+                continue;
             }
+            println!(
+                "{} {} [{}] {} - {}",
+                status_icon(&state.status),
+                state.id,
+                state.started_at.format("%Y-%m-%d %H:%M"),
+                state.current_step,
+                state.user_input
+            );
+            shown += 1;
+        }
+
+        if shown == 0 {
+            println!("No workflows");
         }
 
         Ok(())
     }
 
     async fn cancel_workflow(&self, workflow_id: &str) -> Result<()> {
-        println!("⏹️ Cancelling workflow: {workflow_id}");
-
-        // Mock workflow cancellation
-        if workflow_id.starts_with("WF-") {
-            println!("✅ Workflow cancelled successfully");
-        } else {
-            println!("❌ Invalid workflow ID format");
-            return Err(anyhow::anyhow!("Invalid workflow ID"));
+        let store = open_workflow_store()?;
+        let stored = find_workflow(&store, workflow_id)?;
+        let mut state = stored.state;
+        println!("⏹️ Cancelling workflow: {}", state.id);
+
+        if state.status == ExecutionStatus::Completed {
+            println!("❌ Workflow already completed");
+            return Err(anyhow!("Workflow already completed"));
         }
 
+        state.status = ExecutionStatus::Failed;
+        state.error = Some("Workflow cancelled by user".to_string());
+        state.updated_at = chrono::Utc::now();
+        store.checkpoint(&state, WorkflowTransition::Cancelled)?;
+        println!("✅ Workflow cancelled successfully");
+
         Ok(())
     }
 
     async fn show_workflow_info(&self, workflow_id: &str, show_details: bool) -> Result<()> {
-        // Mock workflow info lookup
-        if !workflow_id.starts_with("WF-") {
-            println!("❌ Workflow not found: {workflow_id}");
-            return Err(anyhow::anyhow!("Workflow not found"));
-        }
+        let store = open_workflow_store()?;
+        let stored = match store.find(workflow_id)? {
+            Some(stored) => stored,
+            None => {
+                println!("❌ Workflow not found: {workflow_id}");
+                return Err(anyhow!("Workflow not found"));
+            }
+        };
+        let state = &stored.state;
 
         println!("📋 Workflow Information");
         println!("======================");
-        println!("🆔 ID: {workflow_id}");
-        println!("💭 Intent: Memory search for user query");
-        println!("📊 Status: Running");
-        println!("📈 Progress: 75%");
-        println!("⏰ Created: 2025-08-12 12:30:15");
+        println!("🆔 ID: {}", state.id);
+        println!("💭 Intent: {}", state.user_input);
+        println!("📊 Status: {} {}", status_icon(&state.status), state.status);
+        println!("📍 Step: {}", state.current_step);
+        println!(
+            "⏰ Created: {}",
+            state.started_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        println!(
+            "🔄 Updated: {}",
+            state.updated_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        println!("🧵 Owner PID: {}", stored.owner_pid);
+        if let Some(error) = &state.error {
+            println!("⚠️  Error: {error}");
+        }
+        if let Some(spend) = &state.resource_usage.spend {
+            println!("💰 Spend: {}", spend.summary());
+        }
+
+        println!("\n📝 Checkpoints:");
+        for checkpoint in store.checkpoints(state.id)? {
+            println!(
+                "   {}. [{}] {}",
+                checkpoint.seq,
+                checkpoint.at.format("%H:%M:%S%.3f"),
+                checkpoint.transition
+            );
+        }
 
         if show_details {
-            println!("\n📝 Execution Steps:");
-            let steps = [
-                ("✅", "IntentAnalyzer", "User intent analysis"),
-                ("✅", "Planner", "Execution plan creation"),
-                ("🔄", "Executor", "Memory search execution"),
-                ("⏳", "Critic", "Result analysis"),
+            println!("\n📦 Step Results:");
+            let results = [
+                ("IntentAnalyzer", &state.intent),
+                ("Planner", &state.plan),
+                ("Executor", &state.execution_results),
+                ("Critic", &state.critique),
             ];
+            for (agent_type, value) in results {
+                match value {
+                    Some(value) => println!(
+                        "   ✅ {agent_type}: {}",
+                        serde_json::to_string_pretty(value)?
+                    ),
+                    None => println!("   ⏳ {agent_type}: -"),
+                }
+            }
 
-            for (i, (status_icon, agent_type, description)) in steps.iter().enumerate() {
-                println!(
-                    "   {}. {} {} - {}",
-                    i + 1,
-                    status_icon,
-                    agent_type,
-                    description
-                );
+            let plan_id = state
+                .plan
+                .as_ref()
+                .and_then(|plan| plan.get("id"))
+                .and_then(|id| id.as_str())
+                .and_then(|id| uuid::Uuid::parse_str(id).ok());
+            if let Some(plan_id) = plan_id {
+                for saga in store.sagas_for_plan(plan_id)? {
+                    println!("\n🔁 Saga {} ({:?})", saga.id, saga.status);
+                    for step in &saga.steps {
+                        println!(
+                            "   • {} {:?}{}",
+                            step.action_step_id,
+                            step.status,
+                            step.error
+                                .as_deref()
+                                .map(|e| format!(" - {e}"))
+                                .unwrap_or_default()
+                        );
+                    }
+                    for compensation in &saga.compensation_steps {
+                        println!(
+                            "   {} {}{}",
+                            compensation_icon(&compensation.status),
+                            compensation.compensation_type.describe(),
+                            compensation
+                                .error
+                                .as_deref()
+                                .map(|e| format!(" - {e}"))
+                                .unwrap_or_default()
+                        );
+                    }
+                    for checkpoint in store.saga_checkpoints(saga.id)? {
+                        println!(
+                            "     {}. [{}] {:?}",
+                            checkpoint.seq,
+                            checkpoint.at.format("%H:%M:%S%.3f"),
+                            checkpoint.saga.status
+                        );
+                    }
+                }
             }
         }
 
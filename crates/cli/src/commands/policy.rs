@@ -0,0 +1,238 @@
+use anyhow::Result;
+use clap::Subcommand;
+use colored::*;
+use common::policy::{
+    find_project_root, load_effective_policy_for_project, load_from_path, project_policy_path,
+    PolicyAction, PolicyEngine, PolicySubjectKind, ProjectTrust,
+};
+use common::redaction::{RedactionTarget, Redactor};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Subcommand)]
+pub enum PolicySubcommand {
+    /// Проверить, какое правило сработает для инструмента и почему
+    #[command(name = "test")]
+    Test {
+        /// Имя инструмента (или команды с --command, напр. memory.backup)
+        subject: String,
+        /// Аргументы в формате key=value (можно несколько раз)
+        #[arg(long, num_args=0.., value_parser=parse_kv)]
+        arg: Vec<(String, String)>,
+        /// Проверять как команду (subject_kind=Command)
+        #[arg(long, default_value_t = false)]
+        command: bool,
+        /// Вывести JSON (решение и трассировка правил)
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Найти секреты в файле или stdin по текущей политике redaction
+    #[command(name = "secrets")]
+    Secrets {
+        /// Файл для проверки (по умолчанию stdin)
+        path: Option<PathBuf>,
+        /// Вывести находки в JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Доверять .magray/policy.json проекта: без этого его Allow-правила и
+    /// ослабление redaction игнорируются
+    #[command(name = "trust")]
+    Trust {
+        /// Корень проекта (по умолчанию ближайший с .magray/policy.json)
+        path: Option<PathBuf>,
+    },
+}
+
+fn parse_kv(s: &str) -> Result<(String, String), String> {
+    let (k, v) = s
+        .split_once('=')
+        .ok_or_else(|| "arg must be in key=value format".to_string())?;
+    Ok((k.to_string(), v.to_string()))
+}
+
+/// Ошибка, если в политике есть правила с невалидными шаблонами
+fn reject_invalid_rules(problems: &[String]) -> Result<()> {
+    if problems.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Политика содержит некорректные правила ({}), исправьте их",
+        problems.len()
+    )
+}
+
+/// Global policy file plus the nearest project root (`.magray/policy.json`)
+pub fn policy_sources() -> Result<(Option<PathBuf>, PathBuf)> {
+    let mut home = crate::util::magray_home();
+    home.push("policy.json");
+    let cwd = std::env::current_dir()?;
+    let project_root = find_project_root(&cwd).unwrap_or(cwd);
+    Ok((home.exists().then_some(home), project_root))
+}
+
+pub async fn handle_policy_subcommand(cmd: PolicySubcommand) -> Result<()> {
+    match cmd {
+        PolicySubcommand::Test {
+            subject,
+            arg,
+            command,
+            json,
+        } => {
+            let (home, project_root) = policy_sources()?;
+            let doc = load_effective_policy_for_project(home.as_deref(), Some(&project_root));
+            let problems = doc.validate();
+            let engine = PolicyEngine::from_document(doc);
+            let kind = if command {
+                PolicySubjectKind::Command
+            } else {
+                PolicySubjectKind::Tool
+            };
+            let args: HashMap<String, String> = arg.into_iter().collect();
+            let explanation = engine.explain(kind, &subject, &args);
+
+            if json {
+                let mut out = serde_json::to_value(&explanation)?;
+                if let Some(obj) = out.as_object_mut() {
+                    obj.insert("problems".into(), serde_json::json!(problems));
+                }
+                println!("{}", serde_json::to_string_pretty(&out)?);
+                return reject_invalid_rules(&problems);
+            }
+
+            println!("{}", "=== Policy test ===".bold().cyan());
+            println!("  Проект: {}", project_root.display());
+            for p in &problems {
+                println!("  {} {}", "⚠".yellow(), p);
+            }
+            for t in &explanation.trace {
+                let mark = if explanation.winner == Some(t.index) {
+                    "→".green().bold()
+                } else if t.matched {
+                    "✓".green()
+                } else {
+                    "·".dimmed()
+                };
+                let prio = if t.rule.priority != 0 {
+                    format!(" prio={}", t.rule.priority)
+                } else {
+                    String::new()
+                };
+                println!(
+                    "  {} #{} {:?} {} {:?}{}: {}",
+                    mark,
+                    t.index,
+                    t.rule.subject_kind,
+                    t.rule.subject_name,
+                    t.rule.action,
+                    prio,
+                    t.detail
+                );
+            }
+            let action = match explanation.decision.action {
+                PolicyAction::Allow => "Allow".green(),
+                PolicyAction::Deny => "Deny".red(),
+                PolicyAction::Ask => "Ask".yellow(),
+            };
+            match explanation.winner {
+                Some(i) => println!(
+                    "Решение: {} (правило #{}{})",
+                    action,
+                    i,
+                    explanation.trace[i]
+                        .rule
+                        .reason
+                        .as_ref()
+                        .map(|r| format!(", {r}"))
+                        .unwrap_or_default()
+                ),
+                None if explanation.emergency_bypass => {
+                    println!("Решение: {} (emergency bypass)", action)
+                }
+                None => println!(
+                    "Решение: {} (нет подходящего правила, secure-by-default)",
+                    action
+                ),
+            }
+            println!("Риск: {:?}", explanation.decision.risk);
+            reject_invalid_rules(&problems)?;
+        }
+        PolicySubcommand::Secrets { path, json } => {
+            let text = match &path {
+                Some(p) => std::fs::read_to_string(p)?,
+                None => std::io::read_to_string(std::io::stdin())?,
+            };
+            let (home, project_root) = policy_sources()?;
+            let doc = load_effective_policy_for_project(home.as_deref(), Some(&project_root));
+            let redaction = doc.redaction.unwrap_or_default();
+            let action = redaction.action_for(RedactionTarget::Llm);
+            let findings = Redactor::new(redaction).scan(&text);
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&findings)?);
+                return Ok(());
+            }
+            if findings.is_empty() {
+                println!("{} Секреты не найдены", "✓".green());
+                return Ok(());
+            }
+            for f in &findings {
+                let line = text[..f.start].matches('\n').count() + 1;
+                println!(
+                    "  {} line {}: {} {} {}",
+                    "⚠".yellow(),
+                    line,
+                    f.kind.to_string().bold(),
+                    f.preview,
+                    f.fingerprint.dimmed()
+                );
+            }
+            println!(
+                "Найдено: {}, действие для LLM: {:?}",
+                findings.len(),
+                action
+            );
+            println!(
+                "Ложное срабатывание? Добавьте отпечаток в \"redaction.allowlist\" в {} и выполните `magray policy trust`",
+                project_policy_path(&project_root).display()
+            );
+        }
+        PolicySubcommand::Trust { path } => {
+            let project_root = match path {
+                Some(p) => p,
+                None => policy_sources()?.1,
+            };
+            let file = project_policy_path(&project_root);
+            let doc = load_from_path(&file)?;
+            let allow: Vec<_> = doc
+                .rules
+                .iter()
+                .filter(|r| r.action == PolicyAction::Allow)
+                .collect();
+            println!("{}", "=== Policy trust ===".bold().cyan());
+            println!("  Файл: {}", file.display());
+            for r in &allow {
+                println!(
+                    "  {} Allow {:?} {}{}",
+                    "•".green(),
+                    r.subject_kind,
+                    r.subject_name,
+                    r.reason
+                        .as_ref()
+                        .map(|reason| format!(": {reason}"))
+                        .unwrap_or_default()
+                );
+            }
+            if doc.redaction.is_some() {
+                println!("  {} Настройки redaction проекта", "•".green());
+            }
+            ProjectTrust::from_env().trust(&file)?;
+            println!(
+                "{} Доверие сохранено ({} Allow-правил); после изменения файла потребуется снова",
+                "✓".green(),
+                allow.len()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,453 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use clap::Args;
+use orchestrator::agents::executor::{ExecutionResult, ExecutionStatus, StepStatus};
+use orchestrator::agents::planner::{ActionStep, ActionStepType, InteractionType};
+use orchestrator::agents::{register_registry_tools, InteractionHandler, STEP_TIMEOUT_PARAM};
+use orchestrator::{
+    CompensationReportEntry, CompiledWorkflow, Executor, ExecutorTrait, Planner, ReviewDecision,
+    SpendApprover, SpendBudget, SpendOverrun, SpendReport, SpendTracker, WorkflowFile,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tools::invocation::ToolGate;
+use tools::ToolRegistry;
+
+use super::orchestrator::{open_workflow_store, print_compensation_report};
+
+#[derive(Debug, Args)]
+pub struct RunCommand {
+    /// Workflow file (.yaml, .yml or .toml)
+    pub file: PathBuf,
+
+    /// Workflow parameter NAME=VALUE (repeatable; VALUE is read as JSON when it parses)
+    #[arg(short, long = "param", value_parser = parse_param)]
+    pub params: Vec<(String, Value)>,
+
+    /// Preview: tools run in dry-run mode, memory is not changed and prompts
+    /// are answered with their defaults
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Spend limits on top of the file's `budget`, e.g. `tokens=20000/50000,cost=1.5,tools=/10,time=10m`
+    /// (SOFT/HARD; crossing a soft limit asks whether to continue)
+    #[arg(long, value_parser = SpendBudget::from_str)]
+    pub budget: Option<SpendBudget>,
+
+    /// Run in a temporary git worktree on a new branch; the combined diff is
+    /// offered for merge, cherry-pick or discard at the end
+    #[arg(long)]
+    pub isolate: bool,
+
+    /// Test command to run in the worktree (detected from the project by default)
+    #[arg(long, requires = "isolate")]
+    pub test_cmd: Option<String>,
+
+    /// What to do with the branch instead of asking in the TUI:
+    /// merge, cherry-pick, discard or keep
+    #[arg(long, requires = "isolate", value_parser = ReviewDecision::from_str)]
+    pub on_finish: Option<ReviewDecision>,
+}
+
+fn parse_param(s: &str) -> Result<(String, Value), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| "param must be in NAME=VALUE format".to_string())?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((name.trim().to_string(), value))
+}
+
+/// Answers `ask` steps on the terminal
+pub(super) struct TerminalInteraction {
+    pub(super) dry_run: bool,
+}
+
+pub(super) fn read_answer(prompt: String) -> Result<String> {
+    use std::io::Write;
+    print!("{prompt}");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .context("Failed to read answer from stdin")?;
+    Ok(answer.trim().to_string())
+}
+
+#[async_trait]
+impl InteractionHandler for TerminalInteraction {
+    async fn interact(
+        &self,
+        interaction_type: &InteractionType,
+        prompt: &str,
+        parameters: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let options: Vec<String> = parameters
+            .get("options")
+            .and_then(Value::as_array)
+            .map(|options| {
+                options
+                    .iter()
+                    .map(|option| match option {
+                        Value::String(option) => option.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let default = parameters.get("default").cloned();
+
+        if self.dry_run || *interaction_type == InteractionType::Information {
+            let answer = match interaction_type {
+                InteractionType::Information => Value::Null,
+                InteractionType::Confirmation => default.unwrap_or(Value::Bool(true)),
+                InteractionType::Input => default.unwrap_or_else(|| Value::from("")),
+                InteractionType::Choice => default
+                    .or_else(|| options.first().cloned().map(Value::from))
+                    .unwrap_or(Value::Null),
+            };
+            if answer.is_null() {
+                println!("ℹ️  {prompt}");
+            } else {
+                println!("❔ {prompt} (dry-run answer: {answer})");
+            }
+            return Ok(answer);
+        }
+
+        let prompt = prompt.to_string();
+        let interaction_type = interaction_type.clone();
+        tokio::task::spawn_blocking(move || match interaction_type {
+            InteractionType::Confirmation => {
+                let answer = read_answer(format!("❔ {prompt} [y/N] "))?.to_lowercase();
+                if answer.is_empty() {
+                    return Ok(default.unwrap_or(Value::Bool(false)));
+                }
+                Ok(Value::Bool(matches!(
+                    answer.as_str(),
+                    "y" | "yes" | "д" | "да"
+                )))
+            }
+            InteractionType::Input => {
+                let hint = match &default {
+                    Some(Value::String(default)) => format!(" [{default}]"),
+                    Some(default) => format!(" [{default}]"),
+                    None => String::new(),
+                };
+                let answer = read_answer(format!("❔ {prompt}{hint}: "))?;
+                match default {
+                    Some(default) if answer.is_empty() => Ok(default),
+                    _ => Ok(Value::String(answer)),
+                }
+            }
+            InteractionType::Choice => {
+                println!("❔ {prompt}");
+                for (index, option) in options.iter().enumerate() {
+                    println!("   {}) {option}", index + 1);
+                }
+                let answer = read_answer("   > ".to_string())?;
+                if answer.is_empty() {
+                    if let Some(default) = default {
+                        return Ok(default);
+                    }
+                }
+                let chosen = answer
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|index| options.get(index))
+                    .or_else(|| options.iter().find(|option| **option == answer));
+                match chosen {
+                    Some(option) => Ok(Value::String(option.clone())),
+                    None => anyhow::bail!("'{answer}' is not one of the options"),
+                }
+            }
+            InteractionType::Information => Ok(Value::Null),
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl SpendApprover for TerminalInteraction {
+    async fn approve_overrun(&self, overruns: &[SpendOverrun], report: &SpendReport) -> bool {
+        for overrun in overruns {
+            println!("💰 {overrun}");
+        }
+        if self.dry_run {
+            println!("   dry-run: continuing ({})", report.summary());
+            return true;
+        }
+        let prompt = format!(
+            "❔ Continue past the soft limit? ({}) [y/N] ",
+            report.summary()
+        );
+        match tokio::task::spawn_blocking(move || read_answer(prompt)).await {
+            Ok(Ok(answer)) => matches!(answer.to_lowercase().as_str(), "y" | "yes" | "д" | "да"),
+            _ => false,
+        }
+    }
+}
+
+fn describe_step(step: &ActionStep) -> String {
+    match &step.step_type {
+        ActionStepType::ToolExecution {
+            tool_name,
+            arguments,
+        } => {
+            let mut args: Vec<String> = arguments.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            args.sort();
+            format!("tool {tool_name} {}", args.join(" "))
+        }
+        ActionStepType::MemoryOperation {
+            operation_type,
+            query,
+        } => format!("memory {operation_type:?}: {query}"),
+        ActionStepType::UserInteraction {
+            interaction_type,
+            prompt,
+        } => format!("ask ({interaction_type:?}): {prompt}"),
+        ActionStepType::Wait { duration } => format!("wait {duration:?}"),
+        ActionStepType::Conditional { condition, .. } => format!("if {condition}"),
+        ActionStepType::Loop {
+            condition,
+            max_iterations,
+            ..
+        } => format!("while {condition} (max {max_iterations})"),
+        ActionStepType::Delegate { profile, task } => format!("delegate @{profile}: {task}"),
+    }
+}
+
+fn print_steps(compiled: &CompiledWorkflow, steps: &[ActionStep], depth: usize) {
+    let indent = "   ".repeat(depth + 1);
+    for step in steps {
+        let mut extras = Vec::new();
+        if let Some(timeout) = step.parameters.get(STEP_TIMEOUT_PARAM) {
+            extras.push(format!("timeout {timeout}ms"));
+        }
+        if step.retry_policy.max_retries > 0 {
+            extras.push(format!("retry ×{}", step.retry_policy.max_retries));
+        }
+        let extras = if extras.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", extras.join(", "))
+        };
+        println!(
+            "{indent}• {}: {}{extras}",
+            compiled.step_name(step.id),
+            describe_step(step)
+        );
+        match &step.step_type {
+            ActionStepType::Conditional {
+                then_steps,
+                else_steps,
+                ..
+            } => {
+                print_steps(compiled, then_steps, depth + 1);
+                if !else_steps.is_empty() {
+                    println!("{indent}  else:");
+                    print_steps(compiled, else_steps, depth + 1);
+                }
+            }
+            ActionStepType::Loop { body_steps, .. } => print_steps(compiled, body_steps, depth + 1),
+            _ => {}
+        }
+    }
+}
+
+fn print_execution_result(compiled: &CompiledWorkflow, result: &ExecutionResult) {
+    for step in &result.step_results {
+        let icon = match step.status {
+            StepStatus::Completed => "✅",
+            StepStatus::Failed => "❌",
+            StepStatus::Skipped => "⏭️",
+            StepStatus::Pending | StepStatus::Running | StepStatus::Retrying => "⏳",
+        };
+        let retries = if step.retry_count > 0 {
+            format!(" after {} retries", step.retry_count)
+        } else {
+            String::new()
+        };
+        match &step.error {
+            Some(error) => println!(
+                "  {icon} {}{retries}: {error}",
+                compiled.step_name(step.step_id)
+            ),
+            None => println!(
+                "  {icon} {} ({}ms){retries}",
+                compiled.step_name(step.step_id),
+                step.execution_time.as_millis()
+            ),
+        }
+    }
+
+    let compensation_report = result
+        .metadata
+        .get("compensation_report")
+        .and_then(|report| {
+            serde_json::from_value::<Vec<CompensationReportEntry>>(report.clone()).ok()
+        });
+    if let Some(report) = compensation_report.filter(|report| !report.is_empty()) {
+        println!("↩️  Rolled back:");
+        print_compensation_report(&report);
+    }
+}
+
+impl RunCommand {
+    pub async fn execute(self) -> Result<()> {
+        let workflow = WorkflowFile::from_path(&self.file)?;
+        let params: HashMap<String, Value> = self.params.iter().cloned().collect();
+        let compiled = workflow.compile(&params);
+
+        // Tools go through the same policy/approval gate as `magray tools run`
+        let registry = Arc::new(super::tools::load_tool_registry().await);
+        let (home_policy, project_root) = super::policy::policy_sources()?;
+        // The policy comes from the checkout, the gate's workdir and sandbox from the worktree
+        let isolation = if self.isolate {
+            Some(super::isolate::start(&workflow.name)?)
+        } else {
+            None
+        };
+        let gate = ToolGate::for_project(home_policy.as_deref(), &project_root);
+        let gate = Arc::new(match &isolation {
+            Some(isolation) => isolation.scope_gate(gate),
+            None => gate,
+        });
+
+        let outcome = self
+            .run_workflow(&workflow, &compiled, registry, gate)
+            .await;
+        match isolation {
+            Some(isolation) => isolation.finish(
+                &format!("workflow {}", workflow.name),
+                outcome,
+                self.test_cmd.as_deref(),
+                self.on_finish,
+            ),
+            None => outcome,
+        }
+    }
+
+    async fn run_workflow(
+        &self,
+        workflow: &WorkflowFile,
+        compiled: &CompiledWorkflow,
+        registry: Arc<ToolRegistry>,
+        gate: Arc<ToolGate>,
+    ) -> Result<()> {
+        let store = open_workflow_store()?;
+        let mut executor = Executor::new().with_state_store(store);
+        register_registry_tools(
+            &mut executor,
+            Arc::clone(&registry),
+            Arc::clone(&gate),
+            self.dry_run,
+        );
+        let terminal = Arc::new(TerminalInteraction {
+            dry_run: self.dry_run,
+        });
+        executor.set_interaction_handler(terminal.clone());
+
+        let budget = match (&workflow.budget, &self.budget) {
+            (Some(file), Some(cli)) => Some(file.merge(cli)),
+            (file, cli) => cli.or(*file),
+        };
+        let spend = budget.filter(|b| !b.is_unlimited()).map(|budget| {
+            Arc::new(
+                SpendTracker::new(budget).with_approver(terminal.clone() as Arc<dyn SpendApprover>),
+            )
+        });
+        if let Some(spend) = &spend {
+            executor.set_spend_tracker(Arc::clone(spend));
+        }
+        #[cfg(not(feature = "minimal"))]
+        {
+            use memory::api::{MemoryServiceTrait, UnifiedMemoryAPI};
+            use orchestrator::agents::{MemoryApiInvoker, MemoryApiUndoTarget};
+            use orchestrator::MemoryUndoHandler;
+
+            let api = Arc::new(UnifiedMemoryAPI::new(
+                Arc::new(memory::di::UnifiedContainer::new()) as Arc<dyn MemoryServiceTrait>,
+            ));
+            executor.set_memory_invoker(Arc::new(
+                MemoryApiInvoker::new(Arc::clone(&api), Arc::clone(&gate))
+                    .with_dry_run(self.dry_run),
+            ));
+            executor.register_compensation_handler(Box::new(MemoryUndoHandler::new(Arc::new(
+                MemoryApiUndoTarget::new(api),
+            ))));
+        }
+
+        // `delegate` steps run as sub-agents of the `[agents.*]` profiles in the config
+        let profiles = super::smart::load_agent_profiles().await?;
+        if !profiles.is_empty() {
+            let factory = super::smart::ProfileAgentFactory {
+                registry: Arc::clone(&registry),
+                gate: Arc::clone(&gate),
+                interaction: terminal.clone(),
+                dry_run: self.dry_run,
+            };
+            executor.set_delegation_handler(Arc::new(
+                super::smart::profile_delegator(profiles.clone(), factory).await?,
+            ));
+        }
+
+        let mut planner = Planner::new();
+        for tool in executor.get_available_tools() {
+            planner.register_available_tool(&tool);
+        }
+        for profile in profiles.names() {
+            planner.register_agent_profile(&profile);
+        }
+        let validation = compiled.validate(&planner).await?;
+
+        println!("📋 Workflow '{}'", workflow.name);
+        if let Some(description) = &workflow.description {
+            println!("   {description}");
+        }
+        print_steps(compiled, &compiled.plan.steps, 0);
+        for warning in &validation.warnings {
+            println!("⚠️  {warning}");
+        }
+        if !validation.is_valid {
+            println!("\n❌ Workflow is invalid:");
+            for error in &validation.errors {
+                let step = error
+                    .step_id
+                    .map(|id| format!("{}: ", compiled.step_name(id)))
+                    .unwrap_or_default();
+                println!("   • {step}{} ({:?})", error.message, error.error_type);
+            }
+            anyhow::bail!(
+                "{} validation error(s) in {}",
+                validation.errors.len(),
+                self.file.display()
+            );
+        }
+
+        if self.dry_run {
+            println!("\n🔍 Dry run: tools preview only, memory is not changed");
+        }
+        println!();
+        let result = executor.execute_plan_with_saga(&compiled.plan).await?;
+        print_execution_result(compiled, &result);
+        println!("⏱️  {}ms", result.execution_time.as_millis());
+        if let Some(spend) = &spend {
+            println!("💰 {}", spend.report().summary());
+        }
+
+        if result.status == ExecutionStatus::Completed {
+            println!("\n✅ Workflow '{}' completed", workflow.name);
+            Ok(())
+        } else {
+            let error = result
+                .error
+                .as_ref()
+                .map_or("no error reported", |e| e.message.as_str());
+            println!("\n❌ Workflow '{}' failed: {}", workflow.name, error);
+            anyhow::bail!("Workflow '{}' failed", workflow.name)
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use clap::{Args, Subcommand};
+use orchestrator::{
+    AgentOrchestrator, CronExpression, CronTimeZone, MissedRunPolicy, ScheduledPayload,
+    ScheduledTask, TaskPriority, TaskSchedule, TaskStatus, TaskType, WorkflowRequest,
+    WorkflowStore,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::orchestrator::{
+    create_durable_orchestrator, open_workflow_store, parse_workflow_config, print_recovery_report,
+    print_workflow_result,
+};
+use super::run::TerminalInteraction;
+
+#[derive(Debug, Args)]
+pub struct ScheduleCommand {
+    #[command(subcommand)]
+    pub action: ScheduleAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScheduleAction {
+    /// Schedule a recurring workflow
+    Add {
+        /// Unique job name
+        name: String,
+
+        /// Cron expression: 5 or 6 fields or @hourly/@daily/@weekly/@monthly
+        #[arg(long)]
+        cron: String,
+
+        /// What the workflow should do
+        #[arg(short, long)]
+        intent: String,
+
+        /// Time zone: UTC (default), local or an IANA name like Europe/Berlin
+        #[arg(long)]
+        tz: Option<String>,
+
+        /// Random delay of up to N seconds added to every run
+        #[arg(long, default_value_t = 0)]
+        jitter: u64,
+
+        /// Runs missed while no scheduler was running: skip or catch-up
+        #[arg(long, default_value = "skip")]
+        missed: MissedRunPolicy,
+
+        /// Attempts per occurrence before waiting for the next one
+        #[arg(long, default_value_t = 3)]
+        max_retries: u32,
+
+        /// Optional workflow configuration (JSON or path to a JSON file)
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+
+    /// List scheduled jobs
+    List,
+
+    /// Remove a scheduled job
+    Rm {
+        /// Job name or ID (prefix)
+        job: String,
+    },
+
+    /// Run a scheduled job immediately
+    RunNow {
+        /// Job name or ID (prefix)
+        job: String,
+    },
+
+    /// Run due jobs in the foreground until interrupted
+    Daemon {
+        /// Run the jobs that are due now and exit (for system cron/timers)
+        #[arg(long)]
+        once: bool,
+
+        /// How often to check for due jobs, in seconds
+        #[arg(long, default_value_t = 30)]
+        poll_secs: u64,
+    },
+}
+
+fn find_job(store: &WorkflowStore, job: &str) -> Result<ScheduledTask> {
+    store
+        .find_scheduled_task(job)?
+        .ok_or_else(|| anyhow!("Scheduled job not found: {job}"))
+}
+
+fn format_time(at: Option<DateTime<Utc>>) -> String {
+    at.map(|at| {
+        at.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    })
+    .unwrap_or_else(|| "-".to_string())
+}
+
+fn describe_schedule(schedule: &TaskSchedule) -> String {
+    match schedule {
+        TaskSchedule::Cron {
+            expression,
+            timezone,
+        } => match timezone {
+            Some(tz) => format!("{expression} ({tz})"),
+            None => expression.clone(),
+        },
+        TaskSchedule::Interval { every } => format!("every {}s", every.as_secs()),
+        TaskSchedule::Once { at } => format!("once at {}", format_time(Some(*at))),
+        TaskSchedule::Immediate => "immediately".to_string(),
+    }
+}
+
+fn job_status_icon(task: &ScheduledTask) -> &'static str {
+    match task.status {
+        TaskStatus::Scheduled if task.retry_count > 0 => "🔁",
+        TaskStatus::Scheduled => "🕒",
+        TaskStatus::Running => "🔄",
+        TaskStatus::Completed => "✅",
+        TaskStatus::Failed => "❌",
+        TaskStatus::Pending | TaskStatus::Cancelled | TaskStatus::Paused => "⏹️",
+    }
+}
+
+/// Run a job's workflow and record the outcome (next run, retries, last status)
+async fn run_job(
+    orchestrator: &AgentOrchestrator,
+    store: &WorkflowStore,
+    task: &mut ScheduledTask,
+) -> Result<()> {
+    let payload: ScheduledPayload = serde_json::from_value(task.payload.clone())
+        .with_context(|| format!("Job '{}' has a corrupt payload", task.name))?;
+    let ScheduledPayload::Workflow(request) = payload else {
+        anyhow::bail!(
+            "Job '{}' runs an action plan; only workflow jobs can be run from the CLI",
+            task.name
+        );
+    };
+
+    // Claim the row so that a second scheduler skips the job while it runs
+    let expected = task.status.clone();
+    if !store.claim_scheduled_task(task, &expected)? {
+        println!(
+            "⏭️  Job '{}' is already running or was changed elsewhere, skipped",
+            task.name
+        );
+        return Ok(());
+    }
+
+    println!("\n▶️  Running job '{}': {}", task.name, request.user_input);
+    let started_at = Utc::now();
+    let outcome = match orchestrator.execute_workflow(request).await {
+        Ok(result) => {
+            print_workflow_result(&result);
+            task.metadata.insert(
+                "last_workflow_id".to_string(),
+                serde_json::json!(result.workflow_id.to_string()),
+            );
+            if result.success {
+                Ok(())
+            } else {
+                Err(result
+                    .error
+                    .unwrap_or_else(|| "workflow failed".to_string()))
+            }
+        }
+        Err(e) => {
+            println!("❌ Job '{}' failed: {}", task.name, e);
+            Err(e.to_string())
+        }
+    };
+
+    task.finish_run(started_at, outcome)?;
+    if !store.finish_scheduled_task(task)? {
+        println!("🗑️  Job '{}' was removed while it ran", task.name);
+        return Ok(());
+    }
+    println!("🕒 Next run: {}", format_time(task.next_run_at));
+    Ok(())
+}
+
+impl ScheduleCommand {
+    pub async fn execute(&self) -> Result<()> {
+        match &self.action {
+            ScheduleAction::Add {
+                name,
+                cron,
+                intent,
+                tz,
+                jitter,
+                missed,
+                max_retries,
+                config,
+            } => {
+                self.add_job(
+                    name,
+                    cron,
+                    intent,
+                    tz.as_deref(),
+                    *jitter,
+                    *missed,
+                    *max_retries,
+                    config.as_deref(),
+                )
+                .await
+            }
+            ScheduleAction::List => self.list_jobs().await,
+            ScheduleAction::Rm { job } => self.remove_job(job).await,
+            ScheduleAction::RunNow { job } => self.run_now(job).await,
+            ScheduleAction::Daemon { once, poll_secs } => {
+                self.run_daemon(*once, Duration::from_secs((*poll_secs).max(1)))
+                    .await
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn add_job(
+        &self,
+        name: &str,
+        cron: &str,
+        intent: &str,
+        tz: Option<&str>,
+        jitter: u64,
+        missed: MissedRunPolicy,
+        max_retries: u32,
+        config: Option<&str>,
+    ) -> Result<()> {
+        let store = open_workflow_store()?;
+        if store
+            .scheduled_tasks()?
+            .iter()
+            .any(|task| task.name == name)
+        {
+            anyhow::bail!("A job named '{name}' already exists; remove it first");
+        }
+
+        let mut expression = CronExpression::parse(cron)
+            .with_context(|| format!("Invalid cron expression '{cron}'"))?;
+        if let Some(tz) = tz {
+            expression = expression.in_time_zone(CronTimeZone::parse(tz)?);
+        }
+        let config_overrides = config.map(parse_workflow_config).transpose()?;
+
+        let now = Utc::now();
+        let mut task = ScheduledTask {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            task_type: TaskType::Cron,
+            schedule: TaskSchedule::Cron {
+                expression: cron.to_string(),
+                timezone: tz.map(str::to_string),
+            },
+            payload: serde_json::to_value(ScheduledPayload::Workflow(WorkflowRequest {
+                user_input: intent.to_string(),
+                context: Some(serde_json::json!({ "scheduled_job": name })),
+                priority: TaskPriority::Normal,
+                dry_run: false,
+                timeout_ms: None,
+                config_overrides,
+                budget: None,
+            }))?,
+            status: TaskStatus::Scheduled,
+            created_at: now,
+            next_run_at: None,
+            last_run_at: None,
+            retry_count: 0,
+            max_retries: max_retries.max(1),
+            metadata: HashMap::new(),
+            jitter_secs: jitter,
+            missed_runs: missed,
+        };
+        task.next_run_at = task.initial_run_at(now)?;
+        if task.next_run_at.is_none() {
+            anyhow::bail!("Cron expression '{cron}' never fires");
+        }
+        store.save_scheduled_task(&task)?;
+
+        println!("✅ Scheduled job '{}' ({})", task.name, task.id);
+        println!("📝 Intent: {intent}");
+        println!(
+            "🕒 {} [{}], missed runs: {}",
+            cron,
+            expression.time_zone(),
+            missed
+        );
+        println!("📅 Next runs:");
+        for at in expression.upcoming(now, 3) {
+            println!("   • {}", format_time(Some(at)));
+        }
+        if jitter > 0 {
+            println!("   (plus up to {jitter}s of random delay)");
+        }
+        println!("\nJobs run while `magray schedule daemon` is active");
+        Ok(())
+    }
+
+    async fn list_jobs(&self) -> Result<()> {
+        let store = open_workflow_store()?;
+        let tasks = store.scheduled_tasks()?;
+        if tasks.is_empty() {
+            println!("No scheduled jobs. Add one with `magray schedule add`");
+            return Ok(());
+        }
+
+        println!("📅 Scheduled jobs");
+        println!("================");
+        for task in &tasks {
+            println!(
+                "{} {} ({})",
+                job_status_icon(task),
+                task.name,
+                &task.id.to_string()[..8]
+            );
+            println!("   Schedule: {}", describe_schedule(&task.schedule));
+            if let Ok(ScheduledPayload::Workflow(request)) =
+                serde_json::from_value::<ScheduledPayload>(task.payload.clone())
+            {
+                println!("   Intent:   {}", request.user_input);
+            }
+            println!(
+                "   Next: {}   Last: {}",
+                format_time(task.next_run_at),
+                format_time(task.last_run_at)
+            );
+            if let Some(error) = task.metadata.get("last_error").and_then(|e| e.as_str()) {
+                println!("   Last error: {error}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_job(&self, job: &str) -> Result<()> {
+        let store = open_workflow_store()?;
+        let task = find_job(&store, job)?;
+        store.delete_scheduled_task(task.id)?;
+        println!("🗑️  Removed job '{}' ({})", task.name, task.id);
+        Ok(())
+    }
+
+    async fn run_now(&self, job: &str) -> Result<()> {
+        let store = open_workflow_store()?;
+        let mut task = find_job(&store, job)?;
+
+        let (orchestrator, report) = create_durable_orchestrator(
+            store.clone(),
+            Some(Arc::new(TerminalInteraction { dry_run: false })),
+        )
+        .await?;
+        print_recovery_report(&report);
+
+        let result = run_job(&orchestrator, &store, &mut task).await;
+        if let Err(e) = orchestrator.shutdown().await {
+            warn!("Orchestrator shutdown failed: {}", e);
+        }
+        result
+    }
+
+    async fn run_daemon(&self, once: bool, poll_interval: Duration) -> Result<()> {
+        let store = open_workflow_store()?;
+
+        // Jobs claimed by a scheduler that died mid-run become due again
+        for task in store.release_stale_claims()? {
+            println!(
+                "↩️  Released job '{}' left running by a dead scheduler",
+                task.name
+            );
+        }
+
+        // Apply missed-run policies for the time no scheduler was running
+        let now = Utc::now();
+        for mut task in store.scheduled_tasks()? {
+            if task.apply_missed_run_policy(now)? {
+                println!(
+                    "⏭️  Skipped missed run of '{}', next run: {}",
+                    task.name,
+                    format_time(task.next_run_at)
+                );
+                store.save_scheduled_task(&task)?;
+            }
+        }
+
+        // Unattended: interaction steps fail instead of waiting on stdin
+        let (orchestrator, report) = create_durable_orchestrator(store.clone(), None).await?;
+        print_recovery_report(&report);
+        if !once {
+            println!(
+                "📅 Scheduler running (checking every {}s, Ctrl+C to stop)",
+                poll_interval.as_secs()
+            );
+        }
+
+        loop {
+            // Re-read the store so jobs added or removed meanwhile are picked up
+            let now = Utc::now();
+            for mut task in store.scheduled_tasks()? {
+                if task.is_due(now) {
+                    if let Err(e) = run_job(&orchestrator, &store, &mut task).await {
+                        println!("❌ {e:#}");
+                    }
+                }
+            }
+
+            if once {
+                break;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(poll_interval) => {}
+                _ = tokio::signal::ctrl_c() => {
+                    println!("\n👋 Scheduler stopped");
+                    break;
+                }
+            }
+        }
+
+        if let Err(e) = orchestrator.shutdown().await {
+            warn!("Orchestrator shutdown failed: {}", e);
+        }
+        Ok(())
+    }
+}
@@ -1,111 +1,30 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use clap::Args;
 use colored::*;
-use domain::orchestrator::{Executor, Goal, Orchestrator, Plan, Planner, StepResult, StepStatus};
+use infrastructure::config::ConfigLoader;
+use llm::{ChatMessage, LlmClient, LlmUsageObserver};
+use orchestrator::agents::delegation::summarize_report;
+use orchestrator::agents::intent_analyzer::{Intent, IntentContext, IntentType};
+use orchestrator::agents::planner::{ActionPlan, ActionStepType};
+use orchestrator::agents::{register_registry_tools, DELEGATE_PARAM};
+use orchestrator::{
+    ActorSystemManager, AgentCommunicationConfig, AgentLoop, AgentLoopConfig, AgentLoopReport,
+    AgentProfile, AgentProfiles, Critic, Delegator, Executor, InteractionHandler, LoopOutcome,
+    Planner, PlannerTrait, ReviewDecision, SpendApprover, SpendBudget, SpendTracker,
+    SubAgentFactory, SystemConfig,
+};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use todo::{create_default_service, Priority, TaskState, TodoService};
-use tools::{ToolInput, ToolOutput, ToolRegistry};
+use std::str::FromStr;
+use std::sync::Arc;
+use todo::{agent_actor, create_default_service, Priority, TaskState, TodoService};
+use tools::invocation::ToolGate;
+use tools::ToolRegistry;
 
-#[derive(Debug)]
-struct SimplePlanner;
-
-#[async_trait]
-impl Planner for SimplePlanner {
-    async fn create_plan(&self, goal: &Goal) -> Result<Plan> {
-        let hint = detect_tool_hint(&goal.title);
-        Ok(Plan {
-            steps: vec![domain::orchestrator::PlanStep {
-                id: "step-1".into(),
-                description: goal.title.clone(),
-                tool_hint: hint,
-                deps: vec![],
-            }],
-        })
-    }
-}
-
-#[derive(Debug)]
-struct SimpleExecutor;
-
-#[async_trait]
-impl Executor for SimpleExecutor {
-    async fn execute(&self, plan: &Plan) -> Result<Vec<StepResult>> {
-        let registry = ToolRegistry::new();
-        let mut results = Vec::with_capacity(plan.steps.len());
-
-        // Инициализируем TodoService (локальная база в ~/.magray/tasks.db)
-        let db_path = default_tasks_db_path();
-        let todo_service: TodoService = create_default_service(&db_path).await?;
-
-        for step in &plan.steps {
-            // Создаём задачу под шаг
-            let task = todo_service
-                .create_task(
-                    step.description.clone(),
-                    "Создано оркестратором MAGRAY".to_string(),
-                    Priority::Medium,
-                    vec!["smart".to_string()],
-                )
-                .await?;
-
-            // Выполняем шаг как инструмент
-            let desc = step.description.clone();
-            let output = execute_step(&registry, &desc, step.tool_hint.clone()).await;
-
-            // Обновляем состояние задачи и сохраняем артефакт/метаданные
-            match output {
-                Ok(out) => {
-                    // Сохраняем короткий артефакт вывода (до 64KB) в ~/.magray/artifacts/<task-id>.txt
-                    if !out.result.trim().is_empty() {
-                        if let Ok(path) = save_text_artifact(&task.id, &out.result) {
-                            let mut meta = std::collections::HashMap::new();
-                            meta.insert(
-                                "artifact_path".to_string(),
-                                serde_json::json!(path.to_string_lossy()),
-                            );
-                            meta.insert(
-                                "tool".to_string(),
-                                serde_json::json!(step
-                                    .tool_hint
-                                    .clone()
-                                    .unwrap_or_else(|| "auto".to_string())),
-                            );
-                            todo_service.upsert_metadata(&task.id, meta).await.ok();
-                        }
-                    }
-                    todo_service.update_state(&task.id, TaskState::Done).await?;
-                    results.push(StepResult {
-                        step_id: step.id.clone(),
-                        status: StepStatus::Succeeded,
-                        output: Some(out.result.clone()),
-                        artifacts: vec![],
-                    })
-                }
-                Err(e) => {
-                    let mut meta = std::collections::HashMap::new();
-                    meta.insert("error".to_string(), serde_json::json!(e.to_string()));
-                    todo_service.upsert_metadata(&task.id, meta).await.ok();
-                    todo_service
-                        .update_state(&task.id, TaskState::Failed)
-                        .await?;
-                    results.push(StepResult {
-                        step_id: step.id.clone(),
-                        status: StepStatus::Failed {
-                            error: e.to_string(),
-                        },
-                        output: None,
-                        artifacts: vec![],
-                    })
-                }
-            }
-        }
-
-        Ok(results)
-    }
-}
+use super::run::TerminalInteraction;
 
 fn default_tasks_db_path() -> PathBuf {
     let mut dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -132,74 +51,543 @@ fn save_text_artifact(task_id: &uuid::Uuid, text: &str) -> Result<PathBuf> {
     Ok(path)
 }
 
-struct SimpleOrchestrator<P: Planner, E: Executor> {
-    planner: P,
-    executor: E,
-}
-impl<P: Planner, E: Executor> SimpleOrchestrator<P, E> {
-    fn new(planner: P, executor: E) -> Self {
-        Self { planner, executor }
-    }
-}
-
-#[async_trait]
-impl<P: Planner + Send + Sync, E: Executor + Send + Sync> Orchestrator
-    for SimpleOrchestrator<P, E>
-{
-    async fn plan(&self, goal: Goal) -> Result<Plan> {
-        self.planner.create_plan(&goal).await
-    }
-    async fn run(&self, plan: Plan) -> Result<Vec<StepResult>> {
-        self.executor.execute(&plan).await
-    }
-}
-
 #[derive(Debug, Args)]
 pub struct SmartCommand {
     /// Сложная задача на естественном языке
     pub task: String,
+
+    /// Максимум циклов план → действие → наблюдение → перепланирование
+    #[arg(long, default_value_t = 5)]
+    pub max_iterations: u32,
+
+    /// Лимиты расхода, напр. `tokens=20000/50000,cost=1.5,tools=/10,time=10m` (SOFT/HARD)
+    #[arg(long, value_parser = SpendBudget::from_str)]
+    pub budget: Option<SpendBudget>,
+
+    /// Предпросмотр: инструменты запускаются в dry-run режиме
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Работать во временном git worktree на отдельной ветке; в конце diff
+    /// показывается для merge, cherry-pick или discard
+    #[arg(long)]
+    pub isolate: bool,
+
+    /// Команда тестов в worktree (по умолчанию определяется по проекту)
+    #[arg(long, requires = "isolate")]
+    pub test_cmd: Option<String>,
+
+    /// Решение по ветке без TUI: merge, cherry-pick, discard или keep
+    #[arg(long, requires = "isolate", value_parser = ReviewDecision::from_str)]
+    pub on_finish: Option<ReviewDecision>,
 }
 
 impl SmartCommand {
     pub async fn execute(self) -> Result<()> {
-        run_smart(self.task).await
+        run_smart(self).await
     }
 }
 
-async fn run_smart(task: String) -> Result<()> {
+async fn run_smart(cmd: SmartCommand) -> Result<()> {
     println!("{} {}", "★".yellow(), "Smart планировщик".bold());
 
-    let orchestrator = SimpleOrchestrator::new(SimplePlanner, SimpleExecutor);
-
-    let goal = Goal {
-        title: task.clone(),
-        description: task,
-        constraints: vec![],
+    // Инструменты идут через тот же policy/approval gate, что и `magray tools run`
+    let registry = Arc::new(super::tools::load_tool_registry().await);
+    let (home_policy, project_root) = super::policy::policy_sources()?;
+    // Политика берётся из рабочей копии, а рабочая папка и песочница gate — из worktree
+    let isolation = if cmd.isolate {
+        Some(super::isolate::start(&cmd.task)?)
+    } else {
+        None
     };
+    let gate = ToolGate::for_project(home_policy.as_deref(), &project_root);
+    let gate = Arc::new(match &isolation {
+        Some(isolation) => isolation.scope_gate(gate),
+        None => gate,
+    });
+    let outcome = run_loop(&cmd, registry, gate, isolation.as_ref().map(|i| i.branch())).await;
+    match isolation {
+        Some(isolation) => {
+            isolation.finish(&cmd.task, outcome, cmd.test_cmd.as_deref(), cmd.on_finish)
+        }
+        None => outcome,
+    }
+}
+
+/// Цикл агента над задачей: план, выполнение, отчёт и задача в todo
+async fn run_loop(
+    cmd: &SmartCommand,
+    registry: Arc<ToolRegistry>,
+    gate: Arc<ToolGate>,
+    isolated_branch: Option<&str>,
+) -> Result<()> {
+    let mut executor = Executor::new();
+    register_registry_tools(
+        &mut executor,
+        Arc::clone(&registry),
+        Arc::clone(&gate),
+        cmd.dry_run,
+    );
+    let terminal = Arc::new(TerminalInteraction {
+        dry_run: cmd.dry_run,
+    });
+    executor.set_interaction_handler(terminal.clone());
+    let spend = cmd.budget.filter(|b| !b.is_unlimited()).map(|budget| {
+        Arc::new(
+            SpendTracker::new(budget).with_approver(terminal.clone() as Arc<dyn SpendApprover>),
+        )
+    });
+    if let Some(spend) = &spend {
+        executor.set_spend_tracker(Arc::clone(spend));
+    }
+
+    // Профили `[agents.*]` из конфига: задачи им выполняют отдельные суб-агенты
+    let profiles = load_agent_profiles().await?;
+    if !profiles.is_empty() {
+        let factory = ProfileAgentFactory {
+            registry: Arc::clone(&registry),
+            gate: Arc::clone(&gate),
+            interaction: terminal.clone(),
+            dry_run: cmd.dry_run,
+        };
+        executor.set_delegation_handler(Arc::new(
+            profile_delegator(profiles.clone(), factory).await?,
+        ));
+    }
 
-    let plan = orchestrator.plan(goal).await?;
+    let mut planner = Planner::new();
+    for tool in executor.get_available_tools() {
+        planner.register_available_tool(&tool);
+    }
+    for profile in profiles.names() {
+        planner.register_agent_profile(&profile);
+    }
+    let plan = match split_delegation(&cmd.task, &profiles) {
+        Some((profile, task)) => delegate_task(&planner, profile, task).await?,
+        None => plan_task(&planner, &registry, &cmd.task).await?,
+    };
     println!("{} План: {} шаг(ов)", "→".cyan(), plan.steps.len());
     for step in &plan.steps {
-        let hint = step.tool_hint.clone().unwrap_or_else(|| "auto".to_string());
-        println!("  - {} {}  [{}]", step.id.bold(), step.description, hint);
+        match &step.step_type {
+            ActionStepType::ToolExecution {
+                tool_name,
+                arguments,
+            } => {
+                let args: Vec<String> = arguments.iter().map(|(k, v)| format!("{k}={v}")).collect();
+                println!("  - {}  {}", tool_name.bold(), args.join(" "));
+            }
+            ActionStepType::Delegate { profile, task } => {
+                println!("  - {}  {}", format!("@{profile}").bold(), task);
+            }
+            _ => {}
+        }
+    }
+    if cmd.dry_run {
+        println!("🔍 Dry run: инструменты только в режиме предпросмотра");
     }
 
-    let results = orchestrator.run(plan).await?;
-    println!(
-        "{} Выполнение завершено: {} шаг(ов)",
-        "✓".green(),
-        results.len()
+    // Задача в локальной базе ~/.magray/tasks.db
+    let todo_service: TodoService = create_default_service(&default_tasks_db_path()).await?;
+    let task = todo_service
+        .create_task(
+            cmd.task.clone(),
+            "Создано оркестратором MAGRAY".to_string(),
+            Priority::Medium,
+            vec!["smart".to_string()],
+        )
+        .await?;
+
+    let mut agent_loop = AgentLoop::new(
+        Arc::new(planner),
+        Arc::new(executor),
+        Arc::new(Critic::new()),
+    )
+    .with_config(AgentLoopConfig {
+        max_iterations: cmd.max_iterations,
+        ..AgentLoopConfig::default()
+    });
+    if let Some(spend) = &spend {
+        agent_loop = agent_loop.with_spend_tracker(Arc::clone(spend));
+    }
+    let report = agent_loop.run(plan).await?;
+    print_report(&report);
+    if let Some(spend) = &spend {
+        println!("💰 {}", spend.report().summary());
+    }
+
+    let mut meta = HashMap::new();
+    meta.insert(
+        "iterations".to_string(),
+        serde_json::json!(report.iterations.len()),
+    );
+    meta.insert(
+        "outcome".to_string(),
+        serde_json::json!(report.outcome.to_string()),
     );
-    for r in results {
-        println!("  • {}: {:?}", r.step_id.bold(), r.status);
-        if let Some(out) = r.output {
-            if !out.trim().is_empty() {
-                println!("{out}");
+    if let Some(branch) = isolated_branch {
+        meta.insert("isolated_branch".to_string(), serde_json::json!(branch));
+    }
+    // Короткий артефакт последнего вывода (до 64KB) в ~/.magray/artifacts/<task-id>.txt
+    if let Some(output) = last_output(&report) {
+        if let Ok(path) = save_text_artifact(&task.id, &output) {
+            meta.insert(
+                "artifact_path".to_string(),
+                serde_json::json!(path.to_string_lossy()),
+            );
+        }
+    }
+    todo_service.upsert_metadata(&task.id, meta).await.ok();
+
+    if report.succeeded() {
+        todo_service
+            .update_state_as(&task.id, TaskState::Done, &agent_actor("smart"), None)
+            .await?;
+        println!("{} Цель достигнута", "✓".green());
+        Ok(())
+    } else {
+        todo_service
+            .update_state_as(
+                &task.id,
+                TaskState::Failed,
+                &agent_actor("smart"),
+                Some(&report.outcome.to_string()),
+            )
+            .await?;
+        println!("{} Цель не достигнута: {}", "✗".red(), report.outcome);
+        Err(anyhow!("Smart задача не выполнена: {}", report.outcome))
+    }
+}
+
+/// Один шаг с инструментом, подобранным по тексту задачи; дальше план
+/// уточняется в цикле агента
+pub(super) async fn plan_task(
+    planner: &Planner,
+    registry: &ToolRegistry,
+    task: &str,
+) -> Result<ActionPlan> {
+    let (tool_name, arguments) = select_tool(registry, task).await?;
+    plan_tool(planner, &tool_name, arguments, task).await
+}
+
+/// План вызова `tool_name` с заданными аргументами
+pub(super) async fn plan_tool(
+    planner: &Planner,
+    tool_name: &str,
+    arguments: HashMap<String, Value>,
+    task: &str,
+) -> Result<ActionPlan> {
+    let intent = Intent {
+        id: uuid::Uuid::new_v4(),
+        intent_type: IntentType::ExecuteTool {
+            tool_name: tool_name.to_string(),
+        },
+        parameters: HashMap::new(),
+        confidence: 0.8,
+        context: IntentContext {
+            session_id: uuid::Uuid::new_v4(),
+            user_id: None,
+            timestamp: chrono::Utc::now(),
+            environment: HashMap::new(),
+            conversation_history: vec![task.to_string()],
+        },
+    };
+    let mut plan = planner.build_plan(&intent).await?;
+    for step in &mut plan.steps {
+        if let ActionStepType::ToolExecution {
+            tool_name: step_tool,
+            arguments: step_args,
+        } = &mut step.step_type
+        {
+            if *step_tool == tool_name {
+                *step_args = arguments.clone();
+            }
+        }
+    }
+    Ok(plan)
+}
+
+/// `@профиль задача` → (профиль, задача), если такой профиль описан в конфиге
+fn split_delegation<'a>(text: &'a str, profiles: &AgentProfiles) -> Option<(&'a str, &'a str)> {
+    let rest = text.trim_start().strip_prefix('@')?;
+    let (profile, task) = rest.split_once(char::is_whitespace)?;
+    profiles
+        .get(profile)
+        .map(|_| (profile, task.trim()))
+        .filter(|(_, task)| !task.is_empty())
+}
+
+/// План из одного шага, передающего задачу суб-агенту профиля
+async fn delegate_task(planner: &Planner, profile: &str, task: &str) -> Result<ActionPlan> {
+    let intent = Intent {
+        id: uuid::Uuid::new_v4(),
+        intent_type: IntentType::Unknown {
+            raw_input: task.to_string(),
+        },
+        parameters: HashMap::from([
+            (
+                DELEGATE_PARAM.to_string(),
+                Value::String(profile.to_string()),
+            ),
+            ("task".to_string(), Value::String(task.to_string())),
+        ]),
+        confidence: 1.0,
+        context: IntentContext {
+            session_id: uuid::Uuid::new_v4(),
+            user_id: None,
+            timestamp: chrono::Utc::now(),
+            environment: HashMap::new(),
+            conversation_history: vec![task.to_string()],
+        },
+    };
+    planner.build_plan(&intent).await
+}
+
+/// Профили агентов из секций `[agents.<name>]` конфига
+pub(super) async fn load_agent_profiles() -> Result<AgentProfiles> {
+    let config = ConfigLoader::new().load().await?;
+    config
+        .agents
+        .into_iter()
+        .map(|(name, agent)| {
+            let mut profile = AgentProfile::new(name.clone())
+                .with_system_prompt(agent.system_prompt)
+                .with_allowed_tools(agent.allowed_tools);
+            profile.description = agent.description;
+            profile.model = agent.model;
+            profile.memory_project = agent.memory_project;
+            if let Some(budget) = agent.budget {
+                profile.budget = SpendBudget::from_str(&budget)
+                    .map_err(|e| anyhow!(e))
+                    .with_context(|| format!("agents.{name}.budget"))?;
+            }
+            if let Some(max_iterations) = agent.max_iterations {
+                profile.max_iterations = max_iterations;
+            }
+            Ok(profile)
+        })
+        .collect()
+}
+
+/// Delegator, запускающий суб-агентов под супервизором отдельной actor-системы
+pub(super) async fn profile_delegator(
+    profiles: AgentProfiles,
+    factory: ProfileAgentFactory,
+) -> Result<Delegator> {
+    let system =
+        ActorSystemManager::new(SystemConfig::default(), AgentCommunicationConfig::default())
+            .await?;
+    Ok(Delegator::new(
+        Arc::new(system),
+        profiles,
+        Arc::new(factory),
+    ))
+}
+
+/// Суб-агенты с инструментами реестра (через тот же policy gate), памятью
+/// проекта профиля и LLM-сводкой результата
+pub(super) struct ProfileAgentFactory {
+    pub(super) registry: Arc<ToolRegistry>,
+    pub(super) gate: Arc<ToolGate>,
+    pub(super) interaction: Arc<dyn InteractionHandler>,
+    pub(super) dry_run: bool,
+}
+
+#[async_trait]
+impl SubAgentFactory for ProfileAgentFactory {
+    async fn executor(&self, profile: &AgentProfile) -> Result<Executor> {
+        let mut executor = Executor::new();
+        register_registry_tools(
+            &mut executor,
+            Arc::clone(&self.registry),
+            Arc::clone(&self.gate),
+            self.dry_run,
+        );
+        executor.set_interaction_handler(Arc::clone(&self.interaction));
+        // Память профиля изолирована его проектом
+        #[cfg(not(feature = "minimal"))]
+        {
+            use memory::api::{MemoryServiceTrait, UnifiedMemoryAPI};
+            use orchestrator::agents::MemoryApiInvoker;
+
+            if let Some(project) = &profile.memory_project {
+                let api = Arc::new(UnifiedMemoryAPI::new(Arc::new(
+                    memory::di::UnifiedContainer::new(),
+                )
+                    as Arc<dyn MemoryServiceTrait>));
+                executor.set_memory_invoker(Arc::new(
+                    MemoryApiInvoker::new(api, Arc::clone(&self.gate))
+                        .with_dry_run(self.dry_run)
+                        .with_project(project.clone()),
+                ));
+            }
+        }
+        #[cfg(feature = "minimal")]
+        let _ = profile;
+        Ok(executor)
+    }
+
+    async fn plan(
+        &self,
+        _profile: &AgentProfile,
+        task: &str,
+        planner: &Planner,
+    ) -> Result<ActionPlan> {
+        plan_task(planner, &self.registry, task).await
+    }
+
+    async fn summarize(
+        &self,
+        profile: &AgentProfile,
+        task: &str,
+        report: &AgentLoopReport,
+        spend: &Arc<SpendTracker>,
+    ) -> Result<String> {
+        let facts = summarize_report(task, report);
+        if profile.system_prompt.is_empty() && profile.model.is_none() {
+            return Ok(facts);
+        }
+        let mut client = LlmClient::from_env()?;
+        if let Some(model) = &profile.model {
+            client = client.with_model(model.clone());
+        }
+        let client = client.with_usage_observer(Arc::clone(spend) as Arc<dyn LlmUsageObserver>);
+        client
+            .chat(&[
+                ChatMessage::system(&profile.system_prompt),
+                ChatMessage::user(&format!(
+                    "Summarize the outcome of this task for the agent that delegated it. \
+                     Be brief and concrete.\n\n{facts}"
+                )),
+            ])
+            .await
+    }
+}
+
+/// Имя инструмента и его аргументы для задачи
+async fn select_tool(
+    registry: &ToolRegistry,
+    task: &str,
+) -> Result<(String, HashMap<String, Value>)> {
+    if let Some(command) = test_command(task) {
+        let mut args = HashMap::new();
+        args.insert("command".to_string(), Value::String(command));
+        return Ok(("shell_exec".to_string(), args));
+    }
+
+    let to_json = |args: HashMap<String, String>| -> HashMap<String, Value> {
+        args.into_iter()
+            .map(|(k, v)| (k, Value::String(v)))
+            .collect()
+    };
+    if let Some(name) = detect_tool_hint(task) {
+        let tool = registry
+            .get(&name)
+            .ok_or_else(|| anyhow!("Неизвестный инструмент: {}", name))?;
+        let mut args = HashMap::new();
+        if tool.supports_natural_language() {
+            if let Ok(input) = tool.parse_natural_language(task).await {
+                args = to_json(input.args);
             }
         }
+        return Ok((name, args));
+    }
+
+    // Автовыбор: пробуем набор инструментов в приоритетном порядке
+    let candidates = [
+        "file_read",
+        "file_write",
+        "dir_list",
+        "git_status",
+        "git_diff",
+        "git_commit",
+        "shell_exec",
+        "web_search",
+    ];
+    for name in candidates {
+        if let Some(tool) = registry.get(name) {
+            if !tool.supports_natural_language() {
+                continue;
+            }
+            if let Ok(input) = tool.parse_natural_language(task).await {
+                // Если парсер выдал какие‑то аргументы — берём этот инструмент
+                if !input.args.is_empty() {
+                    return Ok((name.to_string(), to_json(input.args)));
+                }
+            }
+        }
+    }
+
+    Err(anyhow!("Не удалось подобрать инструмент для шага"))
+}
+
+/// Команда запуска тестов для задач вроде "fix failing tests"
+/// (`MAGRAY_TEST_COMMAND`, по умолчанию `cargo test`)
+fn test_command(text: &str) -> Option<String> {
+    let lower = text.to_lowercase();
+    if !(lower.contains("test") || lower.contains("тест")) {
+        return None;
+    }
+    Some(std::env::var("MAGRAY_TEST_COMMAND").unwrap_or_else(|_| "cargo test".to_string()))
+}
+
+fn print_report(report: &AgentLoopReport) {
+    for iteration in &report.iterations {
+        println!(
+            "{} Итерация {}: шагов {}, выполнено {}, ошибок {}, оценка {:.2}",
+            "↻".cyan(),
+            iteration.number,
+            iteration.steps_planned,
+            iteration.completed,
+            iteration.failed,
+            iteration.score
+        );
+    }
+    for observation in &report.observations {
+        let tool = observation.tool.as_deref().unwrap_or("step");
+        match &observation.error {
+            Some(error) => println!(
+                "  • {} (попытка {}): {:?} — {}",
+                tool.bold(),
+                observation.attempt,
+                observation.status,
+                error
+            ),
+            None => println!(
+                "  • {} (попытка {}): {:?}",
+                tool.bold(),
+                observation.attempt,
+                observation.status
+            ),
+        }
     }
+    if let Some(feedback) = &report.feedback {
+        for suggestion in feedback.improvement_suggestions.iter().take(3) {
+            println!("  💡 {}", suggestion.description);
+        }
+    }
+    let icon = match report.outcome {
+        LoopOutcome::GoalReached => "✓".green(),
+        _ => "■".yellow(),
+    };
+    println!(
+        "{} Цикл завершён после {} итерац.: {}",
+        icon,
+        report.iterations.len(),
+        report.outcome
+    );
+}
 
-    Ok(())
+/// Текстовый вывод последнего успешного шага
+fn last_output(report: &AgentLoopReport) -> Option<String> {
+    report
+        .result
+        .step_results
+        .iter()
+        .rev()
+        .filter_map(|result| result.output.as_ref())
+        .find_map(|output| output.get("result").and_then(Value::as_str))
+        .filter(|text| !text.trim().is_empty())
+        .map(str::to_string)
 }
 
 fn detect_tool_hint(text: &str) -> Option<String> {
@@ -249,70 +637,3 @@ fn looks_like_path(text: &str) -> bool {
         || text.contains(".md")
         || text.contains(".toml")
 }
-
-async fn execute_step(
-    registry: &ToolRegistry,
-    description: &str,
-    hint: Option<String>,
-) -> Result<ToolOutput> {
-    if let Some(name) = hint {
-        if let Some(tool) = registry.get(&name) {
-            // Попробуем использовать парсер NL, иначе отправим пустые args
-            let input = if tool.supports_natural_language() {
-                match tool.parse_natural_language(description).await {
-                    Ok(mut i) => {
-                        i.dry_run = false;
-                        i.timeout_ms = None;
-                        i
-                    }
-                    Err(_) => ToolInput {
-                        command: name.clone(),
-                        args: HashMap::new(),
-                        context: Some(description.to_string()),
-                        dry_run: false,
-                        timeout_ms: None,
-                    },
-                }
-            } else {
-                ToolInput {
-                    command: name.clone(),
-                    args: HashMap::new(),
-                    context: Some(description.to_string()),
-                    dry_run: false,
-                    timeout_ms: None,
-                }
-            };
-            return tool.execute(input).await;
-        } else {
-            return Err(anyhow!("Неизвестный инструмент: {}", name));
-        }
-    }
-
-    // Автовыбор: пробуем набор инструментов в приоритетном порядке
-    let candidates = [
-        "file_read",
-        "file_write",
-        "dir_list",
-        "git_status",
-        "git_diff",
-        "git_commit",
-        "shell_exec",
-        "web_search",
-    ];
-
-    for name in candidates {
-        if let Some(tool) = registry.get(name) {
-            if !tool.supports_natural_language() {
-                continue;
-            }
-            if let Ok(input) = tool.parse_natural_language(description).await {
-                // Если парсер выдал какие‑то аргументы — пробуем исполнить
-                if !input.args.is_empty() || input.context.is_some() {
-                    return tool.execute(input).await;
-                }
-            }
-        }
-    }
-
-    Err(anyhow!("Не удалось подобрать инструмент для шага"))
-}
@@ -1,7 +1,26 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
 use clap::{Args, Subcommand};
 use colored::*;
-use todo::{create_default_service, Priority, TaskState};
+use orchestrator::agents::executor::{ExecutionResult, ExecutionStatus};
+use orchestrator::agents::register_registry_tools;
+use orchestrator::{AgentLoop, Critic, Executor, ExecutorTrait, InteractionHandler, Planner};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use todo::{
+    build_plan_prompt, create_default_service, format_duration, load_templates, parse_date_bound,
+    render_highlight, scan_path, GoalPlan, HistoryEvent, HistoryMemory, PlanDiff, Priority,
+    Recurrence, RunnerConfig, Schedule, ScheduleConfig, TaskEmbedder, TaskEstimate, TaskFilter,
+    TaskHandler, TaskRunEvent, TaskRunStatus, TaskRunner, TaskState, TodoItem, ACTOR_USER,
+    NEXT_OCCURRENCE_KEY,
+};
+use tools::invocation::ToolGate;
+use tools::{Tool, ToolInput, ToolOutput, ToolRegistry, ToolSpec};
+
+use super::run::{read_answer, TerminalInteraction};
+use super::smart::{plan_task, plan_tool};
 
 #[derive(Debug, Args)]
 pub struct TasksCommand {
@@ -25,6 +44,32 @@ pub enum TasksSubcommand {
         /// Теги через запятую
         #[arg(short, long)]
         tags: Option<String>,
+        /// Повтор: daily, weekly, monthly или RRULE (FREQ=WEEKLY;BYDAY=MO)
+        #[arg(long)]
+        recur: Option<String>,
+    },
+    /// Создать граф задач по шаблону (например, `new --template release v1.4`)
+    #[command(name = "new")]
+    New {
+        /// Имя шаблона
+        #[arg(short, long)]
+        template: String,
+        /// Параметры шаблона по порядку или как name=value
+        params: Vec<String>,
+    },
+    /// Показать доступные шаблоны задач
+    #[command(name = "templates")]
+    Templates,
+    /// Задать правило повтора задачи
+    #[command(name = "recur")]
+    Recur {
+        id: String,
+        /// daily, weekly, monthly или RRULE (FREQ=MONTHLY;BYMONTHDAY=1)
+        #[arg(required_unless_present = "clear")]
+        rule: Option<String>,
+        /// Снять правило повтора
+        #[arg(long, conflicts_with = "rule")]
+        clear: bool,
     },
     /// Показать N готовых задач
     #[command(name = "list")]
@@ -37,13 +82,28 @@ pub enum TasksSubcommand {
     },
     /// Показать задачу по ID
     #[command(name = "show")]
-    Show { id: String },
+    Show {
+        id: String,
+        /// Показать историю: переходы, комментарии и время в состояниях
+        #[arg(long)]
+        history: bool,
+    },
     /// Отметить задачу выполненной
     #[command(name = "done")]
     Done { id: String },
     /// Удалить задачу (пока просто смена статуса на cancelled)
     #[command(name = "rm")]
     Rm { id: String },
+    /// Добавить комментарий к задаче
+    #[command(name = "comment")]
+    Comment {
+        id: String,
+        /// Текст комментария
+        text: String,
+        /// Автор (user или agent:<маршрут>)
+        #[arg(long, default_value = ACTOR_USER)]
+        author: String,
+    },
     /// Добавить зависимость между задачами
     #[command(name = "depends")]
     Depends {
@@ -61,10 +121,111 @@ pub enum TasksSubcommand {
         /// Максимальная глубина графа
         #[arg(long, default_value_t = 5)]
         depth: usize,
+        /// Выделить критический путь (цепочку, определяющую срок)
+        #[arg(long)]
+        critical: bool,
+    },
+    /// Задать оценку трудоёмкости задачи в часах (PERT)
+    #[command(name = "estimate")]
+    Estimate {
+        id: String,
+        /// Оптимистичная оценка
+        optimistic: f64,
+        /// Наиболее вероятная оценка
+        likely: f64,
+        /// Пессимистичная оценка
+        pessimistic: f64,
+    },
+    /// Найти TODO/FIXME-комментарии в коде и синхронизировать их с задачами
+    #[command(name = "scan")]
+    Scan {
+        /// Каталог или файл (учитывается .gitignore)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Только показать найденные комментарии
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Показать план-график: сроки, резервы и задачи под риском
+    #[command(name = "schedule")]
+    Schedule {
+        /// Формат вывода (text, gantt, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Начало работ (YYYY-MM-DD или RFC 3339), по умолчанию сейчас
+        #[arg(long)]
+        start: Option<String>,
+        /// Часов работы в день
+        #[arg(long, default_value_t = 8.0)]
+        hours_per_day: f64,
+        /// Оценка для задач без оценки, часы
+        #[arg(long, default_value_t = 4.0)]
+        default_hours: f64,
     },
     /// Показать статистику
     #[command(name = "stats")]
     Stats,
+    /// Выполнить готовые задачи: инструмент из tool_hint или оркестратор
+    #[command(name = "run")]
+    Run {
+        /// Сколько задач выполнять одновременно
+        #[arg(long, default_value_t = 1)]
+        parallel: usize,
+        /// Выполнить только эту задачу и её зависимости
+        #[arg(long)]
+        until: Option<String>,
+        /// Показать порядок выполнения, ничего не запуская
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Разбить цель на подзадачи с зависимостями через LLM
+    #[command(name = "plan")]
+    Plan {
+        /// Цель, которую нужно декомпозировать
+        goal: String,
+        /// Формат предпросмотра (text, mermaid)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Сохранить без подтверждения
+        #[arg(short, long)]
+        yes: bool,
+        /// Только показать план, ничего не сохраняя
+        #[arg(long)]
+        dry_run: bool,
+        /// Записать предложенный план в JSON-файл для правки
+        #[arg(long)]
+        save: Option<PathBuf>,
+        /// Взять план из JSON-файла вместо запроса к LLM
+        #[arg(long)]
+        from: Option<PathBuf>,
+    },
+    /// Найти задачи: полнотекстовый (FTS5) или семантический поиск
+    #[command(name = "search")]
+    Search {
+        /// Поисковый запрос (пустой — только фильтры)
+        #[arg(default_value = "")]
+        query: String,
+        /// Только задачи в этом состоянии
+        #[arg(long)]
+        state: Option<String>,
+        /// Минимальный приоритет (low, medium, high, critical)
+        #[arg(long)]
+        priority: Option<String>,
+        /// Только задачи с этим тегом
+        #[arg(long)]
+        tag: Option<String>,
+        /// Срок не раньше (YYYY-MM-DD или RFC 3339)
+        #[arg(long)]
+        due_after: Option<String>,
+        /// Срок не позже (YYYY-MM-DD или RFC 3339)
+        #[arg(long)]
+        due_before: Option<String>,
+        /// Ранжировать по эмбеддингам вместо полнотекстового поиска
+        #[arg(long)]
+        semantic: bool,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
 }
 
 impl TasksCommand {
@@ -83,7 +244,10 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
             description,
             priority,
             tags,
+            recur,
         } => {
+            // Правило проверяется до создания задачи
+            let recurrence = recur.as_deref().map(str::parse::<Recurrence>).transpose()?;
             let priority = match priority.to_lowercase().as_str() {
                 "low" => Priority::Low,
                 "medium" => Priority::Medium,
@@ -126,6 +290,70 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
             );
             println!("   приоритет: {:?}", task.priority);
             println!("   состояние: {:?}", task.state);
+            if let Some(rule) = recurrence {
+                println!("   повтор: {rule}");
+                svc.set_recurrence(&task.id, Some(rule)).await?;
+            }
+        }
+        TasksSubcommand::New { template, params } => {
+            let templates = load_templates(&crate::util::templates_dir())?;
+            let Some(template) = templates.iter().find(|t| t.name == template) else {
+                let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+                return Err(anyhow!(
+                    "Шаблон '{}' не найден. Доступны: {}",
+                    template,
+                    names.join(", ")
+                ));
+            };
+            let commit = svc.instantiate_template(template, &params).await?;
+            println!(
+                "{} Задачи по шаблону {} созданы: {}, обновлены: {}, отменены: {}",
+                "✓".green(),
+                template.name.bold(),
+                commit.created,
+                commit.updated,
+                commit.cancelled
+            );
+            println!("   цель: {}", commit.goal_id);
+        }
+        TasksSubcommand::Templates => {
+            let dir = crate::util::templates_dir();
+            for template in load_templates(&dir)? {
+                let params: Vec<String> = template
+                    .params
+                    .iter()
+                    .map(|p| match &p.default {
+                        Some(default) => format!("[{}={}]", p.name, default),
+                        None => format!("<{}>", p.name),
+                    })
+                    .collect();
+                println!(
+                    "{} {} {} — {} задач",
+                    "📋".blue(),
+                    template.name.bold(),
+                    params.join(" ").cyan(),
+                    template.tasks.len()
+                );
+                if !template.description.is_empty() {
+                    println!("   {}", template.description.dimmed());
+                }
+            }
+            println!(
+                "{} Свои шаблоны: {}",
+                "•".dimmed(),
+                dir.join("*.json").display()
+            );
+        }
+        TasksSubcommand::Recur { id, rule, .. } => {
+            // --clear и правило взаимоисключающие: без правила повтор снимается
+            let id = uuid::Uuid::parse_str(&id)?;
+            let rule = rule.as_deref().map(str::parse::<Recurrence>).transpose()?;
+            let message = match &rule {
+                Some(rule) => format!("Повтор задан: {rule}"),
+                None => "Повтор снят".to_string(),
+            };
+            svc.set_recurrence(&id, rule).await?;
+            println!("{} {}", "🔁".cyan(), message);
         }
         TasksSubcommand::List { limit, state } => {
             let tasks = if let Some(state_str) = state {
@@ -183,7 +411,7 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
                 }
             }
         }
-        TasksSubcommand::Show { id } => {
+        TasksSubcommand::Show { id, history } => {
             let id = uuid::Uuid::parse_str(&id)?;
             if let Some(t) = svc.get_cached(&id).await? {
                 println!("{} {}", "☐".cyan(), t.title.bold());
@@ -197,20 +425,65 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
                 if let Some(tool) = &t.tool_hint {
                     println!("  tool_hint: {tool}");
                 }
+                if let Some(estimate) = t.estimate() {
+                    println!(
+                        "  estimate: {:.1}h ({}/{}/{})",
+                        estimate.expected(),
+                        estimate.optimistic,
+                        estimate.likely,
+                        estimate.pessimistic
+                    );
+                }
+                if let Some(due) = t.due_date {
+                    println!("  due: {due}");
+                }
+                if let Some(rule) = t.recurrence() {
+                    println!("  recurrence: {rule}");
+                }
+                if history {
+                    print_history(&svc, &t).await?;
+                }
             } else {
                 println!("{} Задача не найдена", "✗".red());
             }
         }
         TasksSubcommand::Done { id } => {
             let id = uuid::Uuid::parse_str(&id)?;
-            svc.update_state(&id, TaskState::Done).await?;
+            svc.update_state_as(&id, TaskState::Done, ACTOR_USER, None)
+                .await?;
             println!("{} Задача помечена выполненной", "✓".green());
+            let next = svc
+                .get_cached(&id)
+                .await?
+                .and_then(|t| t.metadata.get(NEXT_OCCURRENCE_KEY).cloned())
+                .and_then(|next| next.as_str().and_then(|id| uuid::Uuid::parse_str(id).ok()));
+            if let Some(next) = next {
+                if let Some(task) = svc.get_cached(&next).await? {
+                    let due = task
+                        .due_date
+                        .map(|due| due.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    println!(
+                        "{} Следующий повтор: {} [{}] {}",
+                        "🔁".cyan(),
+                        task.title,
+                        task.id,
+                        due
+                    );
+                }
+            }
         }
         TasksSubcommand::Rm { id } => {
             let id = uuid::Uuid::parse_str(&id)?;
-            svc.update_state(&id, TaskState::Cancelled).await?;
+            svc.update_state_as(&id, TaskState::Cancelled, ACTOR_USER, None)
+                .await?;
             println!("{} Задача отменена", "✓".green());
         }
+        TasksSubcommand::Comment { id, text, author } => {
+            let id = uuid::Uuid::parse_str(&id)?;
+            svc.add_comment(&id, &author, &text).await?;
+            println!("{} Комментарий добавлен", "💬".cyan());
+        }
         TasksSubcommand::Depends {
             task_id,
             depends_on,
@@ -227,7 +500,20 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
                 depends_on
             );
         }
-        TasksSubcommand::Graph { format, depth } => match format.to_lowercase().as_str() {
+        TasksSubcommand::Graph {
+            format,
+            critical: true,
+            ..
+        } => {
+            let schedule = svc.schedule(ScheduleConfig::default()).await?;
+            if format.eq_ignore_ascii_case("mermaid") {
+                println!("{} Mermaid граф (критический путь):", "📊".blue());
+                println!("{}", schedule.to_mermaid());
+            } else {
+                print_critical_path(&schedule);
+            }
+        }
+        TasksSubcommand::Graph { format, depth, .. } => match format.to_lowercase().as_str() {
             "text" => {
                 println!("{} Граф зависимостей:", "📊".blue());
                 let graph_text = svc.visualize_graph_text(depth).await?;
@@ -246,6 +532,80 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
                 );
             }
         },
+        TasksSubcommand::Estimate {
+            id,
+            optimistic,
+            likely,
+            pessimistic,
+        } => {
+            let id = uuid::Uuid::parse_str(&id)?;
+            let estimate = TaskEstimate::new(optimistic, likely, pessimistic)?;
+            svc.set_estimate(&id, estimate).await?;
+            println!(
+                "{} Оценка задана: {:.1}h (PERT, {}/{}/{})",
+                "✓".green(),
+                estimate.expected(),
+                optimistic,
+                likely,
+                pessimistic
+            );
+        }
+        TasksSubcommand::Schedule {
+            format,
+            start,
+            hours_per_day,
+            default_hours,
+        } => {
+            let mut config = ScheduleConfig {
+                hours_per_day,
+                default_hours,
+                ..Default::default()
+            };
+            if let Some(start) = start {
+                config.start = parse_date_bound(&start, false)?;
+            }
+            let schedule = svc.schedule(config).await?;
+            match format.to_lowercase().as_str() {
+                "gantt" => println!("{}", schedule.to_gantt("Tasks")),
+                "json" => println!("{}", serde_json::to_string_pretty(&schedule)?),
+                _ => print_schedule(&schedule),
+            }
+        }
+        TasksSubcommand::Scan { path, dry_run } => {
+            let root = path
+                .canonicalize()
+                .with_context(|| format!("Path not found: {}", path.display()))?;
+            let todos = scan_path(&root)?;
+            if dry_run {
+                println!("{} Найдено комментариев: {}", "🔍".blue(), todos.len());
+                for todo in &todos {
+                    let tags = if todo.tags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" [{}]", todo.tags.join(", "))
+                    };
+                    println!(
+                        "  {} {} {}{}",
+                        todo.location().dimmed(),
+                        todo.kind.yellow(),
+                        todo.text,
+                        tags.cyan()
+                    );
+                }
+                return Ok(());
+            }
+
+            let report = svc.sync_code_todos(&root.to_string_lossy(), &todos).await?;
+            println!(
+                "{} Найдено: {}, создано: {}, обновлено: {}, без изменений: {}, закрыто: {}",
+                "✓".green(),
+                report.found,
+                report.created,
+                report.updated,
+                report.unchanged,
+                report.closed
+            );
+        }
         TasksSubcommand::Stats => {
             let (task_stats, graph_stats) = svc.get_stats().await?;
             println!("{} Статистика задач", "📊".yellow());
@@ -267,6 +627,725 @@ async fn handle(cmd: TasksSubcommand) -> Result<()> {
                 graph_stats.cache_size
             );
         }
+        TasksSubcommand::Run {
+            parallel,
+            until,
+            dry_run,
+        } => {
+            let until = until.as_deref().map(uuid::Uuid::parse_str).transpose()?;
+            return run_tasks(Arc::new(svc), parallel, until, dry_run).await;
+        }
+        TasksSubcommand::Plan {
+            goal,
+            format,
+            yes,
+            dry_run,
+            save,
+            from,
+        } => {
+            plan_goal(&svc, &goal, &format, yes, dry_run, save, from).await?;
+        }
+        TasksSubcommand::Search {
+            query,
+            state,
+            priority,
+            tag,
+            due_after,
+            due_before,
+            semantic,
+            limit,
+        } => {
+            let filter = search_filter(
+                state.as_deref(),
+                priority.as_deref(),
+                tag,
+                due_after.as_deref(),
+                due_before.as_deref(),
+            )?;
+            let hits = if semantic {
+                let embedder = task_embedder().await?;
+                svc.semantic_search(&query, &filter, limit, embedder.as_ref())
+                    .await?
+            } else {
+                svc.search_ranked(&query, &filter, limit).await?
+            };
+
+            println!(
+                "{} {}",
+                "✓".green(),
+                format!("Найдено задач: {}", hits.len()).bold()
+            );
+            let mark = |m: &str| m.yellow().bold().to_string();
+            for hit in hits {
+                let id = hit.task.id.to_string();
+                println!(
+                    "{} {} [{}] {} {}",
+                    id.get(0..8).unwrap_or(&id).dimmed(),
+                    render_highlight(&hit.title_highlight, mark),
+                    hit.task.priority,
+                    hit.task.state.to_string().dimmed(),
+                    format!("{:.2}", hit.score).dimmed()
+                );
+                if !hit.snippet.is_empty() {
+                    println!("     {}", render_highlight(&hit.snippet, mark));
+                }
+                if !hit.task.tags.is_empty() {
+                    println!("     tags: {}", hit.task.tags.join(", ").cyan());
+                }
+            }
+        }
     }
     Ok(())
 }
+
+async fn print_history(svc: &todo::TodoService, task: &TodoItem) -> Result<()> {
+    let history = svc.get_history(&task.id).await?;
+    println!();
+    println!("{} История:", "🕓".blue());
+    if history.is_empty() {
+        println!("  {}", "(пусто)".dimmed());
+    }
+    for entry in &history {
+        let at = entry.at.format("%Y-%m-%d %H:%M:%S").to_string();
+        let line = match &entry.event {
+            HistoryEvent::Created { state } => format!("создана в {state}"),
+            HistoryEvent::StateChanged { from, to, reason } => match reason {
+                Some(reason) => format!("{from} → {to}: {reason}"),
+                None => format!("{from} → {to}"),
+            },
+            HistoryEvent::Comment { text } => format!("💬 {text}"),
+        };
+        println!("  {} {} {}", at.dimmed(), entry.actor.cyan(), line);
+    }
+
+    let durations = svc.time_in_state(&task.id).await?;
+    if !durations.is_empty() {
+        println!("{} Время в состояниях:", "⏱".yellow());
+        for d in durations {
+            println!(
+                "  {:<12} {:>8} ({}×)",
+                d.state.to_string(),
+                format_duration(d.total),
+                d.visits
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_critical_path(schedule: &Schedule) {
+    if schedule.critical_path.is_empty() {
+        println!("{} Нет незавершённых задач", "•".dimmed());
+        return;
+    }
+    println!(
+        "{} Критический путь: {:.1}h (±{:.1}h), завершение {}",
+        "🔥".red(),
+        schedule.total_hours,
+        schedule.std_dev,
+        schedule.projected_completion.format("%Y-%m-%d %H:%M")
+    );
+    let chain: Vec<String> = schedule
+        .critical_path
+        .iter()
+        .filter_map(|id| schedule.task(id))
+        .map(|t| format!("{} ({:.1}h)", t.title, t.duration))
+        .collect();
+    println!("   {}", chain.join(" → ").red().bold());
+
+    let others: Vec<_> = schedule.tasks.iter().filter(|t| !t.critical).collect();
+    if !others.is_empty() {
+        println!("{} Задачи с резервом:", "•".dimmed());
+        for task in others {
+            println!(
+                "   {} {}",
+                task.title,
+                format!("(резерв {:.1}h)", task.slack).dimmed()
+            );
+        }
+    }
+}
+
+fn print_schedule(schedule: &Schedule) {
+    if schedule.tasks.is_empty() {
+        println!("{} Нет незавершённых задач", "•".dimmed());
+        return;
+    }
+    println!(
+        "{} План: {:.1}h (±{:.1}h), завершение {}",
+        "📅".blue(),
+        schedule.total_hours,
+        schedule.std_dev,
+        schedule.projected_completion.format("%Y-%m-%d %H:%M")
+    );
+    for task in &schedule.tasks {
+        let icon = if task.late {
+            "❌".red()
+        } else if task.at_risk {
+            "⚠".yellow()
+        } else if task.critical {
+            "🔥".red()
+        } else {
+            "•".normal()
+        };
+        let estimate = if task.estimated {
+            format!("{:.1}h", task.duration)
+        } else {
+            format!("{:.1}h?", task.duration)
+        };
+        let title = if task.critical {
+            task.title.bold().to_string()
+        } else {
+            task.title.clone()
+        };
+        println!(
+            "{} {} {} {} → {} {}",
+            icon,
+            title,
+            estimate.dimmed(),
+            task.projected_start.format("%m-%d %H:%M"),
+            task.projected_finish.format("%m-%d %H:%M"),
+            format!("резерв {:.1}h", task.slack).dimmed()
+        );
+        if let Some(due) = task.due_date.filter(|_| task.at_risk) {
+            let verdict = if task.late {
+                "не успевает".red()
+            } else {
+                "под риском".yellow()
+            };
+            println!("     срок {}: {}", due.format("%Y-%m-%d %H:%M"), verdict);
+        }
+    }
+    let at_risk = schedule.at_risk().count();
+    if at_risk > 0 {
+        println!("{} Под риском срыва срока: {}", "⚠".yellow(), at_risk);
+    }
+}
+
+fn search_filter(
+    state: Option<&str>,
+    priority: Option<&str>,
+    tag: Option<String>,
+    due_after: Option<&str>,
+    due_before: Option<&str>,
+) -> Result<TaskFilter> {
+    let mut filter = TaskFilter::default().with_due_range(
+        due_after.map(|d| parse_date_bound(d, false)).transpose()?,
+        due_before.map(|d| parse_date_bound(d, true)).transpose()?,
+    );
+    if let Some(state) = state {
+        filter = filter.with_state(state.parse()?);
+    }
+    if let Some(priority) = priority {
+        filter = filter.with_min_priority(priority.parse()?);
+    }
+    if let Some(tag) = tag {
+        filter = filter.with_tag(tag);
+    }
+    Ok(filter)
+}
+
+/// Эмбеддинги задач из сервиса эмбеддингов памяти
+#[cfg(not(feature = "minimal"))]
+async fn task_embedder() -> Result<Box<dyn TaskEmbedder>> {
+    let service = memory::UnifiedMemoryService::new(memory::UnifiedMemoryConfig::default()).await?;
+    service.initialize().await?;
+    Ok(Box::new(MemoryTaskEmbedder { service }))
+}
+
+#[cfg(feature = "minimal")]
+async fn task_embedder() -> Result<Box<dyn TaskEmbedder>> {
+    Err(anyhow!(
+        "Семантический поиск недоступен в minimal-сборке: нет сервиса эмбеддингов"
+    ))
+}
+
+#[cfg(not(feature = "minimal"))]
+struct MemoryTaskEmbedder {
+    service: memory::UnifiedMemoryService,
+}
+
+#[cfg(not(feature = "minimal"))]
+#[async_trait]
+impl TaskEmbedder for MemoryTaskEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.service.embed_batch(texts.to_vec()).await
+    }
+}
+
+/// Поиск задач для агентов: тот же FTS5-поиск с фильтрами, что и
+/// `magray tasks search`, результат в JSON
+pub struct TaskSearchTool;
+
+#[async_trait]
+impl Tool for TaskSearchTool {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "task_search".to_string(),
+            description: "Search todo tasks by text with optional state, priority, tag and due date filters".to_string(),
+            usage: "task_search query=<text> [state=ready] [priority=high] [tag=backend]".to_string(),
+            examples: vec![
+                "task_search query=\"database migration\"".to_string(),
+                "task_search state=blocked priority=high".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string", "description": "Full-text query over title, description and tags"},
+                    "state": {"type": "string", "enum": ["planned", "ready", "in_progress", "blocked", "done", "failed", "cancelled"]},
+                    "priority": {"type": "string", "enum": ["low", "medium", "high", "critical"], "description": "Minimum priority"},
+                    "tag": {"type": "string"},
+                    "due_after": {"type": "string", "description": "YYYY-MM-DD or RFC 3339"},
+                    "due_before": {"type": "string", "description": "YYYY-MM-DD or RFC 3339"},
+                    "limit": {"type": "integer", "default": 10}
+                }
+            })
+            .to_string(),
+            usage_guide: None,
+            permissions: None,
+            supports_dry_run: false,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let args = &input.args;
+        let arg = |name: &str| args.get(name).map(String::as_str);
+        let filter = search_filter(
+            arg("state"),
+            arg("priority"),
+            args.get("tag").cloned(),
+            arg("due_after"),
+            arg("due_before"),
+        )?;
+        let limit = arg("limit").and_then(|l| l.parse().ok()).unwrap_or(10);
+
+        let svc = create_default_service(super::super::util::default_tasks_db_path()).await?;
+        let hits = svc
+            .search_ranked(arg("query").unwrap_or_default(), &filter, limit)
+            .await?;
+        let mark = |m: &str| format!("**{m}**");
+        let results: Vec<Value> = hits
+            .iter()
+            .map(|hit| {
+                serde_json::json!({
+                    "id": hit.task.id.to_string(),
+                    "title": hit.task.title,
+                    "state": hit.task.state.to_string(),
+                    "priority": hit.task.priority.to_string(),
+                    "tags": hit.task.tags,
+                    "score": hit.score,
+                    "snippet": render_highlight(&hit.snippet, mark),
+                })
+            })
+            .collect();
+
+        Ok(ToolOutput {
+            success: true,
+            formatted_output: Some(format!("Found {} tasks", results.len())),
+            result: Value::Array(results).to_string(),
+            metadata: HashMap::new(),
+        })
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(ToolInput {
+            command: "task_search".to_string(),
+            args: HashMap::from([("query".to_string(), query.to_string())]),
+            context: Some(query.to_string()),
+            dry_run: false,
+            timeout_ms: None,
+        })
+    }
+}
+
+async fn plan_goal(
+    svc: &todo::TodoService,
+    goal: &str,
+    format: &str,
+    yes: bool,
+    dry_run: bool,
+    save: Option<PathBuf>,
+    from: Option<PathBuf>,
+) -> Result<()> {
+    let goal_task = svc.find_goal_task(goal).await?;
+    let existing = match &goal_task {
+        Some(task) => svc.get_subtasks(&task.id).await?,
+        None => Vec::new(),
+    };
+
+    let plan = match from {
+        Some(path) => {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read plan {}", path.display()))?;
+            GoalPlan::from_llm_response(goal, &json)?
+        }
+        None => {
+            let context = recall_goal_context(goal).await;
+            let prompt = build_plan_prompt(goal, &context, &existing);
+            println!("{} Планирование: {}", "🧠".cyan(), goal.bold());
+            let response = llm::LlmClient::from_env()?.chat_simple(&prompt).await?;
+            GoalPlan::from_llm_response(goal, &response)?
+        }
+    };
+    plan.validate()?;
+
+    if let Some(path) = &save {
+        std::fs::write(path, serde_json::to_string_pretty(&plan)?)
+            .with_context(|| format!("Failed to write plan {}", path.display()))?;
+        println!("{} План сохранён в {}", "💾".blue(), path.display());
+    }
+
+    let diff = PlanDiff::compute(&plan, &existing);
+    match format.to_lowercase().as_str() {
+        "mermaid" => println!("{}", plan.to_mermaid(Some(&diff))),
+        _ => println!("{}", plan.to_text(Some(&diff))),
+    }
+    println!(
+        "{} новых: {}, изменённых: {}, без изменений: {}, к отмене: {}",
+        "📋".blue(),
+        diff.added(),
+        diff.updated(),
+        diff.kept(),
+        diff.removed.len()
+    );
+
+    if diff.is_unchanged() {
+        println!("{} План совпадает с существующими задачами", "✓".green());
+        return Ok(());
+    }
+    if dry_run {
+        return Ok(());
+    }
+    if !yes {
+        let answer = read_answer("Сохранить план? [y/N] ".to_string())?;
+        if !matches!(answer.to_lowercase().as_str(), "y" | "yes" | "д" | "да") {
+            println!("{} План не сохранён", "⚪".white());
+            return Ok(());
+        }
+    }
+
+    let commit = svc.commit_goal_plan(&plan, goal_task.map(|t| t.id)).await?;
+    println!(
+        "{} Цель {}: создано {}, обновлено {}, отменено {}",
+        "✓".green(),
+        commit.goal_id,
+        commit.created,
+        commit.updated,
+        commit.cancelled
+    );
+    Ok(())
+}
+
+/// Контекст проекта для планировщика: ближайшие записи памяти
+#[cfg(not(feature = "minimal"))]
+async fn recall_goal_context(goal: &str) -> Vec<String> {
+    use memory::api::{MemoryServiceTrait, SearchOptions, UnifiedMemoryAPI};
+
+    let api = UnifiedMemoryAPI::new(
+        Arc::new(memory::di::UnifiedContainer::new()) as Arc<dyn MemoryServiceTrait>
+    );
+    match api.recall(goal, SearchOptions::new().limit(5)).await {
+        Ok(results) => results.into_iter().map(|r| r.text).collect(),
+        Err(e) => {
+            tracing::warn!("Memory recall for planning failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(feature = "minimal")]
+async fn recall_goal_context(_goal: &str) -> Vec<String> {
+    Vec::new()
+}
+
+async fn run_tasks(
+    svc: Arc<todo::TodoService>,
+    parallel: usize,
+    until: Option<uuid::Uuid>,
+    dry_run: bool,
+) -> Result<()> {
+    // Инструменты идут через тот же policy/approval gate, что и `magray tools run`
+    let registry = Arc::new(super::tools::load_tool_registry().await);
+    let (home_policy, project_root) = super::policy::policy_sources()?;
+    let gate = Arc::new(ToolGate::for_project(home_policy.as_deref(), &project_root));
+    let handler = PipelineTaskHandler {
+        svc: Arc::clone(&svc),
+        registry,
+        gate,
+        interaction: Arc::new(TerminalInteraction { dry_run: false }),
+    };
+
+    let mut runner = TaskRunner::new(svc, Arc::new(handler))
+        .with_config(RunnerConfig {
+            parallel,
+            until,
+            dry_run,
+        })
+        .with_progress(print_run_event);
+    if let Some(store) = artifact_store(dry_run) {
+        runner = runner.with_artifact_store(store);
+    }
+    if let Some(memory) = history_memory(dry_run) {
+        runner = runner.with_history_memory(memory);
+    }
+
+    if dry_run {
+        println!("{} Dry run: порядок выполнения", "🔍".blue());
+    }
+    let report = runner.run().await?;
+    let done = report
+        .records
+        .iter()
+        .filter(|r| r.status == TaskRunStatus::Done)
+        .count();
+    if report.records.is_empty() {
+        println!("{} Нет готовых задач", "•".dimmed());
+    } else if !dry_run {
+        println!(
+            "{} Выполнено: {}, с ошибкой: {}",
+            "📊".yellow(),
+            done,
+            report.failed()
+        );
+    }
+    if report.until_done == Some(false) {
+        println!(
+            "{} Цель --until не достигнута: её зависимости не выполнены",
+            "✗".red()
+        );
+    }
+
+    if report.succeeded() {
+        Ok(())
+    } else {
+        Err(anyhow!("Не все задачи выполнены"))
+    }
+}
+
+fn print_run_event(event: &TaskRunEvent) {
+    match event {
+        TaskRunEvent::Started { title, route, .. } => {
+            println!(
+                "{} {} {}",
+                "▶".cyan(),
+                title.bold(),
+                format!("({route})").dimmed()
+            );
+        }
+        TaskRunEvent::Finished(record) => match &record.status {
+            TaskRunStatus::Done => {
+                let artifact = record
+                    .artifact
+                    .as_ref()
+                    .map(|a| format!(" → memory {}", a.record_id))
+                    .unwrap_or_default();
+                println!("{} {}{}", "✅".green(), record.title, artifact.dimmed());
+            }
+            TaskRunStatus::Failed(error) => {
+                println!("{} {}: {}", "❌".red(), record.title, error);
+            }
+            TaskRunStatus::Planned => {
+                println!(
+                    "  - {} {} {}",
+                    record
+                        .task_id
+                        .to_string()
+                        .get(0..8)
+                        .unwrap_or_default()
+                        .dimmed(),
+                    record.title.bold(),
+                    format!("({})", record.route).dimmed()
+                );
+            }
+        },
+    }
+}
+
+/// Выполняет задачу через Executor: инструмент из `tool_hint` с
+/// `tool_params`, а задачи без подсказки — циклом агента, как `magray smart`.
+/// Причины прошлых провалов задачи добавляются к её тексту.
+struct PipelineTaskHandler {
+    svc: Arc<todo::TodoService>,
+    registry: Arc<ToolRegistry>,
+    gate: Arc<ToolGate>,
+    interaction: Arc<dyn InteractionHandler>,
+}
+
+#[async_trait]
+impl TaskHandler for PipelineTaskHandler {
+    async fn run(&self, task: &TodoItem) -> Result<String> {
+        let mut executor = Executor::new();
+        register_registry_tools(
+            &mut executor,
+            Arc::clone(&self.registry),
+            Arc::clone(&self.gate),
+            false,
+        );
+        executor.set_interaction_handler(Arc::clone(&self.interaction));
+        let mut planner = Planner::new();
+        for tool in executor.get_available_tools() {
+            planner.register_available_tool(&tool);
+        }
+
+        let mut text = if task.description.trim().is_empty() {
+            task.title.clone()
+        } else {
+            format!("{}\n{}", task.title, task.description)
+        };
+        let failures: Vec<String> = self
+            .svc
+            .get_history(&task.id)
+            .await?
+            .iter()
+            .filter_map(|entry| entry.failure_reason().map(|r| format!("- {r}")))
+            .collect();
+        if !failures.is_empty() {
+            text.push_str(&format!(
+                "\nPrevious attempts failed:\n{}",
+                failures.join("\n")
+            ));
+        }
+        let result = match &task.tool_hint {
+            Some(tool) => {
+                let arguments = task
+                    .tool_params
+                    .clone()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(k, v)| (k, Value::String(v)))
+                    .collect();
+                let plan = plan_tool(&planner, tool, arguments, &text).await?;
+                executor.execute_plan(&plan).await?
+            }
+            None => {
+                let plan = plan_task(&planner, &self.registry, &text).await?;
+                let report = AgentLoop::new(
+                    Arc::new(planner),
+                    Arc::new(executor),
+                    Arc::new(Critic::new()),
+                )
+                .run(plan)
+                .await?;
+                if !report.succeeded() {
+                    return Err(anyhow!("{}", report.outcome));
+                }
+                report.result
+            }
+        };
+
+        if result.status != ExecutionStatus::Completed {
+            let error = result
+                .error
+                .as_ref()
+                .map_or("no error reported", |e| e.message.as_str());
+            return Err(anyhow!("{}", error));
+        }
+        Ok(execution_output(&result))
+    }
+}
+
+/// Вывод последнего шага: `result` текстом или весь JSON
+fn execution_output(result: &ExecutionResult) -> String {
+    result
+        .step_results
+        .iter()
+        .rev()
+        .find_map(|step| step.output.as_ref())
+        .map(
+            |output| match output.get("result").and_then(Value::as_str) {
+                Some(text) => text.to_string(),
+                None => output.to_string(),
+            },
+        )
+        .unwrap_or_default()
+}
+
+/// Результаты задач сохраняются в память и попадают в `artifacts`
+#[cfg(not(feature = "minimal"))]
+fn artifact_store(dry_run: bool) -> Option<Arc<dyn todo::ArtifactStore>> {
+    use memory::api::{MemoryServiceTrait, UnifiedMemoryAPI};
+
+    if dry_run {
+        return None;
+    }
+    let api = Arc::new(UnifiedMemoryAPI::new(
+        Arc::new(memory::di::UnifiedContainer::new()) as Arc<dyn MemoryServiceTrait>,
+    ));
+    Some(Arc::new(MemoryArtifactStore { api }))
+}
+
+#[cfg(feature = "minimal")]
+fn artifact_store(_dry_run: bool) -> Option<Arc<dyn todo::ArtifactStore>> {
+    None
+}
+
+/// История провалов задач сохраняется в память для следующих запусков
+#[cfg(not(feature = "minimal"))]
+fn history_memory(dry_run: bool) -> Option<Arc<dyn HistoryMemory>> {
+    use memory::api::{MemoryServiceTrait, UnifiedMemoryAPI};
+
+    if dry_run {
+        return None;
+    }
+    let api = Arc::new(UnifiedMemoryAPI::new(
+        Arc::new(memory::di::UnifiedContainer::new()) as Arc<dyn MemoryServiceTrait>,
+    ));
+    Some(Arc::new(MemoryArtifactStore { api }))
+}
+
+#[cfg(feature = "minimal")]
+fn history_memory(_dry_run: bool) -> Option<Arc<dyn HistoryMemory>> {
+    None
+}
+
+/// Сохраняет результаты и историю задач записями в памяти (слой Insights)
+#[cfg(not(feature = "minimal"))]
+struct MemoryArtifactStore {
+    api: Arc<memory::api::UnifiedMemoryAPI>,
+}
+
+#[cfg(not(feature = "minimal"))]
+#[async_trait]
+impl todo::ArtifactStore for MemoryArtifactStore {
+    async fn store(&self, task: &TodoItem, output: &str) -> Result<todo::MemoryReference> {
+        use memory::api::MemoryContext;
+
+        let record_id = self
+            .api
+            .remember(
+                format!("{}\n\n{}", task.title, output),
+                MemoryContext::new("task_output")
+                    .with_tags(vec!["task".to_string(), task.id.to_string()])
+                    .with_layer(memory::Layer::Insights),
+            )
+            .await?;
+        Ok(todo::MemoryReference {
+            layer: todo::Layer::Insights,
+            record_id,
+            created_at: chrono::Utc::now(),
+        })
+    }
+}
+
+#[cfg(not(feature = "minimal"))]
+#[async_trait]
+impl HistoryMemory for MemoryArtifactStore {
+    async fn remember(&self, task: &TodoItem, digest: &str) -> Result<todo::MemoryReference> {
+        use memory::api::MemoryContext;
+
+        let record_id = self
+            .api
+            .remember(
+                digest.to_string(),
+                MemoryContext::new("task_history")
+                    .with_tags(vec!["task_history".to_string(), task.id.to_string()])
+                    .with_layer(memory::Layer::Insights),
+            )
+            .await?;
+        Ok(todo::MemoryReference {
+            layer: todo::Layer::Insights,
+            record_id,
+            created_at: chrono::Utc::now(),
+        })
+    }
+}
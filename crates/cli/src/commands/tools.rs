@@ -2,15 +2,15 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
 use colored::*;
-use common::policy::load_effective_policy;
-use common::policy::PolicyEngine;
-use common::{events, topics};
+use common::approval::ApprovalDecision;
+use common::events;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use tools::intelligent_selector::{
     IntelligentToolSelector, SelectorConfig, TaskComplexity, ToolSelectionContext, UrgencyLevel,
     UserExpertise,
 };
+use tools::invocation::ToolGate;
 use tools::ToolRegistry;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -73,6 +73,48 @@ fn preload_persisted_into_registry(registry: &mut ToolRegistry) {
     }
 }
 
+// Helper: export plugins into registry (quiet; best-effort)
+async fn export_plugins_into_registry(registry: &mut ToolRegistry) {
+    if std::env::var("MAGRAY_EXPORT_PLUGINS_AS_TOOLS")
+        .ok()
+        .map(|s| s == "1" || s.to_lowercase() == "true")
+        .unwrap_or(true)
+    {
+        let home = crate::util::magray_home();
+        let mut plugins_dir = home.clone();
+        plugins_dir.push("plugins");
+        let mut cfg_dir = home.clone();
+        cfg_dir.push("plugin-configs");
+        let _ = tokio::fs::create_dir_all(&plugins_dir).await;
+        let _ = tokio::fs::create_dir_all(&cfg_dir).await;
+        let pregistry = tools::plugins::plugin_manager::PluginRegistry::new(plugins_dir, cfg_dir);
+        let _ = pregistry.load_manifests_from_directory().await;
+        let _ = pregistry.load_from_filesystem().await;
+        let plugins = pregistry.list_plugins(None).await;
+        for p in plugins {
+            if matches!(
+                p.plugin_type,
+                tools::plugins::plugin_manager::PluginType::Wasm
+                    | tools::plugins::plugin_manager::PluginType::ExternalProcess
+            ) {
+                if let Ok(tool_box) = pregistry.materialize_as_tool(&p.id).await {
+                    registry.register(&p.id, tool_box);
+                }
+            }
+        }
+    }
+}
+
+/// Registry with the built-in, persisted MCP and plugin tools, as seen by
+/// `tools run`
+pub async fn load_tool_registry() -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry.register("task_search", Box::new(super::tasks::TaskSearchTool));
+    preload_persisted_into_registry(&mut registry);
+    export_plugins_into_registry(&mut registry).await;
+    registry
+}
+
 // Load UsageGuide overrides from file path env and JSON env. Precedence: file < JSON env
 fn load_usage_guide_overrides() -> serde_json::Map<String, serde_json::Value> {
     let mut map = serde_json::Map::new();
@@ -332,6 +374,17 @@ pub enum ToolsSubcommand {
         server_url: String,
     },
 
+    /// Экспорт JSON Schema аргументов инструментов (function calling / MCP tools/list)
+    #[command(name = "schema")]
+    Schema {
+        /// Имя инструмента (по умолчанию все)
+        #[arg(long)]
+        name: Option<String>,
+        /// Формат: json-schema|openai|anthropic|gemini|mcp
+        #[arg(long, default_value_t = String::from("json-schema"))]
+        format: String,
+    },
+
     /// Выполнить инструмент по имени: --name <tool> --command <cmd> --arg k=v --arg x=y
     #[command(name = "run")]
     Run {
@@ -374,39 +427,6 @@ async fn handle_tools_command(cmd: ToolsSubcommand) -> Result<()> {
     preload_persisted_into_registry(&mut registry);
     let guide_overrides = load_usage_guide_overrides();
 
-    // Helper: export plugins into registry (quiet; best-effort)
-    async fn export_plugins_into_registry(registry: &mut ToolRegistry) {
-        if std::env::var("MAGRAY_EXPORT_PLUGINS_AS_TOOLS")
-            .ok()
-            .map(|s| s == "1" || s.to_lowercase() == "true")
-            .unwrap_or(true)
-        {
-            let home = crate::util::magray_home();
-            let mut plugins_dir = home.clone();
-            plugins_dir.push("plugins");
-            let mut cfg_dir = home.clone();
-            cfg_dir.push("plugin-configs");
-            let _ = tokio::fs::create_dir_all(&plugins_dir).await;
-            let _ = tokio::fs::create_dir_all(&cfg_dir).await;
-            let pregistry =
-                tools::plugins::plugin_manager::PluginRegistry::new(plugins_dir, cfg_dir);
-            let _ = pregistry.load_manifests_from_directory().await;
-            let _ = pregistry.load_from_filesystem().await;
-            let plugins = pregistry.list_plugins(None).await;
-            for p in plugins {
-                if matches!(
-                    p.plugin_type,
-                    tools::plugins::plugin_manager::PluginType::Wasm
-                        | tools::plugins::plugin_manager::PluginType::ExternalProcess
-                ) {
-                    if let Ok(tool_box) = pregistry.materialize_as_tool(&p.id).await {
-                        registry.register(&p.id, tool_box);
-                    }
-                }
-            }
-        }
-    }
-
     match cmd {
         ToolsSubcommand::List { details, json } => {
             // Avoid plugin export before JSON to keep output clean
@@ -440,9 +460,9 @@ async fn handle_tools_command(cmd: ToolsSubcommand) -> Result<()> {
                             .cloned()
                             .collect();
                         if all_roots.is_empty() {
-                            "<none>"
+                            "<none>".to_string()
                         } else {
-                            &all_roots.join(":")
+                            all_roots.join(":")
                         }
                     }
                 );
@@ -672,7 +692,7 @@ async fn handle_tools_command(cmd: ToolsSubcommand) -> Result<()> {
                             description: format!("[plugin:{:?}] {}", p.plugin_type, p.description),
                             usage: format!("{} <args>", p.name),
                             examples: vec![format!("{} example", p.name)],
-                            input_schema: p.configuration_schema.to_string(),
+                            input_schema: p.tool_input_schema(),
                             usage_guide: None,
                             permissions: perms_opt,
                             supports_dry_run: false,
@@ -766,6 +786,52 @@ async fn handle_tools_command(cmd: ToolsSubcommand) -> Result<()> {
             );
             Ok(())
         }
+        ToolsSubcommand::Schema { name, format } => {
+            export_plugins_into_registry(&mut registry).await;
+            let mut specs = registry.list_tools();
+            if let Some(name) = &name {
+                specs.retain(|s| &s.name == name);
+                if specs.is_empty() {
+                    return Err(anyhow::anyhow!("Tool not found: {}", name));
+                }
+            }
+            specs.sort_by(|a, b| a.name.cmp(&b.name));
+            let out = match format.as_str() {
+                "json-schema" => {
+                    let mut map = serde_json::Map::new();
+                    for spec in &specs {
+                        let schema = spec.parsed_input_schema().map_err(|e| {
+                            anyhow::anyhow!(
+                                "Tool '{}' has an invalid input schema: {}",
+                                spec.name,
+                                e
+                            )
+                        })?;
+                        map.insert(spec.name.clone(), schema.into_value());
+                    }
+                    serde_json::Value::Object(map)
+                }
+                "openai" | "anthropic" | "gemini" => serde_json::Value::Array(
+                    specs
+                        .iter()
+                        .map(|spec| {
+                            let def = spec.function_definition();
+                            match format.as_str() {
+                                "openai" => def.to_openai(),
+                                "anthropic" => def.to_anthropic(),
+                                _ => def.to_gemini(),
+                            }
+                        })
+                        .collect(),
+                ),
+                "mcp" => tools::mcp_tools_list(&specs),
+                other => anyhow::bail!(
+                    "Unknown schema format: {other} (expected json-schema|openai|anthropic|gemini|mcp)"
+                ),
+            };
+            println!("{}", serde_json::to_string_pretty(&out)?);
+            Ok(())
+        }
         ToolsSubcommand::Run {
             name,
             command,
@@ -778,175 +844,43 @@ async fn handle_tools_command(cmd: ToolsSubcommand) -> Result<()> {
             let tool = registry
                 .get(&name)
                 .ok_or_else(|| anyhow::anyhow!("Tool not found: {}", name))?;
-            let mut args_map = std::collections::HashMap::new();
-            for (k, v) in arg {
-                args_map.insert(k, v);
-            }
-            // Load effective policy
-            let mut policy_path = crate::util::magray_home();
-            policy_path.push("policy.json");
-            let _effective = load_effective_policy(if policy_path.exists() {
-                Some(&policy_path)
-            } else {
-                None
-            });
-            let policy = common::policy::get_policy_engine_with_eventbus();
-            // Enrich policy args
-            if name == "web_fetch" {
-                if let Some(url) = args_map.get("url").cloned() {
-                    let domain = url
-                        .split('/')
-                        .nth(2)
-                        .unwrap_or("")
-                        .split(':')
-                        .next()
-                        .unwrap_or("")
-                        .to_string();
-                    if !domain.is_empty() {
-                        args_map.insert("domain".into(), domain);
-                    }
-                }
-            } else if name == "web_search" {
-                if let Some(q) = args_map.get("query").cloned() {
-                    let lowered = q.to_lowercase();
-                    if lowered.contains("internal") {
-                        args_map.insert("keyword".into(), "internal".into());
-                    }
-                    if lowered.contains("secret") {
-                        args_map.insert("keyword".into(), "secret".into());
-                    }
-                }
-            }
-
-            // Pre-check: SandboxConfig + ToolSpec permissions
-            let spec = tool.spec();
-            if let Some(perms) = &spec.permissions {
-                let sandbox_cfg = common::sandbox_config::SandboxConfig::from_env();
-                let simple = common::policy::SimpleToolPermissions {
-                    fs_read_roots: perms.fs_read_roots.clone(),
-                    fs_write_roots: perms.fs_write_roots.clone(),
-                    net_allowlist: perms.net_allowlist.clone(),
-                    allow_shell: perms.allow_shell,
-                };
-                if let Some(pre) =
-                    common::policy::precheck_permissions(&name, &simple, &sandbox_cfg)
-                {
-                    match pre.action {
-                        common::policy::PolicyAction::Deny => {
-                            anyhow::bail!(
-                                "Инструмент '{}' заблокирован политикой (precheck)",
-                                name
-                            );
-                        }
-                        common::policy::PolicyAction::Ask => {
-                            let non_interactive = std::env::var("MAGRAY_NONINTERACTIVE")
-                                .unwrap_or_default()
-                                == "true";
-                            if non_interactive {
-                                anyhow::bail!("Инструмент '{}' требует подтверждения (precheck), но режим non-interactive", name);
-                            }
-                        }
-                        common::policy::PolicyAction::Allow => { /* continue */ }
-                    }
-                }
-            }
-
-            let decision = policy.evaluate_tool(&name, &args_map);
-
-            // Dynamic guard based on UsageGuide overrides/spec
-            let mut require_ask_due_to_guide = false;
+            let mut input = tools::ToolInput {
+                command,
+                args: arg.into_iter().collect(),
+                context,
+                dry_run,
+                timeout_ms,
+            };
+            // Effective policy: global file + nearest project policy
+            let (home_policy, project_root) = super::policy::policy_sources()?;
+            let gate = ToolGate::for_project(home_policy.as_deref(), &project_root);
+            // Проверка и приведение аргументов по JSON Schema инструмента
             let mut spec = tool.spec();
+            gate.prepare(&spec, &mut input)?;
+            // UsageGuide overrides влияют на Ask для рискованных инструментов
             if let Some(ov) = guide_overrides.get(&name) {
                 apply_usage_guide_override(&mut spec, ov);
             }
-            if matches!(decision.action, common::policy::PolicyAction::Allow)
-                && decision.matched_rule.is_none()
-            {
-                if let Some(guide) = &spec.usage_guide {
-                    let high_risk = guide.risk_score >= 4;
-                    let has_side_effects = !guide.side_effects.is_empty();
-                    if high_risk || has_side_effects {
-                        require_ask_due_to_guide = true;
-                    }
-                }
-            }
-
-            if matches!(decision.action, common::policy::PolicyAction::Deny) {
-                let reason = decision
-                    .matched_rule
-                    .and_then(|r| r.reason)
-                    .unwrap_or_else(|| "blocked".into());
-                let evt = serde_json::json!({"tool": name, "reason": reason});
-                tokio::spawn(events::publish(topics::TOPIC_POLICY_BLOCK, evt));
-                anyhow::bail!("Tool '{}' blocked by policy", name);
-            }
-            if matches!(decision.action, common::policy::PolicyAction::Ask)
-                || require_ask_due_to_guide
+            if let Some(ApprovalDecision::AllowProject) =
+                gate.authorize(tool, &spec, &input).await?
             {
-                let non_interactive =
-                    std::env::var("MAGRAY_NONINTERACTIVE").unwrap_or_default() == "true";
-                if non_interactive {
-                    anyhow::bail!(
-                        "Tool '{}' requires confirmation (ask), but running non-interactive",
-                        name
-                    );
-                }
-                let auto_approve =
-                    std::env::var("MAGRAY_AUTO_APPROVE_ASK").unwrap_or_default() == "true";
-                if !auto_approve {
-                    let preview_input = tools::ToolInput {
-                        command: command.clone(),
-                        args: args_map.clone(),
-                        context: context.clone(),
-                        dry_run: true,
-                        timeout_ms,
-                    };
-                    let preview =
-                        tool.execute(preview_input)
-                            .await
-                            .unwrap_or_else(|e| tools::ToolOutput {
-                                success: false,
-                                result: format!("preview error: {e}"),
-                                formatted_output: None,
-                                metadata: std::collections::HashMap::new(),
-                            });
-                    println!("\n=== Предпросмотр (dry-run) {} ===", name.bold());
-                    if let Some(fmt) = preview.formatted_output {
-                        println!("{fmt}");
-                    } else {
-                        println!("{}", preview.result);
-                    }
-                    if require_ask_due_to_guide {
-                        println!("Требуется подтверждение по UsageGuide (risk/side_effects)");
-                    }
-                    use std::io::{self, Write};
-                    print!("Продолжить выполнение? [y/N]: ");
-                    let _ = io::stdout().flush();
-                    let mut answer = String::new();
-                    if io::stdin().read_line(&mut answer).is_err() {
-                        anyhow::bail!("confirmation failed");
-                    }
-                    let ans = answer.trim().to_lowercase();
-                    if !(ans == "y" || ans == "yes" || ans == "д" || ans == "да") {
-                        anyhow::bail!("Отменено пользователем");
-                    }
-                }
+                println!(
+                    "{} '{}' разрешён для проекта ({})",
+                    "✓".green(),
+                    name,
+                    common::policy::project_policy_path(&project_root).display()
+                );
             }
-            let input = tools::ToolInput {
-                command,
-                args: args_map,
-                context,
-                dry_run,
-                timeout_ms,
-            };
-            let output = tool.execute(input).await?;
+            let started = std::time::Instant::now();
+            let output = tool.execute(input.clone()).await;
+            // Await so the audit log entry is written before the process exits
+            ToolGate::record_invocation(&name, &input, &output, started).await;
+            let output = output?;
             if output.success {
                 println!("{} {}", "✓".green(), output.result);
             } else {
                 println!("{} {}", "✗".red(), output.result);
             }
-            let evt = serde_json::json!({"tool": name, "success": output.success});
-            tokio::spawn(events::publish(topics::TOPIC_TOOL_INVOKED, evt));
             Ok(())
         }
     }
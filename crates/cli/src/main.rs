@@ -18,6 +18,7 @@ mod health_checks;
 mod progress;
 mod services;
 mod tui_chat;
+mod tui_tasks;
 mod util;
 
 #[cfg(test)]
@@ -25,8 +26,8 @@ mod status_tests;
 
 use cli::agent_traits::AgentResponse;
 use commands::{
-    GpuCommand, MemoryCommand, ModelsCommand, OrchestratorCommand, SmartCommand, TasksCommand,
-    ToolsCommand,
+    AuditCommand, GpuCommand, MemoryCommand, ModelsCommand, OrchestratorCommand, PolicySubcommand,
+    RunCommand, ScheduleCommand, SmartCommand, TasksCommand, ToolsCommand,
 };
 use orchestrator::orchestrator::AgentOrchestrator;
 
@@ -104,6 +105,10 @@ enum Commands {
     Tasks(TasksCommand),
     /// [🤖] Multi-Agent Orchestration System
     Orchestrator(OrchestratorCommand),
+    /// [📅] Расписание workflow по cron (add/list/rm/run-now/daemon)
+    Schedule(ScheduleCommand),
+    /// [▶] Запуск workflow из YAML/TOML файла (--param, --dry-run)
+    Run(RunCommand),
     /// [🏥] Проверка здоровья системы
     Health,
     /// [📊] Показать состояние системы
@@ -120,7 +125,11 @@ enum Commands {
         /// Применить правило Allow для shell_exec через env JSON (демо)
         #[arg(long)]
         allow_shell: bool,
+        #[command(subcommand)]
+        command: Option<PolicySubcommand>,
     },
+    /// [📜] Журнал аудита (tail/search/verify/export)
+    Audit(AuditCommand),
     /// [🖥] Запуск TUI интерфейса для Plan→Preview→Execute workflow
     Tui,
 }
@@ -198,6 +207,26 @@ async fn main() -> Result<()> {
         let _ = registry.load_manifests_from_directory().await;
     }
 
+    // Маскирование секретов по секции `redaction` эффективной политики
+    if let Ok((home, project_root)) = commands::policy::policy_sources() {
+        let doc =
+            common::policy::load_effective_policy_for_project(home.as_deref(), Some(&project_root));
+        common::redaction::set_global_redactor(common::redaction::Redactor::new(
+            doc.redaction.unwrap_or_default(),
+        ));
+    }
+
+    // Журнал аудита: tool/policy/sandbox/LLM события пишутся в hash-цепочку
+    if !std::env::var("MAGRAY_AUDIT_DISABLED")
+        .ok()
+        .map(|s| s == "1" || s.to_lowercase() == "true")
+        .unwrap_or(false)
+    {
+        if let Err(e) = common::audit::init_global(commands::audit::audit_config()) {
+            warn!("Audit log disabled: {}", e);
+        }
+    }
+
     // Глобальный таймаут на выполнение команды (по умолчанию 300с)
     let top_timeout_secs: u64 = std::env::var("MAGRAY_CMD_TIMEOUT")
         .ok()
@@ -219,12 +248,15 @@ async fn main() -> Result<()> {
             Some(Commands::Models(_)) => "models",
             Some(Commands::Tasks(_)) => "tasks",
             Some(Commands::Orchestrator(_)) => "orchestrator",
+            Some(Commands::Schedule(_)) => "schedule",
+            Some(Commands::Run(_)) => "run",
             Some(Commands::Health) => "health",
             Some(Commands::Status) => "status",
             Some(Commands::LlmStatus) => "llm_status",
             Some(Commands::Performance) => "performance",
             Some(Commands::Policy { .. }) => "policy",
             Some(Commands::Tools(_)) => "tools",
+            Some(Commands::Audit(_)) => "audit",
             Some(Commands::Tui) => "tui",
             None => "help",
         };
@@ -295,6 +327,15 @@ async fn main() -> Result<()> {
                     .await
                     .map_err(|_| anyhow::anyhow!("Orchestrator command timeout"))??;
             }
+            Some(Commands::Schedule(cmd)) => {
+                // Без таймаута: `schedule daemon` работает до Ctrl+C
+                cmd.execute().await?;
+            }
+            Some(Commands::Run(cmd)) => {
+                // Без общего таймаута: шаги ограничены своими `timeout`, а
+                // `ask` ждёт ответа пользователя
+                cmd.execute().await?;
+            }
             Some(Commands::Health) => {
                 // Инициализируем сервисы для health check
                 let llm_client = LlmClient::from_env().ok().map(Arc::new);
@@ -322,16 +363,22 @@ async fn main() -> Result<()> {
                     .await
                     .map_err(|_| anyhow::anyhow!("Performance command timeout"))??;
             }
-            Some(Commands::Policy { list, allow_shell }) => {
+            Some(Commands::Policy {
+                list,
+                allow_shell,
+                command,
+            }) => {
                 use common::policy::{
-                    load_effective_policy, PolicyAction, PolicyDocument, PolicyRule,
+                    load_effective_policy_for_project, PolicyAction, PolicyDocument, PolicyRule,
                     PolicySubjectKind,
                 };
-                let mut home = util::magray_home();
-                home.push("policy.json");
+                if let Some(sub) = command {
+                    commands::policy::handle_policy_subcommand(sub).await?;
+                }
                 if list {
+                    let (home, project_root) = commands::policy::policy_sources()?;
                     let effective =
-                        load_effective_policy(if home.exists() { Some(&home) } else { None });
+                        load_effective_policy_for_project(home.as_deref(), Some(&project_root));
                     println!(
                         "=== Effective Policy ===\n{}",
                         serde_json::to_string_pretty(&effective).unwrap_or_else(|_| "{}".into())
@@ -340,13 +387,13 @@ async fn main() -> Result<()> {
                 if allow_shell {
                     // Merge small override into MAGRAY_POLICY_JSON
                     let override_doc = PolicyDocument {
-                        rules: vec![PolicyRule {
-                            subject_kind: PolicySubjectKind::Tool,
-                            subject_name: "shell_exec".into(),
-                            when_contains_args: None,
-                            action: PolicyAction::Allow,
-                            reason: Some("cli override".into()),
-                        }],
+                        rules: vec![PolicyRule::new(
+                            PolicySubjectKind::Tool,
+                            "shell_exec",
+                            PolicyAction::Allow,
+                        )
+                        .with_reason("cli override")],
+                        ..Default::default()
                     };
                     let json = serde_json::to_string(&override_doc)?;
                     std::env::set_var("MAGRAY_POLICY_JSON", json);
@@ -358,6 +405,7 @@ async fn main() -> Result<()> {
                     .await
                     .map_err(|_| anyhow::anyhow!("Tools command timeout"))??;
             }
+            Some(Commands::Audit(cmd)) => cmd.execute().await?,
             Some(Commands::Tui) => {
                 timeout(Duration::from_secs(3600), run_tui_mode())
                     .await
@@ -1187,6 +1235,7 @@ async fn process_orchestrator_message(
         dry_run: false,
         timeout_ms: Some(60000),
         config_overrides: None,
+        budget: None,
     };
 
     // Выполняем полный workflow: Intent→Plan→Execute→Critic
@@ -1677,15 +1726,33 @@ async fn run_tui_mode() -> Result<()> {
     println!("🖥  Starting MAGRAY TUI Interface...");
 
     // Создаем TUI приложение
-    let mut app = TUIApp::new().map_err(|e| anyhow::anyhow!("Failed to initialize TUI: {}", e))?;
-
-    println!("🚀 TUI initialized successfully. Press 'q' to quit, 'h' for help.");
-
-    // Интеграция с orchestrator (заглушка для MVP)
-    // В будущем здесь будет реальная интеграция с AgentOrchestrator
+    let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (chat_tx, chat_rx) = tokio::sync::mpsc::unbounded_channel();
+    // Подтверждения политики (ask) показываются диалогом TUI, а не в stdin
+    let (prompter, approvals) = common::approval::ChannelPrompter::new();
+    let mut app = TUIApp::new()
+        .map_err(|e| anyhow::anyhow!("Failed to initialize TUI: {}", e))?
+        .with_task_actions(action_tx)
+        .with_chat(chat_tx)
+        .with_approvals(approvals);
+    common::approval::set_default_prompter(Some(Arc::new(prompter)));
+
+    println!("🚀 TUI initialized successfully. Press 'q' to quit, 'h' for help, 'F2' for tasks.");
+
+    // Доска задач и чат работают в фоне и присылают события в TUI
+    let events = app.get_event_handler().sender();
+    if let Err(e) = tui_tasks::spawn_task_board(events.clone(), action_rx).await {
+        let _ = events.send(ui::tui::TUIEvent::Error(format!(
+            "Task board unavailable: {e}"
+        )));
+    }
+    tui_tasks::spawn_chat(events, chat_rx);
 
-    // Запускаем TUI
-    if let Err(e) = app.run() {
+    // Запускаем TUI; цикл отрисовки блокирующий, фоновые задачи идут на
+    // других потоках runtime
+    let result = tokio::task::block_in_place(|| app.run());
+    common::approval::set_default_prompter(None);
+    if let Err(e) = result {
         eprintln!("TUI error: {e}");
         return Err(anyhow::anyhow!("TUI execution failed: {}", e));
     }
@@ -7,8 +7,8 @@
 use anyhow::{anyhow, Result};
 use llm::LlmClient;
 use orchestrator::{
-    create_agent_event_publisher, AgentOrchestrator, OrchestratorConfig, SystemConfig,
-    TaskPriority, WorkflowRequest,
+    create_agent_event_publisher, AgentOrchestrator, OrchestratorConfig, SpendBudget, SpendTracker,
+    SystemConfig, TaskPriority, WorkflowRequest,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -154,6 +154,9 @@ pub struct OrchestrationService {
     /// LLM client for intelligent fallback execution
     llm_client: Arc<RwLock<Option<Arc<LlmClient>>>>,
 
+    /// Tokens/cost of this session's LLM calls, limited by `MAGRAY_SESSION_BUDGET`
+    session_spend: Arc<SpendTracker>,
+
     /// Configuration for orchestrator
     config: OrchestratorConfig,
 
@@ -171,6 +174,7 @@ impl OrchestrationService {
             orchestrator: Arc::new(RwLock::new(None)),
             fallback_agent: Arc::new(RwLock::new(None)),
             llm_client: Arc::new(RwLock::new(None)),
+            session_spend: session_spend_tracker(),
             config: OrchestratorConfig::default(),
             orchestrator_available: Arc::new(AtomicBool::new(false)),
             fallback_mode: Arc::new(AtomicBool::new(true)),
@@ -198,6 +202,31 @@ impl OrchestrationService {
                 .await
                 .map_err(|e| anyhow!("Failed to create AgentOrchestrator: {}", e))?;
 
+        // Durable workflow state; on startup compensate sagas left behind by a crash
+        let orchestrator = match crate::commands::orchestrator::open_workflow_store() {
+            Ok(store) => orchestrator.with_state_store(store),
+            Err(e) => {
+                warn!(
+                    "Workflow state store unavailable, running without persistence: {}",
+                    e
+                );
+                orchestrator
+            }
+        };
+        match orchestrator.recover_interrupted().await {
+            Ok(report) => {
+                if !report.interrupted_workflows.is_empty() || !report.compensated_sagas.is_empty()
+                {
+                    info!(
+                        "Recovered {} interrupted workflow(s), compensated {} saga(s)",
+                        report.interrupted_workflows.len(),
+                        report.compensated_sagas.len()
+                    );
+                }
+            }
+            Err(e) => warn!("Workflow recovery failed: {}", e),
+        }
+
         // Initialize agents
         orchestrator
             .initialize_agents()
@@ -207,10 +236,11 @@ impl OrchestrationService {
         info!("AgentOrchestrator initialized successfully");
 
         // КРИТИЧЕСКОЕ ИСПРАВЛЕНИЕ: Also initialize LLM client for TUI chat support
+        let session_spend = session_spend_tracker();
         let llm_client_option = match LlmClient::from_env() {
             Ok(client) => {
                 info!("LLM client initialized successfully for orchestrator mode");
-                Some(Arc::new(client))
+                Some(Arc::new(client.with_usage_observer(session_spend.clone())))
             }
             Err(e) => {
                 warn!(
@@ -225,6 +255,7 @@ impl OrchestrationService {
             orchestrator: Arc::new(RwLock::new(Some(orchestrator))),
             fallback_agent: Arc::new(RwLock::new(None)),
             llm_client: Arc::new(RwLock::new(llm_client_option)),
+            session_spend,
             config: orchestrator_config,
             orchestrator_available: Arc::new(AtomicBool::new(true)),
             fallback_mode: Arc::new(AtomicBool::new(false)),
@@ -261,7 +292,9 @@ impl OrchestrationService {
         }
 
         warn!("🔧 Attempting to create LLM client...");
-        let llm_client_result = LlmClient::from_env();
+        let session_spend = session_spend_tracker();
+        let llm_client_result =
+            LlmClient::from_env().map(|client| client.with_usage_observer(session_spend.clone()));
         let llm_client_option = match llm_client_result {
             Ok(client) => {
                 warn!("✅ LLM client created successfully!");
@@ -314,6 +347,7 @@ impl OrchestrationService {
             orchestrator: Arc::new(RwLock::new(None)),
             fallback_agent: Arc::new(RwLock::new(None)),
             llm_client: Arc::new(RwLock::new(llm_client_option)),
+            session_spend,
             config: OrchestratorConfig::default(),
             orchestrator_available: Arc::new(AtomicBool::new(false)),
             fallback_mode: Arc::new(AtomicBool::new(true)),
@@ -382,6 +416,7 @@ impl OrchestrationService {
             dry_run: request.context.dry_run,
             timeout_ms: Some(request.context.timeout_ms),
             config_overrides: None,
+            budget: None,
         };
 
         // Execute workflow through orchestrator
@@ -587,6 +622,11 @@ impl OrchestrationService {
         Ok(())
     }
 
+    /// Spend of the chat session so far
+    pub fn session_spend(&self) -> Arc<SpendTracker> {
+        Arc::clone(&self.session_spend)
+    }
+
     /// Get current orchestrator status
     pub fn get_status(&self) -> OrchestrationStatus {
         OrchestrationStatus {
@@ -600,6 +640,13 @@ impl OrchestrationService {
     }
 }
 
+/// Session tracker with the budget from `MAGRAY_SESSION_BUDGET` (unlimited when unset)
+fn session_spend_tracker() -> Arc<SpendTracker> {
+    Arc::new(SpendTracker::new(
+        SpendBudget::from_env().unwrap_or_default(),
+    ))
+}
+
 /// Current orchestration service status
 #[derive(Debug, Clone)]
 pub struct OrchestrationStatus {
@@ -661,6 +708,10 @@ impl OrchestrationService {
     pub async fn process_tui_message(&self, message: &str) -> Result<String> {
         debug!("Processing TUI message (quiet mode): {}", message);
 
+        if let Err(overrun) = self.session_spend.check_hard() {
+            return Err(anyhow!("Session budget exhausted: {}", overrun));
+        }
+
         // Проверяем доступность LLM client
         if let Some(ref llm_client_arc) = *self.llm_client.read().await {
             let prompt = format!(
@@ -6,6 +6,7 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use orchestrator::{SpendCheck, SpendOverrun};
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -34,6 +35,10 @@ struct TuiChatState {
     cursor_position: usize,
     scroll_offset: usize,
     is_processing: bool,
+    /// Расход сессии для статус бара
+    spend_summary: Option<String>,
+    /// Сообщение, ожидающее подтверждения превышения мягкого лимита
+    pending_overrun: Option<(String, Vec<SpendOverrun>)>,
 }
 
 impl TuiChatState {
@@ -44,6 +49,8 @@ impl TuiChatState {
             cursor_position: 0,
             scroll_offset: 0,
             is_processing: false,
+            spend_summary: None,
+            pending_overrun: None,
         };
 
         state.add_message(
@@ -437,6 +444,14 @@ fn render_status_bar(f: &mut Frame, area: Rect, state: &TuiChatState) {
             Span::styled("Ready", Style::default().fg(Color::Green))
         },
         Span::raw(" | "),
+        Span::styled(
+            state
+                .spend_summary
+                .as_ref()
+                .map(|summary| format!("💰 {summary} | "))
+                .unwrap_or_default(),
+            Style::default().fg(Color::Cyan),
+        ),
         Span::styled(
             "↑↓ scroll | Enter send | ESC/Ctrl+C exit",
             Style::default().fg(Color::DarkGray),
@@ -612,6 +627,10 @@ async fn run_tui_loop(
                                 "✅ AI система готова к работе!\n💬 Напишите ваше сообщение ниже:"
                                     .to_string(),
                             );
+                            if let Some(service) = current_service(async_state) {
+                                state.spend_summary =
+                                    Some(service.session_spend().report().summary());
+                            }
                         }
                     }
                     service_ready = true;
@@ -636,7 +655,23 @@ async fn run_tui_loop(
                     break;
                 }
 
-                if !state.is_processing {
+                if let Some((message, overruns)) = state.pending_overrun.take() {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Char('д') => {
+                            if let Some(service) = current_service(async_state) {
+                                service.session_spend().approve(&overruns);
+                            }
+                            send_message(state, async_state, message).await;
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('н') => {
+                            state.add_message(
+                                "System".to_string(),
+                                "⏹️ Сообщение не отправлено".to_string(),
+                            );
+                        }
+                        _ => state.pending_overrun = Some((message, overruns)),
+                    }
+                } else if !state.is_processing {
                     match key.code {
                         KeyCode::Enter => {
                             if !state.input.trim().is_empty() {
@@ -644,36 +679,7 @@ async fn run_tui_loop(
                                 state.add_message("You".to_string(), message.clone());
                                 state.input.clear();
                                 state.cursor_position = 0;
-                                state.is_processing = true;
-
-                                // Обработка сообщения (исправлен clippy::await_holding_lock)
-                                let service_opt = {
-                                    async_state
-                                        .service
-                                        .read()
-                                        .ok()
-                                        .and_then(|guard| guard.as_ref().map(Arc::clone))
-                                };
-
-                                if let Some(service) = service_opt {
-                                    match process_message_async(service, message).await {
-                                        Ok(response) => {
-                                            state.add_message("AI".to_string(), response);
-                                        }
-                                        Err(error_msg) => {
-                                            state.add_message(
-                                                "AI".to_string(),
-                                                format!("❌ Ошибка: {error_msg}\n\n💡 Попробуйте:\n• Простой вопрос: 'Привет'\n• Или подождите инициализации системы"),
-                                            );
-                                        }
-                                    }
-                                } else {
-                                    state.add_message(
-                                        "AI".to_string(),
-                                        "⏳ Система пока инициализируется. Попробуйте через несколько секунд.".to_string(),
-                                    );
-                                }
-                                state.is_processing = false;
+                                send_message(state, async_state, message).await;
                             }
                         }
                         KeyCode::Char(c) => {
@@ -724,6 +730,63 @@ async fn run_tui_loop(
     Ok(())
 }
 
+fn current_service(async_state: &AsyncServiceState) -> Option<Arc<services::OrchestrationService>> {
+    async_state
+        .service
+        .read()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(Arc::clone))
+}
+
+/// Отправка сообщения с учётом бюджета сессии: жёсткий лимит блокирует
+/// запрос, мягкий требует подтверждения (y/n)
+async fn send_message(state: &mut TuiChatState, async_state: &AsyncServiceState, message: String) {
+    // Обработка сообщения (исправлен clippy::await_holding_lock)
+    let Some(service) = current_service(async_state) else {
+        state.add_message(
+            "AI".to_string(),
+            "⏳ Система пока инициализируется. Попробуйте через несколько секунд.".to_string(),
+        );
+        return;
+    };
+
+    let spend = service.session_spend();
+    match spend.check() {
+        SpendCheck::Within => {}
+        SpendCheck::Hard(overrun) => {
+            state.add_message(
+                "System".to_string(),
+                format!("❌ {overrun}\n💡 Лимит сессии задаётся в MAGRAY_SESSION_BUDGET"),
+            );
+            return;
+        }
+        SpendCheck::Soft(overruns) => {
+            let warnings: Vec<String> = overruns.iter().map(|o| format!("⚠️ {o}")).collect();
+            state.add_message(
+                "System".to_string(),
+                format!("{}\n❔ Отправить сообщение? (y/n)", warnings.join("\n")),
+            );
+            state.pending_overrun = Some((message, overruns));
+            return;
+        }
+    }
+
+    state.is_processing = true;
+    match process_message_async(service, message).await {
+        Ok(response) => {
+            state.add_message("AI".to_string(), response);
+        }
+        Err(error_msg) => {
+            state.add_message(
+                "AI".to_string(),
+                format!("❌ Ошибка: {error_msg}\n\n💡 Попробуйте:\n• Простой вопрос: 'Привет'\n• Или подождите инициализации системы"),
+            );
+        }
+    }
+    state.spend_summary = Some(spend.report().summary());
+    state.is_processing = false;
+}
+
 /// Асинхронная обработка сообщения для TUI (без workflow логов)
 async fn process_message_async(
     service: Arc<services::OrchestrationService>,
@@ -0,0 +1,221 @@
+//! Связка TUI с задачами и чатом
+//!
+//! Доска задач получает полный список при старте и точечные обновления по
+//! событиям `TodoService::subscribe()`. Действия с доски применяются через
+//! тот же сервис, так что их результат приходит обратно событием.
+
+use crate::services;
+use crate::util::default_tasks_db_path;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::sync::{mpsc::Sender, Arc};
+use todo::{
+    create_default_service, Priority, TaskState, TodoEvent, TodoItem, TodoService, ACTOR_USER,
+};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::{timeout, Duration};
+use ui::components::task_board::{BoardAction, TaskColumn, TaskPriority};
+use ui::tui::TUIEvent;
+use uuid::Uuid;
+
+/// Загрузить доску и запустить обработку событий и действий
+pub async fn spawn_task_board(
+    events: Sender<TUIEvent>,
+    mut actions: UnboundedReceiver<BoardAction>,
+) -> Result<()> {
+    let svc = Arc::new(create_default_service(default_tasks_db_path()).await?);
+    let mut tasks = Vec::new();
+    for task in svc.search("", usize::MAX).await? {
+        tasks.push(with_history(&svc, task).await?);
+    }
+    let _ = events.send(TUIEvent::TasksLoaded(serde_json::to_string(&tasks)?));
+
+    let stream = svc.subscribe();
+    let watcher_svc = Arc::clone(&svc);
+    let watcher_events = events.clone();
+    tokio::spawn(async move {
+        while let Some(event) = stream.next().await {
+            for id in affected_tasks(&watcher_svc, &event).await {
+                let task = match watcher_svc.get_cached(&id).await {
+                    Ok(Some(task)) => with_history(&watcher_svc, task).await.map(Some),
+                    other => other,
+                };
+                match task {
+                    Ok(Some(task)) => {
+                        let Ok(json) = serde_json::to_string(&task) else {
+                            continue;
+                        };
+                        if watcher_events.send(TUIEvent::TaskUpdated(json)).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        let _ = watcher_events.send(TUIEvent::Error(format!("Tasks: {e}")));
+                    }
+                }
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some(action) = actions.recv().await {
+            if let Err(e) = apply_action(&svc, action).await {
+                let _ = events.send(TUIEvent::Error(format!("Task action failed: {e}")));
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Задачи, которые нужно перерисовать после события. Смена состояния
+/// каскадом меняет зависимые задачи, а новая связь — обе стороны.
+async fn affected_tasks(svc: &TodoService, event: &TodoEvent) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    match event {
+        TodoEvent::TaskCreated { task_id, .. }
+        | TodoEvent::TaskUpdated { task_id }
+        | TodoEvent::CommentAdded { task_id, .. }
+        | TodoEvent::TaskFailed { task_id, .. } => ids.push(*task_id),
+        TodoEvent::StateChanged { task_id, .. } | TodoEvent::TaskCompleted { task_id, .. } => {
+            ids.push(*task_id);
+            if let Ok(Some(task)) = svc.get_cached(task_id).await {
+                ids.extend(task.blocks);
+            }
+        }
+        TodoEvent::DependencyAdded {
+            task_id,
+            depends_on,
+        }
+        | TodoEvent::DependencyRemoved {
+            task_id,
+            depends_on,
+        } => {
+            ids.push(*task_id);
+            ids.push(*depends_on);
+        }
+    }
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+    ids
+}
+
+/// Журнал задачи в `metadata.history` для панели деталей доски
+async fn with_history(svc: &TodoService, mut task: TodoItem) -> Result<TodoItem> {
+    let entries = svc
+        .get_history(&task.id)
+        .await?
+        .into_iter()
+        .map(|entry| {
+            let mut value = serde_json::to_value(&entry)?;
+            if let Some(fields) = value.as_object_mut() {
+                fields.remove("id");
+                fields.remove("task_id");
+            }
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    task.metadata
+        .insert("history".to_string(), serde_json::Value::Array(entries));
+    Ok(task)
+}
+
+async fn apply_action(svc: &TodoService, action: BoardAction) -> Result<()> {
+    match action {
+        BoardAction::Move { task_id, state } => {
+            svc.update_state_as(&parse_id(&task_id)?, task_state(state), ACTOR_USER, None)
+                .await
+        }
+        BoardAction::Prioritize { task_id, priority } => {
+            let mut task = load_task(svc, &task_id).await?;
+            task.priority = task_priority(priority);
+            svc.update_task(&task).await
+        }
+        BoardAction::Edit { task_id, title } => {
+            let mut task = load_task(svc, &task_id).await?;
+            task.title = title;
+            svc.update_task(&task).await
+        }
+        BoardAction::Link {
+            task_id,
+            depends_on,
+        } => {
+            svc.add_dependency(&parse_id(&task_id)?, &parse_id(&depends_on)?)
+                .await
+        }
+    }
+}
+
+async fn load_task(svc: &TodoService, task_id: &str) -> Result<TodoItem> {
+    svc.get_cached(&parse_id(task_id)?)
+        .await?
+        .ok_or_else(|| anyhow!("Task not found: {}", task_id))
+}
+
+fn parse_id(task_id: &str) -> Result<Uuid> {
+    Uuid::parse_str(task_id).map_err(|e| anyhow!("Invalid task id '{}': {}", task_id, e))
+}
+
+fn task_state(column: TaskColumn) -> TaskState {
+    match column {
+        TaskColumn::Planned => TaskState::Planned,
+        TaskColumn::Ready => TaskState::Ready,
+        TaskColumn::InProgress => TaskState::InProgress,
+        TaskColumn::Blocked => TaskState::Blocked,
+        TaskColumn::Done => TaskState::Done,
+        TaskColumn::Failed => TaskState::Failed,
+        TaskColumn::Cancelled => TaskState::Cancelled,
+    }
+}
+
+fn task_priority(priority: TaskPriority) -> Priority {
+    match priority {
+        TaskPriority::Low => Priority::Low,
+        TaskPriority::Medium => Priority::Medium,
+        TaskPriority::High => Priority::High,
+        TaskPriority::Critical => Priority::Critical,
+    }
+}
+
+/// Отвечать на сообщения из панели чата. Сервис оркестрации поднимается в
+/// фоне; сообщения, пришедшие до готовности, ждут в очереди.
+pub fn spawn_chat(events: Sender<TUIEvent>, mut messages: UnboundedReceiver<String>) {
+    tokio::spawn(async move {
+        let service = match timeout(
+            Duration::from_secs(10),
+            crate::create_orchestrator_service_silent(),
+        )
+        .await
+        {
+            Ok(Ok(service)) => Ok(service),
+            _ => services::OrchestrationService::with_llm_fallback().await,
+        };
+        let service = match service {
+            Ok(service) => service,
+            Err(e) => {
+                let _ = events.send(TUIEvent::ChatResponse(format!("❌ Чат недоступен: {e}")));
+                return;
+            }
+        };
+
+        while let Some(message) = messages.recv().await {
+            let response = match timeout(
+                Duration::from_secs(30),
+                service.process_tui_message(&message),
+            )
+            .await
+            {
+                Ok(Ok(response)) => response,
+                Ok(Err(e)) => format!("❌ Ошибка обработки: {e}"),
+                Err(_) => "❌ Таймаут при обработке запроса (30 сек)".to_string(),
+            };
+            if events.send(TUIEvent::ChatResponse(response)).is_err() {
+                return;
+            }
+            let _ = events.send(TUIEvent::SpendUpdated(
+                service.session_spend().report().summary(),
+            ));
+        }
+    });
+}
@@ -18,6 +18,20 @@ pub fn default_tasks_db_path() -> PathBuf {
     dir
 }
 
+/// Пользовательские шаблоны задач (`*.json`)
+pub fn templates_dir() -> PathBuf {
+    let mut dir = magray_home();
+    dir.push("templates");
+    dir
+}
+
+/// Временные git worktree изолированных запусков (`--isolate`)
+pub fn worktrees_dir() -> PathBuf {
+    let mut dir = magray_home();
+    dir.push("worktrees");
+    dir
+}
+
 #[allow(dead_code)]
 pub fn artifacts_dir() -> PathBuf {
     let mut dir = magray_home();
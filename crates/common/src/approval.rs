@@ -0,0 +1,777 @@
+//! Approval broker for `PolicyAction::Ask`.
+//!
+//! Один брокер обслуживает все фронтенды: CLI (stdin), TUI (`ActionButtons`)
+//! и удалённых клиентов (через [`ChannelPrompter`], запросы и решения
+//! сериализуются в JSON). Каждое решение публикуется в топик `policy.ask`.
+
+use crate::events;
+use crate::policy::{
+    load_from_path, ArgMatcher, PolicyAction, PolicyDocument, PolicyRule, PolicySubjectKind,
+    ProjectTrust,
+};
+use crate::topics;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use tokio::sync::{mpsc, oneshot};
+
+/// Reason stored in policy rules created by "always allow for this project"
+pub const PROJECT_GRANT_REASON: &str = "approved via ask";
+
+/// What the user is asked to approve
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub subject_kind: PolicySubjectKind,
+    /// Tool name or command (e.g. `memory.backup`)
+    pub subject: String,
+    pub command: Option<String>,
+    pub args: BTreeMap<String, String>,
+    /// Dry-run preview of the side effects, if the tool supports it
+    pub preview: Option<String>,
+    /// 0..=5, see `UsageGuide::risk_score`
+    pub risk_score: u8,
+    /// Why confirmation is required (matched policy rule, usage guide, precheck)
+    pub reason: Option<String>,
+    /// Policy rule that asked for confirmation; grants are scoped to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<PolicyRule>,
+    /// Only a person may answer (releasing secrets): `MAGRAY_AUTO_APPROVE_ASK`
+    /// does not apply
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interactive_only: bool,
+}
+
+impl ApprovalRequest {
+    pub fn tool(name: &str, args: &HashMap<String, String>) -> Self {
+        Self::new(PolicySubjectKind::Tool, name, args)
+    }
+
+    pub fn command(name: &str, args: &HashMap<String, String>) -> Self {
+        Self::new(PolicySubjectKind::Command, name, args)
+    }
+
+    fn new(kind: PolicySubjectKind, subject: &str, args: &HashMap<String, String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject_kind: kind,
+            subject: subject.to_string(),
+            command: None,
+            args: args.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            preview: None,
+            risk_score: 1,
+            reason: None,
+            rule: None,
+            interactive_only: false,
+        }
+    }
+
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = Some(command.into());
+        self
+    }
+
+    pub fn with_preview(mut self, preview: impl Into<String>) -> Self {
+        self.preview = Some(preview.into());
+        self
+    }
+
+    pub fn with_risk_score(mut self, risk_score: u8) -> Self {
+        self.risk_score = risk_score.min(5);
+        self
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    pub fn with_rule(mut self, rule: PolicyRule) -> Self {
+        self.rule = Some(rule);
+        self
+    }
+
+    pub fn interactive_only(mut self) -> Self {
+        self.interactive_only = true;
+        self
+    }
+
+    /// What a session or project grant covers: this subject, the matched
+    /// rule and exactly these arguments
+    pub fn grant_scope(&self) -> String {
+        let mut scope = format!("{} {}", self.subject_kind.to_string(), self.subject);
+        if self.args.is_empty() {
+            scope.push_str(" без аргументов");
+        } else {
+            let keys: Vec<&str> = self.args.keys().map(String::as_str).collect();
+            scope.push_str(&format!(" только с этими значениями: {}", keys.join(", ")));
+        }
+        if let Some(rule) = &self.rule {
+            scope.push_str(&format!(
+                " (правило {:?} '{}')",
+                rule.action, rule.subject_name
+            ));
+        }
+        scope
+    }
+
+    fn grant_key(&self) -> GrantKey {
+        GrantKey {
+            kind: self.subject_kind.clone(),
+            subject: self.subject.clone(),
+            rule: self
+                .rule
+                .as_ref()
+                .and_then(|r| serde_json::to_value(r).ok())
+                .map(|v| v.to_string()),
+            args: self.args.clone(),
+        }
+    }
+}
+
+/// Session grant identity: subject, matched rule (as canonical JSON) and
+/// the sorted arguments
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GrantKey {
+    kind: PolicySubjectKind,
+    subject: String,
+    rule: Option<String>,
+    args: BTreeMap<String, String>,
+}
+
+/// User answer to an [`ApprovalRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    AllowOnce,
+    AllowSession,
+    /// Persisted as an `Allow` rule in the project policy file
+    AllowProject,
+    Deny {
+        reason: Option<String>,
+    },
+}
+
+impl ApprovalDecision {
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, ApprovalDecision::Deny { .. })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalDecision::AllowOnce => "allow_once",
+            ApprovalDecision::AllowSession => "allow_session",
+            ApprovalDecision::AllowProject => "allow_project",
+            ApprovalDecision::Deny { .. } => "deny",
+        }
+    }
+
+    /// Parse a short answer: `y`/`o` once, `s` session, `a`/`p` project,
+    /// anything else denies (text after `n` becomes the reason)
+    pub fn parse_answer(answer: &str) -> Self {
+        let answer = answer.trim();
+        let (head, rest) = match answer.split_once(char::is_whitespace) {
+            Some((h, r)) => (h, r.trim()),
+            None => (answer, ""),
+        };
+        match head.to_lowercase().as_str() {
+            "y" | "yes" | "o" | "once" | "д" | "да" => ApprovalDecision::AllowOnce,
+            "s" | "session" => ApprovalDecision::AllowSession,
+            "a" | "always" | "p" | "project" => ApprovalDecision::AllowProject,
+            _ => ApprovalDecision::Deny {
+                reason: (!rest.is_empty()).then(|| rest.to_string()),
+            },
+        }
+    }
+}
+
+/// Where the decision came from (recorded in `policy.ask` events)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionSource {
+    User,
+    SessionGrant,
+    AutoApprove,
+    NonInteractive,
+}
+
+impl DecisionSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            DecisionSource::User => "user",
+            DecisionSource::SessionGrant => "session_grant",
+            DecisionSource::AutoApprove => "auto_approve",
+            DecisionSource::NonInteractive => "non_interactive",
+        }
+    }
+}
+
+/// Frontend that presents a request to the user
+#[async_trait::async_trait]
+pub trait ApprovalPrompter: Send + Sync {
+    async fn prompt(&self, request: &ApprovalRequest) -> Result<ApprovalDecision>;
+}
+
+/// Terminal prompter for the CLI
+#[derive(Debug, Default)]
+pub struct StdinPrompter;
+
+#[async_trait::async_trait]
+impl ApprovalPrompter for StdinPrompter {
+    async fn prompt(&self, request: &ApprovalRequest) -> Result<ApprovalDecision> {
+        let text = render_request(request);
+        tokio::task::spawn_blocking(move || {
+            use std::io::{self, Write};
+            println!("{text}");
+            print!(
+                "[y] один раз  [s] на сессию  [a] всегда для проекта  [n <причина>] отклонить: "
+            );
+            let _ = io::stdout().flush();
+            let mut answer = String::new();
+            io::stdin()
+                .read_line(&mut answer)
+                .map_err(|e| anyhow!("confirmation failed: {e}"))?;
+            Ok(ApprovalDecision::parse_answer(&answer))
+        })
+        .await
+        .map_err(|e| anyhow!("confirmation failed: {e}"))?
+    }
+}
+
+/// Human readable summary of a request (CLI prompt, logs)
+pub fn render_request(request: &ApprovalRequest) -> String {
+    let mut out = format!(
+        "\n=== Требуется подтверждение: {} {} ===\n",
+        request.subject_kind.to_string(),
+        request.subject
+    );
+    if let Some(cmd) = &request.command {
+        out.push_str(&format!("Команда: {cmd}\n"));
+    }
+    if !request.args.is_empty() {
+        out.push_str("Аргументы:\n");
+        for (k, v) in &request.args {
+            out.push_str(&format!("  {k} = {v}\n"));
+        }
+    }
+    out.push_str(&format!("Риск: {}/5\n", request.risk_score));
+    if let Some(reason) = &request.reason {
+        out.push_str(&format!("Причина: {reason}\n"));
+    }
+    out.push_str(&format!(
+        "Разрешение [s]/[a] действует для: {}\n",
+        request.grant_scope()
+    ));
+    if let Some(preview) = &request.preview {
+        out.push_str("--- Предпросмотр (dry-run) ---\n");
+        out.push_str(preview);
+        if !preview.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// A request waiting for an answer from a TUI or remote client
+#[derive(Debug)]
+pub struct PendingApproval {
+    pub request: ApprovalRequest,
+    responder: oneshot::Sender<ApprovalDecision>,
+}
+
+impl PendingApproval {
+    pub fn respond(self, decision: ApprovalDecision) {
+        let _ = self.responder.send(decision);
+    }
+}
+
+/// Forwards requests over a channel; the receiving side (TUI event loop,
+/// MCP bridge) answers via [`PendingApproval::respond`]. A dropped request
+/// counts as a denial.
+#[derive(Debug, Clone)]
+pub struct ChannelPrompter {
+    tx: mpsc::UnboundedSender<PendingApproval>,
+}
+
+impl ChannelPrompter {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<PendingApproval>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self { tx }, rx)
+    }
+}
+
+#[async_trait::async_trait]
+impl ApprovalPrompter for ChannelPrompter {
+    async fn prompt(&self, request: &ApprovalRequest) -> Result<ApprovalDecision> {
+        let (responder, rx) = oneshot::channel();
+        self.tx
+            .send(PendingApproval {
+                request: request.clone(),
+                responder,
+            })
+            .map_err(|_| anyhow!("approval frontend is not connected"))?;
+        Ok(rx.await.unwrap_or(ApprovalDecision::Deny {
+            reason: Some("approval request dropped".into()),
+        }))
+    }
+}
+
+/// Held while a prompter is asked: concurrent plan steps share the terminal
+/// (or TUI dialog), so their prompts are answered one at a time
+fn prompt_lock() -> &'static tokio::sync::Mutex<()> {
+    static LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(|| tokio::sync::Mutex::new(()))
+}
+
+fn default_prompter_slot() -> &'static RwLock<Option<Arc<dyn ApprovalPrompter>>> {
+    static SLOT: OnceLock<RwLock<Option<Arc<dyn ApprovalPrompter>>>> = OnceLock::new();
+    SLOT.get_or_init(|| RwLock::new(None))
+}
+
+/// Replace the prompter used by brokers built with [`default_prompter`]
+/// (`None` restores stdin). The TUI installs a [`ChannelPrompter`] here so
+/// confirmations are answered in its dialog instead of a hidden terminal.
+pub fn set_default_prompter(prompter: Option<Arc<dyn ApprovalPrompter>>) {
+    if let Ok(mut slot) = default_prompter_slot().write() {
+        *slot = prompter;
+    }
+}
+
+/// Prompter of the current frontend, [`StdinPrompter`] unless replaced
+pub fn default_prompter() -> Arc<dyn ApprovalPrompter> {
+    default_prompter_slot()
+        .read()
+        .ok()
+        .and_then(|slot| slot.clone())
+        .unwrap_or_else(|| Arc::new(StdinPrompter))
+}
+
+/// Routes `Ask` decisions to a prompter and remembers session grants
+pub struct ApprovalBroker {
+    prompter: Arc<dyn ApprovalPrompter>,
+    session_grants: Mutex<HashSet<GrantKey>>,
+    project_policy_path: Option<PathBuf>,
+    project_trust: ProjectTrust,
+    auto_approve: bool,
+    non_interactive: bool,
+}
+
+impl ApprovalBroker {
+    pub fn new(prompter: Arc<dyn ApprovalPrompter>) -> Self {
+        Self {
+            prompter,
+            session_grants: Mutex::new(HashSet::new()),
+            project_policy_path: None,
+            project_trust: ProjectTrust::from_env(),
+            auto_approve: false,
+            non_interactive: false,
+        }
+    }
+
+    /// Broker honoring `MAGRAY_AUTO_APPROVE_ASK` and `MAGRAY_NONINTERACTIVE`
+    pub fn from_env(prompter: Arc<dyn ApprovalPrompter>) -> Self {
+        let flag = |name: &str| std::env::var(name).unwrap_or_default() == "true";
+        Self::new(prompter)
+            .with_auto_approve(flag("MAGRAY_AUTO_APPROVE_ASK"))
+            .with_non_interactive(flag("MAGRAY_NONINTERACTIVE"))
+    }
+
+    /// Policy file receiving "always allow for this project" grants
+    pub fn with_project_policy(mut self, path: impl Into<PathBuf>) -> Self {
+        self.project_policy_path = Some(path.into());
+        self
+    }
+
+    /// Trust store updated when a grant is written to a trusted project file
+    pub fn with_project_trust(mut self, trust: ProjectTrust) -> Self {
+        self.project_trust = trust;
+        self
+    }
+
+    pub fn with_auto_approve(mut self, auto_approve: bool) -> Self {
+        self.auto_approve = auto_approve;
+        self
+    }
+
+    pub fn with_non_interactive(mut self, non_interactive: bool) -> Self {
+        self.non_interactive = non_interactive;
+        self
+    }
+
+    pub fn is_non_interactive(&self) -> bool {
+        self.non_interactive
+    }
+
+    /// Whether an earlier "allow for this session" covers the request
+    /// (same subject, rule and arguments)
+    pub fn has_session_grant(&self, request: &ApprovalRequest) -> bool {
+        self.session_grants
+            .lock()
+            .map(|g| g.contains(&request.grant_key()))
+            .unwrap_or(false)
+    }
+
+    pub fn revoke_session_grants(&self) {
+        if let Ok(mut grants) = self.session_grants.lock() {
+            grants.clear();
+        }
+    }
+
+    /// Whether [`ApprovalBroker::request`] will reach the prompter, i.e. a
+    /// dry-run preview is worth computing
+    pub fn will_prompt(&self, request: &ApprovalRequest) -> bool {
+        !self.auto_approves(request) && !self.non_interactive && !self.has_session_grant(request)
+    }
+
+    fn auto_approves(&self, request: &ApprovalRequest) -> bool {
+        self.auto_approve && !request.interactive_only
+    }
+
+    /// Resolve a request: session grant, env overrides, then the prompter.
+    /// Prompts are serialized; a grant given while waiting for the turn
+    /// answers the request without asking again. Every outcome is published
+    /// to `policy.ask`.
+    pub async fn request(&self, request: ApprovalRequest) -> Result<ApprovalDecision> {
+        let _prompt_turn = if self.will_prompt(&request) {
+            Some(prompt_lock().lock().await)
+        } else {
+            None
+        };
+        let (decision, source) = if self.has_session_grant(&request) {
+            (ApprovalDecision::AllowSession, DecisionSource::SessionGrant)
+        } else if self.auto_approves(&request) {
+            (ApprovalDecision::AllowOnce, DecisionSource::AutoApprove)
+        } else if self.non_interactive {
+            (
+                ApprovalDecision::Deny {
+                    reason: Some("non-interactive".into()),
+                },
+                DecisionSource::NonInteractive,
+            )
+        } else {
+            (self.prompter.prompt(&request).await?, DecisionSource::User)
+        };
+
+        match &decision {
+            ApprovalDecision::AllowSession => {
+                if let Ok(mut grants) = self.session_grants.lock() {
+                    grants.insert(request.grant_key());
+                }
+            }
+            ApprovalDecision::AllowProject => {
+                let granted = match &self.project_policy_path {
+                    Some(path) => grant_in_policy_file(path, &request, &self.project_trust)?,
+                    None => {
+                        tracing::warn!(
+                            "No project policy file configured; '{}' allowed for this session only",
+                            request.subject
+                        );
+                        false
+                    }
+                };
+                if !granted {
+                    if let Ok(mut grants) = self.session_grants.lock() {
+                        grants.insert(request.grant_key());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let reason = match &decision {
+            ApprovalDecision::Deny { reason } => reason.clone(),
+            _ => None,
+        };
+        let evt = serde_json::json!({
+            "id": request.id,
+            "subject_kind": request.subject_kind.to_string(),
+            "tool": request.subject,
+            "command": request.command,
+            "risk_score": request.risk_score,
+            "decision": decision.as_str(),
+            "source": source.as_str(),
+            "reason": reason,
+        });
+        events::publish(topics::TOPIC_POLICY_ASK, evt).await;
+        Ok(decision)
+    }
+}
+
+/// Append an `Allow` rule for the request to a policy file (created if
+/// missing). The rule only matches the same argument values and has the
+/// priority of the rule that asked, so it overrides exactly that decision.
+///
+/// The file stays trusted if it was (or did not exist); a grant written to
+/// an untrusted file takes effect only after `magray policy trust`, and
+/// `Ok(false)` is returned.
+pub fn grant_in_policy_file(
+    path: &Path,
+    request: &ApprovalRequest,
+    trust: &ProjectTrust,
+) -> Result<bool> {
+    let trusted = !path.exists() || trust.is_trusted(path);
+    let mut doc = if path.exists() {
+        load_from_path(path)?
+    } else {
+        PolicyDocument::default()
+    };
+    let mut rule = PolicyRule::new(
+        request.subject_kind.clone(),
+        &request.subject,
+        PolicyAction::Allow,
+    )
+    .with_reason(PROJECT_GRANT_REASON)
+    .with_priority(request.rule.as_ref().map(|r| r.priority).unwrap_or(0));
+    for (k, v) in &request.args {
+        rule = rule.with_arg_matcher(k, ArgMatcher::Exact(v.clone()));
+    }
+    if !doc.rules.contains(&rule) {
+        doc.rules.push(rule);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&doc)?)?;
+    if trusted {
+        trust.trust(path)?;
+    } else {
+        tracing::warn!(
+            "'{}' written to untrusted {}; run `magray policy trust` to apply it in later sessions",
+            request.subject,
+            path.display()
+        );
+    }
+    Ok(trusted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedPrompter(ApprovalDecision);
+
+    #[async_trait::async_trait]
+    impl ApprovalPrompter for FixedPrompter {
+        async fn prompt(&self, _request: &ApprovalRequest) -> Result<ApprovalDecision> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Allows for the session and records how many prompts overlapped
+    #[derive(Default)]
+    struct CountingPrompter {
+        prompts: std::sync::atomic::AtomicUsize,
+        active: std::sync::atomic::AtomicUsize,
+        max_active: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl ApprovalPrompter for CountingPrompter {
+        async fn prompt(&self, _request: &ApprovalRequest) -> Result<ApprovalDecision> {
+            use std::sync::atomic::Ordering;
+            self.prompts.fetch_add(1, Ordering::SeqCst);
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_active.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(ApprovalDecision::AllowSession)
+        }
+    }
+
+    #[test]
+    fn parse_answers() {
+        assert_eq!(
+            ApprovalDecision::parse_answer("y"),
+            ApprovalDecision::AllowOnce
+        );
+        assert_eq!(
+            ApprovalDecision::parse_answer("S\n"),
+            ApprovalDecision::AllowSession
+        );
+        assert_eq!(
+            ApprovalDecision::parse_answer("a"),
+            ApprovalDecision::AllowProject
+        );
+        assert_eq!(
+            ApprovalDecision::parse_answer("n too risky"),
+            ApprovalDecision::Deny {
+                reason: Some("too risky".into())
+            }
+        );
+        assert_eq!(
+            ApprovalDecision::parse_answer(""),
+            ApprovalDecision::Deny { reason: None }
+        );
+    }
+
+    #[tokio::test]
+    async fn session_grant_skips_prompt() {
+        let broker = ApprovalBroker::new(Arc::new(FixedPrompter(ApprovalDecision::AllowSession)));
+        let args = |p: &str| HashMap::from([("path".to_string(), p.to_string())]);
+        let req = ApprovalRequest::tool("file_write", &args("src/a.rs"));
+        assert_eq!(
+            broker.request(req.clone()).await.expect("first request"),
+            ApprovalDecision::AllowSession
+        );
+        assert!(broker.has_session_grant(&ApprovalRequest::tool("file_write", &args("src/a.rs"))));
+        // other arguments, subject kind or rule are not covered
+        assert!(
+            !broker.has_session_grant(&ApprovalRequest::tool("file_write", &args("/etc/hosts")))
+        );
+        assert!(
+            !broker.has_session_grant(&ApprovalRequest::command("file_write", &args("src/a.rs")))
+        );
+        let ruled = req.clone().with_rule(PolicyRule::new(
+            PolicySubjectKind::Tool,
+            "file_*",
+            PolicyAction::Ask,
+        ));
+        assert!(!broker.has_session_grant(&ruled));
+        broker.revoke_session_grants();
+        assert!(!broker.has_session_grant(&req));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_requests_prompt_one_at_a_time() {
+        use std::sync::atomic::Ordering;
+        let prompter = Arc::new(CountingPrompter::default());
+        let broker = Arc::new(ApprovalBroker::new(prompter.clone()));
+        let args = |p: &str| HashMap::from([("path".to_string(), p.to_string())]);
+        let requests = [
+            ApprovalRequest::tool("file_write", &args("a.rs")),
+            ApprovalRequest::tool("file_write", &args("b.rs")),
+            ApprovalRequest::tool("file_write", &args("a.rs")),
+        ];
+        let handles: Vec<_> = requests
+            .into_iter()
+            .map(|req| {
+                let broker = Arc::clone(&broker);
+                tokio::spawn(async move { broker.request(req).await })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(
+                handle.await.expect("join").expect("request"),
+                ApprovalDecision::AllowSession
+            );
+        }
+        assert_eq!(prompter.max_active.load(Ordering::SeqCst), 1);
+        // the repeated request is answered by the grant given while it waited
+        assert_eq!(prompter.prompts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn non_interactive_denies_unless_auto_approved() {
+        let broker = ApprovalBroker::new(Arc::new(FixedPrompter(ApprovalDecision::AllowOnce)))
+            .with_non_interactive(true);
+        let req = ApprovalRequest::tool("shell_exec", &HashMap::new());
+        assert!(!broker
+            .request(req.clone())
+            .await
+            .expect("request")
+            .is_allowed());
+        let broker = broker.with_auto_approve(true);
+        assert!(broker.request(req).await.expect("request").is_allowed());
+        // releasing secrets is never auto-approved
+        let secrets = ApprovalRequest::command("secrets.send", &HashMap::new()).interactive_only();
+        assert!(!broker.request(secrets).await.expect("request").is_allowed());
+    }
+
+    #[tokio::test]
+    async fn project_grant_is_written_to_policy_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join(".magray").join("policy.json");
+        let trust = ProjectTrust::at(dir.path().join("trusted_projects.json"));
+        let broker = ApprovalBroker::new(Arc::new(FixedPrompter(ApprovalDecision::AllowProject)))
+            .with_project_policy(&path)
+            .with_project_trust(trust.clone());
+        let url = |u: &str| HashMap::from([("url".to_string(), u.to_string())]);
+        let ask =
+            PolicyRule::new(PolicySubjectKind::Tool, "web_*", PolicyAction::Ask).with_priority(5);
+        let req =
+            ApprovalRequest::tool("web_fetch", &url("https://docs.rs")).with_rule(ask.clone());
+        assert!(render_request(&req).contains("только с этими значениями: url"));
+        broker.request(req.clone()).await.expect("request");
+        broker.request(req).await.expect("request");
+        let doc = load_from_path(&path).expect("policy file");
+        assert_eq!(doc.rules.len(), 1);
+        assert_eq!(doc.rules[0].subject_name, "web_fetch");
+        assert_eq!(doc.rules[0].action, PolicyAction::Allow);
+        assert_eq!(doc.rules[0].priority, 5);
+        // the file the broker created stays trusted after each grant
+        assert!(trust.is_trusted(&path));
+
+        // the grant overrides the asking rule for the same arguments only
+        let engine = crate::policy::PolicyEngine::new()
+            .with_rules(std::iter::once(ask).chain(doc.rules).collect());
+        let d = engine.evaluate_tool("web_fetch", &url("https://docs.rs"));
+        assert_eq!(d.action, PolicyAction::Allow);
+        let d = engine.evaluate_tool("web_fetch", &url("https://evil.example"));
+        assert_eq!(d.action, PolicyAction::Ask);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial(default_prompter)]
+    async fn default_prompter_can_be_replaced() {
+        /// Puts back whatever prompter was installed, even if the test panics
+        struct RestorePrompter(Option<Arc<dyn ApprovalPrompter>>);
+        impl Drop for RestorePrompter {
+            fn drop(&mut self) {
+                set_default_prompter(self.0.take());
+            }
+        }
+
+        let previous = default_prompter_slot()
+            .read()
+            .ok()
+            .and_then(|slot| slot.clone());
+        let _restore = RestorePrompter(previous);
+        let (prompter, mut rx) = ChannelPrompter::new();
+        set_default_prompter(Some(Arc::new(prompter)));
+        let broker = ApprovalBroker::new(default_prompter());
+        tokio::spawn(async move {
+            if let Some(pending) = rx.recv().await {
+                pending.respond(ApprovalDecision::AllowOnce);
+            }
+        });
+        let decision = broker
+            .request(ApprovalRequest::tool("file_write", &HashMap::new()))
+            .await
+            .expect("request");
+        assert_eq!(decision, ApprovalDecision::AllowOnce);
+    }
+
+    #[tokio::test]
+    async fn channel_prompter_round_trip() {
+        let (prompter, mut rx) = ChannelPrompter::new();
+        let broker = ApprovalBroker::new(Arc::new(prompter));
+        let mut ask_events = events::subscribe(topics::TOPIC_POLICY_ASK).await;
+        tokio::spawn(async move {
+            if let Some(pending) = rx.recv().await {
+                assert_eq!(pending.request.subject, "git_commit");
+                pending.respond(ApprovalDecision::Deny {
+                    reason: Some("not now".into()),
+                });
+            }
+        });
+        let decision = broker
+            .request(ApprovalRequest::tool("git_commit", &HashMap::new()).with_risk_score(3))
+            .await
+            .expect("request");
+        assert_eq!(
+            decision,
+            ApprovalDecision::Deny {
+                reason: Some("not now".into())
+            }
+        );
+        let evt = loop {
+            let evt = ask_events.recv().await.expect("policy.ask event");
+            if evt.payload["tool"] == "git_commit" {
+                break evt;
+            }
+        };
+        assert_eq!(evt.payload["decision"], "deny");
+        assert_eq!(evt.payload["reason"], "not now");
+    }
+}
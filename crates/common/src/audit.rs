@@ -0,0 +1,753 @@
+//! Durable, append-only audit log with a hash chain.
+//!
+//! Каждая запись содержит `prev_hash` и `hash = sha256(seq|ts|topic|source|payload|prev_hash)`,
+//! поэтому удаление, вставка или правка любой строки обнаруживается `verify()`.
+//! Лог пишется в JSONL-сегменты `<dir>/audit.jsonl`; при превышении
+//! `max_file_bytes` сегмент ротируется в `audit-<last_seq>.jsonl`, старые
+//! сегменты удаляются по `max_files` и `retention_days`. Последняя удалённая
+//! запись сохраняется как якорь (`audit.anchor.json`): первая сохранившаяся
+//! запись должна ссылаться на него, иначе `verify()` сообщит о потере начала.
+//! Запись и ротация выполняются под файловой блокировкой `audit.lock`, так что
+//! CLI и TUI могут писать в один лог одновременно.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const CURRENT_SEGMENT: &str = "audit.jsonl";
+const LOCK_FILE: &str = "audit.lock";
+const ANCHOR_FILE: &str = "audit.anchor.json";
+
+/// Topics persisted by [`record_event`] (event bus hooks)
+pub const AUDITED_TOPICS: &[&str] = &[
+    "tool.invoked",
+    "policy.block",
+    "policy.ask",
+    "policy.violation",
+    "policy.emergency",
+    "sandbox.violation",
+    "secrets.detected",
+    "mcp.tool_invocation",
+    "mcp.security_violation",
+    "mcp.audit_trail",
+    "llm.request",
+];
+
+/// Payload keys never written for `llm.*` topics (metadata only)
+const LLM_CONTENT_KEYS: &[&str] = &[
+    "prompt",
+    "message",
+    "messages",
+    "content",
+    "response",
+    "completion",
+    "text",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditConfig {
+    pub dir: PathBuf,
+    /// Rotate the current segment once it grows beyond this size
+    pub max_file_bytes: u64,
+    /// Keep at most this many rotated segments (current one not counted)
+    pub max_files: usize,
+    /// Delete rotated segments older than this many days
+    pub retention_days: Option<u32>,
+}
+
+impl AuditConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_file_bytes: 10 * 1024 * 1024,
+            max_files: 20,
+            retention_days: Some(90),
+        }
+    }
+
+    /// `<magray_home>/audit` with overrides from `MAGRAY_AUDIT_DIR`,
+    /// `MAGRAY_AUDIT_MAX_BYTES`, `MAGRAY_AUDIT_MAX_FILES`, `MAGRAY_AUDIT_RETENTION_DAYS`
+    /// (`0` disables age-based retention)
+    pub fn from_env(magray_home: &Path) -> Self {
+        let dir = std::env::var("MAGRAY_AUDIT_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| magray_home.join("audit"));
+        let mut cfg = Self::new(dir);
+        if let Some(v) = env_parse::<u64>("MAGRAY_AUDIT_MAX_BYTES") {
+            cfg.max_file_bytes = v.max(1024);
+        }
+        if let Some(v) = env_parse::<usize>("MAGRAY_AUDIT_MAX_FILES") {
+            cfg.max_files = v;
+        }
+        if let Some(v) = env_parse::<u32>("MAGRAY_AUDIT_RETENTION_DAYS") {
+            cfg.retention_days = (v > 0).then_some(v);
+        }
+        cfg
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub ts: DateTime<Utc>,
+    pub topic: String,
+    pub source: String,
+    pub payload: Value,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seq.to_string());
+        hasher.update(b"|");
+        hasher.update(self.ts.to_rfc3339_opts(SecondsFormat::Nanos, true));
+        hasher.update(b"|");
+        hasher.update(&self.topic);
+        hasher.update(b"|");
+        hasher.update(&self.source);
+        hasher.update(b"|");
+        hasher.update(canonical_json(&self.payload));
+        hasher.update(b"|");
+        hasher.update(&self.prev_hash);
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// JSON with recursively sorted object keys, independent of serde_json's
+/// map ordering features
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let body: Vec<String> = keys
+                .into_iter()
+                .map(|k| format!("{}:{}", Value::String(k.clone()), canonical_json(&map[k])))
+                .collect();
+            format!("{{{}}}", body.join(","))
+        }
+        Value::Array(items) => {
+            let body: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", body.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Result of [`AuditLog::verify`]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VerifyReport {
+    pub entries: u64,
+    pub first_seq: Option<u64>,
+    pub last_seq: Option<u64>,
+    /// Older segments were pruned; the first retained entry continues the
+    /// recorded pruning anchor
+    pub truncated: bool,
+    pub errors: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Filter for [`AuditLog::query`]
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub since: Option<DateTime<Utc>>,
+    /// Topic prefix (`policy` matches `policy.ask`, `policy.block`)
+    pub topic: Option<String>,
+    /// Case-insensitive substring of the serialized entry
+    pub text: Option<String>,
+    /// Keep only the last N matches
+    pub limit: Option<usize>,
+}
+
+/// Last entry removed by rotation/retention: the retained chain must continue it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PruneAnchor {
+    pub seq: u64,
+    pub hash: String,
+}
+
+pub struct AuditLog {
+    config: AuditConfig,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn open(config: AuditConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)
+            .with_context(|| format!("cannot create audit dir {}", config.dir.display()))?;
+        Ok(Self {
+            config,
+            lock: Mutex::new(()),
+        })
+    }
+
+    pub fn config(&self) -> &AuditConfig {
+        &self.config
+    }
+
+    fn current_path(&self) -> PathBuf {
+        self.config.dir.join(CURRENT_SEGMENT)
+    }
+
+    /// Segments oldest first; the current segment is always last
+    pub fn segments(&self) -> Result<Vec<PathBuf>> {
+        let mut rotated: Vec<PathBuf> = fs::read_dir(&self.config.dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.starts_with("audit-") && n.ends_with(".jsonl"))
+                    .unwrap_or(false)
+            })
+            .collect();
+        rotated.sort();
+        let current = self.current_path();
+        if current.exists() {
+            rotated.push(current);
+        }
+        Ok(rotated)
+    }
+
+    fn anchor_path(&self) -> PathBuf {
+        self.config.dir.join(ANCHOR_FILE)
+    }
+
+    /// Pruning anchor, `None` while the log still starts at genesis
+    pub fn anchor(&self) -> Result<Option<PruneAnchor>> {
+        let path = self.anchor_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let anchor = serde_json::from_str(&content)
+            .with_context(|| format!("corrupted audit anchor {}", path.display()))?;
+        Ok(Some(anchor))
+    }
+
+    /// Run `f` holding both the in-process mutex and an exclusive lock on
+    /// `audit.lock`, so other processes cannot interleave their appends
+    fn locked<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| anyhow!("audit log lock poisoned"))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.config.dir.join(LOCK_FILE))?;
+        let mut file_lock = fd_lock::RwLock::new(file);
+        let _file_guard = file_lock.write().context("cannot lock audit log")?;
+        f()
+    }
+
+    /// Append one entry. The chain head is re-read from disk under the file
+    /// lock on every call so that concurrent processes extend the same chain.
+    pub fn append(&self, topic: &str, source: &str, payload: Value) -> Result<AuditEntry> {
+        self.locked(|| self.append_locked(topic, source, payload))
+    }
+
+    fn append_locked(&self, topic: &str, source: &str, payload: Value) -> Result<AuditEntry> {
+        let (prev_seq, prev_hash) = match self.last_entry()? {
+            Some(last) => (Some(last.seq), last.hash),
+            None => match self.anchor()? {
+                Some(anchor) => (Some(anchor.seq), anchor.hash),
+                None => (None, GENESIS_HASH.to_string()),
+            },
+        };
+        let mut entry = AuditEntry {
+            seq: prev_seq.map(|s| s + 1).unwrap_or(0),
+            ts: Utc::now(),
+            topic: topic.to_string(),
+            source: source.to_string(),
+            payload: sanitize_payload(topic, payload),
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let path = self.current_path();
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+
+        if file.metadata()?.len() >= self.config.max_file_bytes {
+            let rotated = self
+                .config
+                .dir
+                .join(format!("audit-{:012}.jsonl", entry.seq));
+            fs::rename(&path, rotated)?;
+            self.retention_locked()?;
+        }
+        Ok(entry)
+    }
+
+    /// Delete rotated segments beyond `max_files` or older than `retention_days`
+    pub fn apply_retention(&self) -> Result<usize> {
+        self.locked(|| self.retention_locked())
+    }
+
+    /// Only the oldest segments are removed, so the retained ones stay one
+    /// chain; the last removed entry becomes the pruning anchor before any
+    /// file is deleted
+    fn retention_locked(&self) -> Result<usize> {
+        let current = self.current_path();
+        let rotated: Vec<PathBuf> = self
+            .segments()?
+            .into_iter()
+            .filter(|p| p != &current)
+            .collect();
+        let mut remove = rotated.len().saturating_sub(self.config.max_files);
+        if let Some(days) = self.config.retention_days {
+            let cutoff = std::time::SystemTime::now()
+                - std::time::Duration::from_secs(u64::from(days) * 86_400);
+            let expired = rotated
+                .iter()
+                .take_while(|p| {
+                    fs::metadata(p)
+                        .and_then(|m| m.modified())
+                        .map(|t| t < cutoff)
+                        .unwrap_or(false)
+                })
+                .count();
+            remove = remove.max(expired);
+        }
+        let Some(newest_removed) = remove.checked_sub(1).map(|i| &rotated[i]) else {
+            return Ok(0);
+        };
+        if let Some(line) = last_line(newest_removed)? {
+            let last: AuditEntry = serde_json::from_str(&line)
+                .with_context(|| format!("corrupted audit tail in {}", newest_removed.display()))?;
+            let anchor = PruneAnchor {
+                seq: last.seq,
+                hash: last.hash,
+            };
+            fs::write(self.anchor_path(), serde_json::to_string(&anchor)?)?;
+        }
+        for seg in &rotated[..remove] {
+            fs::remove_file(seg)?;
+        }
+        Ok(remove)
+    }
+
+    /// Last entry of the newest non-empty segment
+    pub fn last_entry(&self) -> Result<Option<AuditEntry>> {
+        for seg in self.segments()?.iter().rev() {
+            if let Some(line) = last_line(seg)? {
+                let entry = serde_json::from_str(&line)
+                    .with_context(|| format!("corrupted audit tail in {}", seg.display()))?;
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// All entries, oldest first
+    pub fn read_all(&self) -> Result<Vec<AuditEntry>> {
+        let mut out = Vec::new();
+        for seg in self.segments()? {
+            let reader = BufReader::new(File::open(&seg)?);
+            for (i, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry = serde_json::from_str(&line).with_context(|| {
+                    format!("invalid audit entry at {}:{}", seg.display(), i + 1)
+                })?;
+                out.push(entry);
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let needle = query.text.as_ref().map(|t| t.to_lowercase());
+        let mut matched: Vec<AuditEntry> = self
+            .read_all()?
+            .into_iter()
+            .filter(|e| query.since.map(|s| e.ts >= s).unwrap_or(true))
+            .filter(|e| {
+                query
+                    .topic
+                    .as_ref()
+                    .map(|t| e.topic == *t || e.topic.starts_with(&format!("{t}.")))
+                    .unwrap_or(true)
+            })
+            .filter(|e| match &needle {
+                Some(n) => serde_json::to_string(e)
+                    .map(|s| s.to_lowercase().contains(n))
+                    .unwrap_or(false),
+                None => true,
+            })
+            .collect();
+        if let Some(limit) = query.limit {
+            let skip = matched.len().saturating_sub(limit);
+            matched.drain(..skip);
+        }
+        Ok(matched)
+    }
+
+    /// Recompute every hash and check sequence numbers and links. The first
+    /// entry must start at genesis or continue the pruning anchor
+    pub fn verify(&self) -> Result<VerifyReport> {
+        let mut report = VerifyReport::default();
+        let anchor = self.anchor()?;
+        report.truncated = anchor.is_some();
+        let mut prev: Option<(u64, String)> = None;
+        for seg in self.segments()? {
+            let reader = BufReader::new(File::open(&seg)?);
+            for (i, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let at = format!("{}:{}", seg.display(), i + 1);
+                let entry: AuditEntry = match serde_json::from_str(&line) {
+                    Ok(e) => e,
+                    Err(e) => {
+                        report.errors.push(format!("{at}: unparsable entry: {e}"));
+                        prev = None;
+                        continue;
+                    }
+                };
+                report.entries += 1;
+                report.first_seq.get_or_insert(entry.seq);
+                report.last_seq = Some(entry.seq);
+                if entry.compute_hash() != entry.hash {
+                    report.errors.push(format!(
+                        "{at}: seq {} hash mismatch (entry modified)",
+                        entry.seq
+                    ));
+                }
+                match &prev {
+                    Some((seq, hash)) => {
+                        if entry.seq != seq + 1 {
+                            report.errors.push(format!(
+                                "{at}: seq {} follows {} (entries missing or reordered)",
+                                entry.seq, seq
+                            ));
+                        }
+                        if &entry.prev_hash != hash {
+                            report
+                                .errors
+                                .push(format!("{at}: seq {} broken chain link", entry.seq));
+                        }
+                    }
+                    None if report.entries == 1 => {
+                        let (seq, hash) = anchor
+                            .as_ref()
+                            .map(|a| (a.seq + 1, a.hash.as_str()))
+                            .unwrap_or((0, GENESIS_HASH));
+                        if entry.seq != seq || entry.prev_hash != hash {
+                            report.errors.push(match &anchor {
+                                Some(a) => format!(
+                                    "{at}: seq {} does not continue the pruning anchor (seq {}), oldest entries deleted",
+                                    entry.seq, a.seq
+                                ),
+                                None => format!(
+                                    "{at}: seq {} does not start at genesis, oldest entries deleted",
+                                    entry.seq
+                                ),
+                            });
+                        }
+                    }
+                    None => {}
+                }
+                prev = Some((entry.seq, entry.hash.clone()));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Write matching entries as JSONL, returns the number written
+    pub fn export_jsonl<W: Write>(
+        &self,
+        since: Option<DateTime<Utc>>,
+        out: &mut W,
+    ) -> Result<usize> {
+        let entries = self.query(&AuditQuery {
+            since,
+            ..Default::default()
+        })?;
+        for e in &entries {
+            serde_json::to_writer(&mut *out, e)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(entries.len())
+    }
+}
+
+fn last_line(path: &Path) -> Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut window: u64 = 64 * 1024;
+    loop {
+        let start = len.saturating_sub(window);
+        file.seek(SeekFrom::Start(start))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        let text = String::from_utf8_lossy(&buf);
+        let trimmed = text.trim_end();
+        match trimmed.rfind('\n') {
+            Some(idx) => return Ok(Some(trimmed[idx + 1..].to_string())),
+            None if start == 0 => {
+                return Ok((!trimmed.is_empty()).then(|| trimmed.to_string()));
+            }
+            None => window *= 4,
+        }
+    }
+}
+
+/// Strip prompt/response bodies from LLM events and mask secrets everywhere
+fn sanitize_payload(topic: &str, mut payload: Value) -> Value {
+    crate::redaction::global_redactor().mask_value(&mut payload);
+    if !topic.starts_with("llm.") {
+        return payload;
+    }
+    match payload {
+        Value::Object(mut map) => {
+            for key in LLM_CONTENT_KEYS {
+                map.remove(*key);
+            }
+            Value::Object(map)
+        }
+        _ => Value::Null,
+    }
+}
+
+/// Parse `--since`: RFC 3339, `YYYY-MM-DD` or a relative age (`30m`, `24h`, `7d`)
+pub fn parse_since(input: &str) -> Result<DateTime<Utc>> {
+    let s = input.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        if let Some(t) = d.and_hms_opt(0, 0, 0) {
+            return Ok(t.and_utc());
+        }
+    }
+    let (num, unit) = s.split_at(s.len().saturating_sub(1));
+    let n: i64 = num
+        .parse()
+        .map_err(|_| anyhow!("invalid --since '{input}' (use RFC 3339, YYYY-MM-DD or 24h/7d)"))?;
+    let delta = match unit {
+        "s" => chrono::Duration::seconds(n),
+        "m" => chrono::Duration::minutes(n),
+        "h" => chrono::Duration::hours(n),
+        "d" => chrono::Duration::days(n),
+        "w" => chrono::Duration::weeks(n),
+        _ => return Err(anyhow!("invalid --since unit in '{input}'")),
+    };
+    Ok(Utc::now() - delta)
+}
+
+static GLOBAL_AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+/// Enable process-wide auditing (CLI startup). Until this is called
+/// [`record_event`] is a no-op, so library users and tests stay side-effect free.
+pub fn init_global(config: AuditConfig) -> Result<&'static AuditLog> {
+    if let Some(log) = GLOBAL_AUDIT_LOG.get() {
+        return Ok(log);
+    }
+    let log = AuditLog::open(config)?;
+    Ok(GLOBAL_AUDIT_LOG.get_or_init(|| log))
+}
+
+pub fn global() -> Option<&'static AuditLog> {
+    GLOBAL_AUDIT_LOG.get()
+}
+
+/// Persist an event if auditing is enabled and the topic is audited.
+/// Failures are logged, never propagated to the caller.
+pub fn record_event(topic: &str, source: &str, payload: &Value) {
+    let Some(log) = global() else {
+        return;
+    };
+    if !AUDITED_TOPICS.contains(&topic) {
+        return;
+    }
+    if let Err(e) = log.append(topic, source, payload.clone()) {
+        tracing::warn!("audit log append failed for {}: {}", topic, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(dir: &Path) -> AuditLog {
+        AuditLog::open(AuditConfig::new(dir)).expect("open audit log")
+    }
+
+    #[test]
+    fn chain_appends_and_verifies() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let log = open(tmp.path());
+        for i in 0..5 {
+            log.append(
+                "tool.invoked",
+                "test",
+                serde_json::json!({"tool": "file_read", "i": i}),
+            )
+            .expect("append");
+        }
+        // A second handle continues the same chain
+        let log2 = open(tmp.path());
+        let e = log2
+            .append(
+                "policy.ask",
+                "test",
+                serde_json::json!({"decision": "deny"}),
+            )
+            .expect("append");
+        assert_eq!(e.seq, 5);
+        let report = log2.verify().expect("verify");
+        assert!(report.is_valid(), "{:?}", report.errors);
+        assert_eq!(report.entries, 6);
+        assert!(!report.truncated);
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let log = open(tmp.path());
+        for i in 0..3 {
+            log.append("tool.invoked", "test", serde_json::json!({"i": i}))
+                .expect("append");
+        }
+        let path = tmp.path().join(CURRENT_SEGMENT);
+        let content = fs::read_to_string(&path).expect("read");
+        fs::write(&path, content.replacen("\"i\":1", "\"i\":7", 1)).expect("write");
+        let report = log.verify().expect("verify");
+        assert!(!report.is_valid());
+        assert!(report.errors[0].contains("seq 1 hash mismatch"));
+
+        // Dropping a line breaks sequence and link
+        let lines: Vec<&str> = content.lines().collect();
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).expect("write");
+        let report = log.verify().expect("verify");
+        assert!(report
+            .errors
+            .iter()
+            .any(|e| e.contains("broken chain link")));
+    }
+
+    #[test]
+    fn rotation_retention_and_query() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let mut cfg = AuditConfig::new(tmp.path());
+        cfg.max_file_bytes = 600;
+        cfg.max_files = 2;
+        let log = AuditLog::open(cfg).expect("open");
+        for i in 0..20 {
+            let topic = if i % 2 == 0 {
+                "tool.invoked"
+            } else {
+                "llm.request"
+            };
+            log.append(
+                topic,
+                "test",
+                serde_json::json!({"i": i, "prompt": "secret prompt", "model": "m"}),
+            )
+            .expect("append");
+        }
+        assert!(log.segments().expect("segments").len() <= 3);
+        let report = log.verify().expect("verify");
+        assert!(report.is_valid(), "{:?}", report.errors);
+        assert!(report.truncated);
+        let anchor = log.anchor().expect("anchor").expect("pruned");
+        assert_eq!(report.first_seq, Some(anchor.seq + 1));
+
+        let llm = log
+            .query(&AuditQuery {
+                topic: Some("llm".into()),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .expect("query");
+        assert_eq!(llm.len(), 2);
+        assert!(llm.iter().all(|e| e.payload.get("prompt").is_none()));
+        assert_eq!(llm[1].payload["i"], 19);
+
+        let mut out = Vec::new();
+        let n = log
+            .export_jsonl(Some(Utc::now() - chrono::Duration::hours(1)), &mut out)
+            .expect("export");
+        assert_eq!(String::from_utf8(out).expect("utf8").lines().count(), n);
+    }
+
+    #[test]
+    fn pruned_head_must_match_anchor() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let mut cfg = AuditConfig::new(tmp.path());
+        cfg.max_file_bytes = 600;
+        cfg.max_files = 10;
+        let log = AuditLog::open(cfg).expect("open");
+        for i in 0..12 {
+            log.append("tool.invoked", "test", serde_json::json!({"i": i}))
+                .expect("append");
+        }
+        assert!(log.anchor().expect("anchor").is_none());
+        let oldest = log.segments().expect("segments").remove(0);
+        fs::remove_file(oldest).expect("delete oldest segment");
+        let report = log.verify().expect("verify");
+        assert!(!report.is_valid());
+        assert!(report.errors[0].contains("does not start at genesis"));
+    }
+
+    #[test]
+    fn separate_handles_share_one_chain() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let log = open(tmp.path());
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        log.append("tool.invoked", "test", serde_json::json!({"t": t, "i": i}))
+                            .expect("append");
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().expect("writer");
+        }
+        let log = open(tmp.path());
+        let report = log.verify().expect("verify");
+        assert!(report.is_valid(), "{:?}", report.errors);
+        assert_eq!(report.entries, 100);
+        assert_eq!(report.last_seq, Some(99));
+    }
+
+    #[test]
+    fn since_parsing() {
+        let t = parse_since("2024-05-01T10:00:00Z").expect("rfc3339");
+        assert_eq!(t.to_rfc3339(), "2024-05-01T10:00:00+00:00");
+        assert!(parse_since("2024-05-01").is_ok());
+        let d = parse_since("7d").expect("relative");
+        assert!(Utc::now() - d >= chrono::Duration::days(7));
+        assert!(parse_since("yesterday").is_err());
+    }
+}
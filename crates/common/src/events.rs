@@ -10,6 +10,7 @@ lazy_static! {
 }
 
 pub async fn publish(topic: Topic, payload: serde_json::Value) {
+    crate::audit::record_event(topic.0, "event_bus", &payload);
     GLOBAL_EVENT_BUS.publish(topic, payload).await;
 }
 
@@ -1,3 +1,5 @@
+pub mod approval;
+pub mod audit;
 pub mod comprehensive_errors;
 pub mod config_base;
 pub mod event_bus;
@@ -5,6 +7,7 @@ pub mod events;
 pub mod input_validation;
 pub mod macros;
 pub mod policy;
+pub mod redaction;
 pub mod sandbox_config;
 pub mod scheduler;
 pub mod service_macros;
@@ -2,14 +2,15 @@ use anyhow::Result as AnyResult;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // CRITICAL P0.2.6: Import EventPublisher trait for production EventBus integration
 use magray_core::events::EventPublisher;
 
 // CRITICAL SECURITY IMPORTS: For emergency policy disable mechanism
+use crate::redaction::RedactionPolicy;
 use chrono::{DateTime, Utc};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use tracing::{error, info, warn};
 
 // CRITICAL SECURITY: Policy Topics constants - matches core/events/topics.rs for consistency
@@ -131,15 +132,345 @@ impl PolicySubjectKind {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PolicyRule {
     pub subject_kind: PolicySubjectKind,
+    /// Exact name, `*` or a glob (`file_*`)
     pub subject_name: String,
     pub when_contains_args: Option<HashMap<String, String>>, // match if all key/value present
     pub action: PolicyAction,
     pub reason: Option<String>,
+    /// Pattern matchers per argument; all of them must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when_args: Option<HashMap<String, ArgMatcher>>,
+    /// Higher priority wins; among equal priorities the later rule wins
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+    /// The rule is ignored after this moment (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Project directory the rule belongs to; relative `path` patterns resolve
+    /// against it. Filled in when a per-project policy file is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<PathBuf>,
+}
+
+fn is_zero(v: &i32) -> bool {
+    *v == 0
+}
+
+impl PolicyRule {
+    pub fn new(kind: PolicySubjectKind, subject_name: &str, action: PolicyAction) -> Self {
+        Self {
+            subject_kind: kind,
+            subject_name: subject_name.to_string(),
+            when_contains_args: None,
+            action,
+            reason: None,
+            when_args: None,
+            priority: 0,
+            expires_at: None,
+            scope: None,
+        }
+    }
+
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+
+    pub fn with_arg_matcher(mut self, arg: &str, matcher: ArgMatcher) -> Self {
+        self.when_args
+            .get_or_insert_with(HashMap::new)
+            .insert(arg.to_string(), matcher);
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_expiry(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn with_scope(mut self, scope: impl Into<PathBuf>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|t| t <= now)
+    }
+
+    /// Check the rule against a subject. `Err` carries the reason it did not match.
+    pub fn check(
+        &self,
+        kind: &PolicySubjectKind,
+        name: &str,
+        args: &HashMap<String, String>,
+        now: DateTime<Utc>,
+    ) -> Result<String, String> {
+        if &self.subject_kind != kind {
+            return Err(format!("subject kind is {}", self.subject_kind.to_string()));
+        }
+        // A Deny/Ask rule with a broken pattern fails closed: it counts as
+        // matched instead of silently letting the call through
+        let fail_closed = !matches!(self.action, PolicyAction::Allow);
+        if let Some(t) = self.expires_at {
+            if t <= now {
+                return Err(format!("expired at {}", t.to_rfc3339()));
+            }
+        }
+        let mut matched = Vec::new();
+        match subject_matches(&self.subject_name, name) {
+            Ok(true) => matched.push(format!("subject '{}'", self.subject_name)),
+            Ok(false) => return Err(format!("subject '{}' does not match", self.subject_name)),
+            Err(e) if fail_closed => matched.push(format!(
+                "invalid subject pattern '{}' ({e}), treated as matched",
+                self.subject_name
+            )),
+            Err(e) => {
+                return Err(format!(
+                    "invalid subject pattern '{}': {e}",
+                    self.subject_name
+                ))
+            }
+        }
+        if let Some(expected) = &self.when_contains_args {
+            for (k, v) in expected {
+                if args.get(k) != Some(v) {
+                    return Err(format!("arg '{k}' != '{v}'"));
+                }
+                matched.push(format!("{k} == '{v}'"));
+            }
+        }
+        if let Some(matchers) = &self.when_args {
+            let base = self
+                .scope
+                .clone()
+                .or_else(|| std::env::current_dir().ok())
+                .unwrap_or_default();
+            for (k, m) in matchers {
+                let Some(value) = args.get(k) else {
+                    return Err(format!("arg '{k}' is missing"));
+                };
+                match m.matches(value, &base) {
+                    Ok(true) => matched.push(format!("{k} ~ {m}")),
+                    Ok(false) => return Err(format!("{k}='{value}' does not match {m}")),
+                    Err(e) if fail_closed => {
+                        matched.push(format!("invalid {m} for '{k}' ({e}), treated as matched"))
+                    }
+                    Err(e) => return Err(format!("invalid matcher for '{k}': {e}")),
+                }
+            }
+        }
+        Ok(matched.join(", "))
+    }
+
+    /// Compile-time problems of the rule (bad regex/glob)
+    pub fn validate(&self) -> Result<(), String> {
+        if let Err(e) = subject_matches(&self.subject_name, "") {
+            return Err(format!(
+                "invalid subject pattern '{}': {e}",
+                self.subject_name
+            ));
+        }
+        for (k, m) in self.when_args.iter().flatten() {
+            m.validate().map_err(|e| format!("arg '{k}': {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Argument matcher used by `PolicyRule::when_args`.
+/// JSON form: `{"path": {"path": "./src/**"}, "command": {"regex": "^git push"}}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgMatcher {
+    Exact(String),
+    /// Shell glob over the raw value (`*` also matches `/`)
+    Glob(String),
+    /// Regex search; anchor with `^...$` for a full match
+    Regex(String),
+    /// Glob over a normalized absolute path, `**` spans directories
+    Path(String),
+    /// Host of a URL or domain; `*.example.com` also matches `example.com`
+    Host(String),
+}
+
+impl std::fmt::Display for ArgMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgMatcher::Exact(p) => write!(f, "exact '{p}'"),
+            ArgMatcher::Glob(p) => write!(f, "glob '{p}'"),
+            ArgMatcher::Regex(p) => write!(f, "regex '{p}'"),
+            ArgMatcher::Path(p) => write!(f, "path '{p}'"),
+            ArgMatcher::Host(p) => write!(f, "host '{p}'"),
+        }
+    }
+}
+
+impl ArgMatcher {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ArgMatcher::Exact(_) => Ok(()),
+            ArgMatcher::Regex(p) => compiled_regex(p, |_| ()),
+            ArgMatcher::Glob(p) | ArgMatcher::Path(p) => compiled_glob(p, |_| ()),
+            ArgMatcher::Host(p) => compiled_glob(&p.to_lowercase(), |_| ()),
+        }
+    }
+
+    /// `base` resolves relative paths (rule scope or current directory)
+    pub fn matches(&self, value: &str, base: &Path) -> Result<bool, String> {
+        match self {
+            ArgMatcher::Exact(p) => Ok(value == p),
+            ArgMatcher::Glob(p) => compiled_glob(p, |g| g.matches(value)),
+            ArgMatcher::Regex(p) => compiled_regex(p, |re| re.is_match(value)),
+            ArgMatcher::Path(p) => {
+                let pattern = normalize_path(p, base);
+                let value = normalize_path(value, base);
+                let options = glob::MatchOptions {
+                    case_sensitive: !cfg!(windows),
+                    require_literal_separator: true,
+                    require_literal_leading_dot: false,
+                };
+                compiled_glob(&pattern.to_string_lossy(), |g| {
+                    g.matches_with(&value.to_string_lossy(), options)
+                })
+            }
+            ArgMatcher::Host(p) => {
+                let host = extract_host(value);
+                let p = p.to_lowercase();
+                if let Some(apex) = p.strip_prefix("*.") {
+                    if host == apex {
+                        return Ok(true);
+                    }
+                }
+                compiled_glob(&p, |g| g.matches(&host))
+            }
+        }
+    }
+}
+
+type PatternCache<P> = Mutex<HashMap<String, Result<Arc<P>, String>>>;
+
+/// Rules are checked on every tool call; each distinct pattern string is
+/// compiled once per process, compile errors are cached too
+fn with_cached<P>(
+    cache: &'static OnceLock<PatternCache<P>>,
+    pattern: &str,
+    compile: impl FnOnce(&str) -> Result<P, String>,
+) -> Result<Arc<P>, String> {
+    let mut map = cache
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    map.entry(pattern.to_string())
+        .or_insert_with(|| compile(pattern).map(Arc::new))
+        .clone()
+}
+
+fn compiled_regex<T>(pattern: &str, f: impl FnOnce(&regex::Regex) -> T) -> Result<T, String> {
+    static CACHE: OnceLock<PatternCache<regex::Regex>> = OnceLock::new();
+    with_cached(&CACHE, pattern, |p| {
+        regex::Regex::new(p).map_err(|e| e.to_string())
+    })
+    .map(|re| f(&re))
+}
+
+fn compiled_glob<T>(pattern: &str, f: impl FnOnce(&glob::Pattern) -> T) -> Result<T, String> {
+    static CACHE: OnceLock<PatternCache<glob::Pattern>> = OnceLock::new();
+    with_cached(&CACHE, pattern, |p| {
+        glob::Pattern::new(p).map_err(|e| e.to_string())
+    })
+    .map(|g| f(&g))
+}
+
+fn subject_matches(pattern: &str, name: &str) -> Result<bool, String> {
+    if pattern == name || pattern == "*" {
+        return Ok(true);
+    }
+    compiled_glob(pattern, |g| g.matches(name))
+}
+
+/// Absolute against `base` with `~/` expanded. Symlinks are resolved in the
+/// longest prefix that exists, so a link inside an allowed root cannot point
+/// a path rule elsewhere; the missing remainder is collapsed lexically
+pub fn normalize_path(path: &str, base: &Path) -> PathBuf {
+    use std::path::Component;
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|h| h.join(rest)),
+        None => None,
+    };
+    let raw = expanded.unwrap_or_else(|| PathBuf::from(path));
+    let joined = if raw.is_absolute() {
+        raw
+    } else {
+        base.join(raw)
+    };
+    let components: Vec<Component> = joined.components().collect();
+    let (mut out, rest) = (0..=components.len())
+        .rev()
+        .find_map(|n| {
+            let prefix: PathBuf = components[..n].iter().collect();
+            fs::canonicalize(&prefix)
+                .ok()
+                .map(|real| (real, &components[n..]))
+        })
+        .unwrap_or_else(|| (PathBuf::new(), &components[..]));
+    for comp in rest {
+        match comp {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Host part of a URL (`https://user@Docs.rs:443/x` -> `docs.rs`) or a bare domain
+pub fn extract_host(value: &str) -> String {
+    let rest = value.split_once("://").map(|(_, r)| r).unwrap_or(value);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit('@').next().unwrap_or("");
+    let host = if let Some(stripped) = host_port.strip_prefix('[') {
+        stripped.split(']').next().unwrap_or("")
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    host.trim_end_matches('.').to_lowercase()
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PolicyDocument {
     pub rules: Vec<PolicyRule>,
+    /// Secret detection settings (mask/block/ask, allowlists)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionPolicy>,
+}
+
+impl PolicyDocument {
+    /// Problems of individual rules as `rule #i: message`, then redaction settings
+    pub fn validate(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| r.validate().err().map(|e| format!("rule #{i}: {e}")))
+            .chain(self.redaction.iter().flat_map(RedactionPolicy::validate))
+            .collect()
+    }
+
+    /// Attach `scope` to rules that have none (per-project files)
+    pub fn scoped_to(mut self, project_root: &Path) -> Self {
+        for rule in &mut self.rules {
+            if rule.scope.is_none() {
+                rule.scope = Some(project_root.to_path_buf());
+            }
+        }
+        self
+    }
 }
 
 /// CRITICAL SECURITY: Emergency Policy Disable Token
@@ -166,6 +497,25 @@ pub struct PolicyDecision {
     pub risk: RiskLevel,
 }
 
+/// How a single rule was evaluated (see [`PolicyEngine::explain`])
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTrace {
+    pub index: usize,
+    pub rule: PolicyRule,
+    pub matched: bool,
+    pub detail: String,
+}
+
+/// Decision plus the evaluation trace of every rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyExplanation {
+    pub decision: PolicyDecision,
+    /// Index of the winning rule in `trace`
+    pub winner: Option<usize>,
+    pub emergency_bypass: bool,
+    pub trace: Vec<RuleTrace>,
+}
+
 impl PolicyEngine {
     pub fn new() -> Self {
         Self {
@@ -204,6 +554,91 @@ impl PolicyEngine {
         self.evaluate(PolicySubjectKind::Command, command, args)
     }
 
+    pub fn rules(&self) -> &[PolicyRule] {
+        &self.rules
+    }
+
+    /// Evaluate without publishing events and report why each rule did or
+    /// did not match (`magray policy test`)
+    pub fn explain(
+        &self,
+        kind: PolicySubjectKind,
+        name: &str,
+        args: &HashMap<String, String>,
+    ) -> PolicyExplanation {
+        let emergency_bypass = std::env::var("MAGRAY_EMERGENCY_DISABLE_POLICY")
+            .map(|t| self.validate_emergency_token(&t))
+            .unwrap_or(false);
+        let (mut decision, winner, trace) = self.decide(&kind, name, args);
+        if emergency_bypass {
+            decision = PolicyDecision {
+                allowed: true,
+                matched_rule: None,
+                action: PolicyAction::Allow,
+                risk: RiskLevel::Low,
+            };
+        }
+        PolicyExplanation {
+            decision,
+            winner,
+            emergency_bypass,
+            trace,
+        }
+    }
+
+    /// Rule selection: highest `priority` among matching, non-expired rules;
+    /// ties go to the later rule (so merged overlays override the base)
+    fn decide(
+        &self,
+        kind: &PolicySubjectKind,
+        name: &str,
+        args: &HashMap<String, String>,
+    ) -> (PolicyDecision, Option<usize>, Vec<RuleTrace>) {
+        let now = Utc::now();
+        let mut trace = Vec::with_capacity(self.rules.len());
+        let mut winner: Option<usize> = None;
+        for (index, rule) in self.rules.iter().enumerate() {
+            let (matched, detail) = match rule.check(kind, name, args, now) {
+                Ok(detail) => (true, detail),
+                Err(detail) => (false, detail),
+            };
+            if matched
+                && winner
+                    .map(|w| rule.priority >= self.rules[w].priority)
+                    .unwrap_or(true)
+            {
+                winner = Some(index);
+            }
+            trace.push(RuleTrace {
+                index,
+                rule: rule.clone(),
+                matched,
+                detail,
+            });
+        }
+        let decision = if let Some(rule) = winner.map(|w| self.rules[w].clone()) {
+            let action = rule.action.clone();
+            let risk = infer_risk_from_reason(rule.reason.as_deref());
+            let allowed = !matches!(action, PolicyAction::Deny);
+            PolicyDecision {
+                allowed,
+                matched_rule: Some(rule),
+                action,
+                risk,
+            }
+        } else {
+            // SECURE-BY-DEFAULT: Unknown tools require explicit user confirmation
+            // This prevents unauthorized tool execution and MCP bypass attacks
+            PolicyDecision {
+                allowed: false, // SECURITY FIX: Unknown tools are blocked by default
+                matched_rule: None,
+                action: PolicyAction::Ask, // SECURE: Ask for confirmation instead of auto-allow
+                risk: RiskLevel::Medium,   // SECURE: Unknown operations are medium risk
+            }
+        };
+        (decision, winner, trace)
+    }
+
     /// CRITICAL SECURITY: Check for emergency policy disable token
     /// Returns true if emergency mode is activated with valid token
     pub fn check_emergency_mode(&self) -> Option<EmergencyToken> {
@@ -419,48 +854,7 @@ impl PolicyEngine {
                 risk: RiskLevel::Low, // Emergency bypass considered resolved
             };
         }
-        let mut last_match: Option<PolicyRule> = None;
-        for rule in &self.rules {
-            if rule.subject_kind != kind {
-                continue;
-            }
-            if rule.subject_name != name && rule.subject_name != "*" {
-                continue;
-            }
-            if let Some(expected) = &rule.when_contains_args {
-                let mut all_match = true;
-                for (k, v) in expected {
-                    if args.get(k) != Some(v) {
-                        all_match = false;
-                        break;
-                    }
-                }
-                if !all_match {
-                    continue;
-                }
-            }
-            last_match = Some(rule.clone());
-        }
-        let decision = if let Some(rule) = last_match.clone() {
-            let action = rule.action.clone();
-            let risk = infer_risk_from_reason(rule.reason.as_deref());
-            let allowed = !matches!(action, PolicyAction::Deny);
-            PolicyDecision {
-                allowed,
-                matched_rule: last_match,
-                action,
-                risk,
-            }
-        } else {
-            // SECURE-BY-DEFAULT: Unknown tools require explicit user confirmation
-            // This prevents unauthorized tool execution and MCP bypass attacks
-            PolicyDecision {
-                allowed: false, // SECURITY FIX: Unknown tools are blocked by default
-                matched_rule: None,
-                action: PolicyAction::Ask, // SECURE: Ask for confirmation instead of auto-allow
-                risk: RiskLevel::Medium,   // SECURE: Unknown operations are medium risk
-            }
-        };
+        let (decision, _, _) = self.decide(&kind, name, args);
 
         // CRITICAL SECURITY: Log policy violations to EventBus for security audit
         match decision.action {
@@ -481,54 +875,151 @@ impl PolicyEngine {
     }
 }
 
+/// Boolean tool flag in the form the tools accept: `true`, `1` or `yes`
+const TRUE_FLAG: &str = r"(?i)^\s*(true|1|yes)\s*$";
+
 /// Built-in default policies (secure-by-default)
 pub fn default_document() -> PolicyDocument {
     PolicyDocument {
-        rules: vec![PolicyRule {
-            subject_kind: PolicySubjectKind::Tool,
-            subject_name: "shell_exec".into(),
-            when_contains_args: None,
-            action: PolicyAction::Deny,
-            reason: Some("Shell execution disabled by default".into()),
-        }],
+        rules: vec![
+            PolicyRule::new(PolicySubjectKind::Tool, "shell_exec", PolicyAction::Deny)
+                .with_reason("Shell execution disabled by default"),
+            // Destructive git operations: uncommitted work or commits can be lost
+            PolicyRule::new(PolicySubjectKind::Tool, "git_reset", PolicyAction::Ask)
+                .with_reason("High risk: git reset moves the branch and may discard changes"),
+            PolicyRule::new(PolicySubjectKind::Tool, "git_checkout", PolicyAction::Ask)
+                .with_arg_matcher("force", ArgMatcher::Regex(TRUE_FLAG.into()))
+                .with_reason("High risk: forced checkout discards local changes"),
+            PolicyRule::new(PolicySubjectKind::Tool, "git_checkout", PolicyAction::Ask)
+                .with_arg_matcher("paths", ArgMatcher::Regex(".".into()))
+                .with_reason("High risk: checking out paths overwrites local changes"),
+            PolicyRule::new(PolicySubjectKind::Tool, "git_branch", PolicyAction::Ask)
+                .with_arg_matcher("action", ArgMatcher::Exact("delete".into()))
+                .with_arg_matcher("force", ArgMatcher::Regex(TRUE_FLAG.into()))
+                .with_reason("High risk: force-deleting a branch may lose unmerged commits"),
+            PolicyRule::new(PolicySubjectKind::Tool, "git_stash", PolicyAction::Ask)
+                .with_arg_matcher("action", ArgMatcher::Exact("drop".into()))
+                .with_reason("High risk: dropped stash entries are not recoverable"),
+            PolicyRule::new(PolicySubjectKind::Tool, "git_worktree", PolicyAction::Ask)
+                .with_arg_matcher("action", ArgMatcher::Exact("remove".into()))
+                .with_arg_matcher("force", ArgMatcher::Regex(TRUE_FLAG.into()))
+                .with_reason("High risk: forced worktree removal deletes uncommitted files"),
+        ],
+        redaction: None,
     }
 }
 
-/// Load policies from a JSON file path
+/// Load policies from a JSON file path; rules with invalid patterns are rejected
 pub fn load_from_path(path: impl AsRef<Path>) -> AnyResult<PolicyDocument> {
+    let path = path.as_ref();
     let content = fs::read_to_string(path)?;
     let doc: PolicyDocument = serde_json::from_str(&content)?;
+    let problems = doc.validate();
+    if !problems.is_empty() {
+        anyhow::bail!("invalid policy {}: {}", path.display(), problems.join("; "));
+    }
     Ok(doc)
 }
 
+/// Layer of the effective policy. Problems are logged rather than dropping
+/// the layer: an invalid Deny/Ask rule still fails closed in `check`
+fn load_layer(source: &str, content: &str) -> Option<PolicyDocument> {
+    match serde_json::from_str::<PolicyDocument>(content) {
+        Ok(doc) => {
+            for problem in doc.validate() {
+                warn!("policy {source}: {problem}");
+            }
+            Some(doc)
+        }
+        Err(e) => {
+            warn!("policy {source} not loaded: {e}");
+            None
+        }
+    }
+}
+
+fn load_layer_file(path: &Path) -> Option<PolicyDocument> {
+    if !path.exists() {
+        return None;
+    }
+    match fs::read_to_string(path) {
+        Ok(content) => load_layer(&path.display().to_string(), &content),
+        Err(e) => {
+            warn!("policy {} not loaded: {e}", path.display());
+            None
+        }
+    }
+}
+
 /// Merge two documents: later rules take precedence (appended at the end)
 pub fn merge_documents(mut base: PolicyDocument, mut overlay: PolicyDocument) -> PolicyDocument {
     base.rules.append(&mut overlay.rules);
+    base.redaction = match (base.redaction.take(), overlay.redaction.take()) {
+        (Some(b), Some(o)) => Some(b.merge(o)),
+        (b, o) => o.or(b),
+    };
     base
 }
 
+/// Per-project policy file (`<project>/.magray/policy.json`), receives
+/// "always allow for this project" grants from the approval broker
+pub fn project_policy_path(project_root: &Path) -> PathBuf {
+    project_root.join(".magray").join("policy.json")
+}
+
+/// Nearest ancestor of `start` (inclusive) with a project policy file
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| project_policy_path(dir).is_file())
+        .map(Path::to_path_buf)
+}
+
 /// Load effective policy considering default + optional file + env overrides.
 /// Precedence (last wins): default < file_path < MAGRAY_POLICY_PATH < MAGRAY_POLICY_JSON
 pub fn load_effective_policy(file_path: Option<&Path>) -> PolicyDocument {
+    load_effective_policy_for_project(file_path, None)
+}
+
+/// Same as [`load_effective_policy`] with the project policy layered over `file_path`.
+/// Precedence (last wins): default < file_path < project < MAGRAY_POLICY_PATH < MAGRAY_POLICY_JSON.
+/// A project file the user has not trusted (see [`ProjectTrust`]) can only
+/// tighten: its Allow rules and relaxed redaction settings are ignored
+pub fn load_effective_policy_for_project(
+    file_path: Option<&Path>,
+    project_root: Option<&Path>,
+) -> PolicyDocument {
+    load_effective_policy_with_trust(file_path, project_root, &ProjectTrust::from_env())
+}
+
+fn load_effective_policy_with_trust(
+    file_path: Option<&Path>,
+    project_root: Option<&Path>,
+    trust: &ProjectTrust,
+) -> PolicyDocument {
     let mut doc = default_document();
-    if let Some(p) = file_path {
-        if p.exists() {
-            if let Ok(d) = load_from_path(p) {
-                doc = merge_documents(doc, d);
-            }
+    if let Some(d) = file_path.and_then(load_layer_file) {
+        doc = merge_documents(doc, d);
+    }
+    if let Some(root) = project_root {
+        let path = project_policy_path(root);
+        if let Some(d) = load_layer_file(&path) {
+            let d = d.scoped_to(root);
+            doc = if trust.is_trusted(&path) {
+                merge_documents(doc, d)
+            } else {
+                merge_untrusted(doc, d, &path)
+            };
         }
     }
     if let Ok(path_str) = std::env::var("MAGRAY_POLICY_PATH") {
-        let p = Path::new(&path_str);
-        if p.exists() {
-            if let Ok(d) = load_from_path(p) {
-                doc = merge_documents(doc, d);
-            }
+        if let Some(d) = load_layer_file(Path::new(&path_str)) {
+            doc = merge_documents(doc, d);
         }
     }
     if let Ok(json_str) = std::env::var("MAGRAY_POLICY_JSON") {
         if !json_str.trim().is_empty() {
-            if let Ok(d) = serde_json::from_str::<PolicyDocument>(&json_str) {
+            if let Some(d) = load_layer("MAGRAY_POLICY_JSON", &json_str) {
                 doc = merge_documents(doc, d);
             }
         }
@@ -536,6 +1027,102 @@ pub fn load_effective_policy(file_path: Option<&Path>) -> PolicyDocument {
     doc
 }
 
+/// Merge a layer that ships with the repository: Deny/Ask rules apply, Allow
+/// rules are dropped and redaction settings can only get stricter
+fn merge_untrusted(
+    mut base: PolicyDocument,
+    overlay: PolicyDocument,
+    source: &Path,
+) -> PolicyDocument {
+    let (allow, restrict): (Vec<_>, Vec<_>) = overlay
+        .rules
+        .into_iter()
+        .partition(|r| r.action == PolicyAction::Allow);
+    if !allow.is_empty() {
+        warn!(
+            "policy {}: {} Allow rule(s) ignored, the project policy is not trusted (magray policy trust)",
+            source.display(),
+            allow.len()
+        );
+    }
+    base.rules.extend(restrict);
+    if let Some(overlay) = overlay.redaction {
+        base.redaction = Some(base.redaction.unwrap_or_default().tightened_by(overlay));
+    }
+    base
+}
+
+/// Project policy files the user has reviewed, stored outside the repository
+/// (`<magray_home>/trusted_projects.json`) as file path -> sha256 of the
+/// trusted content. Editing the file (e.g. `git pull`) revokes the trust
+#[derive(Debug, Clone)]
+pub struct ProjectTrust {
+    store: PathBuf,
+}
+
+impl ProjectTrust {
+    pub fn at(store: impl Into<PathBuf>) -> Self {
+        Self {
+            store: store.into(),
+        }
+    }
+
+    /// `$MAGRAY_HOME/trusted_projects.json`, `~/.magray/...` by default
+    pub fn from_env() -> Self {
+        let home = std::env::var("MAGRAY_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                dirs::home_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join(".magray")
+            });
+        Self::at(home.join("trusted_projects.json"))
+    }
+
+    fn load(&self) -> HashMap<String, String> {
+        fs::read_to_string(&self.store)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn key(policy_file: &Path) -> String {
+        fs::canonicalize(policy_file)
+            .unwrap_or_else(|_| policy_file.to_path_buf())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn digest(policy_file: &Path) -> Option<String> {
+        use sha2::{Digest, Sha256};
+        fs::read(policy_file)
+            .ok()
+            .map(|content| format!("{:x}", Sha256::digest(content)))
+    }
+
+    /// Whether the file's current content is the one the user trusted
+    pub fn is_trusted(&self, policy_file: &Path) -> bool {
+        match Self::digest(policy_file) {
+            Some(digest) => self.load().get(&Self::key(policy_file)) == Some(&digest),
+            None => false,
+        }
+    }
+
+    /// Trust the current content of `policy_file`
+    pub fn trust(&self, policy_file: &Path) -> AnyResult<()> {
+        let digest = Self::digest(policy_file).ok_or_else(|| {
+            anyhow::anyhow!("policy file {} cannot be read", policy_file.display())
+        })?;
+        let mut trusted = self.load();
+        trusted.insert(Self::key(policy_file), digest);
+        if let Some(parent) = self.store.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.store, serde_json::to_string_pretty(&trusted)?)?;
+        Ok(())
+    }
+}
+
 fn infer_risk_from_reason(reason: Option<&str>) -> RiskLevel {
     if let Some(r) = reason {
         let r = r.to_lowercase();
@@ -725,6 +1312,50 @@ mod tests {
         assert_eq!(d.action, PolicyAction::Deny);
     }
 
+    #[test]
+    fn default_document_asks_for_destructive_git() {
+        std::env::remove_var("MAGRAY_EMERGENCY_DISABLE_POLICY");
+
+        let engine = PolicyEngine::from_document(default_document());
+        let args = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let d = engine.evaluate_tool("git_reset", &args(&[("mode", "hard")]));
+        assert_eq!(d.action, PolicyAction::Ask);
+        assert_eq!(d.risk, RiskLevel::High);
+        for (tool, pairs) in [
+            ("git_checkout", &[("target", "main"), ("force", "true")][..]),
+            (
+                "git_checkout",
+                &[("target", "HEAD"), ("paths", "src/lib.rs")],
+            ),
+            ("git_branch", &[("action", "delete"), ("force", "yes")]),
+            ("git_stash", &[("action", "drop")]),
+            ("git_worktree", &[("action", "remove"), ("force", "1")]),
+        ] {
+            let d = engine.evaluate_tool(tool, &args(pairs));
+            assert_eq!(d.action, PolicyAction::Ask, "{tool} {pairs:?}");
+            assert!(d.matched_rule.is_some(), "{tool} {pairs:?}");
+        }
+
+        // Non-destructive variants are not matched by the default rules
+        for (tool, pairs) in [
+            (
+                "git_checkout",
+                &[("target", "main"), ("force", "false")][..],
+            ),
+            ("git_branch", &[("action", "delete")]),
+            ("git_stash", &[("action", "push")]),
+        ] {
+            let d = engine.evaluate_tool(tool, &args(pairs));
+            assert!(d.matched_rule.is_none(), "{tool} {pairs:?}");
+        }
+    }
+
     #[test]
     fn risk_level_inference_high_medium_low() {
         assert_eq!(
@@ -748,7 +1379,12 @@ mod tests {
                 when_contains_args: None,
                 action: PolicyAction::Ask,
                 reason: Some("medium".into()),
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
         let engine = PolicyEngine::from_document(doc);
         let d = engine.evaluate_tool("web_search", &HashMap::new());
@@ -769,7 +1405,12 @@ mod tests {
                 when_contains_args: None,
                 action: PolicyAction::Deny,
                 reason: Some("Shell exec disabled".into()),
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
         let engine = PolicyEngine::from_document(doc);
         let d = engine.evaluate_tool("shell_exec", &HashMap::new());
@@ -793,7 +1434,12 @@ mod tests {
                 when_contains_args: Some(HashMap::from([("path".into(), "/etc/hosts".into())])),
                 action: PolicyAction::Allow,
                 reason: None,
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
         let engine = PolicyEngine::from_document(doc);
         let d1 = engine.evaluate_tool(
@@ -820,7 +1466,12 @@ mod tests {
                 when_contains_args: None,
                 action: PolicyAction::Allow,
                 reason: Some("override".into()),
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
         let merged = merge_documents(base, overlay);
         let engine = PolicyEngine::from_document(merged);
@@ -829,6 +1480,220 @@ mod tests {
         assert_eq!(d.action, PolicyAction::Allow);
     }
 
+    #[test]
+    fn path_glob_allows_under_src_only() {
+        let root = PathBuf::from("/work/project");
+        let engine = PolicyEngine::from_document(PolicyDocument {
+            rules: vec![
+                PolicyRule::new(PolicySubjectKind::Tool, "file_write", PolicyAction::Ask),
+                PolicyRule::new(PolicySubjectKind::Tool, "file_write", PolicyAction::Allow)
+                    .with_arg_matcher("path", ArgMatcher::Path("./src/**".into()))
+                    .with_scope(&root),
+            ],
+            ..Default::default()
+        });
+        let args = |p: &str| HashMap::from([("path".to_string(), p.to_string())]);
+        let d = engine.evaluate_tool("file_write", &args("/work/project/src/a/b.rs"));
+        assert_eq!(d.action, PolicyAction::Allow);
+        // `..` is normalized before matching
+        let d = engine.evaluate_tool("file_write", &args("/work/project/src/../Cargo.toml"));
+        assert_eq!(d.action, PolicyAction::Ask);
+        let d = engine.evaluate_tool("file_write", &args("/work/project/srcx/a.rs"));
+        assert_eq!(d.action, PolicyAction::Ask);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_rules_follow_symlinks() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let root = tmp.path().join("project");
+        let outside = tmp.path().join("outside");
+        fs::create_dir_all(root.join("src")).expect("mkdir");
+        fs::create_dir_all(&outside).expect("mkdir");
+        std::os::unix::fs::symlink(&outside, root.join("src").join("escape")).expect("symlink");
+        let engine = PolicyEngine::from_document(PolicyDocument {
+            rules: vec![
+                PolicyRule::new(PolicySubjectKind::Tool, "file_write", PolicyAction::Ask),
+                PolicyRule::new(PolicySubjectKind::Tool, "file_write", PolicyAction::Allow)
+                    .with_arg_matcher("path", ArgMatcher::Path("./src/**".into()))
+                    .with_scope(&root),
+            ],
+            ..Default::default()
+        });
+        let args = |p: &Path| HashMap::from([("path".to_string(), p.display().to_string())]);
+        let d = engine.evaluate_tool("file_write", &args(&root.join("src").join("new.rs")));
+        assert_eq!(d.action, PolicyAction::Allow);
+        let d = engine.evaluate_tool(
+            "file_write",
+            &args(&root.join("src").join("escape").join("x.rs")),
+        );
+        assert_eq!(d.action, PolicyAction::Ask);
+    }
+
+    #[test]
+    fn untrusted_project_policy_only_tightens() {
+        let tmp = tempfile::TempDir::new().expect("tempdir");
+        let path = project_policy_path(tmp.path());
+        fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        fs::write(
+            &path,
+            r#"{"rules":[{"subject_kind":"Tool","subject_name":"shell_exec","when_contains_args":null,"action":"Allow","priority":100},
+                        {"subject_kind":"Tool","subject_name":"web_fetch","when_contains_args":null,"action":"Deny"}],
+                "redaction":{"enabled":false,"action":"block","allowlist":["hunter2"]}}"#,
+        )
+        .expect("write policy");
+        let trust = ProjectTrust::at(tmp.path().join("trusted_projects.json"));
+        let shell = HashMap::from([("command".to_string(), "ls".to_string())]);
+
+        let doc = load_effective_policy_with_trust(None, Some(tmp.path()), &trust);
+        let redaction = doc.redaction.clone().expect("redaction");
+        assert!(redaction.is_enabled());
+        assert_eq!(
+            redaction.action,
+            Some(crate::redaction::RedactionAction::Block)
+        );
+        assert!(redaction.allowlist.is_empty());
+        let engine = PolicyEngine::from_document(doc);
+        let d = engine.evaluate_tool("shell_exec", &shell);
+        assert_eq!(d.action, PolicyAction::Deny);
+        let d = engine.evaluate_tool("web_fetch", &HashMap::new());
+        assert_eq!(d.action, PolicyAction::Deny);
+
+        trust.trust(&path).expect("trust");
+        let doc = load_effective_policy_with_trust(None, Some(tmp.path()), &trust);
+        assert!(!doc.redaction.clone().expect("redaction").is_enabled());
+        let engine = PolicyEngine::from_document(doc);
+        let d = engine.evaluate_tool("shell_exec", &shell);
+        assert_eq!(d.action, PolicyAction::Allow);
+
+        // editing the file revokes the trust
+        let edited = fs::read_to_string(&path)
+            .expect("read")
+            .replace("100", "101");
+        fs::write(&path, edited).expect("write policy");
+        assert!(!trust.is_trusted(&path));
+    }
+
+    #[test]
+    fn regex_and_priority() {
+        let engine = PolicyEngine::from_document(PolicyDocument {
+            rules: vec![
+                PolicyRule::new(PolicySubjectKind::Tool, "shell_exec", PolicyAction::Deny)
+                    .with_arg_matcher("command", ArgMatcher::Regex(r"^git\s+push".into()))
+                    .with_priority(10),
+                PolicyRule::new(PolicySubjectKind::Tool, "shell_*", PolicyAction::Allow),
+            ],
+            ..Default::default()
+        });
+        let args = |c: &str| HashMap::from([("command".to_string(), c.to_string())]);
+        // Higher priority deny beats the later allow
+        let d = engine.evaluate_tool("shell_exec", &args("git push origin main"));
+        assert_eq!(d.action, PolicyAction::Deny);
+        let d = engine.evaluate_tool("shell_exec", &args("git status"));
+        assert_eq!(d.action, PolicyAction::Allow);
+    }
+
+    #[test]
+    fn host_pattern_and_expiry() {
+        let future = Utc::now() + chrono::Duration::days(3);
+        let past = Utc::now() - chrono::Duration::days(1);
+        let engine = PolicyEngine::from_document(PolicyDocument {
+            rules: vec![
+                PolicyRule::new(PolicySubjectKind::Tool, "web_fetch", PolicyAction::Allow)
+                    .with_arg_matcher("url", ArgMatcher::Host("*.rust-lang.org".into()))
+                    .with_expiry(future),
+                PolicyRule::new(PolicySubjectKind::Tool, "web_fetch", PolicyAction::Allow)
+                    .with_arg_matcher("url", ArgMatcher::Host("example.com".into()))
+                    .with_expiry(past),
+            ],
+            ..Default::default()
+        });
+        let args = |u: &str| HashMap::from([("url".to_string(), u.to_string())]);
+        let d = engine.evaluate_tool("web_fetch", &args("https://doc.Rust-Lang.org:443/std"));
+        assert_eq!(d.action, PolicyAction::Allow);
+        let d = engine.evaluate_tool("web_fetch", &args("https://rust-lang.org"));
+        assert_eq!(d.action, PolicyAction::Allow);
+        let d = engine.evaluate_tool("web_fetch", &args("https://evilrust-lang.org"));
+        assert_eq!(d.action, PolicyAction::Ask);
+        // expired rule no longer applies
+        let d = engine.evaluate_tool("web_fetch", &args("https://example.com"));
+        assert_eq!(d.action, PolicyAction::Ask);
+    }
+
+    #[test]
+    fn explain_reports_each_rule() {
+        let engine = PolicyEngine::from_document(default_document());
+        let exp = engine.explain(PolicySubjectKind::Tool, "shell_exec", &HashMap::new());
+        assert_eq!(exp.winner, Some(0));
+        assert!(exp.trace[0].matched);
+        assert_eq!(exp.decision.action, PolicyAction::Deny);
+        let exp = engine.explain(PolicySubjectKind::Tool, "file_read", &HashMap::new());
+        assert_eq!(exp.winner, None);
+        assert!(!exp.trace[0].matched);
+        assert!(exp.trace[0].detail.contains("does not match"));
+    }
+
+    #[test]
+    fn project_file_rules_are_scoped_and_validated() {
+        let tmp = tempfile::TempDir::new().expect("Operation failed - converted from unwrap()");
+        let path = project_policy_path(tmp.path());
+        fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+        fs::write(
+            &path,
+            r#"{"rules":[{"subject_kind":"Tool","subject_name":"file_write","when_contains_args":null,"action":"Allow","when_args":{"path":{"path":"src/**"}}},
+                        {"subject_kind":"Tool","subject_name":"shell_exec","when_contains_args":null,"action":"Deny","when_args":{"command":{"regex":"("}}}]}"#,
+        )
+        .expect("write policy");
+        let nested = tmp.path().join("src").join("deep");
+        fs::create_dir_all(&nested).expect("mkdir");
+        assert_eq!(find_project_root(&nested).as_deref(), Some(tmp.path()));
+
+        // strict loading rejects the file, the effective layer keeps the rule
+        let err = load_from_path(&path).expect_err("invalid regex must be rejected");
+        assert!(err.to_string().contains("rule #1"));
+        let doc = load_layer_file(&path)
+            .expect("layer with problems is still loaded")
+            .scoped_to(tmp.path());
+        let errors = doc.validate();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("rule #1"));
+        let engine = PolicyEngine::from_document(doc);
+        let file = tmp.path().join("src").join("main.rs");
+        let d = engine.evaluate_tool(
+            "file_write",
+            &HashMap::from([("path".to_string(), file.to_string_lossy().to_string())]),
+        );
+        assert_eq!(d.action, PolicyAction::Allow);
+        // invalid regex on a Deny rule fails closed
+        let d = engine.evaluate_tool(
+            "shell_exec",
+            &HashMap::from([("command".to_string(), "ls".to_string())]),
+        );
+        assert_eq!(d.action, PolicyAction::Deny);
+    }
+
+    #[test]
+    fn invalid_patterns_fail_closed_only_for_deny_and_ask() {
+        let engine = PolicyEngine::from_document(PolicyDocument {
+            rules: vec![
+                PolicyRule::new(PolicySubjectKind::Tool, "web_fetch", PolicyAction::Allow)
+                    .with_arg_matcher("url", ArgMatcher::Glob("[".into())),
+                PolicyRule::new(PolicySubjectKind::Tool, "file_[", PolicyAction::Ask),
+            ],
+            ..Default::default()
+        });
+        let url = HashMap::from([("url".to_string(), "https://example.com".to_string())]);
+        // broken Allow never grants anything
+        let exp = engine.explain(PolicySubjectKind::Tool, "web_fetch", &url);
+        assert!(!exp.trace[0].matched);
+        assert!(exp.trace[0].detail.contains("invalid matcher"));
+        // broken Ask subject applies to every tool of its kind
+        let exp = engine.explain(PolicySubjectKind::Tool, "file_write", &HashMap::new());
+        assert_eq!(exp.winner, Some(1));
+        assert!(exp.trace[1].detail.contains("treated as matched"));
+        assert_eq!(exp.decision.action, PolicyAction::Ask);
+    }
+
     #[test]
     fn env_json_wins_over_file() {
         // Prepare a temporary file that denies, but env JSON allows
@@ -937,7 +1802,12 @@ mod tests {
                 when_contains_args: None,
                 action: PolicyAction::Deny,
                 reason: Some("Dangerous operation".into()),
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
         let engine = PolicyEngine::from_document(doc);
 
@@ -1026,7 +1896,12 @@ mod tests {
                 when_contains_args: None,
                 action: PolicyAction::Deny,
                 reason: Some("High risk operation".into()),
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
 
         let engine = PolicyEngine::from_document(doc).with_event_publisher(mock_publisher.clone());
@@ -1080,7 +1955,12 @@ mod tests {
                 when_contains_args: None,
                 action: PolicyAction::Ask,
                 reason: Some("Medium risk - requires user confirmation".into()),
+                when_args: None,
+                priority: 0,
+                expires_at: None,
+                scope: None,
             }],
+            ..Default::default()
         };
 
         let engine = PolicyEngine::from_document(doc).with_event_publisher(mock_publisher.clone());
@@ -1173,6 +2053,7 @@ impl LocalEventPublisher for ProductionEventPublisher {
         Box::pin(async move {
             // CRITICAL P0.2.6: Connect to real EventBus for production audit logging
             // Use global EventBus for production security event logging
+            crate::audit::record_event(&topic, &source, &payload);
             let global_bus = magray_core::events::bus::get_global_event_bus();
             match global_bus.publish(&topic, payload.clone(), &source).await {
                 Ok(_) => {
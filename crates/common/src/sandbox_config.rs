@@ -304,7 +304,11 @@ fn validate_path_against_roots(
             })?
         } else {
             // File doesn't exist - check parent directory and construct expected path
-            let parent = path_obj.parent().unwrap_or(std::path::Path::new("."));
+            // A bare file name has an empty parent: the current directory
+            let parent = path_obj
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(std::path::Path::new("."));
             let parent_canonical =
                 std::fs::canonicalize(parent).map_err(|e| {
                     anyhow::anyhow!(
@@ -94,6 +94,7 @@ where
     fn on_event(&self, event: &Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
         let mut visitor = JsonVisitor::default();
         event.record(&mut visitor);
+        visitor.redact_secrets();
 
         let level = match *event.metadata().level() {
             Level::ERROR => "ERROR",
@@ -173,6 +174,20 @@ impl Visit for JsonVisitor {
 }
 
 impl JsonVisitor {
+    /// Маскирует секреты в сообщении и строковых полях перед записью
+    fn redact_secrets(&mut self) {
+        let redactor = crate::redaction::global_redactor();
+        if let Some(message) = self.message.as_mut() {
+            let (masked, findings) = redactor.mask(message);
+            if !findings.is_empty() {
+                *message = masked;
+            }
+        }
+        for value in self.fields.values_mut() {
+            redactor.mask_value(value);
+        }
+    }
+
     /// Извлечь метрики производительности из полей
     fn extract_performance_metrics(&self) -> Option<PerformanceMetrics> {
         // Если нет duration_ms, то метрики не нужны
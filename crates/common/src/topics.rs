@@ -8,7 +8,11 @@ pub const TOPIC_FS_DIFF: Topic = Topic("fs.diff");
 pub const TOPIC_MEMORY_UPSERT: Topic = Topic("memory.upsert");
 pub const TOPIC_MEMORY_SEARCH: Topic = Topic("memory.search");
 pub const TOPIC_POLICY_BLOCK: Topic = Topic("policy.block");
+pub const TOPIC_POLICY_ASK: Topic = Topic("policy.ask");
 pub const TOPIC_JOB_PROGRESS: Topic = Topic("job.progress");
 pub const TOPIC_LLM_TOKENS: Topic = Topic("llm.tokens");
+pub const TOPIC_LLM_REQUEST: Topic = Topic("llm.request");
+pub const TOPIC_SANDBOX_VIOLATION: Topic = Topic("sandbox.violation");
+pub const TOPIC_SECRETS_DETECTED: Topic = Topic("secrets.detected");
 pub const TOPIC_ERROR: Topic = Topic("error");
 pub const TOPIC_HEALTH: Topic = Topic("health");
@@ -170,6 +170,41 @@ pub struct MagrayConfig {
 
     #[serde(default)]
     pub performance: PerformanceConfig,
+
+    /// User-defined agent profiles (`[agents.<name>]`) the planner can
+    /// delegate sub-tasks to
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub agents: HashMap<String, AgentProfileConfig>,
+}
+
+/// A specialist sub-agent: its own prompt, tools, model, memory scope and budget
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct AgentProfileConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub system_prompt: String,
+
+    /// Tool names the sub-agent may call; `"*"` means every registered tool,
+    /// empty means none
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+
+    /// Model override for the configured provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Memory project the sub-agent reads and writes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_project: Option<String>,
+
+    /// Spend limits, e.g. `tokens=20000/50000,tools=/10,time=5m`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_iterations: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -469,6 +469,31 @@ impl ConfigLoader {
                 enable_gpu: false,
                 memory_limit_mb: 1024,
             },
+            agents: {
+                let mut agents = std::collections::HashMap::new();
+                agents.insert(
+                    "reviewer".to_string(),
+                    AgentProfileConfig {
+                        description: Some("Reviews diffs without changing files".to_string()),
+                        system_prompt:
+                            "You are a careful code reviewer. Report concrete issues only."
+                                .to_string(),
+                        allowed_tools: vec![
+                            "git_diff".to_string(),
+                            "git_status".to_string(),
+                            "git_log".to_string(),
+                            "git_show".to_string(),
+                            "git_blame".to_string(),
+                            "file_read".to_string(),
+                        ],
+                        model: None,
+                        memory_project: Some("reviews".to_string()),
+                        budget: Some("tools=/20,time=5m".to_string()),
+                        max_iterations: Some(3),
+                    },
+                );
+                agents
+            },
         };
 
         toml::to_string_pretty(&config)
@@ -181,6 +181,20 @@ level = "debug"
         assert!(example.contains("logging"));
     }
 
+    #[tokio::test]
+    async fn test_example_config_agent_profiles() {
+        let example = ConfigLoader::generate_example_config();
+        let config: MagrayConfig = toml::from_str(&example).expect("Example config should parse");
+
+        let reviewer = config
+            .agents
+            .get("reviewer")
+            .expect("Example config should define a reviewer agent");
+        assert!(reviewer.allowed_tools.contains(&"git_diff".to_string()));
+        assert_eq!(reviewer.memory_project.as_deref(), Some("reviews"));
+        assert_eq!(reviewer.budget.as_deref(), Some("tools=/20,time=5m"));
+    }
+
     #[tokio::test]
     async fn test_api_key_env_override() -> anyhow::Result<()> {
         env::set_var("MAGRAY_OPENAI_API_KEY", "test-openai-key");
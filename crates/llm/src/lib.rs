@@ -16,11 +16,13 @@ mod multi_provider;
 pub mod provider_management;
 pub mod providers;
 pub mod retry;
+mod usage;
 
 pub use agents::*;
 pub use circuit_breaker::*;
 pub use cost_optimizer::*;
 pub use multi_provider::*;
+pub use usage::{LlmCallUsage, LlmUsageObserver, TokenCounts};
 // Import retry items selectively to avoid conflicts with multi_provider
 pub use retry::{
     execute_streaming_with_retry, execute_with_retry, RetryConfig, RetryError, RetryableError,
@@ -33,9 +35,9 @@ pub use provider_management::{
 };
 // Import providers items - keep the main LlmProvider trait
 pub use providers::{
-    ChatMessage as ProviderChatMessage, LatencyClass, LlmProvider, LlmRequest, LlmResponse,
-    MessageRole, ProviderCapabilities, ProviderConfig, ProviderFactory, ProviderHealth, ProviderId,
-    ProviderWrapper, TokenUsage,
+    ChatMessage as ProviderChatMessage, FunctionCall, FunctionDefinition, LatencyClass,
+    LlmProvider, LlmRequest, LlmResponse, MessageRole, ProviderCapabilities, ProviderConfig,
+    ProviderFactory, ProviderHealth, ProviderId, ProviderWrapper, TokenUsage,
 };
 
 /// Legacy LLM provider enum for backwards compatibility
@@ -201,6 +203,7 @@ pub struct LlmClient {
     max_tokens: u32,
     temperature: f32,
     orchestrator: Option<Arc<MultiProviderLlmOrchestrator>>,
+    usage_observer: Option<Arc<dyn LlmUsageObserver>>,
 }
 
 // OpenAI API types
@@ -221,6 +224,25 @@ struct OpenAIChatRequest {
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAIChatResponse {
     choices: Vec<OpenAIChatChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAIUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+impl From<&OpenAIUsage> for TokenCounts {
+    fn from(usage: &OpenAIUsage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -246,6 +268,31 @@ struct AnthropicRequest {
 #[derive(Debug, Serialize, Deserialize)]
 struct AnthropicResponse {
     content: Vec<AnthropicContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+impl From<&AnthropicUsage> for TokenCounts {
+    fn from(usage: &AnthropicUsage) -> Self {
+        Self {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+        }
+    }
+}
+
+/// Provider response text with the usage block, when the API returned one
+struct ChatReply {
+    text: String,
+    usage: Option<TokenCounts>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -253,6 +300,27 @@ struct AnthropicContent {
     text: String,
 }
 
+/// Audit-log an LLM call: provider, sizes, latency and outcome only, never
+/// the prompt or response text
+fn audit_llm_call(
+    provider: &str,
+    prompt_chars: usize,
+    started: std::time::Instant,
+    result: &Result<String>,
+) {
+    let mut payload = serde_json::json!({
+        "provider": provider,
+        "prompt_chars": prompt_chars,
+        "duration_ms": started.elapsed().as_millis() as u64,
+        "success": result.is_ok(),
+    });
+    match result {
+        Ok(text) => payload["response_chars"] = text.chars().count().into(),
+        Err(e) => payload["error"] = e.to_string().chars().take(200).collect::<String>().into(),
+    }
+    common::audit::record_event(common::topics::TOPIC_LLM_REQUEST.0, "llm", &payload);
+}
+
 impl LlmClient {
     pub fn new(provider: LegacyLlmProvider, max_tokens: u32, temperature: f32) -> Self {
         Self {
@@ -261,6 +329,7 @@ impl LlmClient {
             max_tokens,
             temperature,
             orchestrator: None,
+            usage_observer: None,
         }
     }
 
@@ -279,6 +348,7 @@ impl LlmClient {
             max_tokens: 1000,
             temperature: 0.7,
             orchestrator: Some(orchestrator),
+            usage_observer: None,
         }
     }
 
@@ -287,6 +357,40 @@ impl LlmClient {
         self.orchestrator.is_some()
     }
 
+    /// Report token/cost usage of every call to `observer` (e.g. a budget tracker)
+    pub fn with_usage_observer(mut self, observer: Arc<dyn LlmUsageObserver>) -> Self {
+        self.usage_observer = Some(observer);
+        self
+    }
+
+    /// Use `model` instead of the configured one (single-provider mode)
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        let model = model.into();
+        match &mut self.provider {
+            LegacyLlmProvider::OpenAI { model: current, .. }
+            | LegacyLlmProvider::Anthropic { model: current, .. }
+            | LegacyLlmProvider::Local { model: current, .. }
+            | LegacyLlmProvider::Ollama { model: current, .. }
+            | LegacyLlmProvider::LMStudio { model: current, .. }
+            | LegacyLlmProvider::Azure { model: current, .. }
+            | LegacyLlmProvider::Groq { model: current, .. } => *current = model,
+        }
+        self
+    }
+
+    /// Report a successful call; `reported` is the provider's usage block,
+    /// without it the tokens are estimated from the text
+    fn observe_call(&self, prompt: &str, response: &str, reported: Option<TokenCounts>) {
+        if let Some(observer) = &self.usage_observer {
+            observer.record(&LlmCallUsage::from_call(
+                &self.provider,
+                prompt,
+                response,
+                reported,
+            ));
+        }
+    }
+
     pub fn from_env() -> Result<Self> {
         dotenv::dotenv().ok(); // Загружаем .env если есть
 
@@ -330,13 +434,24 @@ impl LlmClient {
             max_tokens,
             temperature,
             orchestrator: None,
+            usage_observer: None,
         })
     }
 
     pub async fn complete(&self, request: CompletionRequest) -> Result<String> {
         if let Some(orchestrator) = &self.orchestrator {
             info!("🎯 Using multi-provider orchestration for request");
-            return orchestrator.complete_smart(request).await;
+            if let Some(observer) = &self.usage_observer {
+                observer.before_call()?;
+            }
+            let started = std::time::Instant::now();
+            let prompt = request.prompt.clone();
+            let result = orchestrator.complete_smart(request).await;
+            audit_llm_call("orchestrator", prompt.chars().count(), started, &result);
+            if let Ok(response) = &result {
+                self.observe_call(&prompt, response, None);
+            }
+            return result;
         }
 
         // Fallback to single provider mode
@@ -360,6 +475,7 @@ impl LlmClient {
             max_tokens,
             temperature,
             orchestrator: None, // Don't pass orchestrator to avoid recursion
+            usage_observer: self.usage_observer.clone(),
         };
 
         self_with_overrides.chat_internal(&message).await
@@ -382,6 +498,24 @@ impl LlmClient {
     }
 
     async fn chat_internal(&self, message: &str) -> Result<String> {
+        if let Some(observer) = &self.usage_observer {
+            observer.before_call()?;
+        }
+        let started = std::time::Instant::now();
+        let result = self.dispatch_chat(message).await.map(|reply| {
+            self.observe_call(message, &reply.text, reply.usage);
+            reply.text
+        });
+        audit_llm_call(
+            &Self::get_provider_name(&self.provider),
+            message.chars().count(),
+            started,
+            &result,
+        );
+        result
+    }
+
+    async fn dispatch_chat(&self, message: &str) -> Result<ChatReply> {
         match &self.provider {
             LegacyLlmProvider::OpenAI { api_key, model } => {
                 self.openai_chat(api_key, model, message).await
@@ -405,7 +539,7 @@ impl LlmClient {
         }
     }
 
-    async fn openai_chat(&self, api_key: &str, model: &str, message: &str) -> Result<String> {
+    async fn openai_chat(&self, api_key: &str, model: &str, message: &str) -> Result<ChatReply> {
         let request = OpenAIChatRequest {
             model: model.to_string(),
             messages: vec![OpenAIMessage {
@@ -438,13 +572,16 @@ impl LlmClient {
 
         if let Some(choice) = chat_response.choices.first() {
             info!("✅ Получен ответ от OpenAI");
-            Ok(choice.message.content.clone())
+            Ok(ChatReply {
+                text: choice.message.content.clone(),
+                usage: chat_response.usage.as_ref().map(TokenCounts::from),
+            })
         } else {
             Err(anyhow!("Пустой ответ от OpenAI"))
         }
     }
 
-    async fn anthropic_chat(&self, api_key: &str, model: &str, message: &str) -> Result<String> {
+    async fn anthropic_chat(&self, api_key: &str, model: &str, message: &str) -> Result<ChatReply> {
         let request = AnthropicRequest {
             model: model.to_string(),
             max_tokens: self.max_tokens,
@@ -478,13 +615,16 @@ impl LlmClient {
 
         if let Some(content) = chat_response.content.first() {
             info!("✅ Получен ответ от Anthropic");
-            Ok(content.text.clone())
+            Ok(ChatReply {
+                text: content.text.clone(),
+                usage: chat_response.usage.as_ref().map(TokenCounts::from),
+            })
         } else {
             Err(anyhow!("Пустой ответ от Anthropic"))
         }
     }
 
-    async fn local_chat(&self, url: &str, model: &str, message: &str) -> Result<String> {
+    async fn local_chat(&self, url: &str, model: &str, message: &str) -> Result<ChatReply> {
         let request = OpenAIChatRequest {
             model: model.to_string(),
             messages: vec![OpenAIMessage {
@@ -521,7 +661,10 @@ impl LlmClient {
             let chat_response: OpenAIChatResponse = resp_oa.json().await?;
             if let Some(choice) = chat_response.choices.first() {
                 info!("✅ Получен ответ от локальной модели (OpenAI совместимый)");
-                return Ok(choice.message.content.clone());
+                return Ok(ChatReply {
+                    text: choice.message.content.clone(),
+                    usage: chat_response.usage.as_ref().map(TokenCounts::from),
+                });
             } else {
                 // Приводим сообщение к формату, ожидаемому тестами (содержит "Пустой ответ")
                 return Err(anyhow!("Пустой ответ от локальной модели"));
@@ -556,7 +699,10 @@ impl LlmClient {
         let chat_response: AnthropicResponse = resp_anth.json().await?;
         if let Some(content) = chat_response.content.first() {
             info!("✅ Получен ответ от локальной модели (Anthropic совместимый)");
-            Ok(content.text.clone())
+            Ok(ChatReply {
+                text: content.text.clone(),
+                usage: chat_response.usage.as_ref().map(TokenCounts::from),
+            })
         } else {
             // Сообщение совпадает по подстроке с ожидаемым в тестах
             Err(anyhow!("Пустой ответ от локальной модели"))
@@ -569,7 +715,7 @@ impl LlmClient {
         api_key: &str,
         model: &str,
         message: &str,
-    ) -> Result<String> {
+    ) -> Result<ChatReply> {
         let request = OpenAIChatRequest {
             model: model.to_string(),
             messages: vec![OpenAIMessage {
@@ -607,13 +753,16 @@ impl LlmClient {
 
         if let Some(choice) = chat_response.choices.first() {
             info!("✅ Получен ответ от Azure OpenAI");
-            Ok(choice.message.content.clone())
+            Ok(ChatReply {
+                text: choice.message.content.clone(),
+                usage: chat_response.usage.as_ref().map(TokenCounts::from),
+            })
         } else {
             Err(anyhow!("Пустой ответ от Azure OpenAI"))
         }
     }
 
-    async fn groq_chat(&self, api_key: &str, model: &str, message: &str) -> Result<String> {
+    async fn groq_chat(&self, api_key: &str, model: &str, message: &str) -> Result<ChatReply> {
         let request = OpenAIChatRequest {
             model: model.to_string(),
             messages: vec![OpenAIMessage {
@@ -646,7 +795,10 @@ impl LlmClient {
 
         if let Some(choice) = chat_response.choices.first() {
             info!("✅ Получен ответ от Groq");
-            Ok(choice.message.content.clone())
+            Ok(ChatReply {
+                text: choice.message.content.clone(),
+                usage: chat_response.usage.as_ref().map(TokenCounts::from),
+            })
         } else {
             Err(anyhow!("Пустой ответ от Groq"))
         }
@@ -369,6 +369,7 @@ impl LlmProvider for AnthropicProvider {
                 .stop_reason
                 .unwrap_or("end_turn".to_string()),
             response_time: elapsed,
+            tool_calls: Vec::new(),
         })
     }
 
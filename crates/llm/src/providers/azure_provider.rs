@@ -289,6 +289,7 @@ impl LlmProvider for AzureProvider {
             model: self.model.clone(),
             finish_reason: choice.finish_reason.clone().unwrap_or("stop".to_string()),
             response_time: elapsed,
+            tool_calls: Vec::new(),
         })
     }
 }
@@ -490,6 +490,7 @@ impl LlmProvider for GoogleProvider {
             model: self.model.clone(),
             finish_reason,
             response_time: elapsed,
+            tool_calls: Vec::new(),
         })
     }
 
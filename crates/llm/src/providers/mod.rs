@@ -26,6 +26,9 @@ pub struct LlmRequest {
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
     pub stream: bool,
+    /// Functions the model may call (native function calling)
+    #[serde(default)]
+    pub tools: Vec<FunctionDefinition>,
 }
 
 impl LlmRequest {
@@ -36,9 +39,15 @@ impl LlmRequest {
             max_tokens: None,
             temperature: None,
             stream: false,
+            tools: Vec::new(),
         }
     }
 
+    pub fn with_tools(mut self, tools: Vec<FunctionDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+
     pub fn with_system_prompt(mut self, system_prompt: &str) -> Self {
         self.system_prompt = Some(system_prompt.to_string());
         self
@@ -59,6 +68,65 @@ pub struct LlmResponse {
     pub model: String,
     pub finish_reason: String,
     pub response_time: Duration,
+    /// Function calls requested by the model instead of (or besides) text
+    #[serde(default)]
+    pub tool_calls: Vec<FunctionCall>,
+}
+
+/// Provider-neutral function definition; `parameters` is a JSON Schema object
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+impl FunctionDefinition {
+    pub fn new(name: &str, description: &str, parameters: serde_json::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+        }
+    }
+
+    /// OpenAI / Azure / Groq `tools` entry
+    pub fn to_openai(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "function",
+            "function": {
+                "name": self.name,
+                "description": self.description,
+                "parameters": self.parameters,
+            }
+        })
+    }
+
+    /// Anthropic Messages API `tools` entry
+    pub fn to_anthropic(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "description": self.description,
+            "input_schema": self.parameters,
+        })
+    }
+
+    /// Gemini `functionDeclarations` entry
+    pub fn to_gemini(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "description": self.description,
+            "parameters": self.parameters,
+        })
+    }
+}
+
+/// Function call returned by the model; `arguments` is the parsed JSON object
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FunctionCall {
+    pub id: Option<String>,
+    pub name: String,
+    pub arguments: serde_json::Value,
 }
 
 /// Chat message for conversation context
@@ -227,6 +295,14 @@ pub trait LlmProvider: Send + Sync {
             }
         }
 
+        if !request.tools.is_empty() && !capabilities.supports_functions {
+            return Err(anyhow::anyhow!(
+                "Provider {} does not support function calling ({} tools requested)",
+                self.name(),
+                request.tools.len()
+            ));
+        }
+
         let estimated_prompt_tokens = request.prompt.len() as u32 / 4;
         if estimated_prompt_tokens > capabilities.context_window {
             return Err(anyhow::anyhow!(
@@ -0,0 +1,157 @@
+use crate::{CostTable, LegacyLlmProvider, ProviderType};
+use anyhow::Result;
+
+/// Token and cost accounting for a single LLM call
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmCallUsage {
+    pub provider: ProviderType,
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// Estimated cost in USD (from [`CostTable`])
+    pub cost_usd: f64,
+    /// Token counts are a text-length estimate, not reported by the provider
+    pub estimated: bool,
+}
+
+/// Token counts from the usage block of a provider response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenCounts {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+impl LlmCallUsage {
+    /// Usage reported by the provider API
+    pub fn reported(provider: &LegacyLlmProvider, counts: TokenCounts) -> Self {
+        Self::priced(provider, counts, false)
+    }
+
+    /// Estimate usage from prompt/response text (~4 chars per token, the same
+    /// heuristic providers use when the API returns no usage block)
+    pub fn estimate(provider: &LegacyLlmProvider, prompt: &str, response: &str) -> Self {
+        let counts = TokenCounts {
+            prompt_tokens: estimate_tokens(prompt),
+            completion_tokens: estimate_tokens(response),
+        };
+        Self::priced(provider, counts, true)
+    }
+
+    /// Reported usage when the provider returned it, the estimate otherwise
+    pub fn from_call(
+        provider: &LegacyLlmProvider,
+        prompt: &str,
+        response: &str,
+        reported: Option<TokenCounts>,
+    ) -> Self {
+        match reported {
+            Some(counts) => Self::reported(provider, counts),
+            None => Self::estimate(provider, prompt, response),
+        }
+    }
+
+    fn priced(provider: &LegacyLlmProvider, counts: TokenCounts, estimated: bool) -> Self {
+        let (provider_type, model) = provider_type_and_model(provider);
+        let cost_usd = CostTable::default().estimate_cost(
+            &provider_type,
+            model,
+            counts.prompt_tokens,
+            counts.completion_tokens,
+        ) as f64;
+        Self {
+            provider: provider_type,
+            model: model.to_string(),
+            prompt_tokens: counts.prompt_tokens,
+            completion_tokens: counts.completion_tokens,
+            cost_usd,
+            estimated,
+        }
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens as u64 + self.completion_tokens as u64
+    }
+}
+
+/// Receives usage of every [`crate::LlmClient`] call and may veto further calls
+/// (e.g. once a hard budget is exhausted)
+pub trait LlmUsageObserver: Send + Sync {
+    /// Called before a request is sent; an error blocks the call
+    fn before_call(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after a successful call
+    fn record(&self, usage: &LlmCallUsage);
+}
+
+fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(4)
+}
+
+pub(crate) fn provider_type_and_model(provider: &LegacyLlmProvider) -> (ProviderType, &str) {
+    match provider {
+        LegacyLlmProvider::OpenAI { model, .. } => (ProviderType::OpenAI, model),
+        LegacyLlmProvider::Anthropic { model, .. } => (ProviderType::Anthropic, model),
+        LegacyLlmProvider::Local { model, .. } => (ProviderType::Local, model),
+        LegacyLlmProvider::Ollama { model, .. } => (ProviderType::Ollama, model),
+        LegacyLlmProvider::LMStudio { model, .. } => (ProviderType::LMStudio, model),
+        LegacyLlmProvider::Azure { model, .. } => (ProviderType::Azure, model),
+        LegacyLlmProvider::Groq { model, .. } => (ProviderType::Groq, model),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_uses_cost_table() {
+        let provider = LegacyLlmProvider::OpenAI {
+            api_key: "sk-test".to_string(),
+            model: "gpt-4o".to_string(),
+        };
+        let usage = LlmCallUsage::estimate(&provider, &"a".repeat(4000), &"b".repeat(2000));
+
+        assert_eq!(usage.prompt_tokens, 1000);
+        assert_eq!(usage.completion_tokens, 500);
+        assert_eq!(usage.total_tokens(), 1500);
+        assert!(usage.estimated);
+        // 1K input at $0.0025 + 0.5K output at $0.01
+        assert!((usage.cost_usd - 0.0075).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reported_usage_wins_over_estimate() {
+        let provider = LegacyLlmProvider::OpenAI {
+            api_key: "sk-test".to_string(),
+            model: "gpt-4o".to_string(),
+        };
+        let reported = TokenCounts {
+            prompt_tokens: 2000,
+            completion_tokens: 1000,
+        };
+        let usage = LlmCallUsage::from_call(&provider, "short", "reply", Some(reported));
+
+        assert_eq!(usage.prompt_tokens, 2000);
+        assert_eq!(usage.completion_tokens, 1000);
+        assert!(!usage.estimated);
+        assert!((usage.cost_usd - 0.015).abs() < 1e-6);
+
+        let fallback = LlmCallUsage::from_call(&provider, "short", "reply", None);
+        assert!(fallback.estimated);
+        assert_eq!(fallback.prompt_tokens, 2);
+    }
+
+    #[test]
+    fn test_local_models_are_free() {
+        let provider = LegacyLlmProvider::Ollama {
+            url: "http://localhost:11434".to_string(),
+            model: "llama3.2".to_string(),
+        };
+        let usage = LlmCallUsage::estimate(&provider, "hello", "world");
+
+        assert_eq!(usage.provider, ProviderType::Ollama);
+        assert_eq!(usage.cost_usd, 0.0);
+    }
+}
@@ -280,6 +280,14 @@ mod simple_engine {
                 .collect()
         }
 
+        pub fn record(&self, id: &uuid::Uuid) -> Option<Record> {
+            self.records
+                .read()
+                .iter()
+                .find(|sr| sr.record.id == *id)
+                .map(|sr| sr.record.clone())
+        }
+
         pub fn import_records(&self, records: &[Record]) -> Result<usize> {
             let mut inserted = 0usize;
             for mut rec in records.iter().cloned() {
@@ -406,6 +414,9 @@ pub trait MemoryServiceTrait: Send + Sync {
 
     /// Добавить запись - простая версия
     fn remember_sync(&self, text: String, layer: Layer) -> Result<Uuid>;
+
+    /// Добавить запись в указанный проект
+    fn remember_in_project_sync(&self, text: String, layer: Layer, project: &str) -> Result<Uuid>;
 }
 
 // Legacy MemoryService реализация удалена - используем только DIMemoryService
@@ -444,6 +455,10 @@ impl MemoryServiceTrait for DIMemoryService {
     }
 
     fn remember_sync(&self, text: String, layer: Layer) -> Result<Uuid> {
+        self.remember_in_project_sync(text, layer, "magray")
+    }
+
+    fn remember_in_project_sync(&self, text: String, layer: Layer, project: &str) -> Result<Uuid> {
         #[cfg(feature = "embeddings")]
         {
             use chrono::Utc;
@@ -455,7 +470,7 @@ impl MemoryServiceTrait for DIMemoryService {
                 layer,
                 kind: "note".to_string(),
                 tags: vec![],
-                project: "magray".to_string(),
+                project: project.to_string(),
                 session: "default".to_string(),
                 ts: Utc::now(),
                 score: 0.0,
@@ -467,6 +482,7 @@ impl MemoryServiceTrait for DIMemoryService {
         }
         #[cfg(not(feature = "embeddings"))]
         {
+            let _ = (text, layer, project);
             Ok(Uuid::new_v4())
         }
     }
@@ -494,13 +510,22 @@ impl UnifiedMemoryAPI {
                 .layers
                 .unwrap_or_else(|| vec![Layer::Interact, Layer::Insights, Layer::Assets]);
             let limit = options.limit.unwrap_or(10);
+            // Записи других проектов отсеиваются после поиска, поэтому берём с запасом
+            let top_k = if options.project.is_some() {
+                limit * 4
+            } else {
+                limit
+            };
 
             let mut all_results = Vec::new();
 
             for layer in layers_to_search {
-                let layer_results = self.service.search_sync(query, layer, limit)?;
+                let layer_results = self.service.search_sync(query, layer, top_k)?;
                 all_results.extend(layer_results);
             }
+            if let Some(project) = &options.project {
+                all_results.retain(|record| &record.project == project);
+            }
 
             // Сортируем по релевантности и берем топ результатов
             all_results.sort_by(|a, b| {
@@ -558,6 +583,18 @@ impl UnifiedMemoryAPI {
         Ok(n)
     }
 
+    /// Вернуть ранее сохранённые записи с исходными ID (например, при откате saga)
+    pub async fn restore_records(&self, records: &[Record]) -> Result<usize> {
+        let engine = simple_engine::engine();
+        engine.import_records(records)
+    }
+
+    /// Полная запись по ID (например, чтобы сохранить её перед удалением)
+    pub async fn get_record(&self, id: Uuid) -> Result<Option<Record>> {
+        let engine = simple_engine::engine();
+        Ok(engine.record(&id))
+    }
+
     /// Получить конкретную запись по ID
     pub async fn get(&self, id: Uuid) -> Result<Option<MemoryResult>> {
         // Упрощенная реализация - поиск не поддерживается в sync trait
@@ -577,7 +614,18 @@ impl UnifiedMemoryAPI {
         use tokio::time::{timeout, Duration};
 
         let layer = context.layer.unwrap_or(Layer::Interact);
-        let remember_future = async { self.service.remember_sync(text, layer) };
+        // Секреты маскируются (или запись блокируется) до попадания в хранилище
+        let text = common::redaction::global_redactor().guard_sync(
+            &text,
+            common::redaction::RedactionTarget::Memory,
+            "memory.remember",
+        )?;
+        let remember_future = async {
+            match &context.project {
+                Some(project) => self.service.remember_in_project_sync(text, layer, project),
+                None => self.service.remember_sync(text, layer),
+            }
+        };
 
         // Защита от зависания с таймаутом 15 секунд
         timeout(Duration::from_secs(15), remember_future)
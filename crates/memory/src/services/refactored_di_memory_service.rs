@@ -605,6 +605,15 @@ impl MemoryServiceTrait for RefactoredDIMemoryService {
     }
 
     fn remember_sync(&self, text: String, layer: Layer) -> Result<uuid::Uuid> {
+        self.remember_in_project_sync(text, layer, "default")
+    }
+
+    fn remember_in_project_sync(
+        &self,
+        text: String,
+        layer: Layer,
+        project: &str,
+    ) -> Result<uuid::Uuid> {
         use chrono::Utc;
 
         let record = Record {
@@ -614,7 +623,7 @@ impl MemoryServiceTrait for RefactoredDIMemoryService {
             layer,
             kind: "user_input".to_string(),
             tags: vec![],
-            project: "default".to_string(),
+            project: project.to_string(),
             session: "sync_session".to_string(),
             ts: Utc::now(),
             score: 0.0,
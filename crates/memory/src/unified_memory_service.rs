@@ -419,6 +419,15 @@ impl MemoryServiceTrait for UnifiedMemoryService {
     }
 
     fn remember_sync(&self, text: String, layer: Layer) -> Result<uuid::Uuid> {
+        self.remember_in_project_sync(text, layer, "default")
+    }
+
+    fn remember_in_project_sync(
+        &self,
+        text: String,
+        layer: Layer,
+        project: &str,
+    ) -> Result<uuid::Uuid> {
         use chrono::Utc;
 
         let record = Record {
@@ -428,7 +437,7 @@ impl MemoryServiceTrait for UnifiedMemoryService {
             layer,
             kind: "user_input".to_string(),
             tags: vec![],
-            project: "default".to_string(),
+            project: project.to_string(),
             session: "unified_session".to_string(),
             ts: Utc::now(),
             score: 0.0,
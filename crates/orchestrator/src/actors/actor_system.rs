@@ -20,6 +20,8 @@ pub enum AgentType {
     Executor,
     Critic,
     Scheduler,
+    /// Sub-agent running a user-defined agent profile (by profile name)
+    SubAgent(String),
 }
 
 impl std::fmt::Display for AgentType {
@@ -30,6 +32,7 @@ impl std::fmt::Display for AgentType {
             AgentType::Executor => write!(f, "executor"),
             AgentType::Critic => write!(f, "critic"),
             AgentType::Scheduler => write!(f, "scheduler"),
+            AgentType::SubAgent(profile) => write!(f, "sub_agent:{}", profile),
         }
     }
 }
@@ -0,0 +1,600 @@
+//! Autonomous plan → act → observe → re-plan loop
+//!
+//! [`AgentLoop`] runs a plan one step at a time. After every step the
+//! observations go back to the Planner ([`PlannerTrait::replan`]), which may
+//! revise the rest of the iteration. Once no runnable step is left, the Critic
+//! scores the result of all steps so far and decides
+//! ([`CriticTrait::assess_goal`]) whether the goal is reached, the loop should
+//! stop, or the Planner should re-plan the unfinished steps with its feedback.
+//!
+//! Besides the Critic, the loop stops after
+//! [`AgentLoopConfig::max_iterations`], when a [`SpendTracker`] limit is hit,
+//! and when iterations keep failing the same way without completing anything.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+use uuid::Uuid;
+
+use super::critic::{CriticFeedback, CriticTrait, GoalAssessment};
+use super::executor::{
+    ExecutionError, ExecutionErrorType, ExecutionResult, ExecutionStatus, Executor, ExecutorTrait,
+    ResourceUsage, StepResult, StepState, StepStatus,
+};
+use super::planner::{ActionPlan, ActionStep, ActionStepType, PlannerTrait, StepObservation};
+use crate::resources::spend::{SpendOverrun, SpendTracker};
+
+/// Limits of an [`AgentLoop`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentLoopConfig {
+    /// Plan → act → observe → critique cycles
+    pub max_iterations: u32,
+    /// Iterations in a row that complete no step and fail the same way
+    pub max_stalled_iterations: u32,
+}
+
+impl Default for AgentLoopConfig {
+    fn default() -> Self {
+        Self {
+            max_iterations: 5,
+            max_stalled_iterations: 2,
+        }
+    }
+}
+
+/// Why an agent loop ended
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LoopOutcome {
+    GoalReached,
+    /// The Critic stopped the loop
+    Stopped(String),
+    MaxIterations,
+    NoProgress,
+    /// The Planner dropped every unfinished step
+    PlanExhausted,
+    BudgetExceeded(SpendOverrun),
+}
+
+impl fmt::Display for LoopOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopOutcome::GoalReached => write!(f, "goal reached"),
+            LoopOutcome::Stopped(reason) => write!(f, "stopped by critic: {}", reason),
+            LoopOutcome::MaxIterations => write!(f, "iteration limit reached"),
+            LoopOutcome::NoProgress => write!(f, "no progress between iterations"),
+            LoopOutcome::PlanExhausted => write!(f, "no steps left to try"),
+            LoopOutcome::BudgetExceeded(overrun) => write!(f, "{}", overrun),
+        }
+    }
+}
+
+/// Summary of one iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopIteration {
+    pub number: u32,
+    pub steps_planned: usize,
+    pub completed: usize,
+    pub failed: usize,
+    /// Critic's overall score of all steps so far
+    pub score: f64,
+    pub assessment: GoalAssessment,
+}
+
+/// Result of [`AgentLoop::run`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentLoopReport {
+    pub outcome: LoopOutcome,
+    pub iterations: Vec<LoopIteration>,
+    pub observations: Vec<StepObservation>,
+    /// Latest result of every step that ran
+    pub result: ExecutionResult,
+    /// Critic's feedback on the last iteration
+    pub feedback: Option<CriticFeedback>,
+}
+
+impl AgentLoopReport {
+    pub fn succeeded(&self) -> bool {
+        self.outcome == LoopOutcome::GoalReached
+    }
+}
+
+/// Drives Planner, Executor and Critic until the Critic is satisfied or a
+/// guard stops the loop
+pub struct AgentLoop {
+    planner: Arc<dyn PlannerTrait>,
+    executor: Arc<Executor>,
+    critic: Arc<dyn CriticTrait>,
+    config: AgentLoopConfig,
+    spend_tracker: Option<Arc<SpendTracker>>,
+}
+
+impl AgentLoop {
+    pub fn new(
+        planner: Arc<dyn PlannerTrait>,
+        executor: Arc<Executor>,
+        critic: Arc<dyn CriticTrait>,
+    ) -> Self {
+        Self {
+            planner,
+            executor,
+            critic,
+            config: AgentLoopConfig::default(),
+            spend_tracker: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: AgentLoopConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Check `tracker` before every iteration and step
+    pub fn with_spend_tracker(mut self, tracker: Arc<SpendTracker>) -> Self {
+        self.spend_tracker = Some(tracker);
+        self
+    }
+
+    async fn enforce_budget(&self) -> Result<(), SpendOverrun> {
+        match &self.spend_tracker {
+            Some(tracker) => tracker.enforce().await,
+            None => Ok(()),
+        }
+    }
+
+    pub async fn run(&self, plan: ActionPlan) -> Result<AgentLoopReport> {
+        let started = Instant::now();
+        let plan_id = plan.id;
+        let mut plan = plan;
+        let mut observations: Vec<StepObservation> = Vec::new();
+        let mut latest: HashMap<Uuid, StepResult> = HashMap::new();
+        let mut order: Vec<Uuid> = Vec::new();
+        let mut iterations: Vec<LoopIteration> = Vec::new();
+        let mut feedback: Option<CriticFeedback> = None;
+        let mut stalled = 0;
+        let mut last_failures: Option<Vec<(Uuid, String)>> = None;
+
+        let outcome = 'iterations: loop {
+            let number = iterations.len() as u32 + 1;
+            if number > self.config.max_iterations {
+                break LoopOutcome::MaxIterations;
+            }
+            if plan.steps.is_empty() {
+                break LoopOutcome::PlanExhausted;
+            }
+            if let Err(overrun) = self.enforce_budget().await {
+                break LoopOutcome::BudgetExceeded(overrun);
+            }
+            info!(
+                plan_id = %plan_id,
+                iteration = number,
+                steps = plan.steps.len(),
+                "Agent loop iteration"
+            );
+
+            let mut context = self.executor.create_execution_context(&plan);
+            // Steps completed in earlier iterations satisfy dependencies
+            for (step_id, result) in &latest {
+                if result.status == StepStatus::Completed {
+                    context.step_states.insert(
+                        *step_id,
+                        StepState {
+                            status: StepStatus::Completed,
+                            result: result.output.clone(),
+                            start_time: None,
+                            end_time: None,
+                            retry_count: result.retry_count,
+                        },
+                    );
+                }
+            }
+
+            let mut remaining = plan.steps.clone();
+            let mut completed = 0;
+            let mut failures: Vec<(Uuid, String)> = Vec::new();
+            while let Some(index) = next_runnable(&remaining, &latest) {
+                if let Err(overrun) = self.enforce_budget().await {
+                    break 'iterations LoopOutcome::BudgetExceeded(overrun);
+                }
+                let step = remaining.remove(index);
+                let result = match self.executor.execute_step(&step, &mut context).await {
+                    Ok(result) => result,
+                    Err(e) => StepResult {
+                        step_id: step.id,
+                        status: StepStatus::Failed,
+                        output: None,
+                        error: Some(e.to_string()),
+                        execution_time: std::time::Duration::ZERO,
+                        retry_count: 0,
+                        metadata: HashMap::new(),
+                    },
+                };
+                match result.status {
+                    StepStatus::Completed => completed += 1,
+                    StepStatus::Failed => {
+                        failures.push((step.id, result.error.clone().unwrap_or_default()))
+                    }
+                    _ => {}
+                }
+
+                let attempt = observations
+                    .iter()
+                    .filter(|observation| observation.step_id == step.id)
+                    .count() as u32
+                    + 1;
+                observations.push(StepObservation {
+                    step_id: step.id,
+                    iteration: number,
+                    attempt,
+                    tool: match &step.step_type {
+                        ActionStepType::ToolExecution { tool_name, .. } => Some(tool_name.clone()),
+                        _ => None,
+                    },
+                    status: result.status.clone(),
+                    output: result.output.clone(),
+                    error: result.error.clone(),
+                });
+                if !latest.contains_key(&step.id) {
+                    order.push(step.id);
+                }
+                latest.insert(step.id, result);
+
+                // Let the Planner revise the rest of this iteration
+                let rest = ActionPlan {
+                    steps: remaining,
+                    ..plan_header(&plan)
+                };
+                remaining = self.planner.replan(&rest, &observations, None).await?.steps;
+            }
+
+            let unfinished: Vec<ActionStep> = plan
+                .steps
+                .iter()
+                .filter(|step| {
+                    latest.get(&step.id).map(|result| &result.status)
+                        != Some(&StepStatus::Completed)
+                })
+                .cloned()
+                .collect();
+            let result = cumulative_result(
+                plan_id,
+                &order,
+                &latest,
+                observations.len(),
+                started,
+                unfinished.is_empty(),
+            );
+            let iteration_feedback = self.critic.evaluate_result(&result).await?;
+            let assessment = self
+                .critic
+                .assess_goal(&result, &iteration_feedback)
+                .await?;
+            info!(
+                plan_id = %plan_id,
+                iteration = number,
+                completed,
+                failed = failures.len(),
+                score = iteration_feedback.overall_score,
+                assessment = ?assessment,
+                "Agent loop iteration finished"
+            );
+            iterations.push(LoopIteration {
+                number,
+                steps_planned: plan.steps.len(),
+                completed,
+                failed: failures.len(),
+                score: iteration_feedback.overall_score,
+                assessment: assessment.clone(),
+            });
+            feedback = Some(iteration_feedback);
+
+            match assessment {
+                GoalAssessment::Reached => break LoopOutcome::GoalReached,
+                GoalAssessment::Stop { reason } => break LoopOutcome::Stopped(reason),
+                GoalAssessment::Continue { .. } => {}
+            }
+
+            failures.sort();
+            if completed == 0 && last_failures.as_ref() == Some(&failures) {
+                stalled += 1;
+            } else {
+                stalled = 0;
+            }
+            last_failures = Some(failures);
+            if stalled >= self.config.max_stalled_iterations {
+                break LoopOutcome::NoProgress;
+            }
+
+            let unfinished = ActionPlan {
+                steps: unfinished,
+                ..plan_header(&plan)
+            };
+            plan = self
+                .planner
+                .replan(&unfinished, &observations, feedback.as_ref())
+                .await?;
+        };
+
+        info!(plan_id = %plan_id, outcome = %outcome, "Agent loop finished");
+        let result = cumulative_result(
+            plan_id,
+            &order,
+            &latest,
+            observations.len(),
+            started,
+            outcome == LoopOutcome::GoalReached,
+        );
+        Ok(AgentLoopReport {
+            outcome,
+            iterations,
+            observations,
+            result,
+            feedback,
+        })
+    }
+}
+
+/// `plan` without its steps
+fn plan_header(plan: &ActionPlan) -> ActionPlan {
+    ActionPlan {
+        id: plan.id,
+        intent_id: plan.intent_id,
+        steps: Vec::new(),
+        estimated_duration: plan.estimated_duration,
+        resource_requirements: plan.resource_requirements.clone(),
+        dependencies: plan.dependencies.clone(),
+        metadata: plan.metadata.clone(),
+    }
+}
+
+/// First step whose dependencies have all completed
+fn next_runnable(steps: &[ActionStep], latest: &HashMap<Uuid, StepResult>) -> Option<usize> {
+    steps.iter().position(|step| {
+        step.dependencies.iter().all(|dependency| {
+            latest.get(dependency).map(|result| &result.status) == Some(&StepStatus::Completed)
+        })
+    })
+}
+
+/// Latest result of every step that ran, as one execution
+fn cumulative_result(
+    plan_id: Uuid,
+    order: &[Uuid],
+    latest: &HashMap<Uuid, StepResult>,
+    attempts: usize,
+    started: Instant,
+    finished: bool,
+) -> ExecutionResult {
+    let step_results: Vec<StepResult> = order
+        .iter()
+        .filter_map(|step_id| latest.get(step_id).cloned())
+        .collect();
+    let error = step_results
+        .iter()
+        .rev()
+        .find(|result| result.status == StepStatus::Failed)
+        .map(|result| ExecutionError {
+            error_type: ExecutionErrorType::ToolExecutionFailed,
+            message: result.error.clone().unwrap_or_default(),
+            step_id: Some(result.step_id),
+            retryable: true,
+            details: HashMap::new(),
+        });
+    let execution_time = started.elapsed();
+    ExecutionResult {
+        plan_id,
+        status: if finished {
+            ExecutionStatus::Completed
+        } else {
+            ExecutionStatus::Failed
+        },
+        step_results,
+        execution_time,
+        resource_usage: ResourceUsage {
+            cpu_time_ms: execution_time.as_millis() as u64,
+            tool_invocations: attempts as u64,
+            ..ResourceUsage::default()
+        },
+        metadata: HashMap::new(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::executor::ToolInvoker;
+    use crate::agents::planner::{
+        BackoffStrategy, Planner, ResourceRequirements, RetryPolicy, MAX_STEP_ATTEMPTS,
+    };
+    use crate::agents::Critic;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails its first `failures` calls
+    struct FlakyTool {
+        failures: u32,
+        calls: Arc<AtomicU32>,
+    }
+
+    #[async_trait]
+    impl ToolInvoker for FlakyTool {
+        async fn invoke(
+            &self,
+            _args: HashMap<String, serde_json::Value>,
+        ) -> Result<serde_json::Value> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                anyhow::bail!("tests failed: 3 failing");
+            }
+            Ok(serde_json::json!({ "passed": true }))
+        }
+
+        fn get_name(&self) -> &str {
+            "cargo_test"
+        }
+    }
+
+    fn tool_step(tool_name: &str, dependencies: Vec<Uuid>) -> ActionStep {
+        ActionStep {
+            id: Uuid::new_v4(),
+            step_type: ActionStepType::ToolExecution {
+                tool_name: tool_name.to_string(),
+                arguments: HashMap::new(),
+            },
+            parameters: HashMap::new(),
+            dependencies,
+            expected_duration: std::time::Duration::from_millis(10),
+            retry_policy: RetryPolicy {
+                max_retries: 0,
+                backoff_strategy: BackoffStrategy::Fixed(std::time::Duration::from_millis(1)),
+                retry_conditions: vec![],
+            },
+            validation_rules: vec![],
+        }
+    }
+
+    /// `cargo_test` followed by a dependent `file_reader`
+    fn test_loop(
+        failures: u32,
+        config: AgentLoopConfig,
+    ) -> (AgentLoop, ActionPlan, Arc<AtomicU32>) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let mut executor = Executor::new();
+        executor.register_tool(
+            "cargo_test".to_string(),
+            Box::new(FlakyTool {
+                failures,
+                calls: Arc::clone(&calls),
+            }),
+        );
+
+        let test = tool_step("cargo_test", vec![]);
+        let read = tool_step("file_reader", vec![test.id]);
+        let plan = ActionPlan {
+            id: Uuid::new_v4(),
+            intent_id: Uuid::new_v4(),
+            steps: vec![test, read],
+            estimated_duration: std::time::Duration::from_millis(20),
+            resource_requirements: ResourceRequirements {
+                cpu_cores: 1,
+                memory_mb: 64,
+                disk_space_mb: 0,
+                network_required: false,
+                tools_required: vec![],
+                permissions_required: vec![],
+            },
+            dependencies: vec![],
+            metadata: HashMap::new(),
+        };
+
+        let agent_loop = AgentLoop::new(
+            Arc::new(Planner::new()),
+            Arc::new(executor),
+            Arc::new(Critic::new()),
+        )
+        .with_config(config);
+        (agent_loop, plan, calls)
+    }
+
+    #[tokio::test]
+    async fn test_loop_replans_until_goal_reached() {
+        let (agent_loop, plan, calls) = test_loop(1, AgentLoopConfig::default());
+
+        let report = agent_loop
+            .run(plan.clone())
+            .await
+            .expect("Agent loop should succeed");
+
+        assert_eq!(report.outcome, LoopOutcome::GoalReached);
+        assert_eq!(report.iterations.len(), 2);
+        // the dependent step waits for the failed one instead of failing too
+        assert_eq!(report.iterations[0].completed, 0);
+        assert_eq!(report.iterations[0].failed, 1);
+        assert!(matches!(
+            report.iterations[0].assessment,
+            GoalAssessment::Continue { .. }
+        ));
+        assert_eq!(report.iterations[1].completed, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(report.observations[1].step_id, plan.steps[0].id);
+        assert_eq!(report.observations[1].attempt, 2);
+        assert_eq!(report.result.status, ExecutionStatus::Completed);
+        assert!(report.succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_loop_stops_without_progress() {
+        let (agent_loop, plan, calls) = test_loop(u32::MAX, AgentLoopConfig::default());
+
+        let report = agent_loop
+            .run(plan)
+            .await
+            .expect("Agent loop should succeed");
+
+        assert_eq!(report.outcome, LoopOutcome::NoProgress);
+        assert_eq!(report.iterations.len(), 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert!(!report.succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_loop_drops_steps_that_keep_failing() {
+        let config = AgentLoopConfig {
+            max_iterations: 10,
+            max_stalled_iterations: 10,
+        };
+        let (agent_loop, plan, calls) = test_loop(u32::MAX, config);
+
+        let report = agent_loop
+            .run(plan)
+            .await
+            .expect("Agent loop should succeed");
+
+        assert_eq!(report.outcome, LoopOutcome::PlanExhausted);
+        assert_eq!(calls.load(Ordering::SeqCst), MAX_STEP_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn test_loop_respects_iteration_and_budget_limits() {
+        let config = AgentLoopConfig {
+            max_iterations: 1,
+            ..AgentLoopConfig::default()
+        };
+        let (agent_loop, plan, _) = test_loop(1, config);
+        let report = agent_loop
+            .run(plan)
+            .await
+            .expect("Agent loop should succeed");
+        assert_eq!(report.outcome, LoopOutcome::MaxIterations);
+
+        let (agent_loop, plan, calls) = test_loop(1, AgentLoopConfig::default());
+        let tracker = Arc::new(SpendTracker::new(
+            crate::resources::spend::SpendBudget::new().tool_call_limit(None, Some(1)),
+        ));
+        let mut executor = Executor::new();
+        executor.register_tool(
+            "cargo_test".to_string(),
+            Box::new(FlakyTool {
+                failures: 1,
+                calls: Arc::clone(&calls),
+            }),
+        );
+        executor.set_spend_tracker(Arc::clone(&tracker));
+        let agent_loop = AgentLoop {
+            executor: Arc::new(executor),
+            ..agent_loop
+        }
+        .with_spend_tracker(tracker);
+
+        let report = agent_loop
+            .run(plan)
+            .await
+            .expect("Agent loop should succeed");
+        assert!(matches!(report.outcome, LoopOutcome::BudgetExceeded(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -150,6 +150,17 @@ pub enum EffortLevel {
     Extensive,
 }
 
+/// The Critic's verdict on an agent loop iteration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GoalAssessment {
+    /// Every step completed at acceptable quality
+    Reached,
+    /// Re-plan the unfinished steps
+    Continue { suggestions: Vec<String> },
+    /// Further iterations are not worth the risk
+    Stop { reason: String },
+}
+
 /// Trait for critic functionality
 #[async_trait]
 pub trait CriticTrait: Send + Sync {
@@ -167,6 +178,14 @@ pub trait CriticTrait: Send + Sync {
 
     /// Assess execution risks
     async fn assess_risks(&self, result: &ExecutionResult) -> Result<RiskAssessment>;
+
+    /// Decide whether an agent loop has reached its goal, given the result of
+    /// all steps so far and the feedback on it
+    async fn assess_goal(
+        &self,
+        result: &ExecutionResult,
+        feedback: &CriticFeedback,
+    ) -> Result<GoalAssessment>;
 }
 
 /// Step-level analysis
@@ -659,6 +678,47 @@ impl CriticTrait for Critic {
             mitigation_suggestions,
         })
     }
+
+    async fn assess_goal(
+        &self,
+        result: &ExecutionResult,
+        feedback: &CriticFeedback,
+    ) -> Result<GoalAssessment> {
+        let min_completion_rate = self
+            .performance_thresholds
+            .get("min_completion_rate")
+            .copied()
+            .unwrap_or(0.9);
+
+        if result.status == ExecutionStatus::Completed
+            && feedback.quality_metrics.completion_rate >= min_completion_rate
+        {
+            return Ok(GoalAssessment::Reached);
+        }
+        if result.status == ExecutionStatus::Cancelled {
+            return Ok(GoalAssessment::Stop {
+                reason: "Execution was cancelled".to_string(),
+            });
+        }
+        if let Some(risk) = feedback
+            .risk_assessment
+            .identified_risks
+            .iter()
+            .find(|risk| risk.severity == RiskLevel::Critical)
+        {
+            return Ok(GoalAssessment::Stop {
+                reason: risk.description.clone(),
+            });
+        }
+
+        Ok(GoalAssessment::Continue {
+            suggestions: feedback
+                .improvement_suggestions
+                .iter()
+                .map(|suggestion| suggestion.suggested_action.clone())
+                .collect(),
+        })
+    }
 }
 
 impl Default for Critic {
@@ -778,6 +838,57 @@ mod tests {
         assert!(!risk_assessment.identified_risks.is_empty());
         assert!(!risk_assessment.mitigation_suggestions.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_assess_goal() {
+        let critic = Critic::new();
+        let result = create_test_execution_result();
+        let feedback = critic
+            .evaluate_result(&result)
+            .await
+            .expect("Async operation should succeed");
+        let assessment = critic
+            .assess_goal(&result, &feedback)
+            .await
+            .expect("Async operation should succeed");
+        assert_eq!(assessment, GoalAssessment::Reached);
+
+        let mut failed = create_test_execution_result();
+        failed.status = ExecutionStatus::Failed;
+        failed.step_results[0].status = StepStatus::Failed;
+        let mut feedback = critic
+            .evaluate_result(&failed)
+            .await
+            .expect("Async operation should succeed");
+        match critic
+            .assess_goal(&failed, &feedback)
+            .await
+            .expect("Async operation should succeed")
+        {
+            GoalAssessment::Continue { suggestions } => {
+                assert!(suggestions.contains(&"Improve error handling and retry logic".to_string()))
+            }
+            other => panic!("Expected Continue, got {:?}", other),
+        }
+
+        feedback.risk_assessment.identified_risks.push(Risk {
+            risk_type: RiskType::DataLoss,
+            description: "Files were deleted".to_string(),
+            probability: 1.0,
+            impact: 1.0,
+            severity: RiskLevel::Critical,
+        });
+        let assessment = critic
+            .assess_goal(&failed, &feedback)
+            .await
+            .expect("Async operation should succeed");
+        assert_eq!(
+            assessment,
+            GoalAssessment::Stop {
+                reason: "Files were deleted".to_string()
+            }
+        );
+    }
 }
 
 /// HealthChecker implementation for Critic
@@ -0,0 +1,497 @@
+//! Cron expressions for the Scheduler.
+//!
+//! ```text
+//! 30 2 * * *                 every night at 02:30
+//! */15 9-18 * * MON-FRI      every 15 minutes during working hours
+//! 0 0 0 1 * *                six fields: seconds first
+//! CRON_TZ=Europe/Moscow 0 3 * * *
+//! @daily
+//! ```
+//!
+//! Fields: `[sec] min hour day-of-month month day-of-week`. Each field accepts
+//! `*` (or `?`), lists `a,b`, ranges `a-b`, steps `*/n` / `a-b/n` / `a/n`, and
+//! month (`JAN`..`DEC`) or weekday (`SUN`..`SAT`) names. Day-of-week is `0-7`,
+//! both `0` and `7` are Sunday. When both day fields are restricted a day
+//! matches if *either* matches (as in Vixie cron).
+//!
+//! Expressions are evaluated in their time zone (UTC unless a `CRON_TZ=`/`TZ=`
+//! prefix or [`CronExpression::in_time_zone`] says otherwise). A wall-clock
+//! time skipped by a DST jump does not fire; a repeated one fires once.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// How many years ahead to search before deciding an expression never fires
+/// (enough for `Feb 29` combined with any other field)
+const SEARCH_YEARS: i32 = 9;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CronError {
+    #[error("expected 5 or 6 fields, got {0}")]
+    FieldCount(usize),
+    #[error("invalid {field} field '{value}': {reason}")]
+    InvalidField {
+        field: &'static str,
+        value: String,
+        reason: String,
+    },
+    #[error("unknown time zone '{0}'")]
+    UnknownTimeZone(String),
+}
+
+/// Time zone a cron expression is evaluated in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CronTimeZone {
+    Utc,
+    /// The time zone of the machine running the scheduler
+    Local,
+    Named(Tz),
+}
+
+impl CronTimeZone {
+    /// `UTC`, `local` or an IANA name such as `Europe/Berlin`
+    pub fn parse(name: &str) -> Result<Self, CronError> {
+        match name.trim() {
+            "" | "UTC" | "utc" | "Etc/UTC" => Ok(CronTimeZone::Utc),
+            name if name.eq_ignore_ascii_case("local") => Ok(CronTimeZone::Local),
+            name => name
+                .parse::<Tz>()
+                .map(CronTimeZone::Named)
+                .map_err(|_| CronError::UnknownTimeZone(name.to_string())),
+        }
+    }
+
+    fn to_wall_clock(self, at: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            CronTimeZone::Utc => at.naive_utc(),
+            CronTimeZone::Local => at.with_timezone(&chrono::Local).naive_local(),
+            CronTimeZone::Named(tz) => at.with_timezone(&tz).naive_local(),
+        }
+    }
+
+    /// First instant showing this wall-clock time, `None` inside a DST gap
+    fn to_instant(self, wall_clock: NaiveDateTime) -> Option<DateTime<Utc>> {
+        fn earliest<T: TimeZone>(result: LocalResult<DateTime<T>>) -> Option<DateTime<Utc>> {
+            result.earliest().map(|at| at.with_timezone(&Utc))
+        }
+        match self {
+            CronTimeZone::Utc => Some(wall_clock.and_utc()),
+            CronTimeZone::Local => earliest(chrono::Local.from_local_datetime(&wall_clock)),
+            CronTimeZone::Named(tz) => earliest(tz.from_local_datetime(&wall_clock)),
+        }
+    }
+}
+
+impl fmt::Display for CronTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CronTimeZone::Utc => write!(f, "UTC"),
+            CronTimeZone::Local => write!(f, "local"),
+            CronTimeZone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// Set of allowed values of one field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FieldSet(u64);
+
+impl FieldSet {
+    fn contains(self, value: u32) -> bool {
+        value < 64 && self.0 & (1 << value) != 0
+    }
+}
+
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+const SECONDS: FieldSpec = FieldSpec {
+    name: "seconds",
+    min: 0,
+    max: 59,
+    names: &[],
+};
+const MINUTES: FieldSpec = FieldSpec {
+    name: "minutes",
+    min: 0,
+    max: 59,
+    names: &[],
+};
+const HOURS: FieldSpec = FieldSpec {
+    name: "hours",
+    min: 0,
+    max: 23,
+    names: &[],
+};
+const DAYS_OF_MONTH: FieldSpec = FieldSpec {
+    name: "day-of-month",
+    min: 1,
+    max: 31,
+    names: &[],
+};
+const MONTHS: FieldSpec = FieldSpec {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &[
+        "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+    ],
+};
+const DAYS_OF_WEEK: FieldSpec = FieldSpec {
+    name: "day-of-week",
+    min: 0,
+    max: 7,
+    names: &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"],
+};
+
+impl FieldSpec {
+    fn error(&self, value: &str, reason: impl Into<String>) -> CronError {
+        CronError::InvalidField {
+            field: self.name,
+            value: value.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    fn value(&self, raw: &str, field: &str) -> Result<u32, CronError> {
+        let value = match self
+            .names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(raw))
+        {
+            Some(index) => index as u32 + self.min,
+            None => raw
+                .parse::<u32>()
+                .map_err(|_| self.error(field, format!("'{raw}' is not a number")))?,
+        };
+        if value < self.min || value > self.max {
+            return Err(self.error(
+                field,
+                format!("{value} is outside {}-{}", self.min, self.max),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Returns the set and whether the field is restricted (does not start
+    /// with `*`/`?`, the same rule Vixie cron uses for the day fields)
+    fn parse(&self, field: &str) -> Result<(FieldSet, bool), CronError> {
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step = step
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|step| *step > 0)
+                        .ok_or_else(|| self.error(field, format!("bad step '{step}'")))?;
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+            let (start, end) = match range {
+                "*" | "?" => (self.min, self.max),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (self.value(start, field)?, self.value(end, field)?),
+                    // `a/n` means "from a to the end, every n"
+                    None if step > 1 => (self.value(range, field)?, self.max),
+                    None => {
+                        let value = self.value(range, field)?;
+                        (value, value)
+                    }
+                },
+            };
+            if start > end {
+                return Err(self.error(field, format!("range {start}-{end} is reversed")));
+            }
+            for value in (start..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+        let restricted = !field.starts_with('*') && !field.starts_with('?');
+        Ok((FieldSet(bits), restricted))
+    }
+}
+
+/// Parsed cron expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpression {
+    source: String,
+    seconds: FieldSet,
+    minutes: FieldSet,
+    hours: FieldSet,
+    days_of_month: FieldSet,
+    months: FieldSet,
+    days_of_week: FieldSet,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+    time_zone: CronTimeZone,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let source = expression.trim();
+        let mut time_zone = CronTimeZone::Utc;
+        let mut body = source;
+        if let Some(rest) = body
+            .strip_prefix("CRON_TZ=")
+            .or_else(|| body.strip_prefix("TZ="))
+        {
+            let (zone, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            time_zone = CronTimeZone::parse(zone)?;
+            body = rest.trim_start();
+        }
+
+        let body = match body {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = body.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            count => return Err(CronError::FieldCount(count)),
+        };
+
+        let (seconds, _) = SECONDS.parse(seconds)?;
+        let (minutes, _) = MINUTES.parse(rest[0])?;
+        let (hours, _) = HOURS.parse(rest[1])?;
+        let (days_of_month, day_of_month_restricted) = DAYS_OF_MONTH.parse(rest[2])?;
+        let (months, _) = MONTHS.parse(rest[3])?;
+        let (days_of_week, day_of_week_restricted) = DAYS_OF_WEEK.parse(rest[4])?;
+        // 7 is an alias for Sunday
+        let days_of_week = if days_of_week.contains(7) {
+            FieldSet((days_of_week.0 | 1) & !(1 << 7))
+        } else {
+            days_of_week
+        };
+
+        Ok(Self {
+            source: source.to_string(),
+            seconds,
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+            day_of_month_restricted,
+            day_of_week_restricted,
+            time_zone,
+        })
+    }
+
+    /// Evaluate the expression in another time zone (overrides a `CRON_TZ=` prefix)
+    pub fn in_time_zone(mut self, time_zone: CronTimeZone) -> Self {
+        self.time_zone = time_zone;
+        self
+    }
+
+    pub fn time_zone(&self) -> CronTimeZone {
+        self.time_zone
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day_of_month = self.days_of_month.contains(date.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(date.weekday().num_days_from_sunday());
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+
+    /// First fire time strictly after `after`, `None` if the expression never fires
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = self.time_zone.to_wall_clock(after).with_nanosecond(0)? + Duration::seconds(1);
+        let last_year = start.year() + SEARCH_YEARS;
+        let mut t = start;
+
+        loop {
+            if t.year() > last_year {
+                return None;
+            }
+            if !self.months.contains(t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !self.hours.contains(t.hour()) {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+                continue;
+            }
+            if !self.minutes.contains(t.minute()) {
+                t = t.date().and_hms_opt(t.hour(), t.minute(), 0)? + Duration::minutes(1);
+                continue;
+            }
+            if !self.seconds.contains(t.second()) {
+                t += Duration::seconds(1);
+                continue;
+            }
+            match self.time_zone.to_instant(t) {
+                Some(at) if at > after => return Some(at),
+                _ => t += Duration::seconds(1),
+            }
+        }
+    }
+
+    /// The next `count` fire times after `after`
+    pub fn upcoming(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        let mut times = Vec::with_capacity(count);
+        let mut cursor = after;
+        while times.len() < count {
+            match self.next_after(cursor) {
+                Some(at) => {
+                    times.push(at);
+                    cursor = at;
+                }
+                None => break,
+            }
+        }
+        times
+    }
+}
+
+impl fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl std::str::FromStr for CronExpression {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(raw: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(raw)
+            .expect("Operation failed - converted from unwrap()")
+            .with_timezone(&Utc)
+    }
+
+    fn next(expression: &str, after: &str) -> Option<DateTime<Utc>> {
+        CronExpression::parse(expression)
+            .expect("expression should parse")
+            .next_after(utc(after))
+    }
+
+    #[test]
+    fn test_five_and_six_field_expressions() {
+        assert_eq!(
+            next("30 2 * * *", "2024-03-10T12:00:00Z"),
+            Some(utc("2024-03-11T02:30:00Z"))
+        );
+        assert_eq!(
+            next("*/15 9-18 * * MON-FRI", "2024-03-08T18:50:00Z"),
+            Some(utc("2024-03-11T09:00:00Z"))
+        );
+        assert_eq!(
+            next("*/10 * * * * *", "2024-01-01T00:00:05Z"),
+            Some(utc("2024-01-01T00:00:10Z"))
+        );
+        assert_eq!(
+            next("@monthly", "2024-01-31T23:59:59Z"),
+            Some(utc("2024-02-01T00:00:00Z"))
+        );
+        // Strictly after: a fire time equal to `after` is skipped
+        assert_eq!(
+            next("0 * * * *", "2024-01-01T05:00:00Z"),
+            Some(utc("2024-01-01T06:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_day_fields_and_never_firing() {
+        // Either the 13th or a Friday
+        assert_eq!(
+            next("0 0 13 * FRI", "2024-09-01T00:00:00Z"),
+            Some(utc("2024-09-06T00:00:00Z"))
+        );
+        // 7 is Sunday
+        assert_eq!(
+            next("0 12 * * 7", "2024-09-01T13:00:00Z"),
+            Some(utc("2024-09-08T12:00:00Z"))
+        );
+        assert_eq!(
+            next("0 0 29 FEB *", "2024-03-01T00:00:00Z"),
+            Some(utc("2028-02-29T00:00:00Z"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2024-01-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_time_zones_and_dst() {
+        assert_eq!(
+            next("CRON_TZ=Europe/Moscow 0 3 * * *", "2024-06-01T12:00:00Z"),
+            Some(utc("2024-06-02T00:00:00Z"))
+        );
+        let berlin = CronTimeZone::parse("Europe/Berlin").expect("zone should parse");
+        let expression = CronExpression::parse("30 2 * * *")
+            .expect("expression should parse")
+            .in_time_zone(berlin);
+        // 02:30 does not exist on 2024-03-31 in Berlin
+        assert_eq!(
+            expression.next_after(utc("2024-03-30T12:00:00Z")),
+            Some(utc("2024-04-01T00:30:00Z"))
+        );
+        // ...and happens twice on 2024-10-27, fires once
+        assert_eq!(
+            expression.upcoming(utc("2024-10-26T12:00:00Z"), 2),
+            vec![utc("2024-10-27T00:30:00Z"), utc("2024-10-28T01:30:00Z")]
+        );
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert_eq!(
+            CronExpression::parse("* * * *"),
+            Err(CronError::FieldCount(4))
+        );
+        assert!(matches!(
+            CronExpression::parse("61 * * * *"),
+            Err(CronError::InvalidField {
+                field: "minutes",
+                ..
+            })
+        ));
+        assert!(CronExpression::parse("*/0 * * * *").is_err());
+        assert!(CronExpression::parse("0 5-1 * * *").is_err());
+        assert!(matches!(
+            CronExpression::parse("TZ=Mars/Olympus 0 0 * * *"),
+            Err(CronError::UnknownTimeZone(_))
+        ));
+    }
+}
@@ -0,0 +1,472 @@
+//! Dependency graph of plan steps
+//!
+//! [`StepAccess`] describes which paths a step reads and writes. The Planner
+//! uses it to turn the written order of a plan into explicit
+//! `ActionStep::dependencies` ([`infer_dependencies`]). The Executor uses it
+//! through [`StepGraph`] to run independent steps concurrently while steps that
+//! touch the same paths stay serialized.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use tools::ToolRegistry;
+use uuid::Uuid;
+
+use super::planner::{ActionStep, ActionStepType, MemoryOperationType};
+
+/// Argument names that carry file system paths
+const PATH_ARGUMENTS: &[&str] = &[
+    "path",
+    "paths",
+    "file",
+    "files",
+    "source",
+    "target",
+    "destination",
+    "dir",
+    "directory",
+];
+
+/// Pseudo path shared by all memory operations
+const MEMORY_RESOURCE: &str = "memory://";
+
+/// Built-in tools, consulted for the side effects their specs declare
+fn builtin_tools() -> &'static ToolRegistry {
+    static BUILTIN: OnceLock<ToolRegistry> = OnceLock::new();
+    BUILTIN.get_or_init(ToolRegistry::new)
+}
+
+/// Side effects declared by the spec of a built-in tool
+/// ([`tools::ToolSpec::is_read_only`]); `None` for unknown tools
+pub(crate) fn builtin_read_only(tool_name: &str) -> Option<bool> {
+    builtin_tools()
+        .get(tool_name)
+        .map(|tool| tool.spec().is_read_only())
+}
+
+/// Whether `tool_name` is a built-in tool whose spec declares no side
+/// effects; unknown tools are not
+pub fn is_read_only_tool(tool_name: &str) -> bool {
+    builtin_read_only(tool_name) == Some(true)
+}
+
+/// Paths a step reads and writes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepAccess {
+    pub reads: HashSet<String>,
+    pub writes: HashSet<String>,
+    /// Unknown side effects (shell commands, prompts, control flow,
+    /// sub-agents): the step runs alone
+    pub exclusive: bool,
+}
+
+impl StepAccess {
+    /// Access of a step, tool side effects taken from the built-in tool specs
+    pub fn of(step: &ActionStep) -> Self {
+        Self::with_tools(step, builtin_read_only)
+    }
+
+    /// Access of a step; `read_only` tells whether a tool declares no side
+    /// effects, `None` for unknown tools
+    pub fn with_tools(step: &ActionStep, read_only: impl Fn(&str) -> Option<bool>) -> Self {
+        match &step.step_type {
+            ActionStepType::ToolExecution {
+                tool_name,
+                arguments,
+            } => {
+                let paths = paths_of(arguments);
+                match read_only(tool_name) {
+                    Some(true) => Self {
+                        reads: paths,
+                        ..Self::default()
+                    },
+                    Some(false) if !paths.is_empty() => Self {
+                        writes: paths,
+                        ..Self::default()
+                    },
+                    // Unknown tools and writers without path arguments may
+                    // touch anything
+                    _ => Self::exclusive(),
+                }
+            }
+            ActionStepType::MemoryOperation { operation_type, .. } => {
+                let resource = HashSet::from([MEMORY_RESOURCE.to_string()]);
+                if *operation_type == MemoryOperationType::Search {
+                    Self {
+                        reads: resource,
+                        ..Self::default()
+                    }
+                } else {
+                    Self {
+                        writes: resource,
+                        ..Self::default()
+                    }
+                }
+            }
+            ActionStepType::UserInteraction { .. }
+            | ActionStepType::Wait { .. }
+            | ActionStepType::Conditional { .. }
+            | ActionStepType::Loop { .. }
+            | ActionStepType::Delegate { .. } => Self::exclusive(),
+        }
+    }
+
+    fn exclusive() -> Self {
+        Self {
+            exclusive: true,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the two steps must not run at the same time (or be reordered)
+    pub fn conflicts_with(&self, other: &StepAccess) -> bool {
+        if self.exclusive || other.exclusive {
+            return true;
+        }
+        let overlaps = |a: &HashSet<String>, b: &HashSet<String>| {
+            a.iter().any(|x| b.iter().any(|y| paths_overlap(x, y)))
+        };
+        overlaps(&self.writes, &other.writes)
+            || overlaps(&self.writes, &other.reads)
+            || overlaps(&self.reads, &other.writes)
+    }
+}
+
+fn paths_of(arguments: &HashMap<String, serde_json::Value>) -> HashSet<String> {
+    let mut paths = HashSet::new();
+    for name in PATH_ARGUMENTS {
+        match arguments.get(*name) {
+            Some(serde_json::Value::String(path)) => {
+                paths.insert(normalize_path(path));
+            }
+            Some(serde_json::Value::Array(items)) => {
+                paths.extend(
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str())
+                        .map(normalize_path),
+                );
+            }
+            _ => {}
+        }
+    }
+    paths
+}
+
+fn normalize_path(path: &str) -> String {
+    let path = path.trim();
+    let path = path.strip_prefix("./").unwrap_or(path);
+    match path.trim_end_matches('/') {
+        "" => "/".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// Same path, or one is a directory containing the other
+fn paths_overlap(a: &str, b: &str) -> bool {
+    let contains = |dir: &str, path: &str| {
+        dir == "/"
+            || path
+                .strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+    };
+    a == b || contains(a, b) || contains(b, a)
+}
+
+/// Make the written order explicit: every step depends on the earlier steps it
+/// conflicts with (only those not already reachable through other
+/// dependencies). Declared dependencies are kept.
+pub fn infer_dependencies(steps: &mut [ActionStep]) {
+    let accesses: Vec<StepAccess> = steps.iter().map(StepAccess::of).collect();
+    let mut ancestors: Vec<HashSet<Uuid>> = Vec::with_capacity(steps.len());
+    let positions: HashMap<Uuid, usize> = steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| (step.id, index))
+        .collect();
+
+    for j in 0..steps.len() {
+        let mut reachable: HashSet<Uuid> = HashSet::new();
+        for dependency in &steps[j].dependencies {
+            reachable.insert(*dependency);
+            if let Some(&index) = positions.get(dependency) {
+                if index < j {
+                    reachable.extend(ancestors[index].iter().copied());
+                }
+            }
+        }
+        for i in (0..j).rev() {
+            if reachable.contains(&steps[i].id) || !accesses[i].conflicts_with(&accesses[j]) {
+                continue;
+            }
+            let id = steps[i].id;
+            steps[j].dependencies.push(id);
+            reachable.insert(id);
+            reachable.extend(ancestors[i].iter().copied());
+        }
+        ancestors.push(reachable);
+    }
+}
+
+/// Most steps that can run at the same time when every step takes as long
+/// (the widest layer of the graph)
+pub fn max_parallelism(steps: &[ActionStep]) -> usize {
+    let positions: HashMap<Uuid, usize> = steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| (step.id, index))
+        .collect();
+    let mut depth: HashMap<Uuid, usize> = HashMap::new();
+    let mut remaining: Vec<&ActionStep> = steps.iter().collect();
+    while !remaining.is_empty() {
+        let before = remaining.len();
+        remaining.retain(|step| {
+            let known: Vec<&Uuid> = step
+                .dependencies
+                .iter()
+                .filter(|dependency| positions.contains_key(*dependency))
+                .collect();
+            if known
+                .iter()
+                .all(|dependency| depth.contains_key(*dependency))
+            {
+                let layer = known
+                    .iter()
+                    .map(|dependency| depth[*dependency] + 1)
+                    .max()
+                    .unwrap_or(0);
+                depth.insert(step.id, layer);
+                false
+            } else {
+                true
+            }
+        });
+        // A cycle: the remaining steps never become ready
+        if remaining.len() == before {
+            break;
+        }
+    }
+    let mut widths: HashMap<usize, usize> = HashMap::new();
+    for layer in depth.values() {
+        *widths.entry(*layer).or_default() += 1;
+    }
+    widths.values().copied().max().unwrap_or(0)
+}
+
+/// Run state of a plan's steps for the Executor: which steps may start now,
+/// and which are skipped once a dependency fails
+pub(crate) struct StepGraph<'a> {
+    steps: &'a [ActionStep],
+    accesses: Vec<StepAccess>,
+    pending: Vec<usize>,
+    running: HashSet<usize>,
+    completed: HashSet<Uuid>,
+}
+
+impl<'a> StepGraph<'a> {
+    /// `read_only` as in [`StepAccess::with_tools`]
+    pub(crate) fn new(steps: &'a [ActionStep], read_only: impl Fn(&str) -> Option<bool>) -> Self {
+        Self {
+            steps,
+            accesses: steps
+                .iter()
+                .map(|step| StepAccess::with_tools(step, &read_only))
+                .collect(),
+            pending: (0..steps.len()).collect(),
+            running: HashSet::new(),
+            completed: HashSet::new(),
+        }
+    }
+
+    /// First pending step (in plan order) whose dependencies completed and
+    /// that conflicts with no running step and no earlier pending step (a
+    /// conflicting step never overtakes one planned before it); it is
+    /// marked running
+    pub(crate) fn start_next(&mut self) -> Option<&'a ActionStep> {
+        let position = (0..self.pending.len()).find(|&position| {
+            let index = self.pending[position];
+            let access = &self.accesses[index];
+            self.steps[index]
+                .dependencies
+                .iter()
+                .all(|dependency| self.completed.contains(dependency))
+                && self
+                    .running
+                    .iter()
+                    .all(|running| !self.accesses[*running].conflicts_with(access))
+                && self.pending[..position]
+                    .iter()
+                    .all(|earlier| !self.accesses[*earlier].conflicts_with(access))
+        })?;
+        let index = self.pending.remove(position);
+        self.running.insert(index);
+        Some(&self.steps[index])
+    }
+
+    pub(crate) fn is_idle(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    pub(crate) fn complete(&mut self, step_id: Uuid) {
+        self.running
+            .retain(|index| self.steps[*index].id != step_id);
+        self.completed.insert(step_id);
+    }
+
+    /// Mark a step failed and remove every pending step that depends on it,
+    /// directly or transitively; returns the removed steps
+    pub(crate) fn fail(&mut self, step_id: Uuid) -> Vec<&'a ActionStep> {
+        self.running
+            .retain(|index| self.steps[*index].id != step_id);
+        let mut failed: HashSet<Uuid> = HashSet::from([step_id]);
+        let mut skipped = Vec::new();
+        while let Some(position) = self.pending.iter().position(|index| {
+            self.steps[*index]
+                .dependencies
+                .iter()
+                .any(|dependency| failed.contains(dependency))
+        }) {
+            let index = self.pending.remove(position);
+            failed.insert(self.steps[index].id);
+            skipped.push(&self.steps[index]);
+        }
+        skipped
+    }
+
+    /// Steps never started: unknown or cyclic dependencies, or cancellation
+    pub(crate) fn take_pending(&mut self) -> Vec<&'a ActionStep> {
+        std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|index| &self.steps[index])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::planner::{BackoffStrategy, RetryPolicy};
+
+    fn tool_step(tool_name: &str, path: Option<&str>) -> ActionStep {
+        let mut arguments = HashMap::new();
+        if let Some(path) = path {
+            arguments.insert("path".to_string(), serde_json::json!(path));
+        }
+        ActionStep {
+            id: Uuid::new_v4(),
+            step_type: ActionStepType::ToolExecution {
+                tool_name: tool_name.to_string(),
+                arguments,
+            },
+            parameters: HashMap::new(),
+            dependencies: vec![],
+            expected_duration: std::time::Duration::from_millis(10),
+            retry_policy: RetryPolicy {
+                max_retries: 0,
+                backoff_strategy: BackoffStrategy::Fixed(std::time::Duration::from_millis(1)),
+                retry_conditions: vec![],
+            },
+            validation_rules: vec![],
+        }
+    }
+
+    #[test]
+    fn test_step_access_conflicts() {
+        let read_a = StepAccess::of(&tool_step("file_read", Some("src/a.rs")));
+        let read_b = StepAccess::of(&tool_step("file_read", Some("./src/b.rs")));
+        let write_a = StepAccess::of(&tool_step("file_write", Some("src/a.rs")));
+        let list_src = StepAccess::of(&tool_step("dir_list", Some("src/")));
+        let shell = StepAccess::of(&tool_step("shell_exec", None));
+
+        assert!(!read_a.conflicts_with(&read_b));
+        assert!(write_a.conflicts_with(&read_a));
+        assert!(!write_a.conflicts_with(&read_b));
+        // a directory listing sees files written below it
+        assert!(list_src.conflicts_with(&write_a));
+        assert!(shell.exclusive);
+        assert!(shell.conflicts_with(&read_a));
+    }
+
+    #[test]
+    fn test_step_access_comes_from_tool_specs() {
+        let status = StepAccess::of(&tool_step("git_status", None));
+        let checkout = StepAccess::of(&tool_step("git_checkout", Some("src/a.rs")));
+        // the name suggests a reader, but the tool is unknown
+        let unknown = StepAccess::of(&tool_step("report_reader", Some("src/a.rs")));
+
+        assert!(!status.exclusive && status.writes.is_empty());
+        assert_eq!(checkout.writes, HashSet::from(["src/a.rs".to_string()]));
+        assert!(unknown.exclusive);
+        assert!(is_read_only_tool("web_fetch"));
+        assert!(!is_read_only_tool("git_commit"));
+        assert!(!is_read_only_tool("report_reader"));
+    }
+
+    #[test]
+    fn test_infer_dependencies_and_parallelism() {
+        // read a, read b, write a, shell, read c
+        let mut steps = vec![
+            tool_step("file_read", Some("a")),
+            tool_step("file_read", Some("b")),
+            tool_step("file_write", Some("a")),
+            tool_step("shell_exec", None),
+            tool_step("file_read", Some("c")),
+        ];
+        let ids: Vec<Uuid> = steps.iter().map(|step| step.id).collect();
+        infer_dependencies(&mut steps);
+
+        assert!(steps[0].dependencies.is_empty());
+        assert!(steps[1].dependencies.is_empty());
+        assert_eq!(steps[2].dependencies, vec![ids[0]]);
+        // the shell step waits for everything; "read a" is reached through "write a"
+        assert_eq!(steps[3].dependencies, vec![ids[2], ids[1]]);
+        assert_eq!(steps[4].dependencies, vec![ids[3]]);
+        assert_eq!(max_parallelism(&steps), 2);
+    }
+
+    #[test]
+    fn test_step_graph_serializes_conflicts_and_skips_dependents() {
+        let write_a = tool_step("file_write", Some("a"));
+        let write_a_again = tool_step("file_write", Some("a"));
+        let read_b = tool_step("file_read", Some("b"));
+        let mut summary = tool_step("file_read", Some("c"));
+        summary.dependencies = vec![read_b.id];
+        let steps = vec![write_a, write_a_again, read_b, summary];
+        let mut graph = StepGraph::new(&steps, builtin_read_only);
+
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[0].id));
+        // the second write waits for the first
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[2].id));
+        assert!(graph.start_next().is_none());
+
+        let skipped: Vec<Uuid> = graph.fail(steps[2].id).iter().map(|step| step.id).collect();
+        assert_eq!(skipped, vec![steps[3].id]);
+        graph.complete(steps[0].id);
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[1].id));
+        graph.complete(steps[1].id);
+        assert!(graph.is_idle());
+        assert!(graph.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_step_graph_keeps_plan_order_of_conflicting_steps() {
+        let read_b = tool_step("file_read", Some("b"));
+        // waits for "read b", so it is pending when the second write is ready
+        let mut write_a = tool_step("file_write", Some("a"));
+        write_a.dependencies = vec![read_b.id];
+        let write_a_again = tool_step("file_write", Some("a"));
+        let read_c = tool_step("file_read", Some("c"));
+        let steps = vec![read_b, write_a, write_a_again, read_c];
+        let mut graph = StepGraph::new(&steps, builtin_read_only);
+
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[0].id));
+        // the second write must not overtake the first
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[3].id));
+        assert!(graph.start_next().is_none());
+
+        graph.complete(steps[0].id);
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[1].id));
+        assert!(graph.start_next().is_none());
+        graph.complete(steps[1].id);
+        assert_eq!(graph.start_next().map(|step| step.id), Some(steps[2].id));
+    }
+}
@@ -0,0 +1,653 @@
+//! Agent profiles and sub-agent delegation
+//!
+//! An [`AgentProfile`] describes a specialist: its system prompt, the tools it
+//! may call, the model it runs on, the memory project it reads and writes and
+//! its own spend budget. `ActionStepType::Delegate` steps are run by a
+//! [`Delegator`], which spawns one [`SubAgentActor`] per task under the actor
+//! system's supervisor. The sub-agent drives its own Planner → Executor →
+//! Critic loop; only its [`SubAgentResult`] goes back to the parent plan.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use super::agent_loop::{AgentLoop, AgentLoopConfig, AgentLoopReport};
+use super::critic::Critic;
+use super::executor::{DelegationHandler, Executor, StepStatus};
+use super::planner::{ActionPlan, ActionStep, ActionStepType, Planner};
+use crate::actors::{
+    ActorContext, ActorError, ActorId, ActorMessage, ActorSystemManager, AgentType, BaseActor,
+};
+use crate::resources::spend::{SpendBudget, SpendTracker, SpendUsage};
+
+/// `allowed_tools` entry that allows every tool
+pub const ALL_TOOLS: &str = "*";
+
+/// Longest summary handed back to the parent plan
+pub const MAX_SUMMARY_CHARS: usize = 2000;
+
+fn default_max_iterations() -> u32 {
+    AgentLoopConfig::default().max_iterations
+}
+
+/// A user-defined specialist the planner can delegate sub-tasks to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub system_prompt: String,
+    /// Tool names the sub-agent may call; `"*"` allows every tool the
+    /// [`SubAgentFactory`] registers, empty allows none
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Model override for the configured LLM provider
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Memory project the sub-agent stores into and searches
+    #[serde(default)]
+    pub memory_project: Option<String>,
+    /// Limits of every delegated run, separate from the parent's budget
+    #[serde(default)]
+    pub budget: SpendBudget,
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+}
+
+impl AgentProfile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            system_prompt: String::new(),
+            allowed_tools: Vec::new(),
+            model: None,
+            memory_project: None,
+            budget: SpendBudget::default(),
+            max_iterations: default_max_iterations(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = prompt.into();
+        self
+    }
+
+    pub fn with_allowed_tools(mut self, tools: Vec<String>) -> Self {
+        self.allowed_tools = tools;
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_memory_project(mut self, project: impl Into<String>) -> Self {
+        self.memory_project = Some(project.into());
+        self
+    }
+
+    pub fn with_budget(mut self, budget: SpendBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn allows_tool(&self, tool_name: &str) -> bool {
+        self.allowed_tools
+            .iter()
+            .any(|tool| tool == ALL_TOOLS || tool == tool_name)
+    }
+}
+
+/// Agent profiles by name
+#[derive(Debug, Clone, Default)]
+pub struct AgentProfiles {
+    profiles: HashMap<String, AgentProfile>,
+}
+
+impl AgentProfiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace the profile with the same name
+    pub fn insert(&mut self, profile: AgentProfile) {
+        self.profiles.insert(profile.name.clone(), profile);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AgentProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Profile names in alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+impl FromIterator<AgentProfile> for AgentProfiles {
+    fn from_iter<I: IntoIterator<Item = AgentProfile>>(iter: I) -> Self {
+        let mut profiles = Self::new();
+        for profile in iter {
+            profiles.insert(profile);
+        }
+        profiles
+    }
+}
+
+/// Builds what a sub-agent runs with; the CLI provides one backed by the real
+/// tool registry, memory and LLM provider
+#[async_trait]
+pub trait SubAgentFactory: Send + Sync {
+    /// Executor with the profile's tools, memory scoped to its
+    /// `memory_project` and any interaction handler; tools outside
+    /// `allowed_tools` are removed before the run
+    async fn executor(&self, profile: &AgentProfile) -> Result<Executor>;
+
+    /// First plan for `task`; `planner` knows the executor's tools
+    async fn plan(
+        &self,
+        profile: &AgentProfile,
+        task: &str,
+        planner: &Planner,
+    ) -> Result<ActionPlan>;
+
+    /// Text handed back to the parent. LLM-backed factories use the
+    /// profile's model and system prompt and count tokens against `spend`
+    async fn summarize(
+        &self,
+        _profile: &AgentProfile,
+        task: &str,
+        report: &AgentLoopReport,
+        _spend: &Arc<SpendTracker>,
+    ) -> Result<String> {
+        Ok(summarize_report(task, report))
+    }
+}
+
+/// What a delegated step returns to the parent plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubAgentResult {
+    pub profile: String,
+    pub task: String,
+    pub outcome: String,
+    pub succeeded: bool,
+    pub iterations: usize,
+    pub summary: String,
+    /// Spend of the sub-agent's own budget; the delegating Executor adds it
+    /// to the parent's tracker as well
+    pub spend: SpendUsage,
+}
+
+/// Outcome, step counts, first error and the last step output of a run
+pub fn summarize_report(task: &str, report: &AgentLoopReport) -> String {
+    let results = &report.result.step_results;
+    let completed = results
+        .iter()
+        .filter(|result| result.status == StepStatus::Completed)
+        .count();
+    let mut summary = format!(
+        "Task: {}\nOutcome: {} after {} iteration(s), {}/{} step(s) completed",
+        task,
+        report.outcome,
+        report.iterations.len(),
+        completed,
+        results.len()
+    );
+    if let Some(error) = results.iter().find_map(|result| result.error.as_deref()) {
+        summary.push_str(&format!("\nError: {}", error));
+    }
+    let output = results.iter().rev().find_map(|result| {
+        let output = result.output.as_ref()?;
+        Some(match output.get("result").and_then(|text| text.as_str()) {
+            Some(text) => text.to_string(),
+            None => output.to_string(),
+        })
+    });
+    if let Some(output) = output.filter(|output| !output.trim().is_empty()) {
+        summary.push_str("\nResult:\n");
+        summary.push_str(output.trim());
+    }
+    truncate_chars(summary, MAX_SUMMARY_CHARS)
+}
+
+fn truncate_chars(text: String, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+/// First tool of `steps` (including nested ones) the profile may not call
+fn disallowed_tool(steps: &[ActionStep], profile: &AgentProfile) -> Option<String> {
+    steps.iter().find_map(|step| match &step.step_type {
+        ActionStepType::ToolExecution { tool_name, .. } if !profile.allows_tool(tool_name) => {
+            Some(tool_name.clone())
+        }
+        ActionStepType::Conditional {
+            then_steps,
+            else_steps,
+            ..
+        } => disallowed_tool(then_steps, profile).or_else(|| disallowed_tool(else_steps, profile)),
+        ActionStepType::Loop { body_steps, .. } => disallowed_tool(body_steps, profile),
+        _ => None,
+    })
+}
+
+/// Run `task` as `profile` with its own Planner, Executor, Critic and budget
+pub async fn run_sub_agent(
+    profile: &AgentProfile,
+    task: &str,
+    factory: &dyn SubAgentFactory,
+    spend: Arc<SpendTracker>,
+) -> Result<SubAgentResult> {
+    let mut executor = factory.executor(profile).await?;
+    executor.retain_tools(|tool| profile.allows_tool(tool));
+    executor.set_spend_tracker(Arc::clone(&spend));
+
+    let mut planner = Planner::new();
+    for tool in executor.get_available_tools() {
+        planner.register_available_tool(&tool);
+    }
+    let plan = factory.plan(profile, task, &planner).await?;
+    if let Some(tool) = disallowed_tool(&plan.steps, profile) {
+        bail!(
+            "Agent profile '{}' is not allowed to use tool '{}'",
+            profile.name,
+            tool
+        );
+    }
+
+    let report = AgentLoop::new(
+        Arc::new(planner),
+        Arc::new(executor),
+        Arc::new(Critic::new()),
+    )
+    .with_config(AgentLoopConfig {
+        max_iterations: profile.max_iterations,
+        ..AgentLoopConfig::default()
+    })
+    .with_spend_tracker(Arc::clone(&spend))
+    .run(plan)
+    .await?;
+
+    let summary = match factory.summarize(profile, task, &report, &spend).await {
+        Ok(summary) => truncate_chars(summary, MAX_SUMMARY_CHARS),
+        Err(e) => {
+            warn!(profile = %profile.name, error = %e, "Sub-agent summary failed");
+            summarize_report(task, &report)
+        }
+    };
+    Ok(SubAgentResult {
+        profile: profile.name.clone(),
+        task: task.to_string(),
+        outcome: report.outcome.to_string(),
+        succeeded: report.succeeded(),
+        iterations: report.iterations.len(),
+        summary,
+        spend: spend.usage(),
+    })
+}
+
+/// One delegated task; runs when the actor system starts it and reports once
+pub struct SubAgentActor {
+    id: ActorId,
+    profile: AgentProfile,
+    task: String,
+    factory: Arc<dyn SubAgentFactory>,
+    spend: Arc<SpendTracker>,
+    reply: Option<oneshot::Sender<Result<SubAgentResult>>>,
+}
+
+impl SubAgentActor {
+    pub fn new(
+        profile: AgentProfile,
+        task: String,
+        factory: Arc<dyn SubAgentFactory>,
+        reply: oneshot::Sender<Result<SubAgentResult>>,
+    ) -> Self {
+        let spend = Arc::new(SpendTracker::new(profile.budget));
+        Self {
+            id: ActorId::new(),
+            profile,
+            task,
+            factory,
+            spend,
+            reply: Some(reply),
+        }
+    }
+}
+
+#[async_trait]
+impl BaseActor for SubAgentActor {
+    fn id(&self) -> ActorId {
+        self.id
+    }
+
+    fn actor_type(&self) -> &'static str {
+        "SubAgent"
+    }
+
+    async fn handle_message(
+        &mut self,
+        message: ActorMessage,
+        _context: &ActorContext,
+    ) -> Result<(), ActorError> {
+        if !matches!(message, ActorMessage::Start) {
+            return Ok(());
+        }
+        // A restarted actor has already reported
+        let Some(reply) = self.reply.take() else {
+            return Ok(());
+        };
+        info!(actor_id = %self.id, profile = %self.profile.name, "Sub-agent started");
+        let result = run_sub_agent(
+            &self.profile,
+            &self.task,
+            self.factory.as_ref(),
+            Arc::clone(&self.spend),
+        )
+        .await;
+        if reply.send(result).is_err() {
+            warn!(actor_id = %self.id, "Delegating step stopped waiting for the sub-agent");
+        }
+        Ok(())
+    }
+}
+
+/// Runs `ActionStepType::Delegate` steps as supervised [`SubAgentActor`]s
+pub struct Delegator {
+    system: Arc<ActorSystemManager>,
+    profiles: AgentProfiles,
+    factory: Arc<dyn SubAgentFactory>,
+}
+
+impl Delegator {
+    pub fn new(
+        system: Arc<ActorSystemManager>,
+        profiles: AgentProfiles,
+        factory: Arc<dyn SubAgentFactory>,
+    ) -> Self {
+        Self {
+            system,
+            profiles,
+            factory,
+        }
+    }
+
+    pub fn profiles(&self) -> &AgentProfiles {
+        &self.profiles
+    }
+
+    /// Run `task` as `profile` in its own actor and wait for the result
+    pub async fn run(&self, profile: &str, task: &str) -> Result<SubAgentResult> {
+        let profile = self.profiles.get(profile).cloned().ok_or_else(|| {
+            anyhow!(
+                "Unknown agent profile '{}' (defined: {})",
+                profile,
+                self.profiles.names().join(", ")
+            )
+        })?;
+        let name = profile.name.clone();
+        let (reply, result) = oneshot::channel();
+        let actor = SubAgentActor::new(profile, task.to_string(), Arc::clone(&self.factory), reply);
+        let actor_id = self
+            .system
+            .spawn_agent(AgentType::SubAgent(name.clone()), Box::new(actor))
+            .await?;
+
+        let result = result.await;
+        if let Err(e) = self.system.stop_agent(actor_id).await {
+            warn!(actor_id = %actor_id, error = %e, "Failed to stop sub-agent");
+        }
+        result.map_err(|_| anyhow!("Sub-agent '{}' stopped before reporting", name))?
+    }
+}
+
+#[async_trait]
+impl DelegationHandler for Delegator {
+    async fn delegate(
+        &self,
+        profile: &str,
+        task: &str,
+        _parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let result = self.run(profile, task).await?;
+        Ok(serde_json::to_value(result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::AgentCommunicationConfig;
+    use crate::agents::executor::{ExecutorTrait, MockToolInvoker};
+    use crate::agents::planner::{BackoffStrategy, ResourceRequirements, RetryPolicy};
+    use crate::system::SystemConfig;
+    use uuid::Uuid;
+
+    /// Registers `file_reader` and `shell_exec`; plans one step per tool in `plan`
+    struct TestFactory {
+        plan: Vec<&'static str>,
+    }
+
+    fn tool_step(tool_name: &str) -> ActionStep {
+        ActionStep {
+            id: Uuid::new_v4(),
+            step_type: ActionStepType::ToolExecution {
+                tool_name: tool_name.to_string(),
+                arguments: HashMap::new(),
+            },
+            parameters: HashMap::new(),
+            dependencies: vec![],
+            expected_duration: std::time::Duration::from_millis(10),
+            retry_policy: RetryPolicy {
+                max_retries: 0,
+                backoff_strategy: BackoffStrategy::Fixed(std::time::Duration::from_millis(1)),
+                retry_conditions: vec![],
+            },
+            validation_rules: vec![],
+        }
+    }
+
+    #[async_trait]
+    impl SubAgentFactory for TestFactory {
+        async fn executor(&self, _profile: &AgentProfile) -> Result<Executor> {
+            let mut executor = Executor::new();
+            executor.clear_tools();
+            for tool in ["file_reader", "shell_exec"] {
+                executor.register_tool(
+                    tool.to_string(),
+                    Box::new(MockToolInvoker::new(tool.to_string())),
+                );
+            }
+            Ok(executor)
+        }
+
+        async fn plan(
+            &self,
+            _profile: &AgentProfile,
+            _task: &str,
+            _planner: &Planner,
+        ) -> Result<ActionPlan> {
+            Ok(ActionPlan {
+                id: Uuid::new_v4(),
+                intent_id: Uuid::new_v4(),
+                steps: self.plan.iter().map(|tool| tool_step(tool)).collect(),
+                estimated_duration: std::time::Duration::from_millis(20),
+                resource_requirements: ResourceRequirements {
+                    cpu_cores: 1,
+                    memory_mb: 64,
+                    disk_space_mb: 0,
+                    network_required: false,
+                    tools_required: vec![],
+                    permissions_required: vec![],
+                },
+                dependencies: vec![],
+                metadata: HashMap::new(),
+            })
+        }
+    }
+
+    fn reviewer() -> AgentProfile {
+        AgentProfile::new("reviewer")
+            .with_system_prompt("Review carefully")
+            .with_allowed_tools(vec!["file_reader".to_string()])
+    }
+
+    #[tokio::test]
+    async fn test_sub_agent_only_gets_allowed_tools() {
+        let spend = Arc::new(SpendTracker::new(SpendBudget::default()));
+        let factory = TestFactory {
+            plan: vec!["shell_exec"],
+        };
+        let error = run_sub_agent(&reviewer(), "check", &factory, spend)
+            .await
+            .expect_err("Disallowed tool should be rejected");
+        assert!(error.to_string().contains("'shell_exec'"), "{}", error);
+
+        let spend = Arc::new(SpendTracker::new(SpendBudget::default()));
+        let factory = TestFactory {
+            plan: vec!["file_reader"],
+        };
+        let result = run_sub_agent(&reviewer(), "check", &factory, Arc::clone(&spend))
+            .await
+            .expect("Sub-agent should succeed");
+        assert!(result.succeeded, "{}", result.outcome);
+        assert!(result.summary.starts_with("Task: check"));
+        assert!(result.summary.contains("1/1 step(s) completed"));
+        assert_eq!(result.spend.tool_calls, 1);
+    }
+
+    #[test]
+    fn test_empty_allowed_tools_allow_nothing() {
+        let profile = AgentProfile::new("idle");
+        assert!(!profile.allows_tool("file_reader"));
+        let profile = profile.with_allowed_tools(vec![ALL_TOOLS.to_string()]);
+        assert!(profile.allows_tool("file_reader"));
+    }
+
+    #[tokio::test]
+    async fn test_sub_agent_stops_at_its_own_budget() {
+        let profile = reviewer()
+            .with_allowed_tools(vec![ALL_TOOLS.to_string()])
+            .with_budget(SpendBudget::new().tool_call_limit(None, Some(1)));
+        let factory = TestFactory {
+            plan: vec!["file_reader", "shell_exec"],
+        };
+        let spend = Arc::new(SpendTracker::new(profile.budget));
+
+        let result = run_sub_agent(&profile, "check", &factory, Arc::clone(&spend))
+            .await
+            .expect("Sub-agent should report");
+        assert!(!result.succeeded);
+        assert!(
+            result.outcome.contains("budget exceeded"),
+            "{}",
+            result.outcome
+        );
+        assert_eq!(spend.usage().tool_calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_delegate_step_returns_only_the_summary() {
+        let system =
+            ActorSystemManager::new(SystemConfig::default(), AgentCommunicationConfig::default())
+                .await
+                .expect("Actor system should start");
+        let profiles: AgentProfiles = [reviewer()].into_iter().collect();
+        let delegator = Delegator::new(
+            Arc::new(system),
+            profiles,
+            Arc::new(TestFactory {
+                plan: vec!["file_reader"],
+            }),
+        );
+
+        let mut executor = Executor::new();
+        executor.set_delegation_handler(Arc::new(delegator));
+        let parent_spend = Arc::new(SpendTracker::new(SpendBudget::default()));
+        executor.set_spend_tracker(Arc::clone(&parent_spend));
+        let step = ActionStep {
+            step_type: ActionStepType::Delegate {
+                profile: "reviewer".to_string(),
+                task: "review src/lib.rs".to_string(),
+            },
+            ..tool_step("unused")
+        };
+        let plan = ActionPlan {
+            id: Uuid::new_v4(),
+            intent_id: Uuid::new_v4(),
+            steps: vec![step.clone()],
+            estimated_duration: std::time::Duration::from_millis(10),
+            resource_requirements: ResourceRequirements {
+                cpu_cores: 1,
+                memory_mb: 64,
+                disk_space_mb: 0,
+                network_required: false,
+                tools_required: vec![],
+                permissions_required: vec![],
+            },
+            dependencies: vec![],
+            metadata: HashMap::new(),
+        };
+        let mut context = executor.create_execution_context(&plan);
+
+        let result = executor
+            .execute_step(&step, &mut context)
+            .await
+            .expect("Delegated step should run");
+        assert_eq!(result.status, StepStatus::Completed, "{:?}", result.error);
+        let output = result.output.expect("Delegated step should have output");
+        assert_eq!(output["profile"], "reviewer");
+        assert_eq!(output["succeeded"], true);
+        assert!(output["summary"]
+            .as_str()
+            .is_some_and(|summary| summary.contains("review src/lib.rs")));
+        assert!(output.get("observations").is_none());
+        // The sub-agent's tool call is charged to the parent as well
+        assert_eq!(parent_spend.usage().tool_calls, 1);
+
+        let unknown = ActionStep {
+            step_type: ActionStepType::Delegate {
+                profile: "nobody".to_string(),
+                task: "anything".to_string(),
+            },
+            ..step
+        };
+        let result = executor
+            .execute_step(&unknown, &mut context)
+            .await
+            .expect("Step should report the failure");
+        assert_eq!(result.status, StepStatus::Failed);
+        assert!(result
+            .error
+            .is_some_and(|error| error.contains("Unknown agent profile 'nobody'")));
+    }
+}
@@ -3,20 +3,49 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::task::Poll;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use super::dag::{builtin_read_only, StepGraph};
+use super::expression::{step_view, Expression, Scope, MAX_LOOP_ITERATIONS};
 use super::planner::{
     ActionPlan, ActionStep, ActionStepType, InteractionType, MemoryOperationType,
 };
-use crate::saga::{DefaultSagaManager, Saga, SagaManager, SagaStatus};
+use crate::persistence::WorkflowStore;
+use crate::resources::spend::{SpendLedger, SpendOverrun, SpendTracker, SpendUsage};
+use crate::saga::{
+    CompensationHandler, CompensationStatus, DefaultSagaManager, Saga, SagaManager, SagaStatus,
+    SagaStepRunner,
+};
 
 // Health monitoring integration
 use crate::reliability::health::{HealthChecker, HealthReport, HealthStatus};
 
+/// Step parameter with a per-step time limit in milliseconds; applies to
+/// tool, memory, interaction, delegate and wait steps
+pub const STEP_TIMEOUT_PARAM: &str = "timeout_ms";
+
+/// Step failures that retry policies can match on (`TemporaryFailure`);
+/// any other error fails the step without retrying
+#[derive(Debug, thiserror::Error)]
+pub enum StepError {
+    #[error("Step timed out after {0:?}")]
+    TimedOut(Duration),
+    /// The tool ran and reported failure (as opposed to being missing,
+    /// blocked by policy or denied approval)
+    #[error("Tool '{tool}' failed: {message}")]
+    ToolFailed { tool: String, message: String },
+    /// A hard spend limit was reached or a soft one was not approved
+    #[error(transparent)]
+    BudgetExceeded(#[from] SpendOverrun),
+}
+
 /// Result of plan execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
@@ -119,6 +148,9 @@ pub struct ExecutionContext {
     pub current_step: usize,
     pub step_states: HashMap<Uuid, StepState>,
     pub shared_data: HashMap<String, serde_json::Value>,
+    /// Finished steps (including nested ones) in completion order, as seen by
+    /// Conditional/Loop expressions
+    pub step_history: Vec<serde_json::Value>,
     pub resource_limits: ResourceLimits,
     pub cancellation_token: tokio_util::sync::CancellationToken,
 }
@@ -184,6 +216,11 @@ pub struct Executor {
     active_executions: dashmap::DashMap<Uuid, ExecutionContext>,
     resource_monitor: Option<sysinfo::System>,
     tool_registry: HashMap<String, Box<dyn ToolInvoker>>,
+    memory_invoker: Option<Arc<dyn MemoryInvoker>>,
+    interaction_handler: Option<Arc<dyn InteractionHandler>>,
+    delegation_handler: Option<Arc<dyn DelegationHandler>>,
+    spend_tracker: Option<Arc<SpendTracker>>,
+    spend_ledger: Option<Arc<SpendLedger>>,
     saga_manager: DefaultSagaManager,
     active_sagas: dashmap::DashMap<Uuid, Saga>,
     // Health monitoring fields
@@ -197,16 +234,68 @@ pub struct Executor {
 pub trait ToolInvoker: Send + Sync {
     async fn invoke(&self, args: HashMap<String, serde_json::Value>) -> Result<serde_json::Value>;
     fn get_name(&self) -> &str;
+
+    /// Whether the tool declares no side effects; `None` leaves it to the
+    /// built-in tool spec of the same name (unknown tools count as writers)
+    fn read_only(&self) -> Option<bool> {
+        None
+    }
+}
+
+/// Trait for memory operations (`ActionStepType::MemoryOperation`)
+#[async_trait]
+pub trait MemoryInvoker: Send + Sync {
+    async fn invoke(
+        &self,
+        operation_type: &MemoryOperationType,
+        query: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<serde_json::Value>;
+}
+
+/// Trait for answering `ActionStepType::UserInteraction` steps
+#[async_trait]
+pub trait InteractionHandler: Send + Sync {
+    /// Returns the response: `bool` for confirmations, a string for input and
+    /// choices (options come from `parameters["options"]`), `null` otherwise
+    async fn interact(
+        &self,
+        interaction_type: &InteractionType,
+        prompt: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<serde_json::Value>;
+}
+
+/// Trait for running `ActionStepType::Delegate` steps as sub-agents
+#[async_trait]
+pub trait DelegationHandler: Send + Sync {
+    /// Returns the sub-agent's summarized result; nothing else from its run
+    /// reaches the parent plan
+    async fn delegate(
+        &self,
+        profile: &str,
+        task: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<serde_json::Value>;
 }
 
 /// Mock tool invoker for testing
 pub struct MockToolInvoker {
     name: String,
+    read_only: Option<bool>,
 }
 
 impl MockToolInvoker {
     pub fn new(name: String) -> Self {
-        Self { name }
+        Self {
+            name,
+            read_only: None,
+        }
+    }
+
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = Some(read_only);
+        self
     }
 }
 
@@ -227,6 +316,10 @@ impl ToolInvoker for MockToolInvoker {
     fn get_name(&self) -> &str {
         &self.name
     }
+
+    fn read_only(&self) -> Option<bool> {
+        self.read_only
+    }
 }
 
 impl Executor {
@@ -234,11 +327,11 @@ impl Executor {
     pub fn new() -> Self {
         let mut tool_registry: HashMap<String, Box<dyn ToolInvoker>> = HashMap::new();
 
-        // Register some mock tools for testing
-        // TODO: Integrate with existing tool system from crates/tools
+        // Mock tools until real ones replace them
+        // (tool_bridge::register_registry_tools, AgentOrchestrator::with_tools)
         tool_registry.insert(
             "file_reader".to_string(),
-            Box::new(MockToolInvoker::new("file_reader".to_string())),
+            Box::new(MockToolInvoker::new("file_reader".to_string()).with_read_only(true)),
         );
         tool_registry.insert(
             "file_writer".to_string(),
@@ -246,11 +339,11 @@ impl Executor {
         );
         tool_registry.insert(
             "file_search".to_string(),
-            Box::new(MockToolInvoker::new("file_search".to_string())),
+            Box::new(MockToolInvoker::new("file_search".to_string()).with_read_only(true)),
         );
         tool_registry.insert(
             "web_search".to_string(),
-            Box::new(MockToolInvoker::new("web_search".to_string())),
+            Box::new(MockToolInvoker::new("web_search".to_string()).with_read_only(true)),
         );
         tool_registry.insert(
             "shell_exec".to_string(),
@@ -258,7 +351,7 @@ impl Executor {
         );
         tool_registry.insert(
             "memory_search".to_string(),
-            Box::new(MockToolInvoker::new("memory_search".to_string())),
+            Box::new(MockToolInvoker::new("memory_search".to_string()).with_read_only(true)),
         );
         tool_registry.insert(
             "memory_store".to_string(),
@@ -270,6 +363,11 @@ impl Executor {
             active_executions: dashmap::DashMap::new(),
             resource_monitor: None,
             tool_registry,
+            memory_invoker: None,
+            interaction_handler: None,
+            delegation_handler: None,
+            spend_tracker: None,
+            spend_ledger: None,
             saga_manager: DefaultSagaManager::new(),
             active_sagas: dashmap::DashMap::new(),
             // Health monitoring fields
@@ -313,12 +411,78 @@ impl Executor {
         );
     }
 
+    /// Persist saga state transitions so interrupted sagas can be compensated
+    /// after a restart
+    pub fn with_state_store(mut self, store: Arc<WorkflowStore>) -> Self {
+        self.saga_manager = std::mem::take(&mut self.saga_manager).with_store(store);
+        self
+    }
+
+    /// Register a saga compensation handler (e.g. memory or tool undo)
+    pub fn register_compensation_handler(&mut self, handler: Box<dyn CompensationHandler>) {
+        self.saga_manager.register_compensation_handler(handler);
+    }
+
     /// Register a new tool invoker
     pub fn register_tool(&mut self, name: String, tool: Box<dyn ToolInvoker>) {
         tracing::info!("Registering tool: {}", name);
         self.tool_registry.insert(name, tool);
     }
 
+    /// Side effects of a tool as declared by its invoker, else by the
+    /// built-in tool spec; `None` for unknown tools
+    fn tool_read_only(&self, tool_name: &str) -> Option<bool> {
+        self.tool_registry
+            .get(tool_name)
+            .and_then(|tool| tool.read_only())
+            .or_else(|| builtin_read_only(tool_name))
+    }
+
+    /// Route memory steps to a real backend instead of the simulated one
+    pub fn set_memory_invoker(&mut self, invoker: Arc<dyn MemoryInvoker>) {
+        self.memory_invoker = Some(invoker);
+    }
+
+    /// Ask the user through a real handler instead of the simulated one
+    pub fn set_interaction_handler(&mut self, handler: Arc<dyn InteractionHandler>) {
+        self.interaction_handler = Some(handler);
+    }
+
+    /// Run delegated steps through `handler` (e.g. a sub-agent `Delegator`)
+    pub fn set_delegation_handler(&mut self, handler: Arc<dyn DelegationHandler>) {
+        self.delegation_handler = Some(handler);
+    }
+
+    /// Count tool calls against `tracker` and check its limits before every step
+    pub fn set_spend_tracker(&mut self, tracker: Arc<SpendTracker>) {
+        self.spend_tracker = Some(tracker);
+    }
+
+    /// Without a fixed tracker, charge the tracker of the workflow the plan
+    /// runs for (see [`SpendLedger::scope`])
+    pub fn set_spend_ledger(&mut self, ledger: Arc<SpendLedger>) {
+        self.spend_ledger = Some(ledger);
+    }
+
+    fn spend(&self) -> Option<Arc<SpendTracker>> {
+        self.spend_tracker.clone().or_else(|| {
+            self.spend_ledger
+                .as_ref()
+                .and_then(|ledger| ledger.current())
+        })
+    }
+
+    /// Remove all registered tools (e.g. the built-in mocks before
+    /// registering real adapters)
+    pub fn clear_tools(&mut self) {
+        self.tool_registry.clear();
+    }
+
+    /// Keep only the tools `keep` accepts (e.g. an agent profile's allow-list)
+    pub fn retain_tools(&mut self, keep: impl Fn(&str) -> bool) {
+        self.tool_registry.retain(|name, _| keep(name));
+    }
+
     /// Get list of available tools
     pub fn get_available_tools(&self) -> Vec<String> {
         self.tool_registry.keys().cloned().collect()
@@ -336,7 +500,7 @@ impl Executor {
     }
 
     /// Create execution context for a plan
-    fn create_execution_context(&self, plan: &ActionPlan) -> ExecutionContext {
+    pub(crate) fn create_execution_context(&self, plan: &ActionPlan) -> ExecutionContext {
         let mut step_states = HashMap::new();
 
         for step in &plan.steps {
@@ -357,11 +521,114 @@ impl Executor {
             current_step: 0,
             step_states,
             shared_data: HashMap::new(),
+            step_history: Vec::new(),
             resource_limits: Self::default_resource_limits(),
             cancellation_token: tokio_util::sync::CancellationToken::new(),
         }
     }
 
+    /// Parse and evaluate a Conditional/Loop expression against finished steps
+    fn evaluate_condition(
+        condition: &str,
+        context: &ExecutionContext,
+        iteration: Option<u32>,
+    ) -> Result<bool> {
+        let expression = Expression::parse(condition)
+            .map_err(|e| anyhow::anyhow!("Invalid condition '{}': {}", condition, e))?;
+        let mut scope = Scope::new(&context.step_history, &context.shared_data);
+        if let Some(iteration) = iteration {
+            scope = scope.with_iteration(iteration);
+        }
+        expression
+            .evaluate_bool(&scope)
+            .map_err(|e| anyhow::anyhow!("Condition '{}' failed to evaluate: {}", condition, e))
+    }
+
+    /// Run a nested step of a Conditional/Loop; its failure fails the parent
+    async fn execute_sub_step(
+        &self,
+        step: &ActionStep,
+        context: &mut ExecutionContext,
+    ) -> Result<StepResult> {
+        let result = self.execute_step(step, context).await?;
+        if result.status == StepStatus::Failed {
+            anyhow::bail!(
+                "Nested step {} failed: {}",
+                step.id,
+                result.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+        Ok(result)
+    }
+
+    /// Run the branch selected by `condition`; an invalid condition or a
+    /// failed nested step fails the Conditional step
+    async fn execute_conditional(
+        &self,
+        condition: &str,
+        then_steps: &[ActionStep],
+        else_steps: &[ActionStep],
+        context: &mut ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let taken = Self::evaluate_condition(condition, context, None)?;
+        tracing::debug!("Condition '{}' evaluated to {}", condition, taken);
+
+        let branch = if taken { then_steps } else { else_steps };
+        let mut conditional_results = Vec::new();
+        for sub_step in branch {
+            let sub_result = self.execute_sub_step(sub_step, context).await?;
+            conditional_results.push(sub_result);
+        }
+
+        Ok(serde_json::json!({
+            "condition": condition,
+            "condition_value": taken,
+            "executed_branch": if taken { "then" } else { "else" },
+            "results": conditional_results,
+            "executed_at": chrono::Utc::now()
+        }))
+    }
+
+    /// Run `body_steps` while `condition` holds. The condition is checked
+    /// before every iteration; a loop still wanting to run at the limit is an
+    /// error, not a silent stop
+    async fn execute_loop(
+        &self,
+        condition: &str,
+        body_steps: &[ActionStep],
+        max_iterations: u32,
+        context: &mut ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        let limit = max_iterations.min(MAX_LOOP_ITERATIONS);
+        let mut loop_results = Vec::new();
+        let mut iteration = 0;
+
+        while Self::evaluate_condition(condition, context, Some(iteration))? {
+            if iteration >= limit {
+                anyhow::bail!(
+                    "Loop condition '{}' still true after {} iterations (max_iterations)",
+                    condition,
+                    limit
+                );
+            }
+            tracing::debug!("Loop iteration {}/{}", iteration + 1, limit);
+
+            for sub_step in body_steps {
+                let sub_result = self.execute_sub_step(sub_step, context).await?;
+                loop_results.push(sub_result);
+            }
+
+            iteration += 1;
+        }
+
+        Ok(serde_json::json!({
+            "condition": condition,
+            "iterations": iteration,
+            "results": loop_results,
+            "executed_at": chrono::Utc::now()
+        }))
+    }
+
     /// Execute tool invocation step
     async fn execute_tool_step(
         &self,
@@ -370,6 +637,9 @@ impl Executor {
     ) -> Result<serde_json::Value> {
         if let Some(tool) = self.tool_registry.get(tool_name) {
             tracing::debug!("Executing tool: {}", tool_name);
+            if let Some(tracker) = self.spend() {
+                tracker.record_tool_call();
+            }
             tool.invoke(arguments.clone()).await
         } else {
             anyhow::bail!("Tool '{}' not found in registry", tool_name)
@@ -381,18 +651,31 @@ impl Executor {
         &self,
         operation_type: &MemoryOperationType,
         query: &str,
+        parameters: &HashMap<String, serde_json::Value>,
     ) -> Result<serde_json::Value> {
-        // Simulate memory operation
         tracing::debug!("Executing memory operation: {:?}", operation_type);
+        if let Some(memory) = &self.memory_invoker {
+            return memory.invoke(operation_type, query, parameters).await;
+        }
 
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        #[cfg(not(test))]
+        anyhow::bail!(
+            "Memory operation {:?} cannot run: no memory backend configured",
+            operation_type
+        );
 
-        Ok(serde_json::json!({
-            "operation": format!("{:?}", operation_type),
-            "query": query,
-            "result": "success",
-            "executed_at": chrono::Utc::now()
-        }))
+        // Simulate memory operation in unit tests
+        #[cfg(test)]
+        {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+            Ok(serde_json::json!({
+                "operation": format!("{:?}", operation_type),
+                "query": query,
+                "result": "success",
+                "executed_at": chrono::Utc::now()
+            }))
+        }
     }
 
     /// Execute user interaction step
@@ -400,43 +683,203 @@ impl Executor {
         &self,
         interaction_type: &InteractionType,
         prompt: &str,
+        parameters: &HashMap<String, serde_json::Value>,
     ) -> Result<serde_json::Value> {
-        // Simulate user interaction
         tracing::debug!("User interaction: {:?} - {}", interaction_type, prompt);
+        if let Some(handler) = &self.interaction_handler {
+            let response = handler
+                .interact(interaction_type, prompt, parameters)
+                .await?;
+            if *interaction_type == InteractionType::Confirmation
+                && response == serde_json::Value::Bool(false)
+            {
+                anyhow::bail!("User declined: {}", prompt);
+            }
+            return Ok(serde_json::json!({
+                "interaction_type": format!("{:?}", interaction_type),
+                "prompt": prompt,
+                "response": response,
+                "executed_at": chrono::Utc::now()
+            }));
+        }
 
-        // In a real implementation, this would prompt the user
-        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        #[cfg(not(test))]
+        anyhow::bail!(
+            "User interaction '{}' cannot run: nobody to ask (no interaction handler)",
+            prompt
+        );
 
-        Ok(serde_json::json!({
-            "interaction_type": format!("{:?}", interaction_type),
-            "prompt": prompt,
-            "response": "ok", // Mock user response
-            "executed_at": chrono::Utc::now()
-        }))
+        // Simulate user interaction in unit tests
+        #[cfg(test)]
+        {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+            Ok(serde_json::json!({
+                "interaction_type": format!("{:?}", interaction_type),
+                "prompt": prompt,
+                "response": "ok", // Mock user response
+                "executed_at": chrono::Utc::now()
+            }))
+        }
     }
 
-    /// Check if step dependencies are satisfied
-    fn check_dependencies(&self, step: &ActionStep, context: &ExecutionContext) -> bool {
-        for dep_id in &step.dependencies {
-            if let Some(dep_state) = context.step_states.get(dep_id) {
-                if dep_state.status != StepStatus::Completed {
-                    return false;
-                }
-            } else {
-                return false; // Dependency not found
+    /// Execute delegated step
+    async fn execute_delegation(
+        &self,
+        profile: &str,
+        task: &str,
+        parameters: &HashMap<String, serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        tracing::debug!("Delegating to agent profile '{}': {}", profile, task);
+        let Some(handler) = &self.delegation_handler else {
+            anyhow::bail!(
+                "Cannot delegate to agent profile '{}': no delegation handler",
+                profile
+            );
+        };
+        let result = handler.delegate(profile, task, parameters).await?;
+        // The sub-agent's own budget limits it; the parent pays for it too
+        if let Some(tracker) = self.spend() {
+            if let Some(spend) = result
+                .get("spend")
+                .and_then(|spend| serde_json::from_value::<SpendUsage>(spend.clone()).ok())
+            {
+                tracker.record_usage(&spend);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Check spend limits before a step attempt
+    async fn enforce_budget(&self) -> Result<()> {
+        if let Some(tracker) = self.spend() {
+            tracker.enforce().await.map_err(StepError::BudgetExceeded)?;
+        }
+        Ok(())
+    }
+
+    /// One attempt of a step, without retries
+    async fn run_step_once(
+        &self,
+        step: &ActionStep,
+        timeout: Option<Duration>,
+        context: &mut ExecutionContext,
+    ) -> Result<serde_json::Value> {
+        match &step.step_type {
+            ActionStepType::ToolExecution {
+                tool_name,
+                arguments,
+            } => Self::with_timeout(timeout, self.execute_tool_step(tool_name, arguments)).await,
+            ActionStepType::MemoryOperation {
+                operation_type,
+                query,
+            } => {
+                Self::with_timeout(
+                    timeout,
+                    self.execute_memory_operation(operation_type, query, &step.parameters),
+                )
+                .await
+            }
+            ActionStepType::UserInteraction {
+                interaction_type,
+                prompt,
+            } => {
+                Self::with_timeout(
+                    timeout,
+                    self.execute_user_interaction(interaction_type, prompt, &step.parameters),
+                )
+                .await
             }
+            ActionStepType::Delegate { profile, task } => {
+                Self::with_timeout(
+                    timeout,
+                    self.execute_delegation(profile, task, &step.parameters),
+                )
+                .await
+            }
+            ActionStepType::Wait { duration } => {
+                Self::with_timeout(timeout, async {
+                    tokio::time::sleep(*duration).await;
+                    Ok(serde_json::json!({
+                        "waited": format!("{:?}", duration),
+                        "executed_at": chrono::Utc::now()
+                    }))
+                })
+                .await
+            }
+            ActionStepType::Conditional {
+                condition,
+                then_steps,
+                else_steps,
+            } => {
+                self.execute_conditional(condition, then_steps, else_steps, context)
+                    .await
+            }
+            ActionStepType::Loop {
+                condition,
+                body_steps,
+                max_iterations,
+            } => {
+                self.execute_loop(condition, body_steps, *max_iterations, context)
+                    .await
+            }
+        }
+    }
+
+    /// Per-step time limit from `parameters["timeout_ms"]`
+    fn step_timeout(step: &ActionStep) -> Option<Duration> {
+        step.parameters
+            .get(STEP_TIMEOUT_PARAM)
+            .and_then(|ms| ms.as_u64())
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// Bound a leaf step by its time limit; Conditional/Loop steps are bounded
+    /// by the limits of their nested steps
+    async fn with_timeout<F>(timeout: Option<Duration>, operation: F) -> Result<serde_json::Value>
+    where
+        F: Future<Output = Result<serde_json::Value>>,
+    {
+        match timeout {
+            Some(limit) => tokio::time::timeout(limit, operation)
+                .await
+                .map_err(|_| StepError::TimedOut(limit))?,
+            None => operation.await,
         }
-        true
+    }
+
+    /// Result of a step that never ran (skipped or blocked)
+    fn unrun_step_result(
+        step_id: Uuid,
+        status: StepStatus,
+        error: String,
+        context: &mut ExecutionContext,
+    ) -> StepResult {
+        if let Some(step_state) = context.step_states.get_mut(&step_id) {
+            step_state.status = status.clone();
+        }
+        let result = StepResult {
+            step_id,
+            status,
+            output: None,
+            error: Some(error),
+            execution_time: Duration::ZERO,
+            retry_count: 0,
+            metadata: HashMap::new(),
+        };
+        context.step_history.push(step_view(&result));
+        result
     }
 
     /// Apply retry logic for failed steps
     async fn should_retry(
         &self,
         step: &ActionStep,
-        step_state: &StepState,
+        retry_count: u32,
         error: &ExecutionError,
     ) -> bool {
-        if step_state.retry_count >= step.retry_policy.max_retries {
+        if retry_count >= step.retry_policy.max_retries {
             return false;
         }
 
@@ -447,6 +890,11 @@ impl Executor {
 
         // Check retry conditions
         match error.error_type {
+            ExecutionErrorType::ToolExecutionFailed | ExecutionErrorType::TimeoutError => step
+                .retry_policy
+                .retry_conditions
+                .iter()
+                .any(|c| matches!(c, super::planner::RetryCondition::TemporaryFailure)),
             ExecutionErrorType::NetworkError => step
                 .retry_policy
                 .retry_conditions
@@ -477,24 +925,6 @@ impl Executor {
             }
         }
     }
-
-    /// Execute rollback operation for a specific step
-    async fn execute_rollback_operation(&self, step_id: Uuid) -> Result<()> {
-        tracing::debug!("Executing rollback for step {}", step_id);
-
-        // Simulate rollback operation
-        // In a real implementation, this would:
-        // - Undo file operations (delete created files, restore modified files)
-        // - Reverse memory operations (remove stored data, restore previous state)
-        // - Cancel network requests or API calls
-        // - Restore system state to previous condition
-
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
-        tracing::debug!("Rollback completed for step {}", step_id);
-
-        Ok(())
-    }
 }
 
 #[async_trait]
@@ -504,7 +934,7 @@ impl ExecutorTrait for Executor {
 
         let start_time = std::time::Instant::now();
         let mut context = self.create_execution_context(plan);
-        let mut step_results = Vec::new();
+        let mut results: HashMap<Uuid, StepResult> = HashMap::new();
         let mut execution_error = None;
 
         // Store execution context
@@ -512,25 +942,50 @@ impl ExecutorTrait for Executor {
 
         let mut status = ExecutionStatus::Running;
 
-        // Execute steps sequentially (for now - could be parallelized based on dependencies)
-        for step in &plan.steps {
-            // Check cancellation
+        // Steps run as soon as their dependencies completed, at most
+        // `cpu_cores` at a time; conflicting steps wait for each other
+        let lanes = plan.resource_requirements.cpu_cores.max(1) as usize;
+        let mut free_lanes: Vec<usize> = (0..lanes).rev().collect();
+        let mut graph = StepGraph::new(&plan.steps, |name| self.tool_read_only(name));
+        let mut running: Vec<RunningStep<'_>> = Vec::new();
+        loop {
             if context.cancellation_token.is_cancelled() {
                 status = ExecutionStatus::Cancelled;
+            }
+            while status == ExecutionStatus::Running && !free_lanes.is_empty() {
+                let Some(step) = graph.start_next() else {
+                    break;
+                };
+                let lane = free_lanes.pop().unwrap_or_default();
+                let mut step_context = context.clone();
+                let history_len = step_context.step_history.len();
+                running.push(Box::pin(async move {
+                    let result = self.execute_step(step, &mut step_context).await;
+                    let history = step_context.step_history.split_off(history_len);
+                    let state = step_context.step_states.remove(&step.id);
+                    (step, lane, state, history, result)
+                }));
+            }
+            if running.is_empty() {
                 break;
             }
 
-            // Check dependencies
-            if !self.check_dependencies(step, &context) {
+            let (step, lane, state, history, result) = next_finished(&mut running).await;
+            free_lanes.push(lane);
+            if let Some(state) = state {
+                context.step_states.insert(step.id, state);
+            }
+            context.step_history.extend(history);
+
+            let mut result = result.unwrap_or_else(|e| {
                 let error = ExecutionError {
-                    error_type: ExecutionErrorType::DependencyFailed,
-                    message: "Step dependencies not satisfied".to_string(),
+                    error_type: ExecutionErrorType::SystemError,
+                    message: e.to_string(),
                     step_id: Some(step.id),
                     retryable: false,
                     details: HashMap::new(),
                 };
-
-                step_results.push(StepResult {
+                let result = StepResult {
                     step_id: step.id,
                     status: StepStatus::Failed,
                     output: None,
@@ -538,43 +993,70 @@ impl ExecutorTrait for Executor {
                     execution_time: std::time::Duration::from_millis(0),
                     retry_count: 0,
                     metadata: HashMap::new(),
-                });
-
-                execution_error = Some(error);
-                status = ExecutionStatus::Failed;
-                break;
-            }
+                };
+                execution_error.get_or_insert(error);
+                result
+            });
+            result
+                .metadata
+                .insert("lane".to_string(), serde_json::json!(lane));
 
-            // Execute step with retry logic
-            match self.execute_step(step, &mut context).await {
-                Ok(result) => {
-                    step_results.push(result);
+            if result.status == StepStatus::Completed {
+                graph.complete(step.id);
+            } else {
+                execution_error.get_or_insert_with(|| ExecutionError {
+                    error_type: ExecutionErrorType::ToolExecutionFailed,
+                    message: result.error.clone().unwrap_or_default(),
+                    step_id: Some(step.id),
+                    retryable: false,
+                    details: HashMap::new(),
+                });
+                // Failures propagate to dependents only
+                for skipped in graph.fail(step.id) {
+                    results.insert(
+                        skipped.id,
+                        Self::unrun_step_result(
+                            skipped.id,
+                            StepStatus::Skipped,
+                            format!("Dependency {} failed", step.id),
+                            &mut context,
+                        ),
+                    );
                 }
-                Err(e) => {
-                    let error = ExecutionError {
-                        error_type: ExecutionErrorType::SystemError,
-                        message: e.to_string(),
-                        step_id: Some(step.id),
-                        retryable: false,
-                        details: HashMap::new(),
-                    };
-
-                    step_results.push(StepResult {
-                        step_id: step.id,
-                        status: StepStatus::Failed,
-                        output: None,
-                        error: Some(error.message.clone()),
-                        execution_time: std::time::Duration::from_millis(0),
-                        retry_count: 0,
-                        metadata: HashMap::new(),
-                    });
+            }
+            results.insert(step.id, result);
+        }
 
-                    execution_error = Some(error);
-                    status = ExecutionStatus::Failed;
-                    break;
-                }
+        if status == ExecutionStatus::Running {
+            // Unknown or circular dependencies never became ready
+            for step in graph.take_pending() {
+                let error = ExecutionError {
+                    error_type: ExecutionErrorType::DependencyFailed,
+                    message: "Step dependencies not satisfied".to_string(),
+                    step_id: Some(step.id),
+                    retryable: false,
+                    details: HashMap::new(),
+                };
+                results.insert(
+                    step.id,
+                    Self::unrun_step_result(
+                        step.id,
+                        StepStatus::Failed,
+                        error.message.clone(),
+                        &mut context,
+                    ),
+                );
+                execution_error.get_or_insert(error);
+            }
+            if execution_error.is_some() {
+                status = ExecutionStatus::Failed;
             }
         }
+        let step_results: Vec<StepResult> = plan
+            .steps
+            .iter()
+            .filter_map(|step| results.remove(&step.id))
+            .collect();
 
         if status == ExecutionStatus::Running {
             status = ExecutionStatus::Completed;
@@ -626,85 +1108,12 @@ impl ExecutorTrait for Executor {
             step_state.start_time = Some(step_start);
         }
 
+        let timeout = Self::step_timeout(step);
         let mut retry_count = 0;
         loop {
-            let result = match &step.step_type {
-                ActionStepType::ToolExecution {
-                    tool_name,
-                    arguments,
-                } => self.execute_tool_step(tool_name, arguments).await,
-                ActionStepType::MemoryOperation {
-                    operation_type,
-                    query,
-                } => self.execute_memory_operation(operation_type, query).await,
-                ActionStepType::UserInteraction {
-                    interaction_type,
-                    prompt,
-                } => {
-                    self.execute_user_interaction(interaction_type, prompt)
-                        .await
-                }
-                ActionStepType::Wait { duration } => {
-                    tokio::time::sleep(*duration).await;
-                    Ok(serde_json::json!({
-                        "waited": format!("{:?}", duration),
-                        "executed_at": chrono::Utc::now()
-                    }))
-                }
-                ActionStepType::Conditional {
-                    condition,
-                    then_steps,
-                    else_steps: _,
-                } => {
-                    // Simplified conditional logic
-                    tracing::debug!("Evaluating condition: {}", condition);
-
-                    // For now, just execute then_steps (would need proper condition evaluation)
-                    let mut conditional_results = Vec::new();
-                    for sub_step in then_steps {
-                        let sub_result = self.execute_step(sub_step, context).await?;
-                        conditional_results.push(sub_result);
-                    }
-
-                    Ok(serde_json::json!({
-                        "condition": condition,
-                        "executed_branch": "then",
-                        "results": conditional_results,
-                        "executed_at": chrono::Utc::now()
-                    }))
-                }
-                ActionStepType::Loop {
-                    condition,
-                    body_steps,
-                    max_iterations,
-                } => {
-                    // Simplified loop logic
-                    let mut loop_results = Vec::new();
-                    let mut iteration = 0;
-
-                    while iteration < *max_iterations {
-                        tracing::debug!("Loop iteration {}/{}", iteration + 1, max_iterations);
-
-                        for sub_step in body_steps {
-                            let sub_result = self.execute_step(sub_step, context).await?;
-                            loop_results.push(sub_result);
-                        }
-
-                        iteration += 1;
-
-                        // Simple break condition (would need proper condition evaluation)
-                        if condition == "break" {
-                            break;
-                        }
-                    }
-
-                    Ok(serde_json::json!({
-                        "condition": condition,
-                        "iterations": iteration,
-                        "results": loop_results,
-                        "executed_at": chrono::Utc::now()
-                    }))
-                }
+            let result = match self.enforce_budget().await {
+                Ok(()) => self.run_step_once(step, timeout, context).await,
+                Err(e) => Err(e),
             };
 
             let execution_time = step_start.elapsed();
@@ -719,7 +1128,7 @@ impl ExecutorTrait for Executor {
                         step_state.retry_count = retry_count;
                     }
 
-                    return Ok(StepResult {
+                    let step_result = StepResult {
                         step_id: step.id,
                         status: StepStatus::Completed,
                         output: Some(output),
@@ -727,33 +1136,44 @@ impl ExecutorTrait for Executor {
                         execution_time,
                         retry_count,
                         metadata: HashMap::new(),
-                    });
+                    };
+                    context.step_history.push(step_view(&step_result));
+                    return Ok(step_result);
                 }
                 Err(e) => {
+                    let (error_type, retryable) = match e.downcast_ref::<StepError>() {
+                        Some(StepError::TimedOut(_)) => (ExecutionErrorType::TimeoutError, true),
+                        Some(StepError::ToolFailed { .. }) => {
+                            (ExecutionErrorType::ToolExecutionFailed, true)
+                        }
+                        Some(StepError::BudgetExceeded(_)) => {
+                            (ExecutionErrorType::ResourceExhausted, false)
+                        }
+                        None => (ExecutionErrorType::ToolExecutionFailed, false),
+                    };
                     let error = ExecutionError {
-                        error_type: ExecutionErrorType::ToolExecutionFailed,
+                        error_type,
                         message: e.to_string(),
                         step_id: Some(step.id),
-                        retryable: true,
+                        retryable,
                         details: HashMap::new(),
                     };
 
-                    // Check if we should retry
-                    if let Some(step_state) = context.step_states.get(&step.id) {
-                        if self.should_retry(step, step_state, &error).await {
-                            retry_count += 1;
-
-                            // Update retry count
-                            if let Some(step_state) = context.step_states.get_mut(&step.id) {
-                                step_state.retry_count = retry_count;
-                                step_state.status = StepStatus::Retrying;
-                            }
-
-                            let delay = self.calculate_backoff_delay(step, retry_count);
-                            tracing::debug!("Retrying step {} after delay: {:?}", step.id, delay);
-                            tokio::time::sleep(delay).await;
-                            continue;
+                    // Check if we should retry (nested Conditional/Loop steps
+                    // have no step state, so count retries locally)
+                    if self.should_retry(step, retry_count, &error).await {
+                        retry_count += 1;
+
+                        // Update retry count
+                        if let Some(step_state) = context.step_states.get_mut(&step.id) {
+                            step_state.retry_count = retry_count;
+                            step_state.status = StepStatus::Retrying;
                         }
+
+                        let delay = self.calculate_backoff_delay(step, retry_count);
+                        tracing::debug!("Retrying step {} after delay: {:?}", step.id, delay);
+                        tokio::time::sleep(delay).await;
+                        continue;
                     }
 
                     // No retry or max retries reached
@@ -763,7 +1183,7 @@ impl ExecutorTrait for Executor {
                         step_state.retry_count = retry_count;
                     }
 
-                    return Ok(StepResult {
+                    let step_result = StepResult {
                         step_id: step.id,
                         status: StepStatus::Failed,
                         output: None,
@@ -771,7 +1191,9 @@ impl ExecutorTrait for Executor {
                         execution_time,
                         retry_count,
                         metadata: HashMap::new(),
-                    });
+                    };
+                    context.step_history.push(step_view(&step_result));
+                    return Ok(step_result);
                 }
             }
         }
@@ -807,71 +1229,67 @@ impl ExecutorTrait for Executor {
         }
     }
 
+    /// Undo a plan through its saga compensations. Steps that cannot be
+    /// undone (no saga, no inverse, failed compensation) fail the rollback
+    /// and are listed in the error.
     async fn rollback_execution(&self, plan_id: Uuid) -> Result<()> {
         tracing::info!("Starting rollback for plan {}", plan_id);
-
-        // Try saga-based rollback first
-        if let Some(saga) = self.active_sagas.get(&plan_id) {
-            let mut saga = saga.clone();
-            match self.saga_manager.compensate_saga(&mut saga).await {
-                Ok(compensation_result) => {
-                    self.active_sagas.insert(plan_id, saga);
-                    match compensation_result.status {
-                        SagaStatus::Compensated => {
-                            tracing::info!("Saga-based rollback completed for plan {}", plan_id);
-
-                            // Remove from active executions after successful saga rollback
-                            self.active_executions.remove(&plan_id);
-                            return Ok(());
-                        }
-                        _ => {
-                            tracing::warn!("Saga compensation failed with status: {:?}, falling back to simple rollback", compensation_result.status);
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Saga compensation failed: {}, falling back to simple rollback",
-                        e
-                    );
-                }
+        let context = self.active_executions.remove(&plan_id).map(|(_, c)| c);
+
+        let saga = self.active_sagas.get(&plan_id).map(|saga| saga.clone());
+        if let Some(mut saga) = saga {
+            let compensation_result = self.saga_manager.compensate_saga(&mut saga).await;
+            self.active_sagas.insert(plan_id, saga);
+            let compensation_result = compensation_result?;
+            if compensation_result.status == SagaStatus::Compensated {
+                tracing::info!("Saga-based rollback completed for plan {}", plan_id);
+                return Ok(());
             }
+            let unrecovered: Vec<String> = compensation_result
+                .report
+                .iter()
+                .filter(|entry| {
+                    matches!(
+                        entry.status,
+                        CompensationStatus::Failed | CompensationStatus::NotCompensable
+                    )
+                })
+                .map(|entry| {
+                    format!(
+                        "step {} {:?}: {}",
+                        entry.action_step_id,
+                        entry.status,
+                        entry.error.as_deref().unwrap_or(&entry.description)
+                    )
+                })
+                .collect();
+            anyhow::bail!(
+                "Rollback of plan {} incomplete ({:?}): {}",
+                plan_id,
+                compensation_result.status,
+                unrecovered.join("; ")
+            );
         }
 
-        // Fallback to existing simple rollback if no saga or saga fails
-        if let Some(context) = self.active_executions.get(&plan_id) {
-            let mut rollback_operations = Vec::new();
-
-            // Collect completed steps in reverse order for rollback
-            for (step_id, step_state) in &context.step_states {
-                if step_state.status == StepStatus::Completed {
-                    rollback_operations.push(*step_id);
-                }
-            }
-
-            rollback_operations.reverse(); // Reverse order for rollback
-
-            for step_id in rollback_operations {
-                match self.execute_rollback_operation(step_id).await {
-                    Ok(_) => {
-                        tracing::debug!("Simple rollback successful for step {}", step_id);
-                    }
-                    Err(e) => {
-                        tracing::error!("Simple rollback failed for step {}: {}", step_id, e);
-                        // Continue with other rollback operations
-                    }
-                }
-            }
-
-            // Remove from active executions after rollback
-            self.active_executions.remove(&plan_id);
-
-            tracing::info!("Simple rollback completed for plan {}", plan_id);
-        } else {
+        // Without a saga nothing was recorded to undo completed steps
+        let Some(context) = context else {
             tracing::warn!("Plan {} not found for rollback", plan_id);
+            return Ok(());
+        };
+        let not_compensable: Vec<String> = context
+            .step_states
+            .iter()
+            .filter(|(_, state)| state.status == StepStatus::Completed)
+            .map(|(step_id, _)| format!("step {step_id} NotCompensable"))
+            .collect();
+        if not_compensable.is_empty() {
+            return Ok(());
         }
-
-        Ok(())
+        anyhow::bail!(
+            "Rollback of plan {} incomplete: no saga recorded compensations for {}",
+            plan_id,
+            not_compensable.join("; ")
+        )
     }
 
     async fn execute_plan_with_saga(&self, plan: &ActionPlan) -> Result<ExecutionResult> {
@@ -883,8 +1301,15 @@ impl ExecutorTrait for Executor {
         // Store saga
         self.active_sagas.insert(plan.id, saga.clone());
 
-        // Execute the saga
-        let saga_result = self.saga_manager.execute_saga(&mut saga).await?;
+        // Execute the real steps; the saga records how to undo each one
+        let runner = PlanStepRunner {
+            executor: self,
+            context: tokio::sync::Mutex::new(self.create_execution_context(plan)),
+        };
+        let saga_result = self
+            .saga_manager
+            .execute_plan(&mut saga, plan, &runner)
+            .await?;
 
         // Handle saga execution result
         match saga_result.status {
@@ -968,6 +1393,10 @@ impl ExecutorTrait for Executor {
                             "compensated_steps".to_string(),
                             serde_json::json!(compensation_result.compensated_steps),
                         );
+                        metadata.insert(
+                            "compensation_report".to_string(),
+                            serde_json::json!(compensation_result.report),
+                        );
                         if !compensation_result.errors.is_empty() {
                             metadata.insert(
                                 "compensation_errors".to_string(),
@@ -1020,6 +1449,70 @@ impl Default for Executor {
     }
 }
 
+/// A step of [`Executor::execute_plan`] in flight: the step, its lane, and
+/// what it changed in its copy of the context
+type RunningStep<'a> = Pin<
+    Box<
+        dyn Future<
+                Output = (
+                    &'a ActionStep,
+                    usize,
+                    Option<StepState>,
+                    Vec<serde_json::Value>,
+                    Result<StepResult>,
+                ),
+            > + Send
+            + 'a,
+    >,
+>;
+
+/// Wait for the first running step to finish and remove it
+async fn next_finished<'a>(
+    running: &mut Vec<RunningStep<'a>>,
+) -> (
+    &'a ActionStep,
+    usize,
+    Option<StepState>,
+    Vec<serde_json::Value>,
+    Result<StepResult>,
+) {
+    let (index, output) = std::future::poll_fn(|cx| {
+        for (index, step) in running.iter_mut().enumerate() {
+            if let Poll::Ready(output) = step.as_mut().poll(cx) {
+                return Poll::Ready((index, output));
+            }
+        }
+        Poll::Pending
+    })
+    .await;
+    drop(running.swap_remove(index));
+    output
+}
+
+/// Runs saga steps through [`Executor::execute_step`] with a shared context
+struct PlanStepRunner<'a> {
+    executor: &'a Executor,
+    context: tokio::sync::Mutex<ExecutionContext>,
+}
+
+#[async_trait]
+impl SagaStepRunner for PlanStepRunner<'_> {
+    async fn run_step(&self, step: &ActionStep) -> Result<serde_json::Value> {
+        let mut context = self.context.lock().await;
+        let result = self.executor.execute_step(step, &mut context).await?;
+        match result.status {
+            StepStatus::Completed => Ok(result.output.unwrap_or(serde_json::Value::Null)),
+            _ => Err(anyhow::anyhow!(result
+                .error
+                .unwrap_or_else(|| format!("Step {} failed", step.id)))),
+        }
+    }
+
+    fn is_read_only_tool(&self, tool_name: &str) -> bool {
+        self.executor.tool_read_only(tool_name) == Some(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1027,6 +1520,7 @@ mod tests {
         ActionPlan, ActionStep, BackoffStrategy, ResourceRequirements, RetryCondition, RetryPolicy,
         ValidationRule,
     };
+    use std::collections::HashSet;
 
     fn create_test_plan() -> ActionPlan {
         let step = ActionStep {
@@ -1088,6 +1582,7 @@ mod tests {
             current_step: 0,
             step_states: HashMap::new(),
             shared_data: HashMap::new(),
+            step_history: Vec::new(),
             resource_limits: Executor::default_resource_limits(),
             cancellation_token: tokio_util::sync::CancellationToken::new(),
         };
@@ -1163,6 +1658,7 @@ mod tests {
             current_step: 0,
             step_states: HashMap::new(),
             shared_data: HashMap::new(),
+            step_history: Vec::new(),
             resource_limits: Executor::default_resource_limits(),
             cancellation_token: tokio_util::sync::CancellationToken::new(),
         };
@@ -1191,9 +1687,13 @@ mod tests {
 
         executor.active_executions.insert(plan.id, context);
 
-        // Test rollback
-        let result = executor.rollback_execution(plan.id).await;
-        assert!(result.is_ok());
+        // Without a saga the completed step cannot be undone
+        let err = executor
+            .rollback_execution(plan.id)
+            .await
+            .expect_err("completed step has no compensation");
+        assert!(err.to_string().contains("NotCompensable"));
+        assert!(err.to_string().contains(&step_id.to_string()));
 
         // Verify execution was removed from active executions
         assert!(!executor.active_executions.contains_key(&plan.id));
@@ -1383,6 +1883,272 @@ mod tests {
 
         tracing::info!("execute_plan_with_saga error handling test completed successfully");
     }
+
+    fn control_step(step_type: ActionStepType) -> ActionStep {
+        ActionStep {
+            id: Uuid::new_v4(),
+            step_type,
+            parameters: HashMap::new(),
+            dependencies: vec![],
+            expected_duration: std::time::Duration::from_secs(1),
+            retry_policy: RetryPolicy {
+                max_retries: 0,
+                backoff_strategy: BackoffStrategy::Fixed(std::time::Duration::from_millis(10)),
+                retry_conditions: vec![],
+            },
+            validation_rules: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conditional_and_loop_evaluate_expressions() {
+        let executor = Executor::new();
+        let tool = || {
+            control_step(ActionStepType::ToolExecution {
+                tool_name: "file_reader".to_string(),
+                arguments: HashMap::new(),
+            })
+        };
+        let mut context = executor.create_execution_context(&create_test_plan());
+
+        let conditional = control_step(ActionStepType::Conditional {
+            condition: "exists(last) && last.exit_code != 0".to_string(),
+            then_steps: vec![tool()],
+            else_steps: vec![],
+        });
+        let result = executor
+            .execute_step(&conditional, &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        let output = result.output.expect("conditional output");
+        assert_eq!(output["executed_branch"], "else");
+
+        let bounded = control_step(ActionStepType::Loop {
+            condition: "iteration < 2".to_string(),
+            body_steps: vec![tool()],
+            max_iterations: 5,
+        });
+        let result = executor
+            .execute_step(&bounded, &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(result.status, StepStatus::Completed);
+        assert_eq!(result.output.expect("loop output")["iterations"], 2);
+
+        let runaway = control_step(ActionStepType::Loop {
+            condition: "last.status == 'Completed'".to_string(),
+            body_steps: vec![tool()],
+            max_iterations: 3,
+        });
+        let result = executor
+            .execute_step(&runaway, &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(result.status, StepStatus::Failed);
+        assert!(result.error.expect("loop error").contains("max_iterations"));
+    }
+
+    #[tokio::test]
+    async fn test_step_timeout_is_retried_on_temporary_failure() {
+        let executor = Executor::new();
+        let mut context = executor.create_execution_context(&create_test_plan());
+
+        let mut step = control_step(ActionStepType::Wait {
+            duration: std::time::Duration::from_secs(5),
+        });
+        step.parameters
+            .insert(STEP_TIMEOUT_PARAM.to_string(), serde_json::json!(20));
+        step.retry_policy.max_retries = 2;
+        step.retry_policy.retry_conditions = vec![RetryCondition::TemporaryFailure];
+
+        let result = executor
+            .execute_step(&step, &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(result.status, StepStatus::Failed);
+        assert_eq!(result.retry_count, 2);
+        assert!(result.error.expect("timeout error").contains("timed out"));
+    }
+
+    struct Answer(serde_json::Value);
+
+    #[async_trait]
+    impl InteractionHandler for Answer {
+        async fn interact(
+            &self,
+            _interaction_type: &InteractionType,
+            _prompt: &str,
+            _parameters: &HashMap<String, serde_json::Value>,
+        ) -> Result<serde_json::Value> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interaction_handler_answers_prompts() {
+        let mut executor = Executor::new();
+        executor.set_interaction_handler(Arc::new(Answer(serde_json::json!(false))));
+        let mut context = executor.create_execution_context(&create_test_plan());
+
+        let confirm = control_step(ActionStepType::UserInteraction {
+            interaction_type: InteractionType::Confirmation,
+            prompt: "Deploy?".to_string(),
+        });
+        let result = executor
+            .execute_step(&confirm, &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(result.status, StepStatus::Failed);
+        assert!(result.error.expect("decline error").contains("declined"));
+
+        executor.set_interaction_handler(Arc::new(Answer(serde_json::json!("blue"))));
+        let input = control_step(ActionStepType::UserInteraction {
+            interaction_type: InteractionType::Input,
+            prompt: "Colour?".to_string(),
+        });
+        let result = executor
+            .execute_step(&input, &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(
+            result.output.expect("interaction output")["response"],
+            "blue"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_budget_stops_steps() {
+        use crate::resources::spend::{SpendBudget, SpendTracker};
+
+        let mut executor = Executor::new();
+        let tracker = Arc::new(SpendTracker::new(
+            SpendBudget::new().tool_call_limit(None, Some(1)),
+        ));
+        executor.set_spend_tracker(Arc::clone(&tracker));
+        let plan = create_test_plan();
+        let mut context = executor.create_execution_context(&plan);
+
+        let first = executor
+            .execute_step(&plan.steps[0], &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(first.status, StepStatus::Completed);
+
+        let second = executor
+            .execute_step(&plan.steps[0], &mut context)
+            .await
+            .expect("Executor operation should succeed");
+        assert_eq!(second.status, StepStatus::Failed);
+        // budget errors are not retried
+        assert_eq!(second.retry_count, 0);
+        assert!(second
+            .error
+            .expect("budget error")
+            .contains("budget exceeded"));
+        assert_eq!(tracker.usage().tool_calls, 1);
+    }
+
+    /// Sleeps, then fails for paths starting with "bad"
+    struct SlowReader;
+
+    #[async_trait]
+    impl ToolInvoker for SlowReader {
+        async fn invoke(
+            &self,
+            args: HashMap<String, serde_json::Value>,
+        ) -> Result<serde_json::Value> {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let path = args.get("path").and_then(|p| p.as_str()).unwrap_or("");
+            if path.starts_with("bad") {
+                anyhow::bail!("cannot read {}", path);
+            }
+            Ok(serde_json::json!({ "path": path }))
+        }
+
+        fn get_name(&self) -> &str {
+            "slow_read"
+        }
+
+        fn read_only(&self) -> Option<bool> {
+            Some(true)
+        }
+    }
+
+    fn read_step(path: &str, dependencies: Vec<Uuid>) -> ActionStep {
+        let mut step = control_step(ActionStepType::ToolExecution {
+            tool_name: "slow_read".to_string(),
+            arguments: HashMap::from([("path".to_string(), serde_json::json!(path))]),
+        });
+        step.dependencies = dependencies;
+        step
+    }
+
+    #[tokio::test]
+    async fn test_independent_steps_run_in_parallel_lanes() {
+        let mut executor = Executor::new();
+        executor.register_tool("slow_read".to_string(), Box::new(SlowReader));
+        let mut plan = create_test_plan();
+        let reads: Vec<ActionStep> = (0..4)
+            .map(|i| read_step(&format!("f{i}"), vec![]))
+            .collect();
+        let summary = read_step("summary", reads.iter().map(|step| step.id).collect());
+        plan.steps = reads;
+        plan.steps.push(summary);
+        plan.resource_requirements.cpu_cores = 4;
+
+        let result = executor
+            .execute_plan(&plan)
+            .await
+            .expect("Executor operation should succeed");
+
+        assert_eq!(result.status, ExecutionStatus::Completed);
+        // four reads side by side, then the summary: ~200ms instead of ~500ms
+        assert!(result.execution_time < Duration::from_millis(400));
+        let lanes: HashSet<u64> = result.step_results[..4]
+            .iter()
+            .filter_map(|step| step.metadata["lane"].as_u64())
+            .collect();
+        assert_eq!(lanes.len(), 4);
+        let ids: Vec<Uuid> = result.step_results.iter().map(|r| r.step_id).collect();
+        let planned: Vec<Uuid> = plan.steps.iter().map(|step| step.id).collect();
+        assert_eq!(ids, planned);
+    }
+
+    #[tokio::test]
+    async fn test_failure_skips_only_dependents() {
+        let mut executor = Executor::new();
+        executor.register_tool("slow_read".to_string(), Box::new(SlowReader));
+        let mut plan = create_test_plan();
+        let bad = read_step("bad", vec![]);
+        let after_bad = read_step("after", vec![bad.id]);
+        let good = read_step("good", vec![]);
+        plan.steps = vec![bad, after_bad, good];
+        plan.resource_requirements.cpu_cores = 2;
+
+        let result = executor
+            .execute_plan(&plan)
+            .await
+            .expect("Executor operation should succeed");
+
+        assert_eq!(result.status, ExecutionStatus::Failed);
+        let statuses: Vec<StepStatus> = result
+            .step_results
+            .iter()
+            .map(|step| step.status.clone())
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StepStatus::Failed,
+                StepStatus::Skipped,
+                StepStatus::Completed
+            ]
+        );
+        assert_eq!(
+            result.error.expect("first failure is reported").step_id,
+            Some(plan.steps[0].id)
+        );
+    }
 }
 
 /// HealthChecker implementation for Executor
@@ -0,0 +1,1000 @@
+//! Sandboxed expression language for `Conditional` and `Loop` plan steps.
+//!
+//! Expressions are pure: no assignment, no I/O, bounded length and nesting,
+//! regex patterns must be string literals (compiled once, with a size limit).
+//!
+//! ```text
+//! last.exit_code == 0 && last.output.result =~ "^ok"
+//! len(steps[0].output.results) > 0 || vars.force == true
+//! steps["<step uuid>"].status != "Failed" and iteration < 3
+//! ```
+//!
+//! Roots:
+//! - `last` — the most recently finished step
+//! - `steps[N]` / `steps["<uuid>"]` — finished steps by index (negative from
+//!   the end) or by step id
+//! - `vars` — `ExecutionContext::shared_data`
+//! - `iteration` — current loop iteration (0-based), `null` outside loops
+//!
+//! A step is seen as `{id, status, output, error, exit_code, retry_count}`.
+//! Missing fields resolve to `null`; use `exists(...)` to test for them.
+//!
+//! Operators: `== != < <= > >= =~ !~ in && || !` (`and`, `or`, `not` are
+//! aliases). Functions: `len`, `exists`, `contains`, `lower`, `matches`.
+
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use uuid::Uuid;
+
+use super::executor::{StepResult, StepStatus};
+
+/// Maximum source length of an expression
+pub const MAX_EXPRESSION_LEN: usize = 2048;
+/// Maximum nesting depth (parentheses, `!`, function arguments)
+pub const MAX_EXPRESSION_DEPTH: usize = 32;
+/// Hard upper bound for `Loop::max_iterations`
+pub const MAX_LOOP_ITERATIONS: u32 = 1000;
+
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ExpressionError {
+    #[error("syntax error at column {position}: {message}")]
+    Syntax { position: usize, message: String },
+    #[error("expression is too long ({0} chars, max {MAX_EXPRESSION_LEN})")]
+    TooLong(usize),
+    #[error("expression is nested too deeply (max depth {MAX_EXPRESSION_DEPTH})")]
+    TooDeep,
+    #[error("unknown identifier '{0}' (expected last, steps, vars or iteration)")]
+    UnknownIdentifier(String),
+    #[error("unknown function '{0}' (expected len, exists, contains, lower or matches)")]
+    UnknownFunction(String),
+    #[error("function '{name}' expects {expected} argument(s), got {got}")]
+    Arity {
+        name: String,
+        expected: usize,
+        got: usize,
+    },
+    #[error("invalid regex \"{pattern}\": {message}")]
+    InvalidRegex { pattern: String, message: String },
+    #[error("expression references unknown step '{0}'")]
+    UnknownStep(String),
+    #[error("type error: {0}")]
+    Type(String),
+}
+
+/// A parsed, validated expression
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    ast: Expr,
+}
+
+/// Data visible to an expression during evaluation
+#[derive(Debug, Clone, Copy)]
+pub struct Scope<'a> {
+    /// Finished steps in completion order (see [`step_view`])
+    pub history: &'a [Value],
+    pub vars: &'a HashMap<String, Value>,
+    pub iteration: Option<u32>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(history: &'a [Value], vars: &'a HashMap<String, Value>) -> Self {
+        Self {
+            history,
+            vars,
+            iteration: None,
+        }
+    }
+
+    pub fn with_iteration(mut self, iteration: u32) -> Self {
+        self.iteration = Some(iteration);
+        self
+    }
+}
+
+/// The shape of a finished step as seen by expressions
+pub fn step_view(result: &StepResult) -> Value {
+    let exit_code = result
+        .output
+        .as_ref()
+        .and_then(|o| {
+            o.get("exit_code")
+                .or_else(|| o.pointer("/metadata/exit_code"))
+                .or_else(|| o.pointer("/metadata/status_code"))
+        })
+        .and_then(|v| match v {
+            Value::Number(n) => n.as_i64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })
+        .unwrap_or(match result.status {
+            StepStatus::Completed | StepStatus::Skipped => 0,
+            _ => 1,
+        });
+    serde_json::json!({
+        "id": result.step_id.to_string(),
+        "status": format!("{:?}", result.status),
+        "output": result.output.clone().unwrap_or(Value::Null),
+        "error": result.error,
+        "exit_code": exit_code,
+        "retry_count": result.retry_count,
+    })
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let len = source.chars().count();
+        if len > MAX_EXPRESSION_LEN {
+            return Err(ExpressionError::TooLong(len));
+        }
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let ast = parser.parse_or()?;
+        if let Some(tok) = parser.tokens.get(parser.pos) {
+            return Err(ExpressionError::Syntax {
+                position: tok.position,
+                message: format!("unexpected {}", tok.kind),
+            });
+        }
+        Ok(Self {
+            source: source.to_string(),
+            ast,
+        })
+    }
+
+    /// Parse and check that every `steps["<uuid>"]` refers to a known step
+    pub fn validate(source: &str, known_steps: &HashSet<Uuid>) -> Result<Self, ExpressionError> {
+        let expr = Self::parse(source)?;
+        for id in expr.step_references() {
+            let known = Uuid::parse_str(&id)
+                .map(|uuid| known_steps.contains(&uuid))
+                .unwrap_or(false);
+            if !known {
+                return Err(ExpressionError::UnknownStep(id));
+            }
+        }
+        Ok(expr)
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Step ids referenced as `steps["<id>"]`
+    pub fn step_references(&self) -> Vec<String> {
+        let mut refs = Vec::new();
+        self.ast.collect_step_refs(&mut refs);
+        refs
+    }
+
+    pub fn evaluate(&self, scope: &Scope<'_>) -> Result<Value, ExpressionError> {
+        self.ast.eval(scope)
+    }
+
+    /// Evaluate a condition; anything but a boolean is an error
+    pub fn evaluate_bool(&self, scope: &Scope<'_>) -> Result<bool, ExpressionError> {
+        match self.evaluate(scope)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(ExpressionError::Type(format!(
+                "condition must evaluate to a boolean, got {}",
+                type_name(&other)
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tokenizer
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    Comma,
+    Op(&'static str),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Ident(s) => write!(f, "identifier '{s}'"),
+            TokenKind::Number(n) => write!(f, "number {n}"),
+            TokenKind::Str(s) => write!(f, "string \"{s}\""),
+            TokenKind::LParen => f.write_str("'('"),
+            TokenKind::RParen => f.write_str("')'"),
+            TokenKind::LBracket => f.write_str("'['"),
+            TokenKind::RBracket => f.write_str("']'"),
+            TokenKind::Dot => f.write_str("'.'"),
+            TokenKind::Comma => f.write_str("','"),
+            TokenKind::Op(op) => write!(f, "operator '{op}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+const OPERATORS: [&str; 13] = [
+    "==", "!=", "<=", ">=", "=~", "!~", "&&", "||", "<", ">", "!", "=", "|",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        let kind = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '[' => TokenKind::LBracket,
+            ']' => TokenKind::RBracket,
+            '.' => TokenKind::Dot,
+            ',' => TokenKind::Comma,
+            '"' | '\'' => {
+                let quote = c;
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(ExpressionError::Syntax {
+                                position,
+                                message: "unterminated string".into(),
+                            })
+                        }
+                        Some('\\') => {
+                            let escaped = chars.get(i + 1).copied().ok_or_else(|| {
+                                ExpressionError::Syntax {
+                                    position,
+                                    message: "unterminated string".into(),
+                                }
+                            })?;
+                            match escaped {
+                                'n' => s.push('\n'),
+                                't' => s.push('\t'),
+                                // Keep regex escapes (\d, \s, \.) intact
+                                '\\' | '"' | '\'' => s.push(escaped),
+                                other => {
+                                    s.push('\\');
+                                    s.push(other);
+                                }
+                            }
+                            i += 2;
+                        }
+                        Some(&ch) if ch == quote => break,
+                        Some(&ch) => {
+                            s.push(ch);
+                            i += 1;
+                        }
+                    }
+                }
+                TokenKind::Str(s)
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let start = i;
+                i += 1;
+                while chars
+                    .get(i)
+                    .is_some_and(|ch| ch.is_ascii_digit() || *ch == '.')
+                {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text.parse::<f64>().map_err(|_| ExpressionError::Syntax {
+                    position,
+                    message: format!("invalid number '{text}'"),
+                })?;
+                tokens.push(Token {
+                    kind: TokenKind::Number(n),
+                    position,
+                });
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .is_some_and(|ch| ch.is_alphanumeric() || *ch == '_' || *ch == '$')
+                {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                let kind = match ident.as_str() {
+                    "and" => TokenKind::Op("&&"),
+                    "or" => TokenKind::Op("||"),
+                    "not" => TokenKind::Op("!"),
+                    _ => TokenKind::Ident(ident),
+                };
+                tokens.push(Token { kind, position });
+                continue;
+            }
+            _ => {
+                let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+                let op = OPERATORS
+                    .iter()
+                    .find(|op| rest.starts_with(**op))
+                    .ok_or_else(|| ExpressionError::Syntax {
+                        position,
+                        message: format!("unexpected character '{c}'"),
+                    })?;
+                if *op == "=" || *op == "|" {
+                    return Err(ExpressionError::Syntax {
+                        position,
+                        message: format!("unexpected '{op}' (did you mean '{op}{op}'?)"),
+                    });
+                }
+                i += op.len();
+                tokens.push(Token {
+                    kind: TokenKind::Op(op),
+                    position,
+                });
+                continue;
+            }
+        };
+        tokens.push(Token { kind, position });
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------
+// AST and parser
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Root {
+    Last,
+    Steps,
+    Vars,
+    Iteration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Func {
+    Len,
+    Exists,
+    Contains,
+    Lower,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Path(Root, Vec<Segment>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cmp(CmpOp, Box<Expr>, Box<Expr>),
+    Match {
+        value: Box<Expr>,
+        regex: Regex,
+        negate: bool,
+    },
+    Call(Func, Vec<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|t| &t.kind)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map(|t| t.position)
+            .unwrap_or(1)
+    }
+
+    fn next(&mut self) -> Option<TokenKind> {
+        let tok = self.tokens.get(self.pos).map(|t| t.kind.clone());
+        self.pos += 1;
+        tok
+    }
+
+    fn error(&self, message: impl Into<String>) -> ExpressionError {
+        ExpressionError::Syntax {
+            position: self.position(),
+            message: message.into(),
+        }
+    }
+
+    fn expect(&mut self, expected: TokenKind) -> Result<(), ExpressionError> {
+        match self.peek() {
+            Some(kind) if *kind == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(kind) => Err(self.error(format!("expected {expected}, found {kind}"))),
+            None => Err(self.error(format!("expected {expected}, found end of expression"))),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), ExpressionError> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err(ExpressionError::TooDeep);
+        }
+        Ok(())
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ExpressionError> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&TokenKind::Op("||")) {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ExpressionError> {
+        let mut left = self.parse_not()?;
+        while self.peek() == Some(&TokenKind::Op("&&")) {
+            self.pos += 1;
+            let right = self.parse_not()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, ExpressionError> {
+        if self.peek() == Some(&TokenKind::Op("!")) {
+            self.pos += 1;
+            self.enter()?;
+            let inner = self.parse_not()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_cmp()
+    }
+
+    fn parse_cmp(&mut self) -> Result<Expr, ExpressionError> {
+        let left = self.parse_primary()?;
+        let op = match self.peek() {
+            Some(TokenKind::Op(op)) => *op,
+            Some(TokenKind::Ident(id)) if id == "in" => "in",
+            _ => return Ok(left),
+        };
+        let cmp = match op {
+            "==" => CmpOp::Eq,
+            "!=" => CmpOp::Ne,
+            "<" => CmpOp::Lt,
+            "<=" => CmpOp::Le,
+            ">" => CmpOp::Gt,
+            ">=" => CmpOp::Ge,
+            "in" => CmpOp::In,
+            "=~" | "!~" => {
+                self.pos += 1;
+                let regex = self.parse_regex_literal()?;
+                return Ok(Expr::Match {
+                    value: Box::new(left),
+                    regex,
+                    negate: op == "!~",
+                });
+            }
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.parse_primary()?;
+        Ok(Expr::Cmp(cmp, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_regex_literal(&mut self) -> Result<Regex, ExpressionError> {
+        match self.next() {
+            Some(TokenKind::Str(pattern)) => compile_regex(&pattern),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("regex pattern must be a string literal"))
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ExpressionError> {
+        match self.next() {
+            Some(TokenKind::Number(n)) => Ok(Expr::Literal(number(n))),
+            Some(TokenKind::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(TokenKind::LParen) => {
+                self.enter()?;
+                let inner = self.parse_or()?;
+                self.expect(TokenKind::RParen)?;
+                self.depth -= 1;
+                Ok(inner)
+            }
+            Some(TokenKind::Ident(ident)) => match ident.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if self.peek() == Some(&TokenKind::LParen) => self.parse_call(ident),
+                _ => self.parse_path(ident),
+            },
+            Some(other) => {
+                self.pos -= 1;
+                Err(self.error(format!("unexpected {other}")))
+            }
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn parse_call(&mut self, name: String) -> Result<Expr, ExpressionError> {
+        self.expect(TokenKind::LParen)?;
+        self.enter()?;
+        let arity = |expected: usize, got: usize| {
+            if expected == got {
+                Ok(())
+            } else {
+                Err(ExpressionError::Arity {
+                    name: name.clone(),
+                    expected,
+                    got,
+                })
+            }
+        };
+
+        if name == "matches" {
+            let value = self.parse_or()?;
+            if self.peek() != Some(&TokenKind::Comma) {
+                return Err(arity(2, 1).unwrap_err());
+            }
+            self.pos += 1;
+            let regex = self.parse_regex_literal()?;
+            self.expect(TokenKind::RParen)?;
+            self.depth -= 1;
+            return Ok(Expr::Match {
+                value: Box::new(value),
+                regex,
+                negate: false,
+            });
+        }
+
+        let func = match name.as_str() {
+            "len" => Func::Len,
+            "exists" => Func::Exists,
+            "contains" => Func::Contains,
+            "lower" => Func::Lower,
+            _ => return Err(ExpressionError::UnknownFunction(name.clone())),
+        };
+        let mut args = Vec::new();
+        if self.peek() != Some(&TokenKind::RParen) {
+            loop {
+                args.push(self.parse_or()?);
+                if self.peek() == Some(&TokenKind::Comma) {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect(TokenKind::RParen)?;
+        self.depth -= 1;
+        let expected = if func == Func::Contains { 2 } else { 1 };
+        arity(expected, args.len())?;
+        Ok(Expr::Call(func, args))
+    }
+
+    fn parse_path(&mut self, ident: String) -> Result<Expr, ExpressionError> {
+        let root = match ident.as_str() {
+            "last" => Root::Last,
+            "steps" => Root::Steps,
+            "vars" => Root::Vars,
+            "iteration" => Root::Iteration,
+            _ => return Err(ExpressionError::UnknownIdentifier(ident)),
+        };
+        let mut segments = Vec::new();
+        loop {
+            match self.peek() {
+                Some(TokenKind::Dot) => {
+                    self.pos += 1;
+                    match self.next() {
+                        Some(TokenKind::Ident(key)) => segments.push(Segment::Key(key)),
+                        Some(TokenKind::Number(n)) if n.fract() == 0.0 && n >= 0.0 => {
+                            segments.push(Segment::Index(n as i64))
+                        }
+                        _ => {
+                            self.pos -= 1;
+                            return Err(self.error("expected field name after '.'"));
+                        }
+                    }
+                }
+                Some(TokenKind::LBracket) => {
+                    self.pos += 1;
+                    match self.next() {
+                        Some(TokenKind::Str(key)) => segments.push(Segment::Key(key)),
+                        Some(TokenKind::Number(n)) if n.fract() == 0.0 => {
+                            segments.push(Segment::Index(n as i64))
+                        }
+                        _ => {
+                            self.pos -= 1;
+                            return Err(self.error("expected string key or integer index"));
+                        }
+                    }
+                    self.expect(TokenKind::RBracket)?;
+                }
+                _ => break,
+            }
+        }
+        if root == Root::Iteration && !segments.is_empty() {
+            return Err(self.error("'iteration' is a number and has no fields"));
+        }
+        Ok(Expr::Path(root, segments))
+    }
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex, ExpressionError> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| ExpressionError::InvalidRegex {
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })
+}
+
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Evaluation
+
+fn type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn index<T>(items: &[T], i: i64) -> Option<&T> {
+    let idx = if i < 0 { items.len() as i64 + i } else { i };
+    usize::try_from(idx).ok().and_then(|idx| items.get(idx))
+}
+
+fn descend(mut value: Value, segments: &[Segment]) -> Value {
+    for segment in segments {
+        value = match (segment, value) {
+            (Segment::Key(key), Value::Object(mut map)) => map.remove(key).unwrap_or(Value::Null),
+            (Segment::Index(i), Value::Array(items)) => {
+                index(&items, *i).cloned().unwrap_or(Value::Null)
+            }
+            _ => Value::Null,
+        };
+    }
+    value
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn expect_bool(v: Value, what: &str) -> Result<bool, ExpressionError> {
+    match v {
+        Value::Bool(b) => Ok(b),
+        other => Err(ExpressionError::Type(format!(
+            "{what} expects boolean operands, got {}",
+            type_name(&other)
+        ))),
+    }
+}
+
+fn contains(haystack: &Value, needle: &Value) -> Result<bool, ExpressionError> {
+    match (haystack, needle) {
+        (Value::Array(items), _) => Ok(items.iter().any(|item| values_equal(item, needle))),
+        (Value::String(s), Value::String(sub)) => Ok(s.contains(sub.as_str())),
+        (Value::Object(map), Value::String(key)) => Ok(map.contains_key(key)),
+        (Value::Null, _) => Ok(false),
+        (h, n) => Err(ExpressionError::Type(format!(
+            "cannot check whether {} contains {}",
+            type_name(h),
+            type_name(n)
+        ))),
+    }
+}
+
+impl Expr {
+    fn collect_step_refs(&self, out: &mut Vec<String>) {
+        match self {
+            Expr::Path(Root::Steps, segments) => {
+                if let Some(Segment::Key(id)) = segments.first() {
+                    out.push(id.clone());
+                }
+            }
+            Expr::Literal(_) | Expr::Path(..) => {}
+            Expr::Not(inner) => inner.collect_step_refs(out),
+            Expr::And(a, b) | Expr::Or(a, b) | Expr::Cmp(_, a, b) => {
+                a.collect_step_refs(out);
+                b.collect_step_refs(out);
+            }
+            Expr::Match { value, .. } => value.collect_step_refs(out),
+            Expr::Call(_, args) => args.iter().for_each(|a| a.collect_step_refs(out)),
+        }
+    }
+
+    fn eval(&self, scope: &Scope<'_>) -> Result<Value, ExpressionError> {
+        match self {
+            Expr::Literal(v) => Ok(v.clone()),
+            Expr::Path(root, segments) => Ok(resolve(*root, segments, scope)),
+            Expr::Not(inner) => Ok(Value::Bool(!expect_bool(inner.eval(scope)?, "'!'")?)),
+            Expr::And(a, b) => {
+                if !expect_bool(a.eval(scope)?, "'&&'")? {
+                    return Ok(Value::Bool(false));
+                }
+                Ok(Value::Bool(expect_bool(b.eval(scope)?, "'&&'")?))
+            }
+            Expr::Or(a, b) => {
+                if expect_bool(a.eval(scope)?, "'||'")? {
+                    return Ok(Value::Bool(true));
+                }
+                Ok(Value::Bool(expect_bool(b.eval(scope)?, "'||'")?))
+            }
+            Expr::Cmp(op, a, b) => {
+                let (l, r) = (a.eval(scope)?, b.eval(scope)?);
+                let result = match op {
+                    CmpOp::Eq => values_equal(&l, &r),
+                    CmpOp::Ne => !values_equal(&l, &r),
+                    CmpOp::In => contains(&r, &l)?,
+                    CmpOp::Lt | CmpOp::Le | CmpOp::Gt | CmpOp::Ge => {
+                        let ordering = match (&l, &r) {
+                            (Value::Number(x), Value::Number(y)) => x
+                                .as_f64()
+                                .zip(y.as_f64())
+                                .and_then(|(x, y)| x.partial_cmp(&y)),
+                            (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+                            _ => None,
+                        }
+                        .ok_or_else(|| {
+                            ExpressionError::Type(format!(
+                                "cannot order {} and {}",
+                                type_name(&l),
+                                type_name(&r)
+                            ))
+                        })?;
+                        match op {
+                            CmpOp::Lt => ordering.is_lt(),
+                            CmpOp::Le => ordering.is_le(),
+                            CmpOp::Gt => ordering.is_gt(),
+                            _ => ordering.is_ge(),
+                        }
+                    }
+                };
+                Ok(Value::Bool(result))
+            }
+            Expr::Match {
+                value,
+                regex,
+                negate,
+            } => {
+                let text = match value.eval(scope)? {
+                    Value::String(s) => s,
+                    v @ (Value::Number(_) | Value::Bool(_)) => v.to_string(),
+                    other => {
+                        return Err(ExpressionError::Type(format!(
+                            "regex match expects a string, got {}",
+                            type_name(&other)
+                        )))
+                    }
+                };
+                Ok(Value::Bool(regex.is_match(&text) != *negate))
+            }
+            Expr::Call(func, args) => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(arg.eval(scope)?);
+                }
+                match func {
+                    Func::Exists => Ok(Value::Bool(!values[0].is_null())),
+                    Func::Len => match &values[0] {
+                        Value::String(s) => Ok(Value::from(s.chars().count())),
+                        Value::Array(a) => Ok(Value::from(a.len())),
+                        Value::Object(o) => Ok(Value::from(o.len())),
+                        Value::Null => Ok(Value::from(0)),
+                        other => Err(ExpressionError::Type(format!(
+                            "len() expects a string, array or object, got {}",
+                            type_name(other)
+                        ))),
+                    },
+                    Func::Contains => Ok(Value::Bool(contains(&values[0], &values[1])?)),
+                    Func::Lower => match &values[0] {
+                        Value::String(s) => Ok(Value::String(s.to_lowercase())),
+                        other => Err(ExpressionError::Type(format!(
+                            "lower() expects a string, got {}",
+                            type_name(other)
+                        ))),
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn resolve(root: Root, segments: &[Segment], scope: &Scope<'_>) -> Value {
+    match root {
+        Root::Last => descend(
+            scope.history.last().cloned().unwrap_or(Value::Null),
+            segments,
+        ),
+        Root::Vars => descend(
+            Value::Object(
+                scope
+                    .vars
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ),
+            segments,
+        ),
+        Root::Iteration => scope.iteration.map(Value::from).unwrap_or(Value::Null),
+        Root::Steps => match segments.split_first() {
+            None => Value::Array(scope.history.to_vec()),
+            Some((Segment::Index(i), rest)) => descend(
+                index(scope.history, *i).cloned().unwrap_or(Value::Null),
+                rest,
+            ),
+            Some((Segment::Key(id), rest)) => descend(
+                scope
+                    .history
+                    .iter()
+                    .rev()
+                    .find(|s| s.get("id").and_then(Value::as_str) == Some(id.as_str()))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                rest,
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> Vec<Value> {
+        vec![
+            serde_json::json!({
+                "id": "00000000-0000-0000-0000-000000000001",
+                "status": "Completed",
+                "output": {"results": [{"text": "a"}, {"text": "b"}]},
+                "error": null,
+                "exit_code": 0,
+                "retry_count": 0,
+            }),
+            serde_json::json!({
+                "id": "00000000-0000-0000-0000-000000000002",
+                "status": "Completed",
+                "output": {"result": "OK: 3 files", "metadata": {"exit_code": "2"}},
+                "error": null,
+                "exit_code": 2,
+                "retry_count": 1,
+            }),
+        ]
+    }
+
+    fn eval(src: &str) -> Result<bool, ExpressionError> {
+        let history = history();
+        let mut vars = HashMap::new();
+        vars.insert("force".to_string(), Value::Bool(true));
+        let scope = Scope::new(&history, &vars).with_iteration(2);
+        Expression::parse(src)?.evaluate_bool(&scope)
+    }
+
+    #[test]
+    fn evaluates_comparisons_paths_and_regex() {
+        assert!(eval("last.exit_code == 2 && last.output.result =~ \"^OK: \\d+\"").unwrap());
+        assert!(eval("len(steps[0].output.results) >= 2 and vars.force").unwrap());
+        assert!(eval("steps[-1].retry_count > 0 || false").unwrap());
+        assert!(eval(
+            "steps[\"00000000-0000-0000-0000-000000000001\"].output.results[1].text == 'b'"
+        )
+        .unwrap());
+        assert!(eval("!exists(last.output.missing) && iteration < 3").unwrap());
+        assert!(eval("'files' in last.output.result && last.status != 'Failed'").unwrap());
+    }
+
+    #[test]
+    fn reports_clear_errors() {
+        assert!(matches!(
+            Expression::parse("last.status = 'ok'"),
+            Err(ExpressionError::Syntax { position: 13, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("foo == 1"),
+            Err(ExpressionError::UnknownIdentifier(_))
+        ));
+        assert!(matches!(
+            Expression::parse("last.output =~ \"(\""),
+            Err(ExpressionError::InvalidRegex { .. })
+        ));
+        assert!(matches!(
+            Expression::parse(&"(".repeat(MAX_EXPRESSION_DEPTH + 1)),
+            Err(ExpressionError::TooDeep)
+        ));
+        assert!(matches!(
+            eval("last.exit_code"),
+            Err(ExpressionError::Type(_))
+        ));
+        assert!(matches!(
+            eval("last.output < 3"),
+            Err(ExpressionError::Type(_))
+        ));
+    }
+
+    #[test]
+    fn validate_checks_step_references() {
+        let known: HashSet<Uuid> = [Uuid::from_u128(1)].into_iter().collect();
+        assert!(Expression::validate(
+            "steps[\"00000000-0000-0000-0000-000000000001\"].exit_code == 0",
+            &known
+        )
+        .is_ok());
+        assert!(matches!(
+            Expression::validate("steps[\"nope\"].exit_code == 0", &known),
+            Err(ExpressionError::UnknownStep(_))
+        ));
+    }
+}
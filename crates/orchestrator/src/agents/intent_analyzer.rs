@@ -268,6 +268,7 @@ impl IntentAnalyzer {
                 stream: false,
                 temperature: Some(0.1),
                 max_tokens: Some(200),
+                tools: Vec::new(),
             };
 
             match provider.complete(request).await {
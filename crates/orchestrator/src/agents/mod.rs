@@ -1,21 +1,43 @@
 // Agents module - Multi-Agent System Components
 // Following ARCHITECTURE_PLAN_ADVANCED.md multi-agent orchestration requirements
 
+pub mod agent_loop;
 pub mod critic;
+pub mod cron;
+pub mod dag;
+pub mod delegation;
 pub mod executor;
+pub mod expression;
 pub mod intent_analyzer;
 pub mod planner;
 pub mod scheduler;
+pub mod tool_bridge;
+pub mod workflow_file;
 
-pub use critic::Critic;
-pub use executor::Executor;
+pub use agent_loop::{AgentLoop, AgentLoopConfig, AgentLoopReport, LoopIteration, LoopOutcome};
+pub use critic::{Critic, GoalAssessment};
+pub use cron::{CronError, CronExpression, CronTimeZone};
+pub use dag::{is_read_only_tool, StepAccess};
+pub use delegation::{AgentProfile, AgentProfiles, Delegator, SubAgentFactory, SubAgentResult};
+pub use executor::{Executor, StepError, STEP_TIMEOUT_PARAM};
+pub use expression::{Expression, ExpressionError};
 pub use intent_analyzer::IntentAnalyzer;
-pub use planner::Planner;
-pub use scheduler::Scheduler;
+pub use planner::{Planner, StepObservation, DELEGATE_PARAM};
+pub use scheduler::{
+    MissedRunPolicy, ScheduleFn, ScheduledPayload, ScheduledTask, Scheduler, TaskSchedule,
+    TaskStatus, TaskType,
+};
+pub use tool_bridge::{
+    register_registry_tools, register_secure_registry_tools, MemoryApiInvoker, MemoryApiUndoTarget,
+    RegistryToolInvoker, SecureRegistryToolInvoker,
+};
+pub use workflow_file::{CompiledWorkflow, WorkflowFile};
 
 // Re-export agent traits
 pub use critic::CriticTrait;
-pub use executor::ExecutorTrait;
+pub use executor::{
+    DelegationHandler, ExecutorTrait, InteractionHandler, MemoryInvoker, ToolInvoker,
+};
 pub use intent_analyzer::IntentAnalyzerTrait;
 pub use planner::PlannerTrait;
 pub use scheduler::SchedulerTrait;
@@ -2,14 +2,22 @@ use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use super::critic::{CriticFeedback, ImprovementCategory};
+use super::dag::{infer_dependencies, max_parallelism};
+use super::executor::{StepStatus, STEP_TIMEOUT_PARAM};
+use super::expression::{Expression, MAX_LOOP_ITERATIONS};
 use super::intent_analyzer::{Intent, IntentType};
 
+/// Intent parameter naming the agent profile the whole intent is delegated to
+/// (the task text comes from `parameters["task"]` or the latest message)
+pub const DELEGATE_PARAM: &str = "delegate_to";
+
 // Tool Context Builder integration for intelligent tool selection
 use tools::context::{
     ContextBuildingConfig, ToolContextBuilder, ToolRankingResult, ToolSelectionRequest,
@@ -76,6 +84,9 @@ pub enum ActionStepType {
     },
     /// Wait/delay step
     Wait { duration: std::time::Duration },
+    /// Hand a sub-task to a user-defined agent profile; the step's output is
+    /// the sub-agent's summary
+    Delegate { profile: String, task: String },
 }
 
 /// Memory operation types
@@ -170,6 +181,42 @@ pub trait PlannerTrait: Send + Sync {
 
     /// Check tool availability for plan
     async fn check_tool_availability(&self, plan: &ActionPlan) -> Result<ToolAvailabilityReport>;
+
+    /// Revise the not yet completed steps of `plan` from what happened so far.
+    /// `feedback` is the Critic's verdict between agent loop iterations and
+    /// `None` after a single step.
+    async fn replan(
+        &self,
+        plan: &ActionPlan,
+        observations: &[StepObservation],
+        feedback: Option<&CriticFeedback>,
+    ) -> Result<ActionPlan>;
+}
+
+/// A failed step is re-planned at most this many times in total
+pub const MAX_STEP_ATTEMPTS: u32 = 3;
+
+/// One attempt of a step in an agent loop, fed back to [`PlannerTrait::replan`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepObservation {
+    pub step_id: Uuid,
+    /// Loop iteration (1-based)
+    pub iteration: u32,
+    /// Attempt of this step across iterations (1-based)
+    pub attempt: u32,
+    /// Tool of a `ToolExecution` step
+    pub tool: Option<String>,
+    pub status: StepStatus,
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl StepObservation {
+    fn timed_out(&self) -> bool {
+        self.error
+            .as_deref()
+            .is_some_and(|error| error.contains("timed out"))
+    }
 }
 
 /// Result of plan validation
@@ -198,6 +245,7 @@ pub enum PlanValidationErrorType {
     ResourceConflict,
     InvalidParameters,
     UnreachableStep,
+    InvalidExpression,
 }
 
 /// Tool availability report
@@ -230,6 +278,7 @@ pub enum ToolHealth {
 pub struct Planner {
     agent_id: Uuid,
     available_tools: HashMap<String, ToolStatus>,
+    agent_profiles: HashSet<String>,
     planning_strategies: HashMap<IntentType, PlanningStrategy>,
     // Intelligent tool selection system
     tool_context_builder: Option<Arc<ToolContextBuilder>>,
@@ -271,6 +320,7 @@ impl Planner {
         Self {
             agent_id: Uuid::new_v4(),
             available_tools: HashMap::new(),
+            agent_profiles: HashSet::new(),
             planning_strategies: strategies,
             tool_context_builder: None,
             tool_registry: None,
@@ -302,6 +352,7 @@ impl Planner {
         Ok(Self {
             agent_id: Uuid::new_v4(),
             available_tools: HashMap::new(),
+            agent_profiles: HashSet::new(),
             planning_strategies: strategies,
             tool_context_builder: Some(tool_context_builder),
             tool_registry: Some(tool_registry),
@@ -312,6 +363,26 @@ impl Planner {
         })
     }
 
+    /// Mark a tool as available so `validate_plan` accepts steps using it
+    /// (e.g. every tool registered in the Executor)
+    pub fn register_available_tool(&mut self, tool_name: &str) {
+        self.available_tools.insert(
+            tool_name.to_string(),
+            ToolStatus {
+                available: true,
+                version: "1.0.0".to_string(),
+                last_check: Utc::now(),
+                health: ToolHealth::Healthy,
+            },
+        );
+    }
+
+    /// Mark an agent profile as known so `validate_plan` accepts steps
+    /// delegating to it
+    pub fn register_agent_profile(&mut self, profile: &str) {
+        self.agent_profiles.insert(profile.to_string());
+    }
+
     /// Check if intelligent tool selection is available
     pub fn has_intelligent_tool_selection(&self) -> bool {
         self.tool_context_builder.is_some()
@@ -351,6 +422,37 @@ impl Planner {
         );
     }
 
+    /// Double a step's timeout (or its expected duration when it has none)
+    fn extend_timeout(step: &mut ActionStep) {
+        let current = step
+            .parameters
+            .get(STEP_TIMEOUT_PARAM)
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(step.expected_duration.as_millis() as u64)
+            .max(1000);
+        step.parameters.insert(
+            STEP_TIMEOUT_PARAM.to_string(),
+            serde_json::json!(current * 2),
+        );
+    }
+
+    /// Turn the written order into explicit dependencies between steps that
+    /// touch the same paths, and allow as many concurrent steps as the widest
+    /// layer of the resulting graph (bounded by the available cores)
+    fn link_steps(plan: &mut ActionPlan) {
+        infer_dependencies(&mut plan.steps);
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+        plan.resource_requirements.cpu_cores =
+            max_parallelism(&plan.steps).clamp(1, cores.max(1)) as u32;
+    }
+
+    fn tool_name(step: &ActionStep) -> Option<&str> {
+        match &step.step_type {
+            ActionStepType::ToolExecution { tool_name, .. } => Some(tool_name),
+            _ => None,
+        }
+    }
+
     /// Create default retry policy
     fn default_retry_policy() -> RetryPolicy {
         RetryPolicy {
@@ -680,6 +782,43 @@ impl Planner {
         })
     }
 
+    /// Plan that hands the whole intent to a sub-agent; delegated runs are
+    /// not retried since each one spends its own budget
+    fn plan_delegation(&self, intent: &Intent, profile: &str) -> ActionPlan {
+        let task = intent
+            .parameters
+            .get("task")
+            .and_then(|task| task.as_str())
+            .map(str::to_string)
+            .or_else(|| intent.context.conversation_history.last().cloned())
+            .unwrap_or_default();
+        let step = ActionStep {
+            id: Uuid::new_v4(),
+            step_type: ActionStepType::Delegate {
+                profile: profile.to_string(),
+                task,
+            },
+            parameters: HashMap::new(),
+            dependencies: vec![],
+            expected_duration: std::time::Duration::from_secs(60),
+            retry_policy: RetryPolicy {
+                max_retries: 0,
+                ..Self::default_retry_policy()
+            },
+            validation_rules: vec![],
+        };
+
+        ActionPlan {
+            id: Uuid::new_v4(),
+            intent_id: intent.id,
+            steps: vec![step],
+            estimated_duration: std::time::Duration::from_secs(60),
+            resource_requirements: Self::default_resource_requirements(),
+            dependencies: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
     /// Plan for question intent
     fn plan_question(&self, intent: &Intent, question: &str) -> Result<ActionPlan> {
         // Simple question answering plan
@@ -708,6 +847,144 @@ impl Planner {
     }
 }
 
+fn collect_step_ids(steps: &[ActionStep], ids: &mut HashSet<Uuid>) {
+    for step in steps {
+        ids.insert(step.id);
+        match &step.step_type {
+            ActionStepType::Conditional {
+                then_steps,
+                else_steps,
+                ..
+            } => {
+                collect_step_ids(then_steps, ids);
+                collect_step_ids(else_steps, ids);
+            }
+            ActionStepType::Loop { body_steps, .. } => collect_step_ids(body_steps, ids),
+            _ => {}
+        }
+    }
+}
+
+/// Check every Conditional/Loop condition (syntax, regexes, step references)
+/// and loop `max_iterations` bounds, including nested steps
+pub fn validate_control_flow(steps: &[ActionStep]) -> Vec<PlanValidationError> {
+    fn walk(
+        steps: &[ActionStep],
+        known_steps: &HashSet<Uuid>,
+        errors: &mut Vec<PlanValidationError>,
+    ) {
+        for step in steps {
+            let (condition, nested): (&str, Vec<&[ActionStep]>) = match &step.step_type {
+                ActionStepType::Conditional {
+                    condition,
+                    then_steps,
+                    else_steps,
+                } => (
+                    condition,
+                    vec![then_steps.as_slice(), else_steps.as_slice()],
+                ),
+                ActionStepType::Loop {
+                    condition,
+                    body_steps,
+                    max_iterations,
+                } => {
+                    if *max_iterations == 0 || *max_iterations > MAX_LOOP_ITERATIONS {
+                        errors.push(PlanValidationError {
+                            step_id: Some(step.id),
+                            error_type: PlanValidationErrorType::InvalidParameters,
+                            message: format!(
+                                "Loop max_iterations must be between 1 and {}, got {}",
+                                MAX_LOOP_ITERATIONS, max_iterations
+                            ),
+                        });
+                    }
+                    (condition, vec![body_steps.as_slice()])
+                }
+                _ => continue,
+            };
+            if let Err(e) = Expression::validate(condition, known_steps) {
+                errors.push(PlanValidationError {
+                    step_id: Some(step.id),
+                    error_type: PlanValidationErrorType::InvalidExpression,
+                    message: format!("Invalid condition '{}': {}", condition, e),
+                });
+            }
+            for body in nested {
+                walk(body, known_steps, errors);
+            }
+        }
+    }
+
+    let mut known_steps = HashSet::new();
+    collect_step_ids(steps, &mut known_steps);
+    let mut errors = Vec::new();
+    walk(steps, &known_steps, &mut errors);
+    errors
+}
+
+/// Check that top-level step dependencies point at steps of the plan and
+/// contain no cycles
+pub fn validate_dependencies(steps: &[ActionStep]) -> Vec<PlanValidationError> {
+    let known: HashSet<Uuid> = steps.iter().map(|step| step.id).collect();
+    let mut errors = Vec::new();
+
+    for step in steps {
+        for dependency in &step.dependencies {
+            if !known.contains(dependency) {
+                errors.push(PlanValidationError {
+                    step_id: Some(step.id),
+                    error_type: PlanValidationErrorType::UnreachableStep,
+                    message: format!("Step depends on unknown step {}", dependency),
+                });
+            }
+        }
+    }
+
+    // Kahn's algorithm: whatever can't be ordered is part of (or waits on) a cycle
+    let mut pending: HashMap<Uuid, usize> = steps
+        .iter()
+        .map(|step| {
+            let count = step
+                .dependencies
+                .iter()
+                .filter(|dependency| known.contains(dependency))
+                .count();
+            (step.id, count)
+        })
+        .collect();
+    let mut ready: Vec<Uuid> = pending
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    while let Some(done) = ready.pop() {
+        pending.remove(&done);
+        for step in steps {
+            if step.dependencies.contains(&done) {
+                if let Some(count) = pending.get_mut(&step.id) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(step.id);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(step) = steps.iter().find(|step| pending.contains_key(&step.id)) {
+        errors.push(PlanValidationError {
+            step_id: Some(step.id),
+            error_type: PlanValidationErrorType::CircularDependency,
+            message: format!(
+                "Circular dependency between {} step(s), starting at step {}",
+                pending.len(),
+                step.id
+            ),
+        });
+    }
+    errors
+}
+
 #[async_trait]
 impl PlannerTrait for Planner {
     async fn build_plan(&self, intent: &Intent) -> Result<ActionPlan> {
@@ -719,7 +996,15 @@ impl PlannerTrait for Planner {
             *plans_created += 1;
         }
 
-        let plan = match &intent.intent_type {
+        if let Some(profile) = intent
+            .parameters
+            .get(DELEGATE_PARAM)
+            .and_then(|profile| profile.as_str())
+        {
+            return Ok(self.plan_delegation(intent, profile));
+        }
+
+        let mut plan = match &intent.intent_type {
             IntentType::ExecuteTool { tool_name } => {
                 if self.has_intelligent_tool_selection() {
                     self.plan_tool_execution_with_intelligence(intent, tool_name)
@@ -814,6 +1099,7 @@ impl PlannerTrait for Planner {
             }
         };
 
+        Self::link_steps(&mut plan);
         tracing::debug!("Created plan {} with {} steps", plan.id, plan.steps.len());
 
         Ok(plan)
@@ -823,22 +1109,44 @@ impl PlannerTrait for Planner {
         let mut errors = vec![];
         let mut warnings = vec![];
 
-        // Check for circular dependencies
-        // TODO: Implement proper dependency cycle detection
+        // Check for unknown and circular dependencies
+        errors.extend(validate_dependencies(&plan.steps));
 
-        // Check tool availability
-        for step in &plan.steps {
-            if let ActionStepType::ToolExecution { tool_name, .. } = &step.step_type {
-                if !self.available_tools.contains_key(tool_name) {
+        // Check tool availability, including steps nested in Conditional/Loop
+        let mut pending: Vec<&ActionStep> = plan.steps.iter().collect();
+        while let Some(step) = pending.pop() {
+            match &step.step_type {
+                ActionStepType::ToolExecution { tool_name, .. }
+                    if !self.available_tools.contains_key(tool_name) =>
+                {
                     errors.push(PlanValidationError {
                         step_id: Some(step.id),
                         error_type: PlanValidationErrorType::MissingTool,
                         message: format!("Tool '{}' is not available", tool_name),
                     });
                 }
+                ActionStepType::Conditional {
+                    then_steps,
+                    else_steps,
+                    ..
+                } => pending.extend(then_steps.iter().chain(else_steps)),
+                ActionStepType::Loop { body_steps, .. } => pending.extend(body_steps),
+                ActionStepType::Delegate { profile, .. }
+                    if !self.agent_profiles.contains(profile) =>
+                {
+                    errors.push(PlanValidationError {
+                        step_id: Some(step.id),
+                        error_type: PlanValidationErrorType::InvalidParameters,
+                        message: format!("Agent profile '{}' is not defined", profile),
+                    });
+                }
+                _ => {}
             }
         }
 
+        // Check Conditional/Loop expressions and loop guards
+        errors.extend(validate_control_flow(&plan.steps));
+
         // Check resource requirements
         if plan.resource_requirements.memory_mb > 1024 {
             warnings.push("Plan requires significant memory usage".to_string());
@@ -856,18 +1164,10 @@ impl PlannerTrait for Planner {
     }
 
     async fn optimize_plan(&mut self, plan: ActionPlan) -> Result<ActionPlan> {
-        // Basic optimization: parallel execution where possible
-        // TODO: Implement proper dependency analysis and parallelization
-
         tracing::debug!("Optimizing plan {}", plan.id);
 
-        // For now, just return the plan as-is
-        // In a real implementation, we would:
-        // - Analyze step dependencies
-        // - Group independent steps for parallel execution
-        // - Optimize resource usage
-        // - Cache intermediate results
-
+        let mut plan = plan;
+        Self::link_steps(&mut plan);
         Ok(plan)
     }
 
@@ -910,6 +1210,108 @@ impl PlannerTrait for Planner {
             tool_status,
         })
     }
+
+    async fn replan(
+        &self,
+        plan: &ActionPlan,
+        observations: &[StepObservation],
+        feedback: Option<&CriticFeedback>,
+    ) -> Result<ActionPlan> {
+        let mut latest: HashMap<Uuid, &StepObservation> = HashMap::new();
+        for observation in observations {
+            latest.insert(observation.step_id, observation);
+        }
+        let status = |id: &Uuid| latest.get(id).map(|observation| &observation.status);
+        // Tools that timed out get more time in the steps still to run
+        let timed_out_tools: HashSet<&str> = latest
+            .values()
+            .filter(|observation| observation.timed_out())
+            .filter_map(|observation| observation.tool.as_deref())
+            .collect();
+        // Between iterations the Critic decides whether failures deserve more retries
+        let retry_more = feedback.is_some_and(|feedback| {
+            feedback.improvement_suggestions.iter().any(|suggestion| {
+                matches!(
+                    suggestion.category,
+                    ImprovementCategory::ErrorHandling | ImprovementCategory::Reliability
+                )
+            })
+        });
+
+        let mut kept: Vec<ActionStep> = Vec::new();
+        let mut kept_ids: HashSet<Uuid> = HashSet::new();
+        let mut dropped: HashSet<Uuid> = HashSet::new();
+        for step in &plan.steps {
+            if status(&step.id) == Some(&StepStatus::Completed) {
+                continue;
+            }
+            let mut step = step.clone();
+            if let Some(observation) = latest.get(&step.id) {
+                if observation.status == StepStatus::Failed {
+                    if observation.attempt >= MAX_STEP_ATTEMPTS {
+                        tracing::info!(
+                            step_id = %step.id,
+                            attempts = observation.attempt,
+                            "Dropping step that keeps failing"
+                        );
+                        dropped.insert(step.id);
+                        continue;
+                    }
+                    if observation.timed_out() {
+                        Self::extend_timeout(&mut step);
+                    } else if retry_more {
+                        step.retry_policy.max_retries += 1;
+                        if !step
+                            .retry_policy
+                            .retry_conditions
+                            .contains(&RetryCondition::TemporaryFailure)
+                        {
+                            step.retry_policy
+                                .retry_conditions
+                                .push(RetryCondition::TemporaryFailure);
+                        }
+                    }
+                }
+            } else if Self::tool_name(&step).is_some_and(|tool| timed_out_tools.contains(tool)) {
+                Self::extend_timeout(&mut step);
+            }
+
+            // A dependency that failed or was dropped and is not re-planned
+            // blocks the step
+            let blocked = step.dependencies.iter().any(|dependency| {
+                !kept_ids.contains(dependency)
+                    && (dropped.contains(dependency)
+                        || matches!(
+                            status(dependency),
+                            Some(StepStatus::Failed | StepStatus::Skipped)
+                        ))
+            });
+            if blocked {
+                dropped.insert(step.id);
+                continue;
+            }
+            kept_ids.insert(step.id);
+            kept.push(step);
+        }
+
+        let revision = plan
+            .metadata
+            .get("revision")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(0)
+            + 1;
+        let mut metadata = plan.metadata.clone();
+        metadata.insert("revision".to_string(), serde_json::json!(revision));
+        Ok(ActionPlan {
+            id: plan.id,
+            intent_id: plan.intent_id,
+            estimated_duration: kept.iter().map(|step| step.expected_duration).sum(),
+            steps: kept,
+            resource_requirements: plan.resource_requirements.clone(),
+            dependencies: plan.dependencies.clone(),
+            metadata,
+        })
+    }
 }
 
 impl Default for Planner {
@@ -1099,6 +1501,46 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_validate_plan_dependencies() {
+        let mut planner = Planner::new();
+        planner.register_available_tool("test_tool");
+        let intent = create_test_intent(IntentType::ExecuteTool {
+            tool_name: "test_tool".to_string(),
+        });
+
+        let mut plan = planner
+            .build_plan(&intent)
+            .await
+            .expect("Async operation should succeed");
+        let validation = planner
+            .validate_plan(&plan)
+            .await
+            .expect("Async operation should succeed");
+        assert!(validation.is_valid, "{:?}", validation.errors);
+
+        let mut second = plan.steps[0].clone();
+        second.id = Uuid::new_v4();
+        second.dependencies = vec![plan.steps[0].id];
+        plan.steps[0].dependencies = vec![second.id];
+        plan.steps.push(second);
+
+        let errors = validate_dependencies(&plan.steps);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].error_type,
+            PlanValidationErrorType::CircularDependency
+        );
+
+        plan.steps[1].dependencies = vec![Uuid::new_v4()];
+        let errors = validate_dependencies(&plan.steps);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].error_type,
+            PlanValidationErrorType::UnreachableStep
+        );
+    }
+
     #[tokio::test]
     async fn test_check_tool_availability() {
         let planner = Planner::new();
@@ -1119,6 +1561,103 @@ mod tests {
         assert_eq!(report.unavailable_tools[0], "test_tool");
         assert!(!report.tool_status["test_tool"].available);
     }
+
+    #[tokio::test]
+    async fn test_optimize_plan_links_conflicting_steps() {
+        let mut planner = Planner::new();
+        let intent = create_test_intent(IntentType::FileOperation {
+            operation: "read".to_string(),
+            path: "notes.md".to_string(),
+        });
+        let mut plan = planner
+            .build_plan(&intent)
+            .await
+            .expect("Async operation should succeed");
+        let read = plan.steps[0].clone();
+        let mut other_read = read.clone();
+        other_read.id = Uuid::new_v4();
+        other_read.step_type = ActionStepType::ToolExecution {
+            tool_name: "file_read".to_string(),
+            arguments: HashMap::from([("path".to_string(), serde_json::json!("todo.md"))]),
+        };
+        let mut write = read.clone();
+        write.id = Uuid::new_v4();
+        write.step_type = ActionStepType::ToolExecution {
+            tool_name: "file_write".to_string(),
+            arguments: HashMap::from([("path".to_string(), serde_json::json!("notes.md"))]),
+        };
+        plan.steps = vec![read.clone(), other_read.clone(), write];
+
+        let plan = planner
+            .optimize_plan(plan)
+            .await
+            .expect("Async operation should succeed");
+
+        assert!(plan.steps[0].dependencies.is_empty());
+        assert!(plan.steps[1].dependencies.is_empty());
+        assert_eq!(plan.steps[2].dependencies, vec![read.id]);
+        assert!(plan.resource_requirements.cpu_cores >= 1);
+        assert!(validate_dependencies(&plan.steps).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replan_from_observations() {
+        let planner = Planner::new();
+        let intent = create_test_intent(IntentType::ExecuteTool {
+            tool_name: "slow_tool".to_string(),
+        });
+        let mut plan = planner
+            .build_plan(&intent)
+            .await
+            .expect("Async operation should succeed");
+
+        // slow (timed out), slow again (not run yet), broken, dependent on broken
+        let slow = plan.steps[0].clone();
+        let mut slow_again = slow.clone();
+        slow_again.id = Uuid::new_v4();
+        let mut broken = slow.clone();
+        broken.id = Uuid::new_v4();
+        broken.step_type = ActionStepType::ToolExecution {
+            tool_name: "broken_tool".to_string(),
+            arguments: HashMap::new(),
+        };
+        let mut dependent = slow.clone();
+        dependent.id = Uuid::new_v4();
+        dependent.dependencies = vec![broken.id];
+        plan.steps = vec![slow.clone(), slow_again.clone(), broken.clone(), dependent];
+
+        let observation =
+            |step: &ActionStep, tool: &str, attempt: u32, error: &str| StepObservation {
+                step_id: step.id,
+                iteration: attempt,
+                attempt,
+                tool: Some(tool.to_string()),
+                status: StepStatus::Failed,
+                output: None,
+                error: Some(error.to_string()),
+            };
+        let observations = vec![
+            observation(&slow, "slow_tool", 1, "Step timed out after 1000ms"),
+            observation(&broken, "broken_tool", MAX_STEP_ATTEMPTS, "exit code 1"),
+        ];
+
+        let revised = planner
+            .replan(&plan, &observations, None)
+            .await
+            .expect("Async operation should succeed");
+
+        assert_eq!(revised.id, plan.id);
+        assert_eq!(revised.metadata["revision"], serde_json::json!(1));
+        let ids: Vec<Uuid> = revised.steps.iter().map(|step| step.id).collect();
+        assert_eq!(ids, vec![slow.id, slow_again.id]);
+        let expected_timeout = slow.expected_duration.as_millis().max(1000) as u64 * 2;
+        for step in &revised.steps {
+            assert_eq!(
+                step.parameters[STEP_TIMEOUT_PARAM],
+                serde_json::json!(expected_timeout)
+            );
+        }
+    }
 }
 
 /// BaseActor implementation for Planner
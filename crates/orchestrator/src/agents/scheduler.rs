@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -12,7 +12,11 @@ use tokio::time::Duration;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::executor::{ExecutionResult, ExecutorTrait};
+use super::cron::{CronExpression, CronTimeZone};
+use super::executor::{ExecutionResult, ExecutionStatus, ExecutorTrait};
+use super::planner::ActionPlan;
+use crate::persistence::WorkflowStore;
+use crate::workflow::WorkflowRequest;
 
 // Health monitoring integration
 use crate::reliability::health::{HealthChecker, HealthReport, HealthStatus};
@@ -32,6 +36,175 @@ pub struct ScheduledTask {
     pub retry_count: u32,
     pub max_retries: u32,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Random delay of up to this many seconds added to every computed run
+    /// time, so that many tasks on the same schedule do not fire at once
+    #[serde(default)]
+    pub jitter_secs: u64,
+    /// What to do with runs missed while no scheduler was running
+    #[serde(default)]
+    pub missed_runs: MissedRunPolicy,
+}
+
+/// Handling of runs whose time passed while the scheduler was down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Drop missed runs and wait for the next regular occurrence
+    #[default]
+    Skip,
+    /// Run once as soon as possible, then continue on schedule
+    CatchUp,
+}
+
+impl std::fmt::Display for MissedRunPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MissedRunPolicy::Skip => write!(f, "skip"),
+            MissedRunPolicy::CatchUp => write!(f, "catch-up"),
+        }
+    }
+}
+
+impl std::str::FromStr for MissedRunPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "skip" => Ok(MissedRunPolicy::Skip),
+            "catch-up" | "catch_up" | "catchup" => Ok(MissedRunPolicy::CatchUp),
+            other => anyhow::bail!("Unknown missed-run policy '{}' (skip, catch-up)", other),
+        }
+    }
+}
+
+/// What a scheduled task runs, stored in `ScheduledTask::payload`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledPayload {
+    /// Action plan run by the Executor (with saga compensation)
+    Plan(ActionPlan),
+    /// Full orchestrator workflow (intent → plan → execute → critique)
+    Workflow(WorkflowRequest),
+}
+
+fn parse_cron(expression: &str, timezone: Option<&str>) -> Result<CronExpression> {
+    let mut cron = CronExpression::parse(expression)
+        .with_context(|| format!("Invalid cron expression '{expression}'"))?;
+    if let Some(timezone) = timezone {
+        cron = cron.in_time_zone(CronTimeZone::parse(timezone)?);
+    }
+    Ok(cron)
+}
+
+fn with_jitter(at: DateTime<Utc>, jitter_secs: u64) -> DateTime<Utc> {
+    if jitter_secs == 0 {
+        return at;
+    }
+    let delay = rand::Rng::gen_range(&mut rand::thread_rng(), 0..=jitter_secs);
+    at + chrono::Duration::seconds(delay as i64)
+}
+
+impl ScheduledTask {
+    fn is_recurring(&self) -> bool {
+        matches!(
+            self.schedule,
+            TaskSchedule::Interval { .. } | TaskSchedule::Cron { .. }
+        )
+    }
+
+    /// Scheduled and its run time has come
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.status == TaskStatus::Scheduled && self.next_run_at.is_none_or(|next| now >= next)
+    }
+
+    /// First run time of a newly scheduled task
+    pub fn initial_run_at(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+        match &self.schedule {
+            TaskSchedule::Once { at } => Ok(Some(*at)),
+            TaskSchedule::Immediate => Ok(Some(now)),
+            TaskSchedule::Interval { .. } | TaskSchedule::Cron { .. } => self.next_run_after(now),
+        }
+    }
+
+    /// Next run strictly after `after` (jitter included); `None` for one-shot
+    /// schedules and cron expressions that never fire again
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+        let next = match &self.schedule {
+            TaskSchedule::Once { .. } | TaskSchedule::Immediate => None,
+            TaskSchedule::Interval { every } => Some(after + chrono::Duration::from_std(*every)?),
+            TaskSchedule::Cron {
+                expression,
+                timezone,
+            } => parse_cron(expression, timezone.as_deref())?.next_after(after),
+        };
+        Ok(next.map(|at| with_jitter(at, self.jitter_secs)))
+    }
+
+    /// Apply `missed_runs` to a task whose run time passed while no scheduler
+    /// was running. Returns `true` if a missed run was skipped. One-shot tasks
+    /// never ran, so they always run late rather than not at all.
+    pub fn apply_missed_run_policy(&mut self, now: DateTime<Utc>) -> Result<bool> {
+        let missed =
+            self.status == TaskStatus::Scheduled && self.next_run_at.is_some_and(|next| next < now);
+        if !missed || !self.is_recurring() || self.missed_runs == MissedRunPolicy::CatchUp {
+            return Ok(false);
+        }
+        self.next_run_at = self.next_run_after(now)?;
+        if self.next_run_at.is_none() {
+            self.status = TaskStatus::Completed;
+        }
+        Ok(true)
+    }
+
+    /// Record the outcome of a run started at `started_at`: recurring tasks
+    /// move on to their next occurrence, failures are retried with
+    /// exponential backoff up to `max_retries`
+    pub fn finish_run(
+        &mut self,
+        started_at: DateTime<Utc>,
+        outcome: std::result::Result<(), String>,
+    ) -> Result<()> {
+        let now = Utc::now();
+        self.last_run_at = Some(started_at);
+        self.metadata.insert(
+            "last_status".to_string(),
+            serde_json::json!(if outcome.is_ok() {
+                "succeeded"
+            } else {
+                "failed"
+            }),
+        );
+
+        match outcome {
+            Ok(()) => {
+                self.metadata.remove("last_error");
+                self.retry_count = 0;
+                self.status = TaskStatus::Completed;
+                if let Some(next_run) = self.next_run_after(now)? {
+                    self.next_run_at = Some(next_run);
+                    self.status = TaskStatus::Scheduled;
+                }
+            }
+            Err(error) => {
+                self.metadata
+                    .insert("last_error".to_string(), serde_json::json!(error));
+                self.retry_count += 1;
+                if self.retry_count < self.max_retries {
+                    let backoff_secs = 2_u64.pow(self.retry_count.min(6));
+                    self.next_run_at = Some(now + chrono::Duration::seconds(backoff_secs as i64));
+                    self.status = TaskStatus::Scheduled;
+                } else if let Some(next_run) = self.next_run_after(now)? {
+                    // Out of retries for this occurrence; try again next time
+                    self.retry_count = 0;
+                    self.next_run_at = Some(next_run);
+                    self.status = TaskStatus::Scheduled;
+                } else {
+                    self.status = TaskStatus::Failed;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Types of scheduled tasks
@@ -52,8 +225,13 @@ pub enum TaskSchedule {
     Immediate,
     /// Recurring interval
     Interval { every: StdDuration },
-    /// Cron-like schedule (simplified)
-    Cron { expression: String },
+    /// Cron expression (5 or 6 fields), see [`super::cron`]
+    Cron {
+        expression: String,
+        /// `UTC` (default), `local` or an IANA zone such as `Europe/Berlin`
+        #[serde(default)]
+        timezone: Option<String>,
+    },
 }
 
 /// Task execution status
@@ -137,9 +315,13 @@ pub enum Schedule {
     Immediate,
     /// Recurring interval
     Interval { every: StdDuration },
-    /// Cron-like schedule
-    Cron { expression: String },
-    /// Custom schedule
+    /// Cron expression (5 or 6 fields), see [`super::cron`]
+    Cron {
+        expression: String,
+        #[serde(default)]
+        timezone: Option<String>,
+    },
+    /// Schedule function registered with [`Scheduler::register_schedule_fn`]
     Custom { schedule_fn: String },
 }
 
@@ -284,6 +466,10 @@ pub struct SchedulerStatistics {
     pub jobs_per_priority: HashMap<JobPriority, u64>,
 }
 
+/// Named schedule function for `Schedule::Custom`: receives the last run time
+/// and returns the next one (`None` stops the job)
+pub type ScheduleFn = Arc<dyn Fn(Option<DateTime<Utc>>) -> Option<DateTime<Utc>> + Send + Sync>;
+
 /// Scheduler implementation with ExecutorTrait integration
 pub struct Scheduler {
     agent_id: uuid::Uuid,
@@ -296,6 +482,8 @@ pub struct Scheduler {
     status: SchedulerStatus,
     statistics: SchedulerStatistics,
     executor: Option<Arc<dyn ExecutorTrait>>,
+    store: Option<Arc<WorkflowStore>>,
+    schedule_fns: HashMap<String, ScheduleFn>,
     background_handle: Option<tokio::task::JoinHandle<()>>,
     shutdown_tx: Option<mpsc::UnboundedSender<()>>,
     // Health monitoring fields
@@ -330,6 +518,8 @@ impl Scheduler {
                 jobs_per_priority: HashMap::new(),
             },
             executor: None,
+            store: None,
+            schedule_fns: HashMap::new(),
             background_handle: None,
             shutdown_tx: None,
             // Health monitoring fields
@@ -384,6 +574,42 @@ impl Scheduler {
         scheduler
     }
 
+    /// Persist scheduled tasks in `store` so they survive restarts
+    pub fn with_store(mut self, store: Arc<WorkflowStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Register a schedule function for `Schedule::Custom { schedule_fn: name }`
+    pub fn register_schedule_fn(&mut self, name: impl Into<String>, schedule_fn: ScheduleFn) {
+        self.schedule_fns.insert(name.into(), schedule_fn);
+    }
+
+    /// Load persisted tasks, applying each task's missed-run policy.
+    /// Returns the number of tasks loaded.
+    pub async fn restore_tasks(&mut self) -> Result<usize> {
+        let Some(store) = self.store.clone() else {
+            return Ok(0);
+        };
+        let now = Utc::now();
+        let mut restored = 0;
+        let mut tasks = self.scheduled_tasks.write().await;
+        for mut task in store.scheduled_tasks()? {
+            if task.apply_missed_run_policy(now)? {
+                info!(
+                    "Skipped missed run of task {}, next run at {:?}",
+                    task.name, task.next_run_at
+                );
+                store.save_scheduled_task(&task)?;
+            }
+            tasks.insert(task.id, task);
+            restored += 1;
+        }
+        drop(tasks);
+        self.update_statistics();
+        Ok(restored)
+    }
+
     /// Start background task execution loop
     pub async fn start_background_execution(&mut self) -> Result<()> {
         if self.background_handle.is_some() {
@@ -394,6 +620,7 @@ impl Scheduler {
         let (shutdown_tx, mut shutdown_rx) = mpsc::unbounded_channel();
         let tasks = Arc::clone(&self.scheduled_tasks);
         let executor = self.executor.clone();
+        let store = self.store.clone();
 
         let handle = tokio::spawn(async move {
             info!("Starting background task execution loop");
@@ -403,7 +630,7 @@ impl Scheduler {
             loop {
                 tokio::select! {
                     _ = interval.tick() => {
-                        if let Err(e) = Self::execute_ready_tasks(&tasks, &executor).await {
+                        if let Err(e) = Self::execute_ready_tasks(&tasks, &executor, &store).await {
                             error!("Error executing ready tasks: {}", e);
                         }
                     }
@@ -443,7 +670,12 @@ impl Scheduler {
     async fn execute_ready_tasks(
         tasks: &Arc<RwLock<HashMap<Uuid, ScheduledTask>>>,
         executor: &Option<Arc<dyn ExecutorTrait>>,
+        store: &Option<Arc<WorkflowStore>>,
     ) -> Result<()> {
+        let Some(executor) = executor else {
+            return Ok(());
+        };
+
         let ready_tasks = {
             let tasks_read = tasks.read().await;
             tasks_read
@@ -454,110 +686,89 @@ impl Scheduler {
         };
 
         for mut task in ready_tasks {
-            if let Some(executor) = executor {
-                debug!("Executing scheduled task: {}", task.name);
-
-                match Self::execute_task_with_executor(&task, executor).await {
-                    Ok(_execution_result) => {
-                        task.status = TaskStatus::Completed;
-                        task.last_run_at = Some(chrono::Utc::now());
-                        debug!("Task {} completed successfully", task.name);
-
-                        // Reschedule if recurring
-                        if let Some(next_run) = Self::calculate_next_task_run(&task) {
-                            task.next_run_at = Some(next_run);
-                            task.status = TaskStatus::Scheduled;
-                            task.retry_count = 0;
-                        }
-                    }
-                    Err(e) => {
-                        task.retry_count += 1;
-                        error!("Task {} failed: {}", task.name, e);
-
-                        if task.retry_count < task.max_retries {
-                            // Schedule retry with exponential backoff
-                            let backoff_secs = 2_u64.pow(task.retry_count.min(6));
-                            task.next_run_at = Some(
-                                chrono::Utc::now() + chrono::Duration::seconds(backoff_secs as i64),
-                            );
-                            task.status = TaskStatus::Scheduled;
-                        } else {
-                            task.status = TaskStatus::Failed;
-                            error!(
-                                "Task {} failed after {} retries",
-                                task.name, task.max_retries
-                            );
-                        }
-                    }
+            if let Some(store) = store {
+                // Another scheduler on the same store may have taken it
+                if !store.claim_scheduled_task(&mut task, &TaskStatus::Scheduled)? {
+                    debug!("Scheduled task {} was claimed elsewhere", task.name);
+                    continue;
                 }
+            }
+            debug!("Executing scheduled task: {}", task.name);
+            let started_at = Utc::now();
+
+            let outcome = match Self::execute_task_with_executor(&task, executor).await {
+                Ok(_execution_result) => {
+                    debug!("Task {} completed successfully", task.name);
+                    Ok(())
+                }
+                Err(e) => {
+                    error!("Task {} failed: {}", task.name, e);
+                    Err(e.to_string())
+                }
+            };
+            task.finish_run(started_at, outcome)?;
+            if task.status == TaskStatus::Failed {
+                error!(
+                    "Task {} failed after {} retries",
+                    task.name, task.max_retries
+                );
+            }
 
-                // Update task in storage
-                let mut tasks_write = tasks.write().await;
-                tasks_write.insert(task.id, task);
+            if let Some(store) = store {
+                match store.finish_scheduled_task(&task) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        info!("Scheduled task {} was removed while running", task.name);
+                        tasks.write().await.remove(&task.id);
+                        continue;
+                    }
+                    Err(e) => warn!("Failed to persist scheduled task {}: {}", task.name, e),
+                }
             }
+
+            // Update task in storage
+            let mut tasks_write = tasks.write().await;
+            tasks_write.insert(task.id, task);
         }
 
         Ok(())
     }
 
-    /// Execute a scheduled task using ExecutorTrait
+    /// Execute a scheduled task's plan payload using ExecutorTrait
     async fn execute_task_with_executor(
         task: &ScheduledTask,
-        _executor: &Arc<dyn ExecutorTrait>,
+        executor: &Arc<dyn ExecutorTrait>,
     ) -> Result<ExecutionResult> {
-        // For simplicity, we'll execute the task payload as if it's a plan
-        // In a real implementation, this would depend on the task type
-        match &task.task_type {
-            TaskType::Immediate | TaskType::Once | TaskType::Interval | TaskType::Cron => {
-                // Create a dummy plan from task payload
-                // This is simplified - in reality you'd convert task to proper ActionPlan
-                debug!(
-                    "Executing task {} with payload: {}",
-                    task.name, task.payload
-                );
-
-                // For now, just return a successful result
-                Ok(ExecutionResult {
-                    plan_id: task.id,
-                    status: super::executor::ExecutionStatus::Completed,
-                    step_results: vec![],
-                    execution_time: StdDuration::from_millis(100),
-                    resource_usage: super::executor::ResourceUsage::default(),
-                    metadata: HashMap::new(),
-                    error: None,
-                })
+        let payload: ScheduledPayload = serde_json::from_value(task.payload.clone())
+            .with_context(|| format!("Task {} has no runnable payload", task.name))?;
+
+        match payload {
+            ScheduledPayload::Plan(plan) => {
+                debug!("Executing plan {} for task {}", plan.id, task.name);
+                let result = executor.execute_plan_with_saga(&plan).await?;
+                match result.status {
+                    ExecutionStatus::Completed => Ok(result),
+                    ref status => anyhow::bail!(
+                        "Plan {} finished with status {:?}: {}",
+                        plan.id,
+                        status,
+                        result
+                            .error
+                            .as_ref()
+                            .map_or("no error reported", |e| e.message.as_str())
+                    ),
+                }
             }
+            ScheduledPayload::Workflow(_) => anyhow::bail!(
+                "Task {} runs a workflow; workflows are run by the orchestrator, not the executor",
+                task.name
+            ),
         }
     }
 
     /// Check if task is ready to run
     fn is_task_ready(task: &ScheduledTask) -> bool {
-        if task.status != TaskStatus::Scheduled {
-            return false;
-        }
-
-        match task.next_run_at {
-            Some(next_run) => chrono::Utc::now() >= next_run,
-            None => true, // Immediate tasks
-        }
-    }
-
-    /// Calculate next run time for recurring tasks
-    fn calculate_next_task_run(task: &ScheduledTask) -> Option<chrono::DateTime<chrono::Utc>> {
-        match &task.schedule {
-            TaskSchedule::Once { .. } | TaskSchedule::Immediate => None, // One-time tasks
-            TaskSchedule::Interval { every } => {
-                let base = task.last_run_at.unwrap_or_else(chrono::Utc::now);
-                Some(
-                    base + chrono::Duration::from_std(*every)
-                        .unwrap_or_else(|_| chrono::Duration::seconds(0)),
-                )
-            }
-            TaskSchedule::Cron { .. } => {
-                // Simplified cron implementation
-                Some(chrono::Utc::now() + chrono::Duration::hours(1))
-            }
-        }
+        task.is_due(chrono::Utc::now())
     }
 
     /// Calculate next run time for recurring jobs
@@ -576,14 +787,23 @@ impl Scheduler {
                         .unwrap_or_else(|_| chrono::Duration::seconds(0)),
                 )
             }
-            Schedule::Cron { expression: _ } => {
-                // Simplified cron implementation
-                Some(chrono::Utc::now() + chrono::Duration::hours(1))
-            }
-            Schedule::Custom { schedule_fn: _ } => {
-                // Custom schedule function would be called here
-                Some(chrono::Utc::now() + chrono::Duration::minutes(30))
-            }
+            Schedule::Cron {
+                expression,
+                timezone,
+            } => match parse_cron(expression, timezone.as_deref()) {
+                Ok(cron) => cron.next_after(chrono::Utc::now()),
+                Err(e) => {
+                    warn!("Cannot schedule job: {:#}", e);
+                    None
+                }
+            },
+            Schedule::Custom { schedule_fn } => match self.schedule_fns.get(schedule_fn) {
+                Some(schedule_fn) => schedule_fn(last_run),
+                None => {
+                    warn!("Schedule function '{}' is not registered", schedule_fn);
+                    None
+                }
+            },
         }
     }
 
@@ -991,22 +1211,15 @@ impl SchedulerTrait for Scheduler {
     async fn schedule_task(&mut self, mut task: ScheduledTask) -> Result<()> {
         debug!("Scheduling task {}: {}", task.id, task.name);
 
-        // Calculate next run time
-        task.next_run_at = match &task.schedule {
-            TaskSchedule::Once { at } => Some(*at),
-            TaskSchedule::Immediate => Some(chrono::Utc::now()),
-            TaskSchedule::Interval { every } => {
-                Some(chrono::Utc::now() + chrono::Duration::from_std(*every)?)
-            }
-            TaskSchedule::Cron { .. } => {
-                // Simplified cron implementation
-                Some(chrono::Utc::now() + chrono::Duration::hours(1))
-            }
-        };
-
+        // Calculate next run time (also validates cron expressions)
+        task.next_run_at = task.initial_run_at(chrono::Utc::now())?;
         task.status = TaskStatus::Scheduled;
         task.created_at = chrono::Utc::now();
 
+        if let Some(store) = &self.store {
+            store.save_scheduled_task(&task)?;
+        }
+
         // Add to scheduled tasks
         let mut tasks = self.scheduled_tasks.write().await;
         tasks.insert(task.id, task);
@@ -1022,6 +1235,9 @@ impl SchedulerTrait for Scheduler {
             task.status = TaskStatus::Cancelled;
             debug!("Cancelled task {}", task_id);
             drop(tasks); // Explicitly drop the lock
+            if let Some(store) = &self.store {
+                store.delete_scheduled_task(task_id)?;
+            }
             self.update_statistics();
             Ok(())
         } else {
@@ -1041,8 +1257,9 @@ impl SchedulerTrait for Scheduler {
 
         let tasks = Arc::clone(&self.scheduled_tasks);
         let executor = self.executor.clone();
+        let store = self.store.clone();
 
-        Self::execute_ready_tasks(&tasks, &executor).await
+        Self::execute_ready_tasks(&tasks, &executor, &store).await
     }
 
     async fn pause_scheduler(&mut self) -> Result<()> {
@@ -1235,7 +1452,7 @@ impl HealthChecker for Scheduler {
 mod tests {
     use super::*;
     use crate::agents::executor::{ExecutionContext, ExecutionStatus, ResourceUsage, StepResult};
-    use crate::agents::{executor, planner};
+    use crate::agents::planner;
     use crate::saga::SagaStatus;
 
     fn create_test_job(priority: JobPriority) -> Job {
@@ -1273,6 +1490,8 @@ mod tests {
             retry_count: 0,
             max_retries: 3,
             metadata: HashMap::new(),
+            jitter_secs: 0,
+            missed_runs: MissedRunPolicy::Skip,
         }
     }
 
@@ -1721,6 +1940,7 @@ mod tests {
             TaskType::Cron,
             TaskSchedule::Cron {
                 expression: "0 */6 * * *".to_string(),
+                timezone: None,
             },
         );
         scheduler
@@ -1807,4 +2027,189 @@ mod tests {
             .expect("get_scheduler_status failed");
         assert_eq!(status, SchedulerStatus::Stopped);
     }
+
+    fn empty_plan() -> planner::ActionPlan {
+        planner::ActionPlan {
+            id: Uuid::new_v4(),
+            intent_id: Uuid::new_v4(),
+            steps: vec![],
+            estimated_duration: StdDuration::from_secs(1),
+            resource_requirements: planner::ResourceRequirements {
+                cpu_cores: 1,
+                memory_mb: 0,
+                disk_space_mb: 0,
+                network_required: false,
+                tools_required: vec![],
+                permissions_required: vec![],
+            },
+            dependencies: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_payload_runs_and_reschedules_cron_task() {
+        let store = Arc::new(
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()"),
+        );
+        let mut scheduler =
+            Scheduler::with_executor(Arc::new(MockExecutor)).with_store(Arc::clone(&store));
+
+        let mut task = create_test_scheduled_task(
+            "nightly",
+            TaskType::Cron,
+            TaskSchedule::Cron {
+                expression: "30 2 * * *".to_string(),
+                timezone: Some("Europe/Berlin".to_string()),
+            },
+        );
+        task.payload = serde_json::to_value(ScheduledPayload::Plan(empty_plan()))
+            .expect("Operation failed - converted from unwrap()");
+        let task_id = task.id;
+        scheduler
+            .schedule_task(task)
+            .await
+            .expect("schedule_task failed");
+
+        // Make the task due
+        scheduler
+            .scheduled_tasks
+            .write()
+            .await
+            .get_mut(&task_id)
+            .expect("task should be scheduled")
+            .next_run_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        scheduler
+            .execute_scheduled_tasks()
+            .await
+            .expect("execute_scheduled_tasks failed");
+
+        let stored = store
+            .find_scheduled_task("nightly")
+            .expect("Operation failed - converted from unwrap()")
+            .expect("task should be persisted");
+        assert_eq!(stored.status, TaskStatus::Scheduled);
+        assert_eq!(
+            stored.metadata.get("last_status"),
+            Some(&serde_json::json!("succeeded"))
+        );
+        assert!(stored.last_run_at.is_some());
+        assert!(stored.next_run_at.expect("next run should be set") > Utc::now());
+    }
+
+    #[test]
+    fn test_claimed_task_runs_once_and_removed_task_stays_removed() {
+        let store =
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()");
+        let mut task =
+            create_test_scheduled_task("nightly", TaskType::Immediate, TaskSchedule::Immediate);
+        task.status = TaskStatus::Scheduled;
+        store
+            .save_scheduled_task(&task)
+            .expect("Operation failed - converted from unwrap()");
+
+        let mut first = task.clone();
+        let mut second = task.clone();
+        assert!(store
+            .claim_scheduled_task(&mut first, &TaskStatus::Scheduled)
+            .expect("Operation failed - converted from unwrap()"));
+        assert_eq!(first.status, TaskStatus::Running);
+        assert!(!store
+            .claim_scheduled_task(&mut second, &TaskStatus::Scheduled)
+            .expect("Operation failed - converted from unwrap()"));
+        // Claimed by this live process: not stale
+        assert!(store
+            .release_stale_claims()
+            .expect("Operation failed - converted from unwrap()")
+            .is_empty());
+
+        store
+            .delete_scheduled_task(task.id)
+            .expect("Operation failed - converted from unwrap()");
+        first.status = TaskStatus::Completed;
+        assert!(!store
+            .finish_scheduled_task(&first)
+            .expect("Operation failed - converted from unwrap()"));
+        assert!(store
+            .scheduled_tasks()
+            .expect("Operation failed - converted from unwrap()")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_stale_claim_is_released() {
+        let store =
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()");
+        let mut task =
+            create_test_scheduled_task("nightly", TaskType::Immediate, TaskSchedule::Immediate);
+        task.status = TaskStatus::Running;
+        task.metadata
+            .insert("runner_pid".to_string(), serde_json::json!(u32::MAX - 1));
+        store
+            .save_scheduled_task(&task)
+            .expect("Operation failed - converted from unwrap()");
+
+        let released = store
+            .release_stale_claims()
+            .expect("Operation failed - converted from unwrap()");
+        assert_eq!(released.len(), 1);
+        let stored = store
+            .find_scheduled_task("nightly")
+            .expect("Operation failed - converted from unwrap()")
+            .expect("task should be persisted");
+        assert_eq!(stored.status, TaskStatus::Scheduled);
+        assert!(!stored.metadata.contains_key("runner_pid"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_tasks_applies_missed_run_policy() {
+        let store = Arc::new(
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()"),
+        );
+        let overdue = Utc::now() - chrono::Duration::hours(3);
+        for (name, policy) in [
+            ("skip", MissedRunPolicy::Skip),
+            ("catch_up", MissedRunPolicy::CatchUp),
+        ] {
+            let mut task = create_test_scheduled_task(
+                name,
+                TaskType::Cron,
+                TaskSchedule::Cron {
+                    expression: "0 * * * *".to_string(),
+                    timezone: None,
+                },
+            );
+            task.status = TaskStatus::Scheduled;
+            task.next_run_at = Some(overdue);
+            task.missed_runs = policy;
+            store
+                .save_scheduled_task(&task)
+                .expect("Operation failed - converted from unwrap()");
+        }
+
+        let mut scheduler = Scheduler::new().with_store(store);
+        assert_eq!(
+            scheduler
+                .restore_tasks()
+                .await
+                .expect("restore_tasks failed"),
+            2
+        );
+        let tasks = scheduler
+            .list_scheduled_tasks()
+            .await
+            .expect("list_scheduled_tasks failed");
+        let skipped = tasks
+            .iter()
+            .find(|t| t.name == "skip")
+            .expect("skip task restored");
+        let caught_up = tasks
+            .iter()
+            .find(|t| t.name == "catch_up")
+            .expect("catch_up task restored");
+        assert!(skipped.next_run_at.expect("next run should be set") > Utc::now());
+        assert!(!Scheduler::is_task_ready(skipped));
+        assert_eq!(caught_up.next_run_at, Some(overdue));
+        assert!(Scheduler::is_task_ready(caught_up));
+    }
 }
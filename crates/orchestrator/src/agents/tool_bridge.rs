@@ -0,0 +1,473 @@
+//! Adapters connecting the Executor to real tools and memory.
+//!
+//! Every invocation goes through [`ToolGate`], the same schema validation,
+//! policy precheck, approval/dry-run preview and `tool.invoked` telemetry path
+//! used by `magray tools run`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use common::{events, topics};
+use memory::api::{MemoryContext, SearchOptions, UnifiedMemoryAPI};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tools::invocation::ToolGate;
+use tools::registry::{SecureToolRegistry, SecurityContext};
+use tools::{ToolInput, ToolOutput, ToolRegistry, ToolSpec};
+use uuid::Uuid;
+
+use super::executor::{Executor, MemoryInvoker, StepError, ToolInvoker};
+use super::planner::MemoryOperationType;
+use crate::saga::MemoryUndoTarget;
+
+/// Planner arguments are JSON values, tools take strings
+fn stringify_args(args: &HashMap<String, Value>) -> HashMap<String, String> {
+    args.iter()
+        .map(|(k, v)| {
+            let s = match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (k.clone(), s)
+        })
+        .collect()
+}
+
+/// `command` selects a subcommand unless the tool's schema declares it as an
+/// argument (e.g. `shell_exec`)
+fn tool_input(spec: &ToolSpec, args: &HashMap<String, Value>, dry_run: bool) -> ToolInput {
+    let mut args = stringify_args(args);
+    let command_is_arg = spec
+        .parsed_input_schema()
+        .is_ok_and(|schema| schema.property_names().iter().any(|p| p == "command"));
+    let command = if command_is_arg {
+        spec.name.clone()
+    } else {
+        args.remove("command").unwrap_or_else(|| spec.name.clone())
+    };
+    let context = args.remove("context");
+    let timeout_ms = args.remove("timeout_ms").and_then(|v| v.parse().ok());
+    ToolInput {
+        command,
+        args,
+        context,
+        dry_run,
+        timeout_ms,
+    }
+}
+
+/// Tool output is redacted before it can reach the Critic or an LLM prompt,
+/// failed output (stderr with tokens) included; a failed tool fails the step
+async fn finish(name: &str, output: ToolOutput, gate: &ToolGate) -> Result<Value> {
+    let output = output.redact_for_llm(name, gate.broker()).await?;
+    if !output.success {
+        return Err(StepError::ToolFailed {
+            tool: name.to_string(),
+            message: output.result,
+        }
+        .into());
+    }
+    Ok(serde_json::json!({
+        "tool": name,
+        "success": output.success,
+        "result": output.result,
+        "formatted_output": output.formatted_output,
+        "metadata": output.metadata,
+        "executed_at": chrono::Utc::now(),
+    }))
+}
+
+/// A `tools::Tool` from [`ToolRegistry`] exposed as a [`ToolInvoker`]
+pub struct RegistryToolInvoker {
+    name: String,
+    registry: Arc<ToolRegistry>,
+    gate: Arc<ToolGate>,
+    dry_run: bool,
+}
+
+impl RegistryToolInvoker {
+    pub fn new(name: String, registry: Arc<ToolRegistry>, gate: Arc<ToolGate>) -> Self {
+        Self {
+            name,
+            registry,
+            gate,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+#[async_trait]
+impl ToolInvoker for RegistryToolInvoker {
+    async fn invoke(&self, args: HashMap<String, Value>) -> Result<Value> {
+        let tool = self
+            .registry
+            .get(&self.name)
+            .ok_or_else(|| anyhow!("Tool '{}' not found in registry", self.name))?;
+        let spec = tool.spec();
+        let input = tool_input(&spec, &args, self.dry_run);
+        let output = self.gate.invoke(tool, &spec, input).await?;
+        finish(&self.name, output, &self.gate).await
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn read_only(&self) -> Option<bool> {
+        Some(self.registry.is_read_only(&self.name))
+    }
+}
+
+/// A tool from [`SecureToolRegistry`]: policy gate first, then the registry's
+/// own trust/permission checks and resource limits
+pub struct SecureRegistryToolInvoker {
+    tool_id: String,
+    registry: Arc<SecureToolRegistry>,
+    security_context: SecurityContext,
+    gate: Arc<ToolGate>,
+    dry_run: bool,
+}
+
+impl SecureRegistryToolInvoker {
+    pub fn new(
+        tool_id: String,
+        registry: Arc<SecureToolRegistry>,
+        security_context: SecurityContext,
+        gate: Arc<ToolGate>,
+    ) -> Self {
+        Self {
+            tool_id,
+            registry,
+            security_context,
+            gate,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+#[async_trait]
+impl ToolInvoker for SecureRegistryToolInvoker {
+    async fn invoke(&self, args: HashMap<String, Value>) -> Result<Value> {
+        let tool = self
+            .registry
+            .get_tool(&self.tool_id)
+            .await
+            .ok_or_else(|| anyhow!("Tool not found: {}", self.tool_id))?;
+        let spec = tool.spec();
+        let mut input = tool_input(&spec, &args, self.dry_run);
+        self.gate.prepare(&spec, &mut input)?;
+        self.gate.authorize(tool.as_ref(), &spec, &input).await?;
+        let started = Instant::now();
+        let output = self
+            .registry
+            .execute_tool(&self.tool_id, input.clone(), &self.security_context)
+            .await;
+        ToolGate::record_invocation(&spec.name, &input, &output, started).await;
+        finish(&spec.name, output?, &self.gate).await
+    }
+
+    fn get_name(&self) -> &str {
+        &self.tool_id
+    }
+}
+
+/// Replace the Executor's tools with every tool from `registry`
+pub fn register_registry_tools(
+    executor: &mut Executor,
+    registry: Arc<ToolRegistry>,
+    gate: Arc<ToolGate>,
+    dry_run: bool,
+) -> usize {
+    executor.clear_tools();
+    let specs = registry.list_tools();
+    for spec in &specs {
+        let invoker =
+            RegistryToolInvoker::new(spec.name.clone(), Arc::clone(&registry), Arc::clone(&gate))
+                .with_dry_run(dry_run);
+        executor.register_tool(spec.name.clone(), Box::new(invoker));
+    }
+    specs.len()
+}
+
+/// Register the tools of `registry` visible to `security_context`
+/// (by name; already registered names are overwritten)
+pub async fn register_secure_registry_tools(
+    executor: &mut Executor,
+    registry: Arc<SecureToolRegistry>,
+    security_context: SecurityContext,
+    gate: Arc<ToolGate>,
+    dry_run: bool,
+) -> usize {
+    let available = registry.get_available_tools(&security_context).await;
+    for metadata in &available {
+        let invoker = SecureRegistryToolInvoker::new(
+            metadata.id.clone(),
+            Arc::clone(&registry),
+            security_context.clone(),
+            Arc::clone(&gate),
+        )
+        .with_dry_run(dry_run);
+        executor.register_tool(metadata.name.clone(), Box::new(invoker));
+    }
+    available.len()
+}
+
+/// Memory steps backed by [`UnifiedMemoryAPI`], gated as `memory.<op>` commands
+pub struct MemoryApiInvoker {
+    api: Arc<UnifiedMemoryAPI>,
+    gate: Arc<ToolGate>,
+    dry_run: bool,
+    project: Option<String>,
+}
+
+impl MemoryApiInvoker {
+    pub fn new(api: Arc<UnifiedMemoryAPI>, gate: Arc<ToolGate>) -> Self {
+        Self {
+            api,
+            gate,
+            dry_run: false,
+            project: None,
+        }
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Store into and search only `project` (e.g. an agent profile's memory scope)
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    fn command_name(operation_type: &MemoryOperationType) -> &'static str {
+        match operation_type {
+            MemoryOperationType::Store => "memory.store",
+            MemoryOperationType::Search => "memory.search",
+            MemoryOperationType::Update => "memory.update",
+            MemoryOperationType::Delete => "memory.delete",
+        }
+    }
+
+    async fn run(
+        &self,
+        operation_type: &MemoryOperationType,
+        query: &str,
+        parameters: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        match operation_type {
+            MemoryOperationType::Search => {
+                let limit = parameters.get("limit").and_then(Value::as_u64).unwrap_or(5) as usize;
+                let results = self
+                    .api
+                    .recall(
+                        query,
+                        SearchOptions {
+                            limit: Some(limit),
+                            project: self.project.clone(),
+                            ..Default::default()
+                        },
+                    )
+                    .await?;
+                let items: Vec<Value> = results
+                    .into_iter()
+                    .map(|r| {
+                        serde_json::json!({
+                            "id": r.id,
+                            "text": r.text,
+                            "layer": r.layer,
+                            "kind": r.kind,
+                            "relevance_score": r.relevance_score,
+                        })
+                    })
+                    .collect();
+                Ok(serde_json::json!({ "results": items }))
+            }
+            MemoryOperationType::Store => {
+                let text = parameters
+                    .get("text")
+                    .and_then(Value::as_str)
+                    .unwrap_or(query)
+                    .to_string();
+                let kind = parameters
+                    .get("kind")
+                    .and_then(Value::as_str)
+                    .unwrap_or("orchestrator")
+                    .to_string();
+                let mut context = MemoryContext::default().with_kind(kind);
+                if let Some(project) = &self.project {
+                    context = context.with_project(project.clone());
+                }
+                let id = self.api.remember(text, context).await?;
+                Ok(serde_json::json!({ "id": id }))
+            }
+            MemoryOperationType::Delete => {
+                let raw = parameters
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or(query);
+                let id = Uuid::parse_str(raw)
+                    .map_err(|e| anyhow!("memory delete expects a record id: {e}"))?;
+                // Capture the record first: saga rollback restores it from `previous`
+                let previous = self.api.get_record(id).await?;
+                let deleted = self.api.forget(id).await?;
+                let mut output = serde_json::json!({ "id": id, "deleted": deleted });
+                if let Some(previous) = previous {
+                    output["previous"] = serde_json::to_value(previous)?;
+                }
+                Ok(output)
+            }
+            MemoryOperationType::Update => {
+                bail!("memory update is not supported by UnifiedMemoryAPI")
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl MemoryInvoker for MemoryApiInvoker {
+    async fn invoke(
+        &self,
+        operation_type: &MemoryOperationType,
+        query: &str,
+        parameters: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let command = Self::command_name(operation_type);
+        let mut args = stringify_args(parameters);
+        args.insert("query".to_string(), query.to_string());
+        self.gate
+            .authorize_command(command, &args, Some(query.chars().take(500).collect()))
+            .await?;
+
+        let started = Instant::now();
+        let read_only = matches!(operation_type, MemoryOperationType::Search);
+        let result = if self.dry_run && !read_only {
+            Ok(serde_json::json!({ "dry_run": true, "operation": command, "query": query }))
+        } else {
+            self.run(operation_type, query, parameters).await
+        };
+        let evt = serde_json::json!({
+            "tool": command,
+            "command": command,
+            "dry_run": self.dry_run,
+            "success": result.is_ok(),
+            "error": result.as_ref().err().map(|e| e.to_string()),
+            "duration_ms": started.elapsed().as_millis() as u64,
+        });
+        events::publish(topics::TOPIC_TOOL_INVOKED, evt).await;
+
+        let mut value = result?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("operation".into(), Value::String(command.to_string()));
+            obj.insert("executed_at".into(), serde_json::json!(chrono::Utc::now()));
+        }
+        Ok(value)
+    }
+}
+
+/// Saga compensation of memory steps through [`UnifiedMemoryAPI`]
+pub struct MemoryApiUndoTarget {
+    api: Arc<UnifiedMemoryAPI>,
+}
+
+impl MemoryApiUndoTarget {
+    pub fn new(api: Arc<UnifiedMemoryAPI>) -> Self {
+        Self { api }
+    }
+}
+
+#[async_trait]
+impl MemoryUndoTarget for MemoryApiUndoTarget {
+    async fn delete_record(&self, id: Uuid) -> Result<bool> {
+        self.api.forget(id).await
+    }
+
+    async fn restore_record(&self, record: &Value) -> Result<()> {
+        let record: memory::Record = serde_json::from_value(record.clone())
+            .context("memory backup is not a stored record")?;
+        self.api.forget(record.id).await?;
+        self.api
+            .restore_records(std::slice::from_ref(&record))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tools::file_ops::FileReader;
+    use tools::shell_ops::ShellExec;
+    use tools::Tool;
+
+    #[test]
+    fn planner_args_become_tool_input() {
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("src/lib.rs"));
+        args.insert("lines".to_string(), serde_json::json!(20));
+        args.insert("timeout_ms".to_string(), serde_json::json!(1500));
+        let input = tool_input(&FileReader::new().spec(), &args, true);
+        assert_eq!(input.command, "file_read");
+        assert_eq!(
+            input.args.get("path").map(String::as_str),
+            Some("src/lib.rs")
+        );
+        assert_eq!(input.args.get("lines").map(String::as_str), Some("20"));
+        assert_eq!(input.timeout_ms, Some(1500));
+        assert!(!input.args.contains_key("timeout_ms"));
+        assert!(input.dry_run);
+    }
+
+    #[test]
+    fn command_stays_an_argument_when_declared() {
+        let mut args = HashMap::new();
+        args.insert("command".to_string(), serde_json::json!("cargo test"));
+
+        let input = tool_input(&ShellExec::new().spec(), &args, false);
+        assert_eq!(input.command, "shell_exec");
+        assert_eq!(
+            input.args.get("command").map(String::as_str),
+            Some("cargo test")
+        );
+
+        let input = tool_input(&FileReader::new().spec(), &args, false);
+        assert_eq!(input.command, "cargo test");
+        assert!(!input.args.contains_key("command"));
+    }
+
+    #[tokio::test]
+    async fn failed_output_is_redacted_before_it_fails_the_step() {
+        use common::approval::{default_prompter, ApprovalBroker};
+        use common::policy::PolicyEngine;
+
+        let gate = ToolGate::new(
+            PolicyEngine::new(),
+            Arc::new(ApprovalBroker::new(default_prompter())),
+        );
+        let token = format!("ghp_{}", "a1B2c3D4e5F6".repeat(3));
+        let output = ToolOutput {
+            success: false,
+            result: format!("fatal: authentication failed for {token}"),
+            formatted_output: None,
+            metadata: HashMap::new(),
+        };
+        let err = finish("shell_exec", output, &gate)
+            .await
+            .expect_err("failed tool fails the step");
+        let message = err.to_string();
+        assert!(!message.contains(&token), "{message}");
+        assert!(message.contains("[REDACTED"), "{message}");
+    }
+}
@@ -0,0 +1,1136 @@
+//! Declarative workflow files (YAML or TOML) compiled to an [`ActionPlan`].
+//!
+//! ```yaml
+//! name: release-notes
+//! params:
+//!   version: { required: true, description: "Tag to describe" }
+//! budget:
+//!   tool_calls: { soft: 10, hard: 20 }
+//!   wall_clock_secs: { hard: 600 }
+//! steps:
+//!   - id: log
+//!     tool: git
+//!     args: { command: "log v{{params.version}}..HEAD --oneline" }
+//!     timeout: 30s
+//!     retry: { max: 2, backoff: exponential, delay: 500ms }
+//!   - id: publish
+//!     depends_on: [log]
+//!     if: 'steps["log"].status == "Completed"'
+//!     then:
+//!       - ask: { type: confirmation, prompt: "Store notes for {{params.version}}?" }
+//!       - memory: { op: store, query: "release notes {{params.version}}" }
+//!   - id: review
+//!     depends_on: [log]
+//!     delegate: { profile: reviewer, task: "Review changes since v{{params.version}}" }
+//!   - while: "iteration < 3"
+//!     max_iterations: 3
+//!     do:
+//!       - wait: 1s
+//! ```
+//!
+//! Every step has exactly one kind: `tool` (+ `args`), `memory`, `ask`,
+//! `wait`, `delegate` (to an agent profile), `if` (+ `then`/`else`) or
+//! `while` (+ `do`, `max_iterations`).
+//! `{{params.NAME}}` is substituted in strings; a string that is only a
+//! placeholder keeps the parameter's JSON type. Conditions may refer to steps
+//! by their file id (`steps["log"]`). The optional `budget` is a
+//! [`SpendBudget`]. Problems are reported as
+//! [`PlanValidationError`]s alongside the Planner's own checks.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::executor::STEP_TIMEOUT_PARAM;
+use super::planner::{
+    ActionPlan, ActionStep, ActionStepType, BackoffStrategy, InteractionType, MemoryOperationType,
+    PlanValidationError, PlanValidationErrorType, PlanValidationResult, PlannerTrait,
+    ResourceRequirements, RetryCondition, RetryPolicy,
+};
+use crate::resources::spend::SpendBudget;
+use crate::util::parse_duration;
+
+/// `max_iterations` of a `while` step that doesn't set one
+pub const DEFAULT_MAX_ITERATIONS: u32 = 10;
+
+/// A workflow file as written by the user
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowFile {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, WorkflowParam>,
+    #[serde(default)]
+    pub budget: Option<SpendBudget>,
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// A parameter that can be set with `--param NAME=VALUE`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowParam {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default: Option<Value>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// One step; exactly one of `tool`, `memory`, `ask`, `wait`, `delegate`,
+/// `if`, `while`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowStep {
+    /// Name used by `depends_on` and `steps["..."]` (default `step-N`)
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub args: HashMap<String, Value>,
+    #[serde(default)]
+    pub memory: Option<MemoryStep>,
+    #[serde(default)]
+    pub ask: Option<AskStep>,
+    #[serde(default)]
+    pub wait: Option<DurationValue>,
+    #[serde(default)]
+    pub delegate: Option<DelegateStep>,
+    #[serde(default, rename = "if")]
+    pub condition: Option<String>,
+    #[serde(default)]
+    pub then: Vec<WorkflowStep>,
+    #[serde(default, rename = "else")]
+    pub otherwise: Vec<WorkflowStep>,
+    #[serde(default, rename = "while")]
+    pub repeat_while: Option<String>,
+    #[serde(default)]
+    pub max_iterations: Option<u32>,
+    #[serde(default, rename = "do")]
+    pub body: Vec<WorkflowStep>,
+    /// Top-level steps only; the plan is ordered to satisfy them
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub retry: Option<RetrySpec>,
+    #[serde(default)]
+    pub timeout: Option<DurationValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryOp {
+    Store,
+    Search,
+    Update,
+    Delete,
+}
+
+/// `memory: { op, query, params }`; `params` go to the memory backend
+/// (e.g. `layer`, `limit`, `id`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryStep {
+    pub op: MemoryOp,
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub params: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AskKind {
+    #[default]
+    Confirmation,
+    Input,
+    Choice,
+    Information,
+}
+
+/// `ask: { type, prompt, options, default }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AskStep {
+    #[serde(default, rename = "type")]
+    pub kind: AskKind,
+    pub prompt: String,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub default: Option<Value>,
+}
+
+/// `delegate: { profile, task }`; the step's output is the sub-agent's summary
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DelegateStep {
+    pub profile: String,
+    pub task: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackoffKind {
+    Fixed,
+    Linear,
+    #[default]
+    Exponential,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryOn {
+    /// Tool failures and timeouts
+    TemporaryFailure,
+    NetworkError,
+    RateLimited,
+    ResourceUnavailable,
+}
+
+/// `retry: { max, backoff, delay, multiplier, on }`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetrySpec {
+    pub max: u32,
+    #[serde(default)]
+    pub backoff: BackoffKind,
+    /// First delay, and the increment for linear backoff (default 1s)
+    #[serde(default)]
+    pub delay: Option<DurationValue>,
+    /// Exponential backoff factor (default 2)
+    #[serde(default)]
+    pub multiplier: Option<f64>,
+    /// Default `[temporary_failure]`
+    #[serde(default)]
+    pub on: Vec<RetryOn>,
+}
+
+/// `30` (seconds) or `"500ms"`, `"30s"`, `"5m"`, `"1h"`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum DurationValue {
+    Seconds(u64),
+    Text(String),
+}
+
+impl DurationValue {
+    pub fn to_duration(&self) -> Option<Duration> {
+        match self {
+            DurationValue::Seconds(secs) => Some(Duration::from_secs(*secs)),
+            DurationValue::Text(text) => parse_duration(text),
+        }
+    }
+}
+
+impl std::fmt::Display for DurationValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationValue::Seconds(secs) => write!(f, "{}", secs),
+            DurationValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+impl WorkflowFile {
+    /// Load a workflow; `.toml` files are TOML, anything else YAML (or JSON)
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read workflow file {}", path.display()))?;
+        let is_toml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+        let parsed = if is_toml {
+            Self::from_toml_str(&source)
+        } else {
+            Self::from_yaml_str(&source)
+        };
+        parsed.with_context(|| format!("Invalid workflow file {}", path.display()))
+    }
+
+    pub fn from_yaml_str(source: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(source)?)
+    }
+
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        Ok(toml::from_str(source)?)
+    }
+
+    /// Build the plan for the given parameter values. Structural problems
+    /// (unknown parameters, bad durations, ambiguous steps...) don't fail
+    /// compilation; they are returned in [`CompiledWorkflow::errors`]
+    pub fn compile(&self, params: &HashMap<String, Value>) -> CompiledWorkflow {
+        let mut compiler = Compiler::new();
+        compiler.resolve_params(&self.params, params);
+
+        if self.steps.is_empty() {
+            compiler.error(
+                None,
+                PlanValidationErrorType::InvalidParameters,
+                "Workflow has no steps".to_string(),
+            );
+        }
+
+        compiler.assign_ids(&self.steps);
+        let mut steps: Vec<ActionStep> = self
+            .steps
+            .iter()
+            .filter_map(|step| compiler.compile_step(step, true))
+            .collect();
+        compiler.order_by_dependencies(&mut steps);
+
+        let tools_required: Vec<String> = compiler.tools.iter().cloned().collect();
+        let mut metadata = HashMap::new();
+        metadata.insert("workflow_name".to_string(), Value::from(self.name.clone()));
+        if let Some(description) = &self.description {
+            metadata.insert(
+                "workflow_description".to_string(),
+                Value::from(description.clone()),
+            );
+        }
+        metadata.insert(
+            "params".to_string(),
+            Value::Object(compiler.params.clone().into_iter().collect()),
+        );
+
+        let plan = ActionPlan {
+            id: Uuid::new_v4(),
+            intent_id: Uuid::new_v4(),
+            estimated_duration: steps.iter().map(|step| step.expected_duration).sum(),
+            steps,
+            resource_requirements: ResourceRequirements {
+                cpu_cores: 1,
+                memory_mb: 256,
+                disk_space_mb: 10,
+                network_required: false,
+                tools_required,
+                permissions_required: vec![],
+            },
+            dependencies: vec![],
+            metadata,
+        };
+
+        CompiledWorkflow {
+            plan,
+            step_names: compiler.step_names,
+            errors: compiler.errors,
+        }
+    }
+}
+
+/// A workflow file turned into an [`ActionPlan`]
+#[derive(Debug, Clone)]
+pub struct CompiledWorkflow {
+    pub plan: ActionPlan,
+    /// File id (or `step-N`) of every plan step, nested steps included
+    pub step_names: HashMap<Uuid, String>,
+    /// Problems found while compiling
+    pub errors: Vec<PlanValidationError>,
+}
+
+impl CompiledWorkflow {
+    /// Compile errors plus `validate_plan` (tool availability, dependency
+    /// cycles, condition expressions, loop bounds)
+    pub async fn validate(&self, planner: &dyn PlannerTrait) -> Result<PlanValidationResult> {
+        let mut result = planner.validate_plan(&self.plan).await?;
+        let mut errors = self.errors.clone();
+        errors.append(&mut result.errors);
+        result.errors = errors;
+        if !result.errors.is_empty() {
+            result.is_valid = false;
+            result.estimated_success_probability = result.estimated_success_probability.min(0.3);
+        }
+        Ok(result)
+    }
+
+    pub fn step_name(&self, step_id: Uuid) -> &str {
+        self.step_names
+            .get(&step_id)
+            .map(String::as_str)
+            .unwrap_or("?")
+    }
+}
+
+struct Compiler {
+    params: HashMap<String, Value>,
+    /// Ids in the order `compile_step` visits the steps
+    ids: VecDeque<Uuid>,
+    names: HashMap<String, Uuid>,
+    step_names: HashMap<Uuid, String>,
+    tools: std::collections::BTreeSet<String>,
+    errors: Vec<PlanValidationError>,
+    placeholder: Regex,
+    step_reference: Regex,
+}
+
+impl Compiler {
+    fn new() -> Self {
+        Self {
+            params: HashMap::new(),
+            ids: VecDeque::new(),
+            names: HashMap::new(),
+            step_names: HashMap::new(),
+            tools: Default::default(),
+            errors: Vec::new(),
+            placeholder: Regex::new(r"\{\{\s*params\.([A-Za-z0-9_-]+)\s*\}\}")
+                .expect("placeholder regex is valid"),
+            step_reference: Regex::new(r#"steps\[\s*(?:"([^"]*)"|'([^']*)')\s*\]"#)
+                .expect("step reference regex is valid"),
+        }
+    }
+
+    fn error(
+        &mut self,
+        step_id: Option<Uuid>,
+        error_type: PlanValidationErrorType,
+        message: String,
+    ) {
+        self.errors.push(PlanValidationError {
+            step_id,
+            error_type,
+            message,
+        });
+    }
+
+    fn resolve_params(
+        &mut self,
+        declared: &BTreeMap<String, WorkflowParam>,
+        provided: &HashMap<String, Value>,
+    ) {
+        let mut unknown: Vec<&String> = provided
+            .keys()
+            .filter(|name| !declared.contains_key(*name))
+            .collect();
+        unknown.sort();
+        for name in unknown {
+            self.error(
+                None,
+                PlanValidationErrorType::InvalidParameters,
+                format!("Unknown parameter '{}'", name),
+            );
+        }
+
+        for (name, param) in declared {
+            let value = match (provided.get(name), &param.default) {
+                (Some(value), _) => value.clone(),
+                (None, Some(default)) => default.clone(),
+                (None, None) if param.required => {
+                    self.error(
+                        None,
+                        PlanValidationErrorType::InvalidParameters,
+                        format!("Missing required parameter '{}'", name),
+                    );
+                    Value::Null
+                }
+                (None, None) => Value::Null,
+            };
+            self.params.insert(name.clone(), value);
+        }
+    }
+
+    /// Allocate plan ids and names up front so conditions and `depends_on`
+    /// can refer to any step
+    fn assign_ids(&mut self, steps: &[WorkflowStep]) {
+        for step in steps {
+            let id = Uuid::new_v4();
+            let name = step
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("step-{}", self.ids.len() + 1));
+            if self.names.insert(name.clone(), id).is_some() {
+                self.error(
+                    Some(id),
+                    PlanValidationErrorType::InvalidParameters,
+                    format!("Duplicate step id '{}'", name),
+                );
+            }
+            self.step_names.insert(id, name);
+            self.ids.push_back(id);
+            self.assign_ids(&step.then);
+            self.assign_ids(&step.otherwise);
+            self.assign_ids(&step.body);
+        }
+    }
+
+    fn substitute_str(&mut self, step_id: Uuid, text: &str) -> Value {
+        if let Some(captures) = self.placeholder.captures(text) {
+            if captures[0].len() == text.len() {
+                return self.param(step_id, &captures[1]);
+            }
+        }
+        let mut missing = Vec::new();
+        let replaced = self
+            .placeholder
+            .replace_all(text, |captures: &regex::Captures| {
+                match self.params.get(&captures[1]) {
+                    Some(Value::String(value)) => value.clone(),
+                    Some(value) => value.to_string(),
+                    None => {
+                        missing.push(captures[1].to_string());
+                        String::new()
+                    }
+                }
+            })
+            .into_owned();
+        for name in missing {
+            self.unknown_param(step_id, &name);
+        }
+        Value::String(replaced)
+    }
+
+    fn substitute_text(&mut self, step_id: Uuid, text: &str) -> String {
+        match self.substitute_str(step_id, text) {
+            Value::String(text) => text,
+            other => other.to_string(),
+        }
+    }
+
+    fn substitute(&mut self, step_id: Uuid, value: &Value) -> Value {
+        match value {
+            Value::String(text) => self.substitute_str(step_id, text),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.substitute(step_id, item))
+                    .collect(),
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, item)| (key.clone(), self.substitute(step_id, item)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn substitute_map(
+        &mut self,
+        step_id: Uuid,
+        map: &HashMap<String, Value>,
+    ) -> HashMap<String, Value> {
+        map.iter()
+            .map(|(key, value)| (key.clone(), self.substitute(step_id, value)))
+            .collect()
+    }
+
+    fn param(&mut self, step_id: Uuid, name: &str) -> Value {
+        match self.params.get(name) {
+            Some(value) => value.clone(),
+            None => {
+                self.unknown_param(step_id, name);
+                Value::Null
+            }
+        }
+    }
+
+    fn unknown_param(&mut self, step_id: Uuid, name: &str) {
+        self.error(
+            Some(step_id),
+            PlanValidationErrorType::InvalidParameters,
+            format!("Reference to undeclared parameter 'params.{}'", name),
+        );
+    }
+
+    /// Parameters become JSON literals, step names become plan ids
+    fn compile_condition(&mut self, step_id: Uuid, condition: &str) -> String {
+        let mut missing = Vec::new();
+        let with_params = self
+            .placeholder
+            .replace_all(condition, |captures: &regex::Captures| {
+                match self.params.get(&captures[1]) {
+                    Some(value) => value.to_string(),
+                    None => {
+                        missing.push(captures[1].to_string());
+                        "null".to_string()
+                    }
+                }
+            })
+            .into_owned();
+        for name in missing {
+            self.unknown_param(step_id, &name);
+        }
+
+        let mut unknown = Vec::new();
+        let compiled = self
+            .step_reference
+            .replace_all(&with_params, |captures: &regex::Captures| {
+                let name = captures
+                    .get(1)
+                    .or_else(|| captures.get(2))
+                    .map_or("", |m| m.as_str());
+                match self.names.get(name) {
+                    Some(id) => format!("steps[\"{}\"]", id),
+                    None => {
+                        if Uuid::parse_str(name).is_err() {
+                            unknown.push(name.to_string());
+                        }
+                        captures[0].to_string()
+                    }
+                }
+            })
+            .into_owned();
+        for name in unknown {
+            self.error(
+                Some(step_id),
+                PlanValidationErrorType::InvalidExpression,
+                format!("Condition refers to unknown step '{}'", name),
+            );
+        }
+        compiled
+    }
+
+    fn duration(&mut self, step_id: Uuid, field: &str, value: &DurationValue) -> Option<Duration> {
+        let duration = value.to_duration();
+        if duration.is_none() {
+            self.error(
+                Some(step_id),
+                PlanValidationErrorType::InvalidParameters,
+                format!(
+                    "Invalid {} '{}' (expected e.g. 500ms, 30s, 5m, 1h or seconds)",
+                    field, value
+                ),
+            );
+        }
+        duration
+    }
+
+    fn retry_policy(&mut self, step_id: Uuid, retry: Option<&RetrySpec>) -> RetryPolicy {
+        let Some(retry) = retry else {
+            return RetryPolicy {
+                max_retries: 0,
+                backoff_strategy: BackoffStrategy::Fixed(Duration::ZERO),
+                retry_conditions: vec![],
+            };
+        };
+
+        let delay = match &retry.delay {
+            Some(delay) => self.duration(step_id, "retry delay", delay),
+            None => None,
+        }
+        .unwrap_or(Duration::from_secs(1));
+        let multiplier = retry.multiplier.unwrap_or(2.0);
+        if retry.multiplier.is_some() && retry.backoff != BackoffKind::Exponential {
+            self.error(
+                Some(step_id),
+                PlanValidationErrorType::InvalidParameters,
+                "retry multiplier only applies to exponential backoff".to_string(),
+            );
+        } else if !multiplier.is_finite() || multiplier < 1.0 {
+            self.error(
+                Some(step_id),
+                PlanValidationErrorType::InvalidParameters,
+                format!("retry multiplier must be at least 1, got {}", multiplier),
+            );
+        }
+
+        let backoff_strategy = match retry.backoff {
+            BackoffKind::Fixed => BackoffStrategy::Fixed(delay),
+            BackoffKind::Linear => BackoffStrategy::Linear {
+                initial: delay,
+                increment: delay,
+            },
+            BackoffKind::Exponential => BackoffStrategy::Exponential {
+                initial: delay,
+                multiplier,
+            },
+        };
+        let on = if retry.on.is_empty() {
+            vec![RetryOn::TemporaryFailure]
+        } else {
+            retry.on.clone()
+        };
+        RetryPolicy {
+            max_retries: retry.max,
+            backoff_strategy,
+            retry_conditions: on
+                .into_iter()
+                .map(|on| match on {
+                    RetryOn::TemporaryFailure => RetryCondition::TemporaryFailure,
+                    RetryOn::NetworkError => RetryCondition::NetworkError,
+                    RetryOn::RateLimited => RetryCondition::RateLimited,
+                    RetryOn::ResourceUnavailable => RetryCondition::ResourceUnavailable,
+                })
+                .collect(),
+        }
+    }
+
+    fn compile_steps(&mut self, steps: &[WorkflowStep]) -> Vec<ActionStep> {
+        steps
+            .iter()
+            .filter_map(|step| self.compile_step(step, false))
+            .collect()
+    }
+
+    /// `None` when the step's kind can't be determined
+    fn compile_step(&mut self, step: &WorkflowStep, top_level: bool) -> Option<ActionStep> {
+        let id = self
+            .ids
+            .pop_front()
+            .expect("assign_ids visits the same steps");
+        let name = self.step_names[&id].clone();
+
+        let kinds: Vec<&str> = [
+            ("tool", step.tool.is_some()),
+            ("memory", step.memory.is_some()),
+            ("ask", step.ask.is_some()),
+            ("wait", step.wait.is_some()),
+            ("delegate", step.delegate.is_some()),
+            ("if", step.condition.is_some()),
+            ("while", step.repeat_while.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, present)| *present)
+        .map(|(kind, _)| kind)
+        .collect();
+        let misplaced = [
+            ("args", !step.args.is_empty() && step.tool.is_none()),
+            (
+                "then/else",
+                (!step.then.is_empty() || !step.otherwise.is_empty()) && step.condition.is_none(),
+            ),
+            (
+                "do/max_iterations",
+                (!step.body.is_empty() || step.max_iterations.is_some())
+                    && step.repeat_while.is_none(),
+            ),
+        ];
+        for (field, misplaced) in misplaced {
+            if misplaced {
+                self.error(
+                    Some(id),
+                    PlanValidationErrorType::InvalidParameters,
+                    format!(
+                        "Step '{}': '{}' doesn't apply to this kind of step",
+                        name, field
+                    ),
+                );
+            }
+        }
+
+        if !top_level && !step.depends_on.is_empty() {
+            self.error(
+                Some(id),
+                PlanValidationErrorType::InvalidParameters,
+                format!(
+                    "Step '{}': depends_on is only supported for top-level steps",
+                    name
+                ),
+            );
+        }
+        let mut dependencies = Vec::new();
+        for dependency in &step.depends_on {
+            match self.names.get(dependency) {
+                Some(dependency_id) => dependencies.push(*dependency_id),
+                None => self.error(
+                    Some(id),
+                    PlanValidationErrorType::UnreachableStep,
+                    format!("Step '{}' depends on unknown step '{}'", name, dependency),
+                ),
+            }
+        }
+
+        let is_control_flow = step.condition.is_some() || step.repeat_while.is_some();
+        if is_control_flow && (step.timeout.is_some() || step.retry.is_some()) {
+            self.error(
+                Some(id),
+                PlanValidationErrorType::InvalidParameters,
+                format!(
+                    "Step '{}': timeout and retry apply to the steps inside if/while, not the block",
+                    name
+                ),
+            );
+        }
+
+        let mut parameters = HashMap::new();
+        let timeout = match &step.timeout {
+            Some(timeout) => self.duration(id, "timeout", timeout),
+            None => None,
+        };
+        if let Some(timeout) = timeout {
+            parameters.insert(
+                STEP_TIMEOUT_PARAM.to_string(),
+                Value::from(timeout.as_millis() as u64),
+            );
+        }
+        let retry_policy = self.retry_policy(id, step.retry.as_ref());
+
+        let mut expected_duration = Duration::from_secs(1);
+        let step_type = match kinds.as_slice() {
+            ["tool"] => {
+                let tool_name = step.tool.clone().unwrap_or_default();
+                self.tools.insert(tool_name.clone());
+                ActionStepType::ToolExecution {
+                    tool_name,
+                    arguments: self.substitute_map(id, &step.args),
+                }
+            }
+            ["memory"] => {
+                let memory = step.memory.as_ref()?;
+                parameters.extend(self.substitute_map(id, &memory.params));
+                ActionStepType::MemoryOperation {
+                    operation_type: match memory.op {
+                        MemoryOp::Store => MemoryOperationType::Store,
+                        MemoryOp::Search => MemoryOperationType::Search,
+                        MemoryOp::Update => MemoryOperationType::Update,
+                        MemoryOp::Delete => MemoryOperationType::Delete,
+                    },
+                    query: self.substitute_text(id, &memory.query),
+                }
+            }
+            ["ask"] => {
+                let ask = step.ask.as_ref()?;
+                if ask.kind == AskKind::Choice && ask.options.is_empty() {
+                    self.error(
+                        Some(id),
+                        PlanValidationErrorType::InvalidParameters,
+                        format!("Step '{}': choice prompts need options", name),
+                    );
+                }
+                if !ask.options.is_empty() {
+                    let options: Vec<Value> = ask
+                        .options
+                        .iter()
+                        .map(|option| self.substitute_str(id, option))
+                        .collect();
+                    parameters.insert("options".to_string(), Value::Array(options));
+                }
+                if let Some(default) = &ask.default {
+                    let default = self.substitute(id, default);
+                    parameters.insert("default".to_string(), default);
+                }
+                ActionStepType::UserInteraction {
+                    interaction_type: match ask.kind {
+                        AskKind::Confirmation => InteractionType::Confirmation,
+                        AskKind::Input => InteractionType::Input,
+                        AskKind::Choice => InteractionType::Choice,
+                        AskKind::Information => InteractionType::Information,
+                    },
+                    prompt: self.substitute_text(id, &ask.prompt),
+                }
+            }
+            ["wait"] => {
+                let duration = step
+                    .wait
+                    .as_ref()
+                    .and_then(|wait| self.duration(id, "wait", wait))
+                    .unwrap_or_default();
+                expected_duration = duration;
+                ActionStepType::Wait { duration }
+            }
+            ["delegate"] => {
+                let delegate = step.delegate.as_ref()?;
+                ActionStepType::Delegate {
+                    profile: delegate.profile.clone(),
+                    task: self.substitute_text(id, &delegate.task),
+                }
+            }
+            ["if"] => {
+                let condition = step.condition.as_deref().unwrap_or_default();
+                ActionStepType::Conditional {
+                    condition: self.compile_condition(id, condition),
+                    then_steps: self.compile_steps(&step.then),
+                    else_steps: self.compile_steps(&step.otherwise),
+                }
+            }
+            ["while"] => {
+                let condition = step.repeat_while.as_deref().unwrap_or_default();
+                ActionStepType::Loop {
+                    condition: self.compile_condition(id, condition),
+                    body_steps: self.compile_steps(&step.body),
+                    max_iterations: step.max_iterations.unwrap_or(DEFAULT_MAX_ITERATIONS),
+                }
+            }
+            [] => {
+                self.error(
+                    Some(id),
+                    PlanValidationErrorType::InvalidParameters,
+                    format!(
+                        "Step '{}' must define one of tool, memory, ask, wait, delegate, if or while",
+                        name
+                    ),
+                );
+                self.skip_nested(step);
+                return None;
+            }
+            several => {
+                self.error(
+                    Some(id),
+                    PlanValidationErrorType::InvalidParameters,
+                    format!(
+                        "Step '{}' defines more than one of {}",
+                        name,
+                        several.join(", ")
+                    ),
+                );
+                self.skip_nested(step);
+                return None;
+            }
+        };
+        if let Some(timeout) = timeout {
+            expected_duration = expected_duration.min(timeout);
+        }
+
+        Some(ActionStep {
+            id,
+            step_type,
+            parameters,
+            dependencies,
+            expected_duration,
+            retry_policy,
+            validation_rules: vec![],
+        })
+    }
+
+    /// Keep `ids` in step with the file when a step is dropped
+    fn skip_nested(&mut self, step: &WorkflowStep) {
+        for nested in step.then.iter().chain(&step.otherwise).chain(&step.body) {
+            self.ids.pop_front();
+            self.skip_nested(nested);
+        }
+    }
+
+    /// Stable topological order of top-level steps; left as written when the
+    /// dependencies have a cycle (validation reports it)
+    fn order_by_dependencies(&self, steps: &mut Vec<ActionStep>) {
+        let mut placed: HashSet<Uuid> = HashSet::new();
+        let mut ordered = Vec::with_capacity(steps.len());
+        let mut remaining: Vec<ActionStep> = steps.clone();
+        let known: HashSet<Uuid> = steps.iter().map(|step| step.id).collect();
+
+        while !remaining.is_empty() {
+            let Some(next) = remaining.iter().position(|step| {
+                step.dependencies
+                    .iter()
+                    .all(|dependency| placed.contains(dependency) || !known.contains(dependency))
+            }) else {
+                return;
+            };
+            let step = remaining.remove(next);
+            placed.insert(step.id);
+            ordered.push(step);
+        }
+        *steps = ordered;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::planner::validate_dependencies;
+
+    const RELEASE_YAML: &str = r#"
+name: release-notes
+description: Summarise a release
+params:
+  version:
+    required: true
+  limit:
+    default: 20
+budget:
+  tokens: { soft: 20000, hard: 50000 }
+  tool_calls: { hard: 10 }
+steps:
+  - id: publish
+    depends_on: [log]
+    if: 'steps["log"].status == "Completed" && {{params.limit}} > 0'
+    then:
+      - ask: { type: confirmation, prompt: "Store notes for {{params.version}}?" }
+      - memory: { op: store, query: "release notes {{params.version}}", params: { layer: Insights } }
+  - id: log
+    tool: git
+    args: { command: "log v{{params.version}}..HEAD", max_count: "{{params.limit}}" }
+    timeout: 30s
+    retry: { max: 2, backoff: linear, delay: 500ms }
+  - while: "iteration < 2"
+    max_iterations: 3
+    do:
+      - wait: 10ms
+"#;
+
+    fn params(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compile_yaml_workflow() {
+        let workflow = WorkflowFile::from_yaml_str(RELEASE_YAML).expect("workflow should parse");
+        let compiled = workflow.compile(&params(&[("version", Value::from("1.2.0"))]));
+        assert!(compiled.errors.is_empty(), "{:?}", compiled.errors);
+
+        let budget = workflow.budget.as_ref().expect("budget should parse");
+        assert_eq!(budget.tokens.soft, Some(20000.0));
+        assert_eq!(budget.tokens.hard, Some(50000.0));
+        assert_eq!(budget.tool_calls.hard, Some(10.0));
+        assert!(budget.cost_usd.is_unlimited());
+
+        let plan = &compiled.plan;
+        assert_eq!(plan.steps.len(), 3);
+        // `publish` depends on `log`, so it moves after it
+        assert_eq!(compiled.step_name(plan.steps[0].id), "log");
+        assert_eq!(compiled.step_name(plan.steps[1].id), "publish");
+        assert_eq!(plan.steps[1].dependencies, vec![plan.steps[0].id]);
+
+        let log = &plan.steps[0];
+        match &log.step_type {
+            ActionStepType::ToolExecution {
+                tool_name,
+                arguments,
+            } => {
+                assert_eq!(tool_name, "git");
+                assert_eq!(arguments["command"], "log v1.2.0..HEAD");
+                // a lone placeholder keeps the parameter's type
+                assert_eq!(arguments["max_count"], 20);
+            }
+            other => panic!("Expected tool step, got {:?}", other),
+        }
+        assert_eq!(log.parameters[STEP_TIMEOUT_PARAM], 30_000);
+        assert_eq!(log.retry_policy.max_retries, 2);
+        assert_eq!(
+            log.retry_policy.backoff_strategy,
+            BackoffStrategy::Linear {
+                initial: Duration::from_millis(500),
+                increment: Duration::from_millis(500),
+            }
+        );
+
+        match &plan.steps[1].step_type {
+            ActionStepType::Conditional {
+                condition,
+                then_steps,
+                ..
+            } => {
+                assert_eq!(
+                    condition,
+                    &format!("steps[\"{}\"].status == \"Completed\" && 20 > 0", log.id)
+                );
+                assert_eq!(then_steps.len(), 2);
+                assert_eq!(then_steps[1].parameters["layer"], "Insights");
+            }
+            other => panic!("Expected conditional step, got {:?}", other),
+        }
+        assert!(validate_dependencies(&plan.steps).is_empty());
+    }
+
+    #[test]
+    fn test_compile_toml_workflow() {
+        let workflow = WorkflowFile::from_toml_str(
+            r#"
+name = "cleanup"
+
+[params.dir]
+default = "/tmp/build"
+
+[[steps]]
+id = "confirm"
+ask = { type = "choice", prompt = "Clean {{params.dir}}?", options = ["yes", "no"] }
+
+[[steps]]
+tool = "shell"
+args = { command = "rm -rf {{params.dir}}" }
+timeout = 5
+retry = { max = 1, on = ["temporary_failure", "network_error"] }
+
+[[steps]]
+delegate = { profile = "reviewer", task = "Check that {{params.dir}} is gone" }
+"#,
+        )
+        .expect("workflow should parse");
+        let compiled = workflow.compile(&HashMap::new());
+        assert!(compiled.errors.is_empty(), "{:?}", compiled.errors);
+
+        let steps = &compiled.plan.steps;
+        assert_eq!(steps.len(), 3);
+        assert_eq!(compiled.step_name(steps[1].id), "step-2");
+        assert_eq!(
+            steps[0].parameters["options"],
+            serde_json::json!(["yes", "no"])
+        );
+        assert_eq!(steps[1].parameters[STEP_TIMEOUT_PARAM], 5_000);
+        assert_eq!(
+            steps[1].retry_policy.retry_conditions,
+            vec![
+                RetryCondition::TemporaryFailure,
+                RetryCondition::NetworkError
+            ]
+        );
+        assert_eq!(
+            steps[2].step_type,
+            ActionStepType::Delegate {
+                profile: "reviewer".to_string(),
+                task: "Check that /tmp/build is gone".to_string(),
+            }
+        );
+        assert_eq!(
+            compiled.plan.resource_requirements.tools_required,
+            vec!["shell".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_compile_reports_structural_errors() {
+        let workflow = WorkflowFile::from_yaml_str(
+            r#"
+name: broken
+params:
+  target: { required: true }
+steps:
+  - id: a
+    tool: shell
+    wait: 1s
+  - id: a
+    wait: soon
+  - if: 'steps["missing"].status == "Completed"'
+    timeout: 1s
+    then:
+      - tool: shell
+        depends_on: [a]
+        args: { path: "{{params.nope}}" }
+"#,
+        )
+        .expect("workflow should parse");
+        let compiled = workflow.compile(&params(&[("extra", Value::from(1))]));
+        let messages: Vec<&str> = compiled
+            .errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect();
+
+        for expected in [
+            "Unknown parameter 'extra'",
+            "Missing required parameter 'target'",
+            "Duplicate step id 'a'",
+            "defines more than one of tool, wait",
+            "Invalid wait",
+            "unknown step 'missing'",
+            "timeout and retry apply",
+            "depends_on is only supported for top-level steps",
+            "undeclared parameter 'params.nope'",
+        ] {
+            assert!(
+                messages.iter().any(|message| message.contains(expected)),
+                "missing error '{}' in {:?}",
+                expected,
+                messages
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        let error = WorkflowFile::from_yaml_str("name: x\nsteps:\n  - tol: shell\n")
+            .expect_err("unknown step field should fail");
+        assert!(error.to_string().contains("tol"));
+    }
+}
@@ -0,0 +1,524 @@
+//! Isolated agent runs in a temporary git worktree.
+//!
+//! [`IsolatedWorkspace::create`] checks the repository's `HEAD` out into a
+//! new worktree on its own `magray/...` branch. [`IsolatedWorkspace::scope_gate`]
+//! gives a [`ToolGate`] the worktree as its working directory and a
+//! filesystem sandbox scoped to it, so file, git and shell tools invoked
+//! through the gate work there and writes to the user's checkout are
+//! rejected. Nothing process-wide changes, so several isolated runs can
+//! share a process.
+//!
+//! When the run is over the changes are committed on the branch, tests can
+//! run in the worktree, and the combined diff against the base commit is
+//! offered for review. [`IsolatedWorkspace::finish`] applies the
+//! [`ReviewDecision`] and removes the worktree.
+
+use anyhow::{anyhow, bail, Context, Result};
+use common::sandbox_config::SandboxConfig;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use tools::invocation::ToolGate;
+use uuid::Uuid;
+
+/// Prefix of isolation branches
+pub const BRANCH_PREFIX: &str = "magray/";
+
+/// Tail of the test output kept in [`TestRun`]
+const TEST_OUTPUT_LIMIT: usize = 8 * 1024;
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Branch-safe slug of a task description
+fn slug(label: &str) -> String {
+    let mut slug = String::new();
+    for c in label.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.len() >= 32 {
+            break;
+        }
+    }
+    match slug.trim_end_matches('-') {
+        "" => "task".to_string(),
+        slug => slug.to_string(),
+    }
+}
+
+/// Test command for the project in `dir`, by its manifest
+pub fn detect_test_command(dir: &Path) -> Option<&'static str> {
+    [
+        ("Cargo.toml", "cargo test"),
+        ("package.json", "npm test"),
+        ("go.mod", "go test ./..."),
+        ("pyproject.toml", "pytest"),
+        ("setup.py", "pytest"),
+    ]
+    .into_iter()
+    .find(|(manifest, _)| dir.join(manifest).is_file())
+    .map(|(_, command)| command)
+}
+
+/// What to do with the branch of a finished isolated run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewDecision {
+    /// Merge the branch into the current branch of the user's checkout
+    Merge,
+    /// Replay the branch commits onto the current branch
+    CherryPick,
+    /// Delete the worktree and the branch
+    Discard,
+    /// Delete the worktree, keep the branch for later
+    Keep,
+}
+
+impl FromStr for ReviewDecision {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "merge" => Ok(Self::Merge),
+            "cherry-pick" | "cherry_pick" | "pick" => Ok(Self::CherryPick),
+            "discard" => Ok(Self::Discard),
+            "keep" => Ok(Self::Keep),
+            other => Err(anyhow!(
+                "Unknown decision '{other}', expected merge, cherry-pick, discard or keep"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ReviewDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Merge => "merge",
+            Self::CherryPick => "cherry-pick",
+            Self::Discard => "discard",
+            Self::Keep => "keep",
+        })
+    }
+}
+
+/// Result of the test command run in the worktree
+#[derive(Debug, Clone)]
+pub struct TestRun {
+    pub command: String,
+    pub success: bool,
+    /// Last [`TEST_OUTPUT_LIMIT`] bytes of stdout and stderr
+    pub output: String,
+}
+
+/// A worktree on a fresh branch where an agent run makes its changes
+#[derive(Debug, Clone)]
+pub struct IsolatedWorkspace {
+    repo_root: PathBuf,
+    path: PathBuf,
+    branch: String,
+    base: String,
+}
+
+impl IsolatedWorkspace {
+    /// New worktree under `worktrees_dir` for the repository containing
+    /// `dir`, branched from its `HEAD`
+    pub fn create(dir: &Path, label: &str, worktrees_dir: &Path) -> Result<Self> {
+        let repo_root = PathBuf::from(
+            git(dir, &["rev-parse", "--show-toplevel"])
+                .context("Isolation requires a git repository")?
+                .trim(),
+        );
+        let base = git(&repo_root, &["rev-parse", "HEAD"])
+            .context("Isolation requires at least one commit")?
+            .trim()
+            .to_string();
+
+        let id = Uuid::new_v4().simple().to_string();
+        let name = format!("{}-{}", slug(label), &id[..8]);
+        let branch = format!("{BRANCH_PREFIX}{name}");
+        let repo_name = repo_root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "repo".to_string());
+        std::fs::create_dir_all(worktrees_dir)?;
+        let path = worktrees_dir.join(format!("{repo_name}-{name}"));
+
+        let path_arg = path.to_string_lossy();
+        git(
+            &repo_root,
+            &["worktree", "add", "-q", "-b", &branch, &path_arg, &base],
+        )?;
+        // Sandbox roots are compared with canonical paths
+        let path = std::fs::canonicalize(&path)?;
+        Ok(Self {
+            repo_root,
+            path,
+            branch,
+            base,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Commit the worktree was created from
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The user's checkout
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// `base` with the filesystem sandbox enabled and writes limited to the
+    /// worktree. Reads keep the configured roots when the sandbox was
+    /// already on, otherwise the worktree and the user's checkout are
+    /// readable.
+    pub fn sandbox_config(&self, base: &SandboxConfig) -> SandboxConfig {
+        let mut cfg = base.clone();
+        let worktree = self.path.to_string_lossy().into_owned();
+        let mut read_roots = if base.fs.enabled {
+            base.fs.fs_read_roots.clone()
+        } else {
+            vec![self.repo_root.to_string_lossy().into_owned()]
+        };
+        read_roots.insert(0, worktree.clone());
+        cfg.fs.enabled = true;
+        cfg.fs.fs_read_roots = read_roots;
+        cfg.fs.fs_write_roots = vec![worktree];
+        cfg
+    }
+
+    /// `gate` working in the worktree: it becomes the tools' working
+    /// directory and the only filesystem write root
+    pub fn scope_gate(&self, gate: ToolGate) -> ToolGate {
+        let sandbox = self.sandbox_config(gate.sandbox());
+        gate.with_sandbox(sandbox).with_workdir(&self.path)
+    }
+
+    /// Stage and commit everything left in the worktree; `false` when there
+    /// was nothing to commit
+    pub fn commit(&self, message: &str) -> Result<bool> {
+        git(&self.path, &["add", "-A"])?;
+        if git(&self.path, &["status", "--porcelain"])?
+            .trim()
+            .is_empty()
+        {
+            return Ok(false);
+        }
+        git(&self.path, &["commit", "-q", "-m", message])?;
+        Ok(true)
+    }
+
+    /// Commits on the branch since `base`
+    pub fn commit_count(&self) -> Result<usize> {
+        let range = format!("{}..{}", self.base, self.branch);
+        let count = git(&self.repo_root, &["rev-list", "--count", &range])?;
+        count
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Unexpected rev-list output: {count}"))
+    }
+
+    /// Combined unified diff of the branch against `base`
+    pub fn diff(&self) -> Result<String> {
+        git(&self.repo_root, &["diff", &self.base, &self.branch])
+    }
+
+    /// `git diff --stat` of the branch against `base`
+    pub fn diff_stat(&self) -> Result<String> {
+        git(
+            &self.repo_root,
+            &["diff", "--stat", &self.base, &self.branch],
+        )
+    }
+
+    /// Run `command` (or the detected test command) in the worktree;
+    /// `None` when no command is given and none could be detected
+    pub fn run_tests(&self, command: Option<&str>) -> Result<Option<TestRun>> {
+        let Some(command) = command.or_else(|| detect_test_command(&self.path)) else {
+            return Ok(None);
+        };
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", command]);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let mut cmd = {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", command]);
+            cmd
+        };
+        let output = cmd
+            .current_dir(&self.path)
+            .output()
+            .with_context(|| format!("Failed to run tests: {command}"))?;
+
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        if text.len() > TEST_OUTPUT_LIMIT {
+            let mut start = text.len() - TEST_OUTPUT_LIMIT;
+            while !text.is_char_boundary(start) {
+                start += 1;
+            }
+            text = text[start..].to_string();
+        }
+        Ok(Some(TestRun {
+            command: command.to_string(),
+            success: output.status.success(),
+            output: text,
+        }))
+    }
+
+    /// Apply `decision` in the user's checkout and remove the worktree.
+    /// A failed merge or cherry-pick is aborted and the branch is kept.
+    pub fn finish(self, decision: ReviewDecision) -> Result<String> {
+        let root = &self.repo_root;
+        match decision {
+            ReviewDecision::Merge => {
+                let message = format!("Merge {}", self.branch);
+                if let Err(e) = git(root, &["merge", "--no-ff", "-m", &message, &self.branch]) {
+                    let _ = git(root, &["merge", "--abort"]);
+                    bail!("{e}\nBranch {} is kept", self.branch);
+                }
+            }
+            ReviewDecision::CherryPick => {
+                let range = format!("{}..{}", self.base, self.branch);
+                if let Err(e) = git(root, &["cherry-pick", &range]) {
+                    let _ = git(root, &["cherry-pick", "--abort"]);
+                    bail!("{e}\nBranch {} is kept", self.branch);
+                }
+            }
+            ReviewDecision::Discard | ReviewDecision::Keep => {}
+        }
+
+        // Everything is committed; only ignored build output may remain
+        let path = self.path.to_string_lossy();
+        git(root, &["worktree", "remove", "--force", &path])?;
+        if decision == ReviewDecision::Keep {
+            return Ok(format!("Branch {} is kept", self.branch));
+        }
+        git(root, &["branch", "-D", &self.branch])?;
+        Ok(match decision {
+            ReviewDecision::Merge => format!("Merged {}", self.branch),
+            ReviewDecision::CherryPick => format!("Cherry-picked {}", self.branch),
+            _ => format!("Discarded {}", self.branch),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let tmp = TempDir::new().expect("Temp dir should be created");
+        for args in [
+            &["init", "-q"][..],
+            &["config", "user.email", "test@example.com"],
+            &["config", "user.name", "Test"],
+        ] {
+            git(tmp.path(), args).expect("git setup should succeed");
+        }
+        std::fs::write(tmp.path().join("a.txt"), "one\n").expect("File should be written");
+        git(tmp.path(), &["add", "."]).expect("git add should succeed");
+        git(tmp.path(), &["commit", "-q", "-m", "init"]).expect("git commit should succeed");
+        tmp
+    }
+
+    #[test]
+    fn test_isolated_changes_merge_into_checkout() {
+        let repo = repo();
+        let worktrees = TempDir::new().expect("Temp dir should be created");
+        let workspace = IsolatedWorkspace::create(repo.path(), "Fix the parser!", worktrees.path())
+            .expect("Worktree should be created");
+        assert!(workspace.branch().starts_with("magray/fix-the-parser-"));
+
+        std::fs::write(workspace.path().join("a.txt"), "one\ntwo\n")
+            .expect("File should be written");
+        std::fs::write(workspace.path().join("b.txt"), "new\n").expect("File should be written");
+        // The user's checkout is untouched
+        assert_eq!(
+            std::fs::read_to_string(repo.path().join("a.txt")).expect("File should be read"),
+            "one\n"
+        );
+
+        assert!(workspace
+            .commit("agent work")
+            .expect("Commit should succeed"));
+        assert!(!workspace.commit("again").expect("Commit should succeed"));
+        assert_eq!(workspace.commit_count().expect("Count should succeed"), 1);
+        let diff = workspace.diff().expect("Diff should succeed");
+        assert!(diff.contains("+two"));
+        assert!(diff.contains("b.txt"));
+
+        let branch = workspace.branch().to_string();
+        let worktree = workspace.path().to_path_buf();
+        workspace
+            .finish(ReviewDecision::Merge)
+            .expect("Merge should succeed");
+        assert_eq!(
+            std::fs::read_to_string(repo.path().join("b.txt")).expect("File should be merged"),
+            "new\n"
+        );
+        assert!(!worktree.exists());
+        assert!(git(repo.path(), &["branch", "--list", &branch])
+            .expect("git branch should succeed")
+            .trim()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_discard_and_keep() {
+        let repo = repo();
+        let worktrees = TempDir::new().expect("Temp dir should be created");
+
+        let workspace = IsolatedWorkspace::create(repo.path(), "", worktrees.path())
+            .expect("Worktree should be created");
+        std::fs::write(workspace.path().join("a.txt"), "changed\n")
+            .expect("File should be written");
+        workspace
+            .commit("agent work")
+            .expect("Commit should succeed");
+        let branch = workspace.branch().to_string();
+        workspace
+            .finish(ReviewDecision::Keep)
+            .expect("Keep should succeed");
+        assert!(!git(repo.path(), &["branch", "--list", &branch])
+            .expect("git branch should succeed")
+            .trim()
+            .is_empty());
+
+        let workspace = IsolatedWorkspace::create(repo.path(), "other", worktrees.path())
+            .expect("Worktree should be created");
+        let branch = workspace.branch().to_string();
+        workspace
+            .finish(ReviewDecision::Discard)
+            .expect("Discard should succeed");
+        assert!(git(repo.path(), &["branch", "--list", &branch])
+            .expect("git branch should succeed")
+            .trim()
+            .is_empty());
+        assert_eq!(
+            std::fs::read_to_string(repo.path().join("a.txt")).expect("File should be read"),
+            "one\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scoped_gate_runs_tools_in_worktree() {
+        use common::approval::{ApprovalBroker, StdinPrompter};
+        use common::policy::PolicyEngine;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+        use tools::{ToolInput, ToolRegistry};
+
+        let repo = repo();
+        let worktrees = TempDir::new().expect("Temp dir should be created");
+        let workspace = IsolatedWorkspace::create(repo.path(), "gate", worktrees.path())
+            .expect("Worktree should be created");
+        let broker = ApprovalBroker::new(Arc::new(StdinPrompter)).with_auto_approve(true);
+        let gate = ToolGate::new(PolicyEngine::new(), Arc::new(broker));
+        let gate = workspace.scope_gate(gate);
+        let registry = ToolRegistry::new();
+        let input = |command: &str, args: &[(&str, &str)]| ToolInput {
+            command: command.into(),
+            args: args
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<HashMap<_, _>>(),
+            context: None,
+            dry_run: false,
+            timeout_ms: None,
+        };
+
+        std::fs::write(workspace.path().join("b.txt"), "new\n").expect("File should be written");
+        let read = registry
+            .get("file_read")
+            .expect("file_read should be registered");
+        let mut relative = input("file_read", &[("path", "b.txt")]);
+        gate.prepare(&read.spec(), &mut relative)
+            .expect("Input should be valid");
+        assert_eq!(
+            relative.args["path"],
+            workspace.path().join("b.txt").to_string_lossy()
+        );
+
+        let write = registry
+            .get("file_write")
+            .expect("file_write should be registered");
+        let outside = repo.path().join("c.txt").to_string_lossy().into_owned();
+        let denied = gate
+            .invoke(
+                write,
+                &write.spec(),
+                input("file_write", &[("path", &outside), ("content", "x")]),
+            )
+            .await
+            .expect_err("Writes to the checkout should be denied");
+        assert!(denied.to_string().contains("песочницей"), "{denied}");
+        assert!(!repo.path().join("c.txt").exists());
+
+        let status = registry
+            .get("git_status")
+            .expect("git_status should be registered");
+        let out = gate
+            .invoke(status, &status.spec(), input("git_status", &[]))
+            .await
+            .expect("git_status should succeed");
+        assert!(out.result.contains("b.txt"), "{}", out.result);
+        workspace
+            .finish(ReviewDecision::Discard)
+            .expect("Discard should succeed");
+    }
+
+    #[test]
+    fn test_sandbox_scopes_writes_to_worktree() {
+        let workspace = IsolatedWorkspace {
+            repo_root: PathBuf::from("/work/repo"),
+            path: PathBuf::from("/tmp/worktrees/repo-x"),
+            branch: "magray/x".to_string(),
+            base: "abc".to_string(),
+        };
+        let cfg = workspace.sandbox_config(&SandboxConfig::default());
+        assert!(cfg.fs.enabled);
+        assert_eq!(cfg.fs.fs_write_roots, vec!["/tmp/worktrees/repo-x"]);
+        assert_eq!(
+            cfg.fs.fs_read_roots,
+            vec!["/tmp/worktrees/repo-x", "/work/repo"]
+        );
+
+        assert_eq!(
+            "cherry-pick"
+                .parse::<ReviewDecision>()
+                .expect("Should parse"),
+            ReviewDecision::CherryPick
+        );
+        assert!("rebase".parse::<ReviewDecision>().is_err());
+    }
+}
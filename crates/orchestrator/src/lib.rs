@@ -77,6 +77,7 @@
 //!         dry_run: false,
 //!         timeout_ms: None,
 //!         config_overrides: None,
+//!         budget: None,
 //!     };
 //!
 //!     let result = orchestrator.execute_workflow(request).await?;
@@ -112,11 +113,14 @@
 pub mod actors;
 pub mod agents;
 pub mod events;
+pub mod isolation;
 pub mod orchestrator;
+pub mod persistence;
 pub mod reliability;
 pub mod resources;
 pub mod saga;
 pub mod system;
+pub mod util;
 pub mod workflow;
 
 pub use actors::{
@@ -125,14 +129,19 @@ pub use actors::{
     ExecutionStatus, TaskPriority,
 };
 pub use agents::{
-    Critic, CriticTrait, Executor, ExecutorTrait, IntentAnalyzer, IntentAnalyzerTrait, Planner,
-    PlannerTrait, Scheduler, SchedulerTrait,
+    AgentLoop, AgentLoopConfig, AgentLoopReport, AgentProfile, AgentProfiles, CompiledWorkflow,
+    Critic, CriticTrait, CronExpression, CronTimeZone, DelegationHandler, Delegator, Executor,
+    ExecutorTrait, IntentAnalyzer, IntentAnalyzerTrait, InteractionHandler, LoopOutcome,
+    MissedRunPolicy, Planner, PlannerTrait, ScheduledPayload, ScheduledTask, Scheduler,
+    SchedulerTrait, SubAgentFactory, SubAgentResult, TaskSchedule, TaskStatus, TaskType,
+    WorkflowFile,
 };
 pub use events::{
     create_agent_event_publisher, AgentEventPublisher, AgentLifecycleEvent, AgentMessageEvent,
     AgentStatus, AgentTopics, MessageStatus, SchedulerEvent, SchedulerEventType, WorkflowEvent,
     WorkflowStep,
 };
+pub use isolation::{IsolatedWorkspace, ReviewDecision, TestRun};
 pub use reliability::{
     AgentReliabilityConfig, AgentReliabilityManager, AgentReliabilityStats, BackoffStrategy,
     CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitBreakerState,
@@ -141,6 +150,8 @@ pub use reliability::{
 };
 pub use resources::{
     BudgetViolation, EnforcementPolicy, ResourceBudget, ResourceLimits, ResourceMonitor,
+    SpendApprover, SpendBudget, SpendCheck, SpendKind, SpendLedger, SpendLimit, SpendOverrun,
+    SpendReport, SpendTracker, SpendUsage, SESSION_BUDGET_ENV,
 };
 pub use system::{ActorSystem, ActorSystemError, RestartStrategy, Supervisor, SystemConfig};
 
@@ -149,12 +160,16 @@ pub use orchestrator::{
     AgentHealthStatus, AgentOrchestrator, AgentRegistry, OrchestratorConfig, OrchestratorError,
     ResourceUsage, WorkflowId, WorkflowState, WorkflowStepType,
 };
+pub use persistence::{
+    SagaCheckpoint, StoredWorkflow, WorkflowCheckpoint, WorkflowStore, WorkflowTransition,
+};
 pub use saga::{
-    CompensationHandler, CompensationStatus, CompensationStep, CompensationType,
-    DefaultSagaManager, Saga, SagaCompensationResult, SagaExecutionResult, SagaManager, SagaStatus,
-    SagaStep, SagaStepStatus,
+    CompensationHandler, CompensationReportEntry, CompensationStatus, CompensationStep,
+    CompensationType, DefaultSagaManager, FileSnapshotStore, FileUndoHandler, MemoryUndoHandler,
+    MemoryUndoTarget, Saga, SagaCompensationResult, SagaExecutionResult, SagaManager, SagaStatus,
+    SagaStep, SagaStepRunner, SagaStepStatus, ToolUndoHandler,
 };
-pub use workflow::{WorkflowConfig, WorkflowRequest, WorkflowResult};
+pub use workflow::{RecoveryReport, WorkflowConfig, WorkflowRequest, WorkflowResult};
 
 /// Result type for Actor System operations
 pub type ActorResult<T> = Result<T, ActorError>;
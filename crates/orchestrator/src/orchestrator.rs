@@ -14,14 +14,23 @@
 //! - EventBus integration for workflow events
 
 use crate::actors::{ActorError, ActorHandle, ActorId, ActorState, AgentType, ExecutionStatus};
-use crate::agents::{Critic, Executor, IntentAnalyzer, Planner, Scheduler};
+use crate::agents::executor::{InteractionHandler, MemoryInvoker};
+use crate::agents::{
+    register_registry_tools, Critic, Executor, IntentAnalyzer, Planner, Scheduler,
+};
 use crate::events::AgentEventPublisher;
+use crate::persistence::WorkflowStore;
 use crate::reliability::{
     AgentReliabilityManager, HealthCheckConfig, HealthChecker, HealthMonitor, HealthStatus,
     ReliabilityError,
 };
-use crate::resources::{ResourceBudget, ResourceMonitor};
+use crate::resources::{
+    ResourceBudget, ResourceMonitor, SpendApprover, SpendLedger, SpendOverrun, SpendReport,
+};
+use crate::saga::{MemoryUndoHandler, MemoryUndoTarget};
 use crate::system::{ActorSystem, SystemConfig};
+use tools::invocation::ToolGate;
+use tools::ToolRegistry;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -58,6 +67,26 @@ pub struct AgentOrchestrator {
 
     /// Orchestrator configuration
     pub config: OrchestratorConfig,
+
+    /// Durable workflow/saga event log (in-memory only when `None`)
+    pub state_store: Option<Arc<WorkflowStore>>,
+
+    /// Spend trackers of running workflows (attach it to LLM clients as a
+    /// usage observer to charge their calls)
+    pub spend_ledger: Arc<SpendLedger>,
+
+    /// Asked before a workflow passes a soft spend limit
+    pub(crate) spend_approver: Option<Arc<dyn SpendApprover>>,
+
+    /// Real tools of spawned Executors, invoked through the policy gate
+    /// (built-in mocks only when `None`)
+    tools: Option<(Arc<ToolRegistry>, Arc<ToolGate>)>,
+
+    /// Memory steps of spawned Executors and their saga compensation
+    memory: Option<(Arc<dyn MemoryInvoker>, Arc<dyn MemoryUndoTarget>)>,
+
+    /// Answers user interaction steps of spawned Executors
+    interaction_handler: Option<Arc<dyn InteractionHandler>>,
 }
 
 /// Registry of all agents by type and instance
@@ -125,6 +154,11 @@ pub struct WorkflowState {
     /// Execution results (if completed)
     pub execution_results: Option<serde_json::Value>,
 
+    /// Results of the plan steps completed so far, in plan order
+    /// (resume continues after the last one)
+    #[serde(default)]
+    pub plan_step_results: Vec<serde_json::Value>,
+
     /// Critique feedback (if completed)
     pub critique: Option<serde_json::Value>,
 
@@ -179,6 +213,10 @@ pub struct ResourceUsage {
 
     /// Total workflow execution time (milliseconds)
     pub total_time_ms: u64,
+
+    /// Token/cost/tool-call spend against the request's budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spend: Option<SpendReport>,
 }
 
 /// Health status of an individual agent
@@ -269,6 +307,9 @@ pub enum OrchestratorError {
     #[error("Resource budget exceeded for workflow {0}")]
     ResourceBudgetExceeded(WorkflowId),
 
+    #[error("Workflow {0} stopped: {1}")]
+    SpendLimitExceeded(WorkflowId, SpendOverrun),
+
     #[error("Invalid workflow state transition from {0} to {1}")]
     InvalidStateTransition(WorkflowStepType, WorkflowStepType),
 
@@ -280,6 +321,9 @@ pub enum OrchestratorError {
 
     #[error("Actor system error: {0}")]
     ActorSystemError(#[from] crate::system::ActorSystemError),
+
+    #[error("Workflow state persistence failed: {0}")]
+    Persistence(String),
 }
 
 impl AgentOrchestrator {
@@ -318,6 +362,12 @@ impl AgentOrchestrator {
             active_workflows: Arc::new(RwLock::new(HashMap::new())),
             completed_workflows: Arc::new(RwLock::new(HashMap::new())),
             config: orchestrator_config,
+            state_store: None,
+            spend_ledger: Arc::new(SpendLedger::new()),
+            spend_approver: None,
+            tools: None,
+            memory: None,
+            interaction_handler: None,
         };
 
         // Start background tasks
@@ -327,6 +377,65 @@ impl AgentOrchestrator {
         Ok(orchestrator)
     }
 
+    /// Persist workflow and saga state transitions to `store`.
+    /// Call before [`initialize_agents`](Self::initialize_agents) so the
+    /// Executor's sagas are persisted as well.
+    pub fn with_state_store(mut self, store: Arc<WorkflowStore>) -> Self {
+        self.state_store = Some(store);
+        self
+    }
+
+    /// Ask `approver` whether a workflow may continue past a soft spend
+    /// limit; without one soft limits are only logged
+    pub fn with_spend_approver(mut self, approver: Arc<dyn SpendApprover>) -> Self {
+        self.spend_approver = Some(approver);
+        self
+    }
+
+    /// Run Executor tool steps with the tools of `registry` through `gate`
+    /// (policy, approval, telemetry) instead of the built-in mocks.
+    /// Call before [`initialize_agents`](Self::initialize_agents).
+    pub fn with_tools(mut self, registry: Arc<ToolRegistry>, gate: Arc<ToolGate>) -> Self {
+        self.tools = Some((registry, gate));
+        self
+    }
+
+    /// Run memory steps through `invoker`; `undo` compensates them on
+    /// rollback and during startup recovery
+    pub fn with_memory(
+        mut self,
+        invoker: Arc<dyn MemoryInvoker>,
+        undo: Arc<dyn MemoryUndoTarget>,
+    ) -> Self {
+        self.memory = Some((invoker, undo));
+        self
+    }
+
+    /// Ask the user through `handler` in interaction steps
+    pub fn with_interaction_handler(mut self, handler: Arc<dyn InteractionHandler>) -> Self {
+        self.interaction_handler = Some(handler);
+        self
+    }
+
+    /// Memory compensation handler for saga managers of this orchestrator
+    pub(crate) fn memory_undo_handler(&self) -> Option<MemoryUndoHandler> {
+        self.memory
+            .as_ref()
+            .map(|(_, undo)| MemoryUndoHandler::new(Arc::clone(undo)))
+    }
+
+    /// LLM client configured from the environment whose calls are charged
+    /// to the budget of the workflow they are made for
+    pub fn create_llm_client(&self) -> anyhow::Result<llm::LlmClient> {
+        Ok(llm::LlmClient::from_env()?
+            .with_usage_observer(Arc::clone(&self.spend_ledger) as Arc<dyn llm::LlmUsageObserver>))
+    }
+
+    /// Live spend of a running workflow
+    pub fn workflow_spend(&self, workflow_id: WorkflowId) -> Option<SpendReport> {
+        self.spend_ledger.report(workflow_id.0)
+    }
+
     /// Start all background tasks
     async fn start_background_tasks(&self) {
         // Start health monitoring
@@ -497,7 +606,29 @@ impl AgentOrchestrator {
     /// Spawn an Executor agent
     async fn spawn_executor(&self) -> Result<ActorId, OrchestratorError> {
         // Create agent instance
-        let executor = Executor::new();
+        let mut executor = match &self.state_store {
+            Some(store) => Executor::new().with_state_store(Arc::clone(store)),
+            None => Executor::new(),
+        };
+        if let Some((registry, gate)) = &self.tools {
+            let count = register_registry_tools(
+                &mut executor,
+                Arc::clone(registry),
+                Arc::clone(gate),
+                false,
+            );
+            debug!(tools = count, "Executor uses registry tools");
+        }
+        if let Some((invoker, _)) = &self.memory {
+            executor.set_memory_invoker(Arc::clone(invoker));
+        }
+        if let Some(handler) = self.memory_undo_handler() {
+            executor.register_compensation_handler(Box::new(handler));
+        }
+        if let Some(handler) = &self.interaction_handler {
+            executor.set_interaction_handler(Arc::clone(handler));
+        }
+        executor.set_spend_ledger(Arc::clone(&self.spend_ledger));
 
         // CRITICAL FIX: Start heartbeat loop IMMEDIATELY after creation
         executor.start_heartbeat_loop();
@@ -736,6 +867,8 @@ impl AgentRegistry {
             AgentType::Executor => self.executors.first(),
             AgentType::Critic => self.critics.first(),
             AgentType::Scheduler => self.schedulers.first(),
+            // Sub-agents are spawned per delegated task, not pooled
+            AgentType::SubAgent(_) => None,
         }
     }
 
@@ -0,0 +1,829 @@
+//! Durable workflow and saga state.
+//!
+//! Every workflow/saga state transition is appended to an SQLite event log
+//! together with a full snapshot, and the latest snapshot is kept in a
+//! separate table for fast lookup. After a crash the log tells which steps
+//! completed (and which side effects may have happened), so a workflow can be
+//! resumed from its last checkpoint and unfinished sagas can be compensated.
+//!
+//! Rows carry the PID of the writing process; a `Running` workflow or an
+//! `Executing`/`Compensating` saga whose owner is no longer alive is considered
+//! interrupted.
+//!
+//! The same database holds the Scheduler's recurring tasks, so schedules
+//! survive restarts together with the workflows they start.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::actors::ExecutionStatus;
+use crate::agents::scheduler::{ScheduledTask, TaskStatus};
+use crate::orchestrator::{WorkflowId, WorkflowState, WorkflowStepType};
+use crate::saga::{Saga, SagaStatus};
+use crate::workflow::WorkflowRequest;
+
+/// A recorded workflow state transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WorkflowTransition {
+    Started,
+    StepCompleted {
+        step: WorkflowStepType,
+    },
+    StepFailed {
+        step: WorkflowStepType,
+        error: String,
+    },
+    /// One step of the plan finished during plan execution
+    PlanStepCompleted {
+        index: usize,
+    },
+    /// The owning process exited while the workflow was running
+    Interrupted {
+        step: WorkflowStepType,
+    },
+    Resumed {
+        step: WorkflowStepType,
+    },
+    Cancelled,
+    Finished {
+        success: bool,
+    },
+}
+
+impl std::fmt::Display for WorkflowTransition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkflowTransition::Started => write!(f, "started"),
+            WorkflowTransition::StepCompleted { step } => write!(f, "{step} completed"),
+            WorkflowTransition::StepFailed { step, error } => write!(f, "{step} failed: {error}"),
+            WorkflowTransition::PlanStepCompleted { index } => {
+                write!(f, "plan step {} completed", index + 1)
+            }
+            WorkflowTransition::Interrupted { step } => write!(f, "interrupted during {step}"),
+            WorkflowTransition::Resumed { step } => write!(f, "resumed at {step}"),
+            WorkflowTransition::Cancelled => write!(f, "cancelled"),
+            WorkflowTransition::Finished { success: true } => write!(f, "finished"),
+            WorkflowTransition::Finished { success: false } => write!(f, "finished with errors"),
+        }
+    }
+}
+
+/// One entry of a workflow's event log: the transition and the state after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowCheckpoint {
+    pub seq: i64,
+    pub at: DateTime<Utc>,
+    pub transition: WorkflowTransition,
+    pub state: WorkflowState,
+}
+
+/// Latest persisted state of a workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredWorkflow {
+    pub state: WorkflowState,
+    pub request: WorkflowRequest,
+    pub owner_pid: u32,
+}
+
+/// One entry of a saga's event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SagaCheckpoint {
+    pub seq: i64,
+    pub at: DateTime<Utc>,
+    pub saga: Saga,
+}
+
+/// SQLite-backed workflow and saga event log
+pub struct WorkflowStore {
+    conn: Mutex<Connection>,
+    path: Option<PathBuf>,
+}
+
+const SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS workflows (
+        id TEXT PRIMARY KEY,
+        status TEXT NOT NULL,
+        current_step TEXT NOT NULL,
+        request TEXT NOT NULL,
+        state TEXT NOT NULL,
+        owner_pid INTEGER NOT NULL,
+        started_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS workflow_events (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        workflow_id TEXT NOT NULL,
+        transition TEXT NOT NULL,
+        state TEXT NOT NULL,
+        at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS sagas (
+        id TEXT PRIMARY KEY,
+        plan_id TEXT NOT NULL,
+        status TEXT NOT NULL,
+        saga TEXT NOT NULL,
+        owner_pid INTEGER NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS saga_events (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        saga_id TEXT NOT NULL,
+        status TEXT NOT NULL,
+        saga TEXT NOT NULL,
+        at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS scheduled_tasks (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        status TEXT NOT NULL,
+        next_run_at TEXT,
+        task TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_workflows_status ON workflows(status);
+    CREATE INDEX IF NOT EXISTS idx_workflow_events_wf ON workflow_events(workflow_id);
+    CREATE INDEX IF NOT EXISTS idx_sagas_plan ON sagas(plan_id);
+    CREATE INDEX IF NOT EXISTS idx_sagas_status ON sagas(status);
+    CREATE INDEX IF NOT EXISTS idx_saga_events_saga ON saga_events(saga_id);
+    CREATE INDEX IF NOT EXISTS idx_scheduled_tasks_name ON scheduled_tasks(name);
+"#;
+
+/// Scheduled task metadata key holding the PID of the process running it
+const RUNNER_PID_KEY: &str = "runner_pid";
+
+fn saga_status_key(status: &SagaStatus) -> String {
+    format!("{status:?}")
+}
+
+fn parse_ts(raw: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+/// Is the process that wrote a row still running?
+pub(crate) fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut sys = sysinfo::System::new();
+    sys.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::Some(&[pid]),
+        sysinfo::ProcessRefreshKind::new(),
+    );
+    sys.process(pid).is_some()
+}
+
+impl WorkflowStore {
+    /// `<magray_home>/orchestrator/workflows.db`
+    pub fn default_path(magray_home: &Path) -> PathBuf {
+        magray_home.join("orchestrator").join("workflows.db")
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("cannot create {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open workflow store {}", path.display()))?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Self::init(conn, Some(path.to_path_buf()))
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?, None)
+    }
+
+    fn init(conn: Connection, path: Option<PathBuf>) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .context("Failed to initialize workflow store schema")?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            path,
+        })
+    }
+
+    /// `saga-snapshots` next to the database file; `None` in memory
+    pub fn snapshot_dir(&self) -> Option<PathBuf> {
+        self.path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(|dir| dir.join("saga-snapshots"))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Register a new workflow and log `Started`
+    pub fn begin(&self, state: &WorkflowState, request: &WorkflowRequest) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO workflows
+                (id, status, current_step, request, state, owner_pid, started_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                state.id.to_string(),
+                state.status.to_string(),
+                state.current_step.to_string(),
+                serde_json::to_string(request)?,
+                serde_json::to_string(state)?,
+                std::process::id(),
+                state.started_at.to_rfc3339(),
+                state.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Self::append_workflow_event(&tx, state, &WorkflowTransition::Started)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Persist `state` after `transition` (snapshot + event log entry)
+    pub fn checkpoint(&self, state: &WorkflowState, transition: WorkflowTransition) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let updated = tx.execute(
+            "UPDATE workflows
+                SET status = ?2, current_step = ?3, state = ?4, owner_pid = ?5, updated_at = ?6
+              WHERE id = ?1",
+            params![
+                state.id.to_string(),
+                state.status.to_string(),
+                state.current_step.to_string(),
+                serde_json::to_string(state)?,
+                std::process::id(),
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        if updated == 0 {
+            anyhow::bail!("Workflow {} is not registered in the store", state.id);
+        }
+        Self::append_workflow_event(&tx, state, &transition)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn append_workflow_event(
+        tx: &rusqlite::Transaction<'_>,
+        state: &WorkflowState,
+        transition: &WorkflowTransition,
+    ) -> Result<()> {
+        tx.execute(
+            "INSERT INTO workflow_events (workflow_id, transition, state, at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                state.id.to_string(),
+                serde_json::to_string(transition)?,
+                serde_json::to_string(state)?,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get(&self, workflow_id: WorkflowId) -> Result<Option<StoredWorkflow>> {
+        let conn = self.conn.lock();
+        let row = conn
+            .query_row(
+                "SELECT state, request, owner_pid FROM workflows WHERE id = ?1",
+                params![workflow_id.to_string()],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, u32>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        row.map(|(state, request, owner_pid)| Self::decode_workflow(&state, &request, owner_pid))
+            .transpose()
+    }
+
+    /// Find a workflow by full id or unique id prefix
+    pub fn find(&self, id_or_prefix: &str) -> Result<Option<StoredWorkflow>> {
+        if let Ok(uuid) = Uuid::parse_str(id_or_prefix) {
+            return self.get(WorkflowId(uuid));
+        }
+        let ids: Vec<String> = {
+            let conn = self.conn.lock();
+            let mut stmt = conn.prepare("SELECT id FROM workflows WHERE id LIKE ?1 LIMIT 2")?;
+            let rows = stmt.query_map(params![format!("{id_or_prefix}%")], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        match ids.as_slice() {
+            [] => Ok(None),
+            [id] => self.get(WorkflowId(Uuid::parse_str(id)?)),
+            _ => anyhow::bail!("Workflow id prefix '{}' is ambiguous", id_or_prefix),
+        }
+    }
+
+    fn decode_workflow(state: &str, request: &str, owner_pid: u32) -> Result<StoredWorkflow> {
+        Ok(StoredWorkflow {
+            state: serde_json::from_str(state).context("corrupt workflow snapshot")?,
+            request: serde_json::from_str(request).context("corrupt workflow request")?,
+            owner_pid,
+        })
+    }
+
+    /// Most recently updated workflows first
+    pub fn list(&self, limit: usize) -> Result<Vec<StoredWorkflow>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT state, request, owner_pid FROM workflows ORDER BY updated_at DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (state, request, owner_pid) = row?;
+            out.push(Self::decode_workflow(&state, &request, owner_pid)?);
+        }
+        Ok(out)
+    }
+
+    /// Event log of a workflow, oldest first
+    pub fn checkpoints(&self, workflow_id: WorkflowId) -> Result<Vec<WorkflowCheckpoint>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT seq, transition, state, at FROM workflow_events
+              WHERE workflow_id = ?1 ORDER BY seq",
+        )?;
+        let rows = stmt.query_map(params![workflow_id.to_string()], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (seq, transition, state, at) = row?;
+            out.push(WorkflowCheckpoint {
+                seq,
+                at: parse_ts(&at),
+                transition: serde_json::from_str(&transition)?,
+                state: serde_json::from_str(&state)?,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Running/pending workflows whose owning process is gone
+    pub fn interrupted_workflows(&self) -> Result<Vec<StoredWorkflow>> {
+        let candidates = {
+            let conn = self.conn.lock();
+            let mut stmt = conn.prepare(
+                "SELECT state, request, owner_pid FROM workflows WHERE status IN (?1, ?2)",
+            )?;
+            let rows = stmt.query_map(
+                params![
+                    ExecutionStatus::Running.to_string(),
+                    ExecutionStatus::Pending.to_string()
+                ],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, u32>(2)?,
+                    ))
+                },
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        let mut out = Vec::new();
+        for (state, request, owner_pid) in candidates {
+            if !process_alive(owner_pid) {
+                out.push(Self::decode_workflow(&state, &request, owner_pid)?);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Persist the current saga state (snapshot + event log entry)
+    pub fn save_saga(&self, saga: &Saga) -> Result<()> {
+        let json = serde_json::to_string(saga)?;
+        let status = saga_status_key(&saga.status);
+        let now = Utc::now().to_rfc3339();
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO sagas (id, plan_id, status, saga, owner_pid, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                status = excluded.status, saga = excluded.saga,
+                owner_pid = excluded.owner_pid, updated_at = excluded.updated_at",
+            params![
+                saga.id.to_string(),
+                saga.plan_id.to_string(),
+                status,
+                json,
+                std::process::id(),
+                now,
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO saga_events (saga_id, status, saga, at) VALUES (?1, ?2, ?3, ?4)",
+            params![saga.id.to_string(), status, json, now],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn get_saga(&self, saga_id: Uuid) -> Result<Option<Saga>> {
+        let conn = self.conn.lock();
+        let raw: Option<String> = conn
+            .query_row(
+                "SELECT saga FROM sagas WHERE id = ?1",
+                params![saga_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        raw.map(|s| serde_json::from_str(&s).context("corrupt saga snapshot"))
+            .transpose()
+    }
+
+    /// Sagas executed for a plan, oldest first
+    pub fn sagas_for_plan(&self, plan_id: Uuid) -> Result<Vec<Saga>> {
+        let conn = self.conn.lock();
+        let mut stmt =
+            conn.prepare("SELECT saga FROM sagas WHERE plan_id = ?1 ORDER BY updated_at")?;
+        let rows = stmt.query_map(params![plan_id.to_string()], |row| row.get::<_, String>(0))?;
+        let mut out = Vec::new();
+        for row in rows {
+            out.push(serde_json::from_str(&row?).context("corrupt saga snapshot")?);
+        }
+        Ok(out)
+    }
+
+    /// Event log of a saga, oldest first
+    pub fn saga_checkpoints(&self, saga_id: Uuid) -> Result<Vec<SagaCheckpoint>> {
+        let conn = self.conn.lock();
+        let mut stmt =
+            conn.prepare("SELECT seq, saga, at FROM saga_events WHERE saga_id = ?1 ORDER BY seq")?;
+        let rows = stmt.query_map(params![saga_id.to_string()], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (seq, saga, at) = row?;
+            out.push(SagaCheckpoint {
+                seq,
+                at: parse_ts(&at),
+                saga: serde_json::from_str(&saga)?,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Executing/compensating sagas whose owning process is gone
+    pub fn unfinished_sagas(&self) -> Result<Vec<Saga>> {
+        let candidates = {
+            let conn = self.conn.lock();
+            let mut stmt =
+                conn.prepare("SELECT saga, owner_pid FROM sagas WHERE status IN (?1, ?2)")?;
+            let rows = stmt.query_map(
+                params![
+                    saga_status_key(&SagaStatus::Executing),
+                    saga_status_key(&SagaStatus::Compensating)
+                ],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)),
+            )?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()?
+        };
+        let mut out = Vec::new();
+        for (saga, owner_pid) in candidates {
+            if !process_alive(owner_pid) {
+                out.push(serde_json::from_str(&saga).context("corrupt saga snapshot")?);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Insert or update a scheduled task
+    pub fn save_scheduled_task(&self, task: &ScheduledTask) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO scheduled_tasks (id, name, status, next_run_at, task, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name, status = excluded.status,
+                next_run_at = excluded.next_run_at, task = excluded.task,
+                updated_at = excluded.updated_at",
+            params![
+                task.id.to_string(),
+                task.name,
+                format!("{:?}", task.status),
+                task.next_run_at.map(|at| at.to_rfc3339()),
+                serde_json::to_string(task)?,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Atomically move a task from `expected` to `Running`, so that two
+    /// schedulers never run the same job. Returns `false` if the stored
+    /// status changed meanwhile or the task is gone; on success `task` is
+    /// the claimed row (owned by this PID until
+    /// [`finish_scheduled_task`](Self::finish_scheduled_task)).
+    pub fn claim_scheduled_task(
+        &self,
+        task: &mut ScheduledTask,
+        expected: &TaskStatus,
+    ) -> Result<bool> {
+        if *expected == TaskStatus::Running {
+            return Ok(false);
+        }
+        let conn = self.conn.lock();
+        let id = task.id.to_string();
+        let claimed = conn.execute(
+            "UPDATE scheduled_tasks SET status = ?1, updated_at = ?2
+             WHERE id = ?3 AND status = ?4",
+            params![
+                format!("{:?}", TaskStatus::Running),
+                Utc::now().to_rfc3339(),
+                id,
+                format!("{expected:?}"),
+            ],
+        )?;
+        if claimed == 0 {
+            return Ok(false);
+        }
+
+        let raw: String = conn.query_row(
+            "SELECT task FROM scheduled_tasks WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        let mut stored: ScheduledTask =
+            serde_json::from_str(&raw).context("corrupt scheduled task")?;
+        stored.status = TaskStatus::Running;
+        stored.metadata.insert(
+            RUNNER_PID_KEY.to_string(),
+            serde_json::json!(std::process::id()),
+        );
+        conn.execute(
+            "UPDATE scheduled_tasks SET task = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&stored)?, id],
+        )?;
+        *task = stored;
+        Ok(true)
+    }
+
+    /// Write the outcome of a claimed run. Unlike
+    /// [`save_scheduled_task`](Self::save_scheduled_task) this never inserts:
+    /// returns `false` if the task was removed while it ran.
+    pub fn finish_scheduled_task(&self, task: &ScheduledTask) -> Result<bool> {
+        let mut task = task.clone();
+        task.metadata.remove(RUNNER_PID_KEY);
+        let conn = self.conn.lock();
+        let updated = conn.execute(
+            "UPDATE scheduled_tasks SET
+                name = ?1, status = ?2, next_run_at = ?3, task = ?4, updated_at = ?5
+             WHERE id = ?6",
+            params![
+                task.name,
+                format!("{:?}", task.status),
+                task.next_run_at.map(|at| at.to_rfc3339()),
+                serde_json::to_string(&task)?,
+                Utc::now().to_rfc3339(),
+                task.id.to_string(),
+            ],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Put `Running` tasks whose claiming process is gone back to
+    /// `Scheduled`; returns the released tasks
+    pub fn release_stale_claims(&self) -> Result<Vec<ScheduledTask>> {
+        let mut released = Vec::new();
+        for mut task in self.scheduled_tasks()? {
+            let owner = task
+                .metadata
+                .get(RUNNER_PID_KEY)
+                .and_then(serde_json::Value::as_u64)
+                .map(|pid| pid as u32);
+            if task.status != TaskStatus::Running || owner.is_some_and(process_alive) {
+                continue;
+            }
+            task.status = TaskStatus::Scheduled;
+            if self.finish_scheduled_task(&task)? {
+                task.metadata.remove(RUNNER_PID_KEY);
+                released.push(task);
+            }
+        }
+        Ok(released)
+    }
+
+    /// Returns `false` if no such task was stored
+    pub fn delete_scheduled_task(&self, task_id: Uuid) -> Result<bool> {
+        let conn = self.conn.lock();
+        let deleted = conn.execute(
+            "DELETE FROM scheduled_tasks WHERE id = ?1",
+            params![task_id.to_string()],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// All scheduled tasks ordered by name
+    pub fn scheduled_tasks(&self) -> Result<Vec<ScheduledTask>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT task FROM scheduled_tasks ORDER BY name, id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut out = Vec::new();
+        for row in rows {
+            out.push(serde_json::from_str(&row?).context("corrupt scheduled task")?);
+        }
+        Ok(out)
+    }
+
+    /// Find a scheduled task by name, full id or unique id prefix
+    pub fn find_scheduled_task(&self, name_or_id: &str) -> Result<Option<ScheduledTask>> {
+        let tasks = self.scheduled_tasks()?;
+        if let Some(task) = tasks.iter().find(|task| task.name == name_or_id) {
+            return Ok(Some(task.clone()));
+        }
+        let mut matches = tasks
+            .into_iter()
+            .filter(|task| task.id.to_string().starts_with(name_or_id));
+        match (matches.next(), matches.next()) {
+            (None, _) => Ok(None),
+            (Some(task), None) => Ok(Some(task)),
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Scheduled task id prefix '{}' is ambiguous", name_or_id)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actors::TaskPriority;
+    use crate::orchestrator::ResourceUsage;
+    use crate::saga::{SagaStep, SagaStepStatus};
+    use std::collections::HashMap;
+
+    fn request() -> WorkflowRequest {
+        WorkflowRequest {
+            user_input: "index the repo".to_string(),
+            context: None,
+            priority: TaskPriority::Normal,
+            dry_run: false,
+            timeout_ms: None,
+            config_overrides: None,
+            budget: None,
+        }
+    }
+
+    fn state() -> WorkflowState {
+        WorkflowState {
+            id: WorkflowId::new(),
+            current_step: WorkflowStepType::IntentAnalysis,
+            status: ExecutionStatus::Running,
+            user_input: "index the repo".to_string(),
+            context: None,
+            intent: None,
+            plan: None,
+            execution_results: None,
+            plan_step_results: Vec::new(),
+            critique: None,
+            started_at: Utc::now(),
+            updated_at: Utc::now(),
+            error: None,
+            resource_usage: ResourceUsage::default(),
+        }
+    }
+
+    #[test]
+    fn workflow_transitions_are_logged_with_snapshots() {
+        let store =
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()");
+        let mut wf = state();
+        store
+            .begin(&wf, &request())
+            .expect("Operation failed - converted from unwrap()");
+
+        wf.intent = Some(serde_json::json!({"action": "index"}));
+        wf.current_step = WorkflowStepType::PlanGeneration;
+        store
+            .checkpoint(
+                &wf,
+                WorkflowTransition::StepCompleted {
+                    step: WorkflowStepType::IntentAnalysis,
+                },
+            )
+            .expect("Operation failed - converted from unwrap()");
+
+        let stored = store
+            .get(wf.id)
+            .expect("Operation failed - converted from unwrap()")
+            .expect("workflow stored");
+        assert_eq!(stored.state.current_step, WorkflowStepType::PlanGeneration);
+        assert!(stored.state.intent.is_some());
+        assert_eq!(stored.request.user_input, "index the repo");
+
+        let log = store
+            .checkpoints(wf.id)
+            .expect("Operation failed - converted from unwrap()");
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].transition, WorkflowTransition::Started);
+        assert!(log[0].state.intent.is_none());
+
+        let prefix = &wf.id.to_string()[..8];
+        assert!(store
+            .find(prefix)
+            .expect("Operation failed - converted from unwrap()")
+            .is_some());
+        // Owned by this (live) process, so not interrupted
+        assert!(store
+            .interrupted_workflows()
+            .expect("Operation failed - converted from unwrap()")
+            .is_empty());
+    }
+
+    #[test]
+    fn saga_state_survives_and_dead_owner_is_unfinished() {
+        let store =
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()");
+        let saga = Saga {
+            id: Uuid::new_v4(),
+            plan_id: Uuid::new_v4(),
+            status: SagaStatus::Executing,
+            steps: vec![SagaStep {
+                id: Uuid::new_v4(),
+                action_step_id: Uuid::new_v4(),
+                status: SagaStepStatus::Completed,
+                result: None,
+                error: None,
+                compensation_needed: true,
+                executed_at: Some(Utc::now()),
+                compensated_at: None,
+            }],
+            compensation_steps: vec![],
+            metadata: HashMap::new(),
+            started_at: Utc::now(),
+            completed_at: None,
+        };
+        store
+            .save_saga(&saga)
+            .expect("Operation failed - converted from unwrap()");
+        assert_eq!(
+            store
+                .sagas_for_plan(saga.plan_id)
+                .expect("Operation failed - converted from unwrap()")
+                .len(),
+            1
+        );
+        assert!(store
+            .unfinished_sagas()
+            .expect("Operation failed - converted from unwrap()")
+            .is_empty());
+
+        // Simulate a crashed owner
+        store
+            .conn
+            .lock()
+            .execute("UPDATE sagas SET owner_pid = ?1", params![u32::MAX - 1])
+            .expect("Operation failed - converted from unwrap()");
+        let unfinished = store
+            .unfinished_sagas()
+            .expect("Operation failed - converted from unwrap()");
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].id, saga.id);
+        assert_eq!(
+            store
+                .saga_checkpoints(saga.id)
+                .expect("Operation failed - converted from unwrap()")
+                .len(),
+            1
+        );
+    }
+}
@@ -2,11 +2,17 @@
 //!
 //! This module provides resource budgeting, monitoring, and enforcement for actors.
 //! It tracks CPU time, memory usage, message queue sizes, and timeouts to ensure
-//! system stability and prevent resource exhaustion.
+//! system stability and prevent resource exhaustion. Token, cost, tool-call and
+//! wall-clock budgets of workflows and chat sessions live in [`spend`].
 
 pub mod budget;
+pub mod spend;
 
 pub use budget::{BudgetViolation, ResourceBudget, ResourceMonitor, ResourceUsage};
+pub use spend::{
+    SpendApprover, SpendBudget, SpendCheck, SpendKind, SpendLedger, SpendLimit, SpendOverrun,
+    SpendReport, SpendTracker, SpendUsage, SESSION_BUDGET_ENV,
+};
 
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
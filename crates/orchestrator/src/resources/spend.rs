@@ -0,0 +1,755 @@
+//! Spend Budgets for Workflows and Sessions
+//!
+//! Soft and hard limits on LLM tokens, dollar cost, tool invocations and
+//! wall-clock time. A [`SpendTracker`] accumulates usage for one workflow or
+//! chat session; crossing a soft limit asks its [`SpendApprover`] whether to
+//! continue, crossing a hard limit stops the run.
+
+use crate::util::parse_duration;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// Environment variable with the budget spec for interactive chat sessions
+pub const SESSION_BUDGET_ENV: &str = "MAGRAY_SESSION_BUDGET";
+
+/// Soft/hard pair for one kind of spend; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendLimit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soft: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hard: Option<f64>,
+}
+
+impl SpendLimit {
+    pub fn new(soft: Option<f64>, hard: Option<f64>) -> Self {
+        Self { soft, hard }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.soft.is_none() && self.hard.is_none()
+    }
+}
+
+/// Spend limits attached to a `WorkflowRequest` or a chat session
+///
+/// In YAML/TOML/JSON: `tokens: {soft: 20000, hard: 50000}`,
+/// `cost_usd: {hard: 1.0}`, `tool_calls: {soft: 20}`, `wall_clock_secs: {hard: 600}`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendBudget {
+    #[serde(default, skip_serializing_if = "SpendLimit::is_unlimited")]
+    pub tokens: SpendLimit,
+    #[serde(default, skip_serializing_if = "SpendLimit::is_unlimited")]
+    pub cost_usd: SpendLimit,
+    #[serde(default, skip_serializing_if = "SpendLimit::is_unlimited")]
+    pub tool_calls: SpendLimit,
+    #[serde(default, skip_serializing_if = "SpendLimit::is_unlimited")]
+    pub wall_clock_secs: SpendLimit,
+}
+
+impl SpendBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set LLM token limits (prompt + completion)
+    pub fn token_limit(mut self, soft: Option<u64>, hard: Option<u64>) -> Self {
+        self.tokens = SpendLimit::new(soft.map(|v| v as f64), hard.map(|v| v as f64));
+        self
+    }
+
+    /// Set estimated LLM cost limits in USD
+    pub fn cost_limit(mut self, soft: Option<f64>, hard: Option<f64>) -> Self {
+        self.cost_usd = SpendLimit::new(soft, hard);
+        self
+    }
+
+    /// Set tool invocation limits
+    pub fn tool_call_limit(mut self, soft: Option<u64>, hard: Option<u64>) -> Self {
+        self.tool_calls = SpendLimit::new(soft.map(|v| v as f64), hard.map(|v| v as f64));
+        self
+    }
+
+    /// Set wall-clock time limits
+    pub fn wall_clock_limit(mut self, soft: Option<Duration>, hard: Option<Duration>) -> Self {
+        self.wall_clock_secs =
+            SpendLimit::new(soft.map(|d| d.as_secs_f64()), hard.map(|d| d.as_secs_f64()));
+        self
+    }
+
+    pub fn limit(&self, kind: SpendKind) -> &SpendLimit {
+        match kind {
+            SpendKind::Tokens => &self.tokens,
+            SpendKind::CostUsd => &self.cost_usd,
+            SpendKind::ToolCalls => &self.tool_calls,
+            SpendKind::WallClock => &self.wall_clock_secs,
+        }
+    }
+
+    fn limit_mut(&mut self, kind: SpendKind) -> &mut SpendLimit {
+        match kind {
+            SpendKind::Tokens => &mut self.tokens,
+            SpendKind::CostUsd => &mut self.cost_usd,
+            SpendKind::ToolCalls => &mut self.tool_calls,
+            SpendKind::WallClock => &mut self.wall_clock_secs,
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        SpendKind::ALL
+            .iter()
+            .all(|kind| self.limit(*kind).is_unlimited())
+    }
+
+    /// Limits set in `other` replace the ones in `self`
+    pub fn merge(mut self, other: &SpendBudget) -> Self {
+        for kind in SpendKind::ALL {
+            let limit = other.limit(kind);
+            let target = self.limit_mut(kind);
+            if limit.soft.is_some() {
+                target.soft = limit.soft;
+            }
+            if limit.hard.is_some() {
+                target.hard = limit.hard;
+            }
+        }
+        self
+    }
+
+    /// Session budget from [`SESSION_BUDGET_ENV`]; `None` when unset or invalid
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var(SESSION_BUDGET_ENV).ok()?;
+        match spec.parse() {
+            Ok(budget) => Some(budget),
+            Err(e) => {
+                warn!("Ignoring {}: {}", SESSION_BUDGET_ENV, e);
+                None
+            }
+        }
+    }
+}
+
+impl FromStr for SpendBudget {
+    type Err = String;
+
+    /// Comma-separated `KIND=[SOFT/]HARD` or `KIND=SOFT/` entries, where KIND is
+    /// `tokens`, `cost`, `tools` or `time` (e.g. `tokens=20000/50000,cost=1.5,time=10m`)
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut budget = SpendBudget::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("'{entry}' is not KIND=[SOFT/]HARD"))?;
+            let kind = match name.trim() {
+                "tokens" => SpendKind::Tokens,
+                "cost" | "cost_usd" | "usd" => SpendKind::CostUsd,
+                "tools" | "tool_calls" => SpendKind::ToolCalls,
+                "time" | "wall_clock" => SpendKind::WallClock,
+                other => {
+                    return Err(format!(
+                        "unknown budget '{other}' (expected tokens, cost, tools or time)"
+                    ))
+                }
+            };
+            let (soft, hard) = match value.split_once('/') {
+                Some((soft, hard)) => (soft.trim(), hard.trim()),
+                None => ("", value.trim()),
+            };
+            let parse = |text: &str| -> Result<Option<f64>, String> {
+                if text.is_empty() {
+                    return Ok(None);
+                }
+                let amount = if kind == SpendKind::WallClock {
+                    parse_duration(text).map(|d| d.as_secs_f64())
+                } else {
+                    text.trim_start_matches('$').parse::<f64>().ok()
+                };
+                match amount {
+                    Some(amount) if amount >= 0.0 => Ok(Some(amount)),
+                    _ => Err(format!("invalid {kind} limit '{text}'")),
+                }
+            };
+            let limit = SpendLimit::new(parse(soft)?, parse(hard)?);
+            if let (Some(soft), Some(hard)) = (limit.soft, limit.hard) {
+                if soft > hard {
+                    return Err(format!("{kind} soft limit is above the hard limit"));
+                }
+            }
+            *budget.limit_mut(kind) = limit;
+        }
+        Ok(budget)
+    }
+}
+
+/// What a limit applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpendKind {
+    Tokens,
+    CostUsd,
+    ToolCalls,
+    WallClock,
+}
+
+impl SpendKind {
+    pub const ALL: [SpendKind; 4] = [
+        SpendKind::Tokens,
+        SpendKind::CostUsd,
+        SpendKind::ToolCalls,
+        SpendKind::WallClock,
+    ];
+
+    /// Human-readable amount of this kind
+    pub fn format_amount(&self, amount: f64) -> String {
+        match self {
+            SpendKind::Tokens => format_count(amount, "tok"),
+            SpendKind::CostUsd => format!("${amount:.4}"),
+            SpendKind::ToolCalls => format!("{amount:.0} tools"),
+            SpendKind::WallClock => format_secs(amount),
+        }
+    }
+}
+
+impl fmt::Display for SpendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendKind::Tokens => write!(f, "token"),
+            SpendKind::CostUsd => write!(f, "cost"),
+            SpendKind::ToolCalls => write!(f, "tool call"),
+            SpendKind::WallClock => write!(f, "wall-clock"),
+        }
+    }
+}
+
+fn format_count(amount: f64, unit: &str) -> String {
+    if amount >= 1_000_000.0 {
+        format!("{:.1}M {unit}", amount / 1_000_000.0)
+    } else if amount >= 1_000.0 {
+        format!("{:.1}k {unit}", amount / 1_000.0)
+    } else {
+        format!("{amount:.0} {unit}")
+    }
+}
+
+fn format_secs(secs: f64) -> String {
+    let secs = secs as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Accumulated spend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendUsage {
+    pub tokens: u64,
+    pub cost_usd: f64,
+    pub tool_calls: u64,
+    pub elapsed_ms: u64,
+}
+
+impl SpendUsage {
+    pub fn amount(&self, kind: SpendKind) -> f64 {
+        match kind {
+            SpendKind::Tokens => self.tokens as f64,
+            SpendKind::CostUsd => self.cost_usd,
+            SpendKind::ToolCalls => self.tool_calls as f64,
+            SpendKind::WallClock => self.elapsed_ms as f64 / 1000.0,
+        }
+    }
+}
+
+/// Usage together with the limits it is measured against; persisted with
+/// the workflow state so other processes can report live spend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendReport {
+    pub budget: SpendBudget,
+    pub usage: SpendUsage,
+}
+
+impl SpendReport {
+    /// One-line summary, e.g. `12.3k tok · $0.0400/$1.0000 · 5 tools · 1m20s/10m00s`
+    pub fn summary(&self) -> String {
+        SpendKind::ALL
+            .iter()
+            .map(|kind| {
+                let used = kind.format_amount(self.usage.amount(*kind));
+                let limit = self.budget.limit(*kind);
+                match limit.hard.or(limit.soft) {
+                    Some(cap) => format!("{used}/{}", kind.format_amount(cap)),
+                    None => used,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// A limit that has been reached
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpendOverrun {
+    pub kind: SpendKind,
+    pub used: f64,
+    pub limit: f64,
+    /// Hard limit, or a soft limit the approver declined to pass
+    pub hard: bool,
+}
+
+impl fmt::Display for SpendOverrun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} budget exceeded: {} of {} ({} limit)",
+            self.kind,
+            self.kind.format_amount(self.used),
+            self.kind.format_amount(self.limit),
+            if self.hard { "hard" } else { "soft" }
+        )
+    }
+}
+
+impl std::error::Error for SpendOverrun {}
+
+/// Result of [`SpendTracker::check`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpendCheck {
+    Within,
+    /// Soft limits reached and not yet approved
+    Soft(Vec<SpendOverrun>),
+    Hard(SpendOverrun),
+}
+
+/// Decides whether a run may continue past its soft limits
+#[async_trait]
+pub trait SpendApprover: Send + Sync {
+    async fn approve_overrun(&self, overruns: &[SpendOverrun], report: &SpendReport) -> bool;
+}
+
+/// Live spend of one workflow or chat session
+pub struct SpendTracker {
+    budget: SpendBudget,
+    started: Instant,
+    tokens: AtomicU64,
+    cost_micro_usd: AtomicU64,
+    tool_calls: AtomicU64,
+    /// Soft limits the approver allowed to pass
+    approved: Mutex<Vec<SpendKind>>,
+    /// Set once a soft overrun is declined; the run stays stopped
+    declined: Mutex<Option<SpendOverrun>>,
+    approver: Option<Arc<dyn SpendApprover>>,
+}
+
+impl fmt::Debug for SpendTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpendTracker")
+            .field("budget", &self.budget)
+            .field("usage", &self.usage())
+            .finish()
+    }
+}
+
+impl SpendTracker {
+    pub fn new(budget: SpendBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            tokens: AtomicU64::new(0),
+            cost_micro_usd: AtomicU64::new(0),
+            tool_calls: AtomicU64::new(0),
+            approved: Mutex::new(Vec::new()),
+            declined: Mutex::new(None),
+            approver: None,
+        }
+    }
+
+    /// Continue counting from previously persisted usage (resumed workflows)
+    pub fn with_usage(mut self, usage: SpendUsage) -> Self {
+        self.tokens = AtomicU64::new(usage.tokens);
+        self.cost_micro_usd = AtomicU64::new((usage.cost_usd * 1_000_000.0).round() as u64);
+        self.tool_calls = AtomicU64::new(usage.tool_calls);
+        self.started = Instant::now()
+            .checked_sub(Duration::from_millis(usage.elapsed_ms))
+            .unwrap_or(self.started);
+        self
+    }
+
+    /// Ask `approver` before passing soft limits; without one, soft limits
+    /// only log a warning
+    pub fn with_approver(mut self, approver: Arc<dyn SpendApprover>) -> Self {
+        self.approver = Some(approver);
+        self
+    }
+
+    pub fn budget(&self) -> &SpendBudget {
+        &self.budget
+    }
+
+    pub fn record_llm(&self, tokens: u64, cost_usd: f64) {
+        self.tokens.fetch_add(tokens, Ordering::Relaxed);
+        self.cost_micro_usd.fetch_add(
+            (cost_usd.max(0.0) * 1_000_000.0).round() as u64,
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn record_tool_call(&self) {
+        self.tool_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add the tokens, cost and tool calls of a sub-run (e.g. a delegated
+    /// sub-agent with its own budget); its wall-clock time overlaps ours
+    pub fn record_usage(&self, usage: &SpendUsage) {
+        self.record_llm(usage.tokens, usage.cost_usd);
+        self.tool_calls
+            .fetch_add(usage.tool_calls, Ordering::Relaxed);
+    }
+
+    pub fn usage(&self) -> SpendUsage {
+        SpendUsage {
+            tokens: self.tokens.load(Ordering::Relaxed),
+            cost_usd: self.cost_micro_usd.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+            tool_calls: self.tool_calls.load(Ordering::Relaxed),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+
+    pub fn report(&self) -> SpendReport {
+        SpendReport {
+            budget: self.budget,
+            usage: self.usage(),
+        }
+    }
+
+    /// Compare usage against the limits; a limit counts as crossed once it is
+    /// reached, so `tools=3` allows exactly three tool calls
+    pub fn check(&self) -> SpendCheck {
+        if let Some(declined) = *self.declined.lock() {
+            return SpendCheck::Hard(declined);
+        }
+        let usage = self.usage();
+        let approved = self.approved.lock();
+        let mut soft = Vec::new();
+        for kind in SpendKind::ALL {
+            let used = usage.amount(kind);
+            let limit = self.budget.limit(kind);
+            if let Some(hard) = limit.hard.filter(|hard| used >= *hard) {
+                return SpendCheck::Hard(SpendOverrun {
+                    kind,
+                    used,
+                    limit: hard,
+                    hard: true,
+                });
+            }
+            if let Some(limit) = limit.soft.filter(|soft| used >= *soft) {
+                if !approved.contains(&kind) {
+                    soft.push(SpendOverrun {
+                        kind,
+                        used,
+                        limit,
+                        hard: false,
+                    });
+                }
+            }
+        }
+        if soft.is_empty() {
+            SpendCheck::Within
+        } else {
+            SpendCheck::Soft(soft)
+        }
+    }
+
+    /// Fail only on hard limits (used where the caller cannot ask)
+    pub fn check_hard(&self) -> Result<(), SpendOverrun> {
+        match self.check() {
+            SpendCheck::Hard(overrun) => Err(overrun),
+            SpendCheck::Within | SpendCheck::Soft(_) => Ok(()),
+        }
+    }
+
+    /// Let the run pass these soft limits
+    pub fn approve(&self, overruns: &[SpendOverrun]) {
+        let mut approved = self.approved.lock();
+        for overrun in overruns {
+            if !approved.contains(&overrun.kind) {
+                approved.push(overrun.kind);
+            }
+        }
+    }
+
+    /// Stop the run at these soft limits
+    pub fn decline(&self, overruns: &[SpendOverrun]) {
+        if let Some(first) = overruns.first() {
+            *self.declined.lock() = Some(SpendOverrun {
+                hard: true,
+                ..*first
+            });
+        }
+    }
+
+    /// Check limits before the next unit of work: hard limits fail, soft
+    /// limits ask the approver once per kind
+    pub async fn enforce(&self) -> Result<(), SpendOverrun> {
+        let overruns = match self.check() {
+            SpendCheck::Within => return Ok(()),
+            SpendCheck::Hard(overrun) => return Err(overrun),
+            SpendCheck::Soft(overruns) => overruns,
+        };
+
+        let approved = match &self.approver {
+            Some(approver) => approver.approve_overrun(&overruns, &self.report()).await,
+            None => {
+                for overrun in &overruns {
+                    warn!("{}", overrun);
+                }
+                true
+            }
+        };
+        if approved {
+            self.approve(&overruns);
+            Ok(())
+        } else {
+            self.decline(&overruns);
+            Err(overruns[0])
+        }
+    }
+}
+
+impl llm::LlmUsageObserver for SpendTracker {
+    fn before_call(&self) -> anyhow::Result<()> {
+        self.check_hard().map_err(anyhow::Error::from)
+    }
+
+    fn record(&self, usage: &llm::LlmCallUsage) {
+        self.record_llm(usage.total_tokens(), usage.cost_usd);
+    }
+}
+
+tokio::task_local! {
+    /// Workflow whose tracker is charged for LLM calls made in this task
+    static SPEND_SCOPE: Uuid;
+}
+
+/// Trackers of the running workflows and sessions; as an LLM usage observer
+/// it charges each call to the tracker of the workflow it was made for
+/// (see [`SpendLedger::scope`])
+#[derive(Debug, Default)]
+pub struct SpendLedger {
+    trackers: DashMap<Uuid, Arc<SpendTracker>>,
+}
+
+impl SpendLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn attach(&self, id: Uuid, tracker: Arc<SpendTracker>) {
+        self.trackers.insert(id, tracker);
+    }
+
+    pub fn detach(&self, id: Uuid) -> Option<Arc<SpendTracker>> {
+        self.trackers.remove(&id).map(|(_, tracker)| tracker)
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<Arc<SpendTracker>> {
+        self.trackers
+            .get(&id)
+            .map(|entry| Arc::clone(entry.value()))
+    }
+
+    pub fn report(&self, id: Uuid) -> Option<SpendReport> {
+        self.get(id).map(|tracker| tracker.report())
+    }
+
+    /// Reports of everything currently attached
+    pub fn active(&self) -> Vec<(Uuid, SpendReport)> {
+        self.trackers
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().report()))
+            .collect()
+    }
+
+    /// Run `future` on behalf of the workflow `id`: LLM calls and executor
+    /// steps inside it are charged to the tracker attached under `id`
+    pub async fn scope<F: std::future::Future>(id: Uuid, future: F) -> F::Output {
+        SPEND_SCOPE.scope(id, future).await
+    }
+
+    /// Tracker of the workflow the current task runs for, if any
+    pub fn current(&self) -> Option<Arc<SpendTracker>> {
+        SPEND_SCOPE
+            .try_with(|id| *id)
+            .ok()
+            .and_then(|id| self.get(id))
+    }
+}
+
+impl llm::LlmUsageObserver for SpendLedger {
+    fn before_call(&self) -> anyhow::Result<()> {
+        if let Some(tracker) = self.current() {
+            tracker.check_hard()?;
+        }
+        Ok(())
+    }
+
+    fn record(&self, usage: &llm::LlmCallUsage) {
+        match self.current() {
+            Some(tracker) => tracker.record_llm(usage.total_tokens(), usage.cost_usd),
+            None => debug!(
+                tokens = usage.total_tokens(),
+                "LLM call outside a workflow scope is not charged"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Approve(bool);
+
+    #[async_trait]
+    impl SpendApprover for Approve {
+        async fn approve_overrun(&self, _: &[SpendOverrun], _: &SpendReport) -> bool {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_parse_budget_spec() {
+        let budget: SpendBudget = "tokens=20000/50000, cost=$1.5, tools=10/, time=1m/10m"
+            .parse()
+            .expect("budget spec should parse");
+
+        assert_eq!(budget.tokens, SpendLimit::new(Some(20000.0), Some(50000.0)));
+        assert_eq!(budget.cost_usd, SpendLimit::new(None, Some(1.5)));
+        assert_eq!(budget.tool_calls, SpendLimit::new(Some(10.0), None));
+        assert_eq!(
+            budget.wall_clock_secs,
+            SpendLimit::new(Some(60.0), Some(600.0))
+        );
+
+        assert!("tokens=100/10".parse::<SpendBudget>().is_err());
+        assert!("bananas=1".parse::<SpendBudget>().is_err());
+        assert!("cost=-1".parse::<SpendBudget>().is_err());
+    }
+
+    #[test]
+    fn test_budget_deserializes_from_yaml() {
+        let budget: SpendBudget =
+            serde_yaml::from_str("tokens: {soft: 100}\ncost_usd: {hard: 0.5}\n")
+                .expect("budget yaml should parse");
+
+        assert_eq!(budget.tokens.soft, Some(100.0));
+        assert_eq!(budget.cost_usd.hard, Some(0.5));
+        assert!(budget.tool_calls.is_unlimited());
+    }
+
+    #[test]
+    fn test_hard_limit_stops_at_limit() {
+        let tracker = SpendTracker::new(SpendBudget::new().tool_call_limit(None, Some(2)));
+        tracker.record_tool_call();
+        assert_eq!(tracker.check(), SpendCheck::Within);
+
+        tracker.record_tool_call();
+        let overrun = tracker
+            .check_hard()
+            .expect_err("second call reaches the limit");
+        assert_eq!(overrun.kind, SpendKind::ToolCalls);
+        assert!(overrun.hard);
+    }
+
+    #[tokio::test]
+    async fn test_soft_limit_asks_once() {
+        let tracker = SpendTracker::new(SpendBudget::new().token_limit(Some(100), Some(1000)))
+            .with_approver(Arc::new(Approve(true)));
+        tracker.record_llm(150, 0.01);
+        assert!(matches!(tracker.check(), SpendCheck::Soft(ref o) if o.len() == 1));
+
+        tracker
+            .enforce()
+            .await
+            .expect("approved soft overrun should pass");
+        assert_eq!(tracker.check(), SpendCheck::Within);
+
+        tracker.record_llm(900, 0.01);
+        assert!(tracker.enforce().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_declined_soft_limit_stops_run() {
+        let tracker = SpendTracker::new(SpendBudget::new().cost_limit(Some(0.01), None))
+            .with_approver(Arc::new(Approve(false)));
+        tracker.record_llm(10, 0.02);
+
+        let overrun = tracker
+            .enforce()
+            .await
+            .expect_err("declined overrun should stop");
+        assert_eq!(overrun.kind, SpendKind::CostUsd);
+        assert!(matches!(tracker.check(), SpendCheck::Hard(_)));
+    }
+
+    #[tokio::test]
+    async fn test_ledger_charges_only_the_scoped_workflow() {
+        use llm::LlmUsageObserver;
+
+        let ledger = SpendLedger::new();
+        let (first_id, second_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let first = Arc::new(SpendTracker::new(
+            SpendBudget::new().token_limit(None, Some(500)),
+        ));
+        let second = Arc::new(SpendTracker::new(SpendBudget::new()));
+        ledger.attach(first_id, Arc::clone(&first));
+        ledger.attach(second_id, Arc::clone(&second));
+        let usage = llm::LlmCallUsage {
+            provider: llm::ProviderType::OpenAI,
+            model: "gpt-4o-mini".to_string(),
+            prompt_tokens: 400,
+            completion_tokens: 200,
+            cost_usd: 0.002,
+            estimated: false,
+        };
+
+        SpendLedger::scope(first_id, async { ledger.record(&usage) }).await;
+        // Outside any workflow nothing is charged
+        ledger.record(&usage);
+
+        assert_eq!(first.usage().tokens, 600);
+        assert_eq!(second.usage().tokens, 0);
+        assert!(SpendLedger::scope(first_id, async { ledger.before_call() })
+            .await
+            .is_err());
+        assert!(
+            SpendLedger::scope(second_id, async { ledger.before_call() })
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_resumed_usage_and_summary() {
+        let tracker = SpendTracker::new(SpendBudget::new().cost_limit(None, Some(1.0))).with_usage(
+            SpendUsage {
+                tokens: 12_345,
+                cost_usd: 0.04,
+                tool_calls: 5,
+                elapsed_ms: 80_000,
+            },
+        );
+        let summary = tracker.report().summary();
+
+        assert!(summary.starts_with("12.3k tok · $0.0400/$1.0000 · 5 tools · 1m2"));
+    }
+}
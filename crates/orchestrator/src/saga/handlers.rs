@@ -0,0 +1,549 @@
+//! Built-in compensation handlers for file, memory and tool side effects.
+//!
+//! [`DefaultSagaManager`](super::DefaultSagaManager) dispatches
+//! `FileOperationUndo`, `MemoryOperationUndo` and `ToolExecutionUndo` steps to
+//! the handler registered under [`FILE_UNDO_HANDLER`], [`MEMORY_UNDO_HANDLER`]
+//! and [`TOOL_UNDO_HANDLER`]. The file handler is registered by default; the
+//! memory and tool handlers need a backend and are registered by the caller.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tools::manifest::{InverseOperation, ToolManifest};
+use uuid::Uuid;
+
+use super::{CompensationHandler, CompensationStep, CompensationType, SagaStep};
+use crate::agents::executor::ToolInvoker;
+
+pub const FILE_UNDO_HANDLER: &str = "file_operation_undo";
+pub const MEMORY_UNDO_HANDLER: &str = "memory_operation_undo";
+pub const TOOL_UNDO_HANDLER: &str = "tool_execution_undo";
+
+/// Parameter key under which the original step output is recorded on a
+/// compensation step (used to resolve `{{result.*}}` in inverse arguments)
+pub const ORIGINAL_OUTPUT_PARAM: &str = "original_output";
+
+/// Returned by a handler when the step left nothing it can undo; the
+/// compensation is reported as `Skipped` rather than failed
+#[derive(Debug, thiserror::Error)]
+#[error("nothing to compensate: {0}")]
+pub struct NothingToCompensate(pub String);
+
+/// Returned when the step had side effects the handler cannot undo; the
+/// compensation is reported as `NotCompensable` and the rollback fails
+#[derive(Debug, thiserror::Error)]
+#[error("not compensable: {0}")]
+pub struct NotCompensable(pub String);
+
+const SNAPSHOT_MANIFEST: &str = "snapshot.json";
+
+fn create_private_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("cannot create snapshot dir {}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("cannot restrict snapshot dir {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotEntry {
+    path: String,
+    /// `None` if the file did not exist before the step
+    blob: Option<String>,
+}
+
+/// Pre-step copies of files a step is about to modify
+#[derive(Debug, Clone)]
+pub struct FileSnapshotStore {
+    root: PathBuf,
+}
+
+impl FileSnapshotStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `$MAGRAY_HOME/orchestrator/saga-snapshots` (`~/.magray/...` by
+    /// default), next to the workflow database
+    pub fn default_root() -> PathBuf {
+        std::env::var("MAGRAY_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                dirs::home_dir()
+                    .unwrap_or_else(|| PathBuf::from("."))
+                    .join(".magray")
+            })
+            .join("orchestrator")
+            .join("saga-snapshots")
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Copy `files` into a fresh snapshot directory keyed by `key`.
+    /// The returned path is what `FileOperationUndo::backup_data` carries.
+    pub fn snapshot(&self, key: Uuid, files: &[String]) -> Result<PathBuf> {
+        // Snapshots hold copies of user files: owner-only access
+        let dir = self.root.join(key.to_string());
+        create_private_dir(&self.root)?;
+        create_private_dir(&dir)?;
+
+        let mut entries = Vec::with_capacity(files.len());
+        for (index, file) in files.iter().enumerate() {
+            let path = Path::new(file);
+            let blob = if path.is_dir() {
+                bail!("cannot snapshot directory {file}");
+            } else if path.exists() {
+                let blob = format!("{index}.blob");
+                std::fs::copy(path, dir.join(&blob))
+                    .with_context(|| format!("cannot snapshot {file}"))?;
+                Some(blob)
+            } else {
+                None
+            };
+            entries.push(SnapshotEntry {
+                path: file.clone(),
+                blob,
+            });
+        }
+        std::fs::write(
+            dir.join(SNAPSHOT_MANIFEST),
+            serde_json::to_vec_pretty(&entries)?,
+        )?;
+        Ok(dir)
+    }
+
+    /// Put every snapshotted path back: restore the previous content, or
+    /// remove files that did not exist before the step. Returns paths touched.
+    pub fn restore(snapshot_dir: &Path) -> Result<usize> {
+        let raw = std::fs::read(snapshot_dir.join(SNAPSHOT_MANIFEST)).with_context(|| {
+            format!(
+                "snapshot {} is missing or unreadable",
+                snapshot_dir.display()
+            )
+        })?;
+        let entries: Vec<SnapshotEntry> = serde_json::from_slice(&raw)?;
+
+        for entry in &entries {
+            let target = Path::new(&entry.path);
+            match &entry.blob {
+                Some(blob) => {
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::copy(snapshot_dir.join(blob), target)
+                        .with_context(|| format!("cannot restore {}", entry.path))?;
+                }
+                None => {
+                    if target.is_file() {
+                        std::fs::remove_file(target)
+                            .with_context(|| format!("cannot remove {}", entry.path))?;
+                    }
+                }
+            }
+        }
+        Ok(entries.len())
+    }
+
+    /// Drop a snapshot once it can no longer be needed
+    pub fn discard(snapshot_dir: &Path) {
+        if let Err(e) = std::fs::remove_dir_all(snapshot_dir) {
+            tracing::debug!(
+                "Failed to remove snapshot {}: {}",
+                snapshot_dir.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Restores files from the snapshot taken before the step ran
+#[derive(Debug, Default)]
+pub struct FileUndoHandler;
+
+#[async_trait]
+impl CompensationHandler for FileUndoHandler {
+    async fn compensate(&self, step: &CompensationStep, _original_step: &SagaStep) -> Result<()> {
+        let CompensationType::FileOperationUndo {
+            affected_files,
+            backup_data,
+            ..
+        } = &step.compensation_type
+        else {
+            bail!(
+                "{} cannot handle {:?}",
+                FILE_UNDO_HANDLER,
+                step.compensation_type
+            );
+        };
+        let snapshot = backup_data
+            .as_deref()
+            .ok_or_else(|| anyhow!("no pre-step snapshot of {}", affected_files.join(", ")))?;
+
+        let snapshot = Path::new(snapshot);
+        let restored = FileSnapshotStore::restore(snapshot)?;
+        FileSnapshotStore::discard(snapshot);
+        tracing::info!("Restored {} file(s) from {}", restored, snapshot.display());
+        Ok(())
+    }
+
+    fn handler_name(&self) -> &str {
+        FILE_UNDO_HANDLER
+    }
+}
+
+/// Memory backend operations needed to undo memory steps
+#[async_trait]
+pub trait MemoryUndoTarget: Send + Sync {
+    /// Remove a record inserted by the compensated step; `false` if absent
+    async fn delete_record(&self, id: Uuid) -> Result<bool>;
+
+    /// Write back a record captured before the step changed or removed it
+    async fn restore_record(&self, record: &serde_json::Value) -> Result<()>;
+}
+
+/// Deletes inserted records and restores updated/deleted ones from
+/// `backup_data` (a record or an array of records)
+pub struct MemoryUndoHandler {
+    target: Arc<dyn MemoryUndoTarget>,
+}
+
+impl MemoryUndoHandler {
+    pub fn new(target: Arc<dyn MemoryUndoTarget>) -> Self {
+        Self { target }
+    }
+}
+
+#[async_trait]
+impl CompensationHandler for MemoryUndoHandler {
+    async fn compensate(&self, step: &CompensationStep, _original_step: &SagaStep) -> Result<()> {
+        let CompensationType::MemoryOperationUndo {
+            operation_type,
+            affected_records,
+            backup_data,
+        } = &step.compensation_type
+        else {
+            bail!(
+                "{} cannot handle {:?}",
+                MEMORY_UNDO_HANDLER,
+                step.compensation_type
+            );
+        };
+
+        match operation_type.to_ascii_lowercase().as_str() {
+            "store" | "insert" => {
+                if affected_records.is_empty() {
+                    return Err(
+                        NotCompensable("memory store reported no record ids".to_string()).into(),
+                    );
+                }
+                for id in affected_records {
+                    if !self.target.delete_record(*id).await? {
+                        tracing::warn!("Memory record {} was already gone", id);
+                    }
+                }
+            }
+            "update" | "delete" => {
+                let records: Vec<&serde_json::Value> = match backup_data {
+                    Some(serde_json::Value::Array(items)) => items.iter().collect(),
+                    Some(record) => vec![record],
+                    None => bail!("memory {operation_type} has no backup to restore from"),
+                };
+                for record in records {
+                    self.target.restore_record(record).await?;
+                }
+            }
+            "search" => {
+                return Err(NothingToCompensate("memory search is read-only".to_string()).into())
+            }
+            other => bail!("unknown memory operation '{other}'"),
+        }
+        Ok(())
+    }
+
+    fn handler_name(&self) -> &str {
+        MEMORY_UNDO_HANDLER
+    }
+}
+
+/// Calls the inverse operation declared in a tool's manifest
+#[derive(Default)]
+pub struct ToolUndoHandler {
+    tools: HashMap<String, Arc<dyn ToolInvoker>>,
+    inverses: HashMap<String, InverseOperation>,
+}
+
+impl ToolUndoHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make a tool callable as an inverse operation
+    pub fn register_tool(&mut self, name: impl Into<String>, invoker: Arc<dyn ToolInvoker>) {
+        self.tools.insert(name.into(), invoker);
+    }
+
+    /// Take the inverse operation (if any) from a tool manifest
+    pub fn register_manifest(&mut self, manifest: &ToolManifest) {
+        if let Some(inverse) = &manifest.inverse {
+            self.declare_inverse(manifest.name.clone(), inverse.clone());
+        }
+    }
+
+    pub fn declare_inverse(&mut self, tool: impl Into<String>, inverse: InverseOperation) {
+        self.inverses.insert(tool.into(), inverse);
+    }
+
+    pub fn has_inverse(&self, tool: &str) -> bool {
+        self.inverses.contains_key(tool)
+    }
+}
+
+#[async_trait]
+impl CompensationHandler for ToolUndoHandler {
+    async fn compensate(&self, step: &CompensationStep, _original_step: &SagaStep) -> Result<()> {
+        let CompensationType::ToolExecutionUndo {
+            tool_name,
+            original_arguments,
+            ..
+        } = &step.compensation_type
+        else {
+            bail!(
+                "{} cannot handle {:?}",
+                TOOL_UNDO_HANDLER,
+                step.compensation_type
+            );
+        };
+
+        let Some(inverse) = self.inverses.get(tool_name) else {
+            return Err(NotCompensable(format!(
+                "tool '{tool_name}' declares no inverse operation"
+            ))
+            .into());
+        };
+        let inverse_tool = inverse.tool.as_deref().unwrap_or(tool_name);
+        let invoker = self
+            .tools
+            .get(inverse_tool)
+            .ok_or_else(|| anyhow!("inverse tool '{inverse_tool}' is not available"))?;
+
+        let arguments = inverse
+            .resolve_arguments(
+                original_arguments,
+                step.parameters.get(ORIGINAL_OUTPUT_PARAM),
+            )
+            .map_err(|e| anyhow!("inverse of '{tool_name}': {e}"))?;
+        invoker
+            .invoke(arguments)
+            .await
+            .with_context(|| format!("inverse '{inverse_tool}' of '{tool_name}' failed"))?;
+        tracing::info!("Compensated '{}' with '{}'", tool_name, inverse_tool);
+        Ok(())
+    }
+
+    fn handler_name(&self) -> &str {
+        TOOL_UNDO_HANDLER
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::saga::{CompensationStatus, SagaStepStatus};
+
+    fn saga_step() -> SagaStep {
+        SagaStep {
+            id: Uuid::new_v4(),
+            action_step_id: Uuid::new_v4(),
+            status: SagaStepStatus::Completed,
+            result: None,
+            error: None,
+            compensation_needed: true,
+            executed_at: None,
+            compensated_at: None,
+        }
+    }
+
+    fn compensation(compensation_type: CompensationType) -> CompensationStep {
+        CompensationStep {
+            id: Uuid::new_v4(),
+            saga_step_id: Uuid::new_v4(),
+            compensation_type,
+            parameters: HashMap::new(),
+            status: CompensationStatus::Pending,
+            executed_at: None,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn file_undo_restores_modified_and_removes_created_files() {
+        let dir = tempfile::tempdir().expect("Operation failed - converted from unwrap()");
+        let modified = dir.path().join("modified.txt");
+        let created = dir.path().join("created.txt");
+        std::fs::write(&modified, "before").expect("Operation failed - converted from unwrap()");
+
+        let files = vec![
+            modified.display().to_string(),
+            created.display().to_string(),
+        ];
+        let store = FileSnapshotStore::new(dir.path().join("snapshots"));
+        let snapshot = store
+            .snapshot(Uuid::new_v4(), &files)
+            .expect("Snapshot should succeed");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for dir in [store.root(), snapshot.as_path()] {
+                let mode = std::fs::metadata(dir)
+                    .expect("Operation failed - converted from unwrap()")
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o700, "{}", dir.display());
+            }
+        }
+
+        std::fs::write(&modified, "after").expect("Operation failed - converted from unwrap()");
+        std::fs::write(&created, "new").expect("Operation failed - converted from unwrap()");
+
+        let step = compensation(CompensationType::FileOperationUndo {
+            operation_type: "file_write".to_string(),
+            affected_files: files,
+            backup_data: Some(snapshot.display().to_string()),
+        });
+        FileUndoHandler
+            .compensate(&step, &saga_step())
+            .await
+            .expect("Compensation should succeed");
+
+        assert_eq!(
+            std::fs::read_to_string(&modified).expect("Operation failed - converted from unwrap()"),
+            "before"
+        );
+        assert!(!created.exists());
+        assert!(!snapshot.exists());
+    }
+
+    struct RecordingTarget {
+        deleted: parking_lot::Mutex<Vec<Uuid>>,
+        restored: parking_lot::Mutex<Vec<serde_json::Value>>,
+    }
+
+    #[async_trait]
+    impl MemoryUndoTarget for RecordingTarget {
+        async fn delete_record(&self, id: Uuid) -> Result<bool> {
+            self.deleted.lock().push(id);
+            Ok(true)
+        }
+
+        async fn restore_record(&self, record: &serde_json::Value) -> Result<()> {
+            self.restored.lock().push(record.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_undo_deletes_inserts_and_restores_backups() {
+        let target = Arc::new(RecordingTarget {
+            deleted: parking_lot::Mutex::new(Vec::new()),
+            restored: parking_lot::Mutex::new(Vec::new()),
+        });
+        let handler = MemoryUndoHandler::new(target.clone());
+        let inserted = Uuid::new_v4();
+
+        let store = compensation(CompensationType::MemoryOperationUndo {
+            operation_type: "Store".to_string(),
+            affected_records: vec![inserted],
+            backup_data: None,
+        });
+        handler
+            .compensate(&store, &saga_step())
+            .await
+            .expect("Compensation should succeed");
+        assert_eq!(*target.deleted.lock(), vec![inserted]);
+
+        let backup = serde_json::json!({ "id": Uuid::new_v4(), "text": "old" });
+        let update = compensation(CompensationType::MemoryOperationUndo {
+            operation_type: "Update".to_string(),
+            affected_records: vec![],
+            backup_data: Some(serde_json::json!([backup.clone()])),
+        });
+        handler
+            .compensate(&update, &saga_step())
+            .await
+            .expect("Compensation should succeed");
+        assert_eq!(*target.restored.lock(), vec![backup]);
+
+        let no_backup = compensation(CompensationType::MemoryOperationUndo {
+            operation_type: "Delete".to_string(),
+            affected_records: vec![],
+            backup_data: None,
+        });
+        assert!(handler.compensate(&no_backup, &saga_step()).await.is_err());
+    }
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl ToolInvoker for EchoTool {
+        async fn invoke(
+            &self,
+            args: HashMap<String, serde_json::Value>,
+        ) -> Result<serde_json::Value> {
+            if args.get("id") != Some(&serde_json::json!(7)) {
+                bail!("unexpected arguments {args:?}");
+            }
+            Ok(serde_json::json!({ "deleted": true }))
+        }
+
+        fn get_name(&self) -> &str {
+            "ticket_delete"
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_undo_calls_declared_inverse() {
+        let mut handler = ToolUndoHandler::new();
+        handler.register_tool("ticket_delete", Arc::new(EchoTool));
+        handler.declare_inverse(
+            "ticket_create",
+            InverseOperation {
+                tool: Some("ticket_delete".to_string()),
+                arguments: HashMap::from([("id".to_string(), serde_json::json!("{{result.id}}"))]),
+            },
+        );
+
+        let mut step = compensation(CompensationType::ToolExecutionUndo {
+            tool_name: "ticket_create".to_string(),
+            original_arguments: HashMap::new(),
+            undo_arguments: HashMap::new(),
+        });
+        step.parameters.insert(
+            ORIGINAL_OUTPUT_PARAM.to_string(),
+            serde_json::json!({ "id": 7 }),
+        );
+        handler
+            .compensate(&step, &saga_step())
+            .await
+            .expect("Compensation should succeed");
+
+        let unknown = compensation(CompensationType::ToolExecutionUndo {
+            tool_name: "shell_exec".to_string(),
+            original_arguments: HashMap::new(),
+            undo_arguments: HashMap::new(),
+        });
+        let err = handler
+            .compensate(&unknown, &saga_step())
+            .await
+            .expect_err("No inverse is declared");
+        assert!(err.downcast_ref::<NotCompensable>().is_some());
+    }
+}
@@ -2,10 +2,21 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::agents::dag::is_read_only_tool;
 use crate::agents::executor::{ExecutionError, StepResult};
-use crate::agents::planner::{ActionPlan, ActionStep};
+use crate::agents::planner::{ActionPlan, ActionStep, ActionStepType};
+use crate::persistence::WorkflowStore;
+
+pub mod handlers;
+
+pub use handlers::{
+    FileSnapshotStore, FileUndoHandler, MemoryUndoHandler, MemoryUndoTarget, NotCompensable,
+    NothingToCompensate, ToolUndoHandler, FILE_UNDO_HANDLER, MEMORY_UNDO_HANDLER,
+    TOOL_UNDO_HANDLER,
+};
 
 /// Saga transaction for managing multi-agent workflows with compensation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +127,45 @@ pub enum CompensationType {
     },
 }
 
+impl CompensationType {
+    /// Name of the built-in handler for this compensation, if any
+    pub fn handler_name(&self) -> Option<&str> {
+        match self {
+            CompensationType::FileOperationUndo { .. } => Some(FILE_UNDO_HANDLER),
+            CompensationType::MemoryOperationUndo { .. } => Some(MEMORY_UNDO_HANDLER),
+            CompensationType::ToolExecutionUndo { .. } => Some(TOOL_UNDO_HANDLER),
+            CompensationType::Custom { handler_name, .. } => Some(handler_name),
+            CompensationType::UserInteractionUndo { .. } => None,
+        }
+    }
+
+    /// One-line summary for compensation reports
+    pub fn describe(&self) -> String {
+        match self {
+            CompensationType::FileOperationUndo {
+                operation_type,
+                affected_files,
+                ..
+            } => format!("undo {operation_type}: {}", affected_files.join(", ")),
+            CompensationType::MemoryOperationUndo {
+                operation_type,
+                affected_records,
+                ..
+            } => format!(
+                "undo memory {operation_type} ({} record(s))",
+                affected_records.len()
+            ),
+            CompensationType::ToolExecutionUndo { tool_name, .. } => {
+                format!("invert tool {tool_name}")
+            }
+            CompensationType::UserInteractionUndo {
+                interaction_type, ..
+            } => format!("notify cancelled {interaction_type}"),
+            CompensationType::Custom { handler_name, .. } => format!("custom {handler_name}"),
+        }
+    }
+}
+
 /// Status of compensation operation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum CompensationStatus {
@@ -129,6 +179,9 @@ pub enum CompensationStatus {
     Failed,
     /// Compensation was skipped (not needed)
     Skipped,
+    /// The step had side effects that cannot be undone (no inverse
+    /// operation or nothing recorded); counts as a failed compensation
+    NotCompensable,
 }
 
 /// Saga transaction manager
@@ -171,12 +224,39 @@ pub struct SagaCompensationResult {
     pub failed_compensations: u32,
     pub compensation_time: std::time::Duration,
     pub errors: Vec<String>,
+    /// What was undone for each compensated step, in compensation order
+    #[serde(default)]
+    pub report: Vec<CompensationReportEntry>,
+}
+
+/// Outcome of one compensation operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompensationReportEntry {
+    pub saga_step_id: Uuid,
+    pub action_step_id: Uuid,
+    pub description: String,
+    pub status: CompensationStatus,
+    pub error: Option<String>,
+}
+
+/// Runs the action steps of a saga against real tools and memory
+#[async_trait]
+pub trait SagaStepRunner: Send + Sync {
+    async fn run_step(&self, step: &ActionStep) -> Result<serde_json::Value>;
+
+    /// Whether the tool declares no side effects, so its steps need no
+    /// compensation; defaults to the built-in tool specs
+    fn is_read_only_tool(&self, tool_name: &str) -> bool {
+        is_read_only_tool(tool_name)
+    }
 }
 
 /// Implementation of Saga transaction manager
 pub struct DefaultSagaManager {
     active_sagas: dashmap::DashMap<Uuid, Saga>,
     compensation_handlers: HashMap<String, Box<dyn CompensationHandler>>,
+    store: Option<Arc<WorkflowStore>>,
+    snapshots: FileSnapshotStore,
 }
 
 /// Trait for custom compensation handlers
@@ -188,12 +268,63 @@ pub trait CompensationHandler: Send + Sync {
 }
 
 impl DefaultSagaManager {
-    /// Create new saga manager
+    /// Create new saga manager with the built-in file undo handler
     pub fn new() -> Self {
-        Self {
+        let mut manager = Self {
             active_sagas: dashmap::DashMap::new(),
             compensation_handlers: HashMap::new(),
+            store: None,
+            snapshots: FileSnapshotStore::new(FileSnapshotStore::default_root()),
+        };
+        manager.register_compensation_handler(Box::new(FileUndoHandler));
+        manager
+    }
+
+    /// Keep pre-step file snapshots under `root`
+    pub fn with_snapshot_dir(mut self, root: impl Into<std::path::PathBuf>) -> Self {
+        self.snapshots = FileSnapshotStore::new(root);
+        self
+    }
+
+    /// Persist every saga state transition to `store`; file snapshots move
+    /// next to its database
+    pub fn with_store(mut self, store: Arc<WorkflowStore>) -> Self {
+        if let Some(dir) = store.snapshot_dir() {
+            self.snapshots = FileSnapshotStore::new(dir);
         }
+        self.store = Some(store);
+        self
+    }
+
+    /// Write the saga snapshot to the durable log (no-op without a store)
+    fn persist(&self, saga: &Saga) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.save_saga(saga)?;
+        }
+        Ok(())
+    }
+
+    /// Compensate a saga left `Executing`/`Compensating` by a crashed process.
+    ///
+    /// Steps caught mid-execution may or may not have produced side effects,
+    /// so they are treated as completed and compensated too.
+    pub async fn recover_saga(&self, saga: &mut Saga) -> Result<SagaCompensationResult> {
+        for step in &mut saga.steps {
+            if matches!(
+                step.status,
+                SagaStepStatus::Executing | SagaStepStatus::Compensating
+            ) {
+                step.status = SagaStepStatus::Completed;
+                step.error
+                    .get_or_insert_with(|| "interrupted; outcome unknown".to_string());
+            }
+        }
+        tracing::warn!(
+            "Recovering interrupted saga {} ({:?})",
+            saga.id,
+            saga.status
+        );
+        self.compensate_saga(saga).await
     }
 
     /// Register a custom compensation handler
@@ -202,64 +333,109 @@ impl DefaultSagaManager {
         self.compensation_handlers.insert(name, handler);
     }
 
+    /// Record how to undo `action_step` before it runs. File steps get a
+    /// snapshot of the affected files; read-only steps need no compensation.
+    fn prepare_compensation(
+        &self,
+        saga: &mut Saga,
+        index: usize,
+        action_step: &ActionStep,
+        runner: &dyn SagaStepRunner,
+    ) -> Result<()> {
+        let saga_step = &saga.steps[index];
+        let compensation = self.create_compensation_step(action_step, saga_step, runner)?;
+        let saga_step = &mut saga.steps[index];
+        saga_step.compensation_needed = compensation.is_some();
+        saga.compensation_steps.extend(compensation);
+        Ok(())
+    }
+
+    /// Attach what the step produced to its compensation (inserted record
+    /// ids, the output referenced by a tool's inverse arguments)
+    fn record_step_output(&self, saga: &mut Saga, index: usize, output: &serde_json::Value) {
+        let saga_step_id = saga.steps[index].id;
+        for compensation in saga
+            .compensation_steps
+            .iter_mut()
+            .filter(|c| c.saga_step_id == saga_step_id)
+        {
+            match &mut compensation.compensation_type {
+                CompensationType::MemoryOperationUndo {
+                    affected_records,
+                    backup_data,
+                    ..
+                } => {
+                    let ids = output
+                        .get("ids")
+                        .and_then(|ids| ids.as_array())
+                        .map(|ids| ids.iter().collect::<Vec<_>>())
+                        .unwrap_or_else(|| output.get("id").into_iter().collect());
+                    affected_records.extend(
+                        ids.into_iter()
+                            .filter_map(|id| id.as_str())
+                            .filter_map(|id| Uuid::parse_str(id).ok()),
+                    );
+                    // Memory backends report the record they replaced/removed as `previous`
+                    if let Some(previous) = output.get("previous") {
+                        *backup_data = Some(previous.clone());
+                    }
+                }
+                CompensationType::ToolExecutionUndo { .. } => {
+                    compensation
+                        .parameters
+                        .insert(handlers::ORIGINAL_OUTPUT_PARAM.to_string(), output.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
     /// Create compensation steps for an action step
     fn create_compensation_step(
         &self,
         action_step: &ActionStep,
         saga_step: &SagaStep,
-    ) -> Option<CompensationStep> {
+        runner: &dyn SagaStepRunner,
+    ) -> Result<Option<CompensationStep>> {
         let compensation_type = match &action_step.step_type {
-            crate::agents::planner::ActionStepType::ToolExecution {
+            ActionStepType::ToolExecution {
                 tool_name,
                 arguments,
-            } => {
-                match tool_name.as_str() {
-                    "file_writer" | "file_creator" => Some(CompensationType::FileOperationUndo {
-                        operation_type: "file_write".to_string(),
-                        affected_files: vec![arguments
-                            .get("path")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string()],
-                        backup_data: None,
-                    }),
-                    "file_deleter" => Some(CompensationType::FileOperationUndo {
-                        operation_type: "file_delete".to_string(),
-                        affected_files: vec![arguments
-                            .get("path")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("unknown")
-                            .to_string()],
-                        backup_data: saga_step
-                            .result
-                            .as_ref()
-                            .and_then(|r| r.get("backup"))
-                            .and_then(|b| b.as_str())
-                            .map(String::from),
-                    }),
-                    "memory_store" => Some(CompensationType::MemoryOperationUndo {
-                        operation_type: "store".to_string(),
-                        affected_records: vec![Uuid::new_v4()], // Would be actual record IDs
-                        backup_data: None,
-                    }),
-                    _ => Some(CompensationType::ToolExecutionUndo {
-                        tool_name: tool_name.clone(),
-                        original_arguments: arguments.clone(),
-                        undo_arguments: self.generate_undo_arguments(tool_name, arguments),
-                    }),
+            } => match tool_name.as_str() {
+                name if runner.is_read_only_tool(name) => None,
+                "file_write" | "file_writer" | "file_creator" | "file_delete" | "file_deleter" => {
+                    let affected_files: Vec<String> = arguments
+                        .get("path")
+                        .and_then(|v| v.as_str())
+                        .map(|path| vec![path.to_string()])
+                        .unwrap_or_default();
+                    let snapshot = self.snapshots.snapshot(saga_step.id, &affected_files)?;
+                    Some(CompensationType::FileOperationUndo {
+                        operation_type: tool_name.clone(),
+                        affected_files,
+                        backup_data: Some(snapshot.display().to_string()),
+                    })
                 }
-            }
-            crate::agents::planner::ActionStepType::MemoryOperation {
-                operation_type,
-                query: _,
-            } => {
-                Some(CompensationType::MemoryOperationUndo {
+                "memory_store" => Some(CompensationType::MemoryOperationUndo {
+                    operation_type: "store".to_string(),
+                    affected_records: Vec::new(),
+                    backup_data: None,
+                }),
+                _ => Some(CompensationType::ToolExecutionUndo {
+                    tool_name: tool_name.clone(),
+                    original_arguments: arguments.clone(),
+                    undo_arguments: HashMap::new(),
+                }),
+            },
+            ActionStepType::MemoryOperation { operation_type, .. } => match operation_type {
+                crate::agents::planner::MemoryOperationType::Search => None,
+                _ => Some(CompensationType::MemoryOperationUndo {
                     operation_type: format!("{:?}", operation_type),
-                    affected_records: vec![Uuid::new_v4()], // Would be actual record IDs
-                    backup_data: saga_step.result.clone(),
-                })
-            }
-            crate::agents::planner::ActionStepType::UserInteraction {
+                    affected_records: Vec::new(),
+                    backup_data: None,
+                }),
+            },
+            ActionStepType::UserInteraction {
                 interaction_type,
                 prompt,
             } => Some(CompensationType::UserInteractionUndo {
@@ -269,7 +445,7 @@ impl DefaultSagaManager {
             _ => None, // Some operations don't need compensation
         };
 
-        compensation_type.map(|comp_type| CompensationStep {
+        Ok(compensation_type.map(|comp_type| CompensationStep {
             id: Uuid::new_v4(),
             saga_step_id: saga_step.id,
             compensation_type: comp_type,
@@ -277,42 +453,7 @@ impl DefaultSagaManager {
             status: CompensationStatus::Pending,
             executed_at: None,
             error: None,
-        })
-    }
-
-    /// Generate undo arguments for tool execution
-    fn generate_undo_arguments(
-        &self,
-        tool_name: &str,
-        original_arguments: &HashMap<String, serde_json::Value>,
-    ) -> HashMap<String, serde_json::Value> {
-        let mut undo_args = HashMap::new();
-
-        match tool_name {
-            "shell_exec" => {
-                // For shell commands, we might need to run reverse commands
-                if let Some(command) = original_arguments.get("command") {
-                    undo_args.insert(
-                        "command".to_string(),
-                        serde_json::Value::String(format!("# Undo for: {}", command)),
-                    );
-                }
-            }
-            "web_request" => {
-                // Web requests might need to be reversed with DELETE/rollback calls
-                undo_args.insert(
-                    "method".to_string(),
-                    serde_json::Value::String("DELETE".to_string()),
-                );
-            }
-            _ => {
-                // Default: mark as undo operation
-                undo_args.insert("undo".to_string(), serde_json::Value::Bool(true));
-                undo_args.extend(original_arguments.clone());
-            }
-        }
-
-        undo_args
+        }))
     }
 
     /// Execute compensation for a specific step
@@ -324,30 +465,6 @@ impl DefaultSagaManager {
         compensation_step.status = CompensationStatus::Executing;
 
         let result = match &compensation_step.compensation_type {
-            CompensationType::FileOperationUndo {
-                operation_type,
-                affected_files,
-                backup_data,
-            } => {
-                self.compensate_file_operation(operation_type, affected_files, backup_data)
-                    .await
-            }
-            CompensationType::MemoryOperationUndo {
-                operation_type,
-                affected_records,
-                backup_data,
-            } => {
-                self.compensate_memory_operation(operation_type, affected_records, backup_data)
-                    .await
-            }
-            CompensationType::ToolExecutionUndo {
-                tool_name,
-                original_arguments,
-                undo_arguments,
-            } => {
-                self.compensate_tool_execution(tool_name, original_arguments, undo_arguments)
-                    .await
-            }
             CompensationType::UserInteractionUndo {
                 interaction_type,
                 notification_message,
@@ -355,12 +472,15 @@ impl DefaultSagaManager {
                 self.compensate_user_interaction(interaction_type, notification_message)
                     .await
             }
-            CompensationType::Custom {
-                handler_name,
-                parameters,
-            } => {
-                self.compensate_custom(handler_name, parameters, compensation_step, saga_step)
-                    .await
+            other => {
+                let handler_name = other.handler_name().unwrap_or_default();
+                match self.compensation_handlers.get(handler_name) {
+                    Some(handler) => handler.compensate(compensation_step, saga_step).await,
+                    None => Err(anyhow::anyhow!(
+                        "No compensation handler '{}' registered",
+                        handler_name
+                    )),
+                }
             }
         };
 
@@ -370,6 +490,21 @@ impl DefaultSagaManager {
                 compensation_step.executed_at = Some(chrono::Utc::now());
                 tracing::info!("Compensation completed for step {}", compensation_step.id);
             }
+            Err(e) if e.downcast_ref::<NotCompensable>().is_some() => {
+                compensation_step.status = CompensationStatus::NotCompensable;
+                compensation_step.error = Some(e.to_string());
+                tracing::error!("Step {} cannot be compensated: {}", compensation_step.id, e);
+            }
+            Err(e) if e.downcast_ref::<NothingToCompensate>().is_some() => {
+                compensation_step.status = CompensationStatus::Skipped;
+                compensation_step.executed_at = Some(chrono::Utc::now());
+                compensation_step.error = Some(e.to_string());
+                tracing::warn!(
+                    "Compensation skipped for step {}: {}",
+                    compensation_step.id,
+                    e
+                );
+            }
             Err(e) => {
                 compensation_step.status = CompensationStatus::Failed;
                 compensation_step.error = Some(e.to_string());
@@ -384,114 +519,6 @@ impl DefaultSagaManager {
         Ok(())
     }
 
-    /// Compensate file operation
-    async fn compensate_file_operation(
-        &self,
-        operation_type: &str,
-        affected_files: &[String],
-        backup_data: &Option<String>,
-    ) -> Result<()> {
-        tracing::debug!("Compensating file operation: {}", operation_type);
-
-        for file_path in affected_files {
-            match operation_type {
-                "file_write" | "file_create" => {
-                    // Delete created file or restore original content
-                    if let Some(_backup) = backup_data {
-                        tracing::debug!("Restoring file {} from backup", file_path);
-                        // Would restore from backup
-                    } else {
-                        tracing::debug!("Deleting created file {}", file_path);
-                        // Would delete file
-                    }
-                }
-                "file_delete" => {
-                    // Restore deleted file from backup
-                    if let Some(_backup) = backup_data {
-                        tracing::debug!("Restoring deleted file {} from backup", file_path);
-                        // Would restore file from backup
-                    }
-                }
-                _ => {
-                    tracing::warn!(
-                        "Unknown file operation type for compensation: {}",
-                        operation_type
-                    );
-                }
-            }
-        }
-
-        // Simulate compensation time
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
-
-        Ok(())
-    }
-
-    /// Compensate memory operation
-    async fn compensate_memory_operation(
-        &self,
-        operation_type: &str,
-        affected_records: &[Uuid],
-        backup_data: &Option<serde_json::Value>,
-    ) -> Result<()> {
-        tracing::debug!("Compensating memory operation: {}", operation_type);
-
-        for record_id in affected_records {
-            match operation_type {
-                "Store" => {
-                    tracing::debug!("Removing stored memory record {}", record_id);
-                    // Would remove from memory store
-                }
-                "Update" => {
-                    if let Some(_backup) = backup_data {
-                        tracing::debug!("Restoring memory record {} from backup", record_id);
-                        // Would restore from backup
-                    }
-                }
-                "Delete" => {
-                    if let Some(_backup) = backup_data {
-                        tracing::debug!("Restoring deleted memory record {}", record_id);
-                        // Would restore deleted record
-                    }
-                }
-                _ => {
-                    tracing::warn!(
-                        "Unknown memory operation type for compensation: {}",
-                        operation_type
-                    );
-                }
-            }
-        }
-
-        // Simulate compensation time
-        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
-
-        Ok(())
-    }
-
-    /// Compensate tool execution
-    async fn compensate_tool_execution(
-        &self,
-        tool_name: &str,
-        _original_arguments: &HashMap<String, serde_json::Value>,
-        undo_arguments: &HashMap<String, serde_json::Value>,
-    ) -> Result<()> {
-        tracing::debug!("Compensating tool execution: {}", tool_name);
-
-        // Simulate tool execution undo
-        // In real implementation, this would:
-        // 1. Invoke the same tool with undo arguments
-        // 2. Or invoke a different tool that reverses the operation
-        // 3. Or call a specific undo API
-
-        tracing::debug!("Tool {} undo arguments: {:?}", tool_name, undo_arguments);
-
-        // Simulate compensation time
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-
-        Ok(())
-    }
-
     /// Compensate user interaction
     async fn compensate_user_interaction(
         &self,
@@ -512,62 +539,24 @@ impl DefaultSagaManager {
         Ok(())
     }
 
-    /// Compensate custom operation
-    async fn compensate_custom(
+    /// Execute `plan` step by step through `runner`, recording how to undo
+    /// each step before it runs (file snapshots, inserted memory records,
+    /// tool inverse arguments)
+    pub async fn execute_plan(
         &self,
-        handler_name: &str,
-        _parameters: &HashMap<String, serde_json::Value>,
-        compensation_step: &CompensationStep,
-        saga_step: &SagaStep,
-    ) -> Result<()> {
-        if let Some(handler) = self.compensation_handlers.get(handler_name) {
-            handler.compensate(compensation_step, saga_step).await
-        } else {
-            anyhow::bail!("Custom compensation handler '{}' not found", handler_name)
-        }
-    }
-}
-
-#[async_trait]
-impl SagaManager for DefaultSagaManager {
-    async fn create_saga(&self, plan: &ActionPlan) -> Result<Saga> {
-        let saga_id = Uuid::new_v4();
-
-        // Create saga steps from action plan steps
-        let saga_steps: Vec<SagaStep> = plan
-            .steps
-            .iter()
-            .map(|action_step| {
-                SagaStep {
-                    id: Uuid::new_v4(),
-                    action_step_id: action_step.id,
-                    status: SagaStepStatus::Pending,
-                    result: None,
-                    error: None,
-                    compensation_needed: true, // Most operations need compensation
-                    executed_at: None,
-                    compensated_at: None,
-                }
-            })
-            .collect();
-
-        let saga = Saga {
-            id: saga_id,
-            plan_id: plan.id,
-            status: SagaStatus::Preparing,
-            steps: saga_steps,
-            compensation_steps: Vec::new(),
-            metadata: plan.metadata.clone(),
-            started_at: chrono::Utc::now(),
-            completed_at: None,
-        };
-
-        tracing::info!("Created saga {} for plan {}", saga_id, plan.id);
-
-        Ok(saga)
+        saga: &mut Saga,
+        plan: &ActionPlan,
+        runner: &dyn SagaStepRunner,
+    ) -> Result<SagaExecutionResult> {
+        self.run_saga(saga, Some((plan, runner))).await
     }
 
-    async fn execute_saga(&self, saga: &mut Saga) -> Result<SagaExecutionResult> {
+    /// Shared execution loop; without a plan the steps are simulated
+    async fn run_saga(
+        &self,
+        saga: &mut Saga,
+        plan: Option<(&ActionPlan, &dyn SagaStepRunner)>,
+    ) -> Result<SagaExecutionResult> {
         let start_time = std::time::Instant::now();
         saga.status = SagaStatus::Executing;
 
@@ -578,15 +567,39 @@ impl SagaManager for DefaultSagaManager {
 
         // Store saga in active sagas
         self.active_sagas.insert(saga.id, saga.clone());
+        self.persist(saga)?;
 
         tracing::info!("Starting saga execution {}", saga.id);
 
-        // Execute saga steps sequentially
-        for saga_step in &mut saga.steps {
-            saga_step.status = SagaStepStatus::Executing;
-
-            // Simulate step execution
-            let step_result = self.simulate_step_execution(saga_step).await;
+        // Execute saga steps sequentially; each transition is persisted before
+        // and after the step so a crash leaves a record of possible side effects
+        for index in 0..saga.steps.len() {
+            saga.steps[index].status = SagaStepStatus::Executing;
+            let step_result = match plan {
+                Some((plan, runner)) => {
+                    let action_step_id = saga.steps[index].action_step_id;
+                    let action_step = plan
+                        .steps
+                        .iter()
+                        .find(|step| step.id == action_step_id)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Plan {} has no step {}", plan.id, action_step_id)
+                        })?;
+                    self.prepare_compensation(saga, index, action_step, runner)?;
+                    self.persist(saga)?;
+
+                    let output = runner.run_step(action_step).await;
+                    if let Ok(output) = &output {
+                        self.record_step_output(saga, index, output);
+                    }
+                    output
+                }
+                None => {
+                    self.persist(saga)?;
+                    self.simulate_step_execution(&saga.steps[index]).await
+                }
+            };
+            let saga_step = &mut saga.steps[index];
 
             match step_result {
                 Ok(result) => {
@@ -630,14 +643,17 @@ impl SagaManager for DefaultSagaManager {
 
                     // On failure, mark saga for compensation
                     saga.status = SagaStatus::Compensating;
+                    self.persist(saga)?;
                     break;
                 }
             }
+            self.persist(saga)?;
         }
 
         if saga.status == SagaStatus::Executing {
             saga.status = SagaStatus::Completed;
             saga.completed_at = Some(chrono::Utc::now());
+            self.discard_snapshots(saga);
         }
 
         let execution_time = start_time.elapsed();
@@ -654,6 +670,7 @@ impl SagaManager for DefaultSagaManager {
 
         // Update stored saga
         self.active_sagas.insert(saga.id, saga.clone());
+        self.persist(saga)?;
 
         tracing::info!(
             "Saga execution {} completed with status: {:?}",
@@ -663,6 +680,51 @@ impl SagaManager for DefaultSagaManager {
 
         Ok(result)
     }
+}
+
+#[async_trait]
+impl SagaManager for DefaultSagaManager {
+    async fn create_saga(&self, plan: &ActionPlan) -> Result<Saga> {
+        let saga_id = Uuid::new_v4();
+
+        // Create saga steps from action plan steps
+        let saga_steps: Vec<SagaStep> = plan
+            .steps
+            .iter()
+            .map(|action_step| {
+                SagaStep {
+                    id: Uuid::new_v4(),
+                    action_step_id: action_step.id,
+                    status: SagaStepStatus::Pending,
+                    result: None,
+                    error: None,
+                    compensation_needed: true, // Most operations need compensation
+                    executed_at: None,
+                    compensated_at: None,
+                }
+            })
+            .collect();
+
+        let saga = Saga {
+            id: saga_id,
+            plan_id: plan.id,
+            status: SagaStatus::Preparing,
+            steps: saga_steps,
+            compensation_steps: Vec::new(),
+            metadata: plan.metadata.clone(),
+            started_at: chrono::Utc::now(),
+            completed_at: None,
+        };
+
+        self.persist(&saga)?;
+        tracing::info!("Created saga {} for plan {}", saga_id, plan.id);
+
+        Ok(saga)
+    }
+
+    async fn execute_saga(&self, saga: &mut Saga) -> Result<SagaExecutionResult> {
+        self.run_saga(saga, None).await
+    }
 
     async fn compensate_saga(&self, saga: &mut Saga) -> Result<SagaCompensationResult> {
         let start_time = std::time::Instant::now();
@@ -671,31 +733,96 @@ impl SagaManager for DefaultSagaManager {
         let mut compensated_steps = 0;
         let mut failed_compensations = 0;
         let mut errors = Vec::new();
+        let mut report = Vec::new();
 
         tracing::info!("Starting saga compensation {}", saga.id);
+        self.persist(saga)?;
 
         // Execute compensations in reverse order
-        for saga_step in saga.steps.iter_mut().rev() {
-            if saga_step.status == SagaStepStatus::Completed && saga_step.compensation_needed {
-                saga_step.status = SagaStepStatus::Compensating;
-
-                // Create and execute compensation
-                // In a real implementation, this would reference the original action step
-                // For now, simulate compensation
-                match self.simulate_compensation(saga_step).await {
-                    Ok(_) => {
-                        saga_step.status = SagaStepStatus::Compensated;
-                        saga_step.compensated_at = Some(chrono::Utc::now());
-                        compensated_steps += 1;
-                    }
-                    Err(e) => {
-                        saga_step.status = SagaStepStatus::CompensationFailed;
-                        failed_compensations += 1;
-                        errors.push(e.to_string());
-                        tracing::error!("Compensation failed for step {}: {}", saga_step.id, e);
+        for index in (0..saga.steps.len()).rev() {
+            let saga_step_id = saga.steps[index].id;
+            let compensation_indices: Vec<usize> = saga
+                .compensation_steps
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.saga_step_id == saga_step_id)
+                .map(|(i, _)| i)
+                .collect();
+
+            // A failed step may have partially written files; restoring its
+            // snapshot is always safe, inverting other side effects is not
+            let eligible = match saga.steps[index].status {
+                SagaStepStatus::Completed => saga.steps[index].compensation_needed,
+                SagaStepStatus::Failed => compensation_indices.iter().any(|&i| {
+                    matches!(
+                        saga.compensation_steps[i].compensation_type,
+                        CompensationType::FileOperationUndo { .. }
+                    )
+                }),
+                _ => false,
+            };
+            if !eligible {
+                continue;
+            }
+
+            saga.steps[index].status = SagaStepStatus::Compensating;
+            self.persist(saga)?;
+
+            let outcome = if compensation_indices.is_empty() {
+                // The step needed compensation but nothing was recorded
+                // (e.g. a saga executed without a step runner)
+                let error = NotCompensable("no compensation recorded for the step".to_string());
+                report.push(CompensationReportEntry {
+                    saga_step_id,
+                    action_step_id: saga.steps[index].action_step_id,
+                    description: "no compensation".to_string(),
+                    status: CompensationStatus::NotCompensable,
+                    error: Some(error.to_string()),
+                });
+                Err(error.into())
+            } else {
+                let mut step_errors = Vec::new();
+                for &i in compensation_indices.iter().rev() {
+                    let mut compensation = saga.compensation_steps[i].clone();
+                    self.execute_compensation_step(&mut compensation, &saga.steps[index])
+                        .await?;
+                    if matches!(
+                        compensation.status,
+                        CompensationStatus::Failed | CompensationStatus::NotCompensable
+                    ) {
+                        step_errors.push(compensation.error.clone().unwrap_or_default());
                     }
+                    report.push(CompensationReportEntry {
+                        saga_step_id,
+                        action_step_id: saga.steps[index].action_step_id,
+                        description: compensation.compensation_type.describe(),
+                        status: compensation.status.clone(),
+                        error: compensation.error.clone(),
+                    });
+                    saga.compensation_steps[i] = compensation;
+                }
+                if step_errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(step_errors.join("; ")))
+                }
+            };
+
+            let saga_step = &mut saga.steps[index];
+            match outcome {
+                Ok(_) => {
+                    saga_step.status = SagaStepStatus::Compensated;
+                    saga_step.compensated_at = Some(chrono::Utc::now());
+                    compensated_steps += 1;
+                }
+                Err(e) => {
+                    saga_step.status = SagaStepStatus::CompensationFailed;
+                    failed_compensations += 1;
+                    errors.push(e.to_string());
+                    tracing::error!("Compensation failed for step {}: {}", saga_step.id, e);
                 }
             }
+            self.persist(saga)?;
         }
 
         if failed_compensations == 0 {
@@ -715,10 +842,12 @@ impl SagaManager for DefaultSagaManager {
             failed_compensations,
             compensation_time,
             errors,
+            report,
         };
 
         // Update stored saga
         self.active_sagas.insert(saga.id, saga.clone());
+        self.persist(saga)?;
 
         tracing::info!(
             "Saga compensation {} completed with status: {:?}",
@@ -740,6 +869,7 @@ impl SagaManager for DefaultSagaManager {
     async fn cancel_saga(&self, saga_id: Uuid) -> Result<()> {
         if let Some(mut saga) = self.active_sagas.get_mut(&saga_id) {
             saga.status = SagaStatus::Cancelled;
+            self.persist(&saga)?;
             tracing::info!("Cancelled saga {}", saga_id);
             Ok(())
         } else {
@@ -765,19 +895,19 @@ impl DefaultSagaManager {
             anyhow::bail!("Simulated step failure")
         }
     }
+}
 
-    /// Simulate compensation for testing
-    async fn simulate_compensation(&self, step: &SagaStep) -> Result<()> {
-        // Simulate compensation time
-        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
-
-        tracing::debug!("Compensating step {}", step.id);
-
-        // 95% success rate for compensation
-        if rand::random::<f64>() < 0.95 {
-            Ok(())
-        } else {
-            anyhow::bail!("Simulated compensation failure")
+impl DefaultSagaManager {
+    /// Snapshots of a completed saga can never be restored
+    fn discard_snapshots(&self, saga: &Saga) {
+        for compensation in &saga.compensation_steps {
+            if let CompensationType::FileOperationUndo {
+                backup_data: Some(snapshot),
+                ..
+            } = &compensation.compensation_type
+            {
+                FileSnapshotStore::discard(std::path::Path::new(snapshot));
+            }
         }
     }
 }
@@ -923,6 +1053,67 @@ mod tests {
         }
     }
 
+    /// Writes `content` to `path` for file steps, fails on anything else
+    struct FileWritingRunner;
+
+    #[async_trait]
+    impl SagaStepRunner for FileWritingRunner {
+        async fn run_step(&self, step: &ActionStep) -> Result<serde_json::Value> {
+            match &step.step_type {
+                ActionStepType::ToolExecution { arguments, .. } => {
+                    let path = arguments["path"].as_str().unwrap_or_default();
+                    let content = arguments["content"].as_str().unwrap_or_default();
+                    std::fs::write(path, content)?;
+                    Ok(serde_json::json!({ "written": path }))
+                }
+                _ => anyhow::bail!("memory backend unavailable"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_plan_rolls_back_file_writes() {
+        let dir = tempfile::tempdir().expect("Operation failed - converted from unwrap()");
+        let target = dir.path().join("notes.txt");
+        std::fs::write(&target, "original").expect("Operation failed - converted from unwrap()");
+
+        let mut plan = create_test_plan();
+        if let ActionStepType::ToolExecution { arguments, .. } = &mut plan.steps[0].step_type {
+            arguments.insert(
+                "path".to_string(),
+                serde_json::json!(target.display().to_string()),
+            );
+        }
+
+        let manager = DefaultSagaManager::new().with_snapshot_dir(dir.path().join("snapshots"));
+        let mut saga = manager
+            .create_saga(&plan)
+            .await
+            .expect("Async operation should succeed");
+        let result = manager
+            .execute_plan(&mut saga, &plan, &FileWritingRunner)
+            .await
+            .expect("Async operation should succeed");
+        assert_eq!(result.status, SagaStatus::Compensating);
+        assert_eq!(
+            std::fs::read_to_string(&target).expect("Operation failed - converted from unwrap()"),
+            "test content"
+        );
+
+        let compensation = manager
+            .compensate_saga(&mut saga)
+            .await
+            .expect("Async operation should succeed");
+        assert_eq!(compensation.status, SagaStatus::Compensated);
+        assert_eq!(compensation.compensated_steps, 1);
+        assert_eq!(compensation.report.len(), 1);
+        assert_eq!(compensation.report[0].status, CompensationStatus::Completed);
+        assert_eq!(
+            std::fs::read_to_string(&target).expect("Operation failed - converted from unwrap()"),
+            "original"
+        );
+    }
+
     #[tokio::test]
     async fn test_saga_status_tracking() {
         let manager = DefaultSagaManager::new();
@@ -283,6 +283,8 @@ impl ActorSystem {
                 actor_info.lifecycle_handle.abort();
             }
         }
+        // Release the map entry before removing it below
+        drop(actor_info);
 
         // Unregister from supervisor
         self.supervisor.unregister_actor(actor_id).await;
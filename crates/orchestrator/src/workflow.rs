@@ -17,8 +17,12 @@ use crate::orchestrator::{
     AgentOrchestrator, OrchestratorError, ResourceUsage, WorkflowId, WorkflowState,
     WorkflowStepType,
 };
+use crate::persistence::WorkflowTransition;
+use crate::resources::{SpendBudget, SpendLedger, SpendTracker};
+use crate::saga::{DefaultSagaManager, SagaCompensationResult};
 
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::sync::oneshot;
 use tracing::{debug, error, info, warn};
 
@@ -52,6 +56,10 @@ pub struct WorkflowRequest {
 
     /// Custom configuration overrides
     pub config_overrides: Option<WorkflowConfig>,
+
+    /// Token, cost, tool-call and wall-clock limits for this workflow
+    #[serde(default)]
+    pub budget: Option<SpendBudget>,
 }
 
 /// Configuration for workflow execution
@@ -126,6 +134,23 @@ pub struct WorkflowResult {
     pub steps_completed: Vec<WorkflowStepType>,
 }
 
+/// Outcome of [`AgentOrchestrator::recover_interrupted`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Workflows left running by a dead process, now resumable
+    pub interrupted_workflows: Vec<WorkflowId>,
+
+    /// Compensations run for sagas left executing by a dead process
+    pub compensated_sagas: Vec<SagaCompensationResult>,
+
+    /// Sagas whose recovery failed
+    pub errors: Vec<String>,
+}
+
+fn persistence_error(e: anyhow::Error) -> OrchestratorError {
+    OrchestratorError::Persistence(e.to_string())
+}
+
 impl AgentOrchestrator {
     /// Execute a complete Intent→Plan→Execute→Critic workflow (P1.1.10.b)
     pub async fn execute_workflow(
@@ -145,7 +170,7 @@ impl AgentOrchestrator {
         self.check_workflow_limits().await?;
 
         // Create workflow state
-        let mut workflow_state = WorkflowState {
+        let workflow_state = WorkflowState {
             id: workflow_id,
             current_step: WorkflowStepType::IntentAnalysis,
             status: ExecutionStatus::Running,
@@ -154,12 +179,153 @@ impl AgentOrchestrator {
             intent: None,
             plan: None,
             execution_results: None,
+            plan_step_results: Vec::new(),
             critique: None,
             started_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             error: None,
             resource_usage: ResourceUsage::default(),
         };
+        if let Some(store) = &self.state_store {
+            store
+                .begin(&workflow_state, &request)
+                .map_err(persistence_error)?;
+        }
+
+        self.run_workflow(workflow_state, request, start_time, false)
+            .await
+    }
+
+    /// Continue an interrupted or failed workflow from its last checkpoint.
+    /// Steps whose results were persisted are not executed again.
+    pub async fn resume_workflow(
+        &self,
+        workflow_id: WorkflowId,
+    ) -> Result<WorkflowResult, OrchestratorError> {
+        let store = self.state_store.as_ref().ok_or_else(|| {
+            OrchestratorError::Persistence("no workflow state store configured".to_string())
+        })?;
+        let stored = store
+            .get(workflow_id)
+            .map_err(persistence_error)?
+            .ok_or(OrchestratorError::WorkflowNotFound(workflow_id))?;
+
+        if stored.state.status == ExecutionStatus::Completed {
+            return Err(OrchestratorError::WorkflowExecutionFailed(format!(
+                "Workflow {} already completed",
+                workflow_id
+            )));
+        }
+        let running_here = self
+            .active_workflows
+            .read()
+            .await
+            .contains_key(&workflow_id);
+        let running_elsewhere = stored.state.status == ExecutionStatus::Running
+            && stored.owner_pid != std::process::id()
+            && crate::persistence::process_alive(stored.owner_pid);
+        if running_here || running_elsewhere {
+            return Err(OrchestratorError::WorkflowExecutionFailed(format!(
+                "Workflow {} is still running (pid {})",
+                workflow_id, stored.owner_pid
+            )));
+        }
+
+        self.check_workflow_limits().await?;
+
+        let mut workflow_state = stored.state;
+        workflow_state.status = ExecutionStatus::Running;
+        workflow_state.error = None;
+        workflow_state.updated_at = chrono::Utc::now();
+        store
+            .checkpoint(
+                &workflow_state,
+                WorkflowTransition::Resumed {
+                    step: workflow_state.current_step,
+                },
+            )
+            .map_err(persistence_error)?;
+
+        info!(
+            workflow_id = %workflow_id,
+            step = %workflow_state.current_step,
+            "Resuming workflow"
+        );
+
+        self.run_workflow(
+            workflow_state,
+            stored.request,
+            tokio::time::Instant::now(),
+            true,
+        )
+        .await
+    }
+
+    /// Startup recovery: mark workflows left running by a dead process as
+    /// interrupted (resumable) and compensate their unfinished sagas
+    pub async fn recover_interrupted(&self) -> Result<RecoveryReport, OrchestratorError> {
+        let mut report = RecoveryReport::default();
+        let Some(store) = &self.state_store else {
+            return Ok(report);
+        };
+
+        for stored in store.interrupted_workflows().map_err(persistence_error)? {
+            let mut state = stored.state;
+            let step = state.current_step;
+            state.status = ExecutionStatus::Failed;
+            state.error = Some(format!("Interrupted during {step}"));
+            state.updated_at = chrono::Utc::now();
+            store
+                .checkpoint(&state, WorkflowTransition::Interrupted { step })
+                .map_err(persistence_error)?;
+            warn!(
+                workflow_id = %state.id,
+                step = %step,
+                owner_pid = stored.owner_pid,
+                "Found interrupted workflow"
+            );
+            report.interrupted_workflows.push(state.id);
+        }
+
+        let mut saga_manager = DefaultSagaManager::new().with_store(Arc::clone(store));
+        if let Some(handler) = self.memory_undo_handler() {
+            saga_manager.register_compensation_handler(Box::new(handler));
+        }
+        for mut saga in store.unfinished_sagas().map_err(persistence_error)? {
+            match saga_manager.recover_saga(&mut saga).await {
+                Ok(result) => report.compensated_sagas.push(result),
+                Err(e) => {
+                    error!(saga_id = %saga.id, error = %e, "Saga recovery failed");
+                    report.errors.push(format!("saga {}: {}", saga.id, e));
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Persist a workflow state transition (no-op without a state store)
+    fn checkpoint(
+        &self,
+        workflow_state: &WorkflowState,
+        transition: WorkflowTransition,
+    ) -> Result<(), OrchestratorError> {
+        if let Some(store) = &self.state_store {
+            store
+                .checkpoint(workflow_state, transition)
+                .map_err(persistence_error)?;
+        }
+        Ok(())
+    }
+
+    async fn run_workflow(
+        &self,
+        mut workflow_state: WorkflowState,
+        request: WorkflowRequest,
+        start_time: tokio::time::Instant,
+        resumed: bool,
+    ) -> Result<WorkflowResult, OrchestratorError> {
+        let workflow_id = workflow_state.id;
 
         // Register workflow as active
         {
@@ -167,6 +333,19 @@ impl AgentOrchestrator {
             workflows.insert(workflow_id, workflow_state.clone());
         }
 
+        // Track spend against the request budget, continuing from the
+        // persisted usage when resuming
+        if let Some(budget) = request.budget {
+            let mut tracker = SpendTracker::new(budget);
+            if let Some(spend) = &workflow_state.resource_usage.spend {
+                tracker = tracker.with_usage(spend.usage);
+            }
+            if let Some(approver) = &self.spend_approver {
+                tracker = tracker.with_approver(Arc::clone(approver));
+            }
+            self.spend_ledger.attach(workflow_id.0, Arc::new(tracker));
+        }
+
         // Publish workflow started event
         let workflow_event = WorkflowEvent {
             workflow_id: workflow_id.0,
@@ -176,7 +355,8 @@ impl AgentOrchestrator {
             payload: serde_json::json!({
                 "user_input": request.user_input,
                 "priority": request.priority,
-                "dry_run": request.dry_run
+                "dry_run": request.dry_run,
+                "resumed": resumed
             }),
             duration_ms: None,
             step_dependencies: vec![],
@@ -203,16 +383,19 @@ impl AgentOrchestrator {
             steps_completed: vec![],
         };
 
-        // Execute workflow steps in sequence
-        let workflow_result = self
-            .execute_workflow_steps(
+        // Execute workflow steps in sequence; LLM calls and tool steps made
+        // meanwhile are charged to this workflow's budget
+        let workflow_result = SpendLedger::scope(
+            workflow_id.0,
+            self.execute_workflow_steps(
                 workflow_id,
                 &mut workflow_state,
                 &request,
                 &config,
                 &mut result,
-            )
-            .await;
+            ),
+        )
+        .await;
 
         // Calculate execution time IMMEDIATELY after workflow steps
         let elapsed_time_ms = start_time.elapsed().as_millis() as u64;
@@ -237,12 +420,29 @@ impl AgentOrchestrator {
                 workflow_state.error = Some(error.to_string());
                 result.error = Some(error.to_string());
                 error!(workflow_id = %workflow_id, error = %error, "Workflow failed");
+                self.checkpoint(
+                    &workflow_state,
+                    WorkflowTransition::StepFailed {
+                        step: workflow_state.current_step,
+                        error: error.to_string(),
+                    },
+                )?;
             }
         }
 
-        // Update workflow state with execution time
+        // Update workflow state with execution time and final spend
         workflow_state.updated_at = chrono::Utc::now();
         workflow_state.resource_usage.total_time_ms = elapsed_time_ms;
+        if let Some(tracker) = self.spend_ledger.detach(workflow_id.0) {
+            workflow_state.resource_usage.spend = Some(tracker.report());
+        }
+        let final_spend = workflow_state.resource_usage.spend;
+        self.checkpoint(
+            &workflow_state,
+            WorkflowTransition::Finished {
+                success: result.success,
+            },
+        )?;
         {
             let mut workflows = self.active_workflows.write().await;
             workflows.insert(workflow_id, workflow_state);
@@ -287,6 +487,7 @@ impl AgentOrchestrator {
         // Ensure execution time is ALWAYS recorded regardless of success/failure
         result.execution_time_ms = elapsed_time_ms;
         result.resource_usage.total_time_ms = elapsed_time_ms;
+        result.resource_usage.spend = final_spend;
         println!(
             "🔥 DEBUG: Final result.execution_time_ms = {}ms before return",
             result.execution_time_ms
@@ -302,7 +503,10 @@ impl AgentOrchestrator {
         Ok(result)
     }
 
-    /// Execute all workflow steps in sequence
+    /// Execute all workflow steps in sequence.
+    /// Steps whose results are already present in `workflow_state` (restored
+    /// from a checkpoint on resume) are not executed again; neither are the
+    /// plan steps recorded in `plan_step_results`.
     async fn execute_workflow_steps(
         &self,
         workflow_id: WorkflowId,
@@ -312,35 +516,92 @@ impl AgentOrchestrator {
         result: &mut WorkflowResult,
     ) -> Result<(), OrchestratorError> {
         // Step 1: Intent Analysis
-        if config.enable_intent_analysis {
+        if let Some(intent) = &workflow_state.intent {
+            result.intent = Some(intent.clone());
+            result
+                .steps_completed
+                .push(WorkflowStepType::IntentAnalysis);
+        } else if config.enable_intent_analysis {
+            self.enforce_spend(workflow_id).await?;
             self.execute_intent_analysis_step(workflow_id, workflow_state, request, config, result)
                 .await?;
+            self.checkpoint_step(workflow_state, WorkflowStepType::IntentAnalysis)?;
         }
 
         // Step 2: Plan Generation
-        if config.enable_plan_generation {
+        if let Some(plan) = &workflow_state.plan {
+            result.plan = Some(plan.clone());
+            result
+                .steps_completed
+                .push(WorkflowStepType::PlanGeneration);
+        } else if config.enable_plan_generation {
+            self.enforce_spend(workflow_id).await?;
             self.execute_plan_generation_step(workflow_id, workflow_state, request, config, result)
                 .await?;
+            self.checkpoint_step(workflow_state, WorkflowStepType::PlanGeneration)?;
         }
 
         // Step 3: Plan Execution
-        if config.enable_plan_execution && !request.dry_run {
+        if let Some(execution_results) = &workflow_state.execution_results {
+            result.execution_results = Some(execution_results.clone());
+            result.steps_completed.push(WorkflowStepType::PlanExecution);
+        } else if config.enable_plan_execution && !request.dry_run {
+            self.enforce_spend(workflow_id).await?;
             self.execute_plan_execution_step(workflow_id, workflow_state, request, config, result)
                 .await?;
+            self.checkpoint_step(workflow_state, WorkflowStepType::PlanExecution)?;
         } else if request.dry_run {
             info!(workflow_id = %workflow_id, "Skipping plan execution (dry-run mode)");
             result.steps_completed.push(WorkflowStepType::PlanExecution);
         }
 
         // Step 4: Result Critique
-        if config.enable_result_critique {
+        if let Some(critique) = &workflow_state.critique {
+            result.critique = Some(critique.clone());
+            result
+                .steps_completed
+                .push(WorkflowStepType::ResultCritique);
+        } else if config.enable_result_critique {
+            self.enforce_spend(workflow_id).await?;
             self.execute_critique_step(workflow_id, workflow_state, request, config, result)
                 .await?;
+            self.checkpoint_step(workflow_state, WorkflowStepType::ResultCritique)?;
         }
 
         Ok(())
     }
 
+    fn checkpoint_step(
+        &self,
+        workflow_state: &mut WorkflowState,
+        step: WorkflowStepType,
+    ) -> Result<(), OrchestratorError> {
+        self.checkpoint_progress(workflow_state, WorkflowTransition::StepCompleted { step })
+    }
+
+    fn checkpoint_progress(
+        &self,
+        workflow_state: &mut WorkflowState,
+        transition: WorkflowTransition,
+    ) -> Result<(), OrchestratorError> {
+        // Persist live spend so `magray orchestrator status` can report it
+        if let Some(spend) = self.spend_ledger.report(workflow_state.id.0) {
+            workflow_state.resource_usage.spend = Some(spend);
+        }
+        self.checkpoint(workflow_state, transition)
+    }
+
+    /// Check the workflow's spend limits before the next step
+    async fn enforce_spend(&self, workflow_id: WorkflowId) -> Result<(), OrchestratorError> {
+        if let Some(tracker) = self.spend_ledger.get(workflow_id.0) {
+            tracker.enforce().await.map_err(|overrun| {
+                warn!(workflow_id = %workflow_id, %overrun, "Workflow stopped by budget");
+                OrchestratorError::SpendLimitExceeded(workflow_id, overrun)
+            })?;
+        }
+        Ok(())
+    }
+
     /// Execute Intent Analysis step
     async fn execute_intent_analysis_step(
         &self,
@@ -622,76 +883,113 @@ impl AgentOrchestrator {
             )
         })?;
 
-        // Create execution message
-        let execution_message = AgentMessage::ExecutePlan {
-            plan,
-            dry_run: request.dry_run,
+        // Run the plan one step at a time and checkpoint every result, so a
+        // resumed workflow continues after the last completed step
+        let steps = match plan.get("steps").and_then(serde_json::Value::as_array) {
+            Some(steps) if !steps.is_empty() => steps.clone(),
+            _ => vec![plan],
         };
+        let done = workflow_state.plan_step_results.len();
+        if done > 0 {
+            info!(
+                workflow_id = %workflow_id,
+                completed = done,
+                total = steps.len(),
+                "Skipping plan steps completed before resume"
+            );
+        }
+        for (index, step) in steps.iter().enumerate().skip(done) {
+            self.enforce_spend(workflow_id).await?;
 
-        // Execute with retry and timeout
-        let execution_result = self
-            .execute_agent_step_with_retries(
-                &executor_handle,
-                execution_message,
-                config.max_step_retries,
-                config.step_timeout_ms * 2, // Execution might take longer
-                workflow_id,
-                "plan_execution",
-            )
-            .await?;
-
-        // Process execution result
-        if let AgentMessage::ExecutionCompleted {
-            success,
-            results,
-            execution_time,
-            ..
-        } = execution_result
-        {
-            workflow_state.execution_results = Some(results.clone());
-            result.execution_results = Some(results);
-            result.steps_completed.push(WorkflowStepType::PlanExecution);
-
-            if success {
-                info!(
-                    workflow_id = %workflow_id,
-                    execution_time = execution_time,
-                    "Plan Execution completed successfully"
-                );
-            } else {
-                warn!(
-                    workflow_id = %workflow_id,
-                    execution_time = execution_time,
-                    "Plan Execution completed with partial success"
-                );
-            }
+            let execution_message = AgentMessage::ExecutePlan {
+                plan: serde_json::json!({ "steps": [step], "step_index": index }),
+                dry_run: request.dry_run,
+            };
+            let execution_result = self
+                .execute_agent_step_with_retries(
+                    &executor_handle,
+                    execution_message,
+                    config.max_step_retries,
+                    config.step_timeout_ms * 2, // Execution might take longer
+                    workflow_id,
+                    "plan_execution",
+                )
+                .await?;
 
-            // Publish step completed event
-            let completion_event = WorkflowEvent {
-                workflow_id: workflow_id.0,
-                step: WorkflowStep::ExecutionCompleted,
-                timestamp: chrono::Utc::now(),
-                agent_id: None,
-                payload: serde_json::json!({
-                    "success": success,
-                    "execution_time": execution_time,
-                    "execution_summary": "Plan executed successfully"
-                }),
-                duration_ms: None,
-                step_dependencies: vec!["PlanGeneration".to_string()],
-                timeout: request.timeout_ms,
-                priority: convert_task_priority(&request.priority),
+            let AgentMessage::ExecutionCompleted {
+                success,
+                results,
+                execution_time,
+                ..
+            } = execution_result
+            else {
+                return Err(OrchestratorError::WorkflowExecutionFailed(
+                    "Invalid plan execution result".to_string(),
+                ));
             };
-            let _ = self
-                .event_publisher
-                .publish_workflow_event(completion_event)
-                .await;
+            workflow_state.plan_step_results.push(serde_json::json!({
+                "index": index,
+                "step": step,
+                "success": success,
+                "results": results,
+                "execution_time": execution_time,
+            }));
+            workflow_state.updated_at = chrono::Utc::now();
+            self.checkpoint_progress(
+                workflow_state,
+                WorkflowTransition::PlanStepCompleted { index },
+            )?;
+        }
+
+        let success = workflow_state
+            .plan_step_results
+            .iter()
+            .all(|step| step["success"].as_bool().unwrap_or(false));
+        let execution_time: u64 = workflow_state
+            .plan_step_results
+            .iter()
+            .filter_map(|step| step["execution_time"].as_u64())
+            .sum();
+        let results = serde_json::json!({ "steps": workflow_state.plan_step_results });
+        workflow_state.execution_results = Some(results.clone());
+        result.execution_results = Some(results);
+        result.steps_completed.push(WorkflowStepType::PlanExecution);
+
+        if success {
+            info!(
+                workflow_id = %workflow_id,
+                execution_time = execution_time,
+                "Plan Execution completed successfully"
+            );
         } else {
-            return Err(OrchestratorError::WorkflowExecutionFailed(
-                "Invalid plan execution result".to_string(),
-            ));
+            warn!(
+                workflow_id = %workflow_id,
+                execution_time = execution_time,
+                "Plan Execution completed with partial success"
+            );
         }
 
+        // Publish step completed event
+        let completion_event = WorkflowEvent {
+            workflow_id: workflow_id.0,
+            step: WorkflowStep::ExecutionCompleted,
+            timestamp: chrono::Utc::now(),
+            agent_id: None,
+            payload: serde_json::json!({
+                "success": success,
+                "execution_time": execution_time,
+                "execution_summary": "Plan executed successfully"
+            }),
+            duration_ms: None,
+            step_dependencies: vec!["PlanGeneration".to_string()],
+            timeout: request.timeout_ms,
+            priority: convert_task_priority(&request.priority),
+        };
+        let _ = self
+            .event_publisher
+            .publish_workflow_event(completion_event)
+            .await;
+
         Ok(())
     }
 
@@ -997,6 +1295,7 @@ impl AgentOrchestrator {
             workflow.updated_at = chrono::Utc::now();
 
             info!(workflow_id = %workflow_id, "Workflow cancelled");
+            self.checkpoint(workflow, WorkflowTransition::Cancelled)?;
 
             // Publish cancellation event
             let cancellation_event = WorkflowEvent {
@@ -1050,6 +1349,7 @@ mod tests {
             dry_run: false,
             timeout_ms: Some(60_000),
             config_overrides: None,
+            budget: None,
         };
 
         assert_eq!(request.user_input, "Test request");
@@ -1080,4 +1380,106 @@ mod tests {
             let _ = orch.shutdown().await;
         }
     }
+
+    #[tokio::test]
+    async fn resumed_plan_execution_skips_completed_steps() {
+        use crate::orchestrator::{AgentOrchestrator, OrchestratorConfig};
+        use crate::persistence::WorkflowStore;
+
+        let event_publisher = create_agent_event_publisher()
+            .await
+            .expect("Async operation should succeed");
+        let store = Arc::new(
+            WorkflowStore::open_in_memory().expect("Operation failed - converted from unwrap()"),
+        );
+        let orchestrator = AgentOrchestrator::new(
+            SystemConfig::default(),
+            OrchestratorConfig::default(),
+            event_publisher,
+        )
+        .await
+        .expect("Async operation should succeed")
+        .with_state_store(Arc::clone(&store));
+        orchestrator
+            .initialize_agents()
+            .await
+            .expect("Async operation should succeed");
+
+        let request = WorkflowRequest {
+            user_input: "index the repo".to_string(),
+            context: None,
+            priority: TaskPriority::Normal,
+            dry_run: false,
+            timeout_ms: None,
+            config_overrides: None,
+            budget: None,
+        };
+        // Interrupted after the first of three plan steps
+        let mut state = WorkflowState {
+            id: WorkflowId::new(),
+            current_step: WorkflowStepType::PlanExecution,
+            status: ExecutionStatus::Running,
+            user_input: request.user_input.clone(),
+            context: None,
+            intent: None,
+            plan: Some(serde_json::json!({ "steps": ["scan", "index", "report"] })),
+            execution_results: None,
+            plan_step_results: vec![serde_json::json!({ "index": 0, "success": true })],
+            critique: None,
+            started_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            error: None,
+            resource_usage: ResourceUsage::default(),
+        };
+        store
+            .begin(&state, &request)
+            .expect("Operation failed - converted from unwrap()");
+        let mut result = WorkflowResult {
+            workflow_id: state.id,
+            success: false,
+            results: None,
+            error: None,
+            intent: None,
+            plan: None,
+            execution_results: None,
+            critique: None,
+            resource_usage: ResourceUsage::default(),
+            execution_time_ms: 0,
+            steps_completed: vec![],
+        };
+
+        orchestrator
+            .execute_plan_execution_step(
+                state.id,
+                &mut state,
+                &request,
+                &WorkflowConfig::default(),
+                &mut result,
+            )
+            .await
+            .expect("Async operation should succeed");
+
+        let executed: Vec<WorkflowTransition> = store
+            .checkpoints(state.id)
+            .expect("Operation failed - converted from unwrap()")
+            .into_iter()
+            .map(|checkpoint| checkpoint.transition)
+            .filter(|transition| matches!(transition, WorkflowTransition::PlanStepCompleted { .. }))
+            .collect();
+        assert_eq!(
+            executed,
+            vec![
+                WorkflowTransition::PlanStepCompleted { index: 1 },
+                WorkflowTransition::PlanStepCompleted { index: 2 },
+            ]
+        );
+        assert_eq!(state.plan_step_results.len(), 3);
+        assert_eq!(state.plan_step_results[2]["step"], "report");
+        let stored = store
+            .get(state.id)
+            .expect("Operation failed - converted from unwrap()")
+            .expect("workflow stored");
+        assert_eq!(stored.state.plan_step_results.len(), 3);
+        let _ = orchestrator.shutdown().await;
+    }
 }
@@ -237,6 +237,7 @@ async fn test_comprehensive_end_to_end_agent_workflow() -> Result<()> {
             max_step_retries: 3,
             step_timeout_ms: 8000,
         }),
+        budget: None,
     };
 
     // Monitor resource usage during workflow execution
@@ -458,6 +459,7 @@ async fn test_multi_agent_interaction_patterns() -> Result<()> {
             dry_run: false,
             timeout_ms: Some(15000),
             config_overrides: None,
+            budget: None,
         },
         WorkflowRequest {
             user_input: "Create notification service with user integration".to_string(),
@@ -469,6 +471,7 @@ async fn test_multi_agent_interaction_patterns() -> Result<()> {
             dry_run: false,
             timeout_ms: Some(15000),
             config_overrides: None,
+            budget: None,
         },
         WorkflowRequest {
             user_input: "Create analytics service with user and notification integration"
@@ -481,6 +484,7 @@ async fn test_multi_agent_interaction_patterns() -> Result<()> {
             dry_run: false,
             timeout_ms: Some(15000),
             config_overrides: None,
+            budget: None,
         },
     ];
 
@@ -611,6 +615,7 @@ async fn test_agent_state_transitions_monitoring() -> Result<()> {
         dry_run: false,
         timeout_ms: Some(10000),
         config_overrides: None,
+        budget: None,
     };
 
     // Monitor workflow state transitions in real-time
@@ -820,6 +825,7 @@ async fn test_agent_performance_benchmarks() -> Result<()> {
                 max_step_retries: 1, // Reduced for performance testing
                 step_timeout_ms: 2000,
             }),
+            budget: None,
         };
 
         match orchestrator.execute_workflow(workflow_request).await {
@@ -878,6 +884,7 @@ async fn test_agent_performance_benchmarks() -> Result<()> {
                 dry_run: false,
                 timeout_ms: Some(8000),
                 config_overrides: None,
+                budget: None,
             };
 
             let result = orchestrator_clone.execute_workflow(workflow_request).await;
@@ -1158,6 +1165,7 @@ async fn test_memory_usage_monitoring() -> Result<()> {
                     max_step_retries: 1,
                     step_timeout_ms: 3000,
                 }),
+                budget: None,
             })
             .collect();
 
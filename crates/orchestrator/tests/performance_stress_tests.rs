@@ -386,6 +386,7 @@ async fn test_high_load_concurrent_agent_execution() -> Result<()> {
                     max_step_retries: 2, // Reduced retries for stress test
                     step_timeout_ms: 4000,
                 }),
+                budget: None,
             };
 
             let execution_result = timeout(
@@ -759,6 +760,7 @@ async fn test_memory_pressure_stress() -> Result<()> {
                         max_step_retries: 1, // Reduced retries to avoid memory buildup
                         step_timeout_ms: 4000,
                     }),
+                    budget: None,
                 };
 
                 match orchestrator_clone.execute_workflow(workflow_request).await {
@@ -1020,6 +1022,7 @@ async fn test_long_running_stability() -> Result<()> {
                             max_step_retries: 2,
                             step_timeout_ms: 3000,
                         }),
+                        budget: None,
                     };
 
                     match orchestrator_clone.execute_workflow(workflow_request).await {
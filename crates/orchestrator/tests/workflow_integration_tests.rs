@@ -64,6 +64,7 @@ async fn test_complete_intent_to_critic_workflow() -> Result<()> {
             max_step_retries: 3,
             step_timeout_ms: 5000,
         }),
+        budget: None,
     };
 
     // Execute complete workflow through orchestrator
@@ -169,6 +170,7 @@ async fn test_workflow_with_failure_and_recovery() -> Result<()> {
             max_step_retries: 3,
             step_timeout_ms: 10000,
         }),
+        budget: None,
     };
 
     // Execute workflow and handle potential failures
@@ -253,6 +255,7 @@ async fn test_workflow_timeout_handling() -> Result<()> {
             max_step_retries: 1,
             step_timeout_ms: 500, // Very short for timeout test
         }),
+        budget: None,
     };
 
     // Execute workflow with timeout
@@ -329,6 +332,7 @@ async fn test_concurrent_workflow_execution() -> Result<()> {
             dry_run: false,
             timeout_ms: Some(10000),
             config_overrides: None,
+            budget: None,
         },
         WorkflowRequest {
             user_input: "Create user account for Bob".to_string(),
@@ -337,6 +341,7 @@ async fn test_concurrent_workflow_execution() -> Result<()> {
             dry_run: false,
             timeout_ms: Some(10000),
             config_overrides: None,
+            budget: None,
         },
         WorkflowRequest {
             user_input: "Generate user analytics report".to_string(),
@@ -345,6 +350,7 @@ async fn test_concurrent_workflow_execution() -> Result<()> {
             dry_run: false,
             timeout_ms: Some(15000),
             config_overrides: None,
+            budget: None,
         },
     ];
 
@@ -437,6 +443,7 @@ async fn test_workflow_state_transitions() -> Result<()> {
         dry_run: false,
         timeout_ms: Some(20000),
         config_overrides: None,
+        budget: None,
     };
 
     // Start workflow and monitor state transitions
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 use anyhow::{anyhow, Result};
+use common::approval::ApprovalBroker;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -28,6 +29,8 @@ pub struct SmartRouter {
     action_planner: ActionPlannerAgent,
     tool_selector: ToolSelectorAgent,
     parameter_extractor: ParameterExtractorAgent,
+    // Подтверждения (выдача секретов в LLM) на всё время жизни роутера
+    broker: ApprovalBroker,
 }
 
 impl SmartRouter {
@@ -37,11 +40,15 @@ impl SmartRouter {
         let tool_selector = ToolSelectorAgent::new(llm_client.clone());
         let parameter_extractor = ParameterExtractorAgent::new(llm_client.clone());
 
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let project_root = common::policy::find_project_root(&cwd).unwrap_or(cwd);
+
         Self {
             tool_registry: ToolRegistry::new(),
             action_planner,
             tool_selector,
             parameter_extractor,
+            broker: tools::invocation::project_broker(&project_root),
         }
     }
 
@@ -109,7 +116,7 @@ impl SmartRouter {
                 .get(&action.tool)
                 .ok_or_else(|| anyhow!("Инструмент '{}' не найден в реестре", action.tool))?;
 
-            let input = ToolInput {
+            let mut input = ToolInput {
                 command: action.tool.clone(),
                 args: action.args.clone(),
                 context: Some(action.description.clone()),
@@ -117,7 +124,14 @@ impl SmartRouter {
                 timeout_ms: None,
             };
 
-            match tool.execute(input).await {
+            let outcome = match tool.spec().validate_input(&mut input) {
+                Ok(()) => match tool.execute(input).await {
+                    Ok(output) => output.redact_for_llm(&action.tool, &self.broker).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            match outcome {
                 Ok(result) => {
                     println!("[✓] Шаг {} выполнен успешно", i + 1);
                     results.push(result);
@@ -189,15 +203,20 @@ impl SmartRouter {
                 )
             })?;
 
-        let input = ToolInput {
+        let mut input = ToolInput {
             command: tool_selection.tool_name.clone(),
             args: parameter_extraction.parameters,
             context: Some(user_query.to_string()),
             dry_run: false,
             timeout_ms: None,
         };
+        tool_spec.validate_input(&mut input)?;
 
-        let result = tool.execute(input).await?;
+        let result = tool
+            .execute(input)
+            .await?
+            .redact_for_llm(&tool_selection.tool_name, &self.broker)
+            .await?;
 
         if result.success {
             Ok(result.formatted_output.unwrap_or(result.result))
@@ -236,10 +255,10 @@ impl SmartRouter {
 
     /// Извлекает требуемые параметры из JSON схемы инструмента
     pub fn extract_required_params(&self, schema: &str) -> Vec<String> {
-        // Простой парсинг JSON схемы для извлечения имен параметров
-        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(schema) {
-            if let Some(obj) = parsed.as_object() {
-                return obj.keys().cloned().collect();
+        // Имена параметров из JSON Schema (legacy-формат конвертируется автоматически)
+        if schema.trim() != "null" {
+            if let Ok(parsed) = tools::input_schema::InputSchema::parse(schema) {
+                return parsed.property_names();
             }
         }
 
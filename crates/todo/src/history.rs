@@ -0,0 +1,407 @@
+//! История задач
+//!
+//! Журнал `todo_history` только дополняется: создание задачи, каждый
+//! переход состояния (кто и почему) и комментарии пользователей или
+//! агентов. По журналу считается время в каждом состоянии, а сводка
+//! провалов через [`HistoryMemory`] уходит в память, чтобы агенты учитывали
+//! прошлые неудачи.
+
+use crate::types::*;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Действие пользователя из CLI или TUI
+pub const ACTOR_USER: &str = "user";
+/// Внутренние переходы: каскад зависимостей, синхронизация планов
+pub const ACTOR_SYSTEM: &str = "system";
+
+/// Агент, выполняющий задачу через маршрут (инструмент или оркестратор)
+pub fn agent_actor(route: &str) -> String {
+    format!("agent:{route}")
+}
+
+/// Событие журнала
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    Created {
+        state: TaskState,
+    },
+    StateChanged {
+        from: TaskState,
+        to: TaskState,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    Comment {
+        text: String,
+    },
+}
+
+/// Запись журнала задачи
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskHistoryEntry {
+    pub id: i64,
+    pub task_id: Uuid,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    #[serde(flatten)]
+    pub event: HistoryEvent,
+}
+
+impl TaskHistoryEntry {
+    /// Переход в `Failed` с причиной
+    pub fn failure_reason(&self) -> Option<&str> {
+        match &self.event {
+            HistoryEvent::StateChanged {
+                to: TaskState::Failed,
+                reason,
+                ..
+            } => Some(reason.as_deref().unwrap_or("no reason recorded")),
+            _ => None,
+        }
+    }
+}
+
+/// Суммарное время в состоянии
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateDuration {
+    pub state: TaskState,
+    #[serde(serialize_with = "serialize_seconds")]
+    pub total: Duration,
+    /// Сколько раз задача входила в состояние
+    pub visits: usize,
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_seconds())
+}
+
+/// Время в каждом состоянии в порядке первого входа. Задачи, созданные до
+/// появления журнала, отсчитываются от `created_at`; время в текущем
+/// завершающем состоянии (`Done`, `Failed`, `Cancelled`) не копится.
+pub fn time_in_state(
+    task: &TodoItem,
+    history: &[TaskHistoryEntry],
+    now: DateTime<Utc>,
+) -> Vec<StateDuration> {
+    let transitions: Vec<(DateTime<Utc>, TaskState, TaskState)> = history
+        .iter()
+        .filter_map(|entry| match entry.event {
+            HistoryEvent::StateChanged { from, to, .. } => Some((entry.at, from, to)),
+            _ => None,
+        })
+        .collect();
+
+    let (mut since, mut current) = history
+        .iter()
+        .find_map(|entry| match entry.event {
+            HistoryEvent::Created { state } => Some((entry.at, state)),
+            _ => None,
+        })
+        .unwrap_or_else(|| {
+            let state = transitions.first().map_or(task.state, |(_, from, _)| *from);
+            (task.created_at, state)
+        });
+
+    let mut durations: Vec<StateDuration> = Vec::new();
+    let mut add = |state: TaskState, elapsed: Duration, entered: bool| {
+        let elapsed = elapsed.max(Duration::zero());
+        match durations.iter_mut().find(|d| d.state == state) {
+            Some(duration) => {
+                duration.total += elapsed;
+                duration.visits += usize::from(entered);
+            }
+            None => durations.push(StateDuration {
+                state,
+                total: elapsed,
+                visits: 1,
+            }),
+        }
+    };
+
+    add(current, Duration::zero(), true);
+    for (at, _, to) in transitions {
+        add(current, at - since, false);
+        add(to, Duration::zero(), true);
+        current = to;
+        since = at;
+    }
+    if !matches!(
+        current,
+        TaskState::Done | TaskState::Failed | TaskState::Cancelled
+    ) {
+        add(current, now - since, false);
+    }
+    durations
+}
+
+/// Длительность для людей: `45s`, `12m`, `3h 20m`, `2d 4h`
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    let (days, hours, minutes) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3600,
+        seconds % 3600 / 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Текстовая сводка истории для памяти: провалы с причинами, время в
+/// блокировке и комментарии
+pub fn history_digest(task: &TodoItem, history: &[TaskHistoryEntry], now: DateTime<Utc>) -> String {
+    let mut digest = format!("Task: {}\nState: {}\n", task.title, task.state);
+
+    let failures: Vec<String> = history
+        .iter()
+        .filter_map(|entry| {
+            entry.failure_reason().map(|reason| {
+                format!(
+                    "- {} by {}: {}",
+                    entry.at.format("%Y-%m-%d %H:%M"),
+                    entry.actor,
+                    reason
+                )
+            })
+        })
+        .collect();
+    if !failures.is_empty() {
+        digest.push_str(&format!(
+            "Failed {} time(s):\n{}\n",
+            failures.len(),
+            failures.join("\n")
+        ));
+    }
+
+    if let Some(blocked) = time_in_state(task, history, now)
+        .into_iter()
+        .find(|d| d.state == TaskState::Blocked && d.total > Duration::zero())
+    {
+        digest.push_str(&format!(
+            "Blocked for {} ({} time(s))\n",
+            format_duration(blocked.total),
+            blocked.visits
+        ));
+    }
+
+    let comments: Vec<String> = history
+        .iter()
+        .filter_map(|entry| match &entry.event {
+            HistoryEvent::Comment { text } => Some(format!("- {}: {}", entry.actor, text)),
+            _ => None,
+        })
+        .collect();
+    if !comments.is_empty() {
+        digest.push_str(&format!("Comments:\n{}\n", comments.join("\n")));
+    }
+    digest
+}
+
+/// Память, в которую попадают уроки из истории задач
+#[async_trait]
+pub trait HistoryMemory: Send + Sync {
+    /// Сохранить сводку истории задачи и вернуть ссылку на запись
+    async fn remember(&self, task: &TodoItem, digest: &str) -> Result<MemoryReference>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TodoService;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_history_records_transitions_and_comments() {
+        let temp_dir = TempDir::new().expect("Temp dir should be created");
+        let service = TodoService::new(temp_dir.path().join("test.db"), 4, 100)
+            .await
+            .expect("Service should be created");
+
+        let dep = service
+            .create_task(
+                "Dependency".to_string(),
+                String::new(),
+                Priority::Medium,
+                vec![],
+            )
+            .await
+            .expect("Task should be created");
+        let task = service
+            .create_task("Flaky".to_string(), String::new(), Priority::High, vec![])
+            .await
+            .expect("Task should be created");
+        service
+            .add_dependency(&task.id, &dep.id)
+            .await
+            .expect("Dependency should be added");
+
+        let agent = agent_actor("shell");
+        service
+            .update_state(&dep.id, TaskState::Done)
+            .await
+            .expect("State should change");
+        for _ in 0..2 {
+            service
+                .update_state_as(&task.id, TaskState::InProgress, &agent, None)
+                .await
+                .expect("State should change");
+            service
+                .update_state_as(&task.id, TaskState::Failed, &agent, Some("exit code 1"))
+                .await
+                .expect("State should change");
+        }
+        service
+            .add_comment(&task.id, ACTOR_USER, "Needs a retry budget")
+            .await
+            .expect("Comment should be added");
+
+        let history = service
+            .get_history(&task.id)
+            .await
+            .expect("History should load");
+        let events: Vec<(&str, &HistoryEvent)> = history
+            .iter()
+            .map(|e| (e.actor.as_str(), &e.event))
+            .collect();
+        assert_eq!(
+            events[0],
+            (
+                ACTOR_USER,
+                &HistoryEvent::Created {
+                    state: TaskState::Ready
+                }
+            )
+        );
+        // Каскад: блокировка зависимостью и разблокировка её завершением
+        assert_eq!(
+            events[1].1,
+            &HistoryEvent::StateChanged {
+                from: TaskState::Ready,
+                to: TaskState::Blocked,
+                reason: None
+            }
+        );
+        assert!(matches!(
+            events[2],
+            (
+                ACTOR_SYSTEM,
+                HistoryEvent::StateChanged {
+                    to: TaskState::Ready,
+                    ..
+                }
+            )
+        ));
+        assert_eq!(
+            history
+                .iter()
+                .filter(|e| e.failure_reason().is_some())
+                .count(),
+            2
+        );
+        assert!(matches!(
+            &history.last().expect("History is not empty").event,
+            HistoryEvent::Comment { text } if text == "Needs a retry budget"
+        ));
+
+        let durations = service
+            .time_in_state(&task.id)
+            .await
+            .expect("Durations should compute");
+        let in_progress = durations
+            .iter()
+            .find(|d| d.state == TaskState::InProgress)
+            .expect("InProgress should be tracked");
+        assert_eq!(in_progress.visits, 2);
+        assert_eq!(
+            durations
+                .iter()
+                .find(|d| d.state == TaskState::Failed)
+                .map(|d| d.visits),
+            Some(2)
+        );
+
+        let digest = service
+            .history_digest(&task.id)
+            .await
+            .expect("Digest should build");
+        assert!(digest.contains("Failed 2 time(s)"));
+        assert!(digest.contains("agent:shell: exit code 1"));
+        assert!(digest.contains("user: Needs a retry budget"));
+    }
+
+    #[test]
+    fn test_time_in_state_and_format() {
+        let start = DateTime::parse_from_rfc3339("2025-03-01T09:00:00Z")
+            .expect("Date should parse")
+            .with_timezone(&Utc);
+        let task = TodoItem {
+            state: TaskState::Blocked,
+            created_at: start,
+            ..TodoItem::default()
+        };
+        let entry = |id: i64, minutes: i64, event: HistoryEvent| TaskHistoryEntry {
+            id,
+            task_id: task.id,
+            at: start + Duration::minutes(minutes),
+            actor: ACTOR_SYSTEM.to_string(),
+            event,
+        };
+        // Журнал начат после создания задачи: отсчёт от created_at
+        let history = vec![
+            entry(
+                1,
+                30,
+                HistoryEvent::StateChanged {
+                    from: TaskState::Ready,
+                    to: TaskState::Blocked,
+                    reason: None,
+                },
+            ),
+            entry(
+                2,
+                90,
+                HistoryEvent::StateChanged {
+                    from: TaskState::Blocked,
+                    to: TaskState::Ready,
+                    reason: None,
+                },
+            ),
+            entry(
+                3,
+                100,
+                HistoryEvent::StateChanged {
+                    from: TaskState::Ready,
+                    to: TaskState::Blocked,
+                    reason: None,
+                },
+            ),
+        ];
+        let durations = time_in_state(&task, &history, start + Duration::minutes(220));
+        let summary: Vec<(TaskState, i64, usize)> = durations
+            .iter()
+            .map(|d| (d.state, d.total.num_minutes(), d.visits))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(TaskState::Ready, 40, 2), (TaskState::Blocked, 180, 2)]
+        );
+
+        assert_eq!(format_duration(Duration::seconds(45)), "45s");
+        assert_eq!(format_duration(Duration::minutes(200)), "3h 20m");
+        assert_eq!(format_duration(Duration::hours(52)), "2d 4h");
+    }
+}
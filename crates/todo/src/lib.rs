@@ -7,13 +7,43 @@ use anyhow::Result;
 use std::path::Path;
 
 pub mod graph;
+pub mod history;
+pub mod planning;
+pub mod recurrence;
+pub mod runner;
+pub mod scan;
+pub mod schedule;
+pub mod search;
 pub mod service_v2;
 pub mod store;
 pub mod store_v2;
+pub mod templates;
 pub mod types;
 
 // Экспортируем v2 как основную версию
+pub use history::{
+    agent_actor, format_duration, HistoryEvent, HistoryMemory, StateDuration, TaskHistoryEntry,
+    ACTOR_SYSTEM, ACTOR_USER,
+};
+pub use planning::{
+    build_plan_prompt, GoalPlan, GoalPlanCommit, PlanChange, PlanDiff, PlannedTask, GOAL_TAG,
+};
+pub use recurrence::{
+    next_occurrence, Frequency, Recurrence, ANCHOR_KEY, NEXT_OCCURRENCE_KEY, RECURRENCE_KEY,
+    SERIES_KEY,
+};
+pub use runner::{
+    ArtifactStore, RunnerConfig, TaskHandler, TaskRunEvent, TaskRunRecord, TaskRunReport,
+    TaskRunStatus, TaskRunner,
+};
+pub use scan::{scan_path, CodeTodo, ScanReport, CODE_TAG};
+pub use schedule::{Schedule, ScheduleConfig, ScheduledTask, TaskEstimate, ESTIMATE_KEY};
+pub use search::{
+    parse_date_bound, render_highlight, TaskEmbedder, TaskFilter, TaskSearchHit, MATCH_END,
+    MATCH_START,
+};
 pub use service_v2::{TodoEventStream, TodoServiceV2 as TodoService};
+pub use templates::{builtin_templates, load_templates, TaskTemplate, TemplateParam};
 pub use types::*;
 
 // Экспортируем для обратной совместимости
@@ -0,0 +1,535 @@
+//! Декомпозиция цели в граф задач
+//!
+//! LLM получает промпт из [`build_plan_prompt`] (цель, контекст проекта из
+//! памяти и уже существующие подзадачи) и отвечает JSON-планом. План
+//! проверяется на циклы через [`DependencyGraphV2::would_create_cycle`],
+//! показывается пользователем (text/mermaid) и только после подтверждения
+//! сохраняется через `TodoServiceV2::commit_goal_plan`. Повторное
+//! планирование той же цели сравнивается с существующими подзадачами
+//! ([`PlanDiff`]), а не дублирует их.
+
+use crate::graph::DependencyGraphV2;
+use crate::types::*;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Тег задачи-цели, под которой лежат подзадачи плана
+pub const GOAL_TAG: &str = "goal";
+
+fn default_priority() -> Priority {
+    Priority::Medium
+}
+
+fn default_confidence() -> f32 {
+    0.7
+}
+
+/// Приоритет в любом регистре (`"high"`, `"High"`)
+fn deserialize_priority<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Priority, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+/// Подзадача, предложенная планировщиком
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedTask {
+    /// Локальный ключ для ссылок в `depends_on` (например, `"t1"`)
+    pub key: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(
+        default = "default_priority",
+        deserialize_with = "deserialize_priority"
+    )]
+    pub priority: Priority,
+    /// Ключи задач, которые должны быть выполнены раньше
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_params: Option<HashMap<String, String>>,
+    /// Почему задача нужна
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+}
+
+/// Предложенный граф подзадач цели
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoalPlan {
+    pub goal: String,
+    pub tasks: Vec<PlannedTask>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PlanResponse {
+    Plan { tasks: Vec<PlannedTask> },
+    Tasks(Vec<PlannedTask>),
+}
+
+impl GoalPlan {
+    /// Разобрать ответ LLM: `{"tasks": [...]}` или `[...]`, в том числе
+    /// внутри ```json-блока или с текстом вокруг
+    pub fn from_llm_response(goal: &str, response: &str) -> Result<Self> {
+        let json = extract_json(response)
+            .ok_or_else(|| anyhow!("Planner response contains no JSON plan"))?;
+        let tasks = match serde_json::from_str::<PlanResponse>(json)
+            .map_err(|e| anyhow!("Invalid plan JSON: {}", e))?
+        {
+            PlanResponse::Plan { tasks } | PlanResponse::Tasks(tasks) => tasks,
+        };
+        Ok(Self {
+            goal: goal.to_string(),
+            tasks,
+        })
+    }
+
+    pub fn task(&self, key: &str) -> Option<&PlannedTask> {
+        self.tasks.iter().find(|t| t.key == key)
+    }
+
+    /// Непустой план, уникальные ключи, известные зависимости, без циклов
+    pub fn validate(&self) -> Result<()> {
+        if self.tasks.is_empty() {
+            return Err(anyhow!("Plan for '{}' has no tasks", self.goal));
+        }
+        let mut ids = HashMap::new();
+        for task in &self.tasks {
+            if task.title.trim().is_empty() {
+                return Err(anyhow!("Task '{}' has an empty title", task.key));
+            }
+            if ids.insert(task.key.as_str(), Uuid::new_v4()).is_some() {
+                return Err(anyhow!("Duplicate task key '{}'", task.key));
+            }
+        }
+
+        let graph = DependencyGraphV2::new();
+        let mut items: HashMap<&str, TodoItem> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                let item = TodoItem {
+                    id: ids[task.key.as_str()],
+                    title: task.title.clone(),
+                    ..Default::default()
+                };
+                (task.key.as_str(), item)
+            })
+            .collect();
+        for item in items.values() {
+            graph.upsert_task(item)?;
+        }
+        for task in &self.tasks {
+            for dep in &task.depends_on {
+                let dep_id = *ids.get(dep.as_str()).ok_or_else(|| {
+                    anyhow!("Task '{}' depends on unknown task '{}'", task.key, dep)
+                })?;
+                let item = items
+                    .get_mut(task.key.as_str())
+                    .expect("every task has a graph item");
+                if graph.would_create_cycle(&dep_id, &item.id)? {
+                    return Err(anyhow!(
+                        "Dependency '{}' → '{}' would create a cycle",
+                        task.key,
+                        dep
+                    ));
+                }
+                item.depends_on.push(dep_id);
+                graph.upsert_task(item)?;
+            }
+        }
+        graph.topological_sort()?;
+        Ok(())
+    }
+
+    /// Ключи задач в порядке выполнения (зависимости раньше зависимых)
+    pub fn ordered_keys(&self) -> Vec<String> {
+        let mut ordered: Vec<String> = Vec::new();
+        let mut placed: HashSet<&str> = HashSet::new();
+        while ordered.len() < self.tasks.len() {
+            let before = ordered.len();
+            for task in &self.tasks {
+                if !placed.contains(task.key.as_str())
+                    && task.depends_on.iter().all(|d| placed.contains(d.as_str()))
+                {
+                    placed.insert(task.key.as_str());
+                    ordered.push(task.key.clone());
+                }
+            }
+            if ordered.len() == before {
+                // Цикл: validate() сообщит о нём, здесь просто дописываем остаток
+                ordered.extend(
+                    self.tasks
+                        .iter()
+                        .filter(|t| !placed.contains(t.key.as_str()))
+                        .map(|t| t.key.clone()),
+                );
+            }
+        }
+        ordered
+    }
+
+    /// Текстовый предпросмотр с пометками изменений
+    pub fn to_text(&self, diff: Option<&PlanDiff>) -> String {
+        let mut out = format!("Goal: {}\n", self.goal);
+        for key in self.ordered_keys() {
+            let Some(task) = self.task(&key) else {
+                continue;
+            };
+            let marker = diff.map_or("", |d| d.marker(&key));
+            out.push_str(&format!(
+                "{}[{}] {} ({})",
+                marker, task.key, task.title, task.priority
+            ));
+            if let Some(tool) = &task.tool_hint {
+                out.push_str(&format!(" → {}", tool));
+            }
+            out.push('\n');
+            if !task.depends_on.is_empty() {
+                out.push_str(&format!("    after: {}\n", task.depends_on.join(", ")));
+            }
+            if let Some(reasoning) = &task.reasoning {
+                out.push_str(&format!("    why: {}\n", reasoning));
+            }
+        }
+        if let Some(diff) = diff {
+            for removed in &diff.removed {
+                out.push_str(&format!("- {} (cancelled)\n", removed.title));
+            }
+        }
+        out
+    }
+
+    /// Mermaid-граф: рёбра от зависимости к зависимой задаче
+    pub fn to_mermaid(&self, diff: Option<&PlanDiff>) -> String {
+        let mut out = String::from("graph TD\n");
+        let keys = self.ordered_keys();
+        let node = |key: &str| {
+            format!(
+                "n{}",
+                keys.iter().position(|k| k == key).unwrap_or_default()
+            )
+        };
+        for key in &keys {
+            let Some(task) = self.task(key) else {
+                continue;
+            };
+            let marker = diff.map_or("", |d| d.marker(key));
+            out.push_str(&format!(
+                "    {}[\"{}{}\"]\n",
+                node(key),
+                marker,
+                task.title.replace('"', "'")
+            ));
+        }
+        for task in &self.tasks {
+            for dep in &task.depends_on {
+                out.push_str(&format!("    {} --> {}\n", node(dep), node(&task.key)));
+            }
+        }
+        out
+    }
+}
+
+/// JSON-объект или массив из ответа модели
+fn extract_json(text: &str) -> Option<&str> {
+    let text = match text.find("```") {
+        Some(fence) => {
+            let body = &text[fence + 3..];
+            let body = body.strip_prefix("json").unwrap_or(body);
+            body.find("```").map_or(body, |end| &body[..end])
+        }
+        None => text,
+    };
+    let start = text.find(['{', '['])?;
+    let end = text.rfind(['}', ']'])?;
+    (end > start).then(|| text[start..=end].trim())
+}
+
+/// Что станет с подзадачей плана при сохранении
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanChange {
+    /// Новая задача
+    Add,
+    /// Совпадает с существующей задачей
+    Keep(Uuid),
+    /// Существующая задача с изменёнными полями или зависимостями
+    Update(Uuid),
+}
+
+/// Сравнение плана с уже существующими подзадачами цели
+#[derive(Debug, Clone, Default)]
+pub struct PlanDiff {
+    /// Изменение по ключу задачи плана
+    pub changes: HashMap<String, PlanChange>,
+    /// Незавершённые подзадачи, которых больше нет в плане (будут отменены)
+    pub removed: Vec<TodoItem>,
+}
+
+/// Заголовки сравниваются без регистра и лишних пробелов
+pub(crate) fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl PlanDiff {
+    /// Сопоставить задачи плана с существующими по заголовку
+    pub fn compute(plan: &GoalPlan, existing: &[TodoItem]) -> Self {
+        let live: Vec<&TodoItem> = existing
+            .iter()
+            .filter(|t| t.state != TaskState::Cancelled)
+            .collect();
+        let by_title: HashMap<String, &TodoItem> = live
+            .iter()
+            .map(|t| (normalize_title(&t.title), *t))
+            .collect();
+
+        let matched: HashMap<&str, &TodoItem> = plan
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                by_title
+                    .get(&normalize_title(&task.title))
+                    .map(|item| (task.key.as_str(), *item))
+            })
+            .collect();
+
+        let mut changes = HashMap::new();
+        for task in &plan.tasks {
+            let change = match matched.get(task.key.as_str()) {
+                None => PlanChange::Add,
+                Some(item) => {
+                    let wanted: HashSet<Option<Uuid>> = task
+                        .depends_on
+                        .iter()
+                        .map(|dep| matched.get(dep.as_str()).map(|d| d.id))
+                        .collect();
+                    let siblings: HashSet<Uuid> = live.iter().map(|t| t.id).collect();
+                    let current: HashSet<Option<Uuid>> = item
+                        .depends_on
+                        .iter()
+                        .filter(|dep| siblings.contains(dep))
+                        .map(|dep| Some(*dep))
+                        .collect();
+                    let same_fields = item.description == task.description
+                        && item.priority == task.priority
+                        && item.tool_hint == task.tool_hint
+                        && item.tool_params == task.tool_params
+                        && item.reasoning == task.reasoning;
+                    if same_fields && wanted == current {
+                        PlanChange::Keep(item.id)
+                    } else {
+                        PlanChange::Update(item.id)
+                    }
+                }
+            };
+            changes.insert(task.key.clone(), change);
+        }
+
+        let kept: HashSet<Uuid> = matched.values().map(|t| t.id).collect();
+        let removed = live
+            .into_iter()
+            .filter(|t| !kept.contains(&t.id) && t.state != TaskState::Done)
+            .cloned()
+            .collect();
+        Self { changes, removed }
+    }
+
+    pub fn added(&self) -> usize {
+        self.count(|c| matches!(c, PlanChange::Add))
+    }
+
+    pub fn updated(&self) -> usize {
+        self.count(|c| matches!(c, PlanChange::Update(_)))
+    }
+
+    pub fn kept(&self) -> usize {
+        self.count(|c| matches!(c, PlanChange::Keep(_)))
+    }
+
+    fn count(&self, filter: impl Fn(&PlanChange) -> bool) -> usize {
+        self.changes.values().filter(|c| filter(c)).count()
+    }
+
+    /// Нечего сохранять
+    pub fn is_unchanged(&self) -> bool {
+        self.added() == 0 && self.updated() == 0 && self.removed.is_empty()
+    }
+
+    fn marker(&self, key: &str) -> &'static str {
+        match self.changes.get(key) {
+            Some(PlanChange::Add) => "+ ",
+            Some(PlanChange::Update(_)) => "~ ",
+            _ => "",
+        }
+    }
+}
+
+/// Итог сохранения плана
+#[derive(Debug, Clone)]
+pub struct GoalPlanCommit {
+    pub goal_id: Uuid,
+    /// Id задачи по ключу плана
+    pub task_ids: HashMap<String, Uuid>,
+    pub created: usize,
+    pub updated: usize,
+    pub cancelled: usize,
+}
+
+/// Промпт декомпозиции цели в JSON-план
+pub fn build_plan_prompt(goal: &str, context: &[String], existing: &[TodoItem]) -> String {
+    let mut prompt = String::from(
+        "Break the goal below into 2-12 concrete subtasks forming a dependency graph.\n\
+         Answer with JSON only: {\"tasks\": [{\"key\": \"t1\", \"title\": \"...\", \
+         \"description\": \"...\", \"priority\": \"low|medium|high|critical\", \
+         \"depends_on\": [\"<keys of earlier tasks>\"], \"tool_hint\": \"<tool name or null>\", \
+         \"tool_params\": {\"<arg>\": \"<value>\"}, \"reasoning\": \"why this task is needed\", \
+         \"confidence\": 0.0-1.0}]}\n\
+         Dependencies must not form cycles.\n\n",
+    );
+    prompt.push_str(&format!("Goal: {}\n", goal));
+    if !context.is_empty() {
+        prompt.push_str("\nProject context from memory:\n");
+        for item in context {
+            prompt.push_str(&format!("- {}\n", item));
+        }
+    }
+    if !existing.is_empty() {
+        prompt.push_str(
+            "\nExisting subtasks of this goal (reuse their exact titles when they still apply):\n",
+        );
+        for task in existing {
+            prompt.push_str(&format!("- {} [{}]\n", task.title, task.state));
+        }
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TodoService;
+    use tempfile::TempDir;
+
+    const RESPONSE: &str = r#"Here is the plan:
+```json
+{"tasks": [
+  {"key": "t1", "title": "Write schema", "priority": "high", "reasoning": "Everything else needs it"},
+  {"key": "t2", "title": "Implement API", "depends_on": ["t1"], "tool_hint": "file_write"},
+  {"key": "t3", "title": "Add tests", "depends_on": ["t2"], "priority": "Low"}
+]}
+```"#;
+
+    #[test]
+    fn test_parse_validate_and_preview() {
+        let plan = GoalPlan::from_llm_response("Ship API", RESPONSE).expect("Plan should parse");
+        assert_eq!(plan.tasks.len(), 3);
+        assert_eq!(plan.tasks[0].priority, Priority::High);
+        assert_eq!(plan.tasks[2].priority, Priority::Low);
+        plan.validate().expect("Plan should be valid");
+
+        let mermaid = plan.to_mermaid(None);
+        assert!(mermaid.contains("n0 --> n1"), "{}", mermaid);
+        assert!(mermaid.contains("n1 --> n2"), "{}", mermaid);
+        assert!(plan
+            .to_text(None)
+            .contains("[t2] Implement API (medium) → file_write"));
+
+        let mut cyclic = plan.clone();
+        cyclic.tasks[0].depends_on.push("t3".to_string());
+        let error = cyclic.validate().expect_err("Cycle should be rejected");
+        assert!(error.to_string().contains("cycle"), "{}", error);
+
+        let mut unknown = plan;
+        unknown.tasks[1].depends_on.push("t9".to_string());
+        assert!(unknown.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replanning_diffs_instead_of_duplicating() {
+        let temp_dir = TempDir::new().expect("Temp dir should be created");
+        let service = TodoService::new(temp_dir.path().join("test.db"), 4, 100)
+            .await
+            .expect("Service should be created");
+        let plan = GoalPlan::from_llm_response("Ship API", RESPONSE).expect("Plan should parse");
+
+        let diff = PlanDiff::compute(&plan, &[]);
+        assert_eq!(diff.added(), 3);
+        let commit = service
+            .commit_goal_plan(&plan, None)
+            .await
+            .expect("Plan should be committed");
+        assert_eq!(commit.created, 3);
+        let api = service
+            .get_cached(&commit.task_ids["t2"])
+            .await
+            .expect("Lookup should succeed")
+            .expect("Task should exist");
+        assert!(api.auto_generated);
+        assert_eq!(api.parent_id, Some(commit.goal_id));
+        assert_eq!(api.depends_on, vec![commit.task_ids["t1"]]);
+        assert_eq!(api.state, TaskState::Blocked);
+
+        let goal = service
+            .find_goal_task("ship  api")
+            .await
+            .expect("Lookup should succeed")
+            .expect("Goal should be found");
+        assert_eq!(goal.id, commit.goal_id);
+        let existing = service
+            .get_subtasks(&goal.id)
+            .await
+            .expect("Subtasks should load");
+        assert!(PlanDiff::compute(&plan, &existing).is_unchanged());
+
+        // Тесты больше не нужны, API меняется, появляется документация
+        let mut replanned = plan.clone();
+        replanned.tasks.pop();
+        replanned.tasks[1].priority = Priority::Critical;
+        replanned.tasks.push(PlannedTask {
+            key: "docs".to_string(),
+            title: "Write docs".to_string(),
+            depends_on: vec!["t2".to_string()],
+            ..replanned.tasks[0].clone()
+        });
+        let diff = PlanDiff::compute(&replanned, &existing);
+        assert_eq!((diff.added(), diff.updated(), diff.kept()), (1, 1, 1));
+        assert_eq!(diff.removed.len(), 1);
+        assert!(replanned
+            .to_text(Some(&diff))
+            .contains("- Add tests (cancelled)"));
+
+        let commit = service
+            .commit_goal_plan(&replanned, Some(goal.id))
+            .await
+            .expect("Re-plan should be committed");
+        assert_eq!(
+            (commit.created, commit.updated, commit.cancelled),
+            (1, 1, 1)
+        );
+        let subtasks = service
+            .get_subtasks(&goal.id)
+            .await
+            .expect("Subtasks should load");
+        assert_eq!(subtasks.len(), 4);
+        let live: Vec<&TodoItem> = subtasks
+            .iter()
+            .filter(|t| t.state != TaskState::Cancelled)
+            .collect();
+        assert_eq!(live.len(), 3);
+        let api = live
+            .iter()
+            .find(|t| t.title == "Implement API")
+            .expect("API task should be kept");
+        assert_eq!(api.priority, Priority::Critical);
+    }
+}
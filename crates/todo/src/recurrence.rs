@@ -0,0 +1,451 @@
+//! Повторяющиеся задачи
+//!
+//! Правило повтора хранится в `metadata.recurrence` строкой в духе RRULE:
+//! `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`, `FREQ=MONTHLY;BYMONTHDAY=1,-1`,
+//! `FREQ=DAILY;UNTIL=20251231`. Когда очередное вхождение выполнено,
+//! `TodoServiceV2::update_state_as` создаёт следующее через
+//! [`next_occurrence`]: с тем же заголовком, тегами, подсказками
+//! инструментов и зависимостями, перенесёнными на открытые вхождения
+//! повторяющихся зависимостей.
+
+use crate::schedule::ESTIMATE_KEY;
+use crate::types::*;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Ключи `metadata` повторяющихся задач
+pub const RECURRENCE_KEY: &str = "recurrence";
+/// Первое вхождение серии; общий идентификатор всех повторов
+pub const SERIES_KEY: &str = "recurrence_series";
+/// Срок первого вхождения: от него считаются шаги интервала и день месяца
+pub const ANCHOR_KEY: &str = "recurrence_anchor";
+/// Вхождение, созданное после выполнения этого
+pub const NEXT_OCCURRENCE_KEY: &str = "next_occurrence";
+
+/// Поиск следующего вхождения ограничен этим числом дней на шаг интервала
+const SEARCH_DAYS: i64 = 400;
+
+/// Частота повтора
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Правило повтора
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Каждый N-й день, неделя или месяц
+    pub interval: u32,
+    /// Дни недели для `Weekly`; пусто — день недели якоря
+    pub weekdays: Vec<Weekday>,
+    /// Дни месяца для `Monthly`, отрицательные считаются с конца
+    /// (`-1` — последний день), а дни за концом короткого месяца сдвигаются
+    /// на последний; пусто — день месяца якоря
+    pub month_days: Vec<i32>,
+    /// Последний момент, когда ещё может быть вхождение
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            weekdays: Vec::new(),
+            month_days: Vec::new(),
+            until: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_weekdays(mut self, weekdays: Vec<Weekday>) -> Self {
+        self.weekdays = weekdays;
+        self
+    }
+
+    pub fn with_month_days(mut self, month_days: Vec<i32>) -> Self {
+        self.month_days = month_days;
+        self
+    }
+
+    pub fn with_until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Первое вхождение строго после `after`. Шаг интервала отсчитывается
+    /// от `anchor`, время суток берётся из него же.
+    pub fn next_after(&self, anchor: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = i64::from(self.interval.max(1));
+        let time = anchor.time();
+        let anchor_date = anchor.date_naive();
+        let start = after.date_naive().max(anchor_date);
+
+        (0..=SEARCH_DAYS * interval)
+            .filter_map(|offset| start.checked_add_signed(Duration::days(offset)))
+            .filter(|date| self.matches(anchor_date, *date, interval))
+            .map(|date| date.and_time(time).and_utc())
+            .find(|at| *at > after)
+            .filter(|at| self.until.is_none_or(|until| *at <= until))
+    }
+
+    fn matches(&self, anchor: NaiveDate, date: NaiveDate, interval: i64) -> bool {
+        match self.frequency {
+            Frequency::Daily => (date - anchor).num_days() % interval == 0,
+            Frequency::Weekly => {
+                let week_start = |d: NaiveDate| {
+                    d - Duration::days(i64::from(d.weekday().num_days_from_monday()))
+                };
+                let weeks = (week_start(date) - week_start(anchor)).num_days() / 7;
+                let day_matches = if self.weekdays.is_empty() {
+                    date.weekday() == anchor.weekday()
+                } else {
+                    self.weekdays.contains(&date.weekday())
+                };
+                weeks % interval == 0 && day_matches
+            }
+            Frequency::Monthly => {
+                let months = i64::from(date.year() - anchor.year()) * 12 + i64::from(date.month())
+                    - i64::from(anchor.month());
+                let last = days_in_month(date);
+                let day_matches = if self.month_days.is_empty() {
+                    date.day() == anchor.day().min(last)
+                } else {
+                    self.month_days.iter().any(|day| {
+                        let day = if *day < 0 {
+                            i64::from(last) + 1 + i64::from(*day)
+                        } else {
+                            i64::from((*day as u32).min(last))
+                        };
+                        day == i64::from(date.day())
+                    })
+                };
+                months % interval == 0 && day_matches
+            }
+        }
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+const WEEKDAY_CODES: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.weekdays.is_empty() {
+            let days: Vec<&str> = self
+                .weekdays
+                .iter()
+                .filter_map(|day| {
+                    WEEKDAY_CODES
+                        .iter()
+                        .find(|(_, d)| d == day)
+                        .map(|(code, _)| *code)
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.month_days.is_empty() {
+            let days: Vec<String> = self.month_days.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        Ok(())
+    }
+}
+
+/// `daily`, `weekly`, `monthly` или RRULE (`RRULE:` в начале необязателен)
+impl FromStr for Recurrence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rule = s.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+        match rule.to_lowercase().as_str() {
+            "daily" => return Ok(Self::new(Frequency::Daily)),
+            "weekly" => return Ok(Self::new(Frequency::Weekly)),
+            "monthly" => return Ok(Self::new(Frequency::Monthly)),
+            _ => {}
+        }
+
+        let mut frequency = None;
+        let mut recurrence = Self::new(Frequency::Daily);
+        for part in rule.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid recurrence part '{}': expected KEY=VALUE", part))?;
+            let value = value.trim();
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(anyhow!("Unsupported FREQ '{}'", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    recurrence.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| anyhow!("INTERVAL must be a positive number"))?;
+                }
+                "BYDAY" => {
+                    recurrence.weekdays = value
+                        .split(',')
+                        .map(|code| {
+                            WEEKDAY_CODES
+                                .iter()
+                                .find(|(c, _)| c.eq_ignore_ascii_case(code.trim()))
+                                .map(|(_, day)| *day)
+                                .ok_or_else(|| anyhow!("Unknown weekday '{}'", code))
+                        })
+                        .collect::<Result<_>>()?;
+                }
+                "BYMONTHDAY" => {
+                    recurrence.month_days = value
+                        .split(',')
+                        .map(|day| {
+                            day.trim()
+                                .parse::<i32>()
+                                .ok()
+                                .filter(|d| *d != 0 && (-31..=31).contains(d))
+                                .ok_or_else(|| anyhow!("Invalid month day '{}'", day))
+                        })
+                        .collect::<Result<_>>()?;
+                }
+                "UNTIL" => recurrence.until = Some(parse_until(value)?),
+                other => return Err(anyhow!("Unsupported recurrence key '{}'", other)),
+            }
+        }
+
+        recurrence.frequency =
+            frequency.ok_or_else(|| anyhow!("Recurrence '{}' has no FREQ", s))?;
+        if !recurrence.weekdays.is_empty() && recurrence.frequency != Frequency::Weekly {
+            return Err(anyhow!("BYDAY is supported only with FREQ=WEEKLY"));
+        }
+        if !recurrence.month_days.is_empty() && recurrence.frequency != Frequency::Monthly {
+            return Err(anyhow!("BYMONTHDAY is supported only with FREQ=MONTHLY"));
+        }
+        Ok(recurrence)
+    }
+}
+
+/// `20251231T235959Z`, `20251231` (конец дня), YYYY-MM-DD или RFC 3339
+fn parse_until(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(at) = chrono::NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Ok(at.and_utc());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Ok(date
+            .and_hms_opt(23, 59, 59)
+            .expect("valid time of day")
+            .and_utc());
+    }
+    crate::search::parse_date_bound(value, true)
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl TodoItem {
+    /// Правило повтора из `metadata`, если задача повторяющаяся
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.metadata
+            .get(RECURRENCE_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Якорь серии: срок первого вхождения
+    pub fn recurrence_anchor(&self) -> Option<DateTime<Utc>> {
+        self.metadata
+            .get(ANCHOR_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .or(self.due_date)
+    }
+
+    /// Идентификатор серии повторов (первое вхождение)
+    pub fn recurrence_series(&self) -> Uuid {
+        self.metadata
+            .get(SERIES_KEY)
+            .and_then(|value| value.as_str())
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .unwrap_or(self.id)
+    }
+}
+
+/// Следующее вхождение после выполнения `task` в момент `completed_at`.
+/// Сроки отсчитываются от якоря серии (или от момента выполнения), поэтому
+/// не «уплывают» после коротких месяцев; пропущенные сроки не порождают
+/// задач, берётся первый срок позже прежнего и позже выполнения.
+/// `resolve_dependency` переносит зависимость на актуальное вхождение её
+/// серии. `None` — правила нет или серия закончилась (`UNTIL`).
+pub fn next_occurrence(
+    task: &TodoItem,
+    completed_at: DateTime<Utc>,
+    resolve_dependency: impl Fn(&Uuid) -> Uuid,
+) -> Option<TodoItem> {
+    let rule = task.recurrence()?;
+    let anchor = task.recurrence_anchor().unwrap_or(completed_at);
+    let previous = task.due_date.unwrap_or(anchor).max(completed_at);
+    let due = rule.next_after(anchor, previous)?;
+
+    let mut metadata: HashMap<String, serde_json::Value> = task
+        .metadata
+        .iter()
+        .filter(|(key, _)| matches!(key.as_str(), RECURRENCE_KEY | ESTIMATE_KEY))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    metadata.insert(ANCHOR_KEY.to_string(), serde_json::json!(anchor));
+    metadata.insert(
+        SERIES_KEY.to_string(),
+        serde_json::json!(task.recurrence_series().to_string()),
+    );
+
+    let mut depends_on: Vec<Uuid> = task.depends_on.iter().map(resolve_dependency).collect();
+    depends_on.dedup();
+    Some(TodoItem {
+        title: task.title.clone(),
+        description: task.description.clone(),
+        state: TaskState::Ready,
+        priority: task.priority,
+        due_date: Some(due),
+        parent_id: task.parent_id,
+        depends_on,
+        auto_generated: task.auto_generated,
+        confidence: task.confidence,
+        reasoning: task.reasoning.clone(),
+        tool_hint: task.tool_hint.clone(),
+        tool_params: task.tool_params.clone(),
+        tags: task.tags.clone(),
+        metadata,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let rule: Recurrence = "RRULE:FREQ=weekly;INTERVAL=2;BYDAY=mo,TH;UNTIL=20251231"
+            .parse()
+            .expect("Rule should parse");
+        assert_eq!(rule.frequency, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.weekdays, vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(
+            rule.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;UNTIL=20251231T235959Z"
+        );
+        assert_eq!(rule.to_string().parse::<Recurrence>().ok(), Some(rule));
+
+        assert_eq!(
+            "monthly".parse::<Recurrence>().ok(),
+            Some(Recurrence::new(Frequency::Monthly))
+        );
+        assert!("FREQ=DAILY;BYDAY=MO".parse::<Recurrence>().is_err());
+        assert!("FREQ=YEARLY".parse::<Recurrence>().is_err());
+        assert!("INTERVAL=2".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn test_next_after_rules() {
+        // 2025-03-03 — понедельник
+        let monday = at(2025, 3, 3);
+
+        let daily = Recurrence::new(Frequency::Daily).with_interval(3);
+        assert_eq!(daily.next_after(monday, monday), Some(at(2025, 3, 6)));
+
+        let weekly = Recurrence::new(Frequency::Weekly)
+            .with_interval(2)
+            .with_weekdays(vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(weekly.next_after(monday, monday), Some(at(2025, 3, 6)));
+        assert_eq!(
+            weekly.next_after(monday, at(2025, 3, 6)),
+            Some(at(2025, 3, 17))
+        );
+
+        // 31-е число в коротком месяце превращается в последний день
+        let month_end = at(2025, 1, 31);
+        let monthly = Recurrence::new(Frequency::Monthly);
+        assert_eq!(
+            monthly.next_after(month_end, month_end),
+            Some(at(2025, 2, 28))
+        );
+        let first_and_last = Recurrence::new(Frequency::Monthly).with_month_days(vec![1, -1]);
+        assert_eq!(
+            first_and_last.next_after(at(2025, 4, 1), at(2025, 4, 1)),
+            Some(at(2025, 4, 30))
+        );
+
+        // Пропущенные сроки не накапливаются
+        assert_eq!(
+            Recurrence::new(Frequency::Weekly).next_after(monday, at(2025, 3, 20)),
+            Some(at(2025, 3, 24))
+        );
+
+        let limited = Recurrence::new(Frequency::Daily).with_until(at(2025, 3, 4));
+        assert_eq!(limited.next_after(monday, monday), Some(at(2025, 3, 4)));
+        assert_eq!(limited.next_after(monday, at(2025, 3, 4)), None);
+    }
+}
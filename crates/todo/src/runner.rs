@@ -0,0 +1,714 @@
+//! Выполнение готовых задач
+//!
+//! [`TaskRunner`] берёт задачи из [`TodoService::get_next_ready`] в порядке
+//! приоритета, выполняет их через [`TaskHandler`] (инструмент из `tool_hint`
+//! или оркестратор) и двигает по состояниям `InProgress` → `Done`/`Failed`.
+//! Завершение задачи каскадно разблокирует зависимые, а результат
+//! сохраняется в память через [`ArtifactStore`] и попадает в `artifacts`.
+//! Переходы пишутся в журнал от имени агента маршрута; после провала сводка
+//! истории задачи уходит в [`HistoryMemory`].
+
+use crate::history::{agent_actor, HistoryMemory};
+use crate::types::*;
+use crate::TodoService;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Сколько готовых задач запрашивать за раз
+const READY_BATCH: usize = 64;
+/// Предел выборки задач для dry-run
+const SCAN_LIMIT: usize = 10_000;
+/// Маршрут задач без `tool_hint`
+pub const ORCHESTRATOR_ROUTE: &str = "orchestrator";
+
+/// Запущенные обработчики и их задачи
+#[derive(Default)]
+struct Workers {
+    running: JoinSet<Result<String>>,
+    tasks: HashMap<tokio::task::Id, TodoItem>,
+}
+
+impl Workers {
+    fn spawn(&mut self, handler: Arc<dyn TaskHandler>, task: TodoItem) {
+        let worker = task.clone();
+        let handle = self
+            .running
+            .spawn(async move { handler.run(&worker).await });
+        self.tasks.insert(handle.id(), task);
+    }
+
+    /// Следующая завершённая задача; паника обработчика становится ошибкой задачи
+    async fn join_next(&mut self) -> Option<(TodoItem, Result<String>)> {
+        loop {
+            let (id, result) = match self.running.join_next_with_id().await? {
+                Ok((id, result)) => (id, result),
+                Err(e) => (e.id(), Err(anyhow!("Task worker failed: {}", e))),
+            };
+            if let Some(task) = self.tasks.remove(&id) {
+                return Some((task, result));
+            }
+        }
+    }
+}
+
+/// Исполнитель одной задачи
+#[async_trait]
+pub trait TaskHandler: Send + Sync {
+    /// Выполнить задачу и вернуть её текстовый результат
+    async fn run(&self, task: &TodoItem) -> Result<String>;
+}
+
+/// Хранилище результатов задач (записи в памяти)
+#[async_trait]
+pub trait ArtifactStore: Send + Sync {
+    /// Сохранить результат задачи и вернуть ссылку на запись
+    async fn store(&self, task: &TodoItem, output: &str) -> Result<MemoryReference>;
+}
+
+/// Параметры запуска
+#[derive(Debug, Clone)]
+pub struct RunnerConfig {
+    /// Сколько задач выполнять одновременно
+    pub parallel: usize,
+    /// Выполнить только эту задачу и её зависимости
+    pub until: Option<Uuid>,
+    /// Только показать порядок выполнения, не меняя состояния
+    pub dry_run: bool,
+}
+
+impl Default for RunnerConfig {
+    fn default() -> Self {
+        Self {
+            parallel: 1,
+            until: None,
+            dry_run: false,
+        }
+    }
+}
+
+/// Итог одной задачи
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskRunStatus {
+    Done,
+    Failed(String),
+    /// Была бы выполнена (dry-run)
+    Planned,
+}
+
+/// Запись о выполнении задачи
+#[derive(Debug, Clone)]
+pub struct TaskRunRecord {
+    pub task_id: Uuid,
+    pub title: String,
+    /// Инструмент из `tool_hint` или [`ORCHESTRATOR_ROUTE`]
+    pub route: String,
+    pub status: TaskRunStatus,
+    pub output: Option<String>,
+    pub artifact: Option<MemoryReference>,
+}
+
+/// События запуска для вывода прогресса
+#[derive(Debug, Clone)]
+pub enum TaskRunEvent {
+    Started {
+        task_id: Uuid,
+        title: String,
+        route: String,
+    },
+    Finished(TaskRunRecord),
+}
+
+/// Итог запуска
+#[derive(Debug, Clone, Default)]
+pub struct TaskRunReport {
+    /// Задачи в порядке завершения
+    pub records: Vec<TaskRunRecord>,
+    /// Выполнена ли цель `--until` (None без цели)
+    pub until_done: Option<bool>,
+}
+
+impl TaskRunReport {
+    pub fn failed(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|r| matches!(r.status, TaskRunStatus::Failed(_)))
+            .count()
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.failed() == 0 && self.until_done != Some(false)
+    }
+}
+
+type ProgressFn = Arc<dyn Fn(&TaskRunEvent) + Send + Sync>;
+
+/// Выполняет готовые задачи сервиса
+pub struct TaskRunner {
+    service: Arc<TodoService>,
+    handler: Arc<dyn TaskHandler>,
+    artifacts: Option<Arc<dyn ArtifactStore>>,
+    history_memory: Option<Arc<dyn HistoryMemory>>,
+    config: RunnerConfig,
+    progress: Option<ProgressFn>,
+}
+
+impl TaskRunner {
+    pub fn new(service: Arc<TodoService>, handler: Arc<dyn TaskHandler>) -> Self {
+        Self {
+            service,
+            handler,
+            artifacts: None,
+            history_memory: None,
+            config: RunnerConfig::default(),
+            progress: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: RunnerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_artifact_store(mut self, store: Arc<dyn ArtifactStore>) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    pub fn with_history_memory(mut self, memory: Arc<dyn HistoryMemory>) -> Self {
+        self.history_memory = Some(memory);
+        self
+    }
+
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&TaskRunEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    fn emit(&self, event: TaskRunEvent) {
+        if let Some(progress) = &self.progress {
+            progress(&event);
+        }
+    }
+
+    fn route(task: &TodoItem) -> String {
+        task.tool_hint
+            .clone()
+            .unwrap_or_else(|| ORCHESTRATOR_ROUTE.to_string())
+    }
+
+    /// Выполнять готовые задачи, пока они есть (или до цели `until`)
+    pub async fn run(&self) -> Result<TaskRunReport> {
+        let scope = match self.config.until {
+            Some(target) => {
+                let task = self
+                    .service
+                    .get_cached(&target)
+                    .await?
+                    .ok_or_else(|| anyhow!("Task not found: {}", target))?;
+                if task.state == TaskState::Done {
+                    return Ok(TaskRunReport {
+                        records: Vec::new(),
+                        until_done: Some(true),
+                    });
+                }
+                Some(self.dependency_closure(target).await?)
+            }
+            None => None,
+        };
+
+        let records = if self.config.dry_run {
+            self.plan_dry_run(scope.as_ref()).await?
+        } else {
+            self.execute(scope.as_ref()).await?
+        };
+        let until_done = self.config.until.map(|target| {
+            records.iter().any(|r| {
+                r.task_id == target
+                    && matches!(r.status, TaskRunStatus::Done | TaskRunStatus::Planned)
+            })
+        });
+        Ok(TaskRunReport {
+            records,
+            until_done,
+        })
+    }
+
+    async fn execute(&self, scope: Option<&HashSet<Uuid>>) -> Result<Vec<TaskRunRecord>> {
+        let mut workers = Workers::default();
+        let mut records: Vec<TaskRunRecord> = Vec::new();
+        if let Err(e) = self.drive(scope, &mut workers, &mut records).await {
+            self.settle(&mut workers, &mut records).await;
+            return Err(e);
+        }
+        Ok(records)
+    }
+
+    async fn drive(
+        &self,
+        scope: Option<&HashSet<Uuid>>,
+        workers: &mut Workers,
+        records: &mut Vec<TaskRunRecord>,
+    ) -> Result<()> {
+        let parallel = self.config.parallel.max(1);
+        let mut attempted = HashSet::new();
+
+        loop {
+            let target_finished = self
+                .config
+                .until
+                .is_some_and(|target| records.iter().any(|r| r.task_id == target));
+            while !target_finished && workers.running.len() < parallel {
+                let Some(task) = self.next_ready(&attempted, scope).await? else {
+                    break;
+                };
+                attempted.insert(task.id);
+                self.service
+                    .update_state_as(
+                        &task.id,
+                        TaskState::InProgress,
+                        &agent_actor(&Self::route(&task)),
+                        None,
+                    )
+                    .await?;
+                self.emit(TaskRunEvent::Started {
+                    task_id: task.id,
+                    title: task.title.clone(),
+                    route: Self::route(&task),
+                });
+                workers.spawn(Arc::clone(&self.handler), task);
+            }
+
+            let Some((task, result)) = workers.join_next().await else {
+                break;
+            };
+            let record = self.finish(task, result).await?;
+            self.emit(TaskRunEvent::Finished(record.clone()));
+            records.push(record);
+        }
+
+        Ok(())
+    }
+
+    /// Дожидается оставшихся задач после ошибки, чтобы ни одна не осталась `InProgress`
+    async fn settle(&self, workers: &mut Workers, records: &mut Vec<TaskRunRecord>) {
+        while let Some((task, result)) = workers.join_next().await {
+            let task_id = task.id;
+            match self.finish(task, result).await {
+                Ok(record) => {
+                    self.emit(TaskRunEvent::Finished(record.clone()));
+                    records.push(record);
+                }
+                Err(e) => warn!("Failed to settle task {}: {}", task_id, e),
+            }
+        }
+    }
+
+    /// Следующая готовая задача: приоритет, затем время создания
+    async fn next_ready(
+        &self,
+        attempted: &HashSet<Uuid>,
+        scope: Option<&HashSet<Uuid>>,
+    ) -> Result<Option<TodoItem>> {
+        let mut ready: Vec<TodoItem> = self
+            .service
+            .get_next_ready(READY_BATCH)
+            .await?
+            .into_iter()
+            .filter(|t| t.state == TaskState::Ready && !attempted.contains(&t.id))
+            .filter(|t| scope.is_none_or(|scope| scope.contains(&t.id)))
+            .collect();
+        ready.sort_by(|a, b| {
+            b.priority
+                .cmp(&a.priority)
+                .then(a.created_at.cmp(&b.created_at))
+        });
+        Ok(ready.into_iter().next())
+    }
+
+    async fn finish(&self, task: TodoItem, result: Result<String>) -> Result<TaskRunRecord> {
+        let route = Self::route(&task);
+        let actor = agent_actor(&route);
+        match result {
+            Ok(output) => {
+                let artifact = match &self.artifacts {
+                    Some(store) => match store.store(&task, &output).await {
+                        Ok(artifact) => {
+                            self.service
+                                .add_artifact(&task.id, artifact.clone())
+                                .await?;
+                            Some(artifact)
+                        }
+                        Err(e) => {
+                            warn!("Failed to store output of task {}: {}", task.id, e);
+                            None
+                        }
+                    },
+                    None => None,
+                };
+                self.service
+                    .update_state_as(&task.id, TaskState::Done, &actor, None)
+                    .await?;
+                info!("Task {} done via {}", task.id, route);
+                Ok(TaskRunRecord {
+                    task_id: task.id,
+                    title: task.title,
+                    route,
+                    status: TaskRunStatus::Done,
+                    output: Some(output),
+                    artifact,
+                })
+            }
+            Err(e) => {
+                let error = e.to_string();
+                self.service
+                    .update_state_as(&task.id, TaskState::Failed, &actor, Some(&error))
+                    .await?;
+                self.service
+                    .upsert_metadata(
+                        &task.id,
+                        HashMap::from([("last_error".to_string(), serde_json::json!(error))]),
+                    )
+                    .await?;
+                if let Some(memory) = &self.history_memory {
+                    let digest = self.service.history_digest(&task.id).await?;
+                    if let Err(e) = memory.remember(&task, &digest).await {
+                        warn!("Failed to store history of task {}: {}", task.id, e);
+                    }
+                }
+                warn!("Task {} failed via {}: {}", task.id, route, error);
+                Ok(TaskRunRecord {
+                    task_id: task.id,
+                    title: task.title,
+                    route,
+                    status: TaskRunStatus::Failed(error),
+                    output: None,
+                    artifact: None,
+                })
+            }
+        }
+    }
+
+    /// Цель и все её транзитивные зависимости
+    async fn dependency_closure(&self, target: Uuid) -> Result<HashSet<Uuid>> {
+        let mut scope = HashSet::from([target]);
+        let mut queue = VecDeque::from([target]);
+        while let Some(id) = queue.pop_front() {
+            if let Some(task) = self.service.get_cached(&id).await? {
+                for dep in task.depends_on {
+                    if scope.insert(dep) {
+                        queue.push_back(dep);
+                    }
+                }
+            }
+        }
+        Ok(scope)
+    }
+
+    /// Порядок выполнения без запуска: задачи, чьи зависимости выполнены
+    /// (или были бы выполнены раньше), волнами по приоритету
+    async fn plan_dry_run(&self, scope: Option<&HashSet<Uuid>>) -> Result<Vec<TaskRunRecord>> {
+        let mut pending: Vec<TodoItem> = Vec::new();
+        for state in [TaskState::Ready, TaskState::Blocked] {
+            pending.extend(
+                self.service
+                    .get_by_state(state, SCAN_LIMIT)
+                    .await?
+                    .into_iter()
+                    .filter(|t| scope.is_none_or(|scope| scope.contains(&t.id))),
+            );
+        }
+
+        let pending_ids: HashSet<Uuid> = pending.iter().map(|t| t.id).collect();
+        let mut done = HashSet::new();
+        for dep in pending.iter().flat_map(|t| t.depends_on.iter()) {
+            if pending_ids.contains(dep) || done.contains(dep) {
+                continue;
+            }
+            if let Some(task) = self.service.get_cached(dep).await? {
+                if task.state == TaskState::Done {
+                    done.insert(*dep);
+                }
+            }
+        }
+
+        let mut records = Vec::new();
+        loop {
+            let (mut wave, rest): (Vec<TodoItem>, Vec<TodoItem>) = pending
+                .into_iter()
+                .partition(|t| t.depends_on.iter().all(|dep| done.contains(dep)));
+            pending = rest;
+            if wave.is_empty() {
+                break;
+            }
+            wave.sort_by(|a, b| {
+                b.priority
+                    .cmp(&a.priority)
+                    .then(a.created_at.cmp(&b.created_at))
+            });
+            for task in wave {
+                done.insert(task.id);
+                let record = TaskRunRecord {
+                    task_id: task.id,
+                    route: Self::route(&task),
+                    title: task.title,
+                    status: TaskRunStatus::Planned,
+                    output: None,
+                    artifact: None,
+                };
+                self.emit(TaskRunEvent::Finished(record.clone()));
+                records.push(record);
+                if self.config.until == Some(task.id) {
+                    return Ok(records);
+                }
+            }
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Проваливает задачи с тегом `fail`, считает одновременные запуски
+    #[derive(Default)]
+    struct TestHandler {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        runs: parking_lot::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl TaskHandler for TestHandler {
+        async fn run(&self, task: &TodoItem) -> Result<String> {
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            self.runs.lock().push(task.title.clone());
+            if task.tags.iter().any(|t| t == "panic") {
+                panic!("{} panicked", task.title);
+            }
+            if task.tags.iter().any(|t| t == "fail") {
+                anyhow::bail!("{} broke", task.title);
+            }
+            Ok(format!("output of {}", task.title))
+        }
+    }
+
+    struct TestArtifacts;
+
+    #[async_trait]
+    impl ArtifactStore for TestArtifacts {
+        async fn store(&self, _task: &TodoItem, _output: &str) -> Result<MemoryReference> {
+            Ok(MemoryReference {
+                layer: Layer::Insights,
+                record_id: Uuid::new_v4(),
+                created_at: chrono::Utc::now(),
+            })
+        }
+    }
+
+    /// Запоминает сводки истории
+    #[derive(Default)]
+    struct TestHistoryMemory {
+        digests: parking_lot::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl HistoryMemory for TestHistoryMemory {
+        async fn remember(&self, task: &TodoItem, digest: &str) -> Result<MemoryReference> {
+            self.digests.lock().push(digest.to_string());
+            TestArtifacts.store(task, digest).await
+        }
+    }
+
+    async fn create_test_service() -> (TempDir, Arc<TodoService>) {
+        let temp_dir = TempDir::new().expect("Temp dir should be created");
+        let service = TodoService::new(temp_dir.path().join("test.db"), 4, 100)
+            .await
+            .expect("Service should be created");
+        (temp_dir, Arc::new(service))
+    }
+
+    async fn task(service: &TodoService, title: &str, priority: Priority, tags: &[&str]) -> Uuid {
+        service
+            .create_task(
+                title.to_string(),
+                String::new(),
+                priority,
+                tags.iter().map(|t| t.to_string()).collect(),
+            )
+            .await
+            .expect("Task should be created")
+            .id
+    }
+
+    async fn state(service: &TodoService, id: &Uuid) -> TaskState {
+        service
+            .get_cached(id)
+            .await
+            .expect("Lookup should succeed")
+            .expect("Task should exist")
+            .state
+    }
+
+    #[tokio::test]
+    async fn test_runs_by_priority_and_unblocks_dependents() {
+        let (_dir, service) = create_test_service().await;
+        let low = task(&service, "low", Priority::Low, &[]).await;
+        let high = task(&service, "high", Priority::High, &[]).await;
+        let after_low = task(&service, "after low", Priority::Critical, &[]).await;
+        service
+            .add_dependency(&after_low, &low)
+            .await
+            .expect("Dependency should be added");
+
+        let handler = Arc::new(TestHandler::default());
+        let report = TaskRunner::new(Arc::clone(&service), handler.clone())
+            .with_artifact_store(Arc::new(TestArtifacts))
+            .run()
+            .await
+            .expect("Run should succeed");
+
+        assert!(report.succeeded());
+        assert_eq!(*handler.runs.lock(), vec!["high", "low", "after low"]);
+        for id in [low, high, after_low] {
+            assert_eq!(state(&service, &id).await, TaskState::Done);
+        }
+        let stored = service
+            .get_cached(&after_low)
+            .await
+            .expect("Lookup should succeed")
+            .expect("Task should exist");
+        assert_eq!(stored.artifacts.len(), 1);
+        assert_eq!(
+            Some(stored.artifacts[0].record_id),
+            report.records[2].artifact.as_ref().map(|a| a.record_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failure_keeps_dependents_blocked_and_until_limits_scope() {
+        let (_dir, service) = create_test_service().await;
+        let broken = task(&service, "broken", Priority::High, &["fail"]).await;
+        let blocked = task(&service, "blocked", Priority::High, &[]).await;
+        service
+            .add_dependency(&blocked, &broken)
+            .await
+            .expect("Dependency should be added");
+        let base = task(&service, "base", Priority::Low, &[]).await;
+        let target = task(&service, "target", Priority::Low, &[]).await;
+        service
+            .add_dependency(&target, &base)
+            .await
+            .expect("Dependency should be added");
+
+        let handler = Arc::new(TestHandler::default());
+        let report = TaskRunner::new(Arc::clone(&service), handler.clone())
+            .with_config(RunnerConfig {
+                until: Some(target),
+                ..RunnerConfig::default()
+            })
+            .run()
+            .await
+            .expect("Run should succeed");
+        assert_eq!(report.until_done, Some(true));
+        assert_eq!(*handler.runs.lock(), vec!["base", "target"]);
+        assert_eq!(state(&service, &broken).await, TaskState::Ready);
+
+        let memory = Arc::new(TestHistoryMemory::default());
+        let report = TaskRunner::new(Arc::clone(&service), handler.clone())
+            .with_history_memory(memory.clone())
+            .run()
+            .await
+            .expect("Run should succeed");
+        assert!(!report.succeeded());
+        assert_eq!(report.failed(), 1);
+        assert_eq!(state(&service, &broken).await, TaskState::Failed);
+        assert_eq!(state(&service, &blocked).await, TaskState::Blocked);
+        let failed = service
+            .get_cached(&broken)
+            .await
+            .expect("Lookup should succeed")
+            .expect("Task should exist");
+        assert_eq!(failed.metadata["last_error"], "broken broke");
+
+        let digests = memory.digests.lock();
+        assert_eq!(digests.len(), 1);
+        assert!(digests[0].contains("agent:orchestrator: broken broke"));
+    }
+
+    #[tokio::test]
+    async fn test_panicking_worker_marks_task_failed() {
+        let (_dir, service) = create_test_service().await;
+        let crashed = task(&service, "crashed", Priority::High, &["panic"]).await;
+        let sibling = task(&service, "sibling", Priority::High, &[]).await;
+
+        let handler = Arc::new(TestHandler::default());
+        let report = TaskRunner::new(Arc::clone(&service), handler.clone())
+            .with_config(RunnerConfig {
+                parallel: 2,
+                ..RunnerConfig::default()
+            })
+            .run()
+            .await
+            .expect("Run should succeed");
+        assert_eq!(report.records.len(), 2);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(state(&service, &crashed).await, TaskState::Failed);
+        assert_eq!(state(&service, &sibling).await, TaskState::Done);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_and_dry_run() {
+        let (_dir, service) = create_test_service().await;
+        let first = task(&service, "first", Priority::Medium, &[]).await;
+        for title in ["a", "b", "c"] {
+            let id = task(&service, title, Priority::Medium, &[]).await;
+            service
+                .add_dependency(&id, &first)
+                .await
+                .expect("Dependency should be added");
+        }
+
+        let handler = Arc::new(TestHandler::default());
+        let report = TaskRunner::new(Arc::clone(&service), handler.clone())
+            .with_config(RunnerConfig {
+                dry_run: true,
+                ..RunnerConfig::default()
+            })
+            .run()
+            .await
+            .expect("Dry run should succeed");
+        let titles: Vec<&str> = report.records.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["first", "a", "b", "c"]);
+        assert!(report
+            .records
+            .iter()
+            .all(|r| r.status == TaskRunStatus::Planned && r.route == ORCHESTRATOR_ROUTE));
+        assert!(handler.runs.lock().is_empty());
+        assert_eq!(state(&service, &first).await, TaskState::Ready);
+
+        let report = TaskRunner::new(Arc::clone(&service), handler.clone())
+            .with_config(RunnerConfig {
+                parallel: 2,
+                ..RunnerConfig::default()
+            })
+            .run()
+            .await
+            .expect("Run should succeed");
+        assert_eq!(report.records.len(), 4);
+        assert_eq!(handler.max_running.load(Ordering::SeqCst), 2);
+    }
+}
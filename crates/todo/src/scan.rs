@@ -0,0 +1,298 @@
+//! Сканирование TODO/FIXME-комментариев в исходном коде
+//!
+//! [`scan_path`] обходит дерево с учётом `.gitignore` и находит комментарии
+//! вида `// TODO: ...`, `# FIXME(owner): ...`, `-- HACK ...` на любом языке.
+//! Каждый комментарий получает отпечаток из пути, вида и текста, поэтому
+//! перенос строки не порождает новую задачу, а повторное сканирование
+//! идемпотентно. Синхронизация со store — `TodoServiceV2::sync_code_todos`.
+
+use crate::types::Priority;
+use anyhow::Result;
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Тег всех задач, созданных сканером
+pub const CODE_TAG: &str = "code";
+
+/// Ключи `metadata` задач из кода
+pub const SOURCE_KEY: &str = "source";
+pub const SOURCE_CODE_SCAN: &str = "code_scan";
+pub const FINGERPRINT_KEY: &str = "fingerprint";
+pub const FILE_KEY: &str = "file";
+pub const LINE_KEY: &str = "line";
+pub const SCAN_ROOT_KEY: &str = "scan_root";
+
+/// Файлы крупнее этого размера не сканируются
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+fn comment_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        // Маркер комментария, затем ключевое слово, необязательные
+        // `(теги)` и текст
+        Regex::new(
+            r"(?://+|#+|/\*+|^\s*\*|--|;+|<!--|%+|\bREM\b)\s*(?:@)?\b(TODO|FIXME|HACK|XXX)\b(?:\(([^)]*)\))?\s*:?\s*(.*)$",
+        )
+        .expect("comment regex is valid")
+    })
+}
+
+/// Комментарий-задача, найденный в коде
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodeTodo {
+    /// `TODO`, `FIXME`, `HACK` или `XXX`
+    pub kind: String,
+    /// Путь относительно корня сканирования
+    pub file: PathBuf,
+    /// Номер строки, с единицы
+    pub line: usize,
+    pub text: String,
+    /// Теги из `TODO(perf, db)`
+    pub tags: Vec<String>,
+    /// Стабильный отпечаток: путь, вид, текст и номер повтора в файле
+    pub fingerprint: String,
+}
+
+impl CodeTodo {
+    pub fn priority(&self) -> Priority {
+        match self.kind.as_str() {
+            "FIXME" => Priority::High,
+            _ => Priority::Medium,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        if self.text.is_empty() {
+            format!("{} in {}:{}", self.kind, self.file.display(), self.line)
+        } else {
+            let title: String = self.text.chars().take(100).collect();
+            if title.len() < self.text.len() {
+                format!("{title}…")
+            } else {
+                title
+            }
+        }
+    }
+
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file.display(), self.line)
+    }
+}
+
+/// Найти комментарии-задачи в одном файле
+pub fn scan_source(file: &Path, content: &str) -> Vec<CodeTodo> {
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+    let mut todos = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let Some(captures) = comment_regex().captures(line) else {
+            continue;
+        };
+        let kind = captures[1].to_string();
+        let tags = captures
+            .get(2)
+            .map(|tags| {
+                tags.as_str()
+                    .split([',', ' '])
+                    .map(|tag| tag.trim().trim_start_matches('@').to_lowercase())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let text = captures[3]
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim()
+            .to_string();
+
+        let occurrence = seen.entry((kind.clone(), text.clone())).or_default();
+        let fingerprint = fingerprint(file, &kind, &text, *occurrence);
+        *occurrence += 1;
+
+        todos.push(CodeTodo {
+            kind,
+            file: file.to_path_buf(),
+            line: index + 1,
+            text,
+            tags,
+            fingerprint,
+        });
+    }
+    todos
+}
+
+fn fingerprint(file: &Path, kind: &str, text: &str, occurrence: usize) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut hasher = Sha256::new();
+    hasher.update(file.to_string_lossy().replace('\\', "/").as_bytes());
+    hasher.update([0]);
+    hasher.update(kind.as_bytes());
+    hasher.update([0]);
+    hasher.update(normalized.as_bytes());
+    hasher.update([0]);
+    hasher.update(occurrence.to_le_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Обойти дерево (с учётом `.gitignore`, `.ignore` и скрытых файлов) и
+/// собрать комментарии-задачи. Бинарные и слишком большие файлы пропускаются.
+pub fn scan_path(root: &Path) -> Result<Vec<CodeTodo>> {
+    let mut todos = Vec::new();
+    let walker = WalkBuilder::new(root).require_git(false).build();
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if entry.metadata().map_or(true, |m| m.len() > MAX_FILE_SIZE) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let relative = if relative.as_os_str().is_empty() {
+            // Сканируется один файл
+            entry.path().file_name().map(Path::new).unwrap_or(relative)
+        } else {
+            relative
+        };
+        todos.extend(scan_source(relative, &content));
+    }
+    todos.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+    Ok(todos)
+}
+
+/// Итог синхронизации комментариев со store
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanReport {
+    pub found: usize,
+    pub created: usize,
+    /// Комментарий переместился или вернулся после удаления
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Комментарий исчез, задача закрыта
+    pub closed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TaskState;
+    use crate::TodoService;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_source_across_languages() {
+        let source = r#"
+fn main() {
+    // TODO: handle errors
+    let x = 1; // FIXME(perf, db): avoid the extra query
+    /* HACK temporary workaround */
+}
+# TODO(@alice): python style
+-- XXX: sql style
+<!-- TODO: html style -->
+let todo_list = "TODO: not a comment";
+// todo lower case is ignored
+"#;
+        let todos = scan_source(Path::new("src/main.rs"), source);
+        let summary: Vec<(&str, usize, &str)> = todos
+            .iter()
+            .map(|t| (t.kind.as_str(), t.line, t.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("TODO", 3, "handle errors"),
+                ("FIXME", 4, "avoid the extra query"),
+                ("HACK", 5, "temporary workaround"),
+                ("TODO", 7, "python style"),
+                ("XXX", 8, "sql style"),
+                ("TODO", 9, "html style"),
+            ]
+        );
+        assert_eq!(todos[1].tags, vec!["perf", "db"]);
+        assert_eq!(todos[1].priority(), Priority::High);
+        assert_eq!(todos[3].tags, vec!["alice"]);
+
+        // Отпечаток не зависит от номера строки, но различает повторы
+        let moved = scan_source(Path::new("src/main.rs"), &format!("\n\n{source}"));
+        assert_eq!(moved[0].fingerprint, todos[0].fingerprint);
+        let repeated = scan_source(Path::new("a.rs"), "// TODO: x\n// TODO: x\n");
+        assert_ne!(repeated[0].fingerprint, repeated[1].fingerprint);
+    }
+
+    #[tokio::test]
+    async fn test_sync_is_idempotent_and_closes_removed() {
+        let repo = TempDir::new().expect("Temp dir should be created");
+        std::fs::write(repo.path().join(".gitignore"), "target/\n").expect("Write should succeed");
+        std::fs::create_dir(repo.path().join("target")).expect("Dir should be created");
+        std::fs::write(repo.path().join("target/gen.rs"), "// TODO: generated\n")
+            .expect("Write should succeed");
+        let lib = repo.path().join("lib.rs");
+        std::fs::write(&lib, "// TODO(perf): cache\n// FIXME: leak\n")
+            .expect("Write should succeed");
+
+        let db = TempDir::new().expect("Temp dir should be created");
+        let service = TodoService::new(db.path().join("test.db"), 4, 100)
+            .await
+            .expect("Service should be created");
+        let root = repo.path().to_string_lossy().to_string();
+
+        let todos = scan_path(repo.path()).expect("Scan should succeed");
+        assert_eq!(todos.len(), 2, "ignored files must be skipped");
+        let report = service
+            .sync_code_todos(&root, &todos)
+            .await
+            .expect("Sync should succeed");
+        assert_eq!((report.created, report.unchanged), (2, 0));
+
+        // Сдвиг строки — обновление, а не новая задача
+        std::fs::write(&lib, "\n// TODO(perf): cache\n").expect("Write should succeed");
+        let todos = scan_path(repo.path()).expect("Scan should succeed");
+        let report = service
+            .sync_code_todos(&root, &todos)
+            .await
+            .expect("Sync should succeed");
+        assert_eq!((report.created, report.updated, report.closed), (0, 1, 1));
+
+        let report = service
+            .sync_code_todos(&root, &todos)
+            .await
+            .expect("Sync should succeed");
+        assert_eq!(
+            (
+                report.created,
+                report.updated,
+                report.unchanged,
+                report.closed
+            ),
+            (0, 0, 1, 0)
+        );
+
+        let tasks = service.search("", 10).await.expect("Search should succeed");
+        let cache = tasks
+            .iter()
+            .find(|t| t.title == "cache")
+            .expect("Task should exist");
+        assert!(cache.tags.contains(&"perf".to_string()));
+        assert_eq!(cache.metadata[LINE_KEY], serde_json::json!(2));
+        let leak = tasks
+            .iter()
+            .find(|t| t.title == "leak")
+            .expect("Task should exist");
+        assert_eq!(leak.state, TaskState::Done);
+        assert_eq!(leak.metadata["history"][0]["event"], "comment_removed");
+    }
+}
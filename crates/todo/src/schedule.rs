@@ -0,0 +1,471 @@
+//! Оценки трудоёмкости и план-график по графу задач
+//!
+//! Оценка задачи — три точки PERT (оптимистичная, вероятная,
+//! пессимистичная) в часах, хранится в `metadata["estimate"]`. По оценкам и
+//! зависимостям [`Schedule::compute`] делает прямой и обратный проход
+//! метода критического пути: ранние/поздние сроки, резерв времени,
+//! критическую цепочку и прогноз завершения относительно `due_date`.
+
+use crate::graph::DependencyGraphV2;
+use crate::types::*;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Ключ оценки в `TodoItem::metadata`
+pub const ESTIMATE_KEY: &str = "estimate";
+
+/// Допуск при сравнении резерва с нулём, часы
+const SLACK_EPSILON: f64 = 1e-6;
+
+/// Трёхточечная оценка трудоёмкости в часах
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TaskEstimate {
+    pub optimistic: f64,
+    pub likely: f64,
+    pub pessimistic: f64,
+}
+
+impl TaskEstimate {
+    pub fn new(optimistic: f64, likely: f64, pessimistic: f64) -> Result<Self> {
+        if optimistic < 0.0 || !(optimistic <= likely && likely <= pessimistic) {
+            return Err(anyhow!(
+                "Estimate must satisfy 0 <= optimistic <= likely <= pessimistic, got {}/{}/{}",
+                optimistic,
+                likely,
+                pessimistic
+            ));
+        }
+        Ok(Self {
+            optimistic,
+            likely,
+            pessimistic,
+        })
+    }
+
+    /// Фиксированная оценка без разброса
+    pub fn fixed(hours: f64) -> Self {
+        Self {
+            optimistic: hours,
+            likely: hours,
+            pessimistic: hours,
+        }
+    }
+
+    /// Ожидаемая трудоёмкость PERT: (O + 4M + P) / 6
+    pub fn expected(&self) -> f64 {
+        (self.optimistic + 4.0 * self.likely + self.pessimistic) / 6.0
+    }
+
+    /// Дисперсия PERT: ((P - O) / 6)²
+    pub fn variance(&self) -> f64 {
+        ((self.pessimistic - self.optimistic) / 6.0).powi(2)
+    }
+}
+
+impl TodoItem {
+    /// Оценка трудоёмкости из metadata, если задана
+    pub fn estimate(&self) -> Option<TaskEstimate> {
+        self.metadata
+            .get(ESTIMATE_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+/// Параметры построения графика
+#[derive(Debug, Clone)]
+pub struct ScheduleConfig {
+    /// Момент начала работ
+    pub start: DateTime<Utc>,
+    /// Сколько часов работы укладывается в календарный день
+    pub hours_per_day: f64,
+    /// Оценка для задач без оценки, часы
+    pub default_hours: f64,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            start: Utc::now(),
+            hours_per_day: 8.0,
+            default_hours: 4.0,
+        }
+    }
+}
+
+impl ScheduleConfig {
+    /// Календарная дата через `hours` часов работы от начала
+    pub fn date_at(&self, hours: f64) -> DateTime<Utc> {
+        let days = hours / self.hours_per_day.max(f64::EPSILON);
+        self.start + Duration::seconds((days * 86_400.0).round() as i64)
+    }
+}
+
+/// Задача в плане-графике. Сроки — часы работы от начала.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledTask {
+    pub id: Uuid,
+    pub title: String,
+    pub state: TaskState,
+    /// Ожидаемая трудоёмкость PERT
+    pub duration: f64,
+    /// Оценка задана явно, а не взята по умолчанию
+    pub estimated: bool,
+    pub earliest_start: f64,
+    pub earliest_finish: f64,
+    pub latest_start: f64,
+    pub latest_finish: f64,
+    /// Насколько задачу можно задержать без сдвига всего плана
+    pub slack: f64,
+    pub critical: bool,
+    /// Стандартное отклонение срока завершения по ведущей цепочке
+    pub std_dev: f64,
+    pub projected_start: DateTime<Utc>,
+    pub projected_finish: DateTime<Utc>,
+    pub due_date: Option<DateTime<Utc>>,
+    /// Ожидаемое завершение позже срока
+    pub late: bool,
+    /// Завершение позже срока с учётом разброса оценок (+1σ)
+    pub at_risk: bool,
+}
+
+/// План-график незавершённых задач
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    /// Задачи в топологическом порядке
+    pub tasks: Vec<ScheduledTask>,
+    /// Критическая цепочка от первой задачи к последней
+    pub critical_path: Vec<Uuid>,
+    /// Длительность плана, часы
+    pub total_hours: f64,
+    pub projected_completion: DateTime<Utc>,
+    /// Стандартное отклонение длительности критической цепочки, часы
+    pub std_dev: f64,
+    #[serde(skip)]
+    config: ScheduleConfig,
+    #[serde(skip)]
+    dependencies: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl Schedule {
+    /// Метод критического пути по незавершённым задачам. Выполненные и
+    /// отменённые задачи, а также зависимости на них не учитываются.
+    pub fn compute(tasks: &[TodoItem], config: ScheduleConfig) -> Result<Self> {
+        let open: Vec<&TodoItem> = tasks
+            .iter()
+            .filter(|t| !matches!(t.state, TaskState::Done | TaskState::Cancelled))
+            .collect();
+        let ids: HashSet<Uuid> = open.iter().map(|t| t.id).collect();
+        let by_id: HashMap<Uuid, &TodoItem> = open.iter().map(|t| (t.id, *t)).collect();
+        let dependencies: HashMap<Uuid, Vec<Uuid>> = open
+            .iter()
+            .map(|t| {
+                let deps = t
+                    .depends_on
+                    .iter()
+                    .filter(|d| ids.contains(d))
+                    .copied()
+                    .collect();
+                (t.id, deps)
+            })
+            .collect();
+
+        let graph = DependencyGraphV2::new();
+        graph.load_from_tasks(open.iter().map(|t| (*t).clone()).collect())?;
+        let order = graph.topological_sort()?;
+
+        let estimate = |task: &TodoItem| {
+            task.estimate()
+                .unwrap_or_else(|| TaskEstimate::fixed(config.default_hours))
+        };
+
+        // Прямой проход: ранние сроки и дисперсия по ведущей зависимости
+        let mut early: HashMap<Uuid, (f64, f64, f64)> = HashMap::new();
+        let mut driver: HashMap<Uuid, Uuid> = HashMap::new();
+        for id in &order {
+            let task = by_id[id];
+            let driving = dependencies[id]
+                .iter()
+                .max_by(|a, b| early[*a].1.total_cmp(&early[*b].1));
+            let (start, variance) = match driving {
+                Some(dep) => {
+                    driver.insert(*id, *dep);
+                    (early[dep].1, early[dep].2)
+                }
+                None => (0.0, 0.0),
+            };
+            let estimate = estimate(task);
+            early.insert(
+                *id,
+                (
+                    start,
+                    start + estimate.expected(),
+                    variance + estimate.variance(),
+                ),
+            );
+        }
+        let total_hours = early.values().map(|e| e.1).fold(0.0, f64::max);
+
+        // Обратный проход: поздние сроки
+        let mut dependents: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (id, deps) in &dependencies {
+            for dep in deps {
+                dependents.entry(*dep).or_default().push(*id);
+            }
+        }
+        let mut late_finish: HashMap<Uuid, f64> = HashMap::new();
+        for id in order.iter().rev() {
+            let finish = dependents
+                .get(id)
+                .into_iter()
+                .flatten()
+                .map(|next| late_finish[next] - (early[next].1 - early[next].0))
+                .fold(total_hours, f64::min);
+            late_finish.insert(*id, finish);
+        }
+
+        let scheduled: Vec<ScheduledTask> = order
+            .iter()
+            .map(|id| {
+                let task = by_id[id];
+                let (earliest_start, earliest_finish, variance) = early[id];
+                let duration = earliest_finish - earliest_start;
+                let latest_finish = late_finish[id];
+                let slack = (latest_finish - earliest_finish).max(0.0);
+                let std_dev = variance.sqrt();
+                let projected_finish = config.date_at(earliest_finish);
+                let pessimistic_finish = config.date_at(earliest_finish + std_dev);
+                ScheduledTask {
+                    id: *id,
+                    title: task.title.clone(),
+                    state: task.state,
+                    duration,
+                    estimated: task.estimate().is_some(),
+                    earliest_start,
+                    earliest_finish,
+                    latest_start: latest_finish - duration,
+                    latest_finish,
+                    slack,
+                    critical: slack < SLACK_EPSILON,
+                    std_dev,
+                    projected_start: config.date_at(earliest_start),
+                    projected_finish,
+                    due_date: task.due_date,
+                    late: task.due_date.is_some_and(|due| projected_finish > due),
+                    at_risk: task.due_date.is_some_and(|due| pessimistic_finish > due),
+                }
+            })
+            .collect();
+
+        // Критическая цепочка: от задачи, которая завершается последней,
+        // назад по ведущим зависимостям
+        let mut critical_path = Vec::new();
+        let mut current = scheduled
+            .iter()
+            .filter(|t| t.critical)
+            .max_by(|a, b| a.earliest_finish.total_cmp(&b.earliest_finish))
+            .map(|t| t.id);
+        while let Some(id) = current {
+            critical_path.push(id);
+            current = driver.get(&id).copied();
+        }
+        critical_path.reverse();
+
+        let std_dev = critical_path.last().map_or(0.0, |id| early[id].2.sqrt());
+        Ok(Self {
+            tasks: scheduled,
+            critical_path,
+            total_hours,
+            projected_completion: config.date_at(total_hours),
+            std_dev,
+            config,
+            dependencies,
+        })
+    }
+
+    pub fn task(&self, id: &Uuid) -> Option<&ScheduledTask> {
+        self.tasks.iter().find(|t| t.id == *id)
+    }
+
+    /// Задачи, которые не успевают к сроку (с учётом разброса)
+    pub fn at_risk(&self) -> impl Iterator<Item = &ScheduledTask> {
+        self.tasks.iter().filter(|t| t.at_risk)
+    }
+
+    /// Диаграмма Ганта в формате mermaid: критические задачи помечены
+    /// `crit`, опаздывающие к сроку — `active`
+    pub fn to_gantt(&self, title: &str) -> String {
+        let mut out = format!(
+            "gantt\n    title {}\n    dateFormat YYYY-MM-DD HH:mm\n    axisFormat %m-%d\n",
+            gantt_label(title)
+        );
+        let sections = [("Critical path", true), ("Other tasks", false)];
+        for (section, critical) in sections {
+            let tasks: Vec<&ScheduledTask> = self
+                .tasks
+                .iter()
+                .filter(|t| t.critical == critical)
+                .collect();
+            if tasks.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("    section {}\n", section));
+            for task in tasks {
+                let mut tags = Vec::new();
+                if task.critical {
+                    tags.push("crit".to_string());
+                }
+                if task.at_risk {
+                    tags.push("active".to_string());
+                }
+                tags.push(gantt_id(&task.id));
+                let duration_minutes = (self.config.date_at(task.earliest_finish)
+                    - task.projected_start)
+                    .num_minutes()
+                    .max(1);
+                out.push_str(&format!(
+                    "    {} :{}, {}, {}m\n",
+                    gantt_label(&task.title),
+                    tags.join(", "),
+                    task.projected_start.format("%Y-%m-%d %H:%M"),
+                    duration_minutes
+                ));
+            }
+        }
+        out
+    }
+
+    /// Граф зависимостей mermaid с выделенной критической цепочкой
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph TD\n");
+        for task in &self.tasks {
+            out.push_str(&format!(
+                "    {}[\"{} ({:.1}h, slack {:.1}h)\"]\n",
+                gantt_id(&task.id),
+                task.title.replace('"', "'"),
+                task.duration,
+                task.slack
+            ));
+        }
+        let on_path: HashSet<&Uuid> = self.critical_path.iter().collect();
+        for task in &self.tasks {
+            for dep in &self.dependencies[&task.id] {
+                let arrow = if on_path.contains(dep) && on_path.contains(&task.id) {
+                    "==>"
+                } else {
+                    "-->"
+                };
+                out.push_str(&format!(
+                    "    {} {} {}\n",
+                    gantt_id(dep),
+                    arrow,
+                    gantt_id(&task.id)
+                ));
+            }
+        }
+        out.push_str("    classDef critical fill:#f96,stroke:#c30,stroke-width:2px\n");
+        for id in &self.critical_path {
+            out.push_str(&format!("    class {} critical\n", gantt_id(id)));
+        }
+        out
+    }
+}
+
+/// Идентификатор узла mermaid по задаче
+fn gantt_id(id: &Uuid) -> String {
+    format!("t{}", &id.simple().to_string()[..8])
+}
+
+/// `:` и `#` ломают синтаксис gantt
+fn gantt_label(text: &str) -> String {
+    text.replace([':', '#', ';'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn task(title: &str, estimate: Option<TaskEstimate>, depends_on: Vec<Uuid>) -> TodoItem {
+        let mut item = TodoItem {
+            title: title.to_string(),
+            depends_on,
+            ..Default::default()
+        };
+        if let Some(estimate) = estimate {
+            item.metadata.insert(
+                ESTIMATE_KEY.to_string(),
+                serde_json::to_value(estimate).expect("Estimate should serialize"),
+            );
+        }
+        item
+    }
+
+    #[test]
+    fn test_critical_path_slack_and_risk() {
+        // design(6) → build(12) → release(2); docs(3) параллельно build
+        let design = task("Design", Some(TaskEstimate::fixed(6.0)), vec![]);
+        let build = task(
+            "Build",
+            Some(TaskEstimate::new(8.0, 12.0, 16.0).expect("Estimate should be valid")),
+            vec![design.id],
+        );
+        let docs = task("Docs", Some(TaskEstimate::fixed(3.0)), vec![design.id]);
+        let mut release = task("Release", None, vec![build.id, docs.id]);
+        let config = ScheduleConfig {
+            start: Utc.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap(),
+            hours_per_day: 8.0,
+            default_hours: 2.0,
+        };
+        release.due_date = Some(config.date_at(20.5));
+
+        let schedule = Schedule::compute(
+            &[design.clone(), build.clone(), docs.clone(), release.clone()],
+            config,
+        )
+        .expect("Schedule should compute");
+
+        assert_eq!(
+            schedule.critical_path,
+            vec![design.id, build.id, release.id]
+        );
+        assert!((schedule.total_hours - 20.0).abs() < 1e-9);
+        let docs = schedule.task(&docs.id).expect("Docs should be scheduled");
+        assert!((docs.slack - 9.0).abs() < 1e-9);
+        assert!(!docs.critical);
+
+        // Ожидаемо успевает, но разброс build (σ = 4/3 ч) выводит за срок
+        let release = schedule
+            .task(&release.id)
+            .expect("Release should be scheduled");
+        assert!(!release.estimated);
+        assert!(!release.late);
+        assert!(release.at_risk);
+        assert_eq!(schedule.at_risk().count(), 1);
+
+        let gantt = schedule.to_gantt("Release: v1");
+        assert!(gantt.contains("title Release  v1"), "{}", gantt);
+        assert!(gantt.contains("Build :crit, "), "{}", gantt);
+        assert!(gantt.contains("2025-03-03 18:00, 2160m"), "{}", gantt);
+        let mermaid = schedule.to_mermaid();
+        assert!(mermaid.contains("==>"), "{}", mermaid);
+        assert_eq!(mermaid.matches("class t").count(), 3);
+    }
+
+    #[test]
+    fn test_estimate_validation_and_completed_tasks() {
+        assert!(TaskEstimate::new(5.0, 3.0, 8.0).is_err());
+        assert!((TaskEstimate::new(1.0, 4.0, 7.0).unwrap().expected() - 4.0).abs() < 1e-9);
+
+        let mut done = task("Done", Some(TaskEstimate::fixed(10.0)), vec![]);
+        done.state = TaskState::Done;
+        let next = task("Next", Some(TaskEstimate::fixed(1.0)), vec![done.id]);
+        let schedule = Schedule::compute(&[done, next.clone()], ScheduleConfig::default())
+            .expect("Schedule should compute");
+        assert_eq!(schedule.tasks.len(), 1);
+        assert_eq!(schedule.critical_path, vec![next.id]);
+        assert!((schedule.total_hours - 1.0).abs() < 1e-9);
+    }
+}
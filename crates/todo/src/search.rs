@@ -0,0 +1,316 @@
+//! Поиск задач
+//!
+//! Полнотекстовый поиск идёт через FTS5-индекс `todos_fts` (заголовок,
+//! описание, теги), который триггеры держат в синхронизации с `todos` и
+//! `todo_tags`. Семантический поиск ранжирует кандидатов по косинусной
+//! близости эмбеддингов из [`TaskEmbedder`]; векторы кэшируются в
+//! `todo_embeddings` и пересчитываются при изменении текста задачи.
+
+use crate::types::*;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+/// Маркер начала совпадения в `title_highlight` и `snippet`
+pub const MATCH_START: char = '\u{2}';
+/// Маркер конца совпадения
+pub const MATCH_END: char = '\u{3}';
+
+/// Фильтры поиска, комбинируются через AND
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    pub state: Option<TaskState>,
+    /// Минимальный приоритет
+    pub min_priority: Option<Priority>,
+    pub tag: Option<String>,
+    pub due_after: Option<DateTime<Utc>>,
+    pub due_before: Option<DateTime<Utc>>,
+}
+
+impl TaskFilter {
+    pub fn with_state(mut self, state: TaskState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    pub fn with_min_priority(mut self, priority: Priority) -> Self {
+        self.min_priority = Some(priority);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn with_due_range(
+        mut self,
+        after: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.due_after = after;
+        self.due_before = before;
+        self
+    }
+}
+
+/// Граница диапазона дат: `YYYY-MM-DD` или RFC 3339. Для верхней границы
+/// дата без времени означает конец дня.
+pub fn parse_date_bound(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date '{}': expected YYYY-MM-DD or RFC 3339", value))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.expect("valid time of day").and_utc())
+}
+
+/// Найденная задача с оценкой и подсвеченными совпадениями
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskSearchHit {
+    pub task: TodoItem,
+    /// Чем больше, тем релевантнее (BM25 или косинусная близость)
+    pub score: f32,
+    /// Заголовок с совпадениями между [`MATCH_START`] и [`MATCH_END`]
+    pub title_highlight: String,
+    /// Фрагмент описания вокруг совпадения
+    pub snippet: String,
+}
+
+impl TaskSearchHit {
+    /// Попадание без подсветки (поиск только по фильтрам и семантический)
+    pub fn plain(task: TodoItem, score: f32) -> Self {
+        Self {
+            title_highlight: task.title.clone(),
+            snippet: task.description.chars().take(120).collect(),
+            task,
+            score,
+        }
+    }
+}
+
+/// Заменить маркеры совпадений, например на ANSI-цвета или `**`
+pub fn render_highlight(text: &str, mark: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(MATCH_START) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + MATCH_START.len_utf8()..];
+        let end = after.find(MATCH_END).unwrap_or(after.len());
+        out.push_str(&mark(&after[..end]));
+        rest = after.get(end + MATCH_END.len_utf8()..).unwrap_or_default();
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Пользовательский запрос → FTS5 MATCH: каждое слово как префикс, все
+/// слова обязательны. Кавычки экранируются, так что синтаксис FTS5 из
+/// ввода не интерпретируется.
+pub(crate) fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Источник эмбеддингов для семантического поиска
+#[async_trait]
+pub trait TaskEmbedder: Send + Sync {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Текст задачи, по которому строится эмбеддинг
+pub(crate) fn embedding_text(task: &TodoItem) -> String {
+    let mut text = task.title.clone();
+    if !task.description.is_empty() {
+        text.push('\n');
+        text.push_str(&task.description);
+    }
+    if !task.tags.is_empty() {
+        text.push('\n');
+        text.push_str(&task.tags.join(" "));
+    }
+    text
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TodoService;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Эмбеддинг по наличию ключевых слов
+    struct KeywordEmbedder;
+
+    #[async_trait]
+    impl TaskEmbedder for KeywordEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    let text = text.to_lowercase();
+                    ["database", "ui", "deploy"]
+                        .iter()
+                        .map(|word| if text.contains(word) { 1.0 } else { 0.0 })
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingEmbedder {
+        texts: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TaskEmbedder for CountingEmbedder {
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.texts.fetch_add(texts.len(), Ordering::SeqCst);
+            KeywordEmbedder.embed(texts).await
+        }
+    }
+
+    async fn service_with_tasks(dir: &TempDir) -> TodoService {
+        let service = TodoService::new(dir.path().join("test.db"), 4, 100)
+            .await
+            .expect("Service should be created");
+        for (title, description, priority, tag) in [
+            (
+                "Migrate database schema",
+                "Add FTS index",
+                Priority::High,
+                "backend",
+            ),
+            (
+                "Polish settings UI",
+                "Mention the database size",
+                Priority::Low,
+                "frontend",
+            ),
+            (
+                "Deploy release",
+                "Ship to production",
+                Priority::Critical,
+                "ops",
+            ),
+        ] {
+            service
+                .create_task(
+                    title.to_string(),
+                    description.to_string(),
+                    priority,
+                    vec![tag.to_string()],
+                )
+                .await
+                .expect("Task should be created");
+        }
+        service
+    }
+
+    #[tokio::test]
+    async fn test_fts_ranking_filters_and_highlight() {
+        let temp_dir = TempDir::new().expect("Temp dir should be created");
+        let service = service_with_tasks(&temp_dir).await;
+
+        // Совпадение в заголовке весит больше, чем в описании
+        let hits = service
+            .search_ranked("datab", &TaskFilter::default(), 10)
+            .await
+            .expect("Search should succeed");
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].task.title, "Migrate database schema");
+        assert!(hits[0].score > hits[1].score);
+        let title = render_highlight(&hits[0].title_highlight, |m| format!("**{m}**"));
+        assert_eq!(title, "Migrate **database** schema");
+        assert!(hits[1].snippet.contains(MATCH_START));
+
+        // Теги индексируются триггерами
+        let hits = service
+            .search_ranked("ops", &TaskFilter::default(), 10)
+            .await
+            .expect("Search should succeed");
+        assert_eq!(hits.len(), 1);
+
+        let filter = TaskFilter::default().with_min_priority(Priority::High);
+        let hits = service
+            .search_ranked("database", &filter, 10)
+            .await
+            .expect("Search should succeed");
+        assert_eq!(hits.len(), 1);
+        let hits = service
+            .search_ranked("", &TaskFilter::default().with_tag("frontend"), 10)
+            .await
+            .expect("Search should succeed");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].task.title, "Polish settings UI");
+
+        // Синтаксис FTS5 из запроса не интерпретируется
+        let hits = service
+            .search_ranked("\"database OR", &TaskFilter::default(), 10)
+            .await
+            .expect("Search should succeed");
+        assert!(hits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_caches_embeddings() {
+        let temp_dir = TempDir::new().expect("Temp dir should be created");
+        let service = service_with_tasks(&temp_dir).await;
+
+        let hits = service
+            .semantic_search("deploy it", &TaskFilter::default(), 2, &KeywordEmbedder)
+            .await
+            .expect("Search should succeed");
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].task.title, "Deploy release");
+        assert!((hits[0].score - 1.0).abs() < f32::EPSILON);
+
+        let filter = TaskFilter::default().with_state(TaskState::Done);
+        let hits = service
+            .semantic_search("deploy", &filter, 10, &KeywordEmbedder)
+            .await
+            .expect("Search should succeed");
+        assert!(hits.is_empty());
+
+        // Эмбеддинги задач берутся из кэша, считается только запрос
+        let counting = CountingEmbedder::default();
+        service
+            .semantic_search("database", &TaskFilter::default(), 10, &counting)
+            .await
+            .expect("Search should succeed");
+        assert_eq!(counting.texts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_date_bounds() {
+        let start = parse_date_bound("2025-03-01", false).expect("Date should parse");
+        let end = parse_date_bound("2025-03-01", true).expect("Date should parse");
+        assert_eq!(start.to_rfc3339(), "2025-03-01T00:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2025-03-01T23:59:59+00:00");
+        assert!(parse_date_bound("March", false).is_err());
+    }
+}
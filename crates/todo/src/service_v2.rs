@@ -1,11 +1,23 @@
 use crate::graph::{DependencyGraphV2, GraphStats};
+use crate::history::{
+    history_digest, time_in_state, HistoryEvent, StateDuration, TaskHistoryEntry, ACTOR_SYSTEM,
+};
+use crate::planning::{normalize_title, GoalPlan, GoalPlanCommit, PlanChange, PlanDiff, GOAL_TAG};
+use crate::recurrence::{next_occurrence, Recurrence, NEXT_OCCURRENCE_KEY, RECURRENCE_KEY};
+use crate::scan::{
+    CodeTodo, ScanReport, CODE_TAG, FILE_KEY, FINGERPRINT_KEY, LINE_KEY, SCAN_ROOT_KEY,
+    SOURCE_CODE_SCAN, SOURCE_KEY,
+};
+use crate::schedule::{Schedule, ScheduleConfig, TaskEstimate, ESTIMATE_KEY};
+use crate::search::{cosine_similarity, embedding_text, TaskEmbedder, TaskFilter, TaskSearchHit};
 use crate::store_v2::TodoStoreV2;
+use crate::templates::TaskTemplate;
 use crate::types::*;
 use anyhow::Result;
 use dashmap::DashMap;
 use lru::LruCache;
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
 use std::path::Path;
 use std::sync::Arc;
@@ -25,7 +37,7 @@ pub struct TodoServiceV2 {
     ready_cache: Arc<DashMap<(), Vec<Uuid>>>,
     // Канал событий для реактивности
     events_tx: mpsc::UnboundedSender<TodoEvent>,
-    events_rx: Arc<Mutex<mpsc::UnboundedReceiver<TodoEvent>>>,
+    events_rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<TodoEvent>>>,
 }
 
 impl TodoServiceV2 {
@@ -62,7 +74,7 @@ impl TodoServiceV2 {
             ))),
             ready_cache: Arc::new(DashMap::new()),
             events_tx,
-            events_rx: Arc::new(Mutex::new(events_rx)),
+            events_rx: Arc::new(tokio::sync::Mutex::new(events_rx)),
         })
     }
 
@@ -197,9 +209,21 @@ impl TodoServiceV2 {
         Ok(ready_tasks.into_iter().take(count).collect())
     }
 
-    /// Обновить состояние с оптимизированным каскадом
-    #[instrument(skip(self))]
+    /// Обновить состояние с оптимизированным каскадом (переход от имени системы)
     pub async fn update_state(&self, id: &Uuid, new_state: TaskState) -> Result<()> {
+        self.update_state_as(id, new_state, ACTOR_SYSTEM, None)
+            .await
+    }
+
+    /// Обновить состояние и записать в журнал, кто и почему его сменил
+    #[instrument(skip(self))]
+    pub async fn update_state_as(
+        &self,
+        id: &Uuid,
+        new_state: TaskState,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<()> {
         let old_task = self
             .get_cached(id)
             .await?
@@ -208,7 +232,10 @@ impl TodoServiceV2 {
         let old_state = old_task.state;
 
         // Обновляем в БД с каскадом
-        let affected_ids = self.store.update_state_cascade(id, new_state).await?;
+        let affected_ids = self
+            .store
+            .update_state_cascade(id, new_state, actor, reason)
+            .await?;
 
         // Обновляем граф для всех затронутых задач
         for affected_id in &affected_ids {
@@ -245,6 +272,9 @@ impl TodoServiceV2 {
                     artifacts: old_task.artifacts.clone(),
                 });
             }
+            if old_state != TaskState::Done {
+                self.create_next_occurrence(&old_task).await?;
+            }
         }
 
         info!(
@@ -258,6 +288,108 @@ impl TodoServiceV2 {
         Ok(())
     }
 
+    /// Создать следующее вхождение повторяющейся задачи и запомнить его в
+    /// `metadata.next_occurrence` выполненной
+    async fn create_next_occurrence(&self, task: &TodoItem) -> Result<Option<TodoItem>> {
+        if task.recurrence().is_none() {
+            return Ok(None);
+        }
+        // Зависимость от повторяющейся задачи переносится на её открытое вхождение
+        let mut resolved = HashMap::new();
+        for dep in &task.depends_on {
+            let Some(dep_task) = self.get_cached(dep).await? else {
+                continue;
+            };
+            if dep_task.recurrence().is_none() {
+                continue;
+            }
+            let series = dep_task.recurrence_series();
+            let open = self
+                .store
+                .search("", usize::MAX)
+                .await?
+                .into_iter()
+                .filter(|t| {
+                    t.recurrence_series() == series
+                        && !matches!(t.state, TaskState::Done | TaskState::Cancelled)
+                })
+                .max_by_key(|t| t.created_at);
+            if let Some(open) = open {
+                resolved.insert(*dep, open.id);
+            }
+        }
+
+        let now = chrono::Utc::now();
+        let Some(mut next) = next_occurrence(task, now, |dep| *resolved.get(dep).unwrap_or(dep))
+        else {
+            info!("Recurrence of {} has ended", task.id);
+            return Ok(None);
+        };
+        for dep in &next.depends_on {
+            let done = self
+                .get_cached(dep)
+                .await?
+                .is_some_and(|t| t.state == TaskState::Done);
+            if !done {
+                next.state = TaskState::Blocked;
+            }
+        }
+
+        let created = self.store.create(next).await?;
+        self.graph.upsert_task(&created)?;
+        let meta = HashMap::from([(
+            NEXT_OCCURRENCE_KEY.to_string(),
+            serde_json::json!(created.id.to_string()),
+        )]);
+        self.store.update_metadata(&task.id, meta).await?;
+        self.cache.lock().pop(&task.id);
+        self.ready_cache.clear();
+
+        self.emit_event(TodoEvent::TaskCreated {
+            task_id: created.id,
+            title: created.title.clone(),
+            auto_generated: created.auto_generated,
+        });
+        info!(
+            "Created next occurrence {} of {} due {:?}",
+            created.id, task.id, created.due_date
+        );
+        Ok(Some(created))
+    }
+
+    /// Задать или снять правило повтора задачи
+    pub async fn set_recurrence(&self, id: &Uuid, recurrence: Option<Recurrence>) -> Result<()> {
+        let value = match recurrence {
+            Some(rule) => serde_json::to_value(rule)?,
+            None => serde_json::Value::Null,
+        };
+        let meta = HashMap::from([(RECURRENCE_KEY.to_string(), value)]);
+        self.store.update_metadata(id, meta).await?;
+        self.cache.lock().pop(id);
+        Ok(())
+    }
+
+    /// Создать граф задач по шаблону. Повторный вызов с теми же значениями
+    /// синхронизирует существующую цель, а не дублирует её.
+    pub async fn instantiate_template(
+        &self,
+        template: &TaskTemplate,
+        args: &[String],
+    ) -> Result<GoalPlanCommit> {
+        let plan = template.instantiate(&template.bind(args)?)?;
+        let goal = self.find_goal_task(&plan.goal).await?.map(|t| t.id);
+        self.commit_goal_plan(&plan, goal).await
+    }
+
+    /// Сохранить изменённые заголовок, описание и приоритет задачи
+    pub async fn update_task(&self, task: &TodoItem) -> Result<()> {
+        self.store.update_details(task).await?;
+        self.cache.lock().pop(&task.id);
+        self.ready_cache.clear();
+        self.emit_event(TodoEvent::TaskUpdated { task_id: task.id });
+        Ok(())
+    }
+
     /// Добавить зависимость с проверкой циклов
     pub async fn add_dependency(&self, task_id: &Uuid, depends_on: &Uuid) -> Result<()> {
         // Проверяем на циклы
@@ -340,6 +472,78 @@ impl TodoServiceV2 {
         Ok(results)
     }
 
+    /// Ранжированный полнотекстовый поиск с фильтрами и подсветкой
+    pub async fn search_ranked(
+        &self,
+        query: &str,
+        filter: &TaskFilter,
+        limit: usize,
+    ) -> Result<Vec<TaskSearchHit>> {
+        self.store.search_ranked(query, filter, limit).await
+    }
+
+    /// Семантический поиск: задачи под фильтром ранжируются по близости
+    /// эмбеддингов к запросу. Эмбеддинги задач считаются только для новых
+    /// или изменившихся задач, остальные берутся из кэша в БД.
+    #[instrument(skip(self, embedder))]
+    pub async fn semantic_search(
+        &self,
+        query: &str,
+        filter: &TaskFilter,
+        limit: usize,
+        embedder: &dyn TaskEmbedder,
+    ) -> Result<Vec<TaskSearchHit>> {
+        let candidates: Vec<TodoItem> = self
+            .store
+            .search_ranked("", filter, usize::MAX)
+            .await?
+            .into_iter()
+            .map(|hit| hit.task)
+            .collect();
+        if candidates.is_empty() || query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<Uuid> = candidates.iter().map(|t| t.id).collect();
+        let mut cached = self.store.get_embeddings(&ids).await?;
+        let stale: Vec<(Uuid, String)> = candidates
+            .iter()
+            .map(|task| (task.id, embedding_text(task)))
+            .filter(|(id, text)| cached.get(id).is_none_or(|(source, _)| source != text))
+            .collect();
+
+        let mut texts: Vec<String> = stale.iter().map(|(_, text)| text.clone()).collect();
+        texts.push(query.to_string());
+        let mut vectors = embedder.embed(&texts).await?;
+        if vectors.len() != texts.len() {
+            return Err(anyhow::anyhow!(
+                "Embedder returned {} vectors for {} texts",
+                vectors.len(),
+                texts.len()
+            ));
+        }
+        let query_vector = vectors.pop().expect("query vector is present");
+        for ((id, text), vector) in stale.into_iter().zip(vectors) {
+            self.store.put_embedding(&id, &text, &vector).await?;
+            cached.insert(id, (text, vector));
+        }
+
+        let mut hits: Vec<TaskSearchHit> = candidates
+            .into_iter()
+            .map(|task| {
+                let score = cached
+                    .get(&task.id)
+                    .map_or(0.0, |(_, vector)| cosine_similarity(&query_vector, vector));
+                TaskSearchHit::plain(task, score)
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+
+        debug!("Семантический поиск '{}': {} задач", query, hits.len());
+        Ok(hits)
+    }
+
     /// Получить статистику
     pub async fn get_stats(&self) -> Result<(TaskStats, GraphStats)> {
         let task_stats = self.store.get_stats().await?;
@@ -427,6 +631,160 @@ impl TodoServiceV2 {
         Ok(result)
     }
 
+    /// Задать трёхточечную оценку трудоёмкости задачи
+    pub async fn set_estimate(&self, id: &Uuid, estimate: TaskEstimate) -> Result<()> {
+        let meta = HashMap::from([(ESTIMATE_KEY.to_string(), serde_json::to_value(estimate)?)]);
+        self.store.update_metadata(id, meta).await?;
+        self.cache.lock().pop(id);
+        Ok(())
+    }
+
+    /// План-график незавершённых задач по методу критического пути
+    pub async fn schedule(&self, config: ScheduleConfig) -> Result<Schedule> {
+        let tasks = self.store.search("", usize::MAX).await?;
+        Schedule::compute(&tasks, config)
+    }
+
+    /// Синхронизировать найденные в коде комментарии с задачами: новые
+    /// создаются, перемещённые обновляются, исчезнувшие закрываются с
+    /// записью в `metadata.history`. Закрытие затрагивает только задачи,
+    /// найденные ранее в том же `scan_root`.
+    #[instrument(skip(self, todos))]
+    pub async fn sync_code_todos(&self, scan_root: &str, todos: &[CodeTodo]) -> Result<ScanReport> {
+        let filter = TaskFilter::default().with_tag(CODE_TAG);
+        let existing: HashMap<String, TodoItem> = self
+            .store
+            .search_ranked("", &filter, usize::MAX)
+            .await?
+            .into_iter()
+            .map(|hit| hit.task)
+            .filter(|task| {
+                task.metadata.get(SCAN_ROOT_KEY).and_then(|v| v.as_str()) == Some(scan_root)
+            })
+            .filter_map(|task| {
+                let fingerprint = task.metadata.get(FINGERPRINT_KEY)?.as_str()?.to_string();
+                Some((fingerprint, task))
+            })
+            .collect();
+
+        let mut report = ScanReport {
+            found: todos.len(),
+            ..Default::default()
+        };
+        let history = |event: &str, todo: &CodeTodo| {
+            serde_json::json!({
+                "event": event,
+                "at": chrono::Utc::now().to_rfc3339(),
+                "location": todo.location(),
+            })
+        };
+
+        for todo in todos {
+            let location = HashMap::from([
+                (FILE_KEY.to_string(), serde_json::json!(todo.file)),
+                (LINE_KEY.to_string(), serde_json::json!(todo.line)),
+            ]);
+            match existing.get(&todo.fingerprint) {
+                Some(task) if matches!(task.state, TaskState::Done | TaskState::Cancelled) => {
+                    // Комментарий вернулся — задача снова открыта
+                    self.store.update_metadata(&task.id, location).await?;
+                    self.store
+                        .append_metadata_array(
+                            &task.id,
+                            "history",
+                            history("comment_restored", todo),
+                        )
+                        .await?;
+                    self.update_state_as(
+                        &task.id,
+                        TaskState::Ready,
+                        SOURCE_CODE_SCAN,
+                        Some("comment restored"),
+                    )
+                    .await?;
+                    report.updated += 1;
+                }
+                Some(task) => {
+                    let moved = task.metadata.get(FILE_KEY) != location.get(FILE_KEY)
+                        || task.metadata.get(LINE_KEY) != location.get(LINE_KEY);
+                    if moved {
+                        self.store.update_metadata(&task.id, location).await?;
+                        self.cache.lock().pop(&task.id);
+                        report.updated += 1;
+                    } else {
+                        report.unchanged += 1;
+                    }
+                }
+                None => {
+                    let mut tags = vec![CODE_TAG.to_string(), todo.kind.to_lowercase()];
+                    tags.extend(todo.tags.iter().cloned());
+                    tags.dedup();
+                    let mut metadata = location;
+                    metadata.insert(SOURCE_KEY.to_string(), serde_json::json!(SOURCE_CODE_SCAN));
+                    metadata.insert(
+                        FINGERPRINT_KEY.to_string(),
+                        serde_json::json!(todo.fingerprint),
+                    );
+                    metadata.insert(SCAN_ROOT_KEY.to_string(), serde_json::json!(scan_root));
+                    let task = TodoItem {
+                        title: todo.title(),
+                        description: format!("{} {}: {}", todo.kind, todo.location(), todo.text),
+                        state: TaskState::Ready,
+                        priority: todo.priority(),
+                        auto_generated: true,
+                        tags,
+                        metadata,
+                        ..Default::default()
+                    };
+                    let created = self.store.create(task).await?;
+                    self.graph.upsert_task(&created)?;
+                    self.emit_event(TodoEvent::TaskCreated {
+                        task_id: created.id,
+                        title: created.title.clone(),
+                        auto_generated: true,
+                    });
+                    report.created += 1;
+                }
+            }
+        }
+
+        let found: HashSet<&str> = todos.iter().map(|t| t.fingerprint.as_str()).collect();
+        for (fingerprint, task) in &existing {
+            if found.contains(fingerprint.as_str())
+                || matches!(task.state, TaskState::Done | TaskState::Cancelled)
+            {
+                continue;
+            }
+            let event = serde_json::json!({
+                "event": "comment_removed",
+                "at": chrono::Utc::now().to_rfc3339(),
+                "location": format!(
+                    "{}:{}",
+                    task.metadata.get(FILE_KEY).and_then(|v| v.as_str()).unwrap_or_default(),
+                    task.metadata.get(LINE_KEY).cloned().unwrap_or_default()
+                ),
+            });
+            self.store
+                .append_metadata_array(&task.id, "history", event)
+                .await?;
+            self.update_state_as(
+                &task.id,
+                TaskState::Done,
+                SOURCE_CODE_SCAN,
+                Some("comment removed"),
+            )
+            .await?;
+            report.closed += 1;
+        }
+
+        self.ready_cache.clear();
+        info!(
+            "Code scan of {}: {} found, {} created, {} updated, {} closed",
+            scan_root, report.found, report.created, report.updated, report.closed
+        );
+        Ok(report)
+    }
+
     /// Подписаться на события
     pub fn subscribe(&self) -> TodoEventStream {
         TodoEventStream {
@@ -462,6 +820,216 @@ impl TodoServiceV2 {
         self.store.update_metadata(id, meta).await
     }
 
+    /// Привязать результат выполнения (запись в памяти) к задаче
+    pub async fn add_artifact(&self, id: &Uuid, artifact: MemoryReference) -> Result<()> {
+        self.store.add_artifact(id, &artifact).await?;
+        self.cache.lock().pop(id);
+        Ok(())
+    }
+
+    /// Получить подзадачи задачи
+    pub async fn get_subtasks(&self, parent_id: &Uuid) -> Result<Vec<TodoItem>> {
+        self.store.get_children(parent_id).await
+    }
+
+    /// Найти задачу-цель по заголовку (без учёта регистра и пробелов)
+    pub async fn find_goal_task(&self, goal: &str) -> Result<Option<TodoItem>> {
+        let title = normalize_title(goal);
+        let candidates = self.store.search(&title, 50).await?;
+        Ok(candidates.into_iter().find(|task| {
+            task.tags.iter().any(|tag| tag == GOAL_TAG)
+                && task.state != TaskState::Cancelled
+                && normalize_title(&task.title) == title
+        }))
+    }
+
+    /// Сохранить план цели: создать новые подзадачи, обновить изменённые,
+    /// синхронизировать зависимости и отменить выпавшие из плана
+    #[instrument(skip(self, plan), fields(goal = %plan.goal))]
+    pub async fn commit_goal_plan(
+        &self,
+        plan: &GoalPlan,
+        goal_task: Option<Uuid>,
+    ) -> Result<GoalPlanCommit> {
+        plan.validate()?;
+
+        let goal_id = match goal_task {
+            Some(id) => id,
+            None => {
+                let goal = TodoItem {
+                    title: plan.goal.clone(),
+                    state: TaskState::Planned,
+                    tags: vec![GOAL_TAG.to_string()],
+                    ..Default::default()
+                };
+                let created = self.store.create(goal).await?;
+                self.graph.upsert_task(&created)?;
+                created.id
+            }
+        };
+
+        let existing = self.store.get_children(&goal_id).await?;
+        let diff = PlanDiff::compute(plan, &existing);
+        let siblings: HashMap<Uuid, TodoItem> = existing.into_iter().map(|t| (t.id, t)).collect();
+
+        let mut commit = GoalPlanCommit {
+            goal_id,
+            task_ids: HashMap::new(),
+            created: 0,
+            updated: 0,
+            cancelled: 0,
+        };
+
+        // Зависимости всегда раньше зависимых, поэтому их id уже известны
+        for key in plan.ordered_keys() {
+            let Some(planned) = plan.task(&key) else {
+                continue;
+            };
+            let wanted: Vec<Uuid> = planned
+                .depends_on
+                .iter()
+                .filter_map(|dep| commit.task_ids.get(dep).copied())
+                .collect();
+
+            let id = match diff.changes.get(&key) {
+                Some(PlanChange::Keep(id)) => *id,
+                Some(PlanChange::Update(id)) => {
+                    let mut task = siblings
+                        .get(id)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+                    task.description = planned.description.clone();
+                    task.priority = planned.priority;
+                    task.confidence = planned.confidence;
+                    task.reasoning = planned.reasoning.clone();
+                    task.tool_hint = planned.tool_hint.clone();
+                    task.tool_params = planned.tool_params.clone();
+                    self.store.update_details(&task).await?;
+                    self.cache.lock().pop(id);
+
+                    for dep in task.depends_on.iter().filter(|d| siblings.contains_key(d)) {
+                        if !wanted.contains(dep) {
+                            self.remove_dependency(id, dep).await?;
+                        }
+                    }
+                    for dep in wanted.iter().filter(|d| !task.depends_on.contains(d)) {
+                        self.add_dependency(id, dep).await?;
+                    }
+                    commit.updated += 1;
+                    *id
+                }
+                _ => {
+                    let mut ready = true;
+                    for dep in &wanted {
+                        let done = self
+                            .get_cached(dep)
+                            .await?
+                            .is_some_and(|t| t.state == TaskState::Done);
+                        ready &= done;
+                    }
+                    let task = TodoItem {
+                        title: planned.title.clone(),
+                        description: planned.description.clone(),
+                        state: if ready {
+                            TaskState::Ready
+                        } else {
+                            TaskState::Blocked
+                        },
+                        priority: planned.priority,
+                        parent_id: Some(goal_id),
+                        depends_on: wanted,
+                        auto_generated: true,
+                        confidence: planned.confidence,
+                        reasoning: planned.reasoning.clone(),
+                        tool_hint: planned.tool_hint.clone(),
+                        tool_params: planned.tool_params.clone(),
+                        ..Default::default()
+                    };
+                    let created = self.store.create(task).await?;
+                    self.graph.upsert_task(&created)?;
+                    self.emit_event(TodoEvent::TaskCreated {
+                        task_id: created.id,
+                        title: created.title.clone(),
+                        auto_generated: true,
+                    });
+                    commit.created += 1;
+                    created.id
+                }
+            };
+            commit.task_ids.insert(key, id);
+        }
+
+        for removed in &diff.removed {
+            self.update_state_as(
+                &removed.id,
+                TaskState::Cancelled,
+                ACTOR_SYSTEM,
+                Some("removed from plan"),
+            )
+            .await?;
+            commit.cancelled += 1;
+        }
+
+        self.ready_cache.clear();
+        info!(
+            "Committed plan for {}: {} created, {} updated, {} cancelled",
+            goal_id, commit.created, commit.updated, commit.cancelled
+        );
+        Ok(commit)
+    }
+
+    /// Добавить комментарий пользователя или агента в журнал задачи
+    pub async fn add_comment(
+        &self,
+        id: &Uuid,
+        author: &str,
+        text: &str,
+    ) -> Result<TaskHistoryEntry> {
+        self.get_cached(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        let entry = self
+            .store
+            .append_history(
+                id,
+                author,
+                HistoryEvent::Comment {
+                    text: text.to_string(),
+                },
+            )
+            .await?;
+        self.emit_event(TodoEvent::CommentAdded {
+            task_id: *id,
+            author: author.to_string(),
+        });
+        Ok(entry)
+    }
+
+    /// Журнал задачи: создание, переходы состояний и комментарии
+    pub async fn get_history(&self, id: &Uuid) -> Result<Vec<TaskHistoryEntry>> {
+        self.store.get_history(id).await
+    }
+
+    /// Сколько задача провела в каждом состоянии
+    pub async fn time_in_state(&self, id: &Uuid) -> Result<Vec<StateDuration>> {
+        let task = self
+            .get_cached(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        let history = self.store.get_history(id).await?;
+        Ok(time_in_state(&task, &history, chrono::Utc::now()))
+    }
+
+    /// Сводка истории задачи для памяти агентов
+    pub async fn history_digest(&self, id: &Uuid) -> Result<String> {
+        let task = self
+            .get_cached(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Task not found"))?;
+        let history = self.store.get_history(id).await?;
+        Ok(history_digest(&task, &history, chrono::Utc::now()))
+    }
+
     /// Добавить элемент в массив metadata задачи по ключу
     pub async fn push_metadata_item(
         &self,
@@ -475,14 +1043,13 @@ impl TodoServiceV2 {
 
 /// Поток событий для подписчиков
 pub struct TodoEventStream {
-    rx: Arc<Mutex<mpsc::UnboundedReceiver<TodoEvent>>>,
+    rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<TodoEvent>>>,
 }
 
 impl TodoEventStream {
     /// Получить следующее событие
-    #[allow(clippy::await_holding_lock)]
     pub async fn next(&self) -> Option<TodoEvent> {
-        self.rx.lock().recv().await
+        self.rx.lock().await.recv().await
     }
 }
 
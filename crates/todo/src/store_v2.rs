@@ -1,3 +1,5 @@
+use crate::history::{HistoryEvent, TaskHistoryEntry, ACTOR_SYSTEM, ACTOR_USER};
+use crate::search::{fts_query, TaskFilter, TaskSearchHit, MATCH_END, MATCH_START};
 use crate::types::Layer;
 use crate::types::*;
 use anyhow::{Context, Result};
@@ -91,6 +93,99 @@ impl TodoStoreV2 {
                 FOREIGN KEY (task_id) REFERENCES todos(id) ON DELETE CASCADE
             );
             
+            -- Таблица артефактов (результаты выполнения в памяти)
+            CREATE TABLE IF NOT EXISTS todo_artifacts (
+                task_id TEXT NOT NULL,
+                mem_layer TEXT NOT NULL,
+                mem_key TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (task_id, mem_key),
+                FOREIGN KEY (task_id) REFERENCES todos(id) ON DELETE CASCADE
+            );
+            
+            -- Кэш эмбеддингов для семантического поиска
+            CREATE TABLE IF NOT EXISTS todo_embeddings (
+                task_id TEXT PRIMARY KEY,
+                source TEXT NOT NULL,
+                vector TEXT NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES todos(id) ON DELETE CASCADE
+            );
+            
+            -- Журнал задач: создание, переходы состояний, комментарии.
+            -- Записи только добавляются
+            CREATE TABLE IF NOT EXISTS todo_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                at TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                event TEXT NOT NULL,
+                FOREIGN KEY (task_id) REFERENCES todos(id) ON DELETE CASCADE
+            );
+            
+            CREATE TRIGGER IF NOT EXISTS todo_history_append_only
+            BEFORE UPDATE ON todo_history
+            BEGIN
+                SELECT RAISE(ABORT, 'todo_history is append-only');
+            END;
+            
+            -- Полнотекстовый индекс по заголовку, описанию и тегам
+            CREATE VIRTUAL TABLE IF NOT EXISTS todos_fts USING fts5(
+                task_id UNINDEXED,
+                title,
+                description,
+                tags,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+            
+            CREATE TRIGGER IF NOT EXISTS todos_fts_insert
+            AFTER INSERT ON todos
+            BEGIN
+                INSERT INTO todos_fts (task_id, title, description, tags)
+                VALUES (NEW.id, NEW.title, COALESCE(NEW.description, ''), '');
+            END;
+            
+            CREATE TRIGGER IF NOT EXISTS todos_fts_update
+            AFTER UPDATE OF title, description ON todos
+            BEGIN
+                UPDATE todos_fts
+                SET title = NEW.title, description = COALESCE(NEW.description, '')
+                WHERE task_id = NEW.id;
+            END;
+            
+            CREATE TRIGGER IF NOT EXISTS todos_fts_delete
+            AFTER DELETE ON todos
+            BEGIN
+                DELETE FROM todos_fts WHERE task_id = OLD.id;
+            END;
+            
+            CREATE TRIGGER IF NOT EXISTS todos_fts_tag_insert
+            AFTER INSERT ON todo_tags
+            BEGIN
+                UPDATE todos_fts
+                SET tags = (SELECT group_concat(tag, ' ') FROM todo_tags WHERE task_id = NEW.task_id)
+                WHERE task_id = NEW.task_id;
+            END;
+            
+            CREATE TRIGGER IF NOT EXISTS todos_fts_tag_delete
+            AFTER DELETE ON todo_tags
+            BEGIN
+                UPDATE todos_fts
+                SET tags = COALESCE(
+                    (SELECT group_concat(tag, ' ') FROM todo_tags WHERE task_id = OLD.task_id), ''
+                )
+                WHERE task_id = OLD.task_id;
+            END;
+            
+            -- Индексируем задачи, созданные до появления FTS
+            INSERT INTO todos_fts (task_id, title, description, tags)
+            SELECT
+                t.id,
+                t.title,
+                COALESCE(t.description, ''),
+                COALESCE((SELECT group_concat(tag, ' ') FROM todo_tags WHERE task_id = t.id), '')
+            FROM todos t
+            WHERE t.id NOT IN (SELECT task_id FROM todos_fts);
+            
             -- Оптимизированные индексы
             CREATE INDEX IF NOT EXISTS idx_todos_state_priority ON todos(state, priority DESC, created_at ASC);
             CREATE INDEX IF NOT EXISTS idx_todos_parent ON todos(parent_id) WHERE parent_id IS NOT NULL;
@@ -98,6 +193,7 @@ impl TodoStoreV2 {
             CREATE INDEX IF NOT EXISTS idx_todos_due_date ON todos(due_date) WHERE due_date IS NOT NULL;
             CREATE INDEX IF NOT EXISTS idx_deps_depends_on ON todo_dependencies(depends_on);
             CREATE INDEX IF NOT EXISTS idx_tags_tag ON todo_tags(tag);
+            CREATE INDEX IF NOT EXISTS idx_history_task ON todo_history(task_id, id);
             
             -- Материализованное представление для быстрого поиска готовых задач
             CREATE VIEW IF NOT EXISTS ready_tasks AS
@@ -204,6 +300,35 @@ impl TodoStoreV2 {
             }
         }
 
+        // Батчевая вставка артефактов
+        if !task.artifacts.is_empty() {
+            let mut stmt = tx.prepare(
+                "INSERT INTO todo_artifacts (task_id, mem_layer, mem_key, created_at) VALUES (?1, ?2, ?3, ?4)"
+            )?;
+
+            for mem_ref in &task.artifacts {
+                stmt.execute(params![
+                    task.id.to_string(),
+                    format!("{:?}", mem_ref.layer),
+                    mem_ref.record_id.to_string(),
+                    mem_ref.created_at.to_rfc3339()
+                ])?;
+            }
+        }
+
+        let actor = if task.auto_generated {
+            ACTOR_SYSTEM
+        } else {
+            ACTOR_USER
+        };
+        Self::insert_history(
+            &tx,
+            &task.id,
+            task.created_at,
+            actor,
+            &HistoryEvent::Created { state: task.state },
+        )?;
+
         tx.commit()?;
 
         debug!(
@@ -241,7 +366,14 @@ impl TodoStoreV2 {
                     ) 
                     FROM todo_context_refs 
                     WHERE task_id = t.id
-                ) as context_refs
+                ) as context_refs,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_artifacts 
+                    WHERE task_id = t.id
+                ) as artifacts
             FROM task_data t
         "#;
 
@@ -289,7 +421,14 @@ impl TodoStoreV2 {
                     ) 
                     FROM todo_context_refs 
                     WHERE task_id = t.id
-                ) as context_refs
+                ) as context_refs,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_artifacts 
+                    WHERE task_id = t.id
+                ) as artifacts
             FROM todos t
             WHERE t.id IN ({id_list})
             "#
@@ -326,7 +465,14 @@ impl TodoStoreV2 {
                     ) 
                     FROM todo_context_refs 
                     WHERE task_id = t.id
-                ) as context_refs
+                ) as context_refs,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_artifacts 
+                    WHERE task_id = t.id
+                ) as artifacts
             FROM todos t
             WHERE t.state = 'ready'
             AND NOT EXISTS (
@@ -347,14 +493,45 @@ impl TodoStoreV2 {
         Ok(tasks)
     }
 
-    /// Обновить состояние с каскадным обновлением зависимых задач
-    pub async fn update_state_cascade(&self, id: &Uuid, new_state: TaskState) -> Result<Vec<Uuid>> {
+    /// Обновить состояние с каскадным обновлением зависимых задач. Переход
+    /// и разблокированные зависимые задачи пишутся в журнал той же транзакцией.
+    pub async fn update_state_cascade(
+        &self,
+        id: &Uuid,
+        new_state: TaskState,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<Vec<Uuid>> {
         let mut conn = self.pool.get()?;
         let tx = conn.transaction()?;
 
         let now = Utc::now();
         let mut affected_ids = vec![*id];
 
+        let old_state: Option<String> = tx
+            .query_row(
+                "SELECT state FROM todos WHERE id = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(old_state) = old_state {
+            let from: TaskState = old_state.parse()?;
+            if from != new_state {
+                Self::insert_history(
+                    &tx,
+                    id,
+                    now,
+                    actor,
+                    &HistoryEvent::StateChanged {
+                        from,
+                        to: new_state,
+                        reason: reason.map(str::to_string),
+                    },
+                )?;
+            }
+        }
+
         // Обновляем основную задачу
         match new_state {
             TaskState::InProgress => {
@@ -404,8 +581,16 @@ impl TodoStoreV2 {
                 .query_map(params![id.to_string()], |row| row.get(0))?
                 .collect::<Result<Vec<_>, _>>()?;
 
+            drop(stmt);
+
+            let unblocked = HistoryEvent::StateChanged {
+                from: TaskState::Blocked,
+                to: TaskState::Ready,
+                reason: Some(format!("dependency {id} done")),
+            };
             for id_str in updated {
                 if let Ok(uuid) = Uuid::parse_str(&id_str) {
+                    Self::insert_history(&tx, &uuid, now, ACTOR_SYSTEM, &unblocked)?;
                     affected_ids.push(uuid);
                 }
             }
@@ -415,14 +600,149 @@ impl TodoStoreV2 {
         Ok(affected_ids)
     }
 
+    fn insert_history(
+        conn: &Connection,
+        task_id: &Uuid,
+        at: DateTime<Utc>,
+        actor: &str,
+        event: &HistoryEvent,
+    ) -> Result<i64> {
+        conn.execute(
+            "INSERT INTO todo_history (task_id, at, actor, event) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                task_id.to_string(),
+                at.to_rfc3339(),
+                actor,
+                serde_json::to_string(event)?
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Добавить запись в журнал задачи
+    pub async fn append_history(
+        &self,
+        task_id: &Uuid,
+        actor: &str,
+        event: HistoryEvent,
+    ) -> Result<TaskHistoryEntry> {
+        let conn = self.pool.get()?;
+        let at = Utc::now();
+        let id = Self::insert_history(&conn, task_id, at, actor, &event)?;
+        Ok(TaskHistoryEntry {
+            id,
+            task_id: *task_id,
+            at,
+            actor: actor.to_string(),
+            event,
+        })
+    }
+
+    /// Журнал задачи в порядке записи
+    pub async fn get_history(&self, task_id: &Uuid) -> Result<Vec<TaskHistoryEntry>> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, at, actor, event FROM todo_history WHERE task_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map(params![task_id.to_string()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, at, actor, event)| {
+                Ok(TaskHistoryEntry {
+                    id,
+                    task_id: *task_id,
+                    at: DateTime::parse_from_rfc3339(&at)?.with_timezone(&Utc),
+                    actor,
+                    event: serde_json::from_str(&event)?,
+                })
+            })
+            .collect()
+    }
+
     /// Поиск задач с полнотекстовым поиском
     pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<TodoItem>> {
+        let hits = self
+            .search_ranked(query, &TaskFilter::default(), limit)
+            .await?;
+        Ok(hits.into_iter().map(|hit| hit.task).collect())
+    }
+
+    /// Ранжированный поиск по FTS5 с фильтрами. Пустой запрос возвращает
+    /// отфильтрованные задачи, недавно обновлённые первыми.
+    #[instrument(skip(self))]
+    pub async fn search_ranked(
+        &self,
+        query: &str,
+        filter: &TaskFilter,
+        limit: usize,
+    ) -> Result<Vec<TaskSearchHit>> {
         let conn = self.pool.get()?;
 
-        // Используем LIKE для простого поиска (можно заменить на FTS5)
-        let search_pattern = format!("%{query}%");
+        let match_query = fts_query(query);
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let mut bind = |condition: &str, value: Box<dyn rusqlite::ToSql>| {
+            values.push(value);
+            conditions.push(condition.replace('?', &format!("?{}", values.len())));
+        };
 
-        let sql = r#"
+        if let Some(match_query) = &match_query {
+            bind("todos_fts MATCH ?", Box::new(match_query.clone()));
+        }
+        if let Some(state) = filter.state {
+            bind("t.state = ?", Box::new(state.to_string()));
+        }
+        if let Some(priority) = filter.min_priority {
+            bind("t.priority >= ?", Box::new(priority as i32));
+        }
+        if let Some(tag) = &filter.tag {
+            bind(
+                "EXISTS (SELECT 1 FROM todo_tags WHERE task_id = t.id AND tag = ?)",
+                Box::new(tag.clone()),
+            );
+        }
+        if let Some(after) = filter.due_after {
+            bind("t.due_date >= ?", Box::new(after.to_rfc3339()));
+        }
+        if let Some(before) = filter.due_before {
+            bind("t.due_date <= ?", Box::new(before.to_rfc3339()));
+        }
+        values.push(Box::new(limit as i64));
+        let limit_param = values.len();
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        // Веса BM25: task_id, title, description, tags
+        let (ranking, order) = if match_query.is_some() {
+            (
+                format!(
+                    "bm25(todos_fts, 0.0, 10.0, 3.0, 5.0) as rank,
+                    highlight(todos_fts, 1, '{MATCH_START}', '{MATCH_END}') as title_highlight,
+                    snippet(todos_fts, 2, '{MATCH_START}', '{MATCH_END}', '…', 16) as snippet"
+                ),
+                "rank",
+            )
+        } else {
+            (
+                "0.0 as rank, t.title as title_highlight, '' as snippet".to_string(),
+                "t.updated_at DESC",
+            )
+        };
+
+        let sql = format!(
+            r#"
             SELECT 
                 t.*,
                 (
@@ -441,28 +761,75 @@ impl TodoStoreV2 {
                     ) 
                     FROM todo_context_refs 
                     WHERE task_id = t.id
-                ) as context_refs
+                ) as context_refs,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_artifacts 
+                    WHERE task_id = t.id
+                ) as artifacts,
+                {ranking}
             FROM todos t
-            WHERE t.title LIKE ?1 OR t.description LIKE ?1
-            OR EXISTS (SELECT 1 FROM todo_tags WHERE task_id = t.id AND tag LIKE ?1)
-            ORDER BY 
-                CASE 
-                    WHEN t.title LIKE ?1 THEN 1
-                    WHEN t.description LIKE ?1 THEN 2
-                    ELSE 3
-                END,
-                t.updated_at DESC
-            LIMIT ?2
-        "#;
+            JOIN todos_fts ON todos_fts.task_id = t.id
+            {where_clause}
+            ORDER BY {order}
+            LIMIT ?{limit_param}
+        "#
+        );
 
-        let mut stmt = conn.prepare(sql)?;
-        let tasks = stmt
-            .query_map(params![search_pattern, limit as i64], |row| {
-                Self::parse_todo_row(row)
+        let mut stmt = conn.prepare(&sql)?;
+        let hits = stmt
+            .query_map(rusqlite::params_from_iter(values.iter()), |row| {
+                let task = Self::parse_todo_row(row)?;
+                let rank: f64 = row.get("rank")?;
+                let snippet: String = row.get("snippet")?;
+                Ok(TaskSearchHit {
+                    score: -rank as f32,
+                    title_highlight: row.get("title_highlight")?,
+                    snippet: if snippet.is_empty() {
+                        task.description.chars().take(120).collect()
+                    } else {
+                        snippet
+                    },
+                    task,
+                })
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(tasks)
+        debug!("Поиск '{}': найдено {} задач", query, hits.len());
+        Ok(hits)
+    }
+
+    /// Кэшированные эмбеддинги задач: `(исходный текст, вектор)`
+    pub async fn get_embeddings(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, (String, Vec<f32>)>> {
+        let conn = self.pool.get()?;
+        let mut stmt =
+            conn.prepare("SELECT source, vector FROM todo_embeddings WHERE task_id = ?1")?;
+
+        let mut embeddings = HashMap::new();
+        for id in ids {
+            let row: Option<(String, String)> = stmt
+                .query_row(params![id.to_string()], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .optional()?;
+            if let Some((source, vector)) = row {
+                embeddings.insert(*id, (source, serde_json::from_str(&vector)?));
+            }
+        }
+        Ok(embeddings)
+    }
+
+    /// Сохранить эмбеддинг задачи вместе с текстом, по которому он построен
+    pub async fn put_embedding(&self, id: &Uuid, source: &str, vector: &[f32]) -> Result<()> {
+        let conn = self.pool.get()?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO todo_embeddings (task_id, source, vector) VALUES (?1, ?2, ?3)",
+            params![id.to_string(), source, serde_json::to_string(vector)?],
+        )?;
+        Ok(())
     }
 
     /// Получить статистику по задачам
@@ -497,6 +864,41 @@ impl TodoStoreV2 {
         })
     }
 
+    /// Ссылки на память из JSON-массива `{layer, key, created_at}`
+    fn parse_memory_refs(json: Option<String>) -> Vec<MemoryReference> {
+        let Some(json) = json else {
+            return Vec::new();
+        };
+        serde_json::from_str::<Vec<serde_json::Value>>(&json)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|v| {
+                let layer = v["layer"].as_str()?;
+                let key = v["key"].as_str()?;
+                let created_at = v["created_at"].as_str()?;
+
+                Some(MemoryReference {
+                    layer: match layer {
+                        "Interact" => Layer::Interact,
+                        "Insights" => Layer::Insights,
+                        "Assets" => Layer::Assets,
+                        // Legacy compatibility
+                        "Ephemeral" => Layer::Interact,
+                        "Short" => Layer::Interact,
+                        "Medium" => Layer::Insights,
+                        "Long" => Layer::Insights,
+                        "Semantic" => Layer::Assets,
+                        _ => return None,
+                    },
+                    record_id: uuid::Uuid::parse_str(key).ok()?,
+                    created_at: DateTime::parse_from_rfc3339(created_at)
+                        .ok()?
+                        .with_timezone(&Utc),
+                })
+            })
+            .collect()
+    }
+
     /// Парсинг строки результата в TodoItem
     fn parse_todo_row(row: &Row) -> rusqlite::Result<TodoItem> {
         let id = Uuid::parse_str(&row.get::<_, String>(0)?)
@@ -521,39 +923,8 @@ impl TodoStoreV2 {
             Vec::new()
         };
 
-        let context_refs_json: Option<String> = row.get("context_refs")?;
-        let context_refs = if let Some(json) = context_refs_json {
-            serde_json::from_str::<Vec<serde_json::Value>>(&json)
-                .unwrap_or_default()
-                .into_iter()
-                .filter_map(|v| {
-                    let layer = v["layer"].as_str()?;
-                    let key = v["key"].as_str()?;
-                    let created_at = v["created_at"].as_str()?;
-
-                    Some(MemoryReference {
-                        layer: match layer {
-                            "Interact" => Layer::Interact,
-                            "Insights" => Layer::Insights,
-                            "Assets" => Layer::Assets,
-                            // Legacy compatibility
-                            "Ephemeral" => Layer::Interact,
-                            "Short" => Layer::Interact,
-                            "Medium" => Layer::Insights,
-                            "Long" => Layer::Insights,
-                            "Semantic" => Layer::Assets,
-                            _ => return None,
-                        },
-                        record_id: uuid::Uuid::parse_str(key).ok()?,
-                        created_at: DateTime::parse_from_rfc3339(created_at)
-                            .ok()?
-                            .with_timezone(&Utc),
-                    })
-                })
-                .collect()
-        } else {
-            Vec::new()
-        };
+        let context_refs = Self::parse_memory_refs(row.get("context_refs")?);
+        let artifacts = Self::parse_memory_refs(row.get("artifacts")?);
 
         let tool_params_json: Option<String> = row.get(15)?;
         let tool_params = tool_params_json.and_then(|json| serde_json::from_str(&json).ok());
@@ -615,11 +986,32 @@ impl TodoStoreV2 {
             depends_on: dependencies,
             blocks: Vec::new(), // Вычисляется отдельно при необходимости
             context_refs,
-            artifacts: Vec::new(), // Загружается отдельно при необходимости
+            artifacts,
             tags,
         })
     }
 
+    /// Привязать артефакт (запись в памяти) к задаче
+    pub async fn add_artifact(&self, task_id: &Uuid, artifact: &MemoryReference) -> Result<()> {
+        let conn = self.pool.get()?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO todo_artifacts (task_id, mem_layer, mem_key, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                task_id.to_string(),
+                format!("{:?}", artifact.layer),
+                artifact.record_id.to_string(),
+                artifact.created_at.to_rfc3339()
+            ],
+        )?;
+
+        debug!(
+            "Добавлен артефакт {} к задаче {}",
+            artifact.record_id, task_id
+        );
+        Ok(())
+    }
+
     /// Добавить зависимость между задачами
     pub async fn add_dependency(&self, task_id: &Uuid, depends_on: &Uuid) -> Result<()> {
         let conn = self.pool.get()?;
@@ -755,7 +1147,14 @@ impl TodoStoreV2 {
                     ) 
                     FROM todo_context_refs 
                     WHERE task_id = t.id
-                ) as context_refs
+                ) as context_refs,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_artifacts 
+                    WHERE task_id = t.id
+                ) as artifacts
             FROM todos t
             WHERE t.state = ?1
             ORDER BY t.priority DESC, t.created_at ASC
@@ -783,4 +1182,79 @@ impl TodoStoreV2 {
         debug!("Найдено {} задач со состоянием {:?}", results.len(), state);
         Ok(results)
     }
+
+    /// Получить подзадачи родительской задачи
+    pub async fn get_children(&self, parent_id: &Uuid) -> Result<Vec<TodoItem>> {
+        let conn = self.pool.get()?;
+
+        let sql = r#"
+            SELECT 
+                t.*,
+                (
+                    SELECT json_group_array(depends_on) 
+                    FROM todo_dependencies 
+                    WHERE task_id = t.id
+                ) as dependencies,
+                (
+                    SELECT json_group_array(tag) 
+                    FROM todo_tags 
+                    WHERE task_id = t.id
+                ) as tags,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_context_refs 
+                    WHERE task_id = t.id
+                ) as context_refs,
+                (
+                    SELECT json_group_array(
+                        json_object('layer', mem_layer, 'key', mem_key, 'created_at', created_at)
+                    ) 
+                    FROM todo_artifacts 
+                    WHERE task_id = t.id
+                ) as artifacts
+            FROM todos t
+            WHERE t.parent_id = ?1
+            ORDER BY t.created_at ASC
+        "#;
+
+        let mut stmt = conn.prepare(sql)?;
+        let children = stmt
+            .query_map(params![parent_id.to_string()], Self::parse_todo_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        debug!("Найдено {} подзадач у {}", children.len(), parent_id);
+        Ok(children)
+    }
+
+    /// Обновить описательные поля задачи (без состояния и связей)
+    pub async fn update_details(&self, task: &TodoItem) -> Result<()> {
+        let conn = self.pool.get()?;
+
+        let updated = conn.execute(
+            "UPDATE todos SET title = ?1, description = ?2, priority = ?3, confidence = ?4,
+                reasoning = ?5, tool_hint = ?6, tool_params = ?7, updated_at = ?8
+             WHERE id = ?9",
+            params![
+                task.title,
+                task.description,
+                task.priority as i32,
+                task.confidence,
+                task.reasoning,
+                task.tool_hint,
+                task.tool_params
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                Utc::now().to_rfc3339(),
+                task.id.to_string(),
+            ],
+        )?;
+
+        if updated == 0 {
+            return Err(anyhow::anyhow!("Task not found: {}", task.id));
+        }
+        Ok(())
+    }
 }
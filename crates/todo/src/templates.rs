@@ -0,0 +1,267 @@
+//! Шаблоны задач
+//!
+//! Шаблон — параметризованный [`GoalPlan`]: заголовки, описания и параметры
+//! инструментов могут содержать `{{param}}`. [`TaskTemplate::instantiate`]
+//! подставляет значения и возвращает план, который сохраняется целиком
+//! через `TodoServiceV2::instantiate_template` — задача-цель и весь граф
+//! зависимых подзадач за один раз. Встроенные шаблоны дополняются JSON-файлами
+//! из каталога шаблонов ([`load_templates`]).
+
+use crate::planning::{GoalPlan, PlannedTask};
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const RELEASE_TEMPLATE: &str = r#"{
+  "name": "release",
+  "description": "Выпуск версии: версия, changelog, тесты, тег и публикация",
+  "params": [{ "name": "version", "description": "Номер версии, например v1.4" }],
+  "goal": "Release {{version}}",
+  "tasks": [
+    { "key": "bump", "title": "Bump version to {{version}}", "priority": "high" },
+    { "key": "changelog", "title": "Update CHANGELOG for {{version}}" },
+    { "key": "test", "title": "Run full test suite for {{version}}", "priority": "high", "depends_on": ["bump"] },
+    { "key": "tag", "title": "Tag {{version}}", "priority": "high", "depends_on": ["changelog", "test"] },
+    { "key": "publish", "title": "Publish release notes for {{version}}", "depends_on": ["tag"] }
+  ]
+}"#;
+
+fn placeholder_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}").expect("placeholder regex is valid")
+    })
+}
+
+/// Параметр шаблона
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateParam {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Значение, если параметр не передан; без него параметр обязателен
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Шаблон графа задач
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub params: Vec<TemplateParam>,
+    /// Заголовок задачи-цели
+    pub goal: String,
+    pub tasks: Vec<PlannedTask>,
+}
+
+impl TaskTemplate {
+    /// Разобрать шаблон и проверить, что все `{{param}}` объявлены
+    pub fn from_json(json: &str) -> Result<Self> {
+        let template: Self =
+            serde_json::from_str(json).map_err(|e| anyhow!("Invalid template JSON: {}", e))?;
+        let declared: Vec<&str> = template.params.iter().map(|p| p.name.as_str()).collect();
+        for text in template.texts() {
+            for capture in placeholder_regex().captures_iter(text) {
+                if !declared.contains(&&capture[1]) {
+                    return Err(anyhow!(
+                        "Template '{}' uses undeclared parameter '{}'",
+                        template.name,
+                        &capture[1]
+                    ));
+                }
+            }
+        }
+        Ok(template)
+    }
+
+    fn texts(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.goal).chain(self.tasks.iter().flat_map(|task| {
+            [&task.title, &task.description]
+                .into_iter()
+                .chain(task.reasoning.iter())
+                .chain(task.tool_params.iter().flat_map(|params| params.values()))
+        }))
+    }
+
+    /// Значения параметров из аргументов: `name=value` или по порядку
+    /// объявления; пропущенные берутся из `default`
+    pub fn bind(&self, args: &[String]) -> Result<HashMap<String, String>> {
+        let mut values = HashMap::new();
+        let mut positional = Vec::new();
+        for arg in args {
+            match arg.split_once('=') {
+                Some((name, value)) if self.params.iter().any(|p| p.name == name) => {
+                    values.insert(name.to_string(), value.to_string());
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        for param in &self.params {
+            if values.contains_key(&param.name) {
+                continue;
+            }
+            let value = positional
+                .next()
+                .cloned()
+                .or_else(|| param.default.clone())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Template '{}' requires parameter '{}'",
+                        self.name,
+                        param.name
+                    )
+                })?;
+            values.insert(param.name.clone(), value);
+        }
+        if let Some(extra) = positional.next() {
+            return Err(anyhow!(
+                "Template '{}' got unexpected argument '{}'",
+                self.name,
+                extra
+            ));
+        }
+        Ok(values)
+    }
+
+    /// План с подставленными значениями, проверенный на циклы
+    pub fn instantiate(&self, values: &HashMap<String, String>) -> Result<GoalPlan> {
+        let render = |text: &str| render(text, values);
+        let tasks = self
+            .tasks
+            .iter()
+            .map(|task| {
+                Ok(PlannedTask {
+                    title: render(&task.title)?,
+                    description: render(&task.description)?,
+                    reasoning: task.reasoning.as_deref().map(render).transpose()?,
+                    tool_params: task
+                        .tool_params
+                        .as_ref()
+                        .map(|params| {
+                            params
+                                .iter()
+                                .map(|(k, v)| Ok((k.clone(), render(v)?)))
+                                .collect::<Result<HashMap<_, _>>>()
+                        })
+                        .transpose()?,
+                    ..task.clone()
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let plan = GoalPlan {
+            goal: render(&self.goal)?,
+            tasks,
+        };
+        plan.validate()?;
+        Ok(plan)
+    }
+}
+
+fn render(text: &str, values: &HashMap<String, String>) -> Result<String> {
+    let mut missing = None;
+    let rendered = placeholder_regex().replace_all(text, |capture: &regex::Captures| {
+        values.get(&capture[1]).cloned().unwrap_or_else(|| {
+            missing = Some(capture[1].to_string());
+            String::new()
+        })
+    });
+    match missing {
+        Some(name) => Err(anyhow!("No value for template parameter '{}'", name)),
+        None => Ok(rendered.into_owned()),
+    }
+}
+
+/// Встроенные шаблоны
+pub fn builtin_templates() -> Vec<TaskTemplate> {
+    [RELEASE_TEMPLATE]
+        .into_iter()
+        .map(|json| TaskTemplate::from_json(json).expect("builtin template is valid"))
+        .collect()
+}
+
+/// Встроенные шаблоны и `*.json` из `dir`; файл с тем же именем шаблона
+/// заменяет встроенный. Отсутствующий каталог — не ошибка.
+pub fn load_templates(dir: &Path) -> Result<Vec<TaskTemplate>> {
+    let mut templates = builtin_templates();
+    if !dir.is_dir() {
+        return Ok(templates);
+    }
+
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let json = std::fs::read_to_string(&path)?;
+        let template = TaskTemplate::from_json(&json)
+            .with_context(|| format!("Failed to load template {}", path.display()))?;
+        templates.retain(|t| t.name != template.name);
+        templates.push(template);
+    }
+    Ok(templates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_template_instantiates() {
+        let template = builtin_templates()
+            .into_iter()
+            .find(|t| t.name == "release")
+            .expect("Release template should exist");
+        let values = template
+            .bind(&["v1.4".to_string()])
+            .expect("Params should bind");
+        let plan = template
+            .instantiate(&values)
+            .expect("Template should instantiate");
+
+        assert_eq!(plan.goal, "Release v1.4");
+        assert_eq!(
+            plan.ordered_keys().last().map(String::as_str),
+            Some("publish")
+        );
+        assert_eq!(plan.task("tag").map(|t| t.title.as_str()), Some("Tag v1.4"));
+
+        assert!(template.bind(&[]).is_err());
+        assert!(template
+            .bind(&["v1.4".to_string(), "extra".to_string()])
+            .is_err());
+        assert_eq!(
+            template
+                .bind(&["version=2.0".to_string()])
+                .expect("Named param should bind")["version"],
+            "2.0"
+        );
+    }
+
+    #[test]
+    fn test_template_rejects_undeclared_params() {
+        let json = r#"{
+            "name": "rotate",
+            "params": [{ "name": "service", "default": "api" }],
+            "goal": "Rotate {{service}} keys",
+            "tasks": [{ "key": "a", "title": "Revoke {{old_key}}" }]
+        }"#;
+        let error = TaskTemplate::from_json(json).expect_err("Undeclared param should fail");
+        assert!(error.to_string().contains("old_key"));
+
+        let template = TaskTemplate::from_json(&json.replace("{{old_key}}", "old {{service}} key"))
+            .expect("Template should parse");
+        let plan = template
+            .instantiate(&template.bind(&[]).expect("Default should bind"))
+            .expect("Template should instantiate");
+        assert_eq!(plan.tasks[0].title, "Revoke old api key");
+    }
+}
@@ -94,6 +94,20 @@ impl std::fmt::Display for Priority {
     }
 }
 
+impl std::str::FromStr for Priority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            "critical" => Ok(Priority::Critical),
+            _ => Err(anyhow::anyhow!("Unknown priority: {}", s)),
+        }
+    }
+}
+
 /// Основная структура задачи
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoItem {
@@ -202,6 +216,14 @@ pub enum TodoEvent {
         task_id: Uuid,
         reason: String,
     },
+    /// Изменены заголовок, описание или приоритет
+    TaskUpdated {
+        task_id: Uuid,
+    },
+    CommentAdded {
+        task_id: Uuid,
+        author: String,
+    },
 }
 
 /// Подготовленная к выполнению задача
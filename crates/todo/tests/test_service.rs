@@ -2,7 +2,9 @@
 #![allow(unused_imports)]
 #![allow(clippy::uninlined_format_args)]
 use tempfile::TempDir;
-use todo::{Priority, TaskState, TodoEvent, TodoService};
+use todo::{
+    builtin_templates, Priority, Recurrence, TaskState, TodoEvent, TodoService, NEXT_OCCURRENCE_KEY,
+};
 use uuid::Uuid;
 
 #[tokio::test]
@@ -407,6 +409,177 @@ async fn test_event_stream() {
     }
 }
 
+#[tokio::test]
+async fn test_update_task_emits_event() {
+    let temp_dir = TempDir::new().expect("failed to create temp dir");
+    let db_path = temp_dir.path().join("test.db");
+
+    let service = TodoService::new(&db_path, 4, 100)
+        .await
+        .expect("failed to create TodoService");
+    let events = service.subscribe();
+
+    let mut task = service
+        .create_task("Draft".to_string(), String::new(), Priority::Low, vec![])
+        .await
+        .expect("Failed to create task");
+    events.next().await.expect("Created event expected");
+
+    task.title = "Final".to_string();
+    task.priority = Priority::High;
+    service.update_task(&task).await.expect("Update failed");
+
+    match events.next().await {
+        Some(TodoEvent::TaskUpdated { task_id }) => assert_eq!(task_id, task.id),
+        other => panic!("Unexpected event: {:?}", other),
+    }
+    let stored = service
+        .get_cached(&task.id)
+        .await
+        .expect("Get failed")
+        .expect("Task should exist");
+    assert_eq!(stored.title, "Final");
+    assert_eq!(stored.priority, Priority::High);
+    assert_eq!(
+        service
+            .search("final", 10)
+            .await
+            .expect("Search failed")
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_recurring_task_spawns_next_occurrence() {
+    let temp_dir = TempDir::new().expect("failed to create temp dir");
+    let db_path = temp_dir.path().join("test.db");
+
+    let service = TodoService::new(&db_path, 4, 100)
+        .await
+        .expect("failed to create TodoService");
+
+    let mut audit = service
+        .create_task(
+            "Refresh dependency audit".to_string(),
+            String::new(),
+            Priority::Medium,
+            vec!["chore".to_string()],
+        )
+        .await
+        .expect("Failed to create task");
+    audit.tool_hint = Some("shell".to_string());
+    service.update_task(&audit).await.expect("Update failed");
+    let rotate = service
+        .create_task(
+            "Rotate keys".to_string(),
+            String::new(),
+            Priority::High,
+            vec!["security".to_string()],
+        )
+        .await
+        .expect("Failed to create task");
+    service
+        .add_dependency(&rotate.id, &audit.id)
+        .await
+        .expect("Failed to add dependency");
+    let weekly: Recurrence = "FREQ=WEEKLY".parse().expect("Rule should parse");
+    for id in [&audit.id, &rotate.id] {
+        service
+            .set_recurrence(id, Some(weekly.clone()))
+            .await
+            .expect("Failed to set recurrence");
+    }
+
+    let next_of = |task: &todo::TodoItem| {
+        task.metadata[NEXT_OCCURRENCE_KEY]
+            .as_str()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .expect("Next occurrence should be recorded")
+    };
+
+    service
+        .update_state(&audit.id, TaskState::Done)
+        .await
+        .expect("Failed to complete task");
+    let done_audit = service
+        .get_cached(&audit.id)
+        .await
+        .expect("Get failed")
+        .expect("Task should exist");
+    let next_audit = service
+        .get_cached(&next_of(&done_audit))
+        .await
+        .expect("Get failed")
+        .expect("Next occurrence should exist");
+    assert_eq!(next_audit.state, TaskState::Ready);
+    assert_eq!(next_audit.tags, vec!["chore".to_string()]);
+    assert_eq!(next_audit.tool_hint.as_deref(), Some("shell"));
+    assert_eq!(next_audit.recurrence(), Some(weekly.clone()));
+    assert_eq!(next_audit.recurrence_series(), audit.id);
+    let until_due = next_audit.due_date.expect("Due date should be set") - chrono::Utc::now();
+    assert!(until_due > chrono::Duration::days(6) && until_due <= chrono::Duration::days(7));
+
+    // Зависимость переносится на открытое вхождение повторяющейся задачи
+    service
+        .update_state(&rotate.id, TaskState::Done)
+        .await
+        .expect("Failed to complete task");
+    let done_rotate = service
+        .get_cached(&rotate.id)
+        .await
+        .expect("Get failed")
+        .expect("Task should exist");
+    let next_rotate = service
+        .get_cached(&next_of(&done_rotate))
+        .await
+        .expect("Get failed")
+        .expect("Next occurrence should exist");
+    assert_eq!(next_rotate.depends_on, vec![next_audit.id]);
+    assert_eq!(next_rotate.state, TaskState::Blocked);
+    assert_eq!(next_rotate.priority, Priority::High);
+}
+
+#[tokio::test]
+async fn test_instantiate_template_creates_subgraph() {
+    let temp_dir = TempDir::new().expect("failed to create temp dir");
+    let db_path = temp_dir.path().join("test.db");
+
+    let service = TodoService::new(&db_path, 4, 100)
+        .await
+        .expect("failed to create TodoService");
+
+    let release = builtin_templates()
+        .into_iter()
+        .find(|t| t.name == "release")
+        .expect("Release template should exist");
+    let commit = service
+        .instantiate_template(&release, &["v1.4".to_string()])
+        .await
+        .expect("Template should instantiate");
+    assert_eq!(commit.created, release.tasks.len());
+
+    let subtasks = service
+        .get_subtasks(&commit.goal_id)
+        .await
+        .expect("Failed to get subtasks");
+    assert_eq!(subtasks.len(), release.tasks.len());
+    let tag = subtasks
+        .iter()
+        .find(|t| t.title == "Tag v1.4")
+        .expect("Tag task should exist");
+    assert_eq!(tag.depends_on.len(), 2);
+    assert_eq!(tag.state, TaskState::Blocked);
+
+    // Повторный вызов синхронизирует ту же цель
+    let again = service
+        .instantiate_template(&release, &["v1.4".to_string()])
+        .await
+        .expect("Template should instantiate");
+    assert_eq!(again.goal_id, commit.goal_id);
+    assert_eq!(again.created, 0);
+}
+
 #[tokio::test]
 async fn test_task_statistics() {
     let temp_dir = TempDir::new().expect("failed to create temp dir");
@@ -282,6 +282,13 @@ impl ExecutionPipeline {
         // Validate security permissions
         self.validate_security_permissions(&metadata, &context)?;
 
+        // Validate and coerce arguments against the tool's JSON Schema
+        let mut input = input;
+        crate::input_schema::InputSchema::from_value(metadata.input_schema.clone())
+            .map_err(|e| anyhow!("Tool {} has an invalid input schema: {}", tool_id, e))?
+            .coerce_args(&mut input.args)
+            .map_err(|e| anyhow!("Invalid arguments for tool {}: {}", tool_id, e))?;
+
         // Execute based on strategy
         let result = match strategy {
             ExecutionStrategy::Direct => {
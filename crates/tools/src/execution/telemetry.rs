@@ -312,6 +312,16 @@ impl TelemetryCollector {
         severity: SecuritySeverity,
         details: HashMap<String, String>,
     ) {
+        common::audit::record_event(
+            common::topics::TOPIC_SANDBOX_VIOLATION.0,
+            "telemetry",
+            &serde_json::json!({
+                "event_type": format!("{event_type:?}"),
+                "operation_id": operation_id,
+                "severity": format!("{severity:?}"),
+                "details": details,
+            }),
+        );
         let event = TelemetryEvent::SecurityEvent {
             event_type,
             operation_id,
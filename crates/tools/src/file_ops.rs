@@ -343,7 +343,14 @@ impl Tool for FileReader {
                 "file_read README.md".to_string(),
                 "показать содержимое config.toml".to_string(),
             ],
-            input_schema: r#"{"path": "string"}"#.to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "File path to read", "minLength": 1}
+                },
+                "required": ["path"]
+            })
+            .to_string(),
             usage_guide: Some(crate::UsageGuide {
                 usage_title: "file_read".into(),
                 usage_summary: "🔒 SECURITY: Read access restricted to configured read roots"
@@ -450,7 +457,15 @@ impl Tool for FileWriter {
                 "file_write test.txt Hello World".to_string(),
                 "создать файл config.json с содержимым {...}".to_string(),
             ],
-            input_schema: r#"{"path": "string", "content": "string"}"#.to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "File path to write", "minLength": 1},
+                    "content": {"type": "string", "description": "Content to write"}
+                },
+                "required": ["path"]
+            })
+            .to_string(),
             usage_guide: Some(crate::UsageGuide {
                 usage_title: "file_write".into(),
                 usage_summary: "🔒 SECURITY: Write access restricted to configured write roots"
@@ -580,7 +595,13 @@ impl Tool for DirLister {
                 "dir_list src/".to_string(),
                 "показать содержимое папки".to_string(),
             ],
-            input_schema: r#"{"path": "string"}"#.to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "Directory to list", "default": "."}
+                }
+            })
+            .to_string(),
             usage_guide: Some(crate::UsageGuide {
                 usage_title: "dir_list".into(),
                 usage_summary: "🔒 SECURITY: Directory access restricted to configured read roots"
@@ -715,7 +736,15 @@ impl Tool for FileSearcher {
                 "file_search main.rs src/".to_string(),
                 "найти все файлы .toml".to_string(),
             ],
-            input_schema: r#"{"pattern": "string", "path": "string?"}"#.to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "pattern": {"type": "string", "description": "File name pattern (glob or substring)"},
+                    "path": {"type": "string", "description": "Directory to search in", "default": "."}
+                },
+                "required": ["pattern"]
+            })
+            .to_string(),
             usage_guide: Some(crate::UsageGuide {
                 usage_title: "file_search".into(),
                 usage_summary: "🔒 SECURITY: File search restricted to configured read roots"
@@ -891,7 +920,14 @@ impl Tool for FileDeleter {
                 "file_delete tmp/test.txt".to_string(),
                 "удалить файл build.log".to_string(),
             ],
-            input_schema: r#"{"path": "string"}"#.to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string", "description": "File to delete", "minLength": 1}
+                },
+                "required": ["path"]
+            })
+            .to_string(),
             usage_guide: None,
             permissions: None,
             supports_dry_run: true,
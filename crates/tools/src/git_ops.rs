@@ -1,5 +1,5 @@
 use crate::{Tool, ToolInput, ToolOutput, ToolSpec};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 use std::process::Command;
 
@@ -25,8 +25,23 @@ impl Tool for GitStatus {
             description: "Показывает статус git репозитория".to_string(),
             usage: "git_status".to_string(),
             examples: vec!["git_status".to_string()],
-            input_schema: r#"{}"#.to_string(),
-            usage_guide: None,
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_status",
+                "Show the working tree status: branch, staged, modified and untracked files",
+                &[],
+                &["checking for local changes before committing"],
+                &["file contents (use git_diff)"],
+                &[],
+                1,
+                &["read", "vcs"],
+            )),
             permissions: None,
             supports_dry_run: false,
         }
@@ -96,7 +111,14 @@ impl Tool for GitCommit {
                 "git_commit \"Добавил новую функцию\"".to_string(),
                 "создать коммит с сообщением \"исправил баг\"".to_string(),
             ],
-            input_schema: r#"{"message": "string"}"#.to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "message": {"type": "string", "description": "Commit message", "minLength": 1},
+                    "cwd": {"type": "string", "description": "Repository working directory"}
+                }
+            })
+            .to_string(),
             usage_guide: None,
             permissions: None,
             supports_dry_run: true,
@@ -289,8 +311,23 @@ impl Tool for GitDiff {
             description: "Показывает изменения в файлах".to_string(),
             usage: "git_diff".to_string(),
             examples: vec!["git_diff".to_string()],
-            input_schema: r#"{}"#.to_string(),
-            usage_guide: None,
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_diff",
+                "Show changes of files in the working tree",
+                &[],
+                &["reviewing changes before committing"],
+                &["commit history (use git_log)"],
+                &[],
+                1,
+                &["read", "vcs"],
+            )),
             permissions: None,
             supports_dry_run: false,
         }
@@ -347,6 +384,1643 @@ impl Tool for GitDiff {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Инструменты истории и веток. Результат — JSON в `ToolOutput::result`
+// (`metadata.format = "json"`), человекочитаемая сводка — в
+// `formatted_output`. Разрушительные операции (reset, checkout --force,
+// checkout путей, удаление веток/stash/worktree с force) по умолчанию требуют
+// подтверждения: см. `common::policy::default_document`.
+// ---------------------------------------------------------------------------
+
+/// Разделители полей и записей в `--format`: не встречаются в тексте коммитов
+const FIELD_SEP: char = '\u{1f}';
+const RECORD_SEP: char = '\u{1e}';
+
+/// Потолок строк патча и содержимого файлов в выводе по умолчанию
+const DEFAULT_MAX_LINES: usize = 400;
+
+struct GitRun {
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn run_git(cwd: Option<&String>, args: &[String]) -> Result<GitRun> {
+    run_git_with_stdin(cwd, args, None)
+}
+
+fn run_git_with_stdin(
+    cwd: Option<&String>,
+    args: &[String],
+    stdin: Option<&str>,
+) -> Result<GitRun> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    let output = match stdin {
+        Some(text) => {
+            let mut child = cmd
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()?;
+            if let Some(mut pipe) = child.stdin.take() {
+                pipe.write_all(text.as_bytes())?;
+            }
+            child.wait_with_output()?
+        }
+        None => cmd.output()?,
+    };
+    Ok(GitRun {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Командная строка для предпросмотра
+fn command_line(args: &[String]) -> String {
+    let quoted: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg.clone()
+            }
+        })
+        .collect();
+    format!("git {}", quoted.join(" "))
+}
+
+fn json_output(value: serde_json::Value, formatted: String) -> ToolOutput {
+    ToolOutput {
+        success: true,
+        result: value.to_string(),
+        formatted_output: Some(formatted),
+        metadata: HashMap::from([("format".into(), "json".into())]),
+    }
+}
+
+fn git_failure(run: &GitRun) -> ToolOutput {
+    let error = run.stderr.trim();
+    ToolOutput {
+        success: false,
+        result: serde_json::json!({ "error": error }).to_string(),
+        formatted_output: Some(format!("Git error: {error}")),
+        metadata: HashMap::from([("format".into(), "json".into())]),
+    }
+}
+
+/// Dry-run: команда и, если есть, предпросмотр того, что она затронет
+fn dry_run_output(args: &[String], preview: Option<serde_json::Value>) -> ToolOutput {
+    let command = command_line(args);
+    let mut formatted = format!("$ {command}\n[dry-run: no side effects]");
+    if let Some(preview) = &preview {
+        formatted.push_str(&format!("\n{preview:#}"));
+    }
+    ToolOutput {
+        success: true,
+        result: serde_json::json!({
+            "dry_run": true,
+            "command": command,
+            "preview": preview,
+        })
+        .to_string(),
+        formatted_output: Some(formatted),
+        metadata: HashMap::from([
+            ("format".into(), "json".into()),
+            ("dry_run".into(), "true".into()),
+        ]),
+    }
+}
+
+/// Непустой аргумент; значения с `-` в начале отклоняются, чтобы не
+/// превратиться в опцию git
+fn arg<'a>(input: &'a ToolInput, key: &str) -> Result<Option<&'a str>> {
+    match input
+        .args
+        .get(key)
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+    {
+        Some(value) if value.starts_with('-') => {
+            Err(anyhow!("Argument '{key}' must not start with '-': {value}"))
+        }
+        value => Ok(value),
+    }
+}
+
+fn required_arg<'a>(input: &'a ToolInput, key: &str) -> Result<&'a str> {
+    arg(input, key)?.ok_or_else(|| anyhow!("Missing required argument '{key}'"))
+}
+
+/// Булев флаг: `true`, `1` или `yes` (в том же виде его проверяет политика)
+fn flag(input: &ToolInput, key: &str) -> bool {
+    input
+        .args
+        .get(key)
+        .is_some_and(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+}
+
+fn number(input: &ToolInput, key: &str, default: usize) -> Result<usize> {
+    match input
+        .args
+        .get(key)
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+    {
+        Some(value) => value
+            .parse()
+            .map_err(|_| anyhow!("Argument '{key}' must be a number, got '{value}'")),
+        None => Ok(default),
+    }
+}
+
+/// Список путей через запятую (`a.rs,b.rs`); JSON-массив тоже принимается
+fn paths_arg(input: &ToolInput, key: &str) -> Result<Vec<String>> {
+    let Some(value) = input.args.get(key) else {
+        return Ok(Vec::new());
+    };
+    let paths: Vec<String> = if value.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<String>>(value)
+            .map_err(|e| anyhow!("'{key}' must be an array of paths: {e}"))?
+    } else {
+        value.split(',').map(str::to_string).collect()
+    };
+    let paths: Vec<String> = paths
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if let Some(bad) = paths.iter().find(|p| p.starts_with('-')) {
+        return Err(anyhow!("Path must not start with '-': {bad}"));
+    }
+    Ok(paths)
+}
+
+fn action<'a>(input: &'a ToolInput, allowed: &[&str], default: &'a str) -> Result<&'a str> {
+    let action = arg(input, "action")?.unwrap_or(default);
+    if allowed.contains(&action) {
+        Ok(action)
+    } else {
+        Err(anyhow!(
+            "Unknown action '{action}', expected one of: {}",
+            allowed.join(", ")
+        ))
+    }
+}
+
+fn args_of(parts: &[&str]) -> Vec<String> {
+    parts.iter().map(|p| p.to_string()).collect()
+}
+
+fn head_commit(cwd: Option<&String>) -> Option<String> {
+    run_git(cwd, &args_of(&["rev-parse", "HEAD"]))
+        .ok()
+        .filter(|run| run.success)
+        .map(|run| run.stdout.trim().to_string())
+}
+
+/// Изменённые файлы рабочего дерева (`git status --porcelain`)
+fn dirty_files(cwd: Option<&String>, paths: &[String]) -> Vec<serde_json::Value> {
+    let mut args = args_of(&["status", "--porcelain"]);
+    if !paths.is_empty() {
+        args.push("--".into());
+        args.extend(paths.iter().cloned());
+    }
+    run_git(cwd, &args)
+        .ok()
+        .filter(|run| run.success)
+        .map(|run| {
+            run.stdout
+                .lines()
+                .filter(|line| line.len() > 3)
+                .map(|line| serde_json::json!({ "status": line[..2].trim(), "path": &line[3..] }))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Однострочные коммиты диапазона (`a..b`) для предпросмотра
+fn commits_in(cwd: Option<&String>, range: &str) -> Vec<serde_json::Value> {
+    let format = format!("--format=%h{FIELD_SEP}%s");
+    run_git(cwd, &["log".to_string(), format, range.to_string()])
+        .ok()
+        .filter(|run| run.success)
+        .map(|run| {
+            run.stdout
+                .lines()
+                .filter_map(|line| line.split_once(FIELD_SEP))
+                .map(|(hash, subject)| serde_json::json!({ "hash": hash, "subject": subject }))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Первые `max_lines` строк и признак обрезки
+fn truncate_lines(text: &str, max_lines: usize) -> (String, bool) {
+    let total = text.lines().count();
+    if total <= max_lines {
+        (text.to_string(), false)
+    } else {
+        (
+            text.lines().take(max_lines).collect::<Vec<_>>().join("\n"),
+            true,
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn git_usage_guide(
+    title: &str,
+    summary: &str,
+    arguments: &[(&str, &str)],
+    good_for: &[&str],
+    not_for: &[&str],
+    side_effects: &[&str],
+    risk_score: u8,
+    capabilities: &[&str],
+) -> crate::UsageGuide {
+    let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    crate::UsageGuide {
+        usage_title: title.into(),
+        usage_summary: summary.into(),
+        preconditions: vec!["cwd must be inside a git repository".into()],
+        arguments_brief: arguments
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        good_for: strings(good_for),
+        not_for: strings(not_for),
+        constraints: vec![
+            "Output is JSON in `result`".into(),
+            "Arguments starting with '-' are rejected".into(),
+        ],
+        examples: vec![],
+        platforms: vec!["linux".into(), "mac".into(), "win".into()],
+        cost_class: "free".into(),
+        latency_class: "fast".into(),
+        side_effects: strings(side_effects),
+        risk_score,
+        capabilities: strings(capabilities),
+        tags: vec!["git".into(), "vcs".into()],
+    }
+}
+
+fn simple_input(command: &str, query: &str) -> ToolInput {
+    ToolInput {
+        command: command.to_string(),
+        args: HashMap::new(),
+        context: Some(query.to_string()),
+        dry_run: false,
+        timeout_ms: None,
+    }
+}
+
+/// Разобрать `git log --format=%H␟%h␟%an␟%ae␟%aI␟%P␟%s␞`
+fn parse_log(stdout: &str) -> Vec<serde_json::Value> {
+    stdout
+        .split(RECORD_SEP)
+        .map(|record| record.trim_start_matches('\n'))
+        .filter(|record| !record.trim().is_empty())
+        .filter_map(|record| {
+            let fields: Vec<&str> = record.split(FIELD_SEP).collect();
+            let [hash, short, author, email, date, parents, subject] = fields[..] else {
+                return None;
+            };
+            Some(serde_json::json!({
+                "hash": hash,
+                "short": short,
+                "author": author,
+                "email": email,
+                "date": date,
+                "parents": parents.split_whitespace().collect::<Vec<_>>(),
+                "subject": subject.trim_end(),
+            }))
+        })
+        .collect()
+}
+
+/// Разобрать `--numstat`: `added<TAB>deleted<TAB>path`, `-` у бинарных файлов
+fn parse_numstat(stdout: &str) -> Vec<serde_json::Value> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let (added, deleted, path) = (parts.next()?, parts.next()?, parts.next()?);
+            Some(serde_json::json!({
+                "path": path,
+                "additions": added.parse::<u64>().ok(),
+                "deletions": deleted.parse::<u64>().ok(),
+                "binary": added == "-",
+            }))
+        })
+        .collect()
+}
+
+/// Разобрать `git blame --porcelain`: сведения о коммите приходят один раз,
+/// при первой строке из него
+fn parse_blame(stdout: &str) -> Vec<serde_json::Value> {
+    #[derive(Default, Clone)]
+    struct CommitInfo {
+        author: String,
+        email: String,
+        time: i64,
+        summary: String,
+    }
+
+    let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current: Option<(String, u64)> = None;
+    for line in stdout.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            if let Some((hash, number)) = current.take() {
+                let info = commits.get(&hash).cloned().unwrap_or_default();
+                let date = chrono::DateTime::from_timestamp(info.time, 0)
+                    .map(|d| d.to_rfc3339())
+                    .unwrap_or_default();
+                lines.push(serde_json::json!({
+                    "line": number,
+                    "commit": hash,
+                    "author": info.author,
+                    "email": info.email,
+                    "date": date,
+                    "summary": info.summary,
+                    "content": content,
+                }));
+            }
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        let is_header = key.len() == 40 && key.chars().all(|c| c.is_ascii_hexdigit());
+        if is_header {
+            let number = value
+                .split_whitespace()
+                .nth(1)
+                .and_then(|n| n.parse().ok())
+                .unwrap_or_default();
+            commits.entry(key.to_string()).or_default();
+            current = Some((key.to_string(), number));
+            continue;
+        }
+        let Some(info) = current.as_ref().and_then(|(hash, _)| commits.get_mut(hash)) else {
+            continue;
+        };
+        match key {
+            "author" => info.author = value.to_string(),
+            "author-mail" => info.email = value.trim_matches(['<', '>']).to_string(),
+            "author-time" => info.time = value.parse().unwrap_or_default(),
+            "summary" => info.summary = value.to_string(),
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// Разобрать `git worktree list --porcelain`
+fn parse_worktrees(stdout: &str) -> Vec<serde_json::Value> {
+    stdout
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let mut worktree = serde_json::json!({
+                "path": null,
+                "head": null,
+                "branch": null,
+                "detached": false,
+                "bare": false,
+                "locked": false,
+            });
+            for line in block.lines() {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                match key {
+                    "worktree" => worktree["path"] = value.into(),
+                    "HEAD" => worktree["head"] = value.into(),
+                    "branch" => worktree["branch"] = value.trim_start_matches("refs/heads/").into(),
+                    "detached" | "bare" | "locked" => worktree[key] = true.into(),
+                    _ => {}
+                }
+            }
+            worktree
+        })
+        .collect()
+}
+
+pub struct GitLog;
+
+impl GitLog {
+    pub fn new() -> Self {
+        GitLog
+    }
+}
+
+impl Default for GitLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitLog {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_log".to_string(),
+            description: "История коммитов с фильтрами по пути, диапазону и автору (JSON)"
+                .to_string(),
+            usage: "git_log [range] [path] [max_count]".to_string(),
+            examples: vec![
+                "git_log path=src/lib.rs max_count=5".to_string(),
+                "git_log range=main..HEAD".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "range": {"type": "string", "description": "Revision range, e.g. main..HEAD or v1.0"},
+                    "path": {"type": "string", "description": "Only commits touching this path"},
+                    "author": {"type": "string", "description": "Author name or email pattern"},
+                    "since": {"type": "string", "description": "Only commits after this date, e.g. 2025-01-01 or '2 weeks ago'"},
+                    "max_count": {"type": "integer", "minimum": 1, "maximum": 1000, "default": 20}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_log",
+                "Read commit history as JSON: hash, author, date, parents, subject",
+                &[
+                    ("range", "Revision range (main..HEAD)"),
+                    ("path", "Limit to commits touching a path"),
+                    ("author", "Author filter"),
+                    ("since", "Date filter"),
+                    ("max_count", "Maximum commits (default 20)"),
+                ],
+                &["history", "finding when a change was introduced", "release notes"],
+                &["file contents (use git_show)"],
+                &[],
+                1,
+                &["read", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let max_count = number(&input, "max_count", 20)?;
+        let mut args = vec![
+            "log".to_string(),
+            format!(
+                "--format=%H{FIELD_SEP}%h{FIELD_SEP}%an{FIELD_SEP}%ae{FIELD_SEP}%aI{FIELD_SEP}%P{FIELD_SEP}%s{RECORD_SEP}"
+            ),
+            format!("--max-count={max_count}"),
+        ];
+        if let Some(author) = arg(&input, "author")? {
+            args.push(format!("--author={author}"));
+        }
+        if let Some(since) = arg(&input, "since")? {
+            args.push(format!("--since={since}"));
+        }
+        if let Some(range) = arg(&input, "range")? {
+            args.push(range.to_string());
+        }
+        if let Some(path) = arg(&input, "path")? {
+            args.push("--".into());
+            args.push(path.to_string());
+        }
+
+        if input.dry_run {
+            return Ok(dry_run_output(&args, None));
+        }
+        let run = run_git(cwd, &args)?;
+        if !run.success {
+            return Ok(git_failure(&run));
+        }
+        let commits = parse_log(&run.stdout);
+        let formatted = commits
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} {} {} ({})",
+                    c["short"].as_str().unwrap_or_default(),
+                    c["date"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .get(..10)
+                        .unwrap_or_default(),
+                    c["subject"].as_str().unwrap_or_default(),
+                    c["author"].as_str().unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(json_output(
+            serde_json::json!({ "count": commits.len(), "commits": commits }),
+            if formatted.is_empty() {
+                "Коммиты не найдены".to_string()
+            } else {
+                formatted
+            },
+        ))
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_log", query))
+    }
+}
+
+pub struct GitShow;
+
+impl GitShow {
+    pub fn new() -> Self {
+        GitShow
+    }
+}
+
+impl Default for GitShow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitShow {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_show".to_string(),
+            description: "Коммит (метаданные, файлы, патч) или файл на ревизии (JSON)"
+                .to_string(),
+            usage: "git_show [rev] [path]".to_string(),
+            examples: vec![
+                "git_show rev=HEAD~1".to_string(),
+                "git_show rev=v1.0 path=Cargo.toml".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "rev": {"type": "string", "description": "Commit, tag or branch", "default": "HEAD"},
+                    "path": {"type": "string", "description": "Show this file's content at rev instead of the commit"},
+                    "max_lines": {"type": "integer", "minimum": 1, "default": DEFAULT_MAX_LINES}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_show",
+                "Inspect a commit (message, changed files, truncated patch) or a file at a revision",
+                &[
+                    ("rev", "Commit-ish (default HEAD)"),
+                    ("path", "File to read at rev"),
+                    ("max_lines", "Patch/content line limit"),
+                ],
+                &["reviewing a commit", "reading old file versions"],
+                &["searching history (use git_log)"],
+                &[],
+                1,
+                &["read", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let rev = arg(&input, "rev")?.unwrap_or("HEAD");
+        let max_lines = number(&input, "max_lines", DEFAULT_MAX_LINES)?;
+
+        if let Some(path) = arg(&input, "path")? {
+            let args = vec!["show".to_string(), format!("{rev}:{path}")];
+            if input.dry_run {
+                return Ok(dry_run_output(&args, None));
+            }
+            let run = run_git(cwd, &args)?;
+            if !run.success {
+                return Ok(git_failure(&run));
+            }
+            let (content, truncated) = truncate_lines(&run.stdout, max_lines);
+            return Ok(json_output(
+                serde_json::json!({
+                    "rev": rev,
+                    "path": path,
+                    "content": content,
+                    "truncated": truncated,
+                }),
+                format!("{rev}:{path}\n{content}"),
+            ));
+        }
+
+        let meta_args = vec![
+            "show".to_string(),
+            "-s".to_string(),
+            format!(
+                "--format=%H{FIELD_SEP}%an{FIELD_SEP}%ae{FIELD_SEP}%aI{FIELD_SEP}%P{FIELD_SEP}%B"
+            ),
+            rev.to_string(),
+        ];
+        if input.dry_run {
+            return Ok(dry_run_output(&meta_args, None));
+        }
+        let meta = run_git(cwd, &meta_args)?;
+        if !meta.success {
+            return Ok(git_failure(&meta));
+        }
+        let fields: Vec<&str> = meta.stdout.splitn(6, FIELD_SEP).collect();
+        let [hash, author, email, date, parents, message] = fields[..] else {
+            return Err(anyhow!("Unexpected git show output"));
+        };
+
+        let numstat = run_git(cwd, &args_of(&["show", "--format=", "--numstat", hash]))?;
+        let patch = run_git(cwd, &args_of(&["show", "--format=", "--patch", hash]))?;
+        let (patch, truncated) = truncate_lines(&patch.stdout, max_lines);
+        let files = parse_numstat(&numstat.stdout);
+
+        let formatted = format!(
+            "commit {hash}\nAuthor: {author} <{email}>\nDate: {date}\n\n{}\n\n{} file(s) changed",
+            message.trim(),
+            files.len()
+        );
+        Ok(json_output(
+            serde_json::json!({
+                "hash": hash,
+                "author": author,
+                "email": email,
+                "date": date,
+                "parents": parents.split_whitespace().collect::<Vec<_>>(),
+                "message": message.trim(),
+                "files": files,
+                "patch": patch,
+                "truncated": truncated,
+            }),
+            formatted,
+        ))
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_show", query))
+    }
+}
+
+pub struct GitBlame;
+
+impl GitBlame {
+    pub fn new() -> Self {
+        GitBlame
+    }
+}
+
+impl Default for GitBlame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitBlame {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_blame".to_string(),
+            description: "Кто и в каком коммите менял строки файла (JSON)".to_string(),
+            usage: "git_blame <path> [start_line] [end_line] [rev]".to_string(),
+            examples: vec!["git_blame path=src/main.rs start_line=10 end_line=40".to_string()],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "path": {"type": "string", "description": "File to blame", "minLength": 1},
+                    "start_line": {"type": "integer", "minimum": 1},
+                    "end_line": {"type": "integer", "minimum": 1},
+                    "rev": {"type": "string", "description": "Blame as of this revision"}
+                },
+                "required": ["path"]
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_blame",
+                "Attribute each line of a file (or a line range) to its last commit and author",
+                &[
+                    ("path", "File to blame"),
+                    ("start_line", "First line (1-based)"),
+                    ("end_line", "Last line, inclusive"),
+                    ("rev", "Revision to blame at"),
+                ],
+                &[
+                    "finding who changed code and why",
+                    "locating the commit behind a bug",
+                ],
+                &["whole-repository history (use git_log)"],
+                &[],
+                1,
+                &["read", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let path = required_arg(&input, "path")?;
+        let mut args = args_of(&["blame", "--porcelain"]);
+        let start = number(&input, "start_line", 1)?.max(1);
+        if input.args.contains_key("start_line") || input.args.contains_key("end_line") {
+            let end = match input.args.get("end_line") {
+                Some(_) => number(&input, "end_line", start)?.to_string(),
+                None => String::new(),
+            };
+            args.push(format!("-L{start},{end}"));
+        }
+        if let Some(rev) = arg(&input, "rev")? {
+            args.push(rev.to_string());
+        }
+        args.push("--".into());
+        args.push(path.to_string());
+
+        if input.dry_run {
+            return Ok(dry_run_output(&args, None));
+        }
+        let run = run_git(cwd, &args)?;
+        if !run.success {
+            return Ok(git_failure(&run));
+        }
+        let lines = parse_blame(&run.stdout);
+        let formatted = lines
+            .iter()
+            .map(|l| {
+                format!(
+                    "{:>5} {} {:<16} {}",
+                    l["line"],
+                    l["commit"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .get(..8)
+                        .unwrap_or_default(),
+                    l["author"].as_str().unwrap_or_default(),
+                    l["content"].as_str().unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(json_output(
+            serde_json::json!({ "path": path, "lines": lines }),
+            formatted,
+        ))
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        let mut input = simple_input("git_blame", query);
+        if let Some(path) = query
+            .split_whitespace()
+            .find(|w| w.contains('.') || w.contains('/'))
+        {
+            input.args.insert("path".into(), path.to_string());
+        }
+        Ok(input)
+    }
+}
+
+pub struct GitBranch;
+
+impl GitBranch {
+    pub fn new() -> Self {
+        GitBranch
+    }
+}
+
+impl Default for GitBranch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitBranch {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_branch".to_string(),
+            description: "Ветки: список, создание, переключение, удаление (JSON)".to_string(),
+            usage: "git_branch [list|create|switch|delete] [name]".to_string(),
+            examples: vec![
+                "git_branch".to_string(),
+                "git_branch action=create name=feature/x start_point=main".to_string(),
+                "git_branch action=switch name=feature/x".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "action": {"type": "string", "enum": ["list", "create", "switch", "delete"], "default": "list"},
+                    "name": {"type": "string", "description": "Branch name"},
+                    "start_point": {"type": "string", "description": "Commit-ish for a new branch"},
+                    "all": {"type": "boolean", "description": "List remote-tracking branches too"},
+                    "force": {"type": "boolean", "description": "Delete even if not merged (asks for confirmation)"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_branch",
+                "List branches with upstream tracking, create, switch (refuses to overwrite local changes) or delete",
+                &[
+                    ("action", "list | create | switch | delete"),
+                    ("name", "Branch name"),
+                    ("start_point", "Base for create"),
+                    ("force", "Delete unmerged branch"),
+                ],
+                &["starting feature work", "checking what branch is current"],
+                &["discarding local changes (use git_checkout force)"],
+                &["Creates, moves HEAD to or deletes branches"],
+                3,
+                &["write", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        match action(&input, &["list", "create", "switch", "delete"], "list")? {
+            "list" => {
+                let format = [
+                    "refname:short",
+                    "objectname:short",
+                    "HEAD",
+                    "upstream:short",
+                    "upstream:track",
+                    "subject",
+                ]
+                .map(|field| format!("%({field})"))
+                .join("%1f");
+                let mut args = vec!["branch".to_string(), format!("--format={format}")];
+                if flag(&input, "all") {
+                    args.push("--all".into());
+                }
+                if input.dry_run {
+                    return Ok(dry_run_output(&args, None));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                let branches: Vec<serde_json::Value> = run
+                    .stdout
+                    .lines()
+                    .filter_map(|line| {
+                        let fields: Vec<&str> = line.split(FIELD_SEP).collect();
+                        let [name, commit, head, upstream, track, subject] = fields[..] else {
+                            return None;
+                        };
+                        Some(serde_json::json!({
+                            "name": name,
+                            "commit": commit,
+                            "current": head == "*",
+                            "upstream": (!upstream.is_empty()).then_some(upstream),
+                            "track": (!track.is_empty()).then_some(track),
+                            "subject": subject,
+                        }))
+                    })
+                    .collect();
+                let current = branches
+                    .iter()
+                    .find(|b| b["current"] == true)
+                    .map(|b| b["name"].clone());
+                let formatted = branches
+                    .iter()
+                    .map(|b| {
+                        format!(
+                            "{} {} {}",
+                            if b["current"] == true { "*" } else { " " },
+                            b["name"].as_str().unwrap_or_default(),
+                            b["commit"].as_str().unwrap_or_default()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(json_output(
+                    serde_json::json!({ "current": current, "branches": branches }),
+                    formatted,
+                ))
+            }
+            "create" => {
+                let name = required_arg(&input, "name")?;
+                let mut args = args_of(&["branch", name]);
+                if let Some(start) = arg(&input, "start_point")? {
+                    args.push(start.to_string());
+                }
+                if input.dry_run {
+                    return Ok(dry_run_output(&args, None));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                Ok(json_output(
+                    serde_json::json!({ "created": name, "start_point": arg(&input, "start_point")? }),
+                    format!("✅ Создана ветка {name}"),
+                ))
+            }
+            "switch" => {
+                let name = required_arg(&input, "name")?;
+                let args = args_of(&["switch", name]);
+                if input.dry_run {
+                    let preview = serde_json::json!({ "uncommitted": dirty_files(cwd, &[]) });
+                    return Ok(dry_run_output(&args, Some(preview)));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                Ok(json_output(
+                    serde_json::json!({ "switched": name, "head": head_commit(cwd) }),
+                    format!("✅ Переключено на {name}"),
+                ))
+            }
+            _ => {
+                let name = required_arg(&input, "name")?;
+                let force = flag(&input, "force");
+                let args = args_of(&["branch", if force { "-D" } else { "-d" }, name]);
+                if input.dry_run {
+                    let preview = serde_json::json!({
+                        "unmerged_commits": commits_in(cwd, &format!("HEAD..{name}")),
+                    });
+                    return Ok(dry_run_output(&args, Some(preview)));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                Ok(json_output(
+                    serde_json::json!({ "deleted": name, "forced": force }),
+                    format!("🗑 Удалена ветка {name}"),
+                ))
+            }
+        }
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_branch", query))
+    }
+}
+
+pub struct GitCheckout;
+
+impl GitCheckout {
+    pub fn new() -> Self {
+        GitCheckout
+    }
+}
+
+impl Default for GitCheckout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitCheckout {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_checkout".to_string(),
+            description: "Перейти на ревизию или восстановить файлы из неё (JSON); force и восстановление путей отбрасывают незакоммиченные изменения".to_string(),
+            usage: "git_checkout <target> [paths] [force]".to_string(),
+            examples: vec![
+                "git_checkout target=v1.0".to_string(),
+                "git_checkout target=HEAD paths=src/lib.rs".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "target": {"type": "string", "description": "Branch, tag or commit", "minLength": 1},
+                    "paths": {"type": "array", "items": {"type": "string"}, "description": "Restore only these paths from target (overwrites local changes)"},
+                    "create": {"type": "boolean", "description": "Create target as a new branch"},
+                    "force": {"type": "boolean", "description": "Discard local changes (asks for confirmation)"}
+                },
+                "required": ["target"]
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_checkout",
+                "Move HEAD to a revision, or restore paths from it. Destructive: force and path restores discard uncommitted changes in the affected files",
+                &[
+                    ("target", "Branch, tag or commit"),
+                    ("paths", "Paths to restore (array or comma-separated)"),
+                    ("create", "Create a new branch"),
+                    ("force", "Discard local changes"),
+                ],
+                &["inspecting an old revision", "reverting a file to a known state"],
+                &["saving work in progress (use git_stash first)"],
+                &["Changes HEAD and working tree", "May discard uncommitted changes"],
+                5,
+                &["write", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let target = required_arg(&input, "target")?;
+        let paths = paths_arg(&input, "paths")?;
+        let force = flag(&input, "force");
+        let create = flag(&input, "create");
+
+        let mut args = vec!["checkout".to_string()];
+        if force {
+            args.push("--force".into());
+        }
+        if create {
+            args.push("-b".into());
+        }
+        args.push(target.to_string());
+        if !paths.is_empty() {
+            args.push("--".into());
+            args.extend(paths.iter().cloned());
+        }
+
+        if input.dry_run {
+            // Что будет потеряно: локальные изменения в затронутых путях
+            let preview = (force || !paths.is_empty())
+                .then(|| serde_json::json!({ "would_discard": dirty_files(cwd, &paths) }));
+            return Ok(dry_run_output(&args, preview));
+        }
+        let run = run_git(cwd, &args)?;
+        if !run.success {
+            return Ok(git_failure(&run));
+        }
+        Ok(json_output(
+            serde_json::json!({
+                "target": target,
+                "paths": paths,
+                "forced": force,
+                "created": create,
+                "head": head_commit(cwd),
+            }),
+            if paths.is_empty() {
+                format!("✅ HEAD → {target}")
+            } else {
+                format!("✅ Восстановлено из {target}: {}", paths.join(", "))
+            },
+        ))
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_checkout", query))
+    }
+}
+
+pub struct GitStash;
+
+impl GitStash {
+    pub fn new() -> Self {
+        GitStash
+    }
+}
+
+impl Default for GitStash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitStash {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_stash".to_string(),
+            description: "Отложить изменения и работать со stash (JSON)".to_string(),
+            usage: "git_stash [list|push|pop|apply|drop|show] [stash] [message]".to_string(),
+            examples: vec![
+                "git_stash action=push message=\"wip: parser\"".to_string(),
+                "git_stash action=pop".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "action": {"type": "string", "enum": ["list", "push", "pop", "apply", "drop", "show"], "default": "list"},
+                    "stash": {"type": "string", "description": "Stash entry, e.g. stash@{1} (default: latest)"},
+                    "message": {"type": "string", "description": "Message for push"},
+                    "include_untracked": {"type": "boolean", "description": "Stash untracked files too"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_stash",
+                "Save uncommitted work aside and restore it later; drop deletes an entry (asks for confirmation)",
+                &[
+                    ("action", "list | push | pop | apply | drop | show"),
+                    ("stash", "Entry reference"),
+                    ("message", "Description for push"),
+                ],
+                &["switching tasks with a dirty tree", "keeping experiments"],
+                &["long-term storage (commit instead)"],
+                &["Modifies working tree and stash list"],
+                4,
+                &["write", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let stash = arg(&input, "stash")?;
+        let action = action(
+            &input,
+            &["list", "push", "pop", "apply", "drop", "show"],
+            "list",
+        )?;
+        let mut args = vec!["stash".to_string(), action.to_string()];
+        match action {
+            "list" => args.push(format!("--format=%gd{FIELD_SEP}%H{FIELD_SEP}%gs")),
+            "push" => {
+                if flag(&input, "include_untracked") {
+                    args.push("--include-untracked".into());
+                }
+                if let Some(message) = arg(&input, "message")? {
+                    args.push("--message".into());
+                    args.push(message.to_string());
+                }
+            }
+            "show" => args.push("--numstat".into()),
+            _ => {}
+        }
+        if action != "list" && action != "push" {
+            args.extend(stash.map(str::to_string));
+        }
+
+        if input.dry_run {
+            let entry = stash.unwrap_or("stash@{0}");
+            let preview = match action {
+                "push" => Some(serde_json::json!({ "would_stash": dirty_files(cwd, &[]) })),
+                "pop" | "apply" | "drop" => run_git(cwd, &args_of(&["stash", "show", "--numstat", entry]))
+                    .ok()
+                    .filter(|run| run.success)
+                    .map(|run| serde_json::json!({ "stash": entry, "files": parse_numstat(&run.stdout) })),
+                _ => None,
+            };
+            return Ok(dry_run_output(&args, preview));
+        }
+        let run = run_git(cwd, &args)?;
+        if !run.success {
+            return Ok(git_failure(&run));
+        }
+        let (value, formatted) = match action {
+            "list" => {
+                let entries: Vec<serde_json::Value> = run
+                    .stdout
+                    .lines()
+                    .filter_map(|line| {
+                        let fields: Vec<&str> = line.splitn(3, FIELD_SEP).collect();
+                        let [reference, commit, message] = fields[..] else {
+                            return None;
+                        };
+                        Some(serde_json::json!({ "ref": reference, "commit": commit, "message": message }))
+                    })
+                    .collect();
+                let formatted = entries
+                    .iter()
+                    .map(|e| {
+                        format!(
+                            "{} {}",
+                            e["ref"].as_str().unwrap_or_default(),
+                            e["message"].as_str().unwrap_or_default()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (serde_json::json!({ "entries": entries }), formatted)
+            }
+            "show" => {
+                let files = parse_numstat(&run.stdout);
+                let formatted = format!("{} file(s) in stash", files.len());
+                (
+                    serde_json::json!({ "stash": stash.unwrap_or("stash@{0}"), "files": files }),
+                    formatted,
+                )
+            }
+            _ => {
+                let output = run.stdout.trim();
+                // `git stash push` без изменений завершается успешно, но ничего не сохраняет
+                let changed = !output.starts_with("No local changes");
+                (
+                    serde_json::json!({
+                        "action": action,
+                        "stash": stash,
+                        "changed": changed,
+                        "output": output,
+                    }),
+                    output.to_string(),
+                )
+            }
+        };
+        Ok(json_output(value, formatted))
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_stash", query))
+    }
+}
+
+pub struct GitApply;
+
+impl GitApply {
+    pub fn new() -> Self {
+        GitApply
+    }
+}
+
+impl Default for GitApply {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitApply {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_apply".to_string(),
+            description: "Проверить (по умолчанию) или применить патч (JSON)".to_string(),
+            usage: "git_apply <patch|patch_file> [check]".to_string(),
+            examples: vec![
+                "git_apply patch_file=fix.patch".to_string(),
+                "git_apply patch_file=fix.patch check=false".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "patch": {"type": "string", "description": "Unified diff text"},
+                    "patch_file": {"type": "string", "description": "Path to a patch file"},
+                    "check": {"type": "boolean", "default": true, "description": "Only check that the patch applies"},
+                    "reverse": {"type": "boolean", "description": "Apply in reverse"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_apply",
+                "Check whether a patch applies cleanly (`git apply --check`) and list touched files; apply it with check=false",
+                &[
+                    ("patch", "Patch text"),
+                    ("patch_file", "Patch file path"),
+                    ("check", "Check only (default true)"),
+                    ("reverse", "Reverse the patch"),
+                ],
+                &["validating generated patches before writing files"],
+                &["binary or non-git patches"],
+                &["With check=false modifies working tree files"],
+                3,
+                &["write", "vcs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let patch = input.args.get("patch").filter(|p| !p.trim().is_empty());
+        let patch_file = arg(&input, "patch_file")?;
+        if patch.is_none() && patch_file.is_none() {
+            return Err(anyhow!("Either 'patch' or 'patch_file' is required"));
+        }
+        let check_only = input
+            .args
+            .get("check")
+            .is_none_or(|_| flag(&input, "check"));
+
+        let mut base = vec!["apply".to_string()];
+        if flag(&input, "reverse") {
+            base.push("--reverse".into());
+        }
+        let with = |extra: Option<&str>| {
+            let mut args = base.clone();
+            args.extend(extra.map(str::to_string));
+            args.push(patch_file.unwrap_or("-").to_string());
+            args
+        };
+        let run = |args: &[String]| run_git_with_stdin(cwd, args, patch.map(String::as_str));
+
+        let files = parse_numstat(&run(&with(Some("--numstat")))?.stdout);
+        let check = run(&with(Some("--check")))?;
+        let errors: Vec<&str> = check
+            .stderr
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .collect();
+        let apply_args = with(None);
+
+        if !check_only && input.dry_run {
+            let preview = serde_json::json!({ "applicable": check.success, "files": files, "errors": errors });
+            return Ok(dry_run_output(&apply_args, Some(preview)));
+        }
+        let applied = if !check_only && check.success {
+            let applied = run(&apply_args)?;
+            if !applied.success {
+                return Ok(git_failure(&applied));
+            }
+            true
+        } else {
+            false
+        };
+
+        let formatted = if check.success {
+            format!(
+                "{} {} file(s)",
+                if applied {
+                    "✅ Применён патч:"
+                } else {
+                    "✅ Патч применим:"
+                },
+                files.len()
+            )
+        } else {
+            format!("❌ Патч не применяется:\n{}", errors.join("\n"))
+        };
+        let mut output = json_output(
+            serde_json::json!({
+                "applicable": check.success,
+                "applied": applied,
+                "files": files,
+                "errors": errors,
+            }),
+            formatted,
+        );
+        output.success = check.success;
+        Ok(output)
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        let mut input = simple_input("git_apply", query);
+        if let Some(file) = query
+            .split_whitespace()
+            .find(|w| w.ends_with(".patch") || w.ends_with(".diff"))
+        {
+            input.args.insert("patch_file".into(), file.to_string());
+        }
+        Ok(input)
+    }
+}
+
+pub struct GitWorktree;
+
+impl GitWorktree {
+    pub fn new() -> Self {
+        GitWorktree
+    }
+}
+
+impl Default for GitWorktree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitWorktree {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_worktree".to_string(),
+            description: "Дополнительные рабочие деревья: список, добавление, удаление (JSON)"
+                .to_string(),
+            usage: "git_worktree [list|add|remove] [path] [branch] [rev]".to_string(),
+            examples: vec![
+                "git_worktree action=add path=../wt-fix branch=fix/parser".to_string(),
+                "git_worktree action=remove path=../wt-fix".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "action": {"type": "string", "enum": ["list", "add", "remove"], "default": "list"},
+                    "path": {"type": "string", "description": "Worktree directory"},
+                    "branch": {"type": "string", "description": "Create this branch for the new worktree"},
+                    "rev": {"type": "string", "description": "Commit-ish to check out in the new worktree"},
+                    "force": {"type": "boolean", "description": "Remove even with local changes (asks for confirmation)"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_worktree",
+                "Work on several branches at once in separate directories",
+                &[
+                    ("action", "list | add | remove"),
+                    ("path", "Worktree directory"),
+                    ("branch", "New branch for add"),
+                    ("rev", "Base revision for add"),
+                    ("force", "Remove a dirty worktree"),
+                ],
+                &["isolated experiments", "parallel agent work"],
+                &["switching branches in place (use git_branch)"],
+                &["Creates or deletes directories and branches"],
+                4,
+                &["write", "vcs", "fs"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        match action(&input, &["list", "add", "remove"], "list")? {
+            "list" => {
+                let args = args_of(&["worktree", "list", "--porcelain"]);
+                if input.dry_run {
+                    return Ok(dry_run_output(&args, None));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                let worktrees = parse_worktrees(&run.stdout);
+                let formatted = worktrees
+                    .iter()
+                    .map(|w| {
+                        format!(
+                            "{} {}",
+                            w["path"].as_str().unwrap_or_default(),
+                            w["branch"].as_str().unwrap_or("(detached)")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                Ok(json_output(
+                    serde_json::json!({ "worktrees": worktrees }),
+                    formatted,
+                ))
+            }
+            "add" => {
+                let path = required_arg(&input, "path")?;
+                let branch = arg(&input, "branch")?;
+                let mut args = args_of(&["worktree", "add"]);
+                if let Some(branch) = branch {
+                    args.push("-b".into());
+                    args.push(branch.to_string());
+                }
+                args.push(path.to_string());
+                args.extend(arg(&input, "rev")?.map(str::to_string));
+                if input.dry_run {
+                    return Ok(dry_run_output(&args, None));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                let worktree_dir = match cwd {
+                    Some(cwd) => std::path::Path::new(cwd).join(path),
+                    None => std::path::PathBuf::from(path),
+                };
+                let head = head_commit(Some(&worktree_dir.to_string_lossy().into_owned()));
+                Ok(json_output(
+                    serde_json::json!({ "path": path, "branch": branch, "head": head }),
+                    format!("✅ Worktree создан: {path}"),
+                ))
+            }
+            _ => {
+                let path = required_arg(&input, "path")?;
+                let force = flag(&input, "force");
+                let mut args = args_of(&["worktree", "remove"]);
+                if force {
+                    args.push("--force".into());
+                }
+                args.push(path.to_string());
+                if input.dry_run {
+                    let worktree_dir = match cwd {
+                        Some(cwd) => std::path::Path::new(cwd).join(path),
+                        None => std::path::PathBuf::from(path),
+                    };
+                    let dirty =
+                        dirty_files(Some(&worktree_dir.to_string_lossy().into_owned()), &[]);
+                    return Ok(dry_run_output(
+                        &args,
+                        Some(serde_json::json!({ "uncommitted": dirty })),
+                    ));
+                }
+                let run = run_git(cwd, &args)?;
+                if !run.success {
+                    return Ok(git_failure(&run));
+                }
+                Ok(json_output(
+                    serde_json::json!({ "removed": path, "forced": force }),
+                    format!("🗑 Worktree удалён: {path}"),
+                ))
+            }
+        }
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_worktree", query))
+    }
+}
+
+pub struct GitReset;
+
+impl GitReset {
+    pub fn new() -> Self {
+        GitReset
+    }
+}
+
+impl Default for GitReset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GitReset {
+    fn spec(&self) -> ToolSpec {
+        ToolSpec {
+            name: "git_reset".to_string(),
+            description: "Сбросить текущую ветку на ревизию (soft/mixed/hard, JSON)".to_string(),
+            usage: "git_reset [target] [mode]".to_string(),
+            examples: vec![
+                "git_reset target=HEAD~1 mode=soft".to_string(),
+                "git_reset target=HEAD~2 mode=hard".to_string(),
+            ],
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "cwd": {"type": "string", "description": "Repository working directory"},
+                    "target": {"type": "string", "description": "Commit to reset to", "default": "HEAD"},
+                    "mode": {"type": "string", "enum": ["soft", "mixed", "hard"], "default": "mixed"}
+                }
+            })
+            .to_string(),
+            usage_guide: Some(git_usage_guide(
+                "git_reset",
+                "Move the current branch to another commit. Hard mode discards all uncommitted changes; always asks for confirmation",
+                &[
+                    ("target", "Commit-ish (default HEAD)"),
+                    ("mode", "soft | mixed | hard"),
+                ],
+                &["undoing local commits", "unstaging everything"],
+                &["undoing pushed commits (revert instead)"],
+                &[
+                    "Moves the branch; commits may become unreachable",
+                    "Hard mode discards working tree changes",
+                ],
+                5,
+                &["write", "vcs", "destructive"],
+            )),
+            permissions: None,
+            supports_dry_run: true,
+        }
+    }
+
+    async fn execute(&self, input: ToolInput) -> Result<ToolOutput> {
+        let cwd = input.args.get("cwd");
+        let target = arg(&input, "target")?.unwrap_or("HEAD");
+        let mode = arg(&input, "mode")?.unwrap_or("mixed");
+        if !matches!(mode, "soft" | "mixed" | "hard") {
+            return Err(anyhow!(
+                "Unknown reset mode '{mode}', expected soft, mixed or hard"
+            ));
+        }
+        let args = vec!["reset".to_string(), format!("--{mode}"), target.to_string()];
+
+        if input.dry_run {
+            let mut preview = serde_json::json!({
+                "commits_leaving_branch": commits_in(cwd, &format!("{target}..HEAD")),
+            });
+            if mode == "hard" {
+                preview["would_discard"] = dirty_files(cwd, &[]).into();
+            }
+            return Ok(dry_run_output(&args, Some(preview)));
+        }
+        let before = head_commit(cwd);
+        let run = run_git(cwd, &args)?;
+        if !run.success {
+            return Ok(git_failure(&run));
+        }
+        let after = head_commit(cwd);
+        Ok(json_output(
+            serde_json::json!({
+                "mode": mode,
+                "target": target,
+                "head_before": before,
+                "head_after": after,
+            }),
+            format!("✅ reset --{mode} → {target}"),
+        ))
+    }
+
+    async fn parse_natural_language(&self, query: &str) -> Result<ToolInput> {
+        Ok(simple_input("git_reset", query))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -480,4 +2154,97 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_log_records() {
+        let stdout = format!(
+            "{h}{f}abc1234{f}Ann{f}ann@example.com{f}2025-03-01T09:00:00+00:00{f}{p1} {p2}{f}Merge branch 'x'{r}\n\
+             {h}{f}abc1234{f}Bob{f}bob@example.com{f}2025-02-28T10:00:00+00:00{f}{f}Initial{r}\n",
+            h = "a".repeat(40),
+            p1 = "b".repeat(40),
+            p2 = "c".repeat(40),
+            f = FIELD_SEP,
+            r = RECORD_SEP,
+        );
+        let commits = parse_log(&stdout);
+
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0]["author"], "Ann");
+        assert_eq!(commits[0]["parents"].as_array().map(Vec::len), Some(2));
+        assert_eq!(commits[0]["subject"], "Merge branch 'x'");
+        assert_eq!(commits[1]["parents"].as_array().map(Vec::len), Some(0));
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let hash = "d".repeat(40);
+        let stdout = format!(
+            "{hash} 3 10 2\nauthor Ann\nauthor-mail <ann@example.com>\nauthor-time 1740819600\n\
+             author-tz +0000\nsummary Add parser\nfilename src/lib.rs\n\tfn parse() {{\n\
+             {hash} 4 11\n\t}}\n"
+        );
+        let lines = parse_blame(&stdout);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["line"], 10);
+        assert_eq!(lines[0]["email"], "ann@example.com");
+        assert_eq!(lines[0]["summary"], "Add parser");
+        // Сведения о коммите не повторяются для следующих строк
+        assert_eq!(lines[1]["author"], "Ann");
+        assert_eq!(lines[1]["line"], 11);
+        assert_eq!(lines[1]["content"], "}");
+    }
+
+    #[test]
+    fn test_parse_worktrees_and_numstat() {
+        let worktrees = parse_worktrees(
+            "worktree /repo\nHEAD 1111\nbranch refs/heads/main\n\n\
+             worktree /repo-wt\nHEAD 2222\ndetached\nlocked\n\n",
+        );
+        assert_eq!(worktrees.len(), 2);
+        assert_eq!(worktrees[0]["branch"], "main");
+        assert_eq!(worktrees[1]["detached"], true);
+        assert_eq!(worktrees[1]["locked"], true);
+
+        let files = parse_numstat("3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n");
+        assert_eq!(files[0]["additions"], 3);
+        assert_eq!(files[1]["binary"], true);
+    }
+
+    #[tokio::test]
+    async fn test_git_tools_reject_option_injection() {
+        let input = ToolInput {
+            command: "git_log".to_string(),
+            args: HashMap::from([("range".to_string(), "--output=/tmp/x".to_string())]),
+            context: None,
+            dry_run: true,
+            timeout_ms: None,
+        };
+        assert!(GitLog::new().execute(input).await.is_err());
+    }
+
+    #[test]
+    fn test_destructive_git_tools_have_high_risk() {
+        let risk = |spec: ToolSpec| {
+            assert!(
+                spec.supports_dry_run,
+                "{} should support dry-run",
+                spec.name
+            );
+            spec.usage_guide.map(|g| g.risk_score).unwrap_or_default()
+        };
+
+        assert!(risk(GitLog::new().spec()) <= 1);
+        assert!(risk(GitBlame::new().spec()) <= 1);
+        assert_eq!(risk(GitReset::new().spec()), 5);
+        assert_eq!(risk(GitCheckout::new().spec()), 5);
+        assert!(risk(GitStash::new().spec()) >= 4);
+        for spec in [
+            GitBranch::new().spec(),
+            GitApply::new().spec(),
+            GitWorktree::new().spec(),
+        ] {
+            assert!(risk(spec) <= 5, "risk scores stay on the 0..=5 scale");
+        }
+    }
 }
@@ -0,0 +1,969 @@
+// Tool input schemas (JSON Schema draft 2020-12)
+//
+// `ToolSpec.input_schema` carries a JSON Schema document describing the
+// arguments a tool accepts. `ToolInput.args` is a flat string map (CLI
+// `--arg k=v`, natural language parsing, LLM function calls), so validation
+// works in two phases: every string is first coerced into the JSON type the
+// schema declares, then the typed value is checked against the schema.
+//
+// Only the subset of the specification that is meaningful for tool arguments
+// is supported: type, enum, const, properties, required,
+// additionalProperties, items, min/max(Length|Items), minimum/maximum,
+// exclusiveMinimum/exclusiveMaximum, multipleOf, pattern, uniqueItems,
+// default and description. Unknown keywords are preserved and ignored.
+
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// `$schema` URI emitted for every tool input schema
+pub const JSON_SCHEMA_DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+const KNOWN_TYPES: [&str; 7] = [
+    "string", "integer", "number", "boolean", "array", "object", "null",
+];
+
+/// Error in the schema document itself (not in the arguments)
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum SchemaError {
+    #[error("input schema is not valid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("input schema must describe an object (got {0})")]
+    NotAnObject(String),
+
+    #[error("invalid schema at '{path}': {message}")]
+    Invalid { path: String, message: String },
+}
+
+/// Single argument validation failure with the location of the offending value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgumentError {
+    /// Location of the value, e.g. `paths[2]` or `options.mode`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "'{}': {}", self.path, self.message)
+        }
+    }
+}
+
+/// All validation failures collected for one tool input
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgumentErrors {
+    pub errors: Vec<ArgumentError>,
+}
+
+impl std::error::Error for ArgumentErrors {}
+
+impl fmt::Display for ArgumentErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// Description of a single top-level argument, derived from the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgumentInfo {
+    pub name: String,
+    pub types: Vec<String>,
+    pub description: Option<String>,
+    pub required: bool,
+    pub default: Option<Value>,
+}
+
+/// Parsed and checked tool input schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputSchema {
+    schema: Value,
+}
+
+impl Default for InputSchema {
+    /// Schema of a tool that takes no declared arguments but tolerates extra ones
+    fn default() -> Self {
+        Self {
+            schema: serde_json::json!({
+                "$schema": JSON_SCHEMA_DRAFT_2020_12,
+                "type": "object",
+                "properties": {},
+            }),
+        }
+    }
+}
+
+impl InputSchema {
+    /// Parse a schema string. Empty strings and `{}` yield the permissive default.
+    ///
+    /// Legacy informal schemas such as `{"path": "string", "cwd": "string?"}`
+    /// are converted into the equivalent JSON Schema.
+    pub fn parse(raw: &str) -> Result<Self, SchemaError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return Ok(Self::default());
+        }
+        let value: Value =
+            serde_json::from_str(trimmed).map_err(|e| SchemaError::InvalidJson(e.to_string()))?;
+        Self::from_value(value)
+    }
+
+    /// Build from an already parsed JSON value (manifest field, MCP `inputSchema`)
+    pub fn from_value(value: Value) -> Result<Self, SchemaError> {
+        let obj = match value {
+            Value::Object(obj) => obj,
+            Value::Null => return Ok(Self::default()),
+            other => return Err(SchemaError::NotAnObject(json_type_name(&other).into())),
+        };
+        let mut schema = if is_json_schema(&obj) {
+            Value::Object(obj)
+        } else {
+            from_legacy(&obj)
+        };
+        if let Some(root) = schema.as_object_mut() {
+            root.entry("$schema")
+                .or_insert_with(|| Value::String(JSON_SCHEMA_DRAFT_2020_12.into()));
+            root.entry("type")
+                .or_insert_with(|| Value::String("object".into()));
+            root.entry("properties")
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        match schema.get("type") {
+            Some(Value::String(t)) if t == "object" => {}
+            Some(other) => return Err(SchemaError::NotAnObject(other.to_string())),
+            None => {}
+        }
+        check_schema(&schema, "")?;
+        Ok(Self { schema })
+    }
+
+    /// The full JSON Schema document
+    pub fn as_value(&self) -> &Value {
+        &self.schema
+    }
+
+    pub fn into_value(self) -> Value {
+        self.schema
+    }
+
+    /// Schema without `$schema`, suitable for LLM function calling payloads
+    pub fn parameters(&self) -> Value {
+        let mut v = self.schema.clone();
+        if let Some(obj) = v.as_object_mut() {
+            obj.remove("$schema");
+        }
+        v
+    }
+
+    /// Top-level argument names
+    pub fn property_names(&self) -> Vec<String> {
+        self.schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Names listed in `required`
+    pub fn required(&self) -> Vec<String> {
+        self.schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| {
+                r.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Per-argument summary used for usage guides and help output
+    pub fn arguments(&self) -> Vec<ArgumentInfo> {
+        let required = self.required();
+        let Some(props) = self.schema.get("properties").and_then(|p| p.as_object()) else {
+            return Vec::new();
+        };
+        props
+            .iter()
+            .map(|(name, prop)| ArgumentInfo {
+                name: name.clone(),
+                types: schema_types(prop),
+                description: prop
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .map(str::to_string),
+                required: required.contains(name),
+                default: prop.get("default").cloned(),
+            })
+            .collect()
+    }
+
+    /// Coerce string arguments into the declared types and validate them.
+    ///
+    /// Missing optional arguments with a `default` are filled in. Returns the
+    /// typed argument object on success, or every failure found.
+    pub fn validate_args(
+        &self,
+        args: &HashMap<String, String>,
+    ) -> Result<Map<String, Value>, ArgumentErrors> {
+        let mut errors = Vec::new();
+        let mut typed = Map::new();
+        let props = self.schema.get("properties").and_then(|p| p.as_object());
+
+        let mut names: Vec<&String> = args.keys().collect();
+        names.sort();
+        for name in names {
+            let raw = &args[name];
+            match props.and_then(|p| p.get(name)) {
+                Some(prop) => match coerce_str(raw, prop, name) {
+                    Ok(v) => {
+                        typed.insert(name.clone(), v);
+                    }
+                    Err(e) => errors.push(e),
+                },
+                None => {
+                    // Unknown arguments are checked by validate_value below
+                    typed.insert(name.clone(), Value::String(raw.clone()));
+                }
+            }
+        }
+
+        if let Some(props) = props {
+            for (name, prop) in props {
+                if !typed.contains_key(name) {
+                    if let Some(default) = prop.get("default") {
+                        typed.insert(name.clone(), default.clone());
+                    }
+                }
+            }
+        }
+
+        // Arguments that failed coercion are absent from `typed`; their
+        // "missing required" follow-up errors would only be noise.
+        let failed: Vec<String> = errors.iter().map(|e| e.path.clone()).collect();
+        let value = Value::Object(typed);
+        let mut schema_errors = Vec::new();
+        validate_value(&value, &self.schema, "", &mut schema_errors);
+        errors.extend(
+            schema_errors
+                .into_iter()
+                .filter(|e| !failed.contains(&e.path)),
+        );
+        match value {
+            Value::Object(typed) if errors.is_empty() => Ok(typed),
+            _ => Err(ArgumentErrors { errors }),
+        }
+    }
+
+    /// Validate a typed argument object (LLM function call `arguments`)
+    pub fn validate_json(&self, args: &Value) -> Result<(), ArgumentErrors> {
+        let mut errors = Vec::new();
+        validate_value(args, &self.schema, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ArgumentErrors { errors })
+        }
+    }
+
+    /// Normalize string arguments in place: values are replaced with their
+    /// canonical string form (`"YES"` -> `"true"`, `"[\"a\", \"b\"]"` -> `"a,b"`)
+    /// and defaults are added. Tools keep parsing plain strings.
+    pub fn coerce_args(&self, args: &mut HashMap<String, String>) -> Result<(), ArgumentErrors> {
+        let typed = self.validate_args(args)?;
+        args.clear();
+        args.extend(typed.into_iter().map(|(k, v)| (k, value_to_arg(&v))));
+        Ok(())
+    }
+
+    /// Convert typed function call arguments into `ToolInput.args` strings
+    pub fn args_from_json(&self, args: &Value) -> Result<HashMap<String, String>, ArgumentErrors> {
+        let mut obj = match args {
+            Value::Object(obj) => obj.clone(),
+            Value::Null => Map::new(),
+            other => {
+                return Err(ArgumentErrors {
+                    errors: vec![ArgumentError {
+                        path: String::new(),
+                        message: format!(
+                            "arguments must be an object, got {}",
+                            json_type_name(other)
+                        ),
+                    }],
+                })
+            }
+        };
+        if let Some(props) = self.schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, prop) in props {
+                if !obj.contains_key(name) {
+                    if let Some(default) = prop.get("default") {
+                        obj.insert(name.clone(), default.clone());
+                    }
+                }
+            }
+        }
+        let value = Value::Object(obj);
+        self.validate_json(&value)?;
+        let Value::Object(obj) = value else {
+            unreachable!("validated value is an object")
+        };
+        Ok(obj
+            .into_iter()
+            .map(|(k, v)| (k, value_to_arg(&v)))
+            .collect())
+    }
+}
+
+/// String form of a typed value as stored in `ToolInput.args`.
+///
+/// Arrays of scalars become the comma-separated lists tools split on; an
+/// array that cannot round-trip that way (nested values, items containing a
+/// comma) stays JSON text.
+pub fn value_to_arg(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) => {
+            let scalars: Option<Vec<String>> = items
+                .iter()
+                .map(|item| match item {
+                    Value::Array(_) | Value::Object(_) | Value::Null => None,
+                    scalar => Some(value_to_arg(scalar)).filter(|s| !s.contains(',')),
+                })
+                .collect();
+            scalars.map_or_else(|| value.to_string(), |items| items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+fn is_json_schema(obj: &Map<String, Value>) -> bool {
+    obj.is_empty()
+        || obj.contains_key("$schema")
+        || obj.contains_key("properties")
+        || matches!(obj.get("type"), Some(Value::String(t)) if t == "object")
+}
+
+/// Convert `{"name": "type?"}` style descriptions into JSON Schema.
+/// A trailing `?` marks the argument optional; text after the type word
+/// becomes the description.
+fn from_legacy(obj: &Map<String, Value>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (name, spec) in obj {
+        let (prop, optional) = match spec {
+            Value::String(s) => legacy_property(s),
+            Value::Object(o) if o.contains_key("type") => {
+                let mut o = o.clone();
+                let optional = !matches!(o.remove("required"), Some(Value::Bool(true)));
+                (Value::Object(o), optional)
+            }
+            _ => (Value::Object(Map::new()), false),
+        };
+        if !optional {
+            required.push(Value::String(name.clone()));
+        }
+        properties.insert(name.clone(), prop);
+    }
+    serde_json::json!({
+        "$schema": JSON_SCHEMA_DRAFT_2020_12,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn legacy_property(spec: &str) -> (Value, bool) {
+    let spec = spec.trim();
+    let (head, rest) = match spec.find(char::is_whitespace) {
+        Some(idx) => (&spec[..idx], spec[idx..].trim()),
+        None => (spec, ""),
+    };
+    let optional = head.ends_with('?');
+    let ty = head.trim_end_matches('?');
+    let mut prop = Map::new();
+    if KNOWN_TYPES.contains(&ty) {
+        prop.insert("type".into(), Value::String(ty.into()));
+    }
+    let description = rest.trim_start_matches('(').trim_end_matches(')').trim();
+    if !description.is_empty() {
+        prop.insert("description".into(), Value::String(description.into()));
+    }
+    (Value::Object(prop), optional)
+}
+
+/// Structural check of a schema node so that broken schemas fail at
+/// registration rather than at the first call
+fn check_schema(schema: &Value, path: &str) -> Result<(), SchemaError> {
+    let invalid = |message: String| SchemaError::Invalid {
+        path: if path.is_empty() {
+            "#".into()
+        } else {
+            path.into()
+        },
+        message,
+    };
+    let Some(obj) = schema.as_object() else {
+        return match schema {
+            Value::Bool(_) => Ok(()),
+            _ => Err(invalid("schema must be an object or boolean".into())),
+        };
+    };
+
+    if let Some(t) = obj.get("type") {
+        let names: Vec<&Value> = match t {
+            Value::Array(a) => a.iter().collect(),
+            other => vec![other],
+        };
+        for n in names {
+            match n.as_str() {
+                Some(s) if KNOWN_TYPES.contains(&s) => {}
+                _ => return Err(invalid(format!("unknown type {n}"))),
+            }
+        }
+    }
+    if let Some(e) = obj.get("enum") {
+        if !e.is_array() {
+            return Err(invalid("'enum' must be an array".into()));
+        }
+    }
+    if let Some(p) = obj.get("pattern") {
+        let p = p
+            .as_str()
+            .ok_or_else(|| invalid("'pattern' must be a string".into()))?;
+        Regex::new(p).map_err(|e| invalid(format!("invalid pattern: {e}")))?;
+    }
+    for key in [
+        "minimum",
+        "maximum",
+        "exclusiveMinimum",
+        "exclusiveMaximum",
+        "multipleOf",
+    ] {
+        if let Some(v) = obj.get(key) {
+            if !v.is_number() {
+                return Err(invalid(format!("'{key}' must be a number")));
+            }
+        }
+    }
+    for key in ["minLength", "maxLength", "minItems", "maxItems"] {
+        if let Some(v) = obj.get(key) {
+            if v.as_u64().is_none() {
+                return Err(invalid(format!("'{key}' must be a non-negative integer")));
+            }
+        }
+    }
+    if let Some(props) = obj.get("properties") {
+        let props = props
+            .as_object()
+            .ok_or_else(|| invalid("'properties' must be an object".into()))?;
+        for (name, prop) in props {
+            check_schema(prop, &join_path(path, name))?;
+        }
+    }
+    if let Some(req) = obj.get("required") {
+        let req = req
+            .as_array()
+            .ok_or_else(|| invalid("'required' must be an array".into()))?;
+        if req.iter().any(|r| !r.is_string()) {
+            return Err(invalid("'required' must contain strings".into()));
+        }
+    }
+    if let Some(ap) = obj.get("additionalProperties") {
+        check_schema(ap, &join_path(path, "additionalProperties"))?;
+    }
+    if let Some(items) = obj.get("items") {
+        check_schema(items, &format!("{path}[]"))?;
+    }
+    Ok(())
+}
+
+fn schema_types(schema: &Value) -> Vec<String> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.clone()],
+        Some(Value::Array(a)) => a
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn join_path(base: &str, key: &str) -> String {
+    if base.is_empty() {
+        key.to_string()
+    } else {
+        format!("{base}.{key}")
+    }
+}
+
+fn arg_error(path: &str, message: impl Into<String>) -> ArgumentError {
+    ArgumentError {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Turn a raw string into the JSON value the schema asks for.
+/// With several allowed types the most specific successful parse wins,
+/// `string` being the last resort.
+fn coerce_str(raw: &str, schema: &Value, path: &str) -> Result<Value, ArgumentError> {
+    let types = schema_types(schema);
+    if types.is_empty() {
+        return Ok(Value::String(raw.to_string()));
+    }
+    const ORDER: [&str; 7] = [
+        "null", "boolean", "integer", "number", "array", "object", "string",
+    ];
+    let mut last_err = None;
+    for ty in ORDER.iter().filter(|t| types.iter().any(|x| x == *t)) {
+        match coerce_as(raw, ty, schema, path) {
+            Ok(v) => return Ok(v),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| arg_error(path, "cannot coerce value")))
+}
+
+fn coerce_as(raw: &str, ty: &str, schema: &Value, path: &str) -> Result<Value, ArgumentError> {
+    let trimmed = raw.trim();
+    match ty {
+        "string" => Ok(Value::String(raw.to_string())),
+        "null" => match trimmed {
+            "" | "null" => Ok(Value::Null),
+            _ => Err(arg_error(path, format!("expected null, got \"{raw}\""))),
+        },
+        "boolean" => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" | "1" => Ok(Value::Bool(true)),
+            "false" | "no" | "n" | "off" | "0" => Ok(Value::Bool(false)),
+            _ => Err(arg_error(path, format!("expected boolean, got \"{raw}\""))),
+        },
+        "integer" => {
+            if let Ok(i) = trimmed.parse::<i64>() {
+                return Ok(Value::from(i));
+            }
+            match trimmed.parse::<f64>() {
+                Ok(f) if f.is_finite() && f.fract() == 0.0 && f.abs() < 9.0e15 => {
+                    Ok(Value::from(f as i64))
+                }
+                _ => Err(arg_error(path, format!("expected integer, got \"{raw}\""))),
+            }
+        }
+        "number" => match trimmed.parse::<f64>() {
+            Ok(f) if f.is_finite() => Ok(serde_json::Number::from_f64(f)
+                .map(Value::Number)
+                .unwrap_or(Value::Null)),
+            _ => Err(arg_error(path, format!("expected number, got \"{raw}\""))),
+        },
+        "array" => {
+            if trimmed.starts_with('[') {
+                return serde_json::from_str::<Value>(trimmed)
+                    .ok()
+                    .filter(Value::is_array)
+                    .ok_or_else(|| arg_error(path, format!("invalid JSON array \"{raw}\"")));
+            }
+            let item_schema = schema.get("items").cloned().unwrap_or(Value::Bool(true));
+            let mut items = Vec::new();
+            if !trimmed.is_empty() {
+                for (i, part) in trimmed.split(',').enumerate() {
+                    items.push(coerce_str(
+                        part.trim(),
+                        &item_schema,
+                        &format!("{path}[{i}]"),
+                    )?);
+                }
+            }
+            Ok(Value::Array(items))
+        }
+        "object" => serde_json::from_str::<Value>(trimmed)
+            .ok()
+            .filter(Value::is_object)
+            .ok_or_else(|| arg_error(path, format!("expected JSON object, got \"{raw}\""))),
+        _ => Err(arg_error(path, format!("unsupported type '{ty}'"))),
+    }
+}
+
+fn matches_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(n) => {
+                n.is_i64() || n.is_u64() || n.as_f64().map(|f| f.fract() == 0.0).unwrap_or(false)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn validate_value(value: &Value, schema: &Value, path: &str, errors: &mut Vec<ArgumentError>) {
+    let obj = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(arg_error(path, "no value is allowed here"));
+            return;
+        }
+        Value::Object(obj) => obj,
+        _ => return,
+    };
+
+    let types = schema_types(schema);
+    if !types.is_empty() && !types.iter().any(|t| matches_type(value, t)) {
+        errors.push(arg_error(
+            path,
+            format!(
+                "expected {}, got {}",
+                types.join(" or "),
+                json_type_name(value)
+            ),
+        ));
+        return;
+    }
+
+    if let Some(Value::Array(allowed)) = obj.get("enum") {
+        if !allowed.contains(value) {
+            let list: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            errors.push(arg_error(
+                path,
+                format!("{value} is not one of [{}]", list.join(", ")),
+            ));
+        }
+    }
+    if let Some(expected) = obj.get("const") {
+        if expected != value {
+            errors.push(arg_error(path, format!("must be {expected}")));
+        }
+    }
+
+    match value {
+        Value::String(s) => validate_string(s, obj, path, errors),
+        Value::Number(n) => {
+            if let Some(f) = n.as_f64() {
+                validate_number(f, obj, path, errors)
+            }
+        }
+        Value::Array(items) => validate_array(items, obj, path, errors),
+        Value::Object(map) => validate_object(map, obj, path, errors),
+        _ => {}
+    }
+}
+
+fn validate_string(
+    s: &str,
+    schema: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<ArgumentError>,
+) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+        if len < min {
+            errors.push(arg_error(
+                path,
+                format!("must be at least {min} characters long"),
+            ));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()) {
+        if len > max {
+            errors.push(arg_error(
+                path,
+                format!("must be at most {max} characters long"),
+            ));
+        }
+    }
+    if let Some(pattern) = schema.get("pattern").and_then(|v| v.as_str()) {
+        if let Ok(re) = Regex::new(pattern) {
+            if !re.is_match(s) {
+                errors.push(arg_error(
+                    path,
+                    format!("\"{s}\" does not match pattern /{pattern}/"),
+                ));
+            }
+        }
+    }
+}
+
+fn validate_number(
+    n: f64,
+    schema: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<ArgumentError>,
+) {
+    let bound = |key: &str| schema.get(key).and_then(|v| v.as_f64());
+    if let Some(min) = bound("minimum") {
+        if n < min {
+            errors.push(arg_error(path, format!("{n} is less than minimum {min}")));
+        }
+    }
+    if let Some(max) = bound("maximum") {
+        if n > max {
+            errors.push(arg_error(path, format!("{n} exceeds maximum {max}")));
+        }
+    }
+    if let Some(min) = bound("exclusiveMinimum") {
+        if n <= min {
+            errors.push(arg_error(path, format!("{n} must be greater than {min}")));
+        }
+    }
+    if let Some(max) = bound("exclusiveMaximum") {
+        if n >= max {
+            errors.push(arg_error(path, format!("{n} must be less than {max}")));
+        }
+    }
+    if let Some(step) = bound("multipleOf") {
+        if step > 0.0 && ((n / step) - (n / step).round()).abs() > 1e-9 {
+            errors.push(arg_error(path, format!("{n} is not a multiple of {step}")));
+        }
+    }
+}
+
+fn validate_array(
+    items: &[Value],
+    schema: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<ArgumentError>,
+) {
+    let len = items.len() as u64;
+    if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+        if len < min {
+            errors.push(arg_error(
+                path,
+                format!("must contain at least {min} items"),
+            ));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()) {
+        if len > max {
+            errors.push(arg_error(path, format!("must contain at most {max} items")));
+        }
+    }
+    if schema.get("uniqueItems").and_then(|v| v.as_bool()) == Some(true) {
+        for (i, item) in items.iter().enumerate() {
+            if items[..i].contains(item) {
+                errors.push(arg_error(
+                    &format!("{path}[{i}]"),
+                    format!("duplicate item {item}"),
+                ));
+            }
+        }
+    }
+    if let Some(item_schema) = schema.get("items") {
+        for (i, item) in items.iter().enumerate() {
+            validate_value(item, item_schema, &format!("{path}[{i}]"), errors);
+        }
+    }
+}
+
+fn validate_object(
+    map: &Map<String, Value>,
+    schema: &Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<ArgumentError>,
+) {
+    let props = schema.get("properties").and_then(|p| p.as_object());
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(|v| v.as_str()) {
+            if !map.contains_key(name) {
+                errors.push(arg_error(
+                    &join_path(path, name),
+                    "missing required argument",
+                ));
+            }
+        }
+    }
+    for (name, value) in map {
+        let child = join_path(path, name);
+        match props.and_then(|p| p.get(name)) {
+            Some(prop) => validate_value(value, prop, &child, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    let allowed: Vec<&str> = props
+                        .map(|p| p.keys().map(String::as_str).collect())
+                        .unwrap_or_default();
+                    errors.push(arg_error(
+                        &child,
+                        format!("unknown argument (allowed: {})", allowed.join(", ")),
+                    ));
+                }
+                Some(extra) => validate_value(value, extra, &child, errors),
+                None => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn shell_schema() -> InputSchema {
+        InputSchema::from_value(json!({
+            "type": "object",
+            "properties": {
+                "command": {"type": "string", "minLength": 1},
+                "max_output_kb": {"type": "integer", "minimum": 1, "maximum": 4096, "default": 256},
+                "verbose": {"type": "boolean"},
+                "paths": {"type": "array", "items": {"type": "string"}},
+                "mode": {"type": "string", "enum": ["fast", "safe"]}
+            },
+            "required": ["command"],
+            "additionalProperties": false
+        }))
+        .expect("valid schema")
+    }
+
+    #[test]
+    fn test_coerces_strings_into_declared_types() {
+        let schema = shell_schema();
+        let typed = schema
+            .validate_args(&args(&[
+                ("command", "ls"),
+                ("max_output_kb", "64"),
+                ("verbose", "YES"),
+                ("paths", "src, tests"),
+            ]))
+            .expect("valid args");
+        assert_eq!(typed["max_output_kb"], json!(64));
+        assert_eq!(typed["verbose"], json!(true));
+        assert_eq!(typed["paths"], json!(["src", "tests"]));
+    }
+
+    #[test]
+    fn test_fills_defaults_and_rewrites_canonical_strings() {
+        let schema = shell_schema();
+        let mut a = args(&[("command", "ls"), ("verbose", "off")]);
+        schema.coerce_args(&mut a).expect("valid args");
+        assert_eq!(a["verbose"], "false");
+        assert_eq!(a["max_output_kb"], "256");
+    }
+
+    #[test]
+    fn test_array_arguments_keep_comma_form() {
+        let schema = shell_schema();
+        let mut a = args(&[("command", "ls"), ("paths", r#"["src", "tests"]"#)]);
+        schema.coerce_args(&mut a).expect("valid args");
+        assert_eq!(a["paths"], "src,tests");
+
+        let mut a = args(&[("command", "ls"), ("paths", "src, tests")]);
+        schema.coerce_args(&mut a).expect("valid args");
+        assert_eq!(a["paths"], "src,tests");
+
+        assert_eq!(value_to_arg(&json!(["a,b", "c"])), r#"["a,b","c"]"#);
+    }
+
+    #[test]
+    fn test_reports_precise_errors() {
+        let schema = shell_schema();
+        let err = schema
+            .validate_args(&args(&[("max_output_kb", "lots"), ("colour", "red")]))
+            .expect_err("invalid args");
+        let text = err.to_string();
+        assert!(text.contains("'max_output_kb': expected integer"), "{text}");
+
+        let err = schema
+            .validate_args(&args(&[("max_output_kb", "9000"), ("colour", "red")]))
+            .expect_err("invalid args");
+        let paths: Vec<&str> = err.errors.iter().map(|e| e.path.as_str()).collect();
+        assert!(paths.contains(&"command"));
+        assert!(paths.contains(&"max_output_kb"));
+        assert!(paths.contains(&"colour"));
+    }
+
+    #[test]
+    fn test_enum_and_array_items() {
+        let schema = shell_schema();
+        assert!(schema
+            .validate_args(&args(&[("command", "ls"), ("mode", "reckless")]))
+            .is_err());
+        let schema = InputSchema::from_value(json!({
+            "type": "object",
+            "properties": {"ids": {"type": "array", "items": {"type": "integer"}}}
+        }))
+        .expect("valid schema");
+        let err = schema
+            .validate_args(&args(&[("ids", "1,x,3")]))
+            .expect_err("bad item");
+        assert_eq!(err.errors[0].path, "ids[1]");
+        let typed = schema
+            .validate_args(&args(&[("ids", "[4, 5]")]))
+            .expect("json array");
+        assert_eq!(typed["ids"], json!([4, 5]));
+    }
+
+    #[test]
+    fn test_legacy_schema_conversion() {
+        let schema =
+            InputSchema::parse(r#"{"pattern": "string", "path": "string? (search root)"}"#)
+                .expect("legacy schema");
+        assert_eq!(schema.required(), vec!["pattern".to_string()]);
+        let path = schema
+            .arguments()
+            .into_iter()
+            .find(|a| a.name == "path")
+            .expect("path argument");
+        assert!(!path.required);
+        assert_eq!(path.description.as_deref(), Some("search root"));
+    }
+
+    #[test]
+    fn test_rejects_broken_schemas() {
+        assert!(matches!(
+            InputSchema::parse("[]"),
+            Err(SchemaError::NotAnObject(_))
+        ));
+        assert!(InputSchema::parse("not json").is_err());
+        assert!(InputSchema::from_value(json!({
+            "type": "object",
+            "properties": {"x": {"type": "text"}}
+        }))
+        .is_err());
+        assert!(InputSchema::from_value(json!({
+            "type": "object",
+            "properties": {"x": {"type": "string", "pattern": "("}}
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_args_from_function_call_json() {
+        let schema = shell_schema();
+        let a = schema
+            .args_from_json(&json!({"command": "ls", "paths": ["a", "b"], "verbose": true}))
+            .expect("valid call");
+        assert_eq!(a["paths"], "a,b");
+        assert_eq!(a["verbose"], "true");
+        assert_eq!(a["max_output_kb"], "256");
+        assert!(schema.args_from_json(&json!({"command": 5})).is_err());
+    }
+}
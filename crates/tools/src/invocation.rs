@@ -0,0 +1,391 @@
+//! Policy-gated tool invocation.
+//!
+//! One pipeline for every caller that runs tools on behalf of a user
+//! (`magray tools run`, the orchestrator executor): JSON Schema validation →
+//! policy argument enrichment → sandbox permission precheck → policy decision →
+//! approval (with dry-run preview) → execution → `tool.invoked` telemetry.
+
+use crate::{Tool, ToolInput, ToolOutput, ToolSpec};
+use anyhow::{anyhow, bail, Result};
+use common::approval::{default_prompter, ApprovalBroker, ApprovalDecision, ApprovalRequest};
+use common::policy::{
+    load_effective_policy_for_project, precheck_permissions, project_policy_path, PolicyAction,
+    PolicyDecision, PolicyEngine, PolicySubjectKind, ProductionEventPublisher, RiskLevel,
+    SimpleToolPermissions,
+};
+use common::sandbox_config::SandboxConfig;
+use common::{events, topics};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Broker honoring `MAGRAY_*` approval env, prompting through the current
+/// frontend and writing "always for this project" grants to the project policy
+pub fn project_broker(project_root: &Path) -> ApprovalBroker {
+    ApprovalBroker::from_env(default_prompter())
+        .with_project_policy(project_policy_path(project_root))
+}
+
+pub struct ToolGate {
+    policy: PolicyEngine,
+    broker: Arc<ApprovalBroker>,
+    sandbox: SandboxConfig,
+    project_root: Option<PathBuf>,
+    workdir: Option<PathBuf>,
+}
+
+impl ToolGate {
+    pub fn new(policy: PolicyEngine, broker: Arc<ApprovalBroker>) -> Self {
+        Self {
+            policy,
+            broker,
+            sandbox: SandboxConfig::from_env(),
+            project_root: None,
+            workdir: None,
+        }
+    }
+
+    /// Effective policy (global file + project policy + env) with production
+    /// event publishing and a broker honoring `MAGRAY_*` approval env that
+    /// prompts through the current frontend (stdin or the TUI dialog)
+    pub fn for_project(home_policy: Option<&Path>, project_root: &Path) -> Self {
+        let effective = load_effective_policy_for_project(home_policy, Some(project_root));
+        let policy = PolicyEngine::from_document(effective)
+            .with_event_publisher(Arc::new(ProductionEventPublisher::new()));
+        let mut gate = Self::new(policy, Arc::new(project_broker(project_root)));
+        gate.project_root = Some(project_root.to_path_buf());
+        gate
+    }
+
+    pub fn with_sandbox(mut self, sandbox: SandboxConfig) -> Self {
+        self.sandbox = sandbox;
+        self
+    }
+
+    /// Directory tools run in instead of the process working directory:
+    /// it becomes the `cwd` of tools that take one, and relative `path`
+    /// arguments of the others resolve against it
+    pub fn with_workdir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.workdir = Some(dir.into());
+        self
+    }
+
+    pub fn sandbox(&self) -> &SandboxConfig {
+        &self.sandbox
+    }
+
+    pub fn workdir(&self) -> Option<&Path> {
+        self.workdir.as_deref()
+    }
+
+    pub fn broker(&self) -> &ApprovalBroker {
+        &self.broker
+    }
+
+    pub fn project_root(&self) -> Option<&Path> {
+        self.project_root.as_deref()
+    }
+
+    /// Derived arguments policies can match on (`domain` for web_fetch,
+    /// `keyword` for web_search). They are passed on to the tool as well.
+    pub fn enrich_policy_args(name: &str, args: &mut HashMap<String, String>) {
+        if name == "web_fetch" {
+            if let Some(url) = args.get("url").cloned() {
+                let domain = url
+                    .split('/')
+                    .nth(2)
+                    .unwrap_or("")
+                    .split(':')
+                    .next()
+                    .unwrap_or("")
+                    .to_string();
+                if !domain.is_empty() {
+                    args.insert("domain".into(), domain);
+                }
+            }
+        } else if name == "web_search" {
+            if let Some(q) = args.get("query").cloned() {
+                let lowered = q.to_lowercase();
+                if lowered.contains("internal") {
+                    args.insert("keyword".into(), "internal".into());
+                }
+                if lowered.contains("secret") {
+                    args.insert("keyword".into(), "secret".into());
+                }
+            }
+        }
+    }
+
+    /// Validate against the input schema, add policy-derived arguments and
+    /// resolve paths against the gate's working directory
+    pub fn prepare(&self, spec: &ToolSpec, input: &mut ToolInput) -> Result<()> {
+        spec.validate_input(input)?;
+        Self::enrich_policy_args(&spec.name, &mut input.args);
+        if let Some(dir) = &self.workdir {
+            let properties = spec.parsed_input_schema()?.property_names();
+            let key = Self::location_arg(&properties);
+            let location = match input.args.get(key) {
+                Some(value) => dir.join(value),
+                None if properties.iter().any(|p| p == key) => dir.clone(),
+                None => return Ok(()),
+            };
+            input
+                .args
+                .insert(key.to_string(), location.to_string_lossy().into_owned());
+        }
+        Ok(())
+    }
+
+    /// Argument holding the directory or file a tool works on: `cwd` for
+    /// tools that take one (their `path` is relative to it), else `path`
+    fn location_arg(properties: &[String]) -> &'static str {
+        if properties.iter().any(|p| p == "cwd") {
+            "cwd"
+        } else {
+            "path"
+        }
+    }
+
+    /// Filesystem sandbox check of the tool's location argument: tools with
+    /// side effects need a write root, the rest a read root
+    fn check_location(&self, spec: &ToolSpec, input: &ToolInput) -> Result<()> {
+        let properties = spec
+            .parsed_input_schema()
+            .map(|schema| schema.property_names())
+            .unwrap_or_default();
+        let Some(location) = input.args.get(Self::location_arg(&properties)) else {
+            return Ok(());
+        };
+        let writes = spec.supports_dry_run
+            || spec
+                .usage_guide
+                .as_ref()
+                .is_some_and(|g| !g.side_effects.is_empty());
+        if writes {
+            self.sandbox.validate_write_access(location)
+        } else {
+            self.sandbox.validate_read_access(location)
+        }
+        .map_err(|e| anyhow!("Инструмент '{}' заблокирован песочницей: {}", spec.name, e))
+    }
+
+    /// Sandbox precheck, policy and approval for a prepared input.
+    /// `spec` may carry usage-guide overrides; an `Ask` without a matching
+    /// rule is also raised for risky or side-effecting tools.
+    /// Returns the user's decision when one was requested.
+    pub async fn authorize(
+        &self,
+        tool: &dyn Tool,
+        spec: &ToolSpec,
+        input: &ToolInput,
+    ) -> Result<Option<ApprovalDecision>> {
+        let name = spec.name.as_str();
+        self.check_location(spec, input)?;
+        let mut precheck_ask: Option<String> = None;
+        if let Some(perms) = &spec.permissions {
+            let simple = SimpleToolPermissions {
+                fs_read_roots: perms.fs_read_roots.clone(),
+                fs_write_roots: perms.fs_write_roots.clone(),
+                net_allowlist: perms.net_allowlist.clone(),
+                allow_shell: perms.allow_shell,
+            };
+            if let Some(pre) = precheck_permissions(name, &simple, &self.sandbox) {
+                match pre.action {
+                    PolicyAction::Deny => {
+                        bail!("Инструмент '{}' заблокирован политикой (precheck)", name);
+                    }
+                    PolicyAction::Ask => {
+                        precheck_ask = Some(
+                            pre.matched_rule
+                                .and_then(|r| r.reason)
+                                .unwrap_or_else(|| "precheck".into()),
+                        );
+                    }
+                    PolicyAction::Allow => {}
+                }
+            }
+        }
+
+        let decision = self.policy.evaluate_tool(name, &input.args);
+        let require_ask_due_to_guide = matches!(decision.action, PolicyAction::Allow)
+            && decision.matched_rule.is_none()
+            && spec
+                .usage_guide
+                .as_ref()
+                .map(|g| g.risk_score >= 4 || !g.side_effects.is_empty())
+                .unwrap_or(false);
+        let ask_reason = precheck_ask.or_else(|| {
+            require_ask_due_to_guide.then(|| "UsageGuide: risk/side_effects".to_string())
+        });
+        let risk_score = spec.usage_guide.as_ref().map(|g| g.risk_score);
+
+        self.enforce(
+            PolicySubjectKind::Tool,
+            name,
+            &input.args,
+            decision,
+            ask_reason,
+            risk_score,
+            || async {
+                let preview_input = ToolInput {
+                    dry_run: true,
+                    ..input.clone()
+                };
+                let preview = tool
+                    .execute(preview_input)
+                    .await
+                    .unwrap_or_else(|e| ToolOutput {
+                        success: false,
+                        result: format!("preview error: {e}"),
+                        formatted_output: None,
+                        metadata: HashMap::new(),
+                    });
+                Some(preview.formatted_output.unwrap_or(preview.result))
+            },
+            Some(input.command.clone()),
+        )
+        .await
+    }
+
+    /// Policy and approval for a non-tool command (`memory.store`, ...)
+    pub async fn authorize_command(
+        &self,
+        name: &str,
+        args: &HashMap<String, String>,
+        preview: Option<String>,
+    ) -> Result<Option<ApprovalDecision>> {
+        let decision = self.policy.evaluate_command(name, args);
+        self.enforce(
+            PolicySubjectKind::Command,
+            name,
+            args,
+            decision,
+            None,
+            None,
+            || async { preview },
+            None,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn enforce<F, Fut>(
+        &self,
+        kind: PolicySubjectKind,
+        name: &str,
+        args: &HashMap<String, String>,
+        decision: PolicyDecision,
+        ask_reason: Option<String>,
+        risk_score: Option<u8>,
+        preview: F,
+        command: Option<String>,
+    ) -> Result<Option<ApprovalDecision>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Option<String>>,
+    {
+        let subject = match kind {
+            PolicySubjectKind::Tool => "Tool",
+            PolicySubjectKind::Command => "Command",
+        };
+        if matches!(decision.action, PolicyAction::Deny) {
+            let reason = decision
+                .matched_rule
+                .and_then(|r| r.reason)
+                .unwrap_or_else(|| "blocked".into());
+            let evt = match kind {
+                PolicySubjectKind::Tool => serde_json::json!({"tool": name, "reason": reason}),
+                PolicySubjectKind::Command => {
+                    serde_json::json!({"command": name, "reason": reason})
+                }
+            };
+            events::publish(topics::TOPIC_POLICY_BLOCK, evt).await;
+            bail!("{} '{}' blocked by policy", subject, name);
+        }
+        if !matches!(decision.action, PolicyAction::Ask) && ask_reason.is_none() {
+            return Ok(None);
+        }
+
+        let reason = ask_reason
+            .or_else(|| {
+                decision
+                    .matched_rule
+                    .as_ref()
+                    .and_then(|r| r.reason.clone())
+            })
+            .unwrap_or_else(|| "no matching policy rule".into());
+        let risk_score = risk_score.unwrap_or(match decision.risk {
+            RiskLevel::Low => 1,
+            RiskLevel::Medium => 3,
+            RiskLevel::High => 5,
+        });
+        let mut request = match kind {
+            PolicySubjectKind::Tool => ApprovalRequest::tool(name, args),
+            PolicySubjectKind::Command => ApprovalRequest::command(name, args),
+        }
+        .with_risk_score(risk_score)
+        .with_reason(reason);
+        if let Some(rule) = decision.matched_rule {
+            request = request.with_rule(rule);
+        }
+        if let Some(command) = command {
+            request = request.with_command(command);
+        }
+        if self.broker.will_prompt(&request) {
+            if let Some(p) = preview().await {
+                request = request.with_preview(p);
+            }
+        }
+        match self.broker.request(request).await? {
+            ApprovalDecision::Deny { reason } if self.broker.is_non_interactive() => {
+                bail!(
+                    "{} '{}' requires confirmation (ask), but running non-interactive{}",
+                    subject,
+                    name,
+                    reason.map(|r| format!(" ({r})")).unwrap_or_default()
+                );
+            }
+            ApprovalDecision::Deny { reason } => {
+                bail!(
+                    "Отменено пользователем{}",
+                    reason.map(|r| format!(": {r}")).unwrap_or_default()
+                );
+            }
+            allowed => Ok(Some(allowed)),
+        }
+    }
+
+    /// Publish `tool.invoked` (awaited, so the audit entry is durable)
+    pub async fn record_invocation(
+        name: &str,
+        input: &ToolInput,
+        output: &Result<ToolOutput>,
+        started: Instant,
+    ) {
+        let evt = serde_json::json!({
+            "tool": name,
+            "command": input.command,
+            "dry_run": input.dry_run,
+            "success": output.as_ref().map(|o| o.success).unwrap_or(false),
+            "error": output.as_ref().err().map(|e| e.to_string()),
+            "duration_ms": started.elapsed().as_millis() as u64,
+        });
+        events::publish(topics::TOPIC_TOOL_INVOKED, evt).await;
+    }
+
+    /// Full pipeline: prepare → authorize → execute → telemetry
+    pub async fn invoke(
+        &self,
+        tool: &dyn Tool,
+        spec: &ToolSpec,
+        mut input: ToolInput,
+    ) -> Result<ToolOutput> {
+        self.prepare(spec, &mut input)?;
+        self.authorize(tool, spec, &input).await?;
+        let started = Instant::now();
+        let output = tool.execute(input.clone()).await;
+        Self::record_invocation(&spec.name, &input, &output, started).await;
+        output
+    }
+}
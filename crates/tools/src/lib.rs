@@ -15,6 +15,12 @@ pub mod execution;
 // Plugin system with WASM and external process support
 pub mod plugins;
 
+// JSON Schema for tool inputs: validation, coercion, function calling
+pub mod input_schema;
+
+// Policy-gated invocation pipeline (tools run, orchestrator)
+pub mod invocation;
+
 // Tool implementations
 pub mod file_ops;
 pub mod git_ops;
@@ -86,6 +92,38 @@ pub struct ToolOutput {
     pub metadata: HashMap<String, String>,
 }
 
+impl ToolOutput {
+    /// Apply the redaction policy before the output reaches an LLM prompt:
+    /// secrets are masked, the call fails on `block`, `ask` prompts the user
+    /// through `broker` (the caller's, so its session grants apply)
+    pub async fn redact_for_llm(
+        mut self,
+        tool_name: &str,
+        broker: &common::approval::ApprovalBroker,
+    ) -> Result<Self> {
+        use common::redaction::{global_redactor, RedactionTarget};
+
+        let redactor = global_redactor();
+        let result = redactor
+            .guard(&self.result, RedactionTarget::Llm, tool_name, Some(broker))
+            .await?;
+        let mut changed = result != self.result;
+        self.result = result;
+        if let Some(formatted) = self.formatted_output.take() {
+            let guarded = redactor
+                .guard(&formatted, RedactionTarget::Llm, tool_name, Some(broker))
+                .await?;
+            changed |= guarded != formatted;
+            self.formatted_output = Some(guarded);
+        }
+        if changed {
+            self.metadata
+                .insert("secrets_redacted".to_string(), "true".to_string());
+        }
+        Ok(self)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageGuide {
     pub usage_title: String,
@@ -131,18 +169,73 @@ impl ToolSpec {
         self.usage_guide = Some(guide);
         self
     }
+
+    /// Whether the tool's own usage guide declares no side effects; tools
+    /// without a guide are assumed to have some
+    pub fn is_read_only(&self) -> bool {
+        self.usage_guide
+            .as_ref()
+            .is_some_and(|guide| guide.side_effects.is_empty())
+    }
+
+    /// Parsed JSON Schema of the tool arguments
+    pub fn parsed_input_schema(
+        &self,
+    ) -> Result<input_schema::InputSchema, input_schema::SchemaError> {
+        input_schema::InputSchema::parse(&self.input_schema)
+    }
+
+    /// Validate `input.args` against the schema, coercing string values into
+    /// their declared types and filling defaults. Must run before `execute`.
+    pub fn validate_input(&self, input: &mut ToolInput) -> Result<()> {
+        self.validate_args(&mut input.args)
+    }
+
+    /// Same as [`ToolSpec::validate_input`] for a bare argument map
+    pub fn validate_args(&self, args: &mut HashMap<String, String>) -> Result<()> {
+        let schema = self.parsed_input_schema().map_err(|e| {
+            anyhow::anyhow!("Tool '{}' has an invalid input schema: {}", self.name, e)
+        })?;
+        schema
+            .coerce_args(args)
+            .map_err(|e| anyhow::anyhow!("Invalid arguments for tool '{}': {}", self.name, e))
+    }
+
+    /// Provider-neutral function calling definition for LLM requests
+    pub fn function_definition(&self) -> llm::FunctionDefinition {
+        let parameters = self.parsed_input_schema().unwrap_or_default().parameters();
+        llm::FunctionDefinition::new(&self.name, &self.description, parameters)
+    }
+
+    /// Tool descriptor in MCP `tools/list` format
+    pub fn mcp_descriptor(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": self.parsed_input_schema().unwrap_or_default().into_value(),
+        })
+    }
+}
+
+/// MCP `tools/list` result for a set of tools
+pub fn mcp_tools_list(specs: &[ToolSpec]) -> serde_json::Value {
+    let tools: Vec<serde_json::Value> = specs.iter().map(ToolSpec::mcp_descriptor).collect();
+    serde_json::json!({ "tools": tools })
 }
 
 pub fn generate_usage_guide(spec: &ToolSpec) -> UsageGuide {
     let mut args_brief = HashMap::new();
-    if spec.input_schema.contains("url") {
-        args_brief.insert("url".into(), "HTTP/HTTPS URL".into());
-    }
-    if spec.input_schema.contains("path") {
-        args_brief.insert("path".into(), "File path".into());
-    }
-    if spec.input_schema.contains("cmd") {
-        args_brief.insert("cmd".into(), "Shell command".into());
+    if let Ok(schema) = spec.parsed_input_schema() {
+        for arg in schema.arguments() {
+            let mut brief = arg
+                .description
+                .clone()
+                .unwrap_or_else(|| arg.types.join("|"));
+            if !arg.required {
+                brief.push_str(" (optional)");
+            }
+            args_brief.insert(arg.name, brief);
+        }
     }
 
     let examples = if !spec.examples.is_empty() {
@@ -203,6 +296,15 @@ impl ToolRegistry {
         registry.register("git_status", Box::new(git_ops::GitStatus::new()));
         registry.register("git_commit", Box::new(git_ops::GitCommit::new()));
         registry.register("git_diff", Box::new(git_ops::GitDiff::new()));
+        registry.register("git_log", Box::new(git_ops::GitLog::new()));
+        registry.register("git_show", Box::new(git_ops::GitShow::new()));
+        registry.register("git_blame", Box::new(git_ops::GitBlame::new()));
+        registry.register("git_branch", Box::new(git_ops::GitBranch::new()));
+        registry.register("git_checkout", Box::new(git_ops::GitCheckout::new()));
+        registry.register("git_stash", Box::new(git_ops::GitStash::new()));
+        registry.register("git_apply", Box::new(git_ops::GitApply::new()));
+        registry.register("git_worktree", Box::new(git_ops::GitWorktree::new()));
+        registry.register("git_reset", Box::new(git_ops::GitReset::new()));
         registry.register("web_search", Box::new(web_ops::WebSearch::new()));
         registry.register("web_fetch", Box::new(web_ops::WebFetch::new()));
         registry.register("shell_exec", Box::new(shell_ops::ShellExec::new()));
@@ -217,6 +319,12 @@ impl ToolRegistry {
         self.tools.get(name).map(|t| t.as_ref())
     }
 
+    /// Registered and read-only per [`ToolSpec::is_read_only`]
+    pub fn is_read_only(&self, name: &str) -> bool {
+        self.get(name)
+            .is_some_and(|tool| tool.spec().is_read_only())
+    }
+
     pub fn list_tools(&self) -> Vec<ToolSpec> {
         self.tools
             .values()
@@ -102,6 +102,87 @@ pub struct ToolMetadata {
     pub documentation: Option<String>,
 }
 
+/// Operation that undoes a tool's side effects, invoked by saga compensation.
+///
+/// String argument values may reference the original call: `{{args.<name>}}`
+/// is replaced with an original argument, `{{result.<pointer>}}` with a field
+/// of the original output (dot-separated path).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InverseOperation {
+    /// Tool to invoke; `None` means the tool itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Arguments of the inverse call
+    #[serde(default)]
+    pub arguments: HashMap<String, serde_json::Value>,
+}
+
+impl InverseOperation {
+    /// Substitute `{{args.*}}` / `{{result.*}}` placeholders
+    pub fn resolve_arguments(
+        &self,
+        original_arguments: &HashMap<String, serde_json::Value>,
+        original_output: Option<&serde_json::Value>,
+    ) -> Result<HashMap<String, serde_json::Value>, String> {
+        self.arguments
+            .iter()
+            .map(|(key, value)| {
+                let resolved = match value {
+                    serde_json::Value::String(template) => {
+                        Self::resolve_template(template, original_arguments, original_output)?
+                    }
+                    other => other.clone(),
+                };
+                Ok((key.clone(), resolved))
+            })
+            .collect()
+    }
+
+    fn resolve_template(
+        template: &str,
+        original_arguments: &HashMap<String, serde_json::Value>,
+        original_output: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let trimmed = template.trim();
+        let Some(reference) = trimmed
+            .strip_prefix("{{")
+            .and_then(|rest| rest.strip_suffix("}}"))
+            .map(str::trim)
+        else {
+            return Ok(serde_json::Value::String(template.to_string()));
+        };
+
+        let (root, path) = reference.split_once('.').unwrap_or((reference, ""));
+        let mut segments = path.split('.').filter(|segment| !segment.is_empty());
+        let mut current = match root {
+            "args" => {
+                let name = segments
+                    .next()
+                    .ok_or_else(|| format!("'{template}': missing argument name"))?;
+                original_arguments
+                    .get(name)
+                    .ok_or_else(|| format!("'{template}': no argument '{name}'"))?
+            }
+            "result" => {
+                original_output.ok_or_else(|| format!("'{template}': no recorded output"))?
+            }
+            other => return Err(format!("'{template}': unknown root '{other}'")),
+        };
+        for segment in segments {
+            current = match current {
+                serde_json::Value::Object(map) => map.get(segment),
+                serde_json::Value::Array(items) => segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get(index)),
+                _ => None,
+            }
+            .ok_or_else(|| format!("'{template}': '{segment}' not found"))?;
+        }
+        Ok(current.clone())
+    }
+}
+
 /// Tool manifest structure - complete tool.json schema
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolManifest {
@@ -133,6 +214,12 @@ pub struct ToolManifest {
     /// Enhanced capability specification (P1.2.3)
     #[serde(default)]
     pub capability_spec: CapabilitySpec,
+    /// JSON Schema (draft 2020-12) of the tool arguments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    /// How to undo this tool's side effects (saga compensation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inverse: Option<InverseOperation>,
 }
 
 impl ToolManifest {
@@ -164,6 +251,8 @@ impl ToolManifest {
             },
             config: HashMap::new(),
             capability_spec: CapabilitySpec::default(),
+            input_schema: None,
+            inverse: None,
         }
     }
 
@@ -270,6 +359,28 @@ impl ToolManifest {
             .unwrap_or(0)
     }
 
+    /// Set JSON Schema of the tool arguments
+    pub fn with_input_schema(mut self, schema: serde_json::Value) -> Self {
+        self.input_schema = Some(schema);
+        self
+    }
+
+    /// Parsed input schema; manifests without one accept no declared arguments
+    pub fn parsed_input_schema(
+        &self,
+    ) -> Result<crate::input_schema::InputSchema, crate::input_schema::SchemaError> {
+        match &self.input_schema {
+            Some(schema) => crate::input_schema::InputSchema::from_value(schema.clone()),
+            None => Ok(crate::input_schema::InputSchema::default()),
+        }
+    }
+
+    /// Declare the inverse operation used to compensate this tool
+    pub fn with_inverse(mut self, inverse: InverseOperation) -> Self {
+        self.inverse = Some(inverse);
+        self
+    }
+
     /// Add metadata field
     pub fn with_repository(mut self, repository: String) -> Self {
         self.metadata.repository = Some(repository);
@@ -317,6 +428,10 @@ impl ToolManifest {
             }
         }
 
+        if let Err(e) = self.parsed_input_schema() {
+            return Err(format!("Invalid input_schema: {e}"));
+        }
+
         // Check resource limits are reasonable
         if let Some(memory) = self.runtime_config.max_memory_mb {
             if memory == 0 || memory > 2048 {
@@ -384,6 +499,38 @@ mod tests {
         assert_eq!(manifest.entry_point, "main.wasm");
     }
 
+    #[test]
+    fn test_inverse_operation_resolves_placeholders() {
+        let manifest: ToolManifest = serde_json::from_value(serde_json::json!({
+            "name": "ticket_create",
+            "version": "1.0.0",
+            "description": "Create a ticket",
+            "type": "native",
+            "entry_point": "ticket",
+            "metadata": { "author": "Test Author", "license": "MIT" },
+            "inverse": {
+                "tool": "ticket_delete",
+                "arguments": { "id": "{{result.ticket.id}}", "project": "{{args.project}}", "force": true }
+            }
+        }))
+        .expect("Manifest should deserialize");
+
+        let inverse = manifest.inverse.expect("Inverse should be declared");
+        assert_eq!(inverse.tool.as_deref(), Some("ticket_delete"));
+
+        let mut original = HashMap::new();
+        original.insert("project".to_string(), serde_json::json!("MAG"));
+        let output = serde_json::json!({ "ticket": { "id": 42 } });
+        let resolved = inverse
+            .resolve_arguments(&original, Some(&output))
+            .expect("Placeholders should resolve");
+        assert_eq!(resolved["id"], serde_json::json!(42));
+        assert_eq!(resolved["project"], serde_json::json!("MAG"));
+        assert_eq!(resolved["force"], serde_json::json!(true));
+
+        assert!(inverse.resolve_arguments(&original, None).is_err());
+    }
+
     #[test]
     fn test_capability_consistency_validation() {
         let mut manifest = ToolManifest::new(
@@ -425,6 +572,39 @@ mod tests {
         assert!(manifest.validate_consistency().is_err());
     }
 
+    #[test]
+    fn test_input_schema_validation() {
+        let manifest = ToolManifest::new(
+            "test-tool".to_string(),
+            "1.0.0".to_string(),
+            "A test tool".to_string(),
+            ToolType::Wasm,
+            "main.wasm".to_string(),
+            "Test Author".to_string(),
+            "MIT".to_string(),
+        );
+
+        let valid = manifest.clone().with_input_schema(serde_json::json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"]
+        }));
+        assert!(valid.validate_consistency().is_ok());
+        assert_eq!(
+            valid
+                .parsed_input_schema()
+                .expect("Operation failed - converted from unwrap()")
+                .required(),
+            vec!["path".to_string()]
+        );
+
+        let invalid = manifest.with_input_schema(serde_json::json!({
+            "type": "object",
+            "properties": {"path": {"type": "path"}}
+        }));
+        assert!(invalid.validate_consistency().is_err());
+    }
+
     #[test]
     fn test_json_serialization() {
         let manifest = ToolManifest::new(
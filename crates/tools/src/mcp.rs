@@ -48,6 +48,8 @@ pub struct McpTool {
     max_execution_time_ms: u64,
     /// CRITICAL P0.2.6: EventBus publisher for comprehensive audit logging
     event_publisher: Option<Arc<dyn LocalEventPublisher>>,
+    /// JSON Schema of the remote tool arguments (`inputSchema` from the server's `tools/list`)
+    input_schema: Option<serde_json::Value>,
 }
 
 impl McpTool {
@@ -94,6 +96,7 @@ impl McpTool {
                 .clamp(5_000, 1_800_000), // SECURITY: Apply limits even for env values
             // CRITICAL P0.2.6: No EventBus publisher by default - must be explicitly configured
             event_publisher: None,
+            input_schema: None,
         }
     }
 
@@ -131,6 +134,19 @@ impl McpTool {
         self
     }
 
+    /// Declare the remote tool's argument schema; invalid schemas are rejected
+    pub fn with_input_schema(mut self, schema: serde_json::Value) -> Result<Self> {
+        let parsed = crate::input_schema::InputSchema::from_value(schema).map_err(|e| {
+            anyhow!(
+                "Invalid input schema for MCP tool '{}': {}",
+                self.remote_tool,
+                e
+            )
+        })?;
+        self.input_schema = Some(parsed.into_value());
+        Ok(self)
+    }
+
     /// Set declared capabilities for this MCP tool (subject to validation)
     pub fn with_declared_capabilities(mut self, capabilities: Vec<String>) -> Self {
         self.declared_capabilities = capabilities;
@@ -741,7 +757,18 @@ impl Tool for McpTool {
                 "mcp:{}: {{\"command\":\"run\", \"args\":{{}}, \"dry_run\": true}}",
                 self.remote_tool
             )],
-            input_schema: "{command: string, args: object, context?: string, dry_run?: boolean}".to_string(),
+            input_schema: self
+                .input_schema
+                .clone()
+                .unwrap_or_else(|| {
+                    // Unknown remote schema: accept any arguments, validation is left to the server
+                    serde_json::json!({
+                        "type": "object",
+                        "description": format!("Arguments forwarded to MCP tool '{}'", self.remote_tool),
+                        "additionalProperties": true
+                    })
+                })
+                .to_string(),
             usage_guide: None,
             // CRITICAL SECURITY FIX: Return explicit permissions instead of None
             permissions: Some(self.permissions.clone()),
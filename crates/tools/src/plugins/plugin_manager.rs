@@ -67,6 +67,9 @@ pub struct ToolManifest {
     pub configuration_schema: serde_json::Value,
     #[serde(default)]
     pub default_config: serde_json::Value,
+    /// JSON Schema (draft 2020-12) of the tool arguments
+    #[serde(default)]
+    pub input_schema: serde_json::Value,
 
     #[serde(default)]
     pub permissions: ManifestPermissions,
@@ -164,6 +167,10 @@ impl ToolManifest {
         // Compute derived fields before moving parts of self to avoid partial move borrow issues
         let version = self.parse_version()?;
         let plugin_type = self.parse_type();
+        if !self.input_schema.is_null() {
+            crate::input_schema::InputSchema::from_value(self.input_schema.clone())
+                .map_err(|e| anyhow!("Plugin '{}': invalid input_schema: {}", self.id, e))?;
+        }
         let registry_permissions = self.to_registry_permissions();
         let configuration_schema = if self.configuration_schema.is_null() {
             serde_json::json!({})
@@ -189,6 +196,7 @@ impl ToolManifest {
             entry_point: self.entry_point,
             configuration_schema,
             default_config,
+            input_schema: self.input_schema.clone(),
             runtime_requirements: RuntimeRequirements::default(),
             dependencies: Vec::new(),
             required_permissions: registry_permissions,
@@ -227,6 +235,9 @@ pub struct PluginMetadata {
     pub entry_point: String,
     pub configuration_schema: serde_json::Value,
     pub default_config: serde_json::Value,
+    /// JSON Schema of the tool arguments declared in the manifest
+    #[serde(default)]
+    pub input_schema: serde_json::Value,
 
     // Runtime requirements
     pub runtime_requirements: RuntimeRequirements,
@@ -257,6 +268,22 @@ pub struct PluginMetadata {
     pub last_error: Option<String>,
 }
 
+impl PluginMetadata {
+    /// Input schema string for the plugin's `ToolSpec`. Older manifests
+    /// without `input_schema` fall back to `configuration_schema`.
+    pub fn tool_input_schema(&self) -> String {
+        let schema = if self.input_schema.is_null() {
+            &self.configuration_schema
+        } else {
+            &self.input_schema
+        };
+        crate::input_schema::InputSchema::from_value(schema.clone())
+            .unwrap_or_default()
+            .into_value()
+            .to_string()
+    }
+}
+
 /// Plugin version with semantic versioning
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub struct PluginVersion {
@@ -1243,6 +1270,7 @@ mod tests {
             entry_point: "test.wasm".to_string(),
             configuration_schema: serde_json::json!({}),
             default_config: serde_json::json!({}),
+            input_schema: serde_json::Value::Null,
             runtime_requirements: RuntimeRequirements::default(),
             dependencies: Vec::new(),
             required_permissions: ToolPermissions::default(),
@@ -772,7 +772,7 @@ impl Tool for WasmPlugin {
                 self.metadata.name, self.metadata.version
             ),
             examples: Vec::new(),
-            input_schema: self.metadata.configuration_schema.to_string(),
+            input_schema: self.metadata.tool_input_schema(),
             usage_guide: None,
             permissions: Some(perms),
             supports_dry_run: false,
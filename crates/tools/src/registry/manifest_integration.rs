@@ -310,12 +310,17 @@ pub struct ManifestBasedTool {
 impl ManifestBasedTool {
     pub fn new(manifest: ToolManifest) -> Self {
         // Convert manifest to ToolSpec
+        let input_schema = manifest
+            .parsed_input_schema()
+            .unwrap_or_default()
+            .into_value()
+            .to_string();
         let spec = ToolSpec {
             name: manifest.name.clone(),
             description: manifest.description.clone(),
             usage: format!("Tool: {}", manifest.name),
             examples: vec![format!("{} --help", manifest.name)],
-            input_schema: "{}".to_string(), // Basic empty JSON schema
+            input_schema,
             usage_guide: None,
             permissions: None,
             supports_dry_run: false,
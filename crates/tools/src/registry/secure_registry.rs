@@ -62,8 +62,8 @@ impl InputValidator {
     pub fn validate_and_sanitize(input: &ToolInput, metadata: &ToolMetadata) -> Result<ToolInput> {
         let mut sanitized_input = input.clone();
 
-        // Validate required parameters
-        Self::validate_schema(&sanitized_input, &metadata.input_schema)?;
+        // Validate and coerce arguments against the JSON Schema
+        Self::validate_schema(&mut sanitized_input, &metadata.input_schema)?;
 
         // Sanitize based on tool permissions
         Self::sanitize_based_on_permissions(&mut sanitized_input, &metadata.permissions)?;
@@ -74,16 +74,12 @@ impl InputValidator {
         Ok(sanitized_input)
     }
 
-    fn validate_schema(input: &ToolInput, schema: &serde_json::Value) -> Result<()> {
-        if let Some(obj) = schema.as_object() {
-            for (key, _) in obj {
-                if !input.args.contains_key(key) {
-                    warn!("Missing required parameter: {}", key);
-                    // Note: Not failing here to maintain compatibility, but should be configurable
-                }
-            }
-        }
-        Ok(())
+    fn validate_schema(input: &mut ToolInput, schema: &serde_json::Value) -> Result<()> {
+        let schema = crate::input_schema::InputSchema::from_value(schema.clone())
+            .map_err(|e| anyhow!("Invalid tool input schema: {}", e))?;
+        schema
+            .coerce_args(&mut input.args)
+            .map_err(|e| anyhow!("Invalid arguments: {}", e))
     }
 
     fn sanitize_based_on_permissions(
@@ -504,6 +500,12 @@ impl SecureToolRegistry {
         result
     }
 
+    /// Tool instance by id, for callers that run their own policy gate first
+    pub async fn get_tool(&self, tool_id: &str) -> Option<Arc<dyn Tool>> {
+        let tools = self.tools.read().await;
+        tools.get(tool_id).map(|(tool, _)| Arc::clone(tool))
+    }
+
     /// Get tools filtered by security permissions
     pub async fn get_available_tools(
         &self,
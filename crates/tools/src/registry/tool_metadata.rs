@@ -303,10 +303,7 @@ impl ToolMetadata {
             SemanticVersion::new(0, 1, 0),
         );
         meta.description = spec.description.clone();
-        meta.input_schema = match serde_json::from_str::<serde_json::Value>(&spec.input_schema) {
-            Ok(v) => v,
-            Err(_) => serde_json::json!({}),
-        };
+        meta.input_schema = spec.parsed_input_schema().unwrap_or_default().into_value();
         meta.examples = spec
             .examples
             .iter()
@@ -356,6 +356,13 @@ impl ViolationLog {
 
     /// Add violation to log
     pub fn log_violation(&mut self, violation: SandboxViolation) {
+        if let Ok(payload) = serde_json::to_value(&violation) {
+            common::audit::record_event(
+                common::topics::TOPIC_SANDBOX_VIOLATION.0,
+                "sandbox",
+                &payload,
+            );
+        }
         self.violations.push(violation);
 
         // Enforce maximum entries (remove oldest)
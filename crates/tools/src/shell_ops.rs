@@ -155,8 +155,16 @@ impl Tool for ShellExec {
                 "shell_exec \"ls -la\" --dry-run  # Preview command safely".to_string(),
                 "выполни команду pwd (requires policy allow)".to_string(),
             ],
-            input_schema: r#"{"command": "string", "cwd": "string?", "max_output_kb": "number?"}"#
-                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "command": {"type": "string", "description": "Shell command to execute (whitelist restricted)", "minLength": 1},
+                    "cwd": {"type": "string", "description": "Working directory"},
+                    "max_output_kb": {"type": "integer", "description": "Maximum output size in KB", "minimum": 1, "maximum": 16384, "default": 256}
+                },
+                "required": ["command"]
+            })
+            .to_string(),
             usage_guide: Some(crate::UsageGuide {
                 usage_title: "🔒 SECURITY-PROTECTED SHELL EXECUTION".to_string(),
                 usage_summary: "Executes shell commands with mandatory policy checks and sandbox restrictions. Requires explicit permission.".to_string(),
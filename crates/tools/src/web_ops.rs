@@ -148,8 +148,34 @@ impl Tool for WebSearch {
                 "web_search \"Rust async programming\"".to_string(),
                 "web_search \"machine learning tutorials\"".to_string(),
             ],
-            input_schema: r#"{"query": "string (будет искать через разрешённые поисковики)"}"#.to_string(),
-            usage_guide: None,
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string", "description": "Поисковый запрос (ищет через разрешённые поисковики)", "minLength": 1}
+                },
+                "required": ["query"]
+            })
+            .to_string(),
+            usage_guide: Some(crate::UsageGuide {
+                usage_title: "web_search".into(),
+                usage_summary: "Search the web through allowlisted search engines".into(),
+                preconditions: vec!["Search domains must be in the net allowlist".into()],
+                arguments_brief: HashMap::from([(
+                    "query".to_string(),
+                    "Search query".to_string(),
+                )]),
+                good_for: vec!["documentation lookup".into(), "research".into()],
+                not_for: vec!["downloading pages (use web_fetch)".into()],
+                constraints: vec!["Only allowlisted domains are queried".into()],
+                examples: vec!["web_search \"Rust async programming\"".into()],
+                platforms: vec!["linux".into(), "mac".into(), "win".into()],
+                cost_class: "free".into(),
+                latency_class: "slow".into(),
+                side_effects: vec![],
+                risk_score: 1,
+                capabilities: vec!["read".into(), "network".into()],
+                tags: vec!["web".into()],
+            }),
             // CRITICAL: Explicit network permissions for policy checking
             permissions: Some(permissions),
             supports_dry_run: true,
@@ -381,8 +407,38 @@ impl Tool for WebFetch {
                 "web_fetch file:///allowed/path/file.txt".to_string(),
                 "web_fetch data:text/plain;base64,SGVsbG8=".to_string(),
             ],
-            input_schema: r#"{"url": "string (http(s):// требует net allowlist, file:// требует fs read roots + policy)"}"#.to_string(),
-            usage_guide: None,
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "http(s):// требует net allowlist, file:// требует fs read roots + policy",
+                        "pattern": "^(https?|file|data):"
+                    }
+                },
+                "required": ["url"]
+            })
+            .to_string(),
+            usage_guide: Some(crate::UsageGuide {
+                usage_title: "web_fetch".into(),
+                usage_summary: "Fetch the content of an http(s)://, file:// or data: URL".into(),
+                preconditions: vec![
+                    "http(s) hosts must be in the net allowlist".into(),
+                    "file:// paths must be within the fs read roots".into(),
+                ],
+                arguments_brief: HashMap::from([("url".to_string(), "URL to fetch".to_string())]),
+                good_for: vec!["reading documentation pages".into()],
+                not_for: vec!["search (use web_search)".into()],
+                constraints: vec!["GET only, sandboxed by allowlist and read roots".into()],
+                examples: vec!["web_fetch https://docs.rs/crate/tokio".into()],
+                platforms: vec!["linux".into(), "mac".into(), "win".into()],
+                cost_class: "free".into(),
+                latency_class: "slow".into(),
+                side_effects: vec![],
+                risk_score: 1,
+                capabilities: vec!["read".into(), "network".into()],
+                tags: vec!["web".into()],
+            }),
             // CRITICAL: Explicit filesystem + network permissions for policy checking
             permissions: Some(permissions),
             supports_dry_run: true,
@@ -132,3 +132,175 @@ async fn file_delete_dry_run_reports_preview() -> Result<()> {
     assert!(out.result.contains("[dry-run] rm"));
     Ok(())
 }
+
+fn git(repo: &std::path::Path, args: &[&str]) -> Result<()> {
+    assert!(Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .status()?
+        .success());
+    Ok(())
+}
+
+fn tool_input(command: &str, repo: &std::path::Path, args: &[(&str, &str)]) -> ToolInput {
+    let mut map: HashMap<String, String> = args
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    map.insert("cwd".into(), repo.to_string_lossy().to_string());
+    ToolInput {
+        command: command.into(),
+        args: map,
+        context: None,
+        dry_run: false,
+        timeout_ms: None,
+    }
+}
+
+#[tokio::test]
+async fn git_history_tools_return_json() -> Result<()> {
+    use tools::git_ops::{GitBlame, GitBranch, GitLog, GitShow};
+
+    let tmp = TempDir::new().expect("Test operation should succeed");
+    let repo = tmp.path();
+    git(repo, &["init", "-q", "-b", "main"])?;
+    git(repo, &["config", "user.email", "test@example.com"])?;
+    git(repo, &["config", "user.name", "Test"])?;
+    fs::write(repo.join("a.txt"), "one\ntwo\n")?;
+    git(repo, &["add", "."])?;
+    git(repo, &["commit", "-q", "-m", "first"])?;
+    fs::write(repo.join("a.txt"), "one\ntwo\nthree\n")?;
+    git(repo, &["commit", "-q", "-am", "second"])?;
+
+    let out = GitLog::new()
+        .execute(tool_input(
+            "git_log",
+            repo,
+            &[("path", "a.txt"), ("max_count", "1")],
+        ))
+        .await?;
+    let log: serde_json::Value = serde_json::from_str(&out.result)?;
+    assert_eq!(out.metadata.get("format").map(String::as_str), Some("json"));
+    assert_eq!(log["count"], 1);
+    assert_eq!(log["commits"][0]["subject"], "second");
+
+    let out = GitShow::new()
+        .execute(tool_input("git_show", repo, &[]))
+        .await?;
+    let show: serde_json::Value = serde_json::from_str(&out.result)?;
+    assert_eq!(show["files"][0]["path"], "a.txt");
+    assert_eq!(show["files"][0]["additions"], 1);
+
+    let out = GitBlame::new()
+        .execute(tool_input(
+            "git_blame",
+            repo,
+            &[("path", "a.txt"), ("start_line", "2"), ("end_line", "3")],
+        ))
+        .await?;
+    let blame: serde_json::Value = serde_json::from_str(&out.result)?;
+    assert_eq!(blame["lines"][0]["summary"], "first");
+    assert_eq!(blame["lines"][1]["summary"], "second");
+    assert_eq!(blame["lines"][1]["content"], "three");
+
+    let branch = GitBranch::new();
+    branch
+        .execute(tool_input(
+            "git_branch",
+            repo,
+            &[("action", "create"), ("name", "feature")],
+        ))
+        .await?;
+    let out = branch.execute(tool_input("git_branch", repo, &[])).await?;
+    let branches: serde_json::Value = serde_json::from_str(&out.result)?;
+    assert_eq!(branches["current"], "main");
+    assert_eq!(branches["branches"].as_array().map(Vec::len), Some(2));
+    Ok(())
+}
+
+#[tokio::test]
+async fn git_destructive_tools_preview_in_dry_run() -> Result<()> {
+    use tools::git_ops::{GitApply, GitReset};
+
+    let tmp = TempDir::new().expect("Test operation should succeed");
+    let repo = tmp.path();
+    git(repo, &["init", "-q"])?;
+    git(repo, &["config", "user.email", "test@example.com"])?;
+    git(repo, &["config", "user.name", "Test"])?;
+    fs::write(repo.join("a.txt"), "one\n")?;
+    git(repo, &["add", "."])?;
+    git(repo, &["commit", "-q", "-m", "first"])?;
+    fs::write(repo.join("a.txt"), "one\ntwo\n")?;
+    git(repo, &["commit", "-q", "-am", "second"])?;
+    fs::write(repo.join("a.txt"), "dirty\n")?;
+
+    let mut input = tool_input("git_reset", repo, &[("target", "HEAD~1"), ("mode", "hard")]);
+    input.dry_run = true;
+    let out = GitReset::new().execute(input).await?;
+    let preview: serde_json::Value = serde_json::from_str(&out.result)?;
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(
+        preview["preview"]["commits_leaving_branch"][0]["subject"],
+        "second"
+    );
+    assert_eq!(preview["preview"]["would_discard"][0]["path"], "a.txt");
+    // Ничего не изменилось
+    assert_eq!(fs::read_to_string(repo.join("a.txt"))?, "dirty\n");
+
+    let patch = "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-missing\n+x\n";
+    let out = GitApply::new()
+        .execute(tool_input("git_apply", repo, &[("patch", patch)]))
+        .await?;
+    let check: serde_json::Value = serde_json::from_str(&out.result)?;
+    assert!(!out.success);
+    assert_eq!(check["applicable"], false);
+    assert_eq!(check["applied"], false);
+    Ok(())
+}
+
+#[tokio::test]
+async fn git_checkout_restores_paths_through_tool_gate() -> Result<()> {
+    use common::approval::{ApprovalBroker, StdinPrompter};
+    use common::policy::PolicyEngine;
+    use std::sync::Arc;
+    use tools::invocation::ToolGate;
+    use tools::ToolRegistry;
+
+    let tmp = TempDir::new().expect("Test operation should succeed");
+    let repo = tmp.path();
+    git(repo, &["init", "-q"])?;
+    git(repo, &["config", "user.email", "test@example.com"])?;
+    git(repo, &["config", "user.name", "Test"])?;
+    fs::write(repo.join("a.rs"), "a\n")?;
+    fs::write(repo.join("b.rs"), "b\n")?;
+    fs::write(repo.join("c.rs"), "c\n")?;
+    git(repo, &["add", "."])?;
+    git(repo, &["commit", "-q", "-m", "first"])?;
+    for name in ["a.rs", "b.rs", "c.rs"] {
+        fs::write(repo.join(name), "dirty\n")?;
+    }
+
+    let registry = ToolRegistry::new();
+    let tool = registry
+        .get("git_checkout")
+        .expect("git_checkout should be registered");
+    let broker = ApprovalBroker::new(Arc::new(StdinPrompter)).with_auto_approve(true);
+    let gate = ToolGate::new(PolicyEngine::new(), Arc::new(broker));
+    // Schema coercion turns `a.rs,b.rs` into a JSON array argument
+    let out = gate
+        .invoke(
+            tool,
+            &tool.spec(),
+            tool_input(
+                "git_checkout",
+                repo,
+                &[("target", "HEAD"), ("paths", "a.rs, b.rs")],
+            ),
+        )
+        .await?;
+    assert!(out.success, "{}", out.result);
+    assert_eq!(fs::read_to_string(repo.join("a.rs"))?, "a\n");
+    assert_eq!(fs::read_to_string(repo.join("b.rs"))?, "b\n");
+    assert_eq!(fs::read_to_string(repo.join("c.rs"))?, "dirty\n");
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use common::approval::{ApprovalDecision, PendingApproval};
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -7,6 +8,7 @@ use ratatui::{
     Frame,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ButtonAction {
@@ -15,6 +17,12 @@ pub enum ButtonAction {
     Modify,
     Preview,
     Save,
+    /// Merge the isolated branch into the user's checkout
+    Merge,
+    /// Cherry-pick the isolated branch commits
+    CherryPick,
+    /// Drop the isolated branch
+    Discard,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -58,6 +66,8 @@ pub struct ActionButtons {
     confirmation_action: Option<ButtonAction>,
     last_action_result: Option<(ButtonAction, bool)>,
     processing_progress: u16,
+    /// Broker requests in arrival order; the dialog shows the first one
+    pending_approvals: VecDeque<PendingApproval>,
 }
 
 impl Default for ActionButtons {
@@ -103,6 +113,7 @@ impl ActionButtons {
             confirmation_action: None,
             last_action_result: None,
             processing_progress: 0,
+            pending_approvals: VecDeque::new(),
         }
     }
 
@@ -129,6 +140,10 @@ impl ActionButtons {
         if self.show_confirmation {
             self.render_confirmation_dialog(f, area);
         }
+
+        if !self.pending_approvals.is_empty() {
+            self.render_approval_dialog(f, area);
+        }
     }
 
     fn render_buttons(&self, f: &mut Frame, area: Rect) {
@@ -308,6 +323,94 @@ impl ActionButtons {
         f.render_widget(dialog, dialog_area);
     }
 
+    fn render_approval_dialog(&self, f: &mut Frame, area: Rect) {
+        let Some(prompt) = self.pending_approvals.front().map(|p| &p.request) else {
+            return;
+        };
+        let dialog_width = area.width.min(70);
+        let dialog_height = area.height.min(16);
+        let dialog_area = Rect {
+            x: area.x + (area.width.saturating_sub(dialog_width)) / 2,
+            y: area.y + (area.height.saturating_sub(dialog_height)) / 2,
+            width: dialog_width,
+            height: dialog_height,
+        };
+
+        f.render_widget(Clear, dialog_area);
+
+        let risk_color = match prompt.risk_score {
+            0..=1 => Color::Green,
+            2..=3 => Color::Yellow,
+            _ => Color::Red,
+        };
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled(
+                    prompt.subject.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(
+                    prompt
+                        .command
+                        .as_ref()
+                        .map(|c| format!(" {c}"))
+                        .unwrap_or_default(),
+                ),
+            ]),
+            Line::from(vec![
+                Span::raw("Risk: "),
+                Span::styled(
+                    format!("{}/5", prompt.risk_score),
+                    Style::default().fg(risk_color).add_modifier(Modifier::BOLD),
+                ),
+            ]),
+        ];
+        if let Some(reason) = &prompt.reason {
+            lines.push(Line::from(vec![Span::raw(format!("Reason: {reason}"))]));
+        }
+        lines.push(Line::from(vec![Span::styled(
+            format!("S/A allow: {}", prompt.grant_scope()),
+            Style::default().fg(Color::DarkGray),
+        )]));
+        for (k, v) in &prompt.args {
+            lines.push(Line::from(vec![Span::styled(
+                format!("  {k} = {v}"),
+                Style::default().fg(Color::Cyan),
+            )]));
+        }
+        if let Some(preview) = &prompt.preview {
+            lines.push(Line::from(vec![Span::styled(
+                "Preview (dry-run):",
+                Style::default().add_modifier(Modifier::BOLD),
+            )]));
+            for line in preview.lines().take(5) {
+                lines.push(Line::from(vec![Span::raw(line.to_string())]));
+            }
+        }
+        lines.push(Line::from(vec![Span::raw("")]));
+        lines.push(Line::from(vec![
+            Span::styled("O", Style::default().fg(Color::Green)),
+            Span::raw(": once  "),
+            Span::styled("S", Style::default().fg(Color::Green)),
+            Span::raw(": session  "),
+            Span::styled("A", Style::default().fg(Color::Yellow)),
+            Span::raw(": always (project)  "),
+            Span::styled("D", Style::default().fg(Color::Red)),
+            Span::raw(": deny"),
+        ]));
+
+        let dialog = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Approval Required"),
+            )
+            .wrap(ratatui::widgets::Wrap { trim: false });
+
+        f.render_widget(dialog, dialog_area);
+    }
+
     fn get_button_style(&self, button: &Button, is_selected: bool) -> Style {
         let base_style = match button.state {
             ActionState::Ready => {
@@ -331,6 +434,11 @@ impl ActionButtons {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Option<ButtonAction> {
+        if self.has_pending_approval() {
+            self.answer_approval(key);
+            return None;
+        }
+
         if self.show_confirmation {
             match key.code {
                 KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
@@ -384,7 +492,11 @@ impl ActionButtons {
     fn request_confirmation(&mut self, action: ButtonAction) {
         // Some actions require confirmation
         match action {
-            ButtonAction::Execute | ButtonAction::Cancel => {
+            ButtonAction::Execute
+            | ButtonAction::Cancel
+            | ButtonAction::Merge
+            | ButtonAction::CherryPick
+            | ButtonAction::Discard => {
                 self.confirmation_action = Some(action);
                 self.show_confirmation = true;
             }
@@ -395,6 +507,39 @@ impl ActionButtons {
         }
     }
 
+    /// Buttons for reviewing the branch of an isolated run; Cancel keeps
+    /// the branch without touching the checkout
+    pub fn use_review_buttons(&mut self) {
+        self.buttons = vec![
+            Button::new(
+                ButtonAction::Merge,
+                "Merge",
+                'm',
+                "Merge the branch into the current checkout",
+            ),
+            Button::new(
+                ButtonAction::CherryPick,
+                "Cherry-pick",
+                'p',
+                "Apply the branch commits on top of the current branch",
+            ),
+            Button::new(
+                ButtonAction::Discard,
+                "Discard",
+                'd',
+                "Delete the branch and its changes",
+            ),
+            Button::new(
+                ButtonAction::Cancel,
+                "Keep branch",
+                'k',
+                "Leave the branch for later review",
+            ),
+        ];
+        self.selected_button_index = 0;
+        self.last_action_result = None;
+    }
+
     pub fn set_button_state(&mut self, action: ButtonAction, state: ActionState) {
         if let Some(button) = self.buttons.iter_mut().find(|b| b.action == action) {
             button.state = state;
@@ -437,6 +582,39 @@ impl ActionButtons {
         self.buttons[self.selected_button_index].action.clone()
     }
 
+    /// Queue a broker request; the dialog captures keys until every queued
+    /// request is answered
+    pub fn request_approval(&mut self, pending: PendingApproval) {
+        self.show_confirmation = false;
+        self.pending_approvals.push_back(pending);
+    }
+
+    pub fn has_pending_approval(&self) -> bool {
+        !self.pending_approvals.is_empty()
+    }
+
+    /// Answer the shown request from a key press and send the decision back
+    /// to the broker. Returns the subject and decision, `None` for other keys.
+    pub fn answer_approval(&mut self, key: KeyEvent) -> Option<(String, ApprovalDecision)> {
+        let decision = match key.code {
+            KeyCode::Char('o') | KeyCode::Char('O') | KeyCode::Char('y') | KeyCode::Enter => {
+                ApprovalDecision::AllowOnce
+            }
+            KeyCode::Char('s') | KeyCode::Char('S') => ApprovalDecision::AllowSession,
+            KeyCode::Char('a') | KeyCode::Char('A') => ApprovalDecision::AllowProject,
+            KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Char('n') | KeyCode::Esc => {
+                ApprovalDecision::Deny {
+                    reason: Some("denied in TUI".to_string()),
+                }
+            }
+            _ => return None,
+        };
+        let pending = self.pending_approvals.pop_front()?;
+        let subject = pending.request.subject.clone();
+        pending.respond(decision.clone());
+        Some((subject, decision))
+    }
+
     pub fn is_processing(&self) -> bool {
         self.buttons
             .iter()
@@ -40,6 +40,95 @@ pub struct DiffData {
     pub created_at: String,
 }
 
+impl DiffData {
+    /// Parse the output of `git diff`
+    pub fn from_unified(title: &str, diff: &str) -> Self {
+        let mut files: Vec<FileDiff> = Vec::new();
+        let (mut old_line, mut new_line) = (0, 0);
+
+        for line in diff.lines() {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                let (old_path, new_path) = paths
+                    .split_once(" b/")
+                    .map(|(a, b)| (a.trim_start_matches("a/"), b))
+                    .unwrap_or((paths, paths));
+                files.push(FileDiff {
+                    old_path: old_path.to_string(),
+                    new_path: new_path.to_string(),
+                    lines: Vec::new(),
+                    is_binary: false,
+                });
+                continue;
+            }
+            let Some(file) = files.last_mut() else {
+                continue;
+            };
+
+            let in_header = file.lines.is_empty();
+            if let Some(path) = line.strip_prefix("--- ").filter(|_| in_header) {
+                file.old_path = path.trim_start_matches("a/").to_string();
+            } else if let Some(path) = line.strip_prefix("+++ ").filter(|_| in_header) {
+                file.new_path = path.trim_start_matches("b/").to_string();
+            } else if in_header && line.starts_with("Binary files ") {
+                file.is_binary = true;
+            } else if line.starts_with("@@") {
+                // @@ -old_start,old_len +new_start,new_len @@
+                let mut ranges = line.split_whitespace().skip(1);
+                let mut start = |prefix: char| {
+                    ranges
+                        .next()
+                        .and_then(|r| r.strip_prefix(prefix))
+                        .and_then(|r| r.split(',').next())
+                        .and_then(|n| n.parse().ok())
+                        .unwrap_or(1)
+                };
+                old_line = start('-');
+                new_line = start('+');
+                file.lines.push(DiffLine {
+                    line_type: DiffLineType::Header,
+                    old_line_number: None,
+                    new_line_number: None,
+                    content: line.to_string(),
+                });
+            } else if in_header {
+                // index/mode lines before the first hunk
+                continue;
+            } else if let Some(content) = line.strip_prefix('+') {
+                file.lines.push(DiffLine {
+                    line_type: DiffLineType::Add,
+                    old_line_number: None,
+                    new_line_number: Some(new_line),
+                    content: content.to_string(),
+                });
+                new_line += 1;
+            } else if let Some(content) = line.strip_prefix('-') {
+                file.lines.push(DiffLine {
+                    line_type: DiffLineType::Remove,
+                    old_line_number: Some(old_line),
+                    new_line_number: None,
+                    content: content.to_string(),
+                });
+                old_line += 1;
+            } else if !line.starts_with('\\') {
+                file.lines.push(DiffLine {
+                    line_type: DiffLineType::Context,
+                    old_line_number: Some(old_line),
+                    new_line_number: Some(new_line),
+                    content: line.strip_prefix(' ').unwrap_or(line).to_string(),
+                });
+                old_line += 1;
+                new_line += 1;
+            }
+        }
+
+        DiffData {
+            title: title.to_string(),
+            files,
+            created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+}
+
 pub struct DiffViewer {
     diff_data: Option<DiffData>,
     current_file_index: usize,
@@ -165,14 +254,15 @@ impl DiffViewer {
             .map(|line| self.format_diff_line(line))
             .collect();
 
+        let name = if file.old_path == file.new_path {
+            file.old_path.clone()
+        } else {
+            format!("{} -> {}", file.old_path, file.new_path)
+        };
         let list =
             List::new(diff_lines).block(Block::default().borders(Borders::ALL).title(format!(
                 "Diff: {} (Lines {}-{}/{})",
-                if file.old_path == file.new_path {
-                    &file.old_path
-                } else {
-                    &format!("{} -> {}", file.old_path, file.new_path)
-                },
+                name,
                 start_line + 1,
                 end_line,
                 file.lines.len()
@@ -1,4 +1,5 @@
 pub mod action_buttons;
 pub mod diff_viewer;
 pub mod plan_viewer;
+pub mod task_board;
 pub mod timeline;
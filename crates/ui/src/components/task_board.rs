@@ -0,0 +1,628 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Board column, one per todo `TaskState` (same serialized names)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskColumn {
+    Planned,
+    Ready,
+    InProgress,
+    Blocked,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl TaskColumn {
+    pub const ALL: [TaskColumn; 7] = [
+        TaskColumn::Planned,
+        TaskColumn::Ready,
+        TaskColumn::InProgress,
+        TaskColumn::Blocked,
+        TaskColumn::Done,
+        TaskColumn::Failed,
+        TaskColumn::Cancelled,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            TaskColumn::Planned => "Planned",
+            TaskColumn::Ready => "Ready",
+            TaskColumn::InProgress => "In Progress",
+            TaskColumn::Blocked => "Blocked",
+            TaskColumn::Done => "Done",
+            TaskColumn::Failed => "Failed",
+            TaskColumn::Cancelled => "Cancelled",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|c| c == self).unwrap_or(0)
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            TaskColumn::Planned => Color::Gray,
+            TaskColumn::Ready => Color::Cyan,
+            TaskColumn::InProgress => Color::Yellow,
+            TaskColumn::Blocked => Color::Magenta,
+            TaskColumn::Done => Color::Green,
+            TaskColumn::Failed => Color::Red,
+            TaskColumn::Cancelled => Color::DarkGray,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TaskPriority {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl TaskPriority {
+    pub fn raised(self) -> Self {
+        match self {
+            TaskPriority::Low => TaskPriority::Medium,
+            TaskPriority::Medium => TaskPriority::High,
+            TaskPriority::High | TaskPriority::Critical => TaskPriority::Critical,
+        }
+    }
+
+    pub fn lowered(self) -> Self {
+        match self {
+            TaskPriority::Critical => TaskPriority::High,
+            TaskPriority::High => TaskPriority::Medium,
+            TaskPriority::Medium | TaskPriority::Low => TaskPriority::Low,
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "🟢",
+            TaskPriority::Medium => "🟡",
+            TaskPriority::High => "🟠",
+            TaskPriority::Critical => "🔴",
+        }
+    }
+}
+
+/// Memory record attached to a task (context or artifact)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskMemoryRef {
+    pub layer: serde_json::Value,
+    pub record_id: String,
+}
+
+impl TaskMemoryRef {
+    fn label(&self) -> String {
+        let layer = match &self.layer {
+            serde_json::Value::String(layer) => layer.clone(),
+            other => other.to_string(),
+        };
+        format!("{layer}/{}", self.record_id)
+    }
+}
+
+/// Board view of a serialized todo `TodoItem`; unknown fields are ignored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardTask {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub state: TaskColumn,
+    pub priority: TaskPriority,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub blocks: Vec<String>,
+    #[serde(default)]
+    pub context_refs: Vec<TaskMemoryRef>,
+    #[serde(default)]
+    pub artifacts: Vec<TaskMemoryRef>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+impl BoardTask {
+    /// Entries of `metadata.history`, oldest first
+    pub fn history(&self) -> Vec<String> {
+        let Some(serde_json::Value::Array(entries)) = self.metadata.get("history") else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .map(|entry| match entry {
+                serde_json::Value::Object(fields) => {
+                    let at = ["at", "timestamp"]
+                        .iter()
+                        .find_map(|key| fields.get(*key).and_then(|v| v.as_str()))
+                        .map(|at| at.chars().take(16).collect::<String>());
+                    let event = fields
+                        .get("event")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("update");
+                    let rest: Vec<String> = fields
+                        .iter()
+                        .filter(|(key, _)| !matches!(key.as_str(), "at" | "timestamp" | "event"))
+                        .map(|(key, value)| match value {
+                            serde_json::Value::String(value) => format!("{key}={value}"),
+                            value => format!("{key}={value}"),
+                        })
+                        .collect();
+                    let mut line = match at {
+                        Some(at) => format!("{at} {event}"),
+                        None => event.to_string(),
+                    };
+                    if !rest.is_empty() {
+                        line.push_str(&format!(" ({})", rest.join(", ")));
+                    }
+                    line
+                }
+                other => other.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Change requested from the board; the owner applies it to the todo service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BoardAction {
+    Move {
+        task_id: String,
+        state: TaskColumn,
+    },
+    Prioritize {
+        task_id: String,
+        priority: TaskPriority,
+    },
+    Edit {
+        task_id: String,
+        title: String,
+    },
+    /// `task_id` starts depending on `depends_on`
+    Link {
+        task_id: String,
+        depends_on: String,
+    },
+}
+
+enum BoardMode {
+    Browse,
+    Editing { task_id: String, buffer: String },
+    Linking { task_id: String },
+}
+
+pub struct TaskBoard {
+    tasks: Vec<BoardTask>,
+    selected_column: usize,
+    selected_rows: [usize; 7],
+    show_details: bool,
+    mode: BoardMode,
+}
+
+impl Default for TaskBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskBoard {
+    pub fn new() -> Self {
+        TaskBoard {
+            tasks: Vec::new(),
+            selected_column: 0,
+            selected_rows: [0; 7],
+            show_details: true,
+            mode: BoardMode::Browse,
+        }
+    }
+
+    /// Replace all tasks, keeping the selection on the same task if possible
+    pub fn set_tasks(&mut self, tasks: Vec<BoardTask>) {
+        let selected = self.get_selected_task().map(|t| t.id.clone());
+        self.tasks = tasks;
+        if let Some(id) = selected {
+            self.select_task(&id);
+        }
+        self.clamp_selection();
+    }
+
+    /// Insert or replace a single task; the selection follows a moved task
+    pub fn upsert_task(&mut self, task: BoardTask) {
+        let selected = self.get_selected_task().map(|t| t.id.clone());
+        match self.tasks.iter_mut().find(|t| t.id == task.id) {
+            Some(existing) => *existing = task,
+            None => self.tasks.push(task),
+        }
+        if let Some(id) = selected {
+            self.select_task(&id);
+        }
+        self.clamp_selection();
+    }
+
+    pub fn remove_task(&mut self, task_id: &str) {
+        self.tasks.retain(|t| t.id != task_id);
+        self.clamp_selection();
+    }
+
+    /// Accepts a JSON array of tasks (full reload) or a single task (upsert)
+    pub fn update(&mut self, message: &str) {
+        if let Ok(tasks) = serde_json::from_str::<Vec<BoardTask>>(message) {
+            self.set_tasks(tasks);
+        } else if let Ok(task) = serde_json::from_str::<BoardTask>(message) {
+            self.upsert_task(task);
+        }
+    }
+
+    pub fn has_tasks(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+
+    pub fn get_task(&self, task_id: &str) -> Option<&BoardTask> {
+        self.tasks.iter().find(|t| t.id == task_id)
+    }
+
+    pub fn get_selected_task(&self) -> Option<&BoardTask> {
+        let column = TaskColumn::ALL[self.selected_column];
+        self.column_tasks(column)
+            .get(self.selected_rows[self.selected_column])
+            .copied()
+    }
+
+    /// True while editing a title or choosing a link target; the board then
+    /// needs every key, including the global ones
+    pub fn is_capturing_input(&self) -> bool {
+        !matches!(self.mode, BoardMode::Browse)
+    }
+
+    fn column_tasks(&self, column: TaskColumn) -> Vec<&BoardTask> {
+        let mut tasks: Vec<&BoardTask> = self.tasks.iter().filter(|t| t.state == column).collect();
+        tasks.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.title.cmp(&b.title)));
+        tasks
+    }
+
+    fn select_task(&mut self, task_id: &str) {
+        let Some(task) = self.get_task(task_id) else {
+            return;
+        };
+        let column = task.state.index();
+        if let Some(row) = self
+            .column_tasks(task.state)
+            .iter()
+            .position(|t| t.id == task_id)
+        {
+            self.selected_column = column;
+            self.selected_rows[column] = row;
+        }
+    }
+
+    fn clamp_selection(&mut self) {
+        for (index, column) in TaskColumn::ALL.iter().enumerate() {
+            let len = self.column_tasks(*column).len();
+            self.selected_rows[index] = self.selected_rows[index].min(len.saturating_sub(1));
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let area = if self.show_details {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .split(area);
+            self.render_details(f, chunks[1]);
+            chunks[0]
+        } else {
+            area
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+        self.render_columns(f, chunks[0]);
+        self.render_hint(f, chunks[1]);
+    }
+
+    fn render_columns(&self, f: &mut Frame, area: Rect) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, TaskColumn::ALL.len() as u32); 7])
+            .split(area);
+
+        let linking_from = match &self.mode {
+            BoardMode::Linking { task_id } => Some(task_id.as_str()),
+            _ => None,
+        };
+
+        for (index, column) in TaskColumn::ALL.iter().enumerate() {
+            let tasks = self.column_tasks(*column);
+            let is_selected_column = index == self.selected_column;
+            let visible = columns[index].height.saturating_sub(2) as usize;
+            let skip = (self.selected_rows[index] + 1).saturating_sub(visible);
+
+            let items: Vec<ListItem> = tasks
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .map(|(row, task)| {
+                    let marker = if linking_from == Some(task.id.as_str()) {
+                        "🔗"
+                    } else {
+                        task.priority.icon()
+                    };
+                    let style = if is_selected_column && row == self.selected_rows[index] {
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(column.color())
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    ListItem::new(format!("{marker} {}", task.title)).style(style)
+                })
+                .collect();
+
+            let border = if is_selected_column {
+                Style::default().fg(column.color())
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            let list = List::new(items).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border)
+                    .title(format!("{} ({})", column.title(), tasks.len())),
+            );
+            f.render_widget(list, columns[index]);
+        }
+    }
+
+    fn render_hint(&self, f: &mut Frame, area: Rect) {
+        let hint = match &self.mode {
+            BoardMode::Browse => Line::from(Span::styled(
+                "←→↑↓ select · </> move · +/- priority · e edit · l link · Enter details",
+                Style::default().fg(Color::DarkGray),
+            )),
+            BoardMode::Editing { buffer, .. } => Line::from(vec![
+                Span::styled("Title: ", Style::default().fg(Color::Cyan)),
+                Span::raw(buffer.as_str()),
+                Span::styled(
+                    "▏ Enter save · Esc cancel",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            BoardMode::Linking { task_id } => {
+                let title = self.get_task(task_id).map_or("?", |t| t.title.as_str());
+                Line::from(Span::styled(
+                    format!("Select the task '{title}' depends on · Enter link · Esc cancel"),
+                    Style::default().fg(Color::Magenta),
+                ))
+            }
+        };
+        f.render_widget(Paragraph::new(hint), area);
+    }
+
+    fn render_details(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title("Task Details");
+        let Some(task) = self.get_selected_task() else {
+            let empty = Paragraph::new("No task selected.")
+                .block(block)
+                .style(Style::default().fg(Color::Gray));
+            f.render_widget(empty, area);
+            return;
+        };
+
+        let heading = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+        let mut lines = vec![
+            Line::from(Span::styled(
+                task.title.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Line::from(vec![
+                Span::styled(task.state.title(), Style::default().fg(task.state.color())),
+                Span::raw(format!(" · {} {:?}", task.priority.icon(), task.priority)),
+            ]),
+            Line::from(Span::styled(
+                format!("ID: {}", task.id),
+                Style::default().fg(Color::DarkGray),
+            )),
+        ];
+        if let Some(due) = &task.due_date {
+            lines.push(Line::from(format!(
+                "Due: {}",
+                due.chars().take(10).collect::<String>()
+            )));
+        }
+        if !task.tags.is_empty() {
+            lines.push(Line::from(format!("Tags: {}", task.tags.join(", "))));
+        }
+        if !task.description.is_empty() {
+            lines.push(Line::from(""));
+            lines.extend(task.description.lines().map(|l| Line::from(l.to_string())));
+        }
+
+        let task_label = |id: &String| match self.get_task(id) {
+            Some(other) => format!("  {} {}", other.state.title(), other.title),
+            None => format!("  {id}"),
+        };
+        let mut section = |title: &str, entries: Vec<String>| {
+            if entries.is_empty() {
+                return;
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(title.to_string(), heading)));
+            lines.extend(entries.into_iter().map(Line::from));
+        };
+        section(
+            "Depends on",
+            task.depends_on.iter().map(task_label).collect(),
+        );
+        section("Blocks", task.blocks.iter().map(task_label).collect());
+        section(
+            "Context",
+            task.context_refs
+                .iter()
+                .map(|r| format!("  {}", r.label()))
+                .collect(),
+        );
+        section(
+            "Artifacts",
+            task.artifacts
+                .iter()
+                .map(|r| format!("  {}", r.label()))
+                .collect(),
+        );
+        section(
+            "History",
+            task.history()
+                .into_iter()
+                .map(|entry| format!("  {entry}"))
+                .collect(),
+        );
+
+        let details = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false });
+        f.render_widget(details, area);
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Option<BoardAction> {
+        match std::mem::replace(&mut self.mode, BoardMode::Browse) {
+            BoardMode::Editing {
+                task_id,
+                mut buffer,
+            } => {
+                match key.code {
+                    KeyCode::Enter => {
+                        let title = buffer.trim().to_string();
+                        let changed = self.get_task(&task_id).is_some_and(|t| t.title != title);
+                        if !title.is_empty() && changed {
+                            return Some(BoardAction::Edit { task_id, title });
+                        }
+                        return None;
+                    }
+                    KeyCode::Esc => return None,
+                    KeyCode::Backspace => {
+                        buffer.pop();
+                    }
+                    KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        buffer.push(c);
+                    }
+                    _ => {}
+                }
+                self.mode = BoardMode::Editing { task_id, buffer };
+                None
+            }
+            BoardMode::Linking { task_id } => match key.code {
+                KeyCode::Enter => {
+                    let target = self.get_selected_task()?.id.clone();
+                    (target != task_id).then_some(BoardAction::Link {
+                        task_id,
+                        depends_on: target,
+                    })
+                }
+                KeyCode::Esc => None,
+                _ => {
+                    self.navigate(key.code);
+                    self.mode = BoardMode::Linking { task_id };
+                    None
+                }
+            },
+            BoardMode::Browse => self.handle_browse_key(key),
+        }
+    }
+
+    fn handle_browse_key(&mut self, key: KeyEvent) -> Option<BoardAction> {
+        if self.navigate(key.code) {
+            return None;
+        }
+        let task = self.get_selected_task()?;
+        let task_id = task.id.clone();
+        match key.code {
+            KeyCode::Char('<') | KeyCode::Char(',') => {
+                let column = task.state.index().checked_sub(1)?;
+                Some(BoardAction::Move {
+                    task_id,
+                    state: TaskColumn::ALL[column],
+                })
+            }
+            KeyCode::Char('>') | KeyCode::Char('.') => {
+                let column = TaskColumn::ALL.get(task.state.index() + 1)?;
+                Some(BoardAction::Move {
+                    task_id,
+                    state: *column,
+                })
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                let priority = task.priority.raised();
+                (priority != task.priority).then_some(BoardAction::Prioritize { task_id, priority })
+            }
+            KeyCode::Char('-') => {
+                let priority = task.priority.lowered();
+                (priority != task.priority).then_some(BoardAction::Prioritize { task_id, priority })
+            }
+            KeyCode::Char('e') => {
+                self.mode = BoardMode::Editing {
+                    buffer: task.title.clone(),
+                    task_id,
+                };
+                None
+            }
+            KeyCode::Char('l') => {
+                self.mode = BoardMode::Linking { task_id };
+                None
+            }
+            KeyCode::Enter => {
+                self.show_details = !self.show_details;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Arrow-key selection; returns whether the key was consumed
+    fn navigate(&mut self, code: KeyCode) -> bool {
+        let column = self.selected_column;
+        let len = self.column_tasks(TaskColumn::ALL[column]).len();
+        match code {
+            KeyCode::Left => {
+                self.selected_column = column.saturating_sub(1);
+            }
+            KeyCode::Right => {
+                self.selected_column = (column + 1).min(TaskColumn::ALL.len() - 1);
+            }
+            KeyCode::Up => {
+                self.selected_rows[column] = self.selected_rows[column].saturating_sub(1);
+            }
+            KeyCode::Down => {
+                self.selected_rows[column] =
+                    (self.selected_rows[column] + 1).min(len.saturating_sub(1));
+            }
+            KeyCode::Home => {
+                self.selected_rows[column] = 0;
+            }
+            KeyCode::End => {
+                self.selected_rows[column] = len.saturating_sub(1);
+            }
+            _ => return false,
+        }
+        true
+    }
+}
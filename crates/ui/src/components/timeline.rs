@@ -29,6 +29,9 @@ pub struct TaskEvent {
     pub duration: Option<u64>, // seconds
     pub error_message: Option<String>,
     pub metadata: std::collections::HashMap<String, String>,
+    /// Concurrency lane the task ran in (the executor's `lane` step metadata)
+    #[serde(default)]
+    pub lane: Option<u16>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +44,24 @@ pub struct TimelineData {
     pub estimated_total_duration: Option<u64>,
 }
 
+impl TimelineData {
+    /// Tasks grouped by lane in start order; tasks without a lane share the
+    /// first one
+    pub fn lanes(&self) -> Vec<Vec<&TaskEvent>> {
+        let count = self
+            .tasks
+            .iter()
+            .filter_map(|task| task.lane)
+            .max()
+            .map_or(1, |lane| lane as usize + 1);
+        let mut lanes = vec![Vec::new(); count];
+        for task in &self.tasks {
+            lanes[task.lane.unwrap_or(0) as usize].push(task);
+        }
+        lanes
+    }
+}
+
 pub struct Timeline {
     timeline_data: Option<TimelineData>,
     selected_task_index: usize,
@@ -51,6 +72,7 @@ pub struct Timeline {
     elapsed_time: Duration,
     show_completed: bool,
     show_failed: bool,
+    show_lanes: bool,
 }
 
 impl Default for Timeline {
@@ -71,6 +93,7 @@ impl Timeline {
             elapsed_time: Duration::new(0, 0),
             show_completed: true,
             show_failed: true,
+            show_lanes: false,
         }
     }
 
@@ -98,8 +121,12 @@ impl Timeline {
                 // Render overall progress
                 self.render_overall_progress(f, chunks[0], &timeline);
 
-                // Render task list
-                self.render_task_list(f, chunks[1], &timeline);
+                // Render task list or concurrent lanes
+                if self.show_lanes {
+                    self.render_lanes(f, chunks[1], &timeline);
+                } else {
+                    self.render_task_list(f, chunks[1], &timeline);
+                }
 
                 // Render details/stats
                 if self.show_details && self.selected_task_index < timeline.tasks.len() {
@@ -259,6 +286,44 @@ impl Timeline {
         }
     }
 
+    fn render_lanes(&self, f: &mut Frame, area: Rect, timeline: &TimelineData) {
+        let lanes = timeline.lanes();
+        let items: Vec<ListItem> = lanes
+            .iter()
+            .enumerate()
+            .map(|(index, tasks)| {
+                let mut spans = vec![Span::styled(
+                    format!("Lane {} │ ", index + 1),
+                    Style::default().fg(Color::Cyan),
+                )];
+                for task in tasks {
+                    let icon = match task.status {
+                        TaskStatus::Pending => "⏳",
+                        TaskStatus::Running => "🔄",
+                        TaskStatus::Completed => "✅",
+                        TaskStatus::Failed => "❌",
+                        TaskStatus::Cancelled => "⛔",
+                    };
+                    spans.push(Span::styled(
+                        format!("{} {} ", icon, task.name),
+                        self.get_status_style(&task.status),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let running = timeline
+            .tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Running)
+            .count();
+        let title = format!("Lanes ({} lanes, {} running)", lanes.len(), running);
+        let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+
+        f.render_widget(list, area);
+    }
+
     fn render_task_details(&self, f: &mut Frame, area: Rect, task: &TaskEvent) {
         let mut details = vec![
             Line::from(vec![Span::styled(
@@ -308,6 +373,13 @@ impl Timeline {
             ]));
         }
 
+        if let Some(lane) = task.lane {
+            details.push(Line::from(vec![
+                Span::styled("Lane: ", Style::default().fg(Color::Cyan)),
+                Span::raw(format!("{}", lane + 1)),
+            ]));
+        }
+
         if let Some(error) = &task.error_message {
             details.push(Line::from(vec![Span::raw("")]));
             details.push(Line::from(vec![
@@ -410,8 +482,7 @@ impl Timeline {
     }
 
     fn render_controls(&self, f: &mut Frame, area: Rect) {
-        let controls =
-            "↑↓: Navigate, Enter: Toggle details, A: Auto-scroll, C/F: Toggle completed/failed";
+        let controls = "↑↓: Navigate, Enter: Toggle details, A: Auto-scroll, C/F: Toggle completed/failed, L: Lanes";
         let controls_widget = Paragraph::new(controls)
             .block(Block::default().borders(Borders::ALL).title("Controls"))
             .style(Style::default().fg(Color::DarkGray))
@@ -463,6 +534,10 @@ impl Timeline {
                     self.show_failed = !self.show_failed;
                     true
                 }
+                KeyCode::Char('l') | KeyCode::Char('L') => {
+                    self.show_lanes = !self.show_lanes;
+                    true
+                }
                 KeyCode::Home => {
                     self.selected_task_index = 0;
                     self.auto_scroll = false;
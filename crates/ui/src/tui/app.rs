@@ -1,10 +1,12 @@
 use super::{
+    chat::MessageRole,
     events::{should_quit, EventHandler, TUIEvent},
-    state::{AppMode, AppState, FocusedComponent},
+    state::{AppMode, AppState, AppView, FocusedComponent},
 };
-use crate::components::action_buttons::ButtonAction;
+use crate::components::{action_buttons::ButtonAction, task_board::BoardAction};
+use common::approval::PendingApproval;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, KeyCode},
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -13,11 +15,12 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
 use std::io::{self, Stdout};
 use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 type Backend = CrosstermBackend<Stdout>;
 
@@ -25,6 +28,10 @@ pub struct TUIApp {
     terminal: Terminal<Backend>,
     event_handler: EventHandler,
     state: AppState,
+    task_actions: Option<UnboundedSender<BoardAction>>,
+    chat_sender: Option<UnboundedSender<String>>,
+    review_actions: Option<UnboundedSender<ButtonAction>>,
+    approvals: Option<UnboundedReceiver<PendingApproval>>,
 }
 
 impl TUIApp {
@@ -42,9 +49,51 @@ impl TUIApp {
             terminal,
             event_handler,
             state,
+            task_actions: None,
+            chat_sender: None,
+            review_actions: None,
+            approvals: None,
         })
     }
 
+    /// Board actions (move, prioritize, edit, link) are sent here to be
+    /// applied to the todo service; results come back as `TaskUpdated`
+    pub fn with_task_actions(mut self, sender: UnboundedSender<BoardAction>) -> Self {
+        self.task_actions = Some(sender);
+        self
+    }
+
+    /// Chat pane messages are sent here; replies come back as `ChatResponse`
+    pub fn with_chat(mut self, sender: UnboundedSender<String>) -> Self {
+        self.chat_sender = Some(sender);
+        self
+    }
+
+    /// Confirmations from a `ChannelPrompter`: each request opens the
+    /// approval dialog and the answer goes straight back to the broker
+    pub fn with_approvals(mut self, requests: UnboundedReceiver<PendingApproval>) -> Self {
+        self.approvals = Some(requests);
+        self
+    }
+
+    /// Review the diff of an isolated run: the buttons offer merge,
+    /// cherry-pick, discard or keeping the branch; the chosen action is sent
+    /// here and the TUI exits
+    pub fn with_review(
+        mut self,
+        diff_json: String,
+        summary: String,
+        sender: UnboundedSender<ButtonAction>,
+    ) -> Self {
+        self.load_diff(diff_json);
+        self.state.action_buttons.use_review_buttons();
+        self.state.focused_component = FocusedComponent::ActionButtons;
+        self.state
+            .set_status(format!("{summary} | Tab: scroll the diff"));
+        self.review_actions = Some(sender);
+        self
+    }
+
     pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         while !self.state.should_quit {
             // Draw UI
@@ -75,6 +124,24 @@ impl TUIApp {
         // Render header
         Self::render_header(f, chunks[0], state);
 
+        if state.view == AppView::Tasks {
+            let content_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(65), // Task board
+                        Constraint::Percentage(35), // Chat
+                    ]
+                    .as_ref(),
+                )
+                .split(chunks[1]);
+
+            Self::render_task_board(f, content_chunks[0], state);
+            Self::render_chat(f, content_chunks[1], state);
+            Self::render_status_bar(f, chunks[2], state);
+            return;
+        }
+
         // Create main content layout
         let content_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -113,7 +180,7 @@ impl TUIApp {
             " | Orchestration: Idle".to_string()
         };
 
-        let title = Line::from(vec![
+        let mut title = vec![
             Span::styled(
                 "MAGRAY CLI - TUI Interface",
                 Style::default()
@@ -130,9 +197,15 @@ impl TUIApp {
                     Color::Gray
                 }),
             ),
-        ]);
+        ];
+        if let Some(spend) = &state.spend_summary {
+            title.push(Span::styled(
+                format!(" | 💰 {spend}"),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
 
-        let header = Paragraph::new(title)
+        let header = Paragraph::new(Line::from(title))
             .block(Block::default().borders(Borders::ALL))
             .style(Style::default().fg(Color::White));
 
@@ -201,6 +274,92 @@ impl TUIApp {
         state.action_buttons.render(f, inner_area);
     }
 
+    fn render_task_board(f: &mut Frame, area: Rect, state: &mut AppState) {
+        let is_focused = state.is_focused(FocusedComponent::TaskBoard);
+
+        let block = if is_focused {
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title("Tasks (Focused)")
+        } else {
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray))
+                .title("Tasks")
+        };
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+        state.task_board.render(f, inner_area);
+    }
+
+    fn render_chat(f: &mut Frame, area: Rect, state: &AppState) {
+        let is_focused = state.is_focused(FocusedComponent::Chat);
+
+        let block = if is_focused {
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .title("Chat (Focused)")
+        } else {
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Gray))
+                .title("Chat")
+        };
+
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+            .split(inner_area);
+
+        let lines: Vec<ListItem> = state
+            .chat
+            .messages
+            .iter()
+            .flat_map(|msg| {
+                let (label, style) = match msg.role {
+                    MessageRole::User => ("You", Style::default().fg(Color::Cyan)),
+                    MessageRole::Assistant => ("Assistant", Style::default().fg(Color::Green)),
+                    MessageRole::System => ("System", Style::default().fg(Color::Yellow)),
+                };
+                let mut lines = vec![ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("[{}] ", msg.timestamp),
+                        Style::default().fg(Color::Gray),
+                    ),
+                    Span::styled(format!("{label}: "), style.add_modifier(Modifier::BOLD)),
+                ]))];
+                lines.extend(
+                    msg.content
+                        .lines()
+                        .map(|line| ListItem::new(Span::styled(format!("  {line}"), style))),
+                );
+                lines
+            })
+            .collect();
+        // Keep the newest messages visible
+        let skip = lines.len().saturating_sub(chunks[0].height as usize);
+        f.render_widget(
+            List::new(lines.into_iter().skip(skip).collect::<Vec<_>>()),
+            chunks[0],
+        );
+
+        let title = if state.chat.is_processing {
+            "Waiting for reply..."
+        } else {
+            "Message (Enter to send)"
+        };
+        let input = Paragraph::new(state.chat.input_buffer.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false });
+        f.render_widget(input, chunks[1]);
+    }
+
     fn render_status_bar(f: &mut Frame, area: Rect, state: &AppState) {
         let status_text = if let Some(ref error) = state.error_message {
             Line::from(vec![
@@ -216,7 +375,7 @@ impl TUIApp {
                 Span::raw(&state.status_message),
                 Span::raw(" | "),
                 Span::styled(
-                    "Press 'Tab' to switch focus, 'h' for help, 'q' to quit",
+                    "Press 'Tab' to switch focus, 'F2' for tasks, 'h' for help, 'q' to quit",
                     Style::default().fg(Color::DarkGray),
                 ),
             ])
@@ -229,9 +388,59 @@ impl TUIApp {
         f.render_widget(status, area);
     }
 
+    /// Move broker requests into the dialog queue (checked on every event,
+    /// ticks keep the latency at the tick rate)
+    fn poll_approvals(&mut self) {
+        let Some(requests) = self.approvals.as_mut() else {
+            return;
+        };
+        while let Ok(pending) = requests.try_recv() {
+            self.state
+                .set_status(format!("Approval required: {}", pending.request.subject));
+            self.state.action_buttons.request_approval(pending);
+        }
+    }
+
     fn handle_events(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        match self.event_handler.next()? {
+        let event = self.event_handler.next()?;
+        self.poll_approvals();
+        match event {
             TUIEvent::Key(key) => {
+                // Approval dialog captures keys until answered
+                if self.state.action_buttons.has_pending_approval() {
+                    if let Some((subject, decision)) =
+                        self.state.action_buttons.answer_approval(key)
+                    {
+                        self.state.set_status(format!(
+                            "Approval: {} {}",
+                            decision.as_str(),
+                            subject
+                        ));
+                    }
+                    return Ok(());
+                }
+
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    self.state.quit();
+                    return Ok(());
+                }
+
+                // Title editing and link selection capture every key
+                if self.state.is_focused(FocusedComponent::TaskBoard)
+                    && self.state.task_board.is_capturing_input()
+                {
+                    self.handle_board_key(key);
+                    return Ok(());
+                }
+
+                // Chat input takes printable keys before the global shortcuts
+                if self.state.is_focused(FocusedComponent::Chat)
+                    && !matches!(key.code, KeyCode::Tab | KeyCode::F(2))
+                {
+                    self.handle_chat_key(key);
+                    return Ok(());
+                }
+
                 if should_quit(&key) {
                     self.state.quit();
                     return Ok(());
@@ -243,6 +452,10 @@ impl TUIApp {
                         self.state.cycle_focus();
                         return Ok(());
                     }
+                    KeyCode::F(2) => {
+                        self.state.toggle_view();
+                        return Ok(());
+                    }
                     KeyCode::Char('h') => {
                         self.show_help();
                         return Ok(());
@@ -267,6 +480,8 @@ impl TUIApp {
                             self.handle_button_action(action);
                         }
                     }
+                    FocusedComponent::TaskBoard => self.handle_board_key(key),
+                    FocusedComponent::Chat => self.handle_chat_key(key),
                 }
             }
             TUIEvent::Tick => {
@@ -292,6 +507,18 @@ impl TUIApp {
                 self.state
                     .set_status(format!("Execution complete: {result}"));
             }
+            TUIEvent::SpendUpdated(summary) => {
+                self.state.spend_summary = Some(summary);
+            }
+            TUIEvent::TasksLoaded(tasks_json) | TUIEvent::TaskUpdated(tasks_json) => {
+                self.state.task_board.update(&tasks_json);
+            }
+            TUIEvent::ChatResponse(response) => {
+                self.state
+                    .chat
+                    .add_message(MessageRole::Assistant, response);
+                self.state.chat.is_processing = false;
+            }
             TUIEvent::Error(error) => {
                 self.state.set_error(error);
             }
@@ -300,7 +527,63 @@ impl TUIApp {
         Ok(())
     }
 
+    fn handle_board_key(&mut self, key: KeyEvent) {
+        let Some(action) = self.state.task_board.handle_key_event(key) else {
+            return;
+        };
+        match &self.task_actions {
+            Some(sender) if sender.send(action).is_ok() => {}
+            _ => self
+                .state
+                .set_error("Task board is read-only: no todo service connected".to_string()),
+        }
+    }
+
+    fn handle_chat_key(&mut self, key: KeyEvent) {
+        let chat = &mut self.state.chat;
+        match key.code {
+            KeyCode::Esc => {
+                self.state.focused_component = FocusedComponent::TaskBoard;
+            }
+            KeyCode::Enter if !chat.is_processing => {
+                let message = chat.input_buffer.trim().to_string();
+                if message.is_empty() {
+                    return;
+                }
+                chat.clear_input();
+                chat.add_message(MessageRole::User, message.clone());
+                match &self.chat_sender {
+                    Some(sender) if sender.send(message).is_ok() => chat.is_processing = true,
+                    _ => chat.add_message(
+                        MessageRole::System,
+                        "Chat is not connected in this session".to_string(),
+                    ),
+                }
+            }
+            KeyCode::Backspace => {
+                chat.input_buffer.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                chat.input_buffer.push(c);
+            }
+            _ => {}
+        }
+    }
+
     fn handle_button_action(&mut self, action: ButtonAction) {
+        if let Some(sender) = &self.review_actions {
+            if matches!(
+                action,
+                ButtonAction::Merge
+                    | ButtonAction::CherryPick
+                    | ButtonAction::Discard
+                    | ButtonAction::Cancel
+            ) {
+                let _ = sender.send(action);
+                self.state.quit();
+                return;
+            }
+        }
         if let Some(command) = self.state.handle_button_action(action.clone()) {
             // Here we would send the command to the orchestrator
             // For now, we'll simulate the command execution
@@ -337,7 +620,8 @@ impl TUIApp {
 
     fn show_help(&mut self) {
         self.state.set_status(
-            "Help: Tab=Focus, ↑↓=Navigate, Enter=Select, Space=Expand, q=Quit".to_string(),
+            "Help: Tab=Focus, F2=Tasks, ↑↓=Navigate, Enter=Select, Space=Expand, q=Quit"
+                .to_string(),
         );
     }
 
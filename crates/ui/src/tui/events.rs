@@ -12,6 +12,14 @@ pub enum TUIEvent {
     PlanGenerated(String),
     ExecutionProgress(String),
     ExecutionComplete(String),
+    /// One-line spend summary of the running workflow or session
+    SpendUpdated(String),
+    /// JSON array of serialized todo tasks (full board reload)
+    TasksLoaded(String),
+    /// JSON of a single created or changed todo task
+    TaskUpdated(String),
+    /// Assistant reply to a message sent from the chat pane
+    ChatResponse(String),
     Error(String),
 }
 
@@ -80,6 +88,27 @@ impl EventHandler {
         let _ = self.sender.send(TUIEvent::ExecutionComplete(result));
     }
 
+    pub fn send_spend_update(&self, summary: String) {
+        let _ = self.sender.send(TUIEvent::SpendUpdated(summary));
+    }
+
+    pub fn send_tasks_loaded(&self, tasks_json: String) {
+        let _ = self.sender.send(TUIEvent::TasksLoaded(tasks_json));
+    }
+
+    pub fn send_task_updated(&self, task_json: String) {
+        let _ = self.sender.send(TUIEvent::TaskUpdated(task_json));
+    }
+
+    pub fn send_chat_response(&self, response: String) {
+        let _ = self.sender.send(TUIEvent::ChatResponse(response));
+    }
+
+    /// Sender for producers running on other threads or tasks
+    pub fn sender(&self) -> mpsc::Sender<TUIEvent> {
+        self.sender.clone()
+    }
+
     pub fn send_error(&self, error: String) {
         let _ = self.sender.send(TUIEvent::Error(error));
     }
@@ -2,7 +2,9 @@ use crate::components::{
     action_buttons::{ActionButtons, ButtonAction},
     diff_viewer::DiffViewer,
     plan_viewer::PlanViewer,
+    task_board::TaskBoard,
 };
+use crate::tui::chat::{ChatState, MessageRole};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Copy)]
@@ -13,24 +15,38 @@ pub enum AppMode {
     Idle,
 }
 
+/// Main content: orchestration panes or the task board with chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppView {
+    Orchestration,
+    Tasks,
+}
+
 #[derive(Debug, Clone)]
 pub enum FocusedComponent {
     PlanViewer,
     DiffViewer,
     ActionButtons,
+    TaskBoard,
+    Chat,
 }
 
 pub struct AppState {
     pub mode: AppMode,
+    pub view: AppView,
     pub focused_component: FocusedComponent,
     pub plan_viewer: PlanViewer,
     pub diff_viewer: DiffViewer,
     pub action_buttons: ActionButtons,
+    pub task_board: TaskBoard,
+    pub chat: ChatState,
     pub status_message: String,
     pub error_message: Option<String>,
     pub should_quit: bool,
     pub orchestration_active: bool,
     pub current_operation: Option<String>,
+    /// Spend summary shown in the header (tokens · cost · tools · time)
+    pub spend_summary: Option<String>,
 }
 
 impl Default for AppState {
@@ -41,22 +57,36 @@ impl Default for AppState {
 
 impl AppState {
     pub fn new() -> Self {
+        let mut chat = ChatState::new();
+        chat.messages.clear();
+        chat.add_message(
+            MessageRole::System,
+            "Chat is available next to the task board. Enter sends, Esc returns to the board."
+                .to_string(),
+        );
+
         AppState {
             mode: AppMode::Idle,
+            view: AppView::Orchestration,
             focused_component: FocusedComponent::PlanViewer,
             plan_viewer: PlanViewer::new(),
             diff_viewer: DiffViewer::new(),
             action_buttons: ActionButtons::new(),
+            task_board: TaskBoard::new(),
+            chat,
             status_message: "Welcome to MAGRAY TUI. Press 'h' for help.".to_string(),
             error_message: None,
             should_quit: false,
             orchestration_active: false,
             current_operation: None,
+            spend_summary: None,
         }
     }
 
     pub fn set_mode(&mut self, mode: AppMode) {
         self.mode = mode;
+        // The task view keeps its focus; plan and diff panes update in the background
+        let focused = self.focused_component.clone();
         match &mode {
             AppMode::PlanViewing => {
                 self.status_message =
@@ -77,6 +107,9 @@ impl AppState {
                 self.focused_component = FocusedComponent::PlanViewer;
             }
         }
+        if self.view == AppView::Tasks {
+            self.focused_component = focused;
+        }
     }
 
     pub fn set_error(&mut self, error: String) {
@@ -98,15 +131,34 @@ impl AppState {
             FocusedComponent::PlanViewer => FocusedComponent::DiffViewer,
             FocusedComponent::DiffViewer => FocusedComponent::ActionButtons,
             FocusedComponent::ActionButtons => FocusedComponent::PlanViewer,
+            FocusedComponent::TaskBoard => FocusedComponent::Chat,
+            FocusedComponent::Chat => FocusedComponent::TaskBoard,
         };
 
         self.status_message = match self.focused_component {
             FocusedComponent::PlanViewer => "Focused: Plan Viewer".to_string(),
             FocusedComponent::DiffViewer => "Focused: Diff Viewer".to_string(),
             FocusedComponent::ActionButtons => "Focused: Action Buttons".to_string(),
+            FocusedComponent::TaskBoard => "Focused: Task Board".to_string(),
+            FocusedComponent::Chat => "Focused: Chat".to_string(),
         };
     }
 
+    pub fn toggle_view(&mut self) {
+        match self.view {
+            AppView::Orchestration => {
+                self.view = AppView::Tasks;
+                self.focused_component = FocusedComponent::TaskBoard;
+                self.set_status("Task board. Tab switches to chat, F2 returns.".to_string());
+            }
+            AppView::Tasks => {
+                self.view = AppView::Orchestration;
+                self.focused_component = FocusedComponent::PlanViewer;
+                self.set_status("Orchestration view.".to_string());
+            }
+        }
+    }
+
     pub fn start_orchestration(&mut self, operation: String) {
         self.orchestration_active = true;
         self.current_operation = Some(operation.clone());
@@ -160,6 +212,11 @@ impl AppState {
                     None
                 }
             }
+            // Handled by the host of an isolated run review
+            ButtonAction::Merge | ButtonAction::CherryPick | ButtonAction::Discard => {
+                self.set_error("No branch to review".to_string());
+                None
+            }
         }
     }
 